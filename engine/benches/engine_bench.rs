@@ -9,7 +9,9 @@ use realpolitik::resolve::Resolver;
 use std::sync::atomic::AtomicBool;
 
 use realpolitik::search::cartesian::search;
-use realpolitik::search::regret_matching_search;
+use realpolitik::search::{
+    greedy_lookahead_orders, regret_matching_search, regret_matching_search_with_options, RmOptions,
+};
 
 const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
 
@@ -89,6 +91,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: Location::new(Province::Gal),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -96,6 +99,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Bud),
                 dest: Location::new(Province::Ser),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -103,6 +107,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Tri),
                 dest: Location::new(Province::Alb),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -111,6 +116,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Lon),
                 dest: Location::new(Province::Nth),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -118,6 +124,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Edi),
                 dest: Location::new(Province::Nrg),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -125,6 +132,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Lvp),
                 dest: Location::new(Province::Yor),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -133,6 +141,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Bre),
                 dest: Location::new(Province::Mao),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -140,6 +149,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Par),
                 dest: Location::new(Province::Bur),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -147,6 +157,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Mar),
                 dest: Location::new(Province::Pie),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -155,6 +166,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Kie),
                 dest: Location::new(Province::Den),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -162,6 +174,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Ber),
                 dest: Location::new(Province::Kie),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -169,6 +182,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: Location::new(Province::Ruh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -177,6 +191,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Nap),
                 dest: Location::new(Province::Ion),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -184,6 +199,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Rom),
                 dest: Location::new(Province::Apu),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -191,6 +207,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Ven),
                 dest: Location::new(Province::Tri),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -199,6 +216,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet_coast(Province::Stp, Coast::South),
                 dest: Location::new(Province::Bot),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -206,6 +224,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Mos),
                 dest: Location::new(Province::Ukr),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -213,6 +232,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::War),
                 dest: Location::new(Province::Gal),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -220,6 +240,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Sev),
                 dest: Location::new(Province::Bla),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -228,6 +249,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Ank),
                 dest: Location::new(Province::Bla),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -235,6 +257,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Con),
                 dest: Location::new(Province::Bul),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -242,6 +265,7 @@ fn bench_resolve_with_moves(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Smy),
                 dest: Location::new(Province::Con),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -382,6 +406,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: Location::new(Province::Gal),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -389,6 +414,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Bud),
                 dest: Location::new(Province::Ser),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -396,6 +422,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Tri),
                 dest: Location::new(Province::Alb),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -403,6 +430,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Lon),
                 dest: Location::new(Province::Nth),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -410,6 +438,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Edi),
                 dest: Location::new(Province::Nrg),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -417,6 +446,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Lvp),
                 dest: Location::new(Province::Yor),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -424,6 +454,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Bre),
                 dest: Location::new(Province::Mao),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -431,6 +462,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Par),
                 dest: Location::new(Province::Bur),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -438,6 +470,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Mar),
                 dest: Location::new(Province::Pie),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -445,6 +478,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Kie),
                 dest: Location::new(Province::Den),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -452,6 +486,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Ber),
                 dest: Location::new(Province::Kie),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -459,6 +494,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: Location::new(Province::Ruh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -466,6 +502,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Nap),
                 dest: Location::new(Province::Ion),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -473,6 +510,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Rom),
                 dest: Location::new(Province::Apu),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -480,6 +518,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Ven),
                 dest: Location::new(Province::Tri),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -487,6 +526,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet_coast(Province::Stp, Coast::South),
                 dest: Location::new(Province::Bot),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -494,6 +534,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Mos),
                 dest: Location::new(Province::Ukr),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -501,6 +542,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::War),
                 dest: Location::new(Province::Gal),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -508,6 +550,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Sev),
                 dest: Location::new(Province::Bla),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -515,6 +558,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: fleet(Province::Ank),
                 dest: Location::new(Province::Bla),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -522,6 +566,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Con),
                 dest: Location::new(Province::Bul),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -529,6 +574,7 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
             Order::Move {
                 unit: army(Province::Smy),
                 dest: Location::new(Province::Con),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -546,6 +592,54 @@ fn bench_resolve_then_evaluate(c: &mut Criterion) {
     });
 }
 
+/// Mid-game position with several threatened SCs (DUI spec example 7.2).
+const MIDGAME_DFEN: &str = "1903fm/Aabud,Aarum,Afgre,Aavie,Efnth,Efnwy,Eayor,Eflon,Ffmao,Fabur,Famar,Ffpor,Gaden,Gahol,Gamun,Gfkie,Gfska,Iftys,Iaven,Iarom,Rfsev,Ramos,Rawar,Tfank,Tabul,Tacon,Tasmy/Abud,Agre,Arum,Atri,Avie,Eedi,Elon,Elvp,Enwy,Fbre,Fmar,Fpar,Fspa,Gber,Gden,Ghol,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rwar,Tank,Tbul,Tcon,Tsmy,Nbel,Npor,Nser,Nstp,Nswe,Ntun/-";
+
+fn bench_greedy_lookahead(c: &mut Criterion) {
+    let initial = parse_dfen(INITIAL_DFEN).unwrap();
+    let midgame = parse_dfen(MIDGAME_DFEN).unwrap();
+    let mut group = c.benchmark_group("greedy_lookahead");
+    for (name, state) in [("initial", &initial), ("midgame", &midgame)] {
+        group.bench_function(format!("{}_moves_only", name), |b| {
+            b.iter(|| greedy_lookahead_orders(black_box(state), false))
+        });
+        group.bench_function(format!("{}_with_supports", name), |b| {
+            b.iter(|| greedy_lookahead_orders(black_box(state), true))
+        });
+    }
+    group.finish();
+}
+
+/// RM+ search with support-aware lookahead. Compare against `austria_500ms`:
+/// the cost shows up as fewer RM+ iterations in the same time budget.
+fn bench_rm_search_lookahead_supports(c: &mut Criterion) {
+    let state = parse_dfen(INITIAL_DFEN).unwrap();
+    let options = RmOptions {
+        lookahead_supports: true,
+        ..RmOptions::default()
+    };
+    let mut group = c.benchmark_group("rm_search");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(15));
+    group.bench_function("austria_500ms_lookahead_supports", |b| {
+        b.iter(|| {
+            let mut out = Vec::new();
+            regret_matching_search_with_options(
+                black_box(Power::Austria),
+                black_box(&state),
+                Duration::from_millis(500),
+                &mut out,
+                None,
+                100,
+                None,
+                &AtomicBool::new(false),
+                &options,
+            )
+        })
+    });
+    group.finish();
+}
+
 fn bench_board_state_clone(c: &mut Criterion) {
     let state = parse_dfen(INITIAL_DFEN).unwrap();
     c.bench_function("board_state_clone", |b| {
@@ -566,5 +660,7 @@ criterion_group!(
     bench_rm_search_russia_500ms,
     bench_resolve_then_evaluate,
     bench_board_state_clone,
+    bench_greedy_lookahead,
+    bench_rm_search_lookahead_supports,
 );
 criterion_main!(benches);
@@ -0,0 +1,262 @@
+//! Per-province contention over a game.
+//!
+//! Some provinces are fought over turn after turn: both sides walk into
+//! Galicia every spring and bounce. [`ContentionTracker`] counts, per
+//! province, the moves made into it, how many of them failed, and the
+//! turns in which two or more powers moved into it at once. It learns from
+//! adjudications when the host has them, and otherwise from the orders it
+//! knows (the engine's own) and the position that followed: a move whose
+//! unit still stands where it was did not go through.
+//!
+//! The counts feed RM+ as a caution prior: once our moves into a province
+//! have bounced [`CAUTION_MIN_BOUNCES`] times, a candidate that tries again
+//! without also supporting the move pays a small penalty. They also appear
+//! in game summaries.
+
+use crate::board::order::Order;
+use crate::board::province::{Power, Province, MAX_PROVINCES};
+use crate::board::state::{BoardState, Phase};
+use crate::resolve::{OrderResult, ResolvedOrder};
+
+/// Failed moves into a province before repeating one unsupported is
+/// penalized.
+pub const CAUTION_MIN_BOUNCES: u32 = 2;
+
+/// Penalty per failed move into the target, for an unsupported move.
+const CAUTION_WEIGHT: f64 = 0.3;
+
+/// Failed moves beyond this do not raise the penalty further.
+const MAX_CAUTION_BOUNCES: u32 = 4;
+
+/// Contention counts for one province.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvinceContention {
+    pub province: Province,
+    /// Moves into the province.
+    pub attempts: u32,
+    /// Moves into the province that failed.
+    pub bounces: u32,
+    /// Turns in which two or more powers moved into the province.
+    pub contested: u32,
+}
+
+/// Contention counts for every province since the start of tracking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentionTracker {
+    attempts: [u32; MAX_PROVINCES],
+    bounces: [u32; MAX_PROVINCES],
+    contested: [u32; MAX_PROVINCES],
+}
+
+impl Default for ContentionTracker {
+    fn default() -> Self {
+        ContentionTracker {
+            attempts: [0; MAX_PROVINCES],
+            bounces: [0; MAX_PROVINCES],
+            contested: [0; MAX_PROVINCES],
+        }
+    }
+}
+
+impl ContentionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets all observations.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Observes a movement turn from the orders known for it and the
+    /// position that followed. A move failed if its unit still stands in
+    /// its province and no other unit of its power was ordered there.
+    /// Positions other than movement phases are ignored.
+    pub fn observe(&mut self, prev: &BoardState, orders: &[(Order, Power)], next: &BoardState) {
+        if prev.phase != Phase::Movement {
+            return;
+        }
+        for &(order, power) in orders {
+            let Order::Move { unit, dest, .. } = order else {
+                continue;
+            };
+            let from = unit.location.province;
+            let refilled = orders.iter().any(|&(o, p)| {
+                p == power && matches!(o, Order::Move { dest, .. } if dest.province == from)
+            });
+            let stayed = next.units[from.index()] == Some((power, unit.unit_type));
+            self.count_move(dest.province, stayed && !refilled);
+        }
+        self.count_contested(orders.iter().copied());
+    }
+
+    /// Observes an adjudicated movement turn.
+    pub fn observe_resolution(&mut self, resolved: &[ResolvedOrder]) {
+        for r in resolved {
+            if let Order::Move { dest, .. } = r.order {
+                self.count_move(dest.province, r.result != OrderResult::Succeeded);
+            }
+        }
+        self.count_contested(resolved.iter().map(|r| (r.order, r.power)));
+    }
+
+    fn count_move(&mut self, dest: Province, failed: bool) {
+        self.attempts[dest.index()] += 1;
+        if failed {
+            self.bounces[dest.index()] += 1;
+        }
+    }
+
+    /// Counts a contested turn for each province moved into by two or more
+    /// powers.
+    fn count_contested(&mut self, orders: impl Iterator<Item = (Order, Power)>) {
+        let mut movers: [Option<Power>; MAX_PROVINCES] = [None; MAX_PROVINCES];
+        let mut counted = [false; MAX_PROVINCES];
+        for (order, power) in orders {
+            let Order::Move { dest, .. } = order else {
+                continue;
+            };
+            let idx = dest.province.index();
+            match movers[idx] {
+                None => movers[idx] = Some(power),
+                Some(p) if p != power && !counted[idx] => {
+                    counted[idx] = true;
+                    self.contested[idx] += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The counts for `province`.
+    pub fn get(&self, province: Province) -> ProvinceContention {
+        let idx = province.index();
+        ProvinceContention {
+            province,
+            attempts: self.attempts[idx],
+            bounces: self.bounces[idx],
+            contested: self.contested[idx],
+        }
+    }
+
+    /// Every province moved into at least once, most failed moves first.
+    pub fn provinces(&self) -> Vec<ProvinceContention> {
+        let mut out: Vec<ProvinceContention> = (0..MAX_PROVINCES)
+            .map(|i| self.get(Province::from_index(i)))
+            .filter(|c| c.attempts > 0)
+            .collect();
+        out.sort_by(|a, b| {
+            b.bounces
+                .cmp(&a.bounces)
+                .then(b.contested.cmp(&a.contested))
+                .then(a.province.index().cmp(&b.province.index()))
+        });
+        out
+    }
+
+    /// Provinces with at least [`CAUTION_MIN_BOUNCES`] failed moves, with
+    /// their counts, for [`contention_penalty`].
+    pub fn cautions(&self) -> Vec<(Province, u32)> {
+        (0..MAX_PROVINCES)
+            .filter(|&i| self.bounces[i] >= CAUTION_MIN_BOUNCES)
+            .map(|i| (Province::from_index(i), self.bounces[i]))
+            .collect()
+    }
+}
+
+/// Penalty for a candidate that moves into a province on the caution
+/// list without also supporting that move.
+pub fn contention_penalty(orders: &[(Order, Power)], cautions: &[(Province, u32)]) -> f64 {
+    let mut penalty = 0.0;
+    for &(order, _) in orders {
+        let Order::Move { unit, dest, .. } = order else {
+            continue;
+        };
+        let Some(&(_, bounces)) = cautions.iter().find(|(p, _)| *p == dest.province) else {
+            continue;
+        };
+        let supported = orders.iter().any(|(o, _)| {
+            matches!(o, Order::SupportMove { supported, dest: d, .. }
+                if supported.location.province == unit.location.province
+                    && d.province == dest.province)
+        });
+        if !supported {
+            penalty += CAUTION_WEIGHT * bounces.min(MAX_CAUTION_BOUNCES) as f64;
+        }
+    }
+    penalty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::{parse_order, parse_orders};
+    use crate::resolve::{apply_resolution, Resolver};
+
+    const BORDER: &str = "1901sm/Aavie,Rawar/Avie,Rwar/-";
+
+    fn played(list: &[(&str, Power)]) -> Vec<(Order, Power)> {
+        list.iter()
+            .map(|&(o, p)| (parse_order(o).unwrap(), p))
+            .collect()
+    }
+
+    #[test]
+    fn bounce_is_inferred_from_the_next_position() {
+        let prev = parse_dfen(BORDER).unwrap();
+        let orders = played(&[
+            ("A vie - gal", Power::Austria),
+            ("A war - gal", Power::Russia),
+        ]);
+        let mut tracker = ContentionTracker::new();
+        tracker.observe(&prev, &orders, &prev);
+        tracker.observe(&prev, &orders[..1], &prev);
+        let gal = tracker.get(Province::Gal);
+        assert_eq!((gal.attempts, gal.bounces, gal.contested), (3, 3, 1));
+        assert_eq!(tracker.cautions(), vec![(Province::Gal, 3)]);
+
+        let moved = parse_dfen("1901fm/Aagal,Rawar/Avie,Rwar/-").unwrap();
+        tracker.observe(&prev, &orders[..1], &moved);
+        assert_eq!(tracker.get(Province::Gal).bounces, 3);
+        assert_eq!(tracker.get(Province::Gal).attempts, 4);
+    }
+
+    #[test]
+    fn resolutions_count_failed_moves() {
+        let mut state = parse_dfen(BORDER).unwrap();
+        let orders = played(&[
+            ("A vie - gal", Power::Austria),
+            ("A war - gal", Power::Russia),
+        ]);
+        let (resolved, dislodged) = Resolver::new(64).resolve(&orders, &state);
+        let mut tracker = ContentionTracker::new();
+        tracker.observe_resolution(&resolved);
+        let from_results = tracker.clone();
+
+        apply_resolution(&mut state, &resolved, &dislodged);
+        let mut inferred = ContentionTracker::new();
+        inferred.observe(&parse_dfen(BORDER).unwrap(), &orders, &state);
+        assert_eq!(from_results, inferred);
+        assert_eq!(tracker.provinces().len(), 1);
+    }
+
+    #[test]
+    fn unsupported_moves_into_cautioned_provinces_pay() {
+        let cautions = [(Province::Gal, 5)];
+        let bare: Vec<_> = parse_orders("A vie - gal ; A bud H")
+            .unwrap()
+            .into_iter()
+            .map(|o| (o, Power::Austria))
+            .collect();
+        let backed: Vec<_> = parse_orders("A vie - gal ; A bud S A vie - gal")
+            .unwrap()
+            .into_iter()
+            .map(|o| (o, Power::Austria))
+            .collect();
+        let penalty = contention_penalty(&bare, &cautions);
+        assert!((penalty - CAUTION_WEIGHT * MAX_CAUTION_BOUNCES as f64).abs() < 1e-9);
+        assert_eq!(contention_penalty(&backed, &cautions), 0.0);
+        assert_eq!(contention_penalty(&bare, &[]), 0.0);
+    }
+}
@@ -0,0 +1,17 @@
+//! Analysis of game histories.
+//!
+//! Works on self-play `GameRecord`s (or any record rebuilt from a game log)
+//! after the fact, for arena reports, replay tooling and training-data
+//! filters, and on the positions the engine sees during a game (relation
+//! inference feeding the trust model and RM+'s front avoidance).
+
+pub mod contention;
+pub mod relations;
+pub mod summary;
+
+pub use contention::{contention_penalty, ContentionTracker, ProvinceContention};
+pub use relations::{
+    alliances_json, inferred_moves, new_front_penalty, AllianceMatrix, PairRelation, Relation,
+    RelationTracker, ALLIANCE_WINDOW,
+};
+pub use summary::{summary, AllianceSpell, GameOutcome, GameSummary, StabEvent, YearScs};
@@ -0,0 +1,636 @@
+//! De facto alliances and hostilities inferred from play.
+//!
+//! Without press, the only evidence of who is working with whom is what the
+//! powers do. [`RelationTracker`] keeps a symmetric score per pair of powers
+//! and updates it from each turn:
+//!
+//! - a support for another power's unit raises the pair's score;
+//! - a move onto another power's unit or center lowers it, as does a
+//!   dislodgement or a center changing hands between the two;
+//! - a province both could enter but both left empty (a DMZ kept) raises
+//!   it slightly.
+//!
+//! Scores decay every movement turn, so old behaviour fades. Orders are
+//! optional: the engine, which only sees positions, still learns from
+//! captures, dislodgements and DMZs, while game records add the supports
+//! and attacks behind them.
+//!
+//! The search needs something blunter than a decaying score: who has
+//! actually been working with or fighting whom in the last few turns. The
+//! tracker also keeps, for each of the last [`ALLIANCE_WINDOW`] movement
+//! turns whose orders it saw, two counts per pair:
+//!
+//! - supports: one power supporting the other's unit to hold or move;
+//! - clashes: one power moving onto the other's unit or center, supporting
+//!   such a move, or both moving into the same province (a bounce).
+//!
+//! [`RelationTracker::recent`] sums them into an [`AllianceMatrix`]. A pair
+//! with supports and no clashes is allied; a pair with clashes has an
+//! active front. While we already fight on a front, RM+ charges our
+//! candidates that strike a power we have no front with
+//! ([`new_front_penalty`]), so the engine does not open a second war on a
+//! whim.
+
+use std::collections::VecDeque;
+
+use serde_json::json;
+
+use crate::board::order::{Location, Order, OrderUnit};
+use crate::board::province::{Coast, Power, ALL_POWERS, MAX_PROVINCES};
+use crate::board::state::{BoardState, Phase};
+use crate::board::unit::UnitType;
+use crate::search::conventions::dmz_candidates;
+
+/// Score added for one support of another power's unit.
+const SUPPORT_WEIGHT: f64 = 1.0;
+
+/// Score removed for one move onto another power's unit or center.
+const ATTACK_WEIGHT: f64 = 1.0;
+
+/// Score removed when one power dislodges another's unit or takes its center.
+const CAPTURE_WEIGHT: f64 = 0.5;
+
+/// Score added per movement turn in which a pair kept at least one DMZ.
+const DMZ_WEIGHT: f64 = 0.1;
+
+/// Fraction of every score kept from one movement turn to the next.
+const DECAY: f64 = 0.85;
+
+/// Scores at or beyond this magnitude classify a pair as allied or hostile.
+const RELATION_THRESHOLD: f64 = 1.0;
+
+/// Movement turns [`RelationTracker::recent`] looks back over.
+pub const ALLIANCE_WINDOW: usize = 4;
+
+/// Penalty per power a candidate opens a new front against.
+const NEW_FRONT_WEIGHT: f64 = 0.75;
+
+/// How two powers currently treat each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Allied,
+    Neutral,
+    Hostile,
+}
+
+/// What two powers did to each other over the recent window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PairRelation {
+    /// Supports either gave the other's units.
+    pub supports: u32,
+    /// Attacks and bounces between them.
+    pub clashes: u32,
+}
+
+impl PairRelation {
+    /// Supports and no clashes.
+    pub fn allied(&self) -> bool {
+        self.supports > 0 && self.clashes == 0
+    }
+
+    /// Any clash in the window.
+    pub fn front(&self) -> bool {
+        self.clashes > 0
+    }
+}
+
+/// Symmetric support and clash counts over the recent movement turns, in
+/// `ALL_POWERS` order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AllianceMatrix {
+    pairs: [[PairRelation; 7]; 7],
+    /// Movement turns summed.
+    pub turns: usize,
+}
+
+impl AllianceMatrix {
+    /// The counts for a pair; all zero for a power with itself.
+    pub fn get(&self, a: Power, b: Power) -> PairRelation {
+        self.pairs[a.index()][b.index()]
+    }
+
+    /// Powers `power` is allied with.
+    pub fn allies(&self, power: Power) -> Vec<Power> {
+        self.others(power, PairRelation::allied)
+    }
+
+    /// Powers `power` has an active front with.
+    pub fn fronts(&self, power: Power) -> Vec<Power> {
+        self.others(power, PairRelation::front)
+    }
+
+    fn others(&self, power: Power, keep: fn(&PairRelation) -> bool) -> Vec<Power> {
+        ALL_POWERS
+            .iter()
+            .copied()
+            .filter(|&p| p != power && keep(&self.get(power, p)))
+            .collect()
+    }
+
+    fn add(&mut self, other: &[[PairRelation; 7]; 7]) {
+        for (row, other_row) in self.pairs.iter_mut().zip(other) {
+            for (pair, o) in row.iter_mut().zip(other_row) {
+                pair.supports += o.supports;
+                pair.clashes += o.clashes;
+            }
+        }
+    }
+}
+
+/// Pairwise relation scores and recent counts. See the module docs.
+#[derive(Debug, Clone, Default)]
+pub struct RelationTracker {
+    scores: [[f64; 7]; 7],
+    last_movement: Option<BoardState>,
+    /// (turn index, counts of that turn) for the recent window, oldest
+    /// first.
+    recent: VecDeque<(u32, [[PairRelation; 7]; 7])>,
+}
+
+impl RelationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets all observations.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Current score of the pair; positive is friendly.
+    pub fn score(&self, a: Power, b: Power) -> f64 {
+        self.scores[a.index()][b.index()]
+    }
+
+    /// The full symmetric score matrix in `ALL_POWERS` order.
+    pub fn matrix(&self) -> [[f64; 7]; 7] {
+        self.scores
+    }
+
+    /// Classifies the pair by its score.
+    pub fn relation(&self, a: Power, b: Power) -> Relation {
+        let s = self.score(a, b);
+        if a == b || s.abs() < RELATION_THRESHOLD {
+            Relation::Neutral
+        } else if s > 0.0 {
+            Relation::Allied
+        } else {
+            Relation::Hostile
+        }
+    }
+
+    /// Powers currently in the given relation with `power`.
+    pub fn with(&self, power: Power, relation: Relation) -> Vec<Power> {
+        ALL_POWERS
+            .iter()
+            .copied()
+            .filter(|&p| p != power && self.relation(power, p) == relation)
+            .collect()
+    }
+
+    /// The support and clash counts of the recent window.
+    pub fn recent(&self) -> AllianceMatrix {
+        let mut m = AllianceMatrix {
+            turns: self.recent.len(),
+            ..AllianceMatrix::default()
+        };
+        for (_, counts) in &self.recent {
+            m.add(counts);
+        }
+        m
+    }
+
+    /// Observes a position. Movement positions decay the scores and credit
+    /// DMZs kept since the previous turn and centers taken; retreat
+    /// positions charge the dislodgements they show.
+    pub fn observe_position(&mut self, state: &BoardState) {
+        match state.phase {
+            Phase::Movement => {
+                for row in self.scores.iter_mut() {
+                    for s in row.iter_mut() {
+                        *s *= DECAY;
+                    }
+                }
+                if let Some(prev) = self.last_movement.take() {
+                    if state.turn_index() == prev.turn_index() + 1 {
+                        self.credit_dmzs(&prev, state);
+                    }
+                    for (before, after) in prev.sc_owner.iter().zip(state.sc_owner.iter()) {
+                        if let (Some(loser), Some(taker)) = (before, after) {
+                            if loser != taker {
+                                self.add(*loser, *taker, -CAPTURE_WEIGHT);
+                            }
+                        }
+                    }
+                }
+                self.last_movement = Some(state.clone());
+            }
+            Phase::Retreat => {
+                for (unit, dislodged) in state.units.iter().zip(state.dislodged.iter()) {
+                    if let (Some((attacker, _)), Some(d)) = (unit, dislodged) {
+                        if *attacker != d.power {
+                            self.add(*attacker, d.power, -CAPTURE_WEIGHT);
+                        }
+                    }
+                }
+            }
+            Phase::Build => {}
+        }
+    }
+
+    /// Observes the orders played from a movement position.
+    ///
+    /// The scores take each turn's orders once. In the recent counts, the
+    /// same turn observed again replaces the earlier observation, and turns
+    /// older than the window behind the newest are dropped.
+    pub fn observe_orders(&mut self, state: &BoardState, orders: &[(Order, Power)]) {
+        if state.phase != Phase::Movement {
+            return;
+        }
+        let turn = state.turn_index();
+        let seen = self.recent.iter().any(|&(t, _)| t == turn);
+        self.recent.retain(|&(t, _)| t != turn);
+        self.recent.push_back((turn, turn_counts(state, orders)));
+        self.recent.make_contiguous().sort_by_key(|&(t, _)| t);
+        let newest = self.recent.back().map_or(turn, |&(t, _)| t);
+        self.recent
+            .retain(|&(t, _)| t + ALLIANCE_WINDOW as u32 > newest);
+        if seen {
+            return;
+        }
+        for &(order, power) in orders {
+            match order {
+                Order::SupportHold { supported, .. } | Order::SupportMove { supported, .. } => {
+                    if let Some((other, _)) = state.units[supported.location.province.index()] {
+                        if other != power {
+                            self.add(power, other, SUPPORT_WEIGHT);
+                        }
+                    }
+                }
+                Order::Move { dest, .. } => {
+                    if let Some(victim) = victim_at(state, dest.province.index(), power) {
+                        self.add(power, victim, -ATTACK_WEIGHT);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn credit_dmzs(&mut self, prev: &BoardState, state: &BoardState) {
+        let mut kept = [[false; 7]; 7];
+        for (prov, a, b) in dmz_candidates(prev) {
+            if state.units[prov.index()].is_none() {
+                kept[a.index()][b.index()] = true;
+            }
+        }
+        for (i, row) in kept.iter().enumerate() {
+            for (j, &k) in row.iter().enumerate() {
+                if k {
+                    self.add(ALL_POWERS[i], ALL_POWERS[j], DMZ_WEIGHT);
+                }
+            }
+        }
+    }
+
+    fn add(&mut self, a: Power, b: Power, delta: f64) {
+        let (i, j) = (a.index(), b.index());
+        self.scores[i][j] += delta;
+        self.scores[j][i] = self.scores[i][j];
+    }
+}
+
+/// Supports and clashes in one turn's orders, counted for both powers of
+/// each pair.
+fn turn_counts(state: &BoardState, orders: &[(Order, Power)]) -> [[PairRelation; 7]; 7] {
+    let mut counts = [[PairRelation::default(); 7]; 7];
+    let mut bump = |a: Power, b: Power, clash: bool| {
+        if a == b {
+            return;
+        }
+        for (i, j) in [(a.index(), b.index()), (b.index(), a.index())] {
+            if clash {
+                counts[i][j].clashes += 1;
+            } else {
+                counts[i][j].supports += 1;
+            }
+        }
+    };
+    let mut movers: [Option<Power>; MAX_PROVINCES] = [None; MAX_PROVINCES];
+    for &(order, power) in orders {
+        match order {
+            Order::SupportHold { supported, .. } => {
+                if let Some((other, _)) = state.units[supported.location.province.index()] {
+                    bump(power, other, false);
+                }
+            }
+            Order::SupportMove {
+                supported, dest, ..
+            } => {
+                if let Some((other, _)) = state.units[supported.location.province.index()] {
+                    bump(power, other, false);
+                }
+                if let Some(victim) = victim_at(state, dest.province.index(), power) {
+                    bump(power, victim, true);
+                }
+            }
+            Order::Move { dest, .. } => {
+                let idx = dest.province.index();
+                if let Some(victim) = victim_at(state, idx, power) {
+                    bump(power, victim, true);
+                }
+                match movers[idx] {
+                    None => movers[idx] = Some(power),
+                    // A bounce, unless the province was someone's already
+                    // and counted above.
+                    Some(p) if p != power && victim_at(state, idx, power).is_none() => {
+                        bump(power, p, true)
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    counts
+}
+
+/// The other power whose unit or, failing one, center stands in the
+/// province.
+fn victim_at(state: &BoardState, idx: usize, power: Power) -> Option<Power> {
+    state.units[idx]
+        .map(|(p, _)| p)
+        .or(state.sc_owner[idx])
+        .filter(|&p| p != power)
+}
+
+/// The moves that took units from `prev`, a movement position, to `next`,
+/// for powers whose orders nobody reported: each unit gone from its
+/// province whose power now has a unit of the same type in exactly one
+/// province next to it that it did not hold before. Holds, supports and
+/// bounces leave no trace in the positions and are not inferred.
+pub fn inferred_moves(prev: &BoardState, next: &BoardState) -> Vec<(Order, Power)> {
+    let mut moves = Vec::new();
+    for &from in prev.map().provinces() {
+        let idx = from.index();
+        let Some((power, unit_type)) = prev.units[idx] else {
+            continue;
+        };
+        if next.units[idx] == Some((power, unit_type)) || next.dislodged[idx].is_some() {
+            continue;
+        }
+        let coast = prev.fleet_coast[idx].unwrap_or(Coast::None);
+        let is_fleet = unit_type == UnitType::Fleet;
+        let arrivals: Vec<_> = prev
+            .map()
+            .provinces_adjacent_to(from, coast, is_fleet)
+            .into_iter()
+            .filter(|&to| {
+                next.units[to.index()] == Some((power, unit_type))
+                    && prev.units[to.index()] != Some((power, unit_type))
+            })
+            .collect();
+        if let [to] = arrivals[..] {
+            let unit = OrderUnit {
+                unit_type,
+                location: Location::with_coast(from, coast),
+            };
+            let dest =
+                Location::with_coast(to, next.fleet_coast[to.index()].unwrap_or(Coast::None));
+            moves.push((
+                Order::Move {
+                    unit,
+                    dest,
+                    via_convoy: false,
+                },
+                power,
+            ));
+        }
+    }
+    moves
+}
+
+/// Penalty for a candidate of `power` that moves onto, or supports a move
+/// onto, the units or centers of powers outside `fronts`, per power struck.
+/// Nothing while `fronts` is empty: a power at peace has to start somewhere.
+pub fn new_front_penalty(
+    orders: &[(Order, Power)],
+    state: &BoardState,
+    power: Power,
+    fronts: &[Power],
+) -> f64 {
+    if fronts.is_empty() {
+        return 0.0;
+    }
+    let mut struck: Vec<Power> = Vec::new();
+    for &(order, _) in orders {
+        let dest = match order {
+            Order::Move { dest, .. } | Order::SupportMove { dest, .. } => dest.province,
+            _ => continue,
+        };
+        if let Some(victim) = victim_at(state, dest.index(), power) {
+            if !fronts.contains(&victim) && !struck.contains(&victim) {
+                struck.push(victim);
+            }
+        }
+    }
+    struck.len() as f64 * NEW_FRONT_WEIGHT
+}
+
+/// Serializes the matrix as a single-line JSON object, listing only pairs
+/// with supports or clashes.
+///
+/// Format: `{"turns":4,"pairs":[{"powers":["austria","russia"],"supports":2,"clashes":0,"relation":"allied"},...]}`
+pub fn alliances_json(matrix: &AllianceMatrix) -> String {
+    let mut pairs = Vec::new();
+    for (i, &a) in ALL_POWERS.iter().enumerate() {
+        for &b in &ALL_POWERS[i + 1..] {
+            let pair = matrix.get(a, b);
+            if pair == PairRelation::default() {
+                continue;
+            }
+            // Listed pairs have supports or clashes, so one of the two.
+            let relation = if pair.front() { "front" } else { "allied" };
+            pairs.push(json!({
+                "powers": [a.name(), b.name()],
+                "supports": pair.supports,
+                "clashes": pair.clashes,
+                "relation": relation,
+            }));
+        }
+    }
+    json!({ "turns": matrix.turns, "pairs": pairs }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn orders(list: &[(&str, Power)]) -> Vec<(Order, Power)> {
+        list.iter()
+            .map(|(s, p)| (parse_order(s).unwrap(), *p))
+            .collect()
+    }
+
+    #[test]
+    fn supports_ally_and_attacks_sour() {
+        let state = parse_dfen("1901sm/Aavie,Aasil,Ragal,Rawar/Avie,Rwar/-").unwrap();
+        let mut t = RelationTracker::new();
+        t.observe_position(&state);
+        t.observe_orders(
+            &state,
+            &orders(&[
+                ("A vie S A gal H", Power::Austria),
+                ("A gal H", Power::Russia),
+            ]),
+        );
+        assert_eq!(t.relation(Power::Austria, Power::Russia), Relation::Allied);
+        assert_eq!(
+            t.with(Power::Russia, Relation::Allied),
+            vec![Power::Austria]
+        );
+        assert_eq!(t.score(Power::Russia, Power::Austria), SUPPORT_WEIGHT);
+
+        let fall = parse_dfen("1901fm/Aavie,Aasil,Ragal,Rawar/Avie,Rwar/-").unwrap();
+        t.observe_position(&fall);
+        t.observe_orders(
+            &fall,
+            &orders(&[
+                ("A vie - gal", Power::Austria),
+                ("A sil - war", Power::Austria),
+            ]),
+        );
+        assert_eq!(t.relation(Power::Austria, Power::Russia), Relation::Hostile);
+        assert_eq!(
+            t.relation(Power::Austria, Power::Austria),
+            Relation::Neutral
+        );
+    }
+
+    #[test]
+    fn positions_alone_show_dislodgements_and_kept_dmzs() {
+        let mut t = RelationTracker::new();
+        // Austria and Russia both border gal and keep it empty.
+        let spring = parse_dfen("1901sm/Aavie,Rawar/Avie,Rwar/-").unwrap();
+        let fall = parse_dfen("1901fm/Aavie,Rawar/Avie,Rwar/-").unwrap();
+        t.observe_position(&spring);
+        t.observe_position(&fall);
+        assert!(t.score(Power::Austria, Power::Russia) > 0.0);
+        assert_eq!(t.relation(Power::Austria, Power::Russia), Relation::Neutral);
+
+        // Russia dislodges Austria from vie.
+        let retreat = parse_dfen("1901fr/Ravie/Avie,Rwar/Aavie<gal").unwrap();
+        t.observe_position(&retreat);
+        assert!(t.score(Power::Austria, Power::Russia) < 0.0);
+
+        t.reset();
+        assert_eq!(t.matrix(), [[0.0; 7]; 7]);
+    }
+
+    /// Austria supports Russia in Galicia while Italy and Austria bounce
+    /// in Tyrolia.
+    fn spring() -> (BoardState, Vec<(Order, Power)>) {
+        let state = parse_dfen("1901sm/Aavie,Aaboh,Ragal,Iaven/Avie,Iven,Rwar/-").unwrap();
+        let played = orders(&[
+            ("A vie S A gal H", Power::Austria),
+            ("A boh - tyr", Power::Austria),
+            ("A ven - tyr", Power::Italy),
+            ("A gal H", Power::Russia),
+        ]);
+        (state, played)
+    }
+
+    #[test]
+    fn supports_ally_and_bounces_open_a_front() {
+        let (state, played) = spring();
+        let mut t = RelationTracker::new();
+        t.observe_orders(&state, &played);
+        let m = t.recent();
+        assert_eq!(m.turns, 1);
+        assert_eq!(
+            m.get(Power::Austria, Power::Russia),
+            PairRelation {
+                supports: 1,
+                clashes: 0
+            }
+        );
+        assert_eq!(m.get(Power::Italy, Power::Austria).clashes, 1);
+        assert_eq!(m.allies(Power::Austria), vec![Power::Russia]);
+        assert_eq!(m.fronts(Power::Austria), vec![Power::Italy]);
+        assert!(m.fronts(Power::Russia).is_empty());
+    }
+
+    #[test]
+    fn attacks_count_and_old_turns_drop_out() {
+        let (state, played) = spring();
+        let mut t = RelationTracker::new();
+        t.observe_orders(&state, &played);
+        // Russia turns on Vienna in the fall.
+        let fall = parse_dfen("1901fm/Aavie,Aaboh,Ragal,Iaven/Avie,Iven,Rwar/-").unwrap();
+        t.observe_orders(&fall, &orders(&[("A gal - vie", Power::Russia)]));
+        let m = t.recent();
+        assert_eq!(m.turns, 2);
+        assert_eq!(m.get(Power::Russia, Power::Austria).clashes, 1);
+        assert!(!m.get(Power::Russia, Power::Austria).allied());
+
+        // Re-observing a turn replaces it.
+        t.observe_orders(&fall, &[]);
+        assert_eq!(t.recent().get(Power::Russia, Power::Austria).clashes, 0);
+
+        let later = parse_dfen("1903fm/Aavie/Avie/-").unwrap();
+        t.observe_orders(&later, &[]);
+        assert_eq!(t.recent().turns, 1);
+        assert_eq!(t.recent().get(Power::Austria, Power::Russia).supports, 0);
+    }
+
+    #[test]
+    fn moves_are_inferred_from_consecutive_positions() {
+        let spring = parse_dfen("1901sm/Aavie,Aabud,Ragal,Tfank/Avie,Abud,Rwar/-").unwrap();
+        let fall = parse_dfen("1901fm/Aavie,Aarum,Ragal,Tfbla/Avie,Abud,Rwar/-").unwrap();
+        let moves = inferred_moves(&spring, &fall);
+        assert_eq!(
+            moves,
+            orders(&[
+                ("F ank - bla", Power::Turkey),
+                ("A bud - rum", Power::Austria)
+            ])
+        );
+    }
+
+    #[test]
+    fn striking_a_new_power_costs_while_at_war() {
+        let (state, _) = spring();
+        let hit_russia = orders(&[("A vie - gal", Power::Austria)]);
+        let hit_italy = orders(&[("A tri - ven", Power::Austria)]);
+        assert_eq!(
+            new_front_penalty(&hit_russia, &state, Power::Austria, &[]),
+            0.0
+        );
+        assert_eq!(
+            new_front_penalty(&hit_russia, &state, Power::Austria, &[Power::Italy]),
+            NEW_FRONT_WEIGHT
+        );
+        assert_eq!(
+            new_front_penalty(&hit_italy, &state, Power::Austria, &[Power::Italy]),
+            0.0
+        );
+    }
+
+    #[test]
+    fn json_lists_active_pairs() {
+        let (state, played) = spring();
+        let mut t = RelationTracker::new();
+        t.observe_orders(&state, &played);
+        let s = alliances_json(&t.recent());
+        let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(v["turns"], 1);
+        let pairs = v["pairs"].as_array().unwrap();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0]["powers"][0], "austria");
+        assert_eq!(pairs[0]["relation"], "front");
+        assert_eq!(pairs[1]["relation"], "allied");
+        assert!(!s.contains('\n'));
+    }
+}
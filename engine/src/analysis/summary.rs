@@ -0,0 +1,493 @@
+//! Game summary statistics.
+//!
+//! [`summary`] condenses a finished game into the numbers worth charting
+//! or comparing across runs: supply-center counts year by year, the year
+//! the first center changed hands between powers, stabs, alliance spells
+//! and the result.
+//!
+//! A power that moves onto a unit or center of a power it supported within
+//! the last year stabs it. Alliances come from the
+//! [`RelationTracker`](super::relations::RelationTracker) fed with the game's
+//! positions and orders: an alliance spell is a run of consecutive years in
+//! which the pair was classed allied after some movement turn and hostile
+//! after none.
+//!
+//! Contention counts moves into each province and how many failed, a move
+//! failing when its unit still stands in place in the next recorded phase
+//! (see [`ContentionTracker`]).
+
+use serde_json::{json, Value};
+
+use crate::analysis::contention::{ContentionTracker, ProvinceContention};
+use crate::analysis::relations::{Relation, RelationTracker};
+use crate::board::province::{Power, Province, ALL_POWERS};
+use crate::board::state::{BoardState, Phase, Season};
+use crate::board::Order;
+use crate::protocol::dfen::{diff, parse_dfen};
+use crate::protocol::dson::parse_orders;
+use crate::selfplay::GameRecord;
+
+/// Movement phases a support counts as recent for stab detection: the
+/// current year's spring and the previous year's.
+const STAB_WINDOW: usize = 2;
+
+/// Supply-center counts at the start of a year.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YearScs {
+    pub year: u16,
+    /// Counts in `ALL_POWERS` order.
+    pub counts: [i32; 7],
+}
+
+/// A move against a power the mover had recently supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StabEvent {
+    pub year: u16,
+    pub season: Season,
+    pub attacker: Power,
+    pub victim: Power,
+    /// First province of the victim's the attacker moved on that phase.
+    pub province: Province,
+}
+
+/// Consecutive years in which two powers cooperated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllianceSpell {
+    /// The pair, in `ALL_POWERS` order.
+    pub powers: (Power, Power),
+    pub from: u16,
+    /// Last year of the spell, inclusive.
+    pub to: u16,
+}
+
+impl AllianceSpell {
+    /// Length of the spell in game years.
+    pub fn years(&self) -> u16 {
+        self.to - self.from + 1
+    }
+}
+
+/// How a game ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameOutcome {
+    /// One power reached a solo victory.
+    Solo(Power),
+    /// The game stopped without a winner; the surviving powers share it.
+    Draw(Vec<Power>),
+}
+
+/// Summary statistics for one game. See the module docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameSummary {
+    pub game_id: usize,
+    /// SC counts at the start of each year, ending with the final position.
+    pub sc_graph: Vec<YearScs>,
+    /// Year in which a center first passed from one power to another.
+    pub first_blood: Option<u16>,
+    pub stabs: Vec<StabEvent>,
+    pub alliances: Vec<AllianceSpell>,
+    pub outcome: GameOutcome,
+    pub final_year: u16,
+    /// Provinces moved into at least once, most failed moves first.
+    pub contention: Vec<ProvinceContention>,
+}
+
+impl GameSummary {
+    /// Number of stabs `power` committed.
+    pub fn stabs_by(&self, power: Power) -> usize {
+        self.stabs.iter().filter(|s| s.attacker == power).count()
+    }
+
+    /// Renders the summary as a JSON object.
+    pub fn to_json(&self) -> Value {
+        let outcome = match &self.outcome {
+            GameOutcome::Solo(p) => json!({ "kind": "solo", "winner": p.name() }),
+            GameOutcome::Draw(ps) => json!({
+                "kind": "draw",
+                "survivors": ps.iter().map(|p| p.name()).collect::<Vec<_>>(),
+            }),
+        };
+        json!({
+            "game_id": self.game_id,
+            "final_year": self.final_year,
+            "outcome": outcome,
+            "first_blood": self.first_blood,
+            "sc_graph": self
+                .sc_graph
+                .iter()
+                .map(|y| json!({ "year": y.year, "counts": y.counts }))
+                .collect::<Vec<_>>(),
+            "stabs": self
+                .stabs
+                .iter()
+                .map(|s| json!({
+                    "year": s.year,
+                    "season": s.season.dfen_char().to_string(),
+                    "attacker": s.attacker.name(),
+                    "victim": s.victim.name(),
+                    "province": s.province.abbr(),
+                }))
+                .collect::<Vec<_>>(),
+            "alliances": self
+                .alliances
+                .iter()
+                .map(|a| json!({
+                    "powers": [a.powers.0.name(), a.powers.1.name()],
+                    "from": a.from,
+                    "to": a.to,
+                }))
+                .collect::<Vec<_>>(),
+            "contention": self
+                .contention
+                .iter()
+                .map(|c| json!({
+                    "province": c.province.abbr(),
+                    "attempts": c.attempts,
+                    "bounces": c.bounces,
+                    "contested": c.contested,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Computes summary statistics for a recorded game. Phases whose DFEN or
+/// orders fail to parse are skipped.
+pub fn summary(game: &GameRecord) -> GameSummary {
+    let mut sc_graph: Vec<YearScs> = Vec::new();
+    let mut first_blood = None;
+    let mut prev_owners: Option<BoardState> = None;
+    let mut stabs = Vec::new();
+    // Movement-phase index of the last support from power i to power j.
+    let mut last_support = [[None::<usize>; 7]; 7];
+    let mut movement = 0usize;
+    let mut tracker = RelationTracker::new();
+    let mut yearly: Vec<YearRelations> = Vec::new();
+    let mut contention = ContentionTracker::new();
+
+    for (pi, phase) in game.phases.iter().enumerate() {
+        let Ok(state) = parse_dfen(&phase.dfen) else {
+            continue;
+        };
+        if state.season == Season::Spring && state.phase == Phase::Movement {
+            if let Some(prev) = &prev_owners {
+                if first_blood.is_none() && center_taken(prev, &state) {
+                    first_blood = Some(prev.year);
+                }
+            }
+            sc_graph.push(YearScs {
+                year: state.year,
+                counts: phase.sc_counts,
+            });
+            prev_owners = Some(state.clone());
+        }
+        tracker.observe_position(&state);
+        if state.phase != Phase::Movement {
+            continue;
+        }
+
+        let mut played: Vec<(Order, Power)> = Vec::new();
+        for (power, dson) in &phase.orders {
+            let Ok(orders) = parse_orders(dson) else {
+                continue;
+            };
+            played.extend(orders.iter().map(|&o| (o, *power)));
+            let a = power.index();
+            let mut stabbed = [false; 7];
+            for order in &orders {
+                match order {
+                    Order::SupportHold { supported, .. } | Order::SupportMove { supported, .. } => {
+                        if let Some(b) = owner_at(&state, supported.location.province) {
+                            if b != *power {
+                                last_support[a][b.index()] = Some(movement);
+                            }
+                        }
+                    }
+                    Order::Move { dest, .. } => {
+                        let prov = dest.province;
+                        let Some(b) = owner_at(&state, prov)
+                            .or(state.sc_owner[prov.index()])
+                            .filter(|b| b != power)
+                        else {
+                            continue;
+                        };
+                        let bi = b.index();
+                        let recent = last_support[a][bi]
+                            .is_some_and(|m| m + STAB_WINDOW >= movement && m < movement);
+                        if recent && !stabbed[bi] {
+                            stabbed[bi] = true;
+                            stabs.push(StabEvent {
+                                year: state.year,
+                                season: state.season,
+                                attacker: *power,
+                                victim: b,
+                                province: prov,
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        tracker.observe_orders(&state, &played);
+        if let Some(next) = game
+            .phases
+            .get(pi + 1)
+            .and_then(|n| parse_dfen(&n.dfen).ok())
+        {
+            contention.observe(&state, &played, &next);
+        }
+        if yearly.last().is_none_or(|y| y.year != state.year) {
+            yearly.push(YearRelations {
+                year: state.year,
+                allied: [[false; 7]; 7],
+                hostile: [[false; 7]; 7],
+            });
+        }
+        let year = yearly.last_mut().unwrap();
+        for (i, &a) in ALL_POWERS.iter().enumerate() {
+            for (j, &b) in ALL_POWERS.iter().enumerate() {
+                match tracker.relation(a, b) {
+                    Relation::Allied => year.allied[i][j] = true,
+                    Relation::Hostile => year.hostile[i][j] = true,
+                    Relation::Neutral => {}
+                }
+            }
+        }
+        movement += 1;
+    }
+
+    if let Some(prev) = &prev_owners {
+        if first_blood.is_none() && game.final_year > prev.year {
+            let final_counts = game.final_sc_counts;
+            // Records carry no final ownership map; a power ending with
+            // fewer centers than it started the year with lost one.
+            let start = sc_graph.last().map(|y| y.counts).unwrap_or_default();
+            if start.iter().zip(final_counts).any(|(&s, f)| f < s) {
+                first_blood = Some(prev.year);
+            }
+        }
+    }
+    if sc_graph.last().is_none_or(|y| y.year != game.final_year) {
+        sc_graph.push(YearScs {
+            year: game.final_year,
+            counts: game.final_sc_counts,
+        });
+    }
+
+    let outcome = match game.winner {
+        Some(w) => GameOutcome::Solo(w),
+        None => GameOutcome::Draw(
+            ALL_POWERS
+                .iter()
+                .zip(game.final_sc_counts)
+                .filter(|(_, sc)| *sc > 0)
+                .map(|(&p, _)| p)
+                .collect(),
+        ),
+    };
+
+    GameSummary {
+        game_id: game.game_id,
+        sc_graph,
+        first_blood,
+        stabs,
+        alliances: alliance_spells(&yearly),
+        outcome,
+        final_year: game.final_year,
+        contention: contention.provinces(),
+    }
+}
+
+/// Pairs of powers classed allied or hostile after any movement turn of
+/// one year, indexed by `ALL_POWERS` slot.
+struct YearRelations {
+    year: u16,
+    allied: [[bool; 7]; 7],
+    hostile: [[bool; 7]; 7],
+}
+
+/// Returns true if a center owned by one power in `before` belongs to
+/// another in `after`.
+fn center_taken(before: &BoardState, after: &BoardState) -> bool {
+    diff(before, after)
+        .sc_changes
+        .iter()
+        .any(|c| c.from.is_some() && c.to.is_some())
+}
+
+/// Collects runs of cooperative years per pair from per-year flags.
+fn alliance_spells(yearly: &[YearRelations]) -> Vec<AllianceSpell> {
+    let mut spells = Vec::new();
+    for (i, &a) in ALL_POWERS.iter().enumerate() {
+        for (j, &b) in ALL_POWERS.iter().enumerate().skip(i + 1) {
+            let mut open: Option<AllianceSpell> = None;
+            for y in yearly {
+                let year = y.year;
+                let allied = y.allied[i][j] && !y.hostile[i][j];
+                match (&mut open, allied) {
+                    (Some(spell), true) if spell.to + 1 == year => spell.to = year,
+                    (_, true) => {
+                        spells.extend(open.take());
+                        open = Some(AllianceSpell {
+                            powers: (a, b),
+                            from: year,
+                            to: year,
+                        });
+                    }
+                    (_, false) => spells.extend(open.take()),
+                }
+            }
+            spells.extend(open);
+        }
+    }
+    spells.sort_by_key(|s| (s.from, s.powers.0.index(), s.powers.1.index()));
+    spells
+}
+
+/// Owner of the unit in `province`, if any.
+fn owner_at(state: &BoardState, province: Province) -> Option<Power> {
+    state.units[province.index()].map(|(p, _)| p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selfplay::{GameQuality, PhaseRecord};
+
+    fn phase(dfen: &str, orders: &[(Power, &str)], sc_counts: [i32; 7]) -> PhaseRecord {
+        PhaseRecord {
+            dfen: dfen.to_string(),
+            year: 0,
+            season: 's',
+            phase: 'm',
+            orders: orders.iter().map(|(p, o)| (*p, o.to_string())).collect(),
+            values: [0.0; 7],
+            sc_counts,
+            destroyed: Vec::new(),
+        }
+    }
+
+    fn game(phases: Vec<PhaseRecord>, final_year: u16, final_sc_counts: [i32; 7]) -> GameRecord {
+        GameRecord {
+            game_id: 3,
+            phases,
+            winner: None,
+            final_sc_counts,
+            final_year,
+            quality: GameQuality::default(),
+            personas: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn detects_stab_after_support_and_ends_alliance() {
+        // 1901: Austria supports Russia's A gal hold. 1902: Austria moves
+        // onto Russia's War center.
+        let g = game(
+            vec![
+                phase(
+                    "1901sm/Aabud,Aavie,Ragal,Rawar/Abud,Avie,Rwar/-",
+                    &[
+                        (Power::Austria, "A vie S A gal H ; A bud H"),
+                        (Power::Russia, "A gal H ; A war H"),
+                    ],
+                    [2, 0, 0, 0, 0, 1, 0],
+                ),
+                phase(
+                    "1902sm/Aabud,Aavie,Ragal,Rawar/Abud,Avie,Rwar/-",
+                    &[
+                        (Power::Austria, "A vie - boh ; A bud H"),
+                        (Power::Russia, "A gal H ; A war H"),
+                    ],
+                    [2, 0, 0, 0, 0, 1, 0],
+                ),
+                phase(
+                    "1902fm/Aabud,Aagal,Rasil,Rawar/Abud,Avie,Rwar/-",
+                    &[(Power::Austria, "A gal - war ; A bud H")],
+                    [2, 0, 0, 0, 0, 1, 0],
+                ),
+            ],
+            1903,
+            [3, 0, 0, 0, 0, 0, 0],
+        );
+        let s = summary(&g);
+        assert_eq!(s.stabs.len(), 1);
+        let stab = s.stabs[0];
+        assert_eq!(
+            (stab.attacker, stab.victim),
+            (Power::Austria, Power::Russia)
+        );
+        assert_eq!((stab.year, stab.season), (1902, Season::Fall));
+        assert_eq!(stab.province, Province::War);
+        assert_eq!(s.stabs_by(Power::Austria), 1);
+
+        assert_eq!(
+            s.alliances,
+            vec![AllianceSpell {
+                powers: (Power::Austria, Power::Russia),
+                from: 1901,
+                to: 1901,
+            }]
+        );
+        assert_eq!(s.first_blood, Some(1902));
+        assert_eq!(s.sc_graph.len(), 3);
+        assert_eq!(s.sc_graph[2].year, 1903);
+        assert_eq!(s.outcome, GameOutcome::Draw(vec![Power::Austria]));
+    }
+
+    #[test]
+    fn json_export_lists_every_section() {
+        let g = game(
+            vec![phase(
+                "1901sm/Aavie/Avie/-",
+                &[(Power::Austria, "A vie H")],
+                [1, 0, 0, 0, 0, 0, 0],
+            )],
+            1901,
+            [1, 0, 0, 0, 0, 0, 0],
+        );
+        let s = summary(&g);
+        assert_eq!(s.first_blood, None);
+        assert_eq!(s.sc_graph.len(), 1);
+        let v = s.to_json();
+        assert_eq!(v["game_id"], 3);
+        assert_eq!(v["outcome"]["kind"], "draw");
+        assert_eq!(v["outcome"]["survivors"][0], "austria");
+        assert_eq!(v["sc_graph"][0]["counts"][0], 1);
+        assert!(v["stabs"].as_array().unwrap().is_empty());
+        assert!(v["first_blood"].is_null());
+        assert!(v["contention"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn counts_repeated_bounces_per_province() {
+        let standoff = |dfen: &str| {
+            phase(
+                dfen,
+                &[
+                    (Power::Austria, "A vie - gal"),
+                    (Power::Russia, "A war - gal"),
+                ],
+                [1, 0, 0, 0, 0, 1, 0],
+            )
+        };
+        let g = game(
+            vec![
+                standoff("1901sm/Aavie,Rawar/Avie,Rwar/-"),
+                standoff("1901fm/Aavie,Rawar/Avie,Rwar/-"),
+                phase("1902sm/Aavie,Rawar/Avie,Rwar/-", &[], [1, 0, 0, 0, 0, 1, 0]),
+            ],
+            1902,
+            [1, 0, 0, 0, 0, 1, 0],
+        );
+        let s = summary(&g);
+        assert_eq!(s.contention.len(), 1);
+        let gal = s.contention[0];
+        assert_eq!(gal.province, Province::Gal);
+        assert_eq!((gal.attempts, gal.bounces, gal.contested), (4, 4, 2));
+        let v = s.to_json();
+        assert_eq!(v["contention"][0]["province"], "gal");
+        assert_eq!(v["contention"][0]["bounces"], 4);
+    }
+}
@@ -2,15 +2,17 @@
 //!
 //! Each entry records a directed edge: (from, from_coast) -> (to, to_coast)
 //! with flags for army and fleet passability. The table is symmetric: if A->B
-//! exists then B->A also exists. All data is compile-time `static`.
+//! exists then B->A also exists. All data is compile-time `static`; it
+//! builds the standard [`GameMap`](super::map::GameMap), which answers the
+//! adjacency lookups for a game (see `board::map`).
 //!
 //! Split-coast provinces (bul, spa, stp) use coast-specific fleet adjacencies
 //! and Coast::None for army adjacencies.
 
-use super::province::{Coast, Province, PROVINCE_COUNT};
+use super::province::{Coast, Province};
 
 /// A single directed adjacency between two provinces.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AdjacencyEntry {
     pub from: Province,
     pub from_coast: Coast,
@@ -59,7 +61,7 @@ const SC: Coast = Coast::South;
 const EC: Coast = Coast::East;
 
 /// Alias province names for readability.
-use Province::*;
+use super::province::standard::*;
 
 /// Total number of directed adjacency entries in the table.
 ///
@@ -565,132 +567,21 @@ pub static ADJACENCIES: [AdjacencyEntry; ADJACENCY_COUNT] = [
     army(Syr, Arm),
 ];
 
-/// Returns true if a unit of the given type can move from `src` to `dst`,
-/// optionally specifying coasts for fleet movement on split-coast provinces.
-pub fn is_adjacent(
-    src: Province,
-    src_coast: Coast,
-    dst: Province,
-    dst_coast: Coast,
-    is_fleet: bool,
-) -> bool {
-    is_adjacent_fast(src, src_coast, dst, dst_coast, is_fleet)
-}
-
-/// Returns all coasts at the destination reachable by fleet from the given source and coast.
-pub fn fleet_coasts_to(src: Province, src_coast: Coast, dst: Province) -> Vec<Coast> {
-    let mut coasts = Vec::new();
-    for adj in adj_from(src) {
-        if adj.to != dst || !adj.fleet_ok {
-            continue;
-        }
-        if src_coast != Coast::None && adj.from_coast != Coast::None && adj.from_coast != src_coast
-        {
-            continue;
-        }
-        if !coasts.contains(&adj.to_coast) {
-            coasts.push(adj.to_coast);
-        }
-    }
-    coasts
-}
-
-/// Returns all provinces adjacent to the given province for the given unit type.
-pub fn provinces_adjacent_to(prov: Province, coast: Coast, is_fleet: bool) -> Vec<Province> {
-    let mut result = Vec::new();
-    for adj in adj_from(prov) {
-        if is_fleet && !adj.fleet_ok {
-            continue;
-        }
-        if !is_fleet && !adj.army_ok {
-            continue;
-        }
-        if coast != Coast::None && adj.from_coast != Coast::None && adj.from_coast != coast {
-            continue;
-        }
-        if !result.contains(&adj.to) {
-            result.push(adj.to);
-        }
-    }
-    result
-}
-
-/// Pre-computed per-province adjacency index for O(neighbors) lookup.
-///
-/// At first access, copies all adjacency entries into a vec sorted by
-/// `from` province, and stores `(start, end)` offsets for each province.
-/// Subsequent adjacency lookups use this index instead of scanning 424 entries.
-use std::sync::LazyLock;
-
-struct AdjIndex {
-    entries: Vec<AdjacencyEntry>,
-    offsets: [(u16, u16); PROVINCE_COUNT],
-}
-
-static ADJ_INDEX: LazyLock<AdjIndex> = LazyLock::new(|| {
-    let mut sorted: Vec<AdjacencyEntry> = ADJACENCIES.to_vec();
-    sorted.sort_by_key(|a| a.from as u8);
-
-    let mut offsets = [(0u16, 0u16); PROVINCE_COUNT];
-    let mut i = 0;
-    for p in 0..PROVINCE_COUNT {
-        let start = i;
-        while i < sorted.len() && sorted[i].from as u8 == p as u8 {
-            i += 1;
-        }
-        offsets[p] = (start as u16, i as u16);
-    }
-
-    AdjIndex {
-        entries: sorted,
-        offsets,
-    }
-});
-
-/// Returns the adjacency entries originating from the given province.
-#[inline]
-pub fn adj_from(prov: Province) -> &'static [AdjacencyEntry] {
-    let idx = &*ADJ_INDEX;
-    let (start, end) = idx.offsets[prov as usize];
-    &idx.entries[start as usize..end as usize]
-}
-
-/// Fast is_adjacent using per-province index.
-pub fn is_adjacent_fast(
-    src: Province,
-    src_coast: Coast,
-    dst: Province,
-    dst_coast: Coast,
-    is_fleet: bool,
-) -> bool {
-    for adj in adj_from(src) {
-        if adj.to != dst {
-            continue;
-        }
-        if is_fleet && !adj.fleet_ok {
-            continue;
-        }
-        if !is_fleet && !adj.army_ok {
-            continue;
-        }
-        if src_coast != Coast::None && adj.from_coast != Coast::None && adj.from_coast != src_coast
-        {
-            continue;
-        }
-        if dst_coast != Coast::None && adj.to_coast != Coast::None && adj.to_coast != dst_coast {
-            continue;
-        }
-        return true;
-    }
-    false
-}
-
 #[cfg(test)]
 mod tests {
+    use super::super::map::GameMap;
     use super::super::province::{ProvinceType, ALL_PROVINCES};
     use super::*;
     use std::collections::HashSet;
 
+    fn is_adjacent(src: Province, sc: Coast, dst: Province, dc: Coast, is_fleet: bool) -> bool {
+        GameMap::standard().is_adjacent(src, sc, dst, dc, is_fleet)
+    }
+
+    fn provinces_adjacent_to(prov: Province, coast: Coast, is_fleet: bool) -> Vec<Province> {
+        GameMap::standard().provinces_adjacent_to(prov, coast, is_fleet)
+    }
+
     #[test]
     fn adjacency_count() {
         assert_eq!(ADJACENCIES.len(), ADJACENCY_COUNT);
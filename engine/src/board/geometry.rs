@@ -0,0 +1,221 @@
+//! Drawing coordinates for the standard map.
+//!
+//! Each province has a centroid, where its unit and the ends of order
+//! arrows are drawn, and a label anchor, where its name goes: just below
+//! the centroid so the label clears the unit. Coordinates are in the
+//! [`VIEWBOX`] space of the board artwork the UI ships, and match the
+//! province centers the UI calibrated against it, so heat maps and
+//! external GUIs can place things without a geometry table of their own.
+//! Variant maps carry their own coordinates in the map definition.
+
+use super::province::PROVINCE_COUNT;
+
+/// Width and height of the coordinate space.
+pub const VIEWBOX: [u32; 2] = [1152, 1152];
+
+/// How far below the centroid a label is anchored.
+const LABEL_OFFSET: u32 = 22;
+
+/// Where a province is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvinceGeometry {
+    /// Where the province's unit and order arrows are drawn.
+    pub centroid: [u32; 2],
+    /// Where the province's name is drawn.
+    pub label: [u32; 2],
+}
+
+const fn at(x: u32, y: u32) -> ProvinceGeometry {
+    ProvinceGeometry {
+        centroid: [x, y],
+        label: [x, y + LABEL_OFFSET],
+    }
+}
+
+/// Compile-time lookup table: index by `Province::index`.
+pub static GEOMETRY: [ProvinceGeometry; PROVINCE_COUNT] = [
+    // 0: Adr - Adriatic Sea
+    at(540, 860),
+    // 1: Aeg - Aegean Sea
+    at(743, 973),
+    // 2: Alb - Albania
+    at(640, 905),
+    // 3: Ank - Ankara
+    at(920, 920),
+    // 4: Apu - Apulia
+    at(541, 907),
+    // 5: Arm - Armenia
+    at(1094, 905),
+    // 6: Bal - Baltic Sea
+    at(574, 537),
+    // 7: Bar - Barents Sea
+    at(804, 120),
+    // 8: Bel - Belgium
+    at(348, 637),
+    // 9: Ber - Berlin
+    at(525, 605),
+    // 10: Bla - Black Sea
+    at(871, 840),
+    // 11: Boh - Bohemia
+    at(530, 690),
+    // 12: Bot - Gulf of Bothnia
+    at(633, 383),
+    // 13: Bre - Brest
+    at(210, 660),
+    // 14: Bud - Budapest
+    at(655, 758),
+    // 15: Bul - Bulgaria
+    at(730, 885),
+    // 16: Bur - Burgundy
+    at(350, 740),
+    // 17: Cly - Clyde
+    at(262, 435),
+    // 18: Con - Constantinople
+    at(805, 912),
+    // 19: Den - Denmark
+    at(475, 505),
+    // 20: Eas - Eastern Mediterranean
+    at(849, 1085),
+    // 21: Edi - Edinburgh
+    at(294, 445),
+    // 22: Eng - English Channel
+    at(240, 625),
+    // 23: Fin - Finland
+    at(704, 340),
+    // 24: Gal - Galicia
+    at(700, 694),
+    // 25: Gas - Gascony
+    at(252, 795),
+    // 26: Gol - Gulf of Lyon
+    at(318, 870),
+    // 27: Gre - Greece
+    at(680, 985),
+    // 28: Hel - Heligoland Bight
+    at(434, 527),
+    // 29: Hol - Holland
+    at(392, 603),
+    // 30: Ion - Ionian Sea
+    at(577, 1048),
+    // 31: Iri - Irish Sea
+    at(160, 560),
+    // 32: Kie - Kiel
+    at(470, 605),
+    // 33: Lon - London
+    at(305, 590),
+    // 34: Lvn - Livonia
+    at(714, 524),
+    // 35: Lvp - Liverpool
+    at(274, 500),
+    // 36: Mao - Mid-Atlantic Ocean
+    at(44, 730),
+    // 37: Mar - Marseilles
+    at(345, 811),
+    // 38: Mos - Moscow
+    at(920, 490),
+    // 39: Mun - Munich
+    at(470, 715),
+    // 40: Naf - North Africa
+    at(198, 1051),
+    // 41: Nao - North Atlantic Ocean
+    at(82, 365),
+    // 42: Nap - Naples
+    at(544, 956),
+    // 43: Nrg - Norwegian Sea
+    at(350, 260),
+    // 44: Nth - North Sea
+    at(358, 498),
+    // 45: Nwy - Norway
+    at(500, 380),
+    // 46: Par - Paris
+    at(315, 704),
+    // 47: Pic - Picardy
+    at(314, 655),
+    // 48: Pie - Piedmont
+    at(415, 810),
+    // 49: Por - Portugal
+    at(54, 870),
+    // 50: Pru - Prussia
+    at(593, 590),
+    // 51: Rom - Rome
+    at(497, 892),
+    // 52: Ruh - Ruhr
+    at(417, 665),
+    // 53: Rum - Rumania
+    at(780, 815),
+    // 54: Ser - Serbia
+    at(650, 859),
+    // 55: Sev - Sevastopol
+    at(912, 785),
+    // 56: Sil - Silesia
+    at(560, 640),
+    // 57: Ska - Skagerrak
+    at(493, 458),
+    // 58: Smy - Smyrna
+    at(830, 1015),
+    // 59: Spa - Spain
+    at(154, 872),
+    // 60: Stp - St. Petersburg
+    at(819, 390),
+    // 61: Swe - Sweden
+    at(566, 400),
+    // 62: Syr - Syria
+    at(1054, 1015),
+    // 63: Tri - Trieste
+    at(574, 800),
+    // 64: Tun - Tunisia
+    at(410, 1058),
+    // 65: Tus - Tuscany
+    at(467, 855),
+    // 66: Tyr - Tyrolia
+    at(500, 765),
+    // 67: Tys - Tyrrhenian Sea
+    at(450, 960),
+    // 68: Ukr - Ukraine
+    at(785, 693),
+    // 69: Ven - Venice
+    at(475, 814),
+    // 70: Vie - Vienna
+    at(584, 733),
+    // 71: Wal - Wales
+    at(262, 576),
+    // 72: War - Warsaw
+    at(655, 625),
+    // 73: Wes - Western Mediterranean
+    at(230, 970),
+    // 74: Yor - Yorkshire
+    at(300, 535),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::{Province, ALL_PROVINCES};
+
+    #[test]
+    fn every_province_is_placed_inside_the_viewbox() {
+        for &prov in ALL_PROVINCES.iter() {
+            let g = GEOMETRY[prov.index()];
+            for [x, y] in [g.centroid, g.label] {
+                assert!(
+                    x < VIEWBOX[0] && y < VIEWBOX[1],
+                    "{} off the board",
+                    prov.abbr()
+                );
+            }
+            // No two units are drawn on top of each other.
+            assert_eq!(
+                ALL_PROVINCES
+                    .iter()
+                    .filter(|&&p| GEOMETRY[p.index()].centroid == g.centroid)
+                    .count(),
+                1,
+                "{} shares its centroid",
+                prov.abbr()
+            );
+        }
+        // London sits west of Berlin and north of Rome.
+        let [lon_x, lon_y] = GEOMETRY[Province::Lon.index()].centroid;
+        assert!(lon_x < GEOMETRY[Province::Ber.index()].centroid[0]);
+        assert!(lon_y < GEOMETRY[Province::Rom.index()].centroid[1]);
+    }
+}
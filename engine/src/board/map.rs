@@ -0,0 +1,742 @@
+//! The map the engine plays on.
+//!
+//! Adjacency, supply centers and home centers are read through a
+//! [`GameMap`] rather than straight from the compiled standard tables, so
+//! movegen, resolution, DFEN parsing and the evaluator all follow the map
+//! a game is played on. Every [`BoardState`] carries its map as an
+//! `Arc<GameMap>` (see [`BoardState::map`]), and positions derived from it
+//! share it, so engines in one process can play different maps at once.
+//! The engine swaps its map at `newgame` time; positions are standard
+//! unless read or built on another map.
+//!
+//! A map is described as a [`MapDef`] that must pass [`check_map`], and
+//! brings its own provinces: their IDs, names, terrain and split coasts
+//! come from the definition, so a map may add provinces the standard board
+//! lacks, leave standard ones out, or change their terrain, as well as
+//! rewire adjacencies, move supply and home centers and seat fewer powers.
+//! A province the map shares with the standard board (by ID) keeps its
+//! standard index, so the `Province` constants name it on the map too; the
+//! map's own provinces take the indices left free, up to
+//! [`MAX_PROVINCES`]. Its powers must be standard powers.
+//!
+//! A few maps ship with the engine ([`BUILTIN_MAPS`], loaded by name with
+//! [`GameMap::builtin`]); others are read from a file with
+//! [`GameMap::load`].
+//!
+//! [`BoardState`]: super::state::BoardState
+//! [`BoardState::map`]: super::state::BoardState::map
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, OnceLock};
+
+use thiserror::Error;
+
+use super::adjacency::{AdjacencyEntry, ADJACENCIES};
+use super::geometry::{ProvinceGeometry, GEOMETRY, VIEWBOX};
+use super::mapdef::{check_map, MapDef, MapIssue};
+use super::province::{Coast, Power, Province, ProvinceType, MAX_PROVINCES, PROVINCE_INFO};
+
+/// Why a map could not be loaded.
+#[derive(Debug, Error)]
+pub enum MapError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("not a map definition: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("{} problem(s), first: {}", .0.len(), .0[0])]
+    Invalid(Vec<MapIssue>),
+
+    #[error("the map has {0} provinces; at most {MAX_PROVINCES} are supported")]
+    TooManyProvinces(usize),
+
+    #[error("power '{0}' is not a standard power")]
+    UnknownPower(String),
+}
+
+/// Variant maps that ship with the engine: name and JSON definition.
+pub const BUILTIN_MAPS: &[(&str, &str)] = &[
+    // The seven home capitals, every one bordering every other.
+    ("pure", include_str!("../../maps/pure.json")),
+];
+
+/// BFS distances between all province pairs for one unit type.
+pub(crate) struct DistMatrix {
+    dist: Box<[i16]>,
+    /// Province indices of the map's supply centers.
+    pub(crate) sc_indices: Vec<u8>,
+}
+
+impl DistMatrix {
+    fn build(map: &GameMap, fleet: bool) -> Self {
+        let mut dist = vec![-1i16; MAX_PROVINCES * MAX_PROVINCES];
+        let mut queue = VecDeque::with_capacity(MAX_PROVINCES);
+        for &prov in map.provinces() {
+            let src = prov.index();
+            dist[src * MAX_PROVINCES + src] = 0;
+            queue.clear();
+            queue.push_back((prov, 0i16));
+            while let Some((cur, d)) = queue.pop_front() {
+                for adj in map.adj_from(cur) {
+                    if (fleet && !adj.fleet_ok) || (!fleet && !adj.army_ok) {
+                        continue;
+                    }
+                    let to = adj.to.index();
+                    if dist[src * MAX_PROVINCES + to] == -1 {
+                        dist[src * MAX_PROVINCES + to] = d + 1;
+                        queue.push_back((adj.to, d + 1));
+                    }
+                }
+            }
+        }
+        DistMatrix {
+            dist: dist.into_boxed_slice(),
+            sc_indices: (0..MAX_PROVINCES)
+                .filter(|&i| map.supply_center[i])
+                .map(|i| i as u8)
+                .collect(),
+        }
+    }
+
+    /// Moves from `from` to `to`, or -1 if unreachable.
+    #[inline]
+    pub(crate) fn distance(&self, from: Province, to: Province) -> i16 {
+        self.dist[from.index() * MAX_PROVINCES + to.index()]
+    }
+
+    /// Row-major distances, indexed `from * MAX_PROVINCES + to`.
+    #[inline]
+    pub(crate) fn raw(&self) -> &[i16] {
+        &self.dist
+    }
+}
+
+/// One of a map's provinces: the ID DFEN and DSON name it by, its display
+/// name, terrain and split coasts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MapProvince {
+    abbr: String,
+    name: String,
+    kind: ProvinceType,
+    coasts: Vec<Coast>,
+}
+
+/// Provinces, adjacency, supply centers and home centers of one map, and
+/// where its provinces are drawn if the map says.
+pub struct GameMap {
+    name: String,
+    /// The map's provinces in index order.
+    provinces: Vec<Province>,
+    /// Each province by index; None at indices the map leaves unused.
+    info: Vec<Option<MapProvince>>,
+    ids: HashMap<String, Province>,
+    /// Adjacencies sorted by `from`, with each province's range in
+    /// `offsets`.
+    entries: Vec<AdjacencyEntry>,
+    offsets: [(u16, u16); MAX_PROVINCES],
+    supply_center: [bool; MAX_PROVINCES],
+    home: [Option<Power>; MAX_PROVINCES],
+    powers: Vec<Power>,
+    viewbox: Option<[u32; 2]>,
+    geometry: [Option<ProvinceGeometry>; MAX_PROVINCES],
+    army_dist: OnceLock<DistMatrix>,
+    fleet_dist: OnceLock<DistMatrix>,
+}
+
+impl GameMap {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        name: String,
+        info: Vec<Option<MapProvince>>,
+        mut entries: Vec<AdjacencyEntry>,
+        supply_center: [bool; MAX_PROVINCES],
+        home: [Option<Power>; MAX_PROVINCES],
+        powers: Vec<Power>,
+        viewbox: Option<[u32; 2]>,
+        geometry: [Option<ProvinceGeometry>; MAX_PROVINCES],
+    ) -> Self {
+        let provinces: Vec<Province> = (0..info.len())
+            .filter(|&i| info[i].is_some())
+            .map(Province::from_index)
+            .collect();
+        let ids = provinces
+            .iter()
+            .map(|&p| (info[p.index()].as_ref().unwrap().abbr.clone(), p))
+            .collect();
+        entries.sort_by_key(|a| a.from.index());
+        let mut offsets = [(0u16, 0u16); MAX_PROVINCES];
+        let mut i = 0;
+        for (p, offset) in offsets.iter_mut().enumerate() {
+            let start = i;
+            while i < entries.len() && (entries[i].from).index() == p {
+                i += 1;
+            }
+            *offset = (start as u16, i as u16);
+        }
+        GameMap {
+            name,
+            provinces,
+            info,
+            ids,
+            entries,
+            offsets,
+            supply_center,
+            home,
+            powers,
+            viewbox,
+            geometry,
+            army_dist: OnceLock::new(),
+            fleet_dist: OnceLock::new(),
+        }
+    }
+
+    /// The standard map, from the compiled tables. Every call shares one
+    /// map.
+    pub fn standard() -> Arc<GameMap> {
+        Arc::clone(&STANDARD)
+    }
+
+    /// Whether this is the shared standard map.
+    pub fn is_standard(&self) -> bool {
+        std::ptr::eq(self, &**STANDARD)
+    }
+
+    /// Builds a map from a definition, which must pass [`check_map`], have
+    /// at most [`MAX_PROVINCES`] provinces and seat standard powers.
+    pub fn from_def(def: &MapDef) -> Result<GameMap, MapError> {
+        let issues = check_map(def);
+        if !issues.is_empty() {
+            return Err(MapError::Invalid(issues));
+        }
+        if def.provinces.len() > MAX_PROVINCES {
+            return Err(MapError::TooManyProvinces(def.provinces.len()));
+        }
+        let powers = def
+            .powers
+            .iter()
+            .map(|name| Power::from_name(name).ok_or_else(|| MapError::UnknownPower(name.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Standard provinces keep their index; the map's own take the
+        // lowest indices left free.
+        let standard: Vec<Option<Province>> = def
+            .provinces
+            .iter()
+            .map(|p| Province::from_abbr(&p.id))
+            .collect();
+        let mut taken = [false; MAX_PROVINCES];
+        for prov in standard.iter().flatten() {
+            taken[prov.index()] = true;
+        }
+        let mut free = (0..MAX_PROVINCES).filter(|&i| !taken[i]);
+
+        let mut info = vec![None; MAX_PROVINCES];
+        let mut supply_center = [false; MAX_PROVINCES];
+        let mut home = [None; MAX_PROVINCES];
+        let mut geometry = [None; MAX_PROVINCES];
+        let mut ids: HashMap<&str, Province> = HashMap::new();
+        for (p, standard) in def.provinces.iter().zip(standard) {
+            // At most MAX_PROVINCES provinces, so a free index is left.
+            let prov = standard.unwrap_or_else(|| Province::from_index(free.next().unwrap()));
+            // check_map has verified the terrain and coasts.
+            let kind = match p.kind.as_str() {
+                "land" => ProvinceType::Land,
+                "sea" => ProvinceType::Sea,
+                _ => ProvinceType::Coastal,
+            };
+            info[prov.index()] = Some(MapProvince {
+                abbr: p.id.clone(),
+                name: p.name.clone(),
+                kind,
+                coasts: p
+                    .coasts
+                    .iter()
+                    .filter_map(|c| Coast::from_abbr(c))
+                    .collect(),
+            });
+            supply_center[prov.index()] = p.supply_center;
+            // check_map has verified every home power is listed.
+            home[prov.index()] = p.home.as_deref().and_then(Power::from_name);
+            geometry[prov.index()] = p.centroid.map(|centroid| ProvinceGeometry {
+                centroid,
+                label: p.label.unwrap_or(centroid),
+            });
+            ids.insert(&p.id, prov);
+        }
+
+        let coast = |c: &Option<String>| {
+            c.as_deref()
+                .and_then(Coast::from_abbr)
+                .unwrap_or(Coast::None)
+        };
+        let entries = def
+            .adjacencies
+            .iter()
+            .map(|a| AdjacencyEntry {
+                from: ids[a.from.as_str()],
+                from_coast: coast(&a.from_coast),
+                to: ids[a.to.as_str()],
+                to_coast: coast(&a.to_coast),
+                army_ok: a.army,
+                fleet_ok: a.fleet,
+            })
+            .collect();
+        Ok(GameMap::new(
+            def.name.clone(),
+            info,
+            entries,
+            supply_center,
+            home,
+            powers,
+            def.viewbox,
+            geometry,
+        ))
+    }
+
+    /// The standard map or one of the [`BUILTIN_MAPS`], by name.
+    pub fn builtin(name: &str) -> Option<Arc<GameMap>> {
+        if name == "standard" {
+            return Some(GameMap::standard());
+        }
+        let (_, json) = BUILTIN_MAPS.iter().find(|(n, _)| *n == name)?;
+        let def: MapDef = serde_json::from_str(json).expect("built-in map is valid JSON");
+        Some(Arc::new(
+            GameMap::from_def(&def).expect("built-in map passes checkmap"),
+        ))
+    }
+
+    /// Reads and builds the map definition in the JSON file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<GameMap, MapError> {
+        let text = std::fs::read_to_string(path)?;
+        let def: MapDef = serde_json::from_str(&text)?;
+        GameMap::from_def(&def)
+    }
+
+    /// The map's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The powers seated on the map.
+    pub fn powers(&self) -> &[Power] {
+        &self.powers
+    }
+
+    /// The map's provinces in index order.
+    #[inline]
+    pub fn provinces(&self) -> &[Province] {
+        &self.provinces
+    }
+
+    /// Whether `prov` is one of the map's provinces.
+    #[inline]
+    pub fn has_province(&self, prov: Province) -> bool {
+        self.info.get(prov.index()).is_some_and(Option::is_some)
+    }
+
+    /// Looks up one of the map's provinces by its 3-letter ID.
+    pub fn province(&self, abbr: &str) -> Option<Province> {
+        self.ids.get(abbr).copied()
+    }
+
+    fn info(&self, prov: Province) -> &MapProvince {
+        self.info[prov.index()]
+            .as_ref()
+            .unwrap_or_else(|| panic!("{:?} is not on map {}", prov, self.name))
+    }
+
+    /// The 3-letter ID DFEN and DSON name `prov` by.
+    #[inline]
+    pub fn abbr(&self, prov: Province) -> &str {
+        &self.info(prov).abbr
+    }
+
+    /// The display name of `prov`.
+    pub fn province_name(&self, prov: Province) -> &str {
+        &self.info(prov).name
+    }
+
+    /// The terrain of `prov`.
+    #[inline]
+    pub fn province_type(&self, prov: Province) -> ProvinceType {
+        self.info(prov).kind
+    }
+
+    /// The split coasts of `prov`, empty for ordinary provinces.
+    #[inline]
+    pub fn coasts(&self, prov: Province) -> &[Coast] {
+        &self.info(prov).coasts
+    }
+
+    /// Whether `prov` has split coasts.
+    #[inline]
+    pub fn has_coasts(&self, prov: Province) -> bool {
+        !self.info(prov).coasts.is_empty()
+    }
+
+    /// Adjacencies leaving `prov`.
+    #[inline]
+    pub fn adj_from(&self, prov: Province) -> &[AdjacencyEntry] {
+        let (start, end) = self.offsets[prov.index()];
+        &self.entries[start as usize..end as usize]
+    }
+
+    /// Returns true if a unit of the given type can move from `src` to
+    /// `dst`, optionally specifying coasts for fleet movement on split-coast
+    /// provinces.
+    pub fn is_adjacent(
+        &self,
+        src: Province,
+        src_coast: Coast,
+        dst: Province,
+        dst_coast: Coast,
+        is_fleet: bool,
+    ) -> bool {
+        self.adj_from(src).iter().any(|adj| {
+            adj.to == dst
+                && if is_fleet { adj.fleet_ok } else { adj.army_ok }
+                && (src_coast == Coast::None
+                    || adj.from_coast == Coast::None
+                    || adj.from_coast == src_coast)
+                && (dst_coast == Coast::None
+                    || adj.to_coast == Coast::None
+                    || adj.to_coast == dst_coast)
+        })
+    }
+
+    /// Returns all coasts at the destination reachable by fleet from the
+    /// given source and coast.
+    pub fn fleet_coasts_to(&self, src: Province, src_coast: Coast, dst: Province) -> Vec<Coast> {
+        let mut coasts = Vec::new();
+        for adj in self.adj_from(src) {
+            if adj.to != dst || !adj.fleet_ok {
+                continue;
+            }
+            if src_coast != Coast::None
+                && adj.from_coast != Coast::None
+                && adj.from_coast != src_coast
+            {
+                continue;
+            }
+            if !coasts.contains(&adj.to_coast) {
+                coasts.push(adj.to_coast);
+            }
+        }
+        coasts
+    }
+
+    /// Returns all provinces adjacent to the given province for the given
+    /// unit type.
+    pub fn provinces_adjacent_to(
+        &self,
+        prov: Province,
+        coast: Coast,
+        is_fleet: bool,
+    ) -> Vec<Province> {
+        let mut result = Vec::new();
+        for adj in self.adj_from(prov) {
+            if is_fleet && !adj.fleet_ok {
+                continue;
+            }
+            if !is_fleet && !adj.army_ok {
+                continue;
+            }
+            if coast != Coast::None && adj.from_coast != Coast::None && adj.from_coast != coast {
+                continue;
+            }
+            if !result.contains(&adj.to) {
+                result.push(adj.to);
+            }
+        }
+        result
+    }
+
+    /// Whether `prov` is a supply center.
+    #[inline]
+    pub fn is_supply_center(&self, prov: Province) -> bool {
+        self.supply_center[prov.index()]
+    }
+
+    /// The power `prov` is a home center of, if any.
+    #[inline]
+    pub fn home_power(&self, prov: Province) -> Option<Power> {
+        self.home[prov.index()]
+    }
+
+    /// Number of supply centers.
+    pub fn supply_center_count(&self) -> usize {
+        self.supply_center.iter().filter(|&&sc| sc).count()
+    }
+
+    /// Supply centers needed for a solo victory: a majority of them, 18 on
+    /// the standard map.
+    pub fn solo_centers(&self) -> usize {
+        self.supply_center_count() / 2 + 1
+    }
+
+    /// Width and height of the space the map's provinces are placed in,
+    /// if it places them.
+    pub fn viewbox(&self) -> Option<[u32; 2]> {
+        self.viewbox
+    }
+
+    /// Where `prov` is drawn, if the map places it.
+    #[inline]
+    pub fn geometry(&self, prov: Province) -> Option<ProvinceGeometry> {
+        self.geometry[prov.index()]
+    }
+
+    /// Army move distances between provinces.
+    pub(crate) fn army_dist(&self) -> &DistMatrix {
+        self.army_dist
+            .get_or_init(|| DistMatrix::build(self, false))
+    }
+
+    /// Fleet move distances between provinces.
+    pub(crate) fn fleet_dist(&self) -> &DistMatrix {
+        self.fleet_dist
+            .get_or_init(|| DistMatrix::build(self, true))
+    }
+}
+
+static STANDARD: LazyLock<Arc<GameMap>> = LazyLock::new(|| {
+    let info = &PROVINCE_INFO;
+    Arc::new(GameMap::new(
+        "standard".to_string(),
+        (0..MAX_PROVINCES)
+            .map(|i| {
+                info.get(i).map(|p| MapProvince {
+                    abbr: p.abbr.to_string(),
+                    name: p.name.to_string(),
+                    kind: p.province_type,
+                    coasts: p.coasts.to_vec(),
+                })
+            })
+            .collect(),
+        ADJACENCIES.to_vec(),
+        std::array::from_fn(|i| info.get(i).is_some_and(|p| p.is_supply_center)),
+        std::array::from_fn(|i| info.get(i).and_then(|p| p.home_power)),
+        super::province::ALL_POWERS.to_vec(),
+        Some(VIEWBOX),
+        std::array::from_fn(|i| GEOMETRY.get(i).copied()),
+    ))
+});
+
+/// Maps are told apart by name in debug output; the tables are too long to
+/// print with every position.
+impl fmt::Debug for GameMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GameMap").field(&self.name).finish()
+    }
+}
+
+/// Two maps are equal when they are the same map or have the same name,
+/// provinces, powers, centers and adjacencies.
+impl PartialEq for GameMap {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+            || (self.name == other.name
+                && self.info == other.info
+                && self.powers == other.powers
+                && self.supply_center == other.supply_center
+                && self.home == other.home
+                && self.entries == other.entries)
+    }
+}
+
+impl Eq for GameMap {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::mapdef::{AdjacencyDef, MapDef, ProvinceDef};
+    use crate::board::province::{ALL_PROVINCES, PROVINCE_COUNT};
+
+    #[test]
+    fn standard_map_matches_the_compiled_tables() {
+        let map = GameMap::standard();
+        assert_eq!(map.supply_center_count(), 34);
+        assert_eq!(map.solo_centers(), 18);
+        assert_eq!(map.home_power(Province::Vie), Some(Power::Austria));
+        assert!(map
+            .adj_from(Province::Vie)
+            .iter()
+            .any(|a| a.to == Province::Boh));
+        assert_eq!(map.army_dist().distance(Province::Vie, Province::Mun), 2);
+
+        let rebuilt = GameMap::from_def(&MapDef::standard()).unwrap();
+        for &prov in ALL_PROVINCES.iter() {
+            assert_eq!(rebuilt.adj_from(prov).len(), map.adj_from(prov).len());
+            assert_eq!(rebuilt.is_supply_center(prov), map.is_supply_center(prov));
+            assert_eq!(rebuilt.home_power(prov), map.home_power(prov));
+            assert_eq!(rebuilt.geometry(prov), map.geometry(prov));
+        }
+        assert_eq!(rebuilt.viewbox(), Some(VIEWBOX));
+        assert_eq!(
+            map.geometry(Province::Vie).unwrap().centroid,
+            GEOMETRY[Province::Vie.index()].centroid
+        );
+    }
+
+    #[test]
+    fn variant_can_move_centers_and_add_edges() {
+        let mut def = MapDef::standard();
+        def.name = "neutral-tyrolia".to_string();
+        for p in def.provinces.iter_mut().filter(|p| p.id == "tyr") {
+            p.supply_center = true;
+        }
+        let edge = |from: &str, to: &str| AdjacencyDef {
+            from: from.to_string(),
+            from_coast: None,
+            to: to.to_string(),
+            to_coast: None,
+            army: true,
+            fleet: false,
+        };
+        def.adjacencies.push(edge("vie", "ven"));
+        def.adjacencies.push(edge("ven", "vie"));
+
+        let map = GameMap::from_def(&def).unwrap();
+        assert_eq!(map.name(), "neutral-tyrolia");
+        assert_eq!(map.supply_center_count(), 35);
+        assert_eq!(map.solo_centers(), 18);
+        assert!(map.is_supply_center(Province::Tyr));
+        assert_eq!(map.army_dist().distance(Province::Vie, Province::Ven), 1);
+        assert!(map
+            .army_dist()
+            .sc_indices
+            .contains(&(Province::Tyr.index() as u8)));
+
+        // A label anchor defaults to the centroid.
+        let mut def = MapDef::standard();
+        for p in def.provinces.iter_mut() {
+            p.label = None;
+        }
+        let map = GameMap::from_def(&def).unwrap();
+        let vie = map.geometry(Province::Vie).unwrap();
+        assert_eq!(vie.label, vie.centroid);
+    }
+
+    fn province(id: &str, name: &str) -> ProvinceDef {
+        ProvinceDef {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind: "land".to_string(),
+            supply_center: false,
+            home: None,
+            coasts: Vec::new(),
+            centroid: Some([10, 10]),
+            label: None,
+        }
+    }
+
+    fn border(def: &mut MapDef, a: &str, b: &str) {
+        for (from, to) in [(a, b), (b, a)] {
+            def.adjacencies.push(AdjacencyDef {
+                from: from.to_string(),
+                from_coast: None,
+                to: to.to_string(),
+                to_coast: None,
+                army: true,
+                fleet: false,
+            });
+        }
+    }
+
+    #[test]
+    fn provinces_come_from_the_definition() {
+        let mut def = MapDef::standard();
+        def.provinces
+            .iter_mut()
+            .find(|p| p.id == "boh")
+            .unwrap()
+            .name = "Bohemia-Moravia".to_string();
+        def.provinces.iter_mut().find(|p| p.id == "tyr").unwrap().id = "tra".to_string();
+        for a in def.adjacencies.iter_mut() {
+            for id in [&mut a.from, &mut a.to] {
+                if id == "tyr" {
+                    *id = "tra".to_string();
+                }
+            }
+        }
+        def.provinces.push(province("swi", "Switzerland"));
+        for to in ["mar", "pie", "mun", "bur"] {
+            border(&mut def, "swi", to);
+        }
+
+        let map = GameMap::from_def(&def).unwrap();
+        assert_eq!(map.provinces().len(), 76);
+        assert_eq!(map.province("tyr"), None);
+        assert_eq!(map.province_name(Province::Boh), "Bohemia-Moravia");
+        // The map's own provinces take the lowest free indices.
+        let tra = map.province("tra").unwrap();
+        let swi = map.province("swi").unwrap();
+        assert_eq!(tra, Province::Tyr);
+        assert_eq!(map.abbr(tra), "tra");
+        assert_eq!(swi.index(), PROVINCE_COUNT);
+        assert_eq!(map.abbr(swi), "swi");
+        assert_eq!(map.province_type(swi), ProvinceType::Land);
+        assert!(!map.has_coasts(swi));
+        assert!(map.is_adjacent(Province::Mun, Coast::None, swi, Coast::None, false));
+        assert_eq!(map.army_dist().distance(Province::Mar, Province::Mun), 2);
+        assert_eq!(map.army_dist().distance(tra, Province::Vie), 1);
+        // Standard provinces keep their index.
+        assert_eq!(map.province("vie"), Some(Province::Vie));
+    }
+
+    #[test]
+    fn builtin_maps_load() {
+        for (name, _) in BUILTIN_MAPS {
+            let map = GameMap::builtin(name).unwrap();
+            assert_eq!(map.name(), *name);
+        }
+        let pure = GameMap::builtin("pure").unwrap();
+        assert_eq!(pure.provinces().len(), 7);
+        assert_eq!(pure.supply_center_count(), 7);
+        assert_eq!(pure.solo_centers(), 4);
+        assert_eq!(pure.province_type(Province::Lon), ProvinceType::Land);
+        assert_eq!(pure.army_dist().distance(Province::Lon, Province::Con), 1);
+        assert!(GameMap::builtin("standard").unwrap().is_standard());
+        assert!(GameMap::builtin("atlantis").is_none());
+    }
+
+    #[test]
+    fn rejects_maps_the_board_cannot_hold() {
+        let mut def = MapDef::standard();
+        let mut prev = "boh".to_string();
+        for i in 0..=(MAX_PROVINCES - PROVINCE_COUNT) {
+            let id = format!("xa{}", (b'a' + i as u8) as char);
+            def.provinces.push(province(&id, "Extra"));
+            border(&mut def, &prev, &id);
+            prev = id;
+        }
+        assert!(check_map(&def).is_empty());
+        assert!(matches!(
+            GameMap::from_def(&def),
+            Err(MapError::TooManyProvinces(n)) if n == MAX_PROVINCES + 1
+        ));
+
+        let mut def = MapDef::standard();
+        for name in def.powers.iter_mut() {
+            if name == "turkey" {
+                *name = "atlantis".to_string();
+            }
+        }
+        for p in def.provinces.iter_mut() {
+            if p.home.as_deref() == Some("turkey") {
+                p.home = Some("atlantis".to_string());
+            }
+        }
+        assert!(matches!(
+            GameMap::from_def(&def),
+            Err(MapError::UnknownPower(name)) if name == "atlantis"
+        ));
+
+        let mut def = MapDef::standard();
+        def.adjacencies.pop();
+        assert!(matches!(GameMap::from_def(&def), Err(MapError::Invalid(_))));
+    }
+}
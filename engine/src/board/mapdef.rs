@@ -0,0 +1,687 @@
+//! Map definitions as data, and the checks behind `realpolitik checkmap`.
+//!
+//! The engine plays the standard map compiled into `province` and
+//! `adjacency`. Variant authors describe a map as JSON in the same shape
+//! (provinces with terrain, supply centers, home powers and split coasts;
+//! directed adjacencies with army/fleet flags) and run [`check_map`] on it
+//! before anything tries to play on it. The checks mirror the invariants
+//! the standard tables are tested for: symmetric adjacencies with matching
+//! coasts, terrain-consistent army and fleet edges, home centers that are
+//! supply centers, and every province reachable.
+//!
+//! A definition may also place its provinces for drawing: a `viewbox` on
+//! the map, and a `centroid` (plus an optional `label` anchor, which
+//! defaults to the centroid) on every province, all in viewbox units.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::adjacency::ADJACENCIES;
+use super::geometry::{GEOMETRY, VIEWBOX};
+use super::province::{Coast, ProvinceType, ALL_POWERS, PROVINCE_INFO};
+
+/// A province in a map definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvinceDef {
+    /// Three-letter lowercase ID used in DFEN and DSON.
+    pub id: String,
+    pub name: String,
+    /// `land`, `sea`, or `coastal`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub supply_center: bool,
+    /// Power whose home center this is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home: Option<String>,
+    /// Split coasts (`nc`, `sc`, `ec`); empty for ordinary provinces.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coasts: Vec<String>,
+    /// Where the province's unit is drawn, in viewbox units.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub centroid: Option<[u32; 2]>,
+    /// Where the province's name is drawn; the centroid when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<[u32; 2]>,
+}
+
+/// A directed adjacency in a map definition. Every edge must also be listed
+/// in reverse.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdjacencyDef {
+    pub from: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_coast: Option<String>,
+    pub to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_coast: Option<String>,
+    #[serde(default)]
+    pub army: bool,
+    #[serde(default)]
+    pub fleet: bool,
+}
+
+/// A complete map definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapDef {
+    pub name: String,
+    /// Width and height of the space province coordinates are given in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewbox: Option<[u32; 2]>,
+    /// Lowercase power names.
+    pub powers: Vec<String>,
+    pub provinces: Vec<ProvinceDef>,
+    pub adjacencies: Vec<AdjacencyDef>,
+}
+
+/// A problem found by [`check_map`]. Messages say what to change.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum MapIssue {
+    #[error("map defines no powers")]
+    NoPowers,
+
+    #[error("power '{0}' is listed twice in powers")]
+    DuplicatePower(String),
+
+    #[error("province id '{0}' must be exactly three lowercase letters")]
+    BadProvinceId(String),
+
+    #[error("province '{0}' is defined more than once")]
+    DuplicateProvince(String),
+
+    #[error("province '{province}' has unknown type '{kind}'; use land, sea, or coastal")]
+    UnknownType { province: String, kind: String },
+
+    #[error("province '{province}' has unknown coast '{coast}'; use nc, sc, or ec")]
+    UnknownCoast { province: String, coast: String },
+
+    #[error("province '{0}' lists split coasts but is not coastal")]
+    CoastsOnNonCoastal(String),
+
+    #[error("province '{0}' lists a single split coast; list two or more, or none")]
+    SingleCoast(String),
+
+    #[error("province '{0}' is a sea but marked as a supply center")]
+    SeaSupplyCenter(String),
+
+    #[error("province '{province}' is home to '{power}', which is not in powers")]
+    UnknownHomePower { province: String, power: String },
+
+    #[error("province '{0}' is a home center but not a supply center; set supply_center")]
+    HomeNotSupplyCenter(String),
+
+    #[error("power '{0}' has no home centers; set home on at least one supply center")]
+    NoHomeCenters(String),
+
+    #[error("province '{0}' has coordinates but the map sets no viewbox")]
+    CoordinatesWithoutViewbox(String),
+
+    #[error("province '{0}' has no centroid; a map with a viewbox must place every province")]
+    MissingCentroid(String),
+
+    #[error("province '{0}' is placed outside the viewbox")]
+    OutsideViewbox(String),
+
+    #[error("adjacency {from} -> {to} names unknown province '{missing}'")]
+    UnknownAdjacencyProvince {
+        from: String,
+        to: String,
+        missing: String,
+    },
+
+    #[error("adjacency {0} -> {0} connects a province to itself")]
+    SelfAdjacency(String),
+
+    #[error("adjacency {from} -> {to} allows neither army nor fleet")]
+    EmptyAdjacency { from: String, to: String },
+
+    #[error("adjacency {from} -> {to} is listed more than once")]
+    DuplicateAdjacency { from: String, to: String },
+
+    #[error("adjacency {from} -> {to} has no matching reverse entry; add {to} -> {from} with swapped coasts and the same army/fleet flags")]
+    MissingReverse { from: String, to: String },
+
+    #[error("adjacency {from} -> {to} allows armies but touches sea province '{sea}'")]
+    ArmyAtSea {
+        from: String,
+        to: String,
+        sea: String,
+    },
+
+    #[error("adjacency {from} -> {to} allows fleets but touches land province '{land}'")]
+    FleetOnLand {
+        from: String,
+        to: String,
+        land: String,
+    },
+
+    #[error("adjacency {from} -> {to}: coast '{coast}' is not a split coast of '{province}'")]
+    BadAdjacencyCoast {
+        from: String,
+        to: String,
+        province: String,
+        coast: String,
+    },
+
+    #[error("adjacency {from} -> {to}: fleets entering or leaving split-coast '{province}' must name a coast")]
+    MissingAdjacencyCoast {
+        from: String,
+        to: String,
+        province: String,
+    },
+
+    #[error("province '{0}' has no adjacencies")]
+    Isolated(String),
+
+    #[error("province '{province}' cannot be reached by any {unit} starting from a home center")]
+    Unreachable {
+        province: String,
+        unit: &'static str,
+    },
+}
+
+impl MapDef {
+    /// Returns the built-in standard map as a definition, a template for
+    /// variant authors and a reference the checks must accept.
+    pub fn standard() -> Self {
+        let coast_name = |c: Coast| (c != Coast::None).then(|| c.abbr().to_string());
+        MapDef {
+            name: "standard".to_string(),
+            viewbox: Some(VIEWBOX),
+            powers: ALL_POWERS.iter().map(|p| p.name().to_string()).collect(),
+            provinces: PROVINCE_INFO
+                .iter()
+                .zip(GEOMETRY.iter())
+                .map(|(info, geometry)| ProvinceDef {
+                    id: info.abbr.to_string(),
+                    name: info.name.to_string(),
+                    kind: match info.province_type {
+                        ProvinceType::Land => "land",
+                        ProvinceType::Sea => "sea",
+                        ProvinceType::Coastal => "coastal",
+                    }
+                    .to_string(),
+                    supply_center: info.is_supply_center,
+                    home: info.home_power.map(|p| p.name().to_string()),
+                    coasts: info.coasts.iter().map(|c| c.abbr().to_string()).collect(),
+                    centroid: Some(geometry.centroid),
+                    label: Some(geometry.label),
+                })
+                .collect(),
+            adjacencies: ADJACENCIES
+                .iter()
+                .map(|a| AdjacencyDef {
+                    from: a.from.abbr().to_string(),
+                    from_coast: coast_name(a.from_coast),
+                    to: a.to.abbr().to_string(),
+                    to_coast: coast_name(a.to_coast),
+                    army: a.army_ok,
+                    fleet: a.fleet_ok,
+                })
+                .collect(),
+        }
+    }
+
+    /// Number of supply centers the map defines.
+    pub fn supply_center_count(&self) -> usize {
+        self.provinces.iter().filter(|p| p.supply_center).count()
+    }
+}
+
+/// Checks a map definition, returning every problem found (empty if the map
+/// is sound). Problems are reported in definition order: powers, provinces,
+/// adjacencies, then reachability.
+pub fn check_map(def: &MapDef) -> Vec<MapIssue> {
+    let mut issues = Vec::new();
+
+    if def.powers.is_empty() {
+        issues.push(MapIssue::NoPowers);
+    }
+    let mut powers = HashSet::new();
+    for power in &def.powers {
+        if !powers.insert(power.as_str()) {
+            issues.push(MapIssue::DuplicatePower(power.clone()));
+        }
+    }
+
+    // Provinces by id; terrain is None for an unknown type.
+    let mut provinces: HashMap<&str, (&ProvinceDef, Option<ProvinceType>)> = HashMap::new();
+    let mut homes: HashSet<&str> = HashSet::new();
+    for p in &def.provinces {
+        let id = p.id.as_str();
+        if id.len() != 3 || !id.bytes().all(|b| b.is_ascii_lowercase()) {
+            issues.push(MapIssue::BadProvinceId(p.id.clone()));
+        }
+        let kind = match p.kind.as_str() {
+            "land" => Some(ProvinceType::Land),
+            "sea" => Some(ProvinceType::Sea),
+            "coastal" => Some(ProvinceType::Coastal),
+            _ => {
+                issues.push(MapIssue::UnknownType {
+                    province: p.id.clone(),
+                    kind: p.kind.clone(),
+                });
+                None
+            }
+        };
+        if provinces.insert(id, (p, kind)).is_some() {
+            issues.push(MapIssue::DuplicateProvince(p.id.clone()));
+        }
+        for coast in &p.coasts {
+            if !matches!(Coast::from_abbr(coast), Some(c) if c != Coast::None) {
+                issues.push(MapIssue::UnknownCoast {
+                    province: p.id.clone(),
+                    coast: coast.clone(),
+                });
+            }
+        }
+        if !p.coasts.is_empty() && kind.is_some_and(|k| k != ProvinceType::Coastal) {
+            issues.push(MapIssue::CoastsOnNonCoastal(p.id.clone()));
+        }
+        if p.coasts.len() == 1 {
+            issues.push(MapIssue::SingleCoast(p.id.clone()));
+        }
+        if p.supply_center && kind == Some(ProvinceType::Sea) {
+            issues.push(MapIssue::SeaSupplyCenter(p.id.clone()));
+        }
+        if let Some(home) = &p.home {
+            if !powers.contains(home.as_str()) {
+                issues.push(MapIssue::UnknownHomePower {
+                    province: p.id.clone(),
+                    power: home.clone(),
+                });
+            }
+            if !p.supply_center {
+                issues.push(MapIssue::HomeNotSupplyCenter(p.id.clone()));
+            }
+            homes.insert(home.as_str());
+        }
+        match (def.viewbox, p.centroid) {
+            (None, Some(_)) => issues.push(MapIssue::CoordinatesWithoutViewbox(p.id.clone())),
+            (None, None) if p.label.is_some() => {
+                issues.push(MapIssue::CoordinatesWithoutViewbox(p.id.clone()))
+            }
+            (Some(_), None) => issues.push(MapIssue::MissingCentroid(p.id.clone())),
+            (Some([width, height]), Some(centroid)) => {
+                let inside = |[x, y]: [u32; 2]| x <= width && y <= height;
+                if !inside(centroid) || !p.label.is_none_or(inside) {
+                    issues.push(MapIssue::OutsideViewbox(p.id.clone()));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    for power in &def.powers {
+        if !homes.contains(power.as_str()) {
+            issues.push(MapIssue::NoHomeCenters(power.clone()));
+        }
+    }
+
+    check_adjacencies(def, &provinces, &mut issues);
+    check_reachability(def, &provinces, &mut issues);
+    issues
+}
+
+type ProvinceTable<'a> = HashMap<&'a str, (&'a ProvinceDef, Option<ProvinceType>)>;
+
+fn check_adjacencies(def: &MapDef, provinces: &ProvinceTable, issues: &mut Vec<MapIssue>) {
+    let key = |a: &AdjacencyDef| {
+        (
+            a.from.clone(),
+            a.from_coast.clone(),
+            a.to.clone(),
+            a.to_coast.clone(),
+        )
+    };
+    let mut seen = HashMap::new();
+    for a in &def.adjacencies {
+        if seen.insert(key(a), (a.army, a.fleet)).is_some() {
+            issues.push(MapIssue::DuplicateAdjacency {
+                from: a.from.clone(),
+                to: a.to.clone(),
+            });
+        }
+    }
+
+    for a in &def.adjacencies {
+        let (from, to) = (a.from.clone(), a.to.clone());
+        let ends = [(&a.from, &a.from_coast), (&a.to, &a.to_coast)];
+        let mut known = true;
+        for (id, _) in ends {
+            if !provinces.contains_key(id.as_str()) {
+                issues.push(MapIssue::UnknownAdjacencyProvince {
+                    from: from.clone(),
+                    to: to.clone(),
+                    missing: id.clone(),
+                });
+                known = false;
+            }
+        }
+        if a.from == a.to {
+            issues.push(MapIssue::SelfAdjacency(from.clone()));
+        }
+        if !a.army && !a.fleet {
+            issues.push(MapIssue::EmptyAdjacency {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+        let reverse = (
+            a.to.clone(),
+            a.to_coast.clone(),
+            a.from.clone(),
+            a.from_coast.clone(),
+        );
+        if seen.get(&reverse) != Some(&(a.army, a.fleet)) {
+            issues.push(MapIssue::MissingReverse {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+        if !known {
+            continue;
+        }
+
+        for (id, coast) in ends {
+            let (p, kind) = provinces[id.as_str()];
+            if a.army && kind == Some(ProvinceType::Sea) {
+                issues.push(MapIssue::ArmyAtSea {
+                    from: from.clone(),
+                    to: to.clone(),
+                    sea: id.clone(),
+                });
+            }
+            if a.fleet && kind == Some(ProvinceType::Land) {
+                issues.push(MapIssue::FleetOnLand {
+                    from: from.clone(),
+                    to: to.clone(),
+                    land: id.clone(),
+                });
+            }
+            match coast {
+                Some(c) if !p.coasts.contains(c) || a.army => {
+                    issues.push(MapIssue::BadAdjacencyCoast {
+                        from: from.clone(),
+                        to: to.clone(),
+                        province: id.clone(),
+                        coast: c.clone(),
+                    })
+                }
+                None if a.fleet && !p.coasts.is_empty() => {
+                    issues.push(MapIssue::MissingAdjacencyCoast {
+                        from: from.clone(),
+                        to: to.clone(),
+                        province: id.clone(),
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Flags isolated provinces, and provinces no army (moving overland or by
+/// convoy) or fleet can reach from any home center.
+fn check_reachability(def: &MapDef, provinces: &ProvinceTable, issues: &mut Vec<MapIssue>) {
+    let mut army_edges: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut fleet_edges: HashMap<&str, Vec<&str>> = HashMap::new();
+    for a in &def.adjacencies {
+        if !provinces.contains_key(a.from.as_str()) || !provinces.contains_key(a.to.as_str()) {
+            continue;
+        }
+        if a.army {
+            army_edges.entry(&a.from).or_default().push(&a.to);
+        }
+        if a.fleet {
+            fleet_edges.entry(&a.from).or_default().push(&a.to);
+        }
+    }
+
+    let kind_of = |id: &str| provinces.get(id).and_then(|(_, k)| *k);
+
+    let homes: Vec<&str> = def
+        .provinces
+        .iter()
+        .filter(|p| p.home.is_some())
+        .map(|p| p.id.as_str())
+        .collect();
+
+    // Armies move overland and cross seas by convoy: a coastal province can
+    // embark into an adjacent sea, and a sea chain can land on any coast.
+    let mut army_seen: HashSet<&str> = homes
+        .iter()
+        .copied()
+        .filter(|id| kind_of(id) != Some(ProvinceType::Sea))
+        .collect();
+    let mut queue: VecDeque<&str> = army_seen.iter().copied().collect();
+    while let Some(id) = queue.pop_front() {
+        let at_sea = kind_of(id) == Some(ProvinceType::Sea);
+        let mut next: Vec<&str> = Vec::new();
+        if !at_sea {
+            next.extend(army_edges.get(id).into_iter().flatten());
+        }
+        if at_sea || kind_of(id) == Some(ProvinceType::Coastal) {
+            next.extend(fleet_edges.get(id).into_iter().flatten());
+        }
+        for n in next {
+            if army_seen.insert(n) {
+                queue.push_back(n);
+            }
+        }
+    }
+
+    // Fleets start from coastal home centers.
+    let mut fleet_seen: HashSet<&str> = homes
+        .iter()
+        .copied()
+        .filter(|id| kind_of(id) == Some(ProvinceType::Coastal))
+        .collect();
+    let mut queue: VecDeque<&str> = fleet_seen.iter().copied().collect();
+    while let Some(id) = queue.pop_front() {
+        for &n in fleet_edges.get(id).into_iter().flatten() {
+            if fleet_seen.insert(n) {
+                queue.push_back(n);
+            }
+        }
+    }
+
+    let mut isolated = HashSet::new();
+    for p in &def.provinces {
+        let id = p.id.as_str();
+        if !army_edges.contains_key(id) && !fleet_edges.contains_key(id) {
+            isolated.insert(id);
+        }
+    }
+    let mut reported = HashSet::new();
+    for p in &def.provinces {
+        let id = p.id.as_str();
+        // A duplicated province is already reported once.
+        if !reported.insert(id) {
+            continue;
+        }
+        if isolated.contains(id) {
+            issues.push(MapIssue::Isolated(p.id.clone()));
+            continue;
+        }
+        // Nothing sensible to say about reachability without homes.
+        if homes.is_empty() {
+            continue;
+        }
+        let (unit, reached) = match kind_of(id) {
+            Some(ProvinceType::Sea) => ("fleet", fleet_seen.contains(id)),
+            Some(_) => ("army", army_seen.contains(id)),
+            None => continue,
+        };
+        if !reached {
+            issues.push(MapIssue::Unreachable {
+                province: p.id.clone(),
+                unit,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(def: &'a mut MapDef, id: &str) -> &'a mut ProvinceDef {
+        def.provinces.iter_mut().find(|p| p.id == id).unwrap()
+    }
+
+    #[test]
+    fn standard_map_passes_and_roundtrips_through_json() {
+        let def = MapDef::standard();
+        assert_eq!(check_map(&def), Vec::new());
+        assert_eq!(def.supply_center_count(), 34);
+        let json = serde_json::to_string(&def).unwrap();
+        let back: MapDef = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, def);
+    }
+
+    #[test]
+    fn province_and_home_errors_are_reported() {
+        let mut def = MapDef::standard();
+        find(&mut def, "vie").supply_center = false;
+        find(&mut def, "nth").supply_center = true;
+        find(&mut def, "boh").kind = "mountain".to_string();
+        find(&mut def, "spa").coasts = vec!["nc".to_string()];
+        for p in def.provinces.iter_mut() {
+            if p.home.as_deref() == Some("turkey") {
+                p.home = None;
+            }
+        }
+        let issues = check_map(&def);
+        assert!(issues.contains(&MapIssue::HomeNotSupplyCenter("vie".into())));
+        assert!(issues.contains(&MapIssue::SeaSupplyCenter("nth".into())));
+        assert!(issues.contains(&MapIssue::UnknownType {
+            province: "boh".into(),
+            kind: "mountain".into()
+        }));
+        assert!(issues.contains(&MapIssue::SingleCoast("spa".into())));
+        assert!(issues.contains(&MapIssue::NoHomeCenters("turkey".into())));
+    }
+
+    #[test]
+    fn coordinate_errors_are_reported() {
+        let mut def = MapDef::standard();
+        find(&mut def, "vie").centroid = None;
+        find(&mut def, "bud").label = None;
+        find(&mut def, "tri").label = Some([2000, 800]);
+        let issues = check_map(&def);
+        assert_eq!(
+            issues,
+            vec![
+                MapIssue::OutsideViewbox("tri".into()),
+                MapIssue::MissingCentroid("vie".into()),
+            ]
+        );
+
+        // Coordinates are optional, but only as a whole.
+        let mut def = MapDef::standard();
+        def.viewbox = None;
+        for p in def.provinces.iter_mut() {
+            p.centroid = None;
+            p.label = None;
+        }
+        assert_eq!(check_map(&def), Vec::new());
+        find(&mut def, "vie").label = Some([500, 700]);
+        assert_eq!(
+            check_map(&def),
+            vec![MapIssue::CoordinatesWithoutViewbox("vie".into())]
+        );
+    }
+
+    #[test]
+    fn adjacency_errors_are_reported() {
+        let mut def = MapDef::standard();
+        // Drop vie -> boh, keeping boh -> vie.
+        def.adjacencies
+            .retain(|a| !(a.from == "vie" && a.to == "boh"));
+        def.adjacencies.push(AdjacencyDef {
+            from: "mun".into(),
+            from_coast: None,
+            to: "xyz".into(),
+            to_coast: None,
+            army: true,
+            fleet: false,
+        });
+        def.adjacencies.push(AdjacencyDef {
+            from: "mao".into(),
+            from_coast: None,
+            to: "spa".into(),
+            to_coast: None,
+            army: true,
+            fleet: true,
+        });
+        let issues = check_map(&def);
+        assert!(issues.contains(&MapIssue::MissingReverse {
+            from: "boh".into(),
+            to: "vie".into()
+        }));
+        assert!(issues.contains(&MapIssue::UnknownAdjacencyProvince {
+            from: "mun".into(),
+            to: "xyz".into(),
+            missing: "xyz".into()
+        }));
+        assert!(issues.contains(&MapIssue::ArmyAtSea {
+            from: "mao".into(),
+            to: "spa".into(),
+            sea: "mao".into()
+        }));
+        assert!(issues.contains(&MapIssue::MissingAdjacencyCoast {
+            from: "mao".into(),
+            to: "spa".into(),
+            province: "spa".into()
+        }));
+    }
+
+    #[test]
+    fn unreachable_and_isolated_provinces_are_reported() {
+        let mut def = MapDef::standard();
+        // Drop every edge touching Syria.
+        def.adjacencies.retain(|a| a.from != "syr" && a.to != "syr");
+        let issues = check_map(&def);
+        assert!(issues.contains(&MapIssue::Isolated("syr".into())));
+
+        let mut def = MapDef::standard();
+        def.provinces.push(ProvinceDef {
+            id: "atl".into(),
+            name: "Atlantis".into(),
+            kind: "land".into(),
+            supply_center: false,
+            home: None,
+            coasts: Vec::new(),
+            centroid: Some([10, 10]),
+            label: None,
+        });
+        def.provinces.push(ProvinceDef {
+            id: "mu".into(),
+            name: "Mu".into(),
+            kind: "land".into(),
+            supply_center: false,
+            home: None,
+            coasts: Vec::new(),
+            centroid: Some([10, 10]),
+            label: None,
+        });
+        for (from, to) in [("atl", "mu"), ("mu", "atl")] {
+            def.adjacencies.push(AdjacencyDef {
+                from: from.into(),
+                from_coast: None,
+                to: to.into(),
+                to_coast: None,
+                army: true,
+                fleet: false,
+            });
+        }
+        let issues = check_map(&def);
+        assert!(issues.contains(&MapIssue::BadProvinceId("mu".into())));
+        assert!(issues.contains(&MapIssue::Unreachable {
+            province: "atl".into(),
+            unit: "army"
+        }));
+    }
+}
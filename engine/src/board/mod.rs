@@ -4,19 +4,22 @@
 //! orders, and the overall game state.
 
 pub mod adjacency;
+pub mod geometry;
+pub mod map;
+pub mod mapdef;
 pub mod order;
 pub mod province;
 pub mod state;
 pub mod unit;
+pub mod zobrist;
 
-pub use adjacency::{
-    adj_from, fleet_coasts_to, is_adjacent, is_adjacent_fast, provinces_adjacent_to,
-    AdjacencyEntry, ADJACENCIES, ADJACENCY_COUNT,
-};
-pub use order::{Location, Order, OrderUnit};
+pub use adjacency::{AdjacencyEntry, ADJACENCIES, ADJACENCY_COUNT};
+pub use geometry::{ProvinceGeometry, GEOMETRY, VIEWBOX};
+pub use map::GameMap;
+pub use order::{canonical_orders, orders_equal, orders_hash, Location, Order, OrderUnit};
 pub use province::{
-    Coast, Power, Province, ProvinceInfo, ProvinceType, ALL_POWERS, ALL_PROVINCES, PROVINCE_COUNT,
-    PROVINCE_INFO, SUPPLY_CENTER_COUNT,
+    Coast, Power, Province, ProvinceInfo, ProvinceType, ALL_POWERS, ALL_PROVINCES, MAX_PROVINCES,
+    PROVINCE_COUNT, PROVINCE_INFO, SUPPLY_CENTER_COUNT,
 };
-pub use state::{BoardState, DislodgedUnit, Phase, Season};
+pub use state::{BoardState, Delta, DislodgedUnit, Phase, Season, Undo};
 pub use unit::{Unit, UnitPosition, UnitType};
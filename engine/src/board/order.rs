@@ -4,8 +4,14 @@
 //! retreat, disband, build, and waive. The data model maps directly to
 //! DSON (Diplomacy Standard Order Notation) for straightforward parsing
 //! and formatting.
+//!
+//! Order sets are compared and hashed canonically: the order in which a
+//! set's entries are listed never matters.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
-use super::province::{Coast, Province};
+use super::province::{Coast, Power, Province};
 use super::unit::UnitType;
 
 /// A location on the board: a province with an optional coast specifier.
@@ -46,8 +52,17 @@ pub enum Order {
     /// Hold: `A vie H`
     Hold { unit: OrderUnit },
 
-    /// Move: `A bud - rum` or `F nrg - stp/nc`
-    Move { unit: OrderUnit, dest: Location },
+    /// Move: `A bud - rum` or `F nrg - stp/nc`; `A lon - bel via convoy`
+    /// when `via_convoy` is set.
+    ///
+    /// `via_convoy` marks an army's explicit intent to travel by convoy, so an
+    /// adjacent move is convoyed rather than moving over land when a convoy
+    /// route exists (DATC 6.G).
+    Move {
+        unit: OrderUnit,
+        dest: Location,
+        via_convoy: bool,
+    },
 
     /// Support hold: `A tyr S A vie H`
     SupportHold {
@@ -82,6 +97,58 @@ pub enum Order {
     Waive,
 }
 
+impl Order {
+    /// Returns the unit the order is given to, or `None` for Waive.
+    pub fn unit(&self) -> Option<OrderUnit> {
+        match *self {
+            Order::Hold { unit }
+            | Order::Move { unit, .. }
+            | Order::SupportHold { unit, .. }
+            | Order::SupportMove { unit, .. }
+            | Order::Convoy { unit, .. }
+            | Order::Retreat { unit, .. }
+            | Order::Disband { unit }
+            | Order::Build { unit } => Some(unit),
+            Order::Waive => None,
+        }
+    }
+}
+
+/// Hashes a single order entry.
+fn entry_hash(order: &Order, power: Power) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    order.hash(&mut hasher);
+    power.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Sort key placing entries by power, then by the ordered unit's province.
+fn entry_key(order: &Order, power: Power) -> (u8, u8, u64) {
+    let prov = order
+        .unit()
+        .map_or(u8::MAX, |u| u.location.province.index() as u8);
+    (power as u8, prov, entry_hash(order, power))
+}
+
+/// Returns the order set sorted into canonical order.
+pub fn canonical_orders(orders: &[(Order, Power)]) -> Vec<(Order, Power)> {
+    let mut sorted = orders.to_vec();
+    sorted.sort_by_key(|(o, p)| entry_key(o, *p));
+    sorted
+}
+
+/// Hashes an order set independently of entry order.
+pub fn orders_hash(orders: &[(Order, Power)]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    canonical_orders(orders).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns true if both sets contain the same entries, in any order.
+pub fn orders_equal(a: &[(Order, Power)], b: &[(Order, Power)]) -> bool {
+    a.len() == b.len() && canonical_orders(a) == canonical_orders(b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,4 +185,38 @@ mod tests {
         let order = Order::Waive;
         assert_eq!(order, Order::Waive);
     }
+
+    fn hold(prov: Province, power: Power) -> (Order, Power) {
+        let unit = OrderUnit {
+            unit_type: UnitType::Army,
+            location: Location::new(prov),
+        };
+        (Order::Hold { unit }, power)
+    }
+
+    #[test]
+    fn order_sets_compare_independent_of_entry_order() {
+        let a = [
+            hold(Province::Vie, Power::Austria),
+            hold(Province::Bud, Power::Austria),
+            hold(Province::Mos, Power::Russia),
+        ];
+        let b = [a[2], a[0], a[1]];
+        assert!(orders_equal(&a, &b));
+        assert_eq!(orders_hash(&a), orders_hash(&b));
+        assert_eq!(canonical_orders(&b), canonical_orders(&a));
+
+        let c = [a[0], a[1], hold(Province::Mos, Power::Turkey)];
+        assert!(!orders_equal(&a, &c));
+        assert_ne!(orders_hash(&a), orders_hash(&c));
+        assert!(!orders_equal(&a, &a[..2]));
+    }
+
+    #[test]
+    fn duplicate_entries_are_not_collapsed() {
+        let waive = (Order::Waive, Power::Russia);
+        assert!(!orders_equal(&[waive], &[waive, waive]));
+        assert_ne!(orders_hash(&[waive]), orders_hash(&[waive, waive]));
+        assert_eq!(Order::Waive.unit(), None);
+    }
 }
@@ -1,22 +1,57 @@
 //! Province definitions and metadata for the standard Diplomacy map.
 //!
-//! All 75 provinces are enumerated in alphabetical order by their 3-letter ID.
-//! Province metadata (name, type, supply center status, home power) is stored
-//! in a compile-time lookup table indexed by the `Province` enum discriminant.
+//! All 75 provinces are constants on [`Province`], in alphabetical order by
+//! their 3-letter ID. Province metadata (name, type, supply center status,
+//! home power) is stored in a compile-time lookup table indexed by
+//! [`Province::index`].
+//!
+//! A province is an index into its map's province table, so other maps can
+//! have provinces of their own (see [`GameMap`](super::map::GameMap)). A
+//! map gives each standard province it keeps its standard index, which
+//! keeps the constants meaningful on it, and its own provinces the indices
+//! left free.
+
+use std::fmt;
 
 /// The number of provinces on the standard Diplomacy map.
 pub const PROVINCE_COUNT: usize = 75;
 
+/// The most provinces a map may have: the standard board and variants of
+/// about its size fit. Per-province arrays are this long.
+pub const MAX_PROVINCES: usize = 96;
+
 /// The number of supply centers on the standard Diplomacy map.
 pub const SUPPLY_CENTER_COUNT: usize = 34;
 
-/// A province on the standard Diplomacy map.
+/// A province: the index of one of its map's provinces.
 ///
-/// Variants are in alphabetical order by 3-letter abbreviation.
-/// The `#[repr(u8)]` attribute enables use as an array index.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[repr(u8)]
-pub enum Province {
+/// The `u8` index makes a province usable as an array index; arrays over
+/// every province of a map are [`MAX_PROVINCES`] long. The standard
+/// provinces are associated constants, in alphabetical order by 3-letter
+/// abbreviation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Province(u8);
+
+/// Declares the standard provinces as constants on [`Province`] and, for
+/// tables and matches naming many of them, in [`standard`].
+macro_rules! standard_provinces {
+    ($($name:ident = $idx:literal,)*) => {
+        #[allow(non_upper_case_globals)]
+        impl Province {
+            $(pub const $name: Province = Province($idx);)*
+        }
+
+        /// The standard provinces as plain constants, for
+        /// `use crate::board::province::standard::*`.
+        #[allow(non_upper_case_globals)]
+        pub mod standard {
+            use super::Province;
+            $(pub const $name: Province = Province::$name;)*
+        }
+    };
+}
+
+standard_provinces! {
     Adr = 0,  // Adriatic Sea
     Aeg = 1,  // Aegean Sea
     Alb = 2,  // Albania
@@ -174,47 +209,65 @@ pub const ALL_PROVINCES: [Province; PROVINCE_COUNT] = [
 ];
 
 impl Province {
-    /// Returns the 3-letter abbreviation for this province.
-    pub const fn abbr(self) -> &'static str {
-        PROVINCE_INFO[self as usize].abbr
+    /// The province at `index`, which must be below [`MAX_PROVINCES`].
+    #[inline]
+    pub const fn from_index(index: usize) -> Province {
+        debug_assert!(index < MAX_PROVINCES);
+        Province(index as u8)
     }
 
-    /// Returns the full display name for this province.
-    pub const fn name(self) -> &'static str {
-        PROVINCE_INFO[self as usize].name
+    /// Index of this province in per-province arrays.
+    #[inline]
+    pub const fn index(self) -> usize {
+        self.0 as usize
     }
 
-    /// Returns the province type (Land, Sea, or Coastal).
-    pub const fn province_type(self) -> ProvinceType {
-        PROVINCE_INFO[self as usize].province_type
+    /// Returns the 3-letter abbreviation of this standard province. Other
+    /// maps name their provinces themselves (see `GameMap::abbr`).
+    pub const fn abbr(self) -> &'static str {
+        PROVINCE_INFO[self.index()].abbr
     }
 
-    /// Returns true if this province is a supply center.
-    pub const fn is_supply_center(self) -> bool {
-        PROVINCE_INFO[self as usize].is_supply_center
+    /// Returns the full display name of this standard province.
+    pub const fn name(self) -> &'static str {
+        PROVINCE_INFO[self.index()].name
     }
 
-    /// Returns the home power for this province, or None if neutral.
-    pub const fn home_power(self) -> Option<Power> {
-        PROVINCE_INFO[self as usize].home_power
+    /// Returns the standard province type (Land, Sea, or Coastal).
+    pub const fn province_type(self) -> ProvinceType {
+        PROVINCE_INFO[self.index()].province_type
     }
 
-    /// Returns the available coasts for split-coast provinces, empty otherwise.
+    /// Returns the available coasts for standard split-coast provinces,
+    /// empty otherwise.
     pub const fn coasts(self) -> &'static [Coast] {
-        PROVINCE_INFO[self as usize].coasts
+        PROVINCE_INFO[self.index()].coasts
     }
 
-    /// Returns true if this province has split coasts.
+    /// Returns true if this standard province has split coasts.
     pub const fn has_coasts(self) -> bool {
-        !PROVINCE_INFO[self as usize].coasts.is_empty()
+        !PROVINCE_INFO[self.index()].coasts.is_empty()
     }
 
-    /// Looks up a province by its 3-letter abbreviation.
+    /// Looks up a standard province by its 3-letter abbreviation.
     pub fn from_abbr(abbr: &str) -> Option<Province> {
         ABBR_TABLE.iter().find(|(a, _)| *a == abbr).map(|(_, p)| *p)
     }
 }
 
+impl fmt::Debug for Province {
+    /// Standard provinces print as their constant, others as their index.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match PROVINCE_INFO.get(self.index()) {
+            Some(info) => {
+                let (first, rest) = info.abbr.split_at(1);
+                write!(f, "{}{}", first.to_ascii_uppercase(), rest)
+            }
+            None => write!(f, "Province({})", self.0),
+        }
+    }
+}
+
 /// Coast specifier for split-coast provinces.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Coast {
@@ -279,6 +332,11 @@ pub const ALL_POWERS: [Power; 7] = [
 ];
 
 impl Power {
+    /// Position of this power in [`ALL_POWERS`], for per-power arrays.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
     /// Returns the lowercase full name of this power.
     pub const fn name(self) -> &'static str {
         match self {
@@ -344,7 +402,7 @@ pub struct ProvinceInfo {
     pub coasts: &'static [Coast],
 }
 
-/// Compile-time lookup table: index by `Province as usize`.
+/// Compile-time lookup table: index by [`Province::index`].
 pub static PROVINCE_INFO: [ProvinceInfo; PROVINCE_COUNT] = [
     // 0: Adr - Adriatic Sea
     ProvinceInfo {
@@ -1116,7 +1174,7 @@ mod tests {
     fn supply_center_count_is_34() {
         let sc_count = ALL_PROVINCES
             .iter()
-            .filter(|p| p.is_supply_center())
+            .filter(|p| PROVINCE_INFO[p.index()].is_supply_center)
             .count();
         assert_eq!(sc_count, SUPPLY_CENTER_COUNT);
     }
@@ -1124,7 +1182,7 @@ mod tests {
     #[test]
     fn province_indices_are_sequential() {
         for (i, p) in ALL_PROVINCES.iter().enumerate() {
-            assert_eq!(*p as usize, i, "Province {:?} has wrong index", p);
+            assert_eq!(p.index(), i, "Province {:?} has wrong index", p);
         }
     }
 
@@ -1176,7 +1234,10 @@ mod tests {
         let count_for = |power: Power| -> usize {
             ALL_PROVINCES
                 .iter()
-                .filter(|p| p.is_supply_center() && p.home_power() == Some(power))
+                .filter(|p| {
+                    let info = &PROVINCE_INFO[p.index()];
+                    info.is_supply_center && info.home_power == Some(power)
+                })
                 .count()
         };
         assert_eq!(count_for(Power::Austria), 3); // bud, tri, vie
@@ -1189,7 +1250,10 @@ mod tests {
 
         let neutral_sc = ALL_PROVINCES
             .iter()
-            .filter(|p| p.is_supply_center() && p.home_power().is_none())
+            .filter(|p| {
+                let info = &PROVINCE_INFO[p.index()];
+                info.is_supply_center && info.home_power.is_none()
+            })
             .count();
         assert_eq!(neutral_sc, 12);
     }
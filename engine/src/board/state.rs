@@ -2,9 +2,21 @@
 //!
 //! Holds the complete snapshot of a Diplomacy game at a given point in time,
 //! including unit positions, supply-center ownership, phase, season, and year.
+//!
+//! Search plays many order sets against the same position. Instead of
+//! copying the state for each, it can describe a phase's outcome as a
+//! [`Delta`], apply it in place with [`BoardState::apply`], and put the
+//! position back with [`BoardState::undo`], touching only the provinces
+//! that changed.
+
+use std::sync::Arc;
+
+use smallvec::SmallVec;
 
-use super::province::{Coast, Power, Province, PROVINCE_COUNT};
+use super::map::GameMap;
+use super::province::{Coast, Power, Province, MAX_PROVINCES};
 use super::unit::UnitType;
+use super::zobrist;
 
 /// The season of a game turn.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -72,37 +84,111 @@ pub struct DislodgedUnit {
 
 /// Complete board state at a point in time.
 ///
-/// Uses fixed-size arrays indexed by `Province as usize` for O(1) lookup.
-/// This avoids heap allocation and makes the state trivially copyable.
+/// Uses fixed-size arrays indexed by `Province::index` for O(1) lookup.
+/// This keeps cloning cheap: the arrays are copied and the map is shared.
+///
+/// The state carries its Zobrist hash (see `board::zobrist`), updated by
+/// the setters below. Change the turn, units, fleet coasts and supply
+/// center owners through them, not by writing the fields, or the hash goes
+/// stale.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BoardState {
     pub year: u16,
     pub season: Season,
     pub phase: Phase,
     /// Unit at each province: Some((power, unit_type)) or None.
-    pub units: [Option<(Power, UnitType)>; PROVINCE_COUNT],
+    pub units: [Option<(Power, UnitType)>; MAX_PROVINCES],
     /// Coast for fleet units on split-coast provinces.
-    pub fleet_coast: [Option<Coast>; PROVINCE_COUNT],
+    pub fleet_coast: [Option<Coast>; MAX_PROVINCES],
     /// Supply center owner: None if not an SC or if neutral.
-    pub sc_owner: [Option<Power>; PROVINCE_COUNT],
+    pub sc_owner: [Option<Power>; MAX_PROVINCES],
     /// Dislodged units awaiting retreat orders.
-    pub dislodged: [Option<DislodgedUnit>; PROVINCE_COUNT],
+    pub dislodged: [Option<DislodgedUnit>; MAX_PROVINCES],
+    /// Zobrist hash of everything above except `dislodged`.
+    zobrist: u64,
+    /// The map the position is played on; not part of the hash.
+    map: Arc<GameMap>,
 }
 
 impl BoardState {
-    /// Creates an empty board state with no units or ownership.
+    /// Creates an empty board state with no units or ownership, on the
+    /// standard map.
     pub fn empty(year: u16, season: Season, phase: Phase) -> Self {
+        Self::empty_on(GameMap::standard(), year, season, phase)
+    }
+
+    /// Creates an empty board state on `map`.
+    pub fn empty_on(map: Arc<GameMap>, year: u16, season: Season, phase: Phase) -> Self {
         BoardState {
             year,
             season,
             phase,
-            units: [None; PROVINCE_COUNT],
-            fleet_coast: [None; PROVINCE_COUNT],
-            sc_owner: [None; PROVINCE_COUNT],
-            dislodged: [None; PROVINCE_COUNT],
+            units: [None; MAX_PROVINCES],
+            fleet_coast: [None; MAX_PROVINCES],
+            sc_owner: [None; MAX_PROVINCES],
+            dislodged: [None; MAX_PROVINCES],
+            zobrist: zobrist::turn_key(year, season, phase),
+            map,
         }
     }
 
+    /// The map the position is played on.
+    #[inline]
+    pub fn map(&self) -> &GameMap {
+        &self.map
+    }
+
+    /// The map the position is played on, for building other positions on
+    /// it.
+    pub fn shared_map(&self) -> &Arc<GameMap> {
+        &self.map
+    }
+
+    /// The position's Zobrist hash: units, fleet coasts, supply center
+    /// owners and turn, but not dislodged units.
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// Orders movement turns so consecutive ones differ by one: two per
+    /// year, the fall one higher.
+    pub fn turn_index(&self) -> u32 {
+        self.year as u32 * 2 + matches!(self.season, Season::Fall) as u32
+    }
+
+    /// Moves the game to a new turn.
+    pub fn set_turn(&mut self, year: u16, season: Season, phase: Phase) {
+        self.zobrist ^= zobrist::turn_key(self.year, self.season, self.phase)
+            ^ zobrist::turn_key(year, season, phase);
+        self.year = year;
+        self.season = season;
+        self.phase = phase;
+    }
+
+    /// Sets the unit in a province and its fleet coast, replacing whatever
+    /// stood there.
+    pub fn set_unit(
+        &mut self,
+        province: Province,
+        unit: Option<(Power, UnitType)>,
+        coast: Option<Coast>,
+    ) {
+        let idx = province.index();
+        self.zobrist ^= zobrist::unit_key(idx, self.units[idx])
+            ^ zobrist::unit_key(idx, unit)
+            ^ zobrist::coast_key(idx, self.fleet_coast[idx])
+            ^ zobrist::coast_key(idx, coast);
+        self.units[idx] = unit;
+        self.fleet_coast[idx] = coast;
+    }
+
+    /// Removes and returns the unit in a province, with its fleet coast.
+    pub fn remove_unit(&mut self, province: Province) -> Option<(Power, UnitType)> {
+        let unit = self.units[province.index()];
+        self.set_unit(province, None, None);
+        unit
+    }
+
     /// Places a unit on the board. Returns false if the province is already occupied.
     pub fn place_unit(
         &mut self,
@@ -111,28 +197,222 @@ impl BoardState {
         unit_type: UnitType,
         coast: Coast,
     ) -> bool {
-        let idx = province as usize;
+        let idx = province.index();
         if self.units[idx].is_some() {
             return false;
         }
-        self.units[idx] = Some((power, unit_type));
-        if coast != Coast::None {
-            self.fleet_coast[idx] = Some(coast);
-        }
+        let fleet_coast = if coast != Coast::None {
+            Some(coast)
+        } else {
+            self.fleet_coast[idx]
+        };
+        self.set_unit(province, Some((power, unit_type)), fleet_coast);
         true
     }
 
     /// Sets supply center ownership for a province.
     pub fn set_sc_owner(&mut self, province: Province, owner: Option<Power>) {
-        self.sc_owner[province as usize] = owner;
+        let idx = province.index();
+        self.zobrist ^= zobrist::sc_key(idx, self.sc_owner[idx]) ^ zobrist::sc_key(idx, owner);
+        self.sc_owner[idx] = owner;
     }
 
     /// Records a dislodged unit at a province.
     pub fn set_dislodged(&mut self, province: Province, dislodged: DislodgedUnit) {
-        self.dislodged[province as usize] = Some(dislodged);
+        self.dislodged[province.index()] = Some(dislodged);
+    }
+
+    /// Applies `delta` in place, returning what it takes to revert it.
+    pub fn apply(&mut self, delta: &Delta) -> Undo {
+        let mut edits = SmallVec::with_capacity(delta.edits.len());
+        for &edit in &delta.edits {
+            edits.push(self.edit(edit));
+        }
+        Undo { edits }
+    }
+
+    /// Reverts the delta `undo` was returned for. Deltas applied since must
+    /// be undone first.
+    pub fn undo(&mut self, undo: Undo) {
+        for &edit in undo.edits.iter().rev() {
+            self.edit(edit);
+        }
+    }
+
+    /// Makes one edit, returning the edit that reverses it.
+    fn edit(&mut self, edit: Edit) -> Edit {
+        match edit {
+            Edit::Unit {
+                province,
+                unit,
+                coast,
+            } => {
+                let idx = province.index();
+                let old = Edit::Unit {
+                    province,
+                    unit: self.units[idx],
+                    coast: self.fleet_coast[idx],
+                };
+                self.set_unit(province, unit, coast);
+                old
+            }
+            Edit::Owner { province, owner } => {
+                let old = Edit::Owner {
+                    province,
+                    owner: self.sc_owner[province.index()],
+                };
+                self.set_sc_owner(province, owner);
+                old
+            }
+            Edit::Dislodged {
+                province,
+                dislodged,
+            } => {
+                let slot = &mut self.dislodged[province.index()];
+                let old = Edit::Dislodged {
+                    province,
+                    dislodged: *slot,
+                };
+                *slot = dislodged;
+                old
+            }
+            Edit::Turn {
+                year,
+                season,
+                phase,
+            } => {
+                let old = Edit::Turn {
+                    year: self.year,
+                    season: self.season,
+                    phase: self.phase,
+                };
+                self.set_turn(year, season, phase);
+                old
+            }
+        }
     }
 }
 
+/// One change to a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Unit {
+        province: Province,
+        unit: Option<(Power, UnitType)>,
+        coast: Option<Coast>,
+    },
+    Owner {
+        province: Province,
+        owner: Option<Power>,
+    },
+    Dislodged {
+        province: Province,
+        dislodged: Option<DislodgedUnit>,
+    },
+    Turn {
+        year: u16,
+        season: Season,
+        phase: Phase,
+    },
+}
+
+/// Edits held inline before a delta spills to the heap; a movement phase
+/// with every unit moving fits.
+const INLINE_EDITS: usize = 64;
+
+/// Changes to a position, made in order by [`BoardState::apply`]. The
+/// resolve module builds these from resolution results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delta {
+    edits: SmallVec<[Edit; INLINE_EDITS]>,
+    /// Index in `edits` of each province's latest unit edit, or
+    /// `NO_EDIT`.
+    latest_unit: [u16; MAX_PROVINCES],
+}
+
+/// `Delta::latest_unit` for a province whose unit is untouched.
+const NO_EDIT: u16 = u16::MAX;
+
+impl Default for Delta {
+    fn default() -> Self {
+        Delta {
+            edits: SmallVec::new(),
+            latest_unit: [NO_EDIT; MAX_PROVINCES],
+        }
+    }
+}
+
+impl Delta {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the delta changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Sets the unit in a province and its fleet coast, as
+    /// [`BoardState::set_unit`].
+    pub fn set_unit(
+        &mut self,
+        province: Province,
+        unit: Option<(Power, UnitType)>,
+        coast: Option<Coast>,
+    ) {
+        self.latest_unit[province.index()] = self.edits.len() as u16;
+        self.edits.push(Edit::Unit {
+            province,
+            unit,
+            coast,
+        });
+    }
+
+    /// Sets a supply center's owner.
+    pub fn set_sc_owner(&mut self, province: Province, owner: Option<Power>) {
+        self.edits.push(Edit::Owner { province, owner });
+    }
+
+    /// Sets or clears the dislodged unit at a province.
+    pub fn set_dislodged(&mut self, province: Province, dislodged: Option<DislodgedUnit>) {
+        self.edits.push(Edit::Dislodged {
+            province,
+            dislodged,
+        });
+    }
+
+    /// Moves the game to a new turn.
+    pub fn set_turn(&mut self, year: u16, season: Season, phase: Phase) {
+        self.edits.push(Edit::Turn {
+            year,
+            season,
+            phase,
+        });
+    }
+
+    /// The unit and fleet coast at `province` once the edits so far are
+    /// applied to `state`.
+    pub fn unit_at(
+        &self,
+        state: &BoardState,
+        province: Province,
+    ) -> (Option<(Power, UnitType)>, Option<Coast>) {
+        let idx = province.index();
+        match self.edits.get(self.latest_unit[idx] as usize) {
+            Some(&Edit::Unit { unit, coast, .. }) => (unit, coast),
+            _ => (state.units[idx], state.fleet_coast[idx]),
+        }
+    }
+}
+
+/// What a [`Delta`] overwrote, returned by [`BoardState::apply`] for
+/// [`BoardState::undo`].
+#[must_use = "an applied delta can only be reverted with its Undo"]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undo {
+    edits: SmallVec<[Edit; INLINE_EDITS]>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,7 +449,7 @@ mod tests {
         let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
         assert!(state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None));
         assert_eq!(
-            state.units[Province::Vie as usize],
+            state.units[Province::Vie.index()],
             Some((Power::Austria, UnitType::Army))
         );
     }
@@ -186,20 +466,17 @@ mod tests {
         let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
         assert!(state.place_unit(Province::Stp, Power::Russia, UnitType::Fleet, Coast::South));
         assert_eq!(
-            state.units[Province::Stp as usize],
+            state.units[Province::Stp.index()],
             Some((Power::Russia, UnitType::Fleet))
         );
-        assert_eq!(
-            state.fleet_coast[Province::Stp as usize],
-            Some(Coast::South)
-        );
+        assert_eq!(state.fleet_coast[Province::Stp.index()], Some(Coast::South));
     }
 
     #[test]
     fn set_sc_owner_and_dislodged() {
         let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
         state.set_sc_owner(Province::Vie, Some(Power::Austria));
-        assert_eq!(state.sc_owner[Province::Vie as usize], Some(Power::Austria));
+        assert_eq!(state.sc_owner[Province::Vie.index()], Some(Power::Austria));
 
         state.set_dislodged(
             Province::Ser,
@@ -210,8 +487,49 @@ mod tests {
                 attacker_from: Province::Bul,
             },
         );
-        let d = state.dislodged[Province::Ser as usize].unwrap();
+        let d = state.dislodged[Province::Ser.index()].unwrap();
         assert_eq!(d.power, Power::Austria);
         assert_eq!(d.attacker_from, Province::Bul);
     }
+
+    #[test]
+    fn apply_and_undo_restore_the_position() {
+        let mut state = BoardState::empty(1901, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Stp, Power::Russia, UnitType::Fleet, Coast::South);
+        state.set_sc_owner(Province::Gal, Some(Power::Russia));
+        let before = state.clone();
+
+        // Vie -> Gal, capturing it, while the Stp fleet is dislodged.
+        let mut delta = Delta::new();
+        delta.set_unit(Province::Vie, None, None);
+        assert_eq!(delta.unit_at(&state, Province::Vie), (None, None));
+        delta.set_unit(Province::Gal, Some((Power::Austria, UnitType::Army)), None);
+        delta.set_sc_owner(Province::Gal, Some(Power::Austria));
+        delta.set_unit(Province::Stp, None, None);
+        delta.set_dislodged(
+            Province::Stp,
+            Some(DislodgedUnit {
+                power: Power::Russia,
+                unit_type: UnitType::Fleet,
+                coast: Coast::South,
+                attacker_from: Province::Fin,
+            }),
+        );
+        delta.set_turn(1901, Season::Fall, Phase::Retreat);
+        let undo = state.apply(&delta);
+        assert_eq!(state.units[Province::Vie.index()], None);
+        assert_eq!(state.sc_owner[Province::Gal.index()], Some(Power::Austria));
+        assert!(state.dislodged[Province::Stp.index()].is_some());
+        assert_eq!(state.phase, Phase::Retreat);
+
+        let mut fresh = BoardState::empty(1901, Season::Fall, Phase::Retreat);
+        fresh.place_unit(Province::Gal, Power::Austria, UnitType::Army, Coast::None);
+        fresh.set_sc_owner(Province::Gal, Some(Power::Austria));
+        assert_eq!(state.zobrist(), fresh.zobrist());
+
+        state.undo(undo);
+        assert_eq!(state, before);
+        assert_eq!(state.zobrist(), before.zobrist());
+    }
 }
@@ -0,0 +1,163 @@
+//! Zobrist keys for board positions.
+//!
+//! Every unit placement (province, power, unit type), fleet coast, supply
+//! center owner, season and phase has a fixed pseudo-random 64-bit key, and
+//! so does every year. A position's hash is the XOR of the keys of what is
+//! on the board, so changing one field XORs its old key out and its new key
+//! in. [`BoardState`] keeps its hash current that way in every setter,
+//! which makes hashing a position free where the old approach fed every
+//! array through a `DefaultHasher`.
+//!
+//! Keys are generated at compile time from fixed seeds, so a position
+//! hashes the same in every run and build. Dislodged units are not hashed:
+//! they only exist during retreats and follow from the previous movement.
+
+use super::province::{Coast, Power, MAX_PROVINCES};
+use super::state::{BoardState, Phase, Season};
+use super::unit::UnitType;
+
+/// One step of the SplitMix64 generator.
+const fn splitmix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// `N` keys drawn from the generator seeded with `seed`.
+const fn keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut out = [0u64; N];
+    let mut state = seed;
+    let mut i = 0;
+    while i < N {
+        state = splitmix(state);
+        out[i] = state;
+        i += 1;
+    }
+    out
+}
+
+const POWERS: usize = 7;
+const UNIT_TYPES: usize = 2;
+const COASTS: usize = 4;
+
+const UNIT_KEYS: [u64; MAX_PROVINCES * POWERS * UNIT_TYPES] = keys(0x756e_6974);
+const COAST_KEYS: [u64; MAX_PROVINCES * COASTS] = keys(0x636f_6173);
+const SC_KEYS: [u64; MAX_PROVINCES * POWERS] = keys(0x7363_6f77);
+const SEASON_KEYS: [u64; 2] = keys(0x7365_6173);
+const PHASE_KEYS: [u64; 3] = keys(0x7068_6173);
+const YEAR_SEED: u64 = 0x7965_6172;
+
+/// Key of `unit` standing in province index `idx` (0 for no unit).
+pub fn unit_key(idx: usize, unit: Option<(Power, UnitType)>) -> u64 {
+    match unit {
+        Some((power, unit_type)) => {
+            UNIT_KEYS[(idx * POWERS + power as usize) * UNIT_TYPES + unit_type as usize]
+        }
+        None => 0,
+    }
+}
+
+/// Key of a fleet coast in province index `idx` (0 for none).
+pub fn coast_key(idx: usize, coast: Option<Coast>) -> u64 {
+    match coast {
+        Some(coast) => COAST_KEYS[idx * COASTS + coast as usize],
+        None => 0,
+    }
+}
+
+/// Key of the owner of the supply center at province index `idx` (0 for
+/// none).
+pub fn sc_key(idx: usize, owner: Option<Power>) -> u64 {
+    match owner {
+        Some(power) => SC_KEYS[idx * POWERS + power as usize],
+        None => 0,
+    }
+}
+
+/// Key of the turn: year, season and phase.
+pub fn turn_key(year: u16, season: Season, phase: Phase) -> u64 {
+    splitmix(YEAR_SEED ^ year as u64) ^ SEASON_KEYS[season as usize] ^ PHASE_KEYS[phase as usize]
+}
+
+/// Hashes `state` from scratch. Equal to [`BoardState::zobrist`] whenever
+/// the state was only changed through its setters.
+pub fn hash_state(state: &BoardState) -> u64 {
+    let mut hash = turn_key(state.year, state.season, state.phase);
+    for idx in 0..MAX_PROVINCES {
+        hash ^= unit_key(idx, state.units[idx])
+            ^ coast_key(idx, state.fleet_coast[idx])
+            ^ sc_key(idx, state.sc_owner[idx]);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::Province;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+    use crate::resolve::{advance_state, apply_resolution, Resolver};
+
+    #[test]
+    fn keys_are_distinct_and_nonzero() {
+        let mut all: Vec<u64> = UNIT_KEYS
+            .iter()
+            .chain(&COAST_KEYS)
+            .chain(&SC_KEYS)
+            .chain(&SEASON_KEYS)
+            .chain(&PHASE_KEYS)
+            .copied()
+            .collect();
+        assert!(all.iter().all(|&k| k != 0));
+        let n = all.len();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), n);
+    }
+
+    #[test]
+    fn incremental_hash_matches_full_hash() {
+        let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        assert_eq!(state.zobrist(), hash_state(&state));
+
+        state.place_unit(Province::Stp, Power::Russia, UnitType::Fleet, Coast::South);
+        state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
+        state.set_sc_owner(Province::Vie, Some(Power::Austria));
+        assert_eq!(state.zobrist(), hash_state(&state));
+
+        let before = state.zobrist();
+        state.remove_unit(Province::Vie);
+        state.set_unit(Province::Gal, Some((Power::Austria, UnitType::Army)), None);
+        state.set_turn(1901, Season::Fall, Phase::Movement);
+        assert_eq!(state.zobrist(), hash_state(&state));
+        assert_ne!(state.zobrist(), before);
+
+        state.set_unit(Province::Vie, Some((Power::Austria, UnitType::Army)), None);
+        state.remove_unit(Province::Gal);
+        state.set_turn(1901, Season::Spring, Phase::Movement);
+        assert_eq!(state.zobrist(), before);
+    }
+
+    #[test]
+    fn hash_stays_current_through_a_game_year() {
+        let mut state =
+            parse_dfen("1901fm/Aavie,Aabud,Rawar,Rfsev/Avie,Abud,Rwar,Rsev,Nrum,Nser/-").unwrap();
+        assert_eq!(state.zobrist(), hash_state(&state));
+        let orders: Vec<_> = ["A bud - rum", "A vie - gal", "F sev - rum", "A war - gal"]
+            .iter()
+            .zip([Power::Austria, Power::Austria, Power::Russia, Power::Russia])
+            .map(|(o, p)| (parse_order(o).unwrap(), p))
+            .collect();
+        let mut resolver = Resolver::new(64);
+        let (results, dislodged) = resolver.resolve(&orders, &state);
+        apply_resolution(&mut state, &results, &dislodged);
+        advance_state(&mut state, false);
+        assert_eq!(state.phase, Phase::Build);
+        assert_eq!(state.zobrist(), hash_state(&state));
+        advance_state(&mut state, false);
+        assert_eq!((state.year, state.season), (1902, Season::Spring));
+        assert_eq!(state.zobrist(), hash_state(&state));
+    }
+}
@@ -4,32 +4,94 @@
 //! runs search for the `go` command. Uses RM+ search at high strength
 //! (>= 80) and Cartesian search otherwise.
 
+pub mod async_api;
+pub mod platform;
+
+use std::any::Any;
 use std::collections::HashMap;
-use std::io::Write;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
 use std::process::Command;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use rand::rngs::SmallRng;
-use rand::SeedableRng;
 
-use crate::board::province::Power;
+use crate::analysis::{alliances_json, inferred_moves, ContentionTracker, RelationTracker};
+use crate::board::map::{GameMap, BUILTIN_MAPS};
+use crate::board::province::{Power, Province, ALL_POWERS};
 use crate::board::state::{BoardState, Phase};
-use crate::eval::NeuralEvaluator;
-use crate::movegen::random_orders;
+use crate::board::Order;
+use crate::eval::draw::TREND_YEARS;
+use crate::eval::{
+    dangers_json, draw_vote, evaluate_file, lockable_lines, survivors, territory_json,
+    threat_map_json, CenterHistory, DrawSignals, NeuralEvaluator,
+};
+use crate::movegen::{random_orders, validate};
 use crate::opening_book::{self, BookMatchConfig, OpeningBook};
-use crate::press::{format_press_out, generate_outbound_press, parse_press_raw, PressState};
-use crate::protocol::dfen::parse_dfen;
-use crate::protocol::dson::format_orders;
+use crate::press::{
+    format_press_out, generate_outbound_press, parse_press_raw, PressOut, PressState, PressType,
+};
+use crate::protocol::convert::{format_orders_as, Notation};
+use crate::protocol::dfen::parse_dfen_on;
+use crate::protocol::dson::{format_annotated_orders_on, format_orders_on};
+use crate::protocol::info::{write_filtered, write_result_info, InfoLevel};
+use crate::protocol::parser::{Command as DuiCommand, DrawMessage};
+use crate::protocol::record::{GameRecord, GameResult};
+use crate::resolve::{is_game_over, ResolvedOrder};
+use crate::rng::entropy_rng;
+use crate::search::cache_budget::{DEFAULT_CACHE_MB, MAX_CACHE_MB};
+use crate::search::endgame;
+use crate::search::observe::DEFAULT_OBSERVE_MS;
+use crate::search::opening_priors::OpeningStyle;
+use crate::search::repetition::STAGNATION_YEARS;
+use crate::search::replay::{options_hash, ReplayBlob};
 use crate::search::{
-    heuristic_build_orders, heuristic_retreat_orders, regret_matching_search, search,
+    apply_locks, forecast_json, greedy_lookahead_orders, heuristic_build_orders,
+    heuristic_retreat_orders, mcts_search, observe_position, regret_matching_search_in_session,
+    search, winter_search, AgreementReport, AgreementTotals, CacheBudget, Constraints,
+    ConventionTracker, CurrentBest, OpponentSummary, ProvinceForecast, RepetitionTracker,
+    RmOptions, SearchLogConfig, SearchMode, SearchResult, SearchSession, WhyNotReport,
 };
+use crate::throughput::record_search;
+
+/// RM+ candidates listed after each search at `InfoLevel` debug.
+const DEBUG_CANDIDATE_DUMP: usize = 8;
+
+/// Length of the search `forecast` runs when no search of the position
+/// is stored, in milliseconds.
+const FORECAST_SEARCH_MS: u64 = 500;
+
+/// Most order sets `MultiOrderSets` can ask for.
+const MAX_MULTI_ORDER_SETS: usize = 16;
+
+/// Default safety margin kept before the host's `Deadline`, in milliseconds.
+const DEFAULT_DEADLINE_MARGIN_MS: u64 = 1000;
+
+/// How long a search may run past its deadline stop before the engine
+/// answers without it.
+const DEADLINE_GRACE_MS: u64 = 250;
+
+/// Default least time between two `info currbest` lines, in milliseconds.
+const DEFAULT_CURRBEST_INTERVAL_MS: u64 = 1000;
 
 /// Default search time in milliseconds.
 const DEFAULT_MOVETIME_MS: u64 = 5000;
 
+/// Longest a `go ponder` search runs before it stops on its own.
+const PONDER_LIMIT_MS: u64 = 3_600_000;
+
+/// Time limit of a `replay-search`, which stops on its iteration count
+/// long before.
+const REPLAY_LIMIT_MS: u64 = 3_600_000;
+
+/// Least time the search after a ponder hit gets, so it can still add to
+/// the pondered pools and report.
+const PONDER_HIT_MIN_MS: u64 = 100;
+
 /// Default path for the opening book JSON file.
 const DEFAULT_BOOK_PATH: &str = "data/processed/opening_book.json";
 
@@ -48,25 +110,301 @@ fn compute_file_hash(path: &str) -> Option<String> {
     Some(hash.chars().take(8).collect())
 }
 
+/// Commands beyond the core DUI set that this engine understands.
+const PROTOCOL_EXTENSIONS: &[&str] = &[
+    "press",
+    "threatmap",
+    "territory",
+    "alliances",
+    "whynot",
+    "ping",
+    "observe",
+    "lockorder",
+    "dangers",
+    "forecast",
+    "evalfile",
+    "ponder",
+    "replay-search",
+    "draw",
+    "suggest",
+];
+
+/// Maps the engine can play by name: the standard map and the built-in
+/// variants. Any other map is loaded from a file with MapFile.
+fn supported_maps() -> Vec<&'static str> {
+    std::iter::once("standard")
+        .chain(BUILTIN_MAPS.iter().map(|(name, _)| *name))
+        .collect()
+}
+
+/// Optional capabilities compiled into this build.
+fn build_features() -> Vec<&'static str> {
+    let mut features = vec!["press"];
+    if cfg!(feature = "parallel") {
+        features.push("parallel");
+    }
+    if cfg!(feature = "neural") {
+        features.push("neural");
+    }
+    features
+}
+
+/// A `go ponder` search: the position and power it thinks about and when
+/// it started.
+struct Ponder {
+    power: Power,
+    state: BoardState,
+    started: Instant,
+}
+
 /// Output from a completed search thread.
 pub struct SearchOutput {
     pub info_buf: Vec<u8>,
-    pub orders: Vec<crate::board::Order>,
+    pub orders: Vec<Order>,
+    pub whynot: Option<WhyNotReport>,
+    /// The searched orders with their average-strategy confidence; empty
+    /// when the search reports none.
+    pub probabilities: Vec<(Order, f32)>,
+    /// Predicted order sets for the other powers, from RM+.
+    pub opponents: Vec<OpponentSummary>,
+}
+
+/// Runs a search, turning a panic inside it into an error info line and a
+/// fallback order set so `go` is always answered.
+///
+/// Empty results fall back to random legal orders; a panic falls back to the
+/// greedy orders the RM+ lookahead uses, or to random orders if those panic
+/// too. Completed searches are tallied against `movetime` for `/metrics`.
+fn guarded_search<F>(power: Power, state: &BoardState, movetime: Duration, run: F) -> SearchOutput
+where
+    F: FnOnce(&mut Vec<u8>) -> SearchResult,
+{
+    let mut info_buf = Vec::new();
+    let mut rng = entropy_rng();
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| run(&mut info_buf)));
+    match outcome {
+        Ok(mut result) => {
+            let _ = write_result_info(&mut info_buf, state.map(), &result);
+            record_search(
+                result.nodes,
+                result.elapsed,
+                movetime,
+                result.cache.as_ref().map(|c| (c.lookups, c.hits)),
+            );
+            let whynot = result.whynot.take();
+            let probabilities = result
+                .orders
+                .iter()
+                .copied()
+                .zip(result.confidence.iter().copied())
+                .collect();
+            let orders = if result.orders.is_empty() {
+                random_orders(power, state, &mut rng)
+            } else {
+                result.orders
+            };
+            SearchOutput {
+                info_buf,
+                orders,
+                whynot,
+                probabilities,
+                opponents: result.opponents,
+            }
+        }
+        Err(payload) => {
+            let _ = writeln!(
+                info_buf,
+                "info string error search panicked: {}; playing fallback orders",
+                panic_message(payload.as_ref())
+            );
+            SearchOutput {
+                info_buf,
+                orders: fallback_orders(power, state, &mut rng),
+                whynot: None,
+                probabilities: Vec::new(),
+                opponents: Vec::new(),
+            }
+        }
+    }
+}
+
+/// Orders to play without a search result: the greedy orders the RM+
+/// lookahead uses, or random legal orders if those panic or are empty.
+fn fallback_orders(power: Power, state: &BoardState, rng: &mut SmallRng) -> Vec<Order> {
+    let greedy = panic::catch_unwind(|| {
+        greedy_lookahead_orders(state, false)
+            .into_iter()
+            .filter(|&(_, p)| p == power)
+            .map(|(o, _)| o)
+            .collect::<Vec<Order>>()
+    });
+    match greedy {
+        Ok(orders) if !orders.is_empty() => orders,
+        _ => random_orders(power, state, rng),
+    }
+}
+
+/// The power `setpower auto` plays when `go` names none: in a retreat
+/// phase the one with the most dislodged units, in a build phase the one
+/// with the largest adjustment, otherwise the one with the most centers.
+/// Ties go to the most centers, then to the first power. None if every
+/// power is eliminated or has nothing to order.
+pub fn detect_power(state: &BoardState) -> Option<Power> {
+    let mut centers = [0i32; 7];
+    let mut units = [0i32; 7];
+    let mut dislodged = [0i32; 7];
+    for owner in state.sc_owner.iter().flatten() {
+        centers[*owner as usize] += 1;
+    }
+    for &(p, _) in state.units.iter().flatten() {
+        units[p as usize] += 1;
+    }
+    for d in state.dislodged.iter().flatten() {
+        dislodged[d.power as usize] += 1;
+    }
+    let need = |p: Power| match state.phase {
+        Phase::Retreat => dislodged[p as usize],
+        Phase::Build => (centers[p as usize] - units[p as usize]).abs(),
+        Phase::Movement => units[p as usize],
+    };
+    ALL_POWERS
+        .iter()
+        .copied()
+        .filter(|&p| need(p) > 0)
+        .max_by_key(|&p| {
+            let primary = match state.phase {
+                Phase::Movement => centers[p as usize],
+                _ => need(p),
+            };
+            (primary, centers[p as usize], -(p as i32))
+        })
+}
+
+/// Background timer that sets the search stop flag when the host deadline
+/// (less the margin) arrives. Dropping it cancels the timer.
+struct DeadlineTimer {
+    at: Instant,
+    _cancel: mpsc::Sender<()>,
+}
+
+impl DeadlineTimer {
+    fn start(at: Instant, stop: Arc<AtomicBool>) -> Self {
+        let (cancel, cancelled) = mpsc::channel::<()>();
+        let wait = at.saturating_duration_since(Instant::now());
+        std::thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = cancelled.recv_timeout(wait) {
+                stop.store(true, Ordering::Relaxed);
+            }
+        });
+        DeadlineTimer {
+            at,
+            _cancel: cancel,
+        }
+    }
+}
+
+/// Extracts the message from a panic payload, flattened to one line.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let msg = if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    };
+    msg.replace(['\n', '\r'], " ")
+}
+
+/// Pins a search thread to the `SearchCores` set, if there is one.
+fn pin_search_thread(cores: &[usize]) {
+    if !cores.is_empty() {
+        let _ = platform::pin_current_thread(cores);
+    }
+}
+
+/// Pins each worker of the search's thread pool to one of `cores` in turn.
+#[cfg(feature = "parallel")]
+fn pin_worker_pool(cores: &[usize]) -> std::io::Result<()> {
+    rayon::broadcast(|ctx| platform::pin_current_thread(&[cores[ctx.index() % cores.len()]]))
+        .into_iter()
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn pin_worker_pool(cores: &[usize]) -> std::io::Result<()> {
+    let _ = cores;
+    Ok(())
+}
+
+/// A background observe analysis streaming lines over a channel.
+struct Observer {
+    stop: Arc<AtomicBool>,
+    lines: mpsc::Receiver<String>,
+    handle: JoinHandle<()>,
 }
 
 /// Holds the mutable state of the engine between commands.
 pub struct Engine {
     pub position: Option<BoardState>,
     pub active_power: Option<Power>,
+    /// Under `setpower auto`, each `go` picks the power to play.
+    auto_power: bool,
+    /// Cores search threads are pinned to (`SearchCores`); empty for none.
+    search_cores: Vec<usize>,
     pub options: HashMap<String, String>,
     pub neural: Option<Arc<NeuralEvaluator>>,
     pub press: PressState,
     book: Option<OpeningBook>,
     book_loaded: bool,
+    /// The map games are played on, loaded from MapFile at `newgame`.
+    map: Arc<GameMap>,
     model_hash: Option<String>,
     rng: SmallRng,
     stop_flag: Arc<AtomicBool>,
     search_handle: Option<JoinHandle<SearchOutput>>,
+    /// Host deadline of the search in flight, if one was set.
+    deadline: Option<DeadlineTimer>,
+    /// The `go ponder` search in flight, if the search is one.
+    ponder: Option<Ponder>,
+    /// Where the search in flight publishes its leading orders for
+    /// `info currbest`, if it reports them.
+    currbest: Option<Arc<CurrentBest>>,
+    /// Time pondered on the position the next `go` searches, taken off its
+    /// budget.
+    pondered: Option<Duration>,
+    /// Candidate pools and regrets carried between RM+ searches.
+    session: Arc<Mutex<SearchSession>>,
+    /// The session as the last `go` search found it, for `replay-search`.
+    replay_base: SearchSession,
+    last_whynot: Option<WhyNotReport>,
+    /// Confidence in the last search's orders and its opponent predictions,
+    /// for the `OrderProbabilities` option.
+    last_probabilities: Vec<(Order, f32)>,
+    last_opponents: Vec<OpponentSummary>,
+    conventions: ConventionTracker,
+    /// Years the position has stood unchanged.
+    repetition: RepetitionTracker,
+    /// Center counts per year, for the draw vote's trend.
+    centers: CenterHistory,
+    /// Parties to the draw proposal awaiting its outcome.
+    draw_proposal: Option<Vec<Power>>,
+    /// Running `PolicyAgreement` counts for the game.
+    agreement: AgreementTotals,
+    /// Alliances, hostilities and recent fronts inferred from the positions
+    /// and orders seen.
+    relations: RelationTracker,
+    /// Orders the host fixed with `lockorder` for the current position and
+    /// power.
+    locked: Vec<Order>,
+    /// Order sets the host suggested with `suggest` for the current
+    /// position and power.
+    suggestions: Vec<Vec<Order>>,
+    observe: bool,
+    observer: Option<Observer>,
+    /// The game so far: positions seen and orders sent since `newgame`.
+    record: GameRecord,
+    /// Moves into each province and how many failed, since `newgame`.
+    contention: ContentionTracker,
 }
 
 impl Engine {
@@ -75,15 +413,39 @@ impl Engine {
         Engine {
             position: None,
             active_power: None,
+            auto_power: false,
+            search_cores: Vec::new(),
             options: HashMap::new(),
             neural: None,
             press: PressState::new(),
             book: None,
             book_loaded: false,
             model_hash: None,
-            rng: SmallRng::from_entropy(),
+            rng: entropy_rng(),
+            map: GameMap::standard(),
             stop_flag: Arc::new(AtomicBool::new(false)),
             search_handle: None,
+            deadline: None,
+            ponder: None,
+            currbest: None,
+            pondered: None,
+            session: Arc::new(Mutex::new(SearchSession::new())),
+            replay_base: SearchSession::new(),
+            last_whynot: None,
+            last_probabilities: Vec::new(),
+            last_opponents: Vec::new(),
+            conventions: ConventionTracker::new(),
+            repetition: RepetitionTracker::new(),
+            centers: CenterHistory::new(),
+            draw_proposal: None,
+            agreement: AgreementTotals::default(),
+            relations: RelationTracker::new(),
+            locked: Vec::new(),
+            suggestions: Vec::new(),
+            observe: false,
+            observer: None,
+            record: GameRecord::new(),
+            contention: ContentionTracker::new(),
         }
     }
 
@@ -91,7 +453,22 @@ impl Engine {
     pub fn new_game(&mut self) {
         self.position = None;
         self.active_power = None;
+        self.auto_power = false;
         self.press.reset();
+        self.conventions.reset();
+        self.repetition.reset();
+        self.centers.reset();
+        self.draw_proposal = None;
+        self.agreement = AgreementTotals::default();
+        self.relations.reset();
+        self.locked.clear();
+        self.suggestions.clear();
+        self.record = GameRecord::new();
+        self.contention.reset();
+        self.stop_observer();
+        // A search still in flight keeps the old session to itself.
+        self.session = Arc::new(Mutex::new(SearchSession::new()));
+        self.replay_base.clear();
     }
 
     /// Lazily loads the opening book from the configured BookPath (or default).
@@ -145,27 +522,234 @@ impl Engine {
     /// Sets the current board position from a DFEN string.
     /// Returns an error message on failure.
     pub fn set_position(&mut self, dfen: &str) -> Result<(), String> {
-        match parse_dfen(dfen) {
+        match parse_dfen_on(Arc::clone(&self.map), dfen) {
             Ok(state) => {
                 self.press.current_turn = state.year;
                 self.press.clear_turn();
                 self.press.trust.decay();
+                self.press.observe(&state);
+                self.conventions.observe(&state);
+                self.repetition.observe(&state);
+                self.centers.observe(&state);
+                self.observe_turn(&state);
+                self.relations.observe_position(&state);
+                self.record.observe_position(&state);
+                if let Some(winner) = is_game_over(&state) {
+                    self.record.result = Some(GameResult::Solo(winner));
+                }
+                if let Some(power) = self.active_power {
+                    self.press.trust.observe_relations(power, &self.relations);
+                }
+                if self.position.as_ref() != Some(&state) {
+                    self.locked.clear();
+                    self.suggestions.clear();
+                }
                 self.position = Some(state);
+                if self.observe && !self.is_searching() {
+                    self.start_observer();
+                }
                 Ok(())
             }
             Err(e) => Err(format!("failed to parse DFEN: {}", e)),
         }
     }
 
+    /// The record of the game since `newgame`: every position set and the
+    /// orders the engine sent, plus whatever the host added through
+    /// [`Engine::record_orders`] and [`Engine::record_resolution`].
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    /// Records another power's orders for the current position, for hosts
+    /// that know them (e.g. after the phase is adjudicated).
+    pub fn record_orders(&mut self, power: Power, orders: &[Order]) {
+        if let Some(state) = self.position.as_ref() {
+            self.record.add_orders(state, power, orders);
+        }
+    }
+
+    /// Records the adjudication of the current position.
+    pub fn record_resolution(&mut self, resolved: &[ResolvedOrder]) {
+        if let Some(state) = self.position.as_ref() {
+            self.record.add_resolution(state, resolved);
+        }
+    }
+
+    /// Records how the game ended.
+    pub fn record_result(&mut self, result: GameResult) {
+        self.record.result = Some(result);
+    }
+
+    /// Counts the moves of the movement turn that led to `next`, and the
+    /// supports and clashes between powers in it: from its adjudication if
+    /// the host recorded one, otherwise from the orders recorded for it
+    /// and `next` itself.
+    fn observe_turn(&mut self, next: &BoardState) {
+        let (Some(prev), Some(last)) = (self.position.as_ref(), self.record.phases.last()) else {
+            return;
+        };
+        let turn = (prev.year, prev.season, prev.phase);
+        if last.phase != turn || turn == (next.year, next.season, next.phase) {
+            return;
+        }
+        let mut played: Vec<(Order, Power)> = if !last.resolved.is_empty() {
+            self.contention.observe_resolution(&last.resolved);
+            last.resolved.iter().map(|r| (r.order, r.power)).collect()
+        } else {
+            let played: Vec<(Order, Power)> = last
+                .orders
+                .iter()
+                .flat_map(|(p, orders)| orders.iter().map(move |&o| (o, *p)))
+                .collect();
+            self.contention.observe(prev, &played, next);
+            played
+        };
+        // Powers nobody reported orders for still show their moves.
+        let reported: Vec<Power> = played.iter().map(|&(_, p)| p).collect();
+        played.extend(
+            inferred_moves(prev, next)
+                .into_iter()
+                .filter(|(_, p)| !reported.contains(p)),
+        );
+        self.relations.observe_orders(prev, &played);
+    }
+
+    /// Contention counts for the game so far.
+    pub fn contention(&self) -> &ContentionTracker {
+        &self.contention
+    }
+
     /// Sets the active power.
     pub fn set_power(&mut self, power: Power) {
+        self.auto_power = false;
+        self.focus(power);
+    }
+
+    /// Handles `setpower auto`: from now on each `go` plays for its
+    /// `focus` power, or for the one [`detect_power`] picks.
+    pub fn set_power_auto(&mut self) {
+        self.auto_power = true;
+    }
+
+    /// Makes `power` the active power, dropping locks made for another.
+    fn focus(&mut self, power: Power) {
+        if self.active_power != Some(power) {
+            self.locked.clear();
+            self.suggestions.clear();
+        }
         self.active_power = Some(power);
     }
 
+    /// Warns the host when `power` has neither centers nor units left.
+    fn warn_if_eliminated<W: Write>(&self, out: &mut W, power: Power) {
+        let Some(state) = self.position.as_ref() else {
+            return;
+        };
+        if !state.sc_owner.contains(&Some(power))
+            && !state.units.iter().flatten().any(|&(p, _)| p == power)
+        {
+            let _ = writeln!(
+                out,
+                "info string warning setpower {}: eliminated in this position",
+                power.name()
+            );
+        }
+    }
+
+    /// Picks the power for a `go` under `setpower auto`: `focus` when
+    /// given, else the position's pick. Without `setpower auto` a `focus`
+    /// other than the active power is ignored.
+    fn choose_power<W: Write>(&mut self, out: &mut W, focus: Option<Power>) {
+        if !self.auto_power {
+            if let Some(f) = focus.filter(|&f| Some(f) != self.active_power) {
+                eprintln!("go focus {}: ignored without 'setpower auto'", f.name());
+            }
+            return;
+        }
+        let Some(power) = focus.or_else(|| self.position.as_ref().and_then(detect_power)) else {
+            return;
+        };
+        self.warn_if_eliminated(out, power);
+        self.focus(power);
+        if self.info_level() >= InfoLevel::Normal {
+            let _ = writeln!(out, "info string setpower auto {}", power.name());
+        }
+    }
+
+    /// Handles `lockorder`: fixes the given orders for the active power's
+    /// units until the position or power changes. Orders that are not
+    /// legal here are skipped; an empty list drops all locks.
+    pub fn lock_orders(&mut self, orders: Vec<Order>) {
+        if orders.is_empty() {
+            self.locked.clear();
+        } else {
+            let (Some(state), Some(power)) = (self.position.as_ref(), self.active_power) else {
+                eprintln!("lockorder: no position or power set");
+                return;
+            };
+            for order in orders {
+                let (_, corrections) = validate(&[order], power, state);
+                if let Some(c) = corrections.first() {
+                    eprintln!("lockorder: rejected {}", c.describe(state.map()));
+                    continue;
+                }
+                let unit = order.unit().map(|u| u.location.province);
+                self.locked
+                    .retain(|o| unit.is_none() || o.unit().map(|u| u.location.province) != unit);
+                self.locked.push(order);
+            }
+        }
+        // Stored pools were built without these locks.
+        self.session = Arc::new(Mutex::new(SearchSession::new()));
+    }
+
+    /// Handles `suggest`: adds an order set for the active power's units to
+    /// the candidates of the next search on this position. Orders that are
+    /// not legal here are skipped; an empty list drops all suggestions.
+    pub fn suggest(&mut self, orders: Vec<Order>) {
+        if orders.is_empty() {
+            self.suggestions.clear();
+        } else {
+            let (Some(state), Some(power)) = (self.position.as_ref(), self.active_power) else {
+                eprintln!("suggest: no position or power set");
+                return;
+            };
+            let mut legal: Vec<Order> = Vec::new();
+            for order in orders {
+                let (_, corrections) = validate(&[order], power, state);
+                if let Some(c) = corrections.first() {
+                    eprintln!("suggest: rejected {}", c.describe(state.map()));
+                    continue;
+                }
+                let unit = order.unit().map(|u| u.location.province);
+                legal.retain(|o| unit.is_none() || o.unit().map(|u| u.location.province) != unit);
+                legal.push(order);
+            }
+            if legal.is_empty() || self.suggestions.contains(&legal) {
+                return;
+            }
+            self.suggestions.push(legal);
+        }
+        // Stored pools were built without these suggestions.
+        self.session = Arc::new(Mutex::new(SearchSession::new()));
+    }
+
+    /// Order sets currently suggested with `suggest`.
+    pub fn suggestions(&self) -> &[Vec<Order>] {
+        &self.suggestions
+    }
+
+    /// Orders currently locked with `lockorder`.
+    pub fn locked_orders(&self) -> &[Order] {
+        &self.locked
+    }
+
     /// Sets an engine option.
     pub fn set_option(&mut self, name: String, value: Option<String>) {
         let reload_neural = name == "ModelPath";
         let reload_book = name == "BookPath";
+        let placement = name == "SearchCores" || name == "ReaderNice";
         match value {
             Some(v) => {
                 self.options.insert(name, v);
@@ -183,6 +767,81 @@ impl Engine {
             self.book_loaded = false;
             self.ensure_book();
         }
+        if placement {
+            self.apply_thread_placement();
+        }
+    }
+
+    /// Applies `SearchCores` and `ReaderNice`. Pinning takes effect for the
+    /// worker pool at once, each worker on one core of the set in turn,
+    /// and for every search thread started afterwards; an empty set
+    /// releases them to every core. Failures are reported on stderr and
+    /// leave threads where they were.
+    fn apply_thread_placement(&mut self) {
+        let spec = self.options.get("SearchCores").cloned().unwrap_or_default();
+        let cores = match platform::parse_cores(&spec) {
+            Ok(cores) => cores,
+            Err(e) => {
+                eprintln!("info string SearchCores: {}", e);
+                Vec::new()
+            }
+        };
+        if cores != self.search_cores {
+            let pool_cores = if cores.is_empty() {
+                let n = std::thread::available_parallelism().map_or(1, |n| n.get());
+                (0..n).collect()
+            } else {
+                cores.clone()
+            };
+            if let Err(e) = pin_worker_pool(&pool_cores) {
+                eprintln!("info string SearchCores not applied: {}", e);
+            }
+            self.search_cores = cores;
+        }
+
+        let nice = self
+            .options
+            .get("ReaderNice")
+            .and_then(|v| v.parse::<i32>().ok())
+            .map_or(0, |n| n.clamp(0, 19));
+        if let Some(reader) = platform::reader_thread() {
+            if let Err(e) = platform::set_thread_nice(reader, nice) {
+                eprintln!("info string ReaderNice not applied: {}", e);
+            }
+        }
+    }
+
+    /// Plays the new game on the map at the configured MapFile, or the
+    /// built-in map named by Map if the path is empty. Falls back to the
+    /// standard map if the map cannot be loaded.
+    fn load_map(&mut self) {
+        let path_str = self.options.get("MapFile").cloned().unwrap_or_default();
+        if path_str.is_empty() {
+            let name = self.options.get("Map").map_or("standard", String::as_str);
+            self.map = GameMap::builtin(name).unwrap_or_else(|| {
+                eprintln!("info string unknown map {}; playing the standard map", name);
+                GameMap::standard()
+            });
+            return;
+        }
+        match GameMap::load(&path_str) {
+            Ok(m) => {
+                eprintln!(
+                    "info string loaded map {} ({} centers, solo at {})",
+                    m.name(),
+                    m.supply_center_count(),
+                    m.solo_centers()
+                );
+                self.map = Arc::new(m);
+            }
+            Err(e) => {
+                eprintln!(
+                    "info string map not loaded: {}: {}; playing the standard map",
+                    path_str, e
+                );
+                self.map = GameMap::standard();
+            }
+        }
     }
 
     /// Returns the configured search time from options, or the default.
@@ -195,6 +854,56 @@ impl Engine {
         Duration::from_millis(ms)
     }
 
+    /// Returns the configured `info` verbosity (default normal).
+    fn info_level(&self) -> InfoLevel {
+        self.options
+            .get("InfoLevel")
+            .and_then(|v| InfoLevel::parse(v))
+            .unwrap_or_default()
+    }
+
+    /// Returns when the engine must have answered `go`: the host's
+    /// `Deadline` (Unix time in ms) less `DeadlineMargin`. `None` when no
+    /// deadline is set.
+    fn host_deadline(&self) -> Option<Instant> {
+        let deadline_ms = self
+            .options
+            .get("Deadline")
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|&ms| ms > 0)?;
+        let margin_ms = self
+            .options
+            .get("DeadlineMargin")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_DEADLINE_MARGIN_MS);
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let left = deadline_ms.saturating_sub(margin_ms).saturating_sub(now_ms);
+        Some(Instant::now() + Duration::from_millis(left))
+    }
+
+    /// Returns the configured observe analysis budget, or the default.
+    fn observe_time(&self) -> Duration {
+        let ms = self
+            .options
+            .get("ObserveTime")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_OBSERVE_MS);
+        Duration::from_millis(ms)
+    }
+
+    /// Least time between `info currbest` lines, `None` when the
+    /// `CurrBestInterval` option turns them off.
+    fn currbest_interval(&self) -> Option<Duration> {
+        let ms = self
+            .options
+            .get("CurrBestInterval")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CURRBEST_INTERVAL_MS);
+        (ms > 0).then(|| Duration::from_millis(ms))
+    }
+
     /// Returns true if the engine is configured for neural evaluation.
     #[allow(dead_code)]
     fn use_neural(&self) -> bool {
@@ -210,6 +919,7 @@ impl Engine {
     pub fn handle_dui<W: Write>(&self, out: &mut W) {
         writeln!(out, "id name realpolitik {}", env!("GIT_HASH")).unwrap();
         writeln!(out, "id author polite-betrayal").unwrap();
+        writeln!(out, "id version {}", env!("CARGO_PKG_VERSION")).unwrap();
         writeln!(out, "option name Threads type spin default 4 min 1 max 64").unwrap();
         writeln!(
             out,
@@ -221,6 +931,11 @@ impl Engine {
             "option name Strength type spin default 100 min 1 max 100"
         )
         .unwrap();
+        writeln!(
+            out,
+            "option name SearchMode type combo default rm var rm var mcts"
+        )
+        .unwrap();
         writeln!(out, "option name ModelPath type string default models").unwrap();
         writeln!(
             out,
@@ -233,6 +948,104 @@ impl Engine {
             DEFAULT_BOOK_PATH
         )
         .unwrap();
+        let map_vars: String = supported_maps()
+            .iter()
+            .map(|name| format!(" var {}", name))
+            .collect();
+        writeln!(
+            out,
+            "option name Map type combo default standard{}",
+            map_vars
+        )
+        .unwrap();
+        writeln!(out, "option name MapFile type string default <empty>").unwrap();
+        writeln!(out, "option name OpeningPriors type check default true").unwrap();
+        writeln!(
+            out,
+            "option name CornerOpening type combo default standard var standard var consolidate var aggressive"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "option name LookaheadSupports type check default false"
+        )
+        .unwrap();
+        writeln!(out, "option name LocalSearch type check default true").unwrap();
+        writeln!(out, "option name CoalitionProof type check default false").unwrap();
+        writeln!(out, "option name BuildSearch type check default true").unwrap();
+        writeln!(
+            out,
+            "option name TrajectoryYears type spin default 2 min 0 max 10"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "option name MultiOrderSets type spin default 0 min 0 max {}",
+            MAX_MULTI_ORDER_SETS
+        )
+        .unwrap();
+        writeln!(out, "option name Introspection type check default false").unwrap();
+        writeln!(
+            out,
+            "option name OrderProbabilities type check default false"
+        )
+        .unwrap();
+        writeln!(out, "option name PolicyAgreement type check default false").unwrap();
+        writeln!(
+            out,
+            "option name SecondaryNotation type combo default none var none var human var daide"
+        )
+        .unwrap();
+        writeln!(out, "option name SearchLog type string default <empty>").unwrap();
+        writeln!(out, "option name SearchCores type string default <empty>").unwrap();
+        writeln!(
+            out,
+            "option name ReaderNice type spin default 0 min 0 max 19"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "option name SearchLogEvery type spin default 1 min 1 max 1000000"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "option name CacheSize type spin default {} min 1 max {}",
+            DEFAULT_CACHE_MB, MAX_CACHE_MB
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "option name ObserveTime type spin default {} min 50 max 60000",
+            DEFAULT_OBSERVE_MS
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "option name CurrBestInterval type spin default {} min 0 max 60000",
+            DEFAULT_CURRBEST_INTERVAL_MS
+        )
+        .unwrap();
+        writeln!(out, "option name Deadline type string default <empty>").unwrap();
+        writeln!(
+            out,
+            "option name DeadlineMargin type spin default {} min 0 max 60000",
+            DEFAULT_DEADLINE_MARGIN_MS
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "option name InfoLevel type combo default normal var quiet var normal var debug"
+        )
+        .unwrap();
+        writeln!(out, "capability features {}", build_features().join(" ")).unwrap();
+        writeln!(
+            out,
+            "capability extensions {}",
+            PROTOCOL_EXTENSIONS.join(" ")
+        )
+        .unwrap();
+        writeln!(out, "capability maps {}", supported_maps().join(" ")).unwrap();
         writeln!(out, "protocol_version 1").unwrap();
         writeln!(out, "duiok").unwrap();
         out.flush().unwrap();
@@ -255,39 +1068,635 @@ impl Engine {
             .unwrap_or(100)
     }
 
-    /// Handles an inbound press command. Parses the raw text and stores
-    /// the message in press state.
-    pub fn handle_press(&mut self, raw: &str) {
-        if let Some(mut msg) = parse_press_raw(raw) {
-            msg.turn_received = self.press.current_turn;
-            self.press.receive(msg);
-        } else {
-            eprintln!("press: failed to parse: {}", raw);
+    /// Returns the configured movement-phase search algorithm (default RM+).
+    fn search_mode(&self) -> SearchMode {
+        self.options
+            .get("SearchMode")
+            .and_then(|v| SearchMode::parse(v))
+            .unwrap_or_default()
+    }
+
+    /// Builds RM+ search options from the configured engine options.
+    fn rm_options(&self) -> RmOptions {
+        RmOptions {
+            opening_priors: !matches!(
+                self.options.get("OpeningPriors"),
+                Some(v) if v.eq_ignore_ascii_case("false")
+            ),
+            corner_opening: self
+                .options
+                .get("CornerOpening")
+                .and_then(|v| OpeningStyle::parse(v))
+                .unwrap_or_default(),
+            trajectory_years: self
+                .options
+                .get("TrajectoryYears")
+                .and_then(|v| v.parse::<usize>().ok())
+                .map_or(2, |y| y.min(10)),
+            lookahead_supports: matches!(
+                self.options.get("LookaheadSupports"),
+                Some(v) if v.eq_ignore_ascii_case("true")
+            ),
+            local_search: !matches!(
+                self.options.get("LocalSearch"),
+                Some(v) if v.eq_ignore_ascii_case("false")
+            ),
+            coalition_proof: matches!(
+                self.options.get("CoalitionProof"),
+                Some(v) if v.eq_ignore_ascii_case("true")
+            ),
+            introspection: matches!(
+                self.options.get("Introspection"),
+                Some(v) if v.eq_ignore_ascii_case("true")
+            ),
+            conventions: self.conventions.established(),
+            stagnant_years: self.repetition.stagnant_years(),
+            contention: self.contention.cautions(),
+            fronts: self
+                .active_power
+                .map(|p| self.relations.recent().fronts(p))
+                .unwrap_or_default(),
+            multi_order_sets: self
+                .options
+                .get("MultiOrderSets")
+                .and_then(|v| v.parse::<usize>().ok())
+                .map_or(0, |n| n.min(MAX_MULTI_ORDER_SETS)),
+            candidate_dump: if self.info_level() == InfoLevel::Debug {
+                DEBUG_CANDIDATE_DUMP
+            } else {
+                0
+            },
+            cache: CacheBudget::from_mb(
+                self.options
+                    .get("CacheSize")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(DEFAULT_CACHE_MB),
+            ),
+            constraints: self.search_constraints(),
+            agreements: self.agreements(),
+            suggestions: self.suggestions.clone(),
+            search_log: self.search_log(),
+            ..RmOptions::default()
         }
     }
 
-    /// Returns the trust model's scores for use in RM+ search.
-    pub fn trust_scores(&self) -> &[f64; 7] {
-        &self.press.trust.scores
+    /// Whether Build phases are searched (`BuildSearch`) rather than left
+    /// to the build heuristic.
+    fn build_search(&self) -> bool {
+        !matches!(
+            self.options.get("BuildSearch"),
+            Some(v) if v.eq_ignore_ascii_case("false")
+        )
     }
 
-    /// Writes search output (info lines + press + bestorders) to the given writer.
-    fn write_search_output<W: Write>(
-        &mut self,
+    /// Constraints on our candidates: the host's locks, then the terms of
+    /// the deals we agreed to in press.
+    fn search_constraints(&self) -> Constraints {
+        let mut constraints = Constraints::pinning(&self.locked);
+        if let Some(us) = self.active_power {
+            constraints.merge(&self.press.negotiations.constraints_for(us));
+        }
+        constraints
+    }
+
+    /// What each other power agreed to in press.
+    fn agreements(&self) -> Vec<(Power, Constraints)> {
+        ALL_POWERS
+            .iter()
+            .filter(|&&p| Some(p) != self.active_power)
+            .map(|&p| (p, self.press.negotiations.constraints_for(p)))
+            .filter(|(_, c)| !c.is_empty())
+            .collect()
+    }
+
+    /// The RM+ iteration trace configured by `SearchLog` and
+    /// `SearchLogEvery`, if any.
+    fn search_log(&self) -> Option<SearchLogConfig> {
+        let path = self.options.get("SearchLog").filter(|p| !p.is_empty())?;
+        let every = self
+            .options
+            .get("SearchLogEvery")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1);
+        Some(SearchLogConfig::new(path, every))
+    }
+
+    /// Handles an inbound press command. Parses the raw text and stores
+    /// the message in press state. DAIDE press is negotiated on behalf of
+    /// the active power, and the answer to it is returned.
+    pub fn handle_press(&mut self, raw: &str) -> Option<PressOut> {
+        let Some(mut msg) = parse_press_raw(raw) else {
+            eprintln!("press: failed to parse: {}", raw);
+            return None;
+        };
+        msg.turn_received = self.press.current_turn;
+        let reply = match (&msg.press_type, self.active_power) {
+            (PressType::Daide(press), Some(us)) => {
+                let before: Vec<_> = self.press.negotiations.deals().cloned().collect();
+                let reply =
+                    self.press
+                        .negotiate(msg.from, press.clone(), us, self.position.as_ref());
+                if !self.press.negotiations.deals().eq(before.iter()) {
+                    // Stored pools were built without these deals.
+                    self.session = Arc::new(Mutex::new(SearchSession::new()));
+                }
+                reply
+            }
+            _ => None,
+        };
+        self.press.receive(msg);
+        reply
+    }
+
+    /// Returns the trust model's scores for use in RM+ search.
+    pub fn trust_scores(&self) -> &[f64; 7] {
+        &self.press.trust.scores
+    }
+
+    /// Consecutive years the current position has stood unchanged, the
+    /// stagnation signal for draw offers.
+    pub fn stagnant_years(&self) -> u32 {
+        self.repetition.stagnant_years()
+    }
+
+    /// Handles `draw`. A proposal is voted on for the active power and
+    /// answered with `draw accept` or `draw reject`; an accepted draw ends
+    /// the game record with the proposal's parties.
+    pub fn handle_draw<W: Write>(&mut self, out: &mut W, message: DrawMessage) {
+        match message {
+            DrawMessage::Propose { powers } => {
+                let (Some(state), Some(power)) = (self.position.as_ref(), self.active_power) else {
+                    eprintln!("draw: no position or power set");
+                    return;
+                };
+                let parties = if powers.is_empty() {
+                    survivors(state)
+                } else {
+                    powers
+                };
+                let signals = DrawSignals {
+                    win_prob: self
+                        .neural
+                        .as_ref()
+                        .and_then(|nn| nn.value(state, power))
+                        .map(|value| value[1]),
+                    center_trend: self.centers.trend(power, TREND_YEARS),
+                    stagnant_years: self.repetition.stagnant_years(),
+                };
+                let vote = draw_vote(power, &parties, state, &signals);
+                if self.info_level() >= InfoLevel::Debug {
+                    let win = signals
+                        .win_prob
+                        .map_or("none".to_string(), |w| format!("{:.3}", w));
+                    let _ = writeln!(
+                        out,
+                        "info string draw win {} trend {} stagnation {}",
+                        win, signals.center_trend, signals.stagnant_years
+                    );
+                }
+                writeln!(out, "draw {}", vote.name()).unwrap();
+                out.flush().unwrap();
+                self.draw_proposal = Some(parties);
+            }
+            DrawMessage::Accept => {
+                let parties = self
+                    .draw_proposal
+                    .take()
+                    .or_else(|| self.position.as_ref().map(survivors))
+                    .unwrap_or_default();
+                self.record.result = Some(GameResult::Draw(parties));
+            }
+            DrawMessage::Reject => {
+                self.draw_proposal = None;
+            }
+        }
+    }
+
+    /// Handles the `threatmap` command: writes `threatmap <json>` with the
+    /// per-province threat-minus-defense map for the given (or active) power.
+    pub fn handle_threatmap<W: Write>(&self, out: &mut W, power: Option<Power>) {
+        let state = match self.position.as_ref() {
+            Some(s) => s,
+            None => {
+                eprintln!("threatmap: no position set");
+                return;
+            }
+        };
+        let power = match power.or(self.active_power) {
+            Some(p) => p,
+            None => {
+                eprintln!("threatmap: no power given and no active power set");
+                return;
+            }
+        };
+        writeln!(out, "threatmap {}", threat_map_json(power, state)).unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Handles the `dangers` command: writes `dangers <json>` listing the
+    /// immediate threats against the given power (or the active power).
+    pub fn handle_dangers<W: Write>(&self, out: &mut W, power: Option<Power>) {
+        let state = match self.position.as_ref() {
+            Some(s) => s,
+            None => {
+                eprintln!("dangers: no position set");
+                return;
+            }
+        };
+        let power = match power.or(self.active_power) {
+            Some(p) => p,
+            None => {
+                eprintln!("dangers: no power given and no active power set");
+                return;
+            }
+        };
+        let attacked: Vec<(Province, f64)> = self
+            .stored_forecast(power, state)
+            .unwrap_or_default()
+            .iter()
+            .map(|f| (f.province, f.probability))
+            .collect();
+        writeln!(out, "dangers {}", dangers_json(power, state, &attacked)).unwrap();
+        out.flush().unwrap();
+    }
+
+    /// The pressure forecast for `power` from the stored search, if that
+    /// search was of `state` and is not running.
+    fn stored_forecast(&self, power: Power, state: &BoardState) -> Option<Vec<ProvinceForecast>> {
+        self.session.try_lock().ok()?.forecast(power, state)
+    }
+
+    /// Handles the `forecast` command: writes `forecast <json>` with the
+    /// probability and strength of an attack on each province of the given
+    /// power (or the active power), from the equilibrium of the last RM+
+    /// search of the position. Without one, runs a short search first.
+    pub fn handle_forecast<W: Write>(&mut self, out: &mut W, power: Option<Power>) {
+        let Some(state) = self.position.clone() else {
+            eprintln!("forecast: no position set");
+            return;
+        };
+        let Some(power) = power.or(self.active_power) else {
+            eprintln!("forecast: no power given and no active power set");
+            return;
+        };
+        if self.is_searching() {
+            eprintln!("forecast: a search is running");
+            return;
+        }
+        let forecast = if state.phase != Phase::Movement {
+            Vec::new()
+        } else if let Some(f) = self.stored_forecast(power, &state) {
+            f
+        } else {
+            self.forecast_search(power, &state);
+            self.stored_forecast(power, &state).unwrap_or_default()
+        };
+        writeln!(
+            out,
+            "forecast {}",
+            forecast_json(state.map(), power, &forecast)
+        )
+        .unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Runs a short RM+ search of `state` for `power` into the session,
+    /// discarding its output, so there is an equilibrium to forecast from.
+    fn forecast_search(&mut self, power: Power, state: &BoardState) {
+        self.ensure_neural();
+        let options = self.rm_options();
+        let options = RmOptions {
+            trajectory_years: 0,
+            multi_order_sets: 0,
+            candidate_dump: 0,
+            search_log: None,
+            // Our locks only bind our own searches.
+            constraints: if Some(power) == self.active_power {
+                options.constraints.clone()
+            } else {
+                Constraints::default()
+            },
+            ..options
+        };
+        let trust = self.press.trust.scores;
+        let mut session = self.session.lock().unwrap_or_else(|e| {
+            let mut guard = e.into_inner();
+            guard.clear();
+            guard
+        });
+        regret_matching_search_in_session(
+            power,
+            state,
+            Duration::from_millis(FORECAST_SEARCH_MS),
+            &mut std::io::sink(),
+            self.neural.as_deref(),
+            self.strength(),
+            Some(&trust),
+            &AtomicBool::new(false),
+            &options,
+            &mut session,
+        );
+    }
+
+    /// Handles the `territory` command: writes `territory <json>` with the
+    /// nearest-controller partition of the current position.
+    pub fn handle_territory<W: Write>(&self, out: &mut W) {
+        let state = match self.position.as_ref() {
+            Some(s) => s,
+            None => {
+                eprintln!("territory: no position set");
+                return;
+            }
+        };
+        writeln!(out, "territory {}", territory_json(state)).unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Handles the `alliances` command: writes `alliances <json>` with the
+    /// supports and clashes between powers over the last movement turns.
+    pub fn handle_alliances<W: Write>(&self, out: &mut W) {
+        writeln!(
+            out,
+            "alliances {}",
+            alliances_json(&self.relations.recent())
+        )
+        .unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Handles the `evalfile` command: evaluates every DFEN in `path` with
+    /// the heuristic and, if loaded, the value network, writes the CSV to
+    /// `output` (default `<path>.csv`), and reports
+    /// `evalfile {"positions":..,"errors":..,"path":..}`.
+    pub fn handle_evalfile<W: Write>(&mut self, out: &mut W, path: &str, output: Option<&str>) {
+        self.ensure_neural();
+        let csv_path = output.map_or_else(|| format!("{}.csv", path), str::to_string);
+        let result = File::open(path).and_then(|input| {
+            let mut csv = BufWriter::new(File::create(&csv_path)?);
+            evaluate_file(BufReader::new(input), &mut csv, self.neural.as_deref())
+        });
+        match result {
+            Ok(summary) => {
+                let report = serde_json::json!({
+                    "positions": summary.positions,
+                    "errors": summary.errors,
+                    "path": csv_path,
+                });
+                writeln!(out, "evalfile {}", report).unwrap();
+                out.flush().unwrap();
+            }
+            Err(e) => eprintln!("evalfile: {}: {}", path, e),
+        }
+    }
+
+    /// Handles the `whynot` command: writes `whynot <json>` explaining the
+    /// top rejected candidates of the last search. Requires the
+    /// `Introspection` option to have been on during that search.
+    pub fn handle_whynot<W: Write>(&self, out: &mut W) {
+        let report = match self.last_whynot.as_ref() {
+            Some(r) => r,
+            None => {
+                eprintln!("whynot: no introspection data (set Introspection to true before go)");
+                return;
+            }
+        };
+        writeln!(out, "whynot {}", report.to_json()).unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Handles the `ping` command: replies `pong`, echoing the token. Never
+    /// touches an in-flight search, so a host can tell a live engine in a
+    /// long search from a hung one.
+    pub fn handle_ping<W: Write>(&self, out: &mut W, token: Option<&str>) {
+        match token {
+            Some(t) => writeln!(out, "pong {}", t).unwrap(),
+            None => writeln!(out, "pong").unwrap(),
+        }
+        out.flush().unwrap();
+    }
+
+    /// Handles `observe on|off`. Turning it on analyzes the current position
+    /// at once (unless a search is running); turning it off discards any
+    /// analysis in flight.
+    pub fn set_observe(&mut self, enabled: bool) {
+        self.observe = enabled;
+        if !enabled {
+            self.stop_observer();
+        } else if self.position.is_some() && !self.is_searching() {
+            self.start_observer();
+        }
+    }
+
+    /// Starts a background observe analysis of the current position,
+    /// replacing any analysis still running.
+    fn start_observer(&mut self) {
+        self.stop_observer();
+        let state = match self.position.as_ref() {
+            Some(s) => s.clone(),
+            None => return,
+        };
+        let budget = self.observe_time();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, lines) = mpsc::channel();
+        let thread_stop = Arc::clone(&stop);
+        let cores = self.search_cores.clone();
+        let handle = std::thread::spawn(move || {
+            pin_search_thread(&cores);
+            let start = Instant::now();
+            let emitted = observe_position(&state, budget, &thread_stop, |outlook| {
+                let _ = tx.send(outlook.info_line(state.map()));
+            });
+            if emitted == 7 {
+                let _ = tx.send(format!(
+                    "info observe done time {}",
+                    start.elapsed().as_millis()
+                ));
+            }
+        });
+        self.observer = Some(Observer {
+            stop,
+            lines,
+            handle,
+        });
+    }
+
+    /// Stops the observe analysis, if any, and discards its pending lines.
+    fn stop_observer(&mut self) {
+        if let Some(observer) = self.observer.take() {
+            observer.stop.store(true, Ordering::Relaxed);
+            let _ = observer.handle.join();
+        }
+    }
+
+    /// Returns true if an observe analysis is running or has unread output.
+    pub fn is_observing(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    /// Writes any lines the observe analysis has produced so far, without
+    /// blocking. Clears the analysis once it has finished and been drained.
+    pub fn poll_observer<W: Write>(&mut self, out: &mut W) {
+        let observer = match self.observer.as_ref() {
+            Some(o) => o,
+            None => return,
+        };
+        let mut wrote = false;
+        let finished = loop {
+            match observer.lines.try_recv() {
+                Ok(line) => {
+                    writeln!(out, "{}", line).unwrap();
+                    wrote = true;
+                }
+                Err(mpsc::TryRecvError::Empty) => break false,
+                Err(mpsc::TryRecvError::Disconnected) => break true,
+            }
+        };
+        if wrote {
+            out.flush().unwrap();
+        }
+        if finished {
+            self.stop_observer();
+        }
+    }
+
+    /// Keeps what later commands need from a finished search and writes its
+    /// output.
+    fn finish_search<W: Write>(&mut self, out: &mut W, result: SearchOutput) {
+        self.last_whynot = result.whynot;
+        self.last_probabilities = result.probabilities;
+        self.last_opponents = result.opponents;
+        self.write_search_output(out, &result.info_buf, &result.orders);
+    }
+
+    /// Returns true if `bestorders` and predictions carry probabilities.
+    fn order_probabilities(&self) -> bool {
+        matches!(
+            self.options.get("OrderProbabilities"),
+            Some(v) if v.eq_ignore_ascii_case("true")
+        )
+    }
+
+    /// The notation `SecondaryNotation` asks orders to be repeated in, if
+    /// any.
+    fn secondary_notation(&self) -> Option<Notation> {
+        self.options
+            .get("SecondaryNotation")
+            .and_then(|v| v.parse::<Notation>().ok())
+            .filter(|&n| n != Notation::Dson)
+            // DAIDE and human notation know only the standard provinces.
+            .filter(|_| self.map.is_standard())
+    }
+
+    /// Writes `info agreement` for movement orders when `PolicyAgreement`
+    /// is on, adding them to the game's running counts.
+    fn write_agreement<W: Write>(&mut self, out: &mut W, power: Power, orders: &[Order]) {
+        let enabled = matches!(
+            self.options.get("PolicyAgreement"),
+            Some(v) if v.eq_ignore_ascii_case("true")
+        );
+        let Some(state) = self.position.as_ref() else {
+            return;
+        };
+        if !enabled || state.phase != Phase::Movement {
+            return;
+        }
+        let priors_off = matches!(
+            self.options.get("OpeningPriors"),
+            Some(v) if v.eq_ignore_ascii_case("false")
+        );
+        let prior_scale = if priors_off { 0.0 } else { 1.0 };
+        let report =
+            AgreementReport::measure(power, state, orders, self.neural.as_deref(), prior_scale);
+        self.agreement.add(&report);
+        if self.info_level() >= InfoLevel::Normal {
+            writeln!(out, "info agreement {}", report.to_json(&self.agreement)).unwrap();
+        }
+    }
+
+    /// Writes search output (info lines + press + bestorders) to the given writer.
+    fn write_search_output<W: Write>(
+        &mut self,
         out: &mut W,
         info_buf: &[u8],
         orders: &[crate::board::Order],
     ) {
         // Flush buffered info lines from the search thread.
-        out.write_all(info_buf).unwrap();
+        write_filtered(out, info_buf, self.info_level()).unwrap();
 
         let power = self.active_power.unwrap();
-        let dson = format_orders(orders);
+        let locked = apply_locks(orders, &self.locked);
+        let orders = &locked[..];
+
+        // Last safety net: never send an order the server would reject.
+        let checked;
+        let orders = match self.position.as_ref() {
+            Some(state) => {
+                let (valid, corrections) = validate(orders, power, state);
+                for c in &corrections {
+                    writeln!(out, "info string correction {}", c.describe(state.map())).unwrap();
+                }
+                checked = valid;
+                &checked[..]
+            }
+            None => orders,
+        };
+        if let Some(state) = self.position.as_ref() {
+            self.record.add_orders(state, power, orders);
+        }
+        self.write_agreement(out, power, orders);
+        let dson = if self.order_probabilities() && !self.last_probabilities.is_empty() {
+            // Locked orders are certain; orders the search did not choose
+            // (validation corrections) carry no probability.
+            let annotated: Vec<(Order, Option<f32>)> = orders
+                .iter()
+                .map(|o| {
+                    let p = if self.locked.contains(o) {
+                        Some(1.0)
+                    } else {
+                        self.last_probabilities
+                            .iter()
+                            .find(|(po, _)| po == o)
+                            .map(|&(_, p)| p)
+                    };
+                    (*o, p)
+                })
+                .collect();
+            let opponents: &[OpponentSummary] = if self.info_level() == InfoLevel::Quiet {
+                &[]
+            } else {
+                &self.last_opponents
+            };
+            for opp in opponents {
+                let predicted: Vec<(Order, Option<f32>)> = opp
+                    .orders
+                    .iter()
+                    .zip(&opp.confidence)
+                    .map(|(o, &p)| (*o, Some(p)))
+                    .collect();
+                writeln!(
+                    out,
+                    "info predict {} {}",
+                    opp.power.name(),
+                    format_annotated_orders_on(&self.map, &predicted)
+                )
+                .unwrap();
+            }
+            format_annotated_orders_on(&self.map, &annotated)
+        } else {
+            format_orders_on(&self.map, orders)
+        };
+
+        if let Some(notation) = self.secondary_notation() {
+            let text = format_orders_as(orders, power, notation, self.position.as_ref());
+            writeln!(out, "info orders {} {}", notation, text).unwrap();
+        }
 
         // Generate and emit outbound press before bestorders so the Go reader
         // can collect press_out lines while scanning for bestorders without blocking.
         if let Some(state) = self.position.as_ref() {
-            let press_out = generate_outbound_press(power, orders, state, &self.press.trust);
+            let forecast = self.stored_forecast(power, state).unwrap_or_default();
+            let press_out =
+                generate_outbound_press(power, orders, state, &self.press.trust, &forecast);
             for p in &press_out {
                 writeln!(out, "{}", format_press_out(p)).unwrap();
             }
@@ -298,6 +1707,133 @@ impl Engine {
         out.flush().unwrap();
     }
 
+    /// Carries out one parsed command, writing any response to `out`.
+    /// Returns false for `quit`. A `go` only starts the search; poll with
+    /// [`Engine::poll_search_done`] for its answer.
+    pub fn execute<W: Write>(&mut self, out: &mut W, cmd: DuiCommand) -> bool {
+        match cmd {
+            DuiCommand::Dui => {
+                self.handle_dui(out);
+            }
+            DuiCommand::IsReady => {
+                // If searching, wait for it to finish before responding. A
+                // ponder search has nothing to answer and keeps going.
+                if self.is_searching() && self.ponder.is_none() {
+                    self.handle_stop(out);
+                }
+                self.handle_isready(out);
+            }
+            DuiCommand::SetOption { name, value } => {
+                self.set_option(name, value);
+            }
+            DuiCommand::NewGame => {
+                self.end_ponder();
+                if self.is_searching() {
+                    self.handle_stop(out);
+                }
+                self.new_game();
+                self.load_map();
+            }
+            DuiCommand::Position { dfen } => {
+                if let Err(e) = self.set_position(&dfen) {
+                    eprintln!("{}", e);
+                }
+                if self
+                    .ponder
+                    .as_ref()
+                    .is_some_and(|p| Some(&p.state) != self.position.as_ref())
+                {
+                    self.end_ponder();
+                }
+            }
+            DuiCommand::SetPower { power } => {
+                self.warn_if_eliminated(out, power);
+                self.set_power(power);
+                if self.ponder.as_ref().is_some_and(|p| p.power != power) {
+                    self.end_ponder();
+                }
+            }
+            DuiCommand::SetPowerAuto => {
+                self.set_power_auto();
+            }
+            DuiCommand::Go(params) if params.ponder => {
+                self.choose_power(out, params.focus);
+                self.start_ponder(out);
+            }
+            DuiCommand::Go(params) => {
+                self.choose_power(out, params.focus);
+                self.pondered = self.end_ponder();
+                self.handle_go(out, Some(&params));
+            }
+            DuiCommand::PonderHit => {
+                if self.ponder.is_some() {
+                    self.pondered = self.end_ponder();
+                    self.handle_go(out, None);
+                }
+            }
+            DuiCommand::Stop => {
+                // Stopping a ponder answers from it, like any other search.
+                self.ponder = None;
+                if self.is_searching() {
+                    self.handle_stop(out);
+                }
+            }
+            DuiCommand::Press { raw } => {
+                if let Some(reply) = self.handle_press(&raw) {
+                    writeln!(out, "{}", format_press_out(&reply)).unwrap();
+                }
+            }
+            DuiCommand::ThreatMap { power } => {
+                self.handle_threatmap(out, power);
+            }
+            DuiCommand::Dangers { power } => {
+                self.handle_dangers(out, power);
+            }
+            DuiCommand::Forecast { power } => {
+                self.handle_forecast(out, power);
+            }
+            DuiCommand::Territory => {
+                self.handle_territory(out);
+            }
+            DuiCommand::Alliances => {
+                self.handle_alliances(out);
+            }
+            DuiCommand::WhyNot => {
+                self.handle_whynot(out);
+            }
+            DuiCommand::EvalFile { path, output } => {
+                self.handle_evalfile(out, &path, output.as_deref());
+            }
+            DuiCommand::Ping { token } => {
+                self.handle_ping(out, token.as_deref());
+            }
+            DuiCommand::Observe { enabled } => {
+                self.set_observe(enabled);
+            }
+            DuiCommand::Draw(message) => {
+                self.handle_draw(out, message);
+            }
+            DuiCommand::LockOrder { orders } => {
+                self.lock_orders(orders);
+            }
+            DuiCommand::Suggest { orders } => {
+                self.suggest(orders);
+            }
+            DuiCommand::ReplaySearch { blob } => {
+                self.handle_replay_search(out, &blob);
+            }
+            DuiCommand::Quit => {
+                self.end_ponder();
+                // Flush any in-flight search results before exiting.
+                if self.is_searching() {
+                    self.handle_stop(out);
+                }
+                return false;
+            }
+        }
+        true
+    }
+
     /// Handles the `go` command asynchronously. Spawns a search thread for
     /// movement phases; retreat/build/book-hit phases run synchronously.
     ///
@@ -328,8 +1864,13 @@ impl Engine {
             }
         };
 
+        // Playing takes priority over spectating.
+        self.stop_observer();
         self.ensure_neural();
         self.ensure_book();
+        self.last_whynot = None;
+        self.last_probabilities.clear();
+        self.last_opponents.clear();
 
         // Apply movetime override from GoParams.
         if let Some(params) = go_params {
@@ -345,9 +1886,10 @@ impl Engine {
         }
 
         // Try opening book lookup first (before borrowing self mutably for search).
+        // Book lines name standard-board provinces, so other maps skip it.
         let book_hit = {
             let state = self.position.as_ref().unwrap();
-            if state.phase == Phase::Movement {
+            if state.phase == Phase::Movement && state.map().is_standard() {
                 if let Some(ref book) = self.book {
                     let cfg = BookMatchConfig::default();
                     opening_book::lookup_opening(book, state, power, &cfg)
@@ -361,9 +1903,24 @@ impl Engine {
 
         // Synchronous paths: book hits, retreat, build.
         let phase = self.position.as_ref().unwrap().phase;
+        let stagnant = self.repetition.stagnant_years();
+        if phase == Phase::Movement
+            && stagnant >= STAGNATION_YEARS
+            && self.info_level() >= InfoLevel::Normal
+        {
+            let _ = writeln!(out, "info string stagnation years {}", stagnant);
+        }
+        if phase == Phase::Movement && self.info_level() >= InfoLevel::Normal {
+            let state = self.position.as_ref().unwrap();
+            for line in lockable_lines(power, state) {
+                let _ = writeln!(out, "info string stalemate lockable {}", line.name);
+            }
+        }
         if book_hit.is_some() || phase != Phase::Movement {
             let orders = if let Some(book_orders) = book_hit {
-                let _ = writeln!(out, "info string opening book hit for {:?}", power);
+                if self.info_level() >= InfoLevel::Normal {
+                    let _ = writeln!(out, "info string opening book hit for {:?}", power);
+                }
                 book_orders
             } else {
                 match phase {
@@ -378,7 +1935,11 @@ impl Engine {
                     }
                     Phase::Build => {
                         let state = self.position.as_ref().unwrap();
-                        let orders = heuristic_build_orders(power, state);
+                        let orders = if self.build_search() {
+                            winter_search(power, state)
+                        } else {
+                            heuristic_build_orders(power, state)
+                        };
                         if orders.is_empty() {
                             random_orders(power, state, &mut self.rng)
                         } else {
@@ -395,40 +1956,258 @@ impl Engine {
         // Async path: spawn search thread for movement phase.
         let state = self.position.as_ref().unwrap().clone();
         let neural = self.neural.clone();
-        let movetime = self.movetime();
+        let mut movetime = self.movetime();
+        if let Some(pondered) = self.pondered.take() {
+            // The pondered pools carry over, so only the rest of the budget
+            // is searched.
+            let floor = movetime.min(Duration::from_millis(PONDER_HIT_MIN_MS));
+            movetime = movetime.saturating_sub(pondered).max(floor);
+            if self.info_level() >= InfoLevel::Normal {
+                let iterations = self.session.lock().map_or(0, |s| s.iterations());
+                let _ = writeln!(
+                    out,
+                    "info string ponderhit pondered {} iterations {} movetime {}",
+                    pondered.as_millis(),
+                    iterations,
+                    movetime.as_millis()
+                );
+            }
+        }
+        let strength = self.strength();
+        let mode = self.search_mode();
+        self.currbest = self
+            .currbest_interval()
+            .filter(|_| self.info_level() >= InfoLevel::Normal)
+            .map(|interval| Arc::new(CurrentBest::new(interval)));
+        let rm_options = RmOptions {
+            currbest: self.currbest.clone(),
+            ..self.rm_options()
+        };
+        let trust = self.press.trust.scores;
+        let stop = Arc::clone(&self.stop_flag);
+        stop.store(false, Ordering::Relaxed);
+        let session = Arc::clone(&self.session);
+        if let Ok(s) = self.session.lock() {
+            self.replay_base.clone_from(&s);
+        }
+        self.deadline = self
+            .host_deadline()
+            .map(|at| DeadlineTimer::start(at, Arc::clone(&stop)));
+
+        let cores = self.search_cores.clone();
+        let handle = std::thread::spawn(move || {
+            pin_search_thread(&cores);
+            guarded_search(power, &state, movetime, |info_buf| {
+                // Small endgames go to the joint-order minimax whatever the
+                // configured search.
+                if endgame::applies(power, &state) {
+                    if let Some(result) =
+                        endgame::endgame_search(power, &state, movetime, info_buf, &stop)
+                    {
+                        return result;
+                    }
+                }
+                if strength >= 80 && mode == SearchMode::Mcts {
+                    mcts_search(
+                        power,
+                        &state,
+                        movetime,
+                        info_buf,
+                        neural.as_deref(),
+                        strength,
+                        Some(&trust),
+                        &stop,
+                        &rm_options,
+                    )
+                } else if strength >= 80 {
+                    // A search that panicked leaves the session unusable; start over.
+                    let mut session = session.lock().unwrap_or_else(|e| {
+                        let mut guard = e.into_inner();
+                        guard.clear();
+                        guard
+                    });
+                    regret_matching_search_in_session(
+                        power,
+                        &state,
+                        movetime,
+                        info_buf,
+                        neural.as_deref(),
+                        strength,
+                        Some(&trust),
+                        &stop,
+                        &rm_options,
+                        &mut session,
+                    )
+                } else {
+                    search(power, &state, movetime, info_buf, &stop)
+                }
+            })
+        });
+
+        self.search_handle = Some(handle);
+    }
+
+    /// Handles `go ponder`: starts an RM+ search of the current movement
+    /// position that fills the search session without answering, until
+    /// `ponderhit`, `go` or `stop`. Other phases and searches have nothing
+    /// to ponder; the engine just waits for `ponderhit` or `go`.
+    fn start_ponder<W: Write>(&mut self, out: &mut W) {
+        let (Some(power), Some(state)) = (self.active_power, self.position.clone()) else {
+            eprintln!("go ponder: no position or power set");
+            return;
+        };
+        if self.search_handle.is_some() {
+            self.end_ponder();
+            self.handle_stop(out);
+        }
+        self.ponder = Some(Ponder {
+            power,
+            state: state.clone(),
+            started: Instant::now(),
+        });
+        if state.phase != Phase::Movement
+            || self.strength() < 80
+            || self.search_mode() != SearchMode::Rm
+        {
+            return;
+        }
+
+        self.stop_observer();
+        self.ensure_neural();
+        let neural = self.neural.clone();
         let strength = self.strength();
+        let rm_options = RmOptions {
+            early_exit: false,
+            ..self.rm_options()
+        };
         let trust = self.press.trust.scores;
         let stop = Arc::clone(&self.stop_flag);
         stop.store(false, Ordering::Relaxed);
+        let session = Arc::clone(&self.session);
+        let movetime = Duration::from_millis(PONDER_LIMIT_MS);
 
+        let cores = self.search_cores.clone();
         let handle = std::thread::spawn(move || {
-            let mut info_buf = Vec::new();
-            let mut rng = SmallRng::from_entropy();
-            let result = if strength >= 80 {
-                regret_matching_search(
+            pin_search_thread(&cores);
+            guarded_search(power, &state, movetime, |info_buf| {
+                let mut session = session.lock().unwrap_or_else(|e| {
+                    let mut guard = e.into_inner();
+                    guard.clear();
+                    guard
+                });
+                regret_matching_search_in_session(
                     power,
                     &state,
                     movetime,
-                    &mut info_buf,
+                    info_buf,
                     neural.as_deref(),
                     strength,
                     Some(&trust),
                     &stop,
+                    &rm_options,
+                    &mut session,
                 )
-            } else {
-                search(power, &state, movetime, &mut info_buf, &stop)
-            };
+            })
+        });
+        self.search_handle = Some(handle);
+    }
 
-            let orders = if result.orders.is_empty() {
-                random_orders(power, &state, &mut rng)
-            } else {
-                result.orders
-            };
+    /// Stops the ponder search in flight, if any, and discards its answer.
+    /// Returns how long it pondered when its pools are warm for the current
+    /// position and power.
+    fn end_ponder(&mut self) -> Option<Duration> {
+        let ponder = self.ponder.take()?;
+        self.abort_search();
+        let warm = match (self.active_power, self.position.as_ref()) {
+            (Some(power), Some(state)) => self
+                .session
+                .lock()
+                .is_ok_and(|s| s.is_warm_for(power, state)),
+            _ => false,
+        };
+        warm.then(|| ponder.started.elapsed())
+    }
 
-            SearchOutput { info_buf, orders }
-        });
+    /// Handles `replay-search <blob>`: reruns the RM+ search the blob
+    /// describes on the current position and power, writing its info lines
+    /// and `info string replay orders <dson>`. The position and the search
+    /// options must hash as they did, and a search that started from stored
+    /// pools can only be replayed while the engine still has them, i.e.
+    /// before the next `go`.
+    pub fn handle_replay_search<W: Write>(&mut self, out: &mut W, blob: &ReplayBlob) {
+        if self.is_searching() {
+            writeln!(out, "info string error replay-search: a search is running").unwrap();
+            return;
+        }
+        let (Some(power), Some(state)) = (self.active_power, self.position.clone()) else {
+            writeln!(
+                out,
+                "info string error replay-search: no position or power set"
+            )
+            .unwrap();
+            return;
+        };
+        if state.zobrist() != blob.position {
+            writeln!(
+                out,
+                "info string error replay-search: position hash {:x} is not the searched {:x}",
+                state.zobrist(),
+                blob.position
+            )
+            .unwrap();
+            return;
+        }
+        self.ensure_neural();
+        let options = RmOptions {
+            replay: Some(*blob),
+            ..self.rm_options()
+        };
+        let strength = self.strength();
+        let trust = self.press.trust.scores;
+        let hash = options_hash(&options, strength, Some(&trust), self.neural.is_some());
+        if hash != blob.options {
+            writeln!(
+                out,
+                "info string error replay-search: options hash {:x} is not the search's {:x}",
+                hash, blob.options
+            )
+            .unwrap();
+            return;
+        }
+        let mut session = if blob.pools == 0 {
+            SearchSession::new()
+        } else if self.replay_base.fingerprint() == blob.pools {
+            self.replay_base.clone()
+        } else {
+            writeln!(
+                out,
+                "info string error replay-search: the search started from stored pools this engine no longer has"
+            )
+            .unwrap();
+            return;
+        };
 
-        self.search_handle = Some(handle);
+        let mut info = Vec::new();
+        let result = regret_matching_search_in_session(
+            power,
+            &state,
+            Duration::from_millis(REPLAY_LIMIT_MS),
+            &mut info,
+            self.neural.as_deref(),
+            strength,
+            Some(&trust),
+            &AtomicBool::new(false),
+            &options,
+            &mut session,
+        );
+        out.write_all(&info).unwrap();
+        writeln!(
+            out,
+            "info string replay orders {}",
+            format_orders_on(state.map(), &result.orders)
+        )
+        .unwrap();
+        out.flush().unwrap();
     }
 
     /// Synchronous `go` for tests: spawns the search and immediately joins.
@@ -437,10 +2216,19 @@ impl Engine {
         self.handle_go(out, None);
         if self.search_handle.is_some() {
             let result = self.search_handle.take().unwrap().join().unwrap();
-            self.write_search_output(out, &result.info_buf, &result.orders);
+            self.finish_search(out, result);
         }
     }
 
+    /// The searching power's `n` best order sets from the last RM+ search,
+    /// with their strategy probabilities, best first.
+    pub fn top_order_sets(&self, n: usize) -> Vec<(Vec<Order>, f64)> {
+        self.session
+            .lock()
+            .map(|s| s.top_order_sets(n))
+            .unwrap_or_default()
+    }
+
     /// Returns true if an async search is in flight.
     pub fn is_searching(&self) -> bool {
         self.search_handle.is_some()
@@ -448,7 +2236,16 @@ impl Engine {
 
     /// Checks if the search thread has finished without blocking.
     /// If finished, writes output and returns true.
+    ///
+    /// If the search is still running well past the host deadline (it did
+    /// not honor the stop the deadline timer sent), answers with fallback
+    /// orders instead and leaves the search to finish unheard, so the power
+    /// is never left without orders.
     pub fn poll_search_done<W: Write>(&mut self, out: &mut W) -> bool {
+        if self.ponder.is_some() {
+            return false;
+        }
+        self.write_currbest(out);
         let finished = match &self.search_handle {
             Some(h) => h.is_finished(),
             None => return false,
@@ -456,18 +2253,58 @@ impl Engine {
         if finished {
             self.collect_search_result(out);
             true
+        } else if self
+            .deadline
+            .as_ref()
+            .is_some_and(|d| Instant::now() >= d.at + Duration::from_millis(DEADLINE_GRACE_MS))
+        {
+            self.answer_at_deadline(out);
+            true
         } else {
             false
         }
     }
 
+    /// Writes the leading orders the search in flight has published since
+    /// the last call, if any.
+    fn write_currbest<W: Write>(&self, out: &mut W) {
+        let Some(orders) = self.currbest.as_ref().and_then(|c| c.take()) else {
+            return;
+        };
+        writeln!(
+            out,
+            "info currbest {}",
+            format_orders_on(&self.map, &orders)
+        )
+        .unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Abandons the search in flight and writes fallback orders instead.
+    fn answer_at_deadline<W: Write>(&mut self, out: &mut W) {
+        self.search_handle = None;
+        self.deadline = None;
+        self.currbest = None;
+        // The abandoned search keeps the raised flag; later searches get a
+        // fresh one so the next `go` cannot un-stop it.
+        self.stop_flag.store(true, Ordering::Relaxed);
+        self.stop_flag = Arc::new(AtomicBool::new(false));
+        let (Some(power), Some(state)) = (self.active_power, self.position.as_ref()) else {
+            return;
+        };
+        let orders = fallback_orders(power, state, &mut self.rng);
+        let info =
+            b"info string error deadline reached before search finished; playing fallback orders\n";
+        self.write_search_output(out, info, &orders);
+    }
+
     /// Joins the search thread and writes buffered output + bestorders.
     pub fn collect_search_result<W: Write>(&mut self, out: &mut W) {
+        self.deadline = None;
+        self.currbest = None;
         if let Some(handle) = self.search_handle.take() {
             match handle.join() {
-                Ok(result) => {
-                    self.write_search_output(out, &result.info_buf, &result.orders);
-                }
+                Ok(result) => self.finish_search(out, result),
                 Err(_) => {
                     eprintln!("search thread panicked");
                 }
@@ -475,6 +2312,12 @@ impl Engine {
         }
     }
 
+    /// The flag that stops the search in flight, for stopping it from
+    /// another thread without access to the engine.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop_flag)
+    }
+
     /// Sets the stop flag, joins the search thread, and writes output.
     pub fn handle_stop<W: Write>(&mut self, out: &mut W) {
         self.stop_flag.store(true, Ordering::Relaxed);
@@ -483,6 +2326,8 @@ impl Engine {
 
     /// Sets the stop flag, joins the search thread, and discards output.
     pub fn abort_search(&mut self) {
+        self.deadline = None;
+        self.currbest = None;
         if self.search_handle.is_some() {
             self.stop_flag.store(true, Ordering::Relaxed);
             if let Some(handle) = self.search_handle.take() {
@@ -495,7 +2340,11 @@ impl Engine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::province::Province;
     use crate::board::state::{Phase, Season};
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::{parse_annotated_orders, parse_orders};
+    use crate::protocol::parser::GoParams;
 
     const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
 
@@ -543,6 +2392,20 @@ mod tests {
         assert_eq!(engine.options.get("Threads"), Some(&"8".to_string()));
     }
 
+    #[test]
+    fn guarded_search_recovers_from_panic() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let output = guarded_search(Power::France, &state, Duration::from_secs(1), |info| {
+            let _ = writeln!(info, "info depth 1 nodes 0");
+            panic!("index out of bounds\nsecond line")
+        });
+        let info = String::from_utf8(output.info_buf).unwrap();
+        assert!(info.starts_with("info depth 1"));
+        assert!(info.contains("info string error search panicked: index out of bounds second line"));
+        assert_eq!(output.orders.len(), 3);
+        assert!(output.whynot.is_none());
+    }
+
     #[test]
     fn handle_go_outputs_bestorders() {
         let mut engine = Engine::new();
@@ -568,6 +2431,85 @@ mod tests {
         assert_eq!(order_count, 3);
     }
 
+    #[test]
+    fn secondary_notation_repeats_bestorders() {
+        let mut engine = Engine::new();
+        engine.set_option("SecondaryNotation".to_string(), Some("daide".to_string()));
+        engine.set_option("SearchTime".to_string(), Some("200".to_string()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        let output_str = String::from_utf8(output).unwrap();
+        let daide = output_str
+            .lines()
+            .find_map(|l| l.strip_prefix("info orders daide "))
+            .expect("daide line");
+        let dson = output_str
+            .lines()
+            .find_map(|l| l.strip_prefix("bestorders "))
+            .expect("bestorders");
+        let orders = crate::protocol::dson::parse_orders(dson).unwrap();
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        assert_eq!(
+            daide,
+            format_orders_as(&orders, Power::Austria, Notation::Daide, Some(&state))
+        );
+        assert!(output_str.find("info orders").unwrap() < output_str.find("bestorders").unwrap());
+
+        engine.set_option("SecondaryNotation".to_string(), Some("none".to_string()));
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        assert!(!String::from_utf8(output).unwrap().contains("info orders"));
+    }
+
+    #[test]
+    fn policy_agreement_reports_before_bestorders() {
+        let mut engine = Engine::new();
+        engine.set_option("PolicyAgreement".to_string(), Some("true".to_string()));
+        engine.set_option("SearchTime".to_string(), Some("200".to_string()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        for _ in 0..2 {
+            let mut output = Vec::new();
+            engine.handle_go_sync(&mut output);
+            let output_str = String::from_utf8(output).unwrap();
+            let line = output_str
+                .lines()
+                .find_map(|l| l.strip_prefix("info agreement "))
+                .expect("agreement line");
+            let json: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(json["units"], 3);
+            assert!(json["heuristic"].as_u64().unwrap() <= 3);
+            assert!(
+                output_str.find("info agreement").unwrap() < output_str.find("bestorders").unwrap()
+            );
+        }
+        assert_eq!(engine.agreement.units, 6);
+        engine.new_game();
+        assert_eq!(engine.agreement, AgreementTotals::default());
+    }
+
+    #[test]
+    fn search_mode_mcts_plays_a_full_order_set() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchMode".to_string(), Some("mcts".to_string()));
+        engine.set_option("SearchTime".to_string(), Some("300".to_string()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Italy);
+
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+
+        let output_str = String::from_utf8(output).unwrap();
+        let bestorders_line = output_str
+            .lines()
+            .find(|l| l.starts_with("bestorders "))
+            .unwrap();
+        assert_eq!(bestorders_line.split(" ; ").count(), 3);
+    }
+
     #[test]
     fn handle_go_russia_has_four_orders() {
         let mut engine = Engine::new();
@@ -602,6 +2544,46 @@ mod tests {
         assert!(output_str.contains("duiok"));
     }
 
+    #[test]
+    fn handle_dui_advertises_capabilities() {
+        let engine = Engine::new();
+        let mut output = Vec::new();
+        engine.handle_dui(&mut output);
+        let output_str = String::from_utf8(output).unwrap();
+
+        let line = |prefix: &str| {
+            output_str
+                .lines()
+                .find(|l| l.starts_with(prefix))
+                .unwrap_or_else(|| panic!("missing {}", prefix))
+                .to_string()
+        };
+        assert_eq!(
+            line("id version "),
+            format!("id version {}", env!("CARGO_PKG_VERSION"))
+        );
+        let features = line("capability features ");
+        assert!(features.split_whitespace().any(|f| f == "press"));
+        assert_eq!(
+            features.split_whitespace().any(|f| f == "neural"),
+            cfg!(feature = "neural")
+        );
+        assert_eq!(
+            features.split_whitespace().any(|f| f == "parallel"),
+            cfg!(feature = "parallel")
+        );
+        assert!(line("capability extensions ").contains("whynot"));
+        assert_eq!(line("capability maps "), "capability maps standard pure");
+        assert_eq!(
+            line("option name Map "),
+            "option name Map type combo default standard var standard var pure"
+        );
+
+        // Capabilities precede the end of the handshake.
+        let last = output_str.lines().last().unwrap();
+        assert_eq!(last, "duiok");
+    }
+
     #[test]
     fn handle_isready_outputs_readyok() {
         let engine = Engine::new();
@@ -625,6 +2607,431 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_cores_option_is_validated() {
+        let mut engine = Engine::new();
+        let mut output = Vec::new();
+        engine.handle_dui(&mut output);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("option name SearchCores type string default <empty>\n"));
+        assert!(output.contains("option name ReaderNice type spin default 0 min 0 max 19\n"));
+
+        engine.set_option("SearchCores".into(), Some("3-1".into()));
+        assert!(engine.search_cores.is_empty());
+        engine.set_option("ReaderNice".into(), Some("10".into()));
+        assert!(engine.search_cores.is_empty());
+    }
+
+    #[test]
+    fn opening_priors_option_toggles_rm_options() {
+        let mut engine = Engine::new();
+        let mut output = Vec::new();
+        engine.handle_dui(&mut output);
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("option name OpeningPriors type check default true"));
+
+        assert!(engine.rm_options().opening_priors);
+        engine.set_option("OpeningPriors".into(), Some("false".into()));
+        assert!(!engine.rm_options().opening_priors);
+    }
+
+    #[test]
+    fn lookahead_supports_option_defaults_off() {
+        let mut engine = Engine::new();
+        assert!(!engine.rm_options().lookahead_supports);
+        engine.set_option("LookaheadSupports".into(), Some("true".into()));
+        assert!(engine.rm_options().lookahead_supports);
+    }
+
+    #[test]
+    fn local_search_option_defaults_on() {
+        let mut engine = Engine::new();
+        assert!(engine.rm_options().local_search);
+        engine.set_option("LocalSearch".into(), Some("false".into()));
+        assert!(!engine.rm_options().local_search);
+    }
+
+    #[test]
+    fn corner_opening_option_sets_the_style() {
+        let mut engine = Engine::new();
+        let mut out = Vec::new();
+        engine.handle_dui(&mut out);
+        assert!(String::from_utf8(out).unwrap().contains(
+            "option name CornerOpening type combo default standard var standard var consolidate var aggressive"
+        ));
+        assert_eq!(engine.rm_options().corner_opening, OpeningStyle::Standard);
+        engine.set_option("CornerOpening".into(), Some("Consolidate".into()));
+        assert_eq!(
+            engine.rm_options().corner_opening,
+            OpeningStyle::Consolidate
+        );
+        engine.set_option("CornerOpening".into(), Some("turtle".into()));
+        assert_eq!(engine.rm_options().corner_opening, OpeningStyle::Standard);
+    }
+
+    #[test]
+    fn coalition_proof_option_defaults_off() {
+        let mut engine = Engine::new();
+        assert!(!engine.rm_options().coalition_proof);
+        engine.set_option("CoalitionProof".into(), Some("true".into()));
+        assert!(engine.rm_options().coalition_proof);
+    }
+
+    #[test]
+    fn trajectory_years_option_is_clamped() {
+        let mut engine = Engine::new();
+        assert_eq!(engine.rm_options().trajectory_years, 2);
+        engine.set_option("TrajectoryYears".into(), Some("0".into()));
+        assert_eq!(engine.rm_options().trajectory_years, 0);
+        engine.set_option("TrajectoryYears".into(), Some("99".into()));
+        assert_eq!(engine.rm_options().trajectory_years, 10);
+    }
+
+    #[test]
+    fn multi_order_sets_lists_the_chosen_set_first() {
+        let mut engine = Engine::new();
+        assert_eq!(engine.rm_options().multi_order_sets, 0);
+        engine.set_option("MultiOrderSets".into(), Some("99".into()));
+        assert_eq!(engine.rm_options().multi_order_sets, MAX_MULTI_ORDER_SETS);
+
+        engine.set_option("MultiOrderSets".into(), Some("3".into()));
+        engine.set_option("LocalSearch".into(), Some("false".into()));
+        engine.set_option("SearchTime".into(), Some("300".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        let mut out = Vec::new();
+        engine.handle_go_sync(&mut out);
+        let output = String::from_utf8(out).unwrap();
+        let sets: Vec<&str> = output
+            .lines()
+            .filter(|l| l.starts_with("info multiorders "))
+            .collect();
+        assert_eq!(sets.len(), 3, "{}", output);
+        assert!(sets[0].starts_with("info multiorders 1 weight "));
+        assert!(sets[0].contains(" score "));
+        let best = output
+            .lines()
+            .find_map(|l| l.strip_prefix("bestorders "))
+            .expect("bestorders");
+        assert_eq!(sets[0].split(" pv ").nth(1), Some(best));
+        let bestorders_at = output.find("bestorders").unwrap();
+        assert!(output.find("info multiorders 3 ").unwrap() < bestorders_at);
+    }
+
+    #[test]
+    fn cache_size_option_sets_budget() {
+        let mut engine = Engine::new();
+        assert_eq!(engine.rm_options().cache, CacheBudget::default());
+        engine.set_option("CacheSize".into(), Some("64".into()));
+        let budget = engine.rm_options().cache;
+        assert_eq!(budget, CacheBudget::from_mb(64));
+        assert!(budget.future_entries > CacheBudget::default().future_entries);
+        engine.set_option("CacheSize".into(), Some("0".into()));
+        assert_eq!(engine.rm_options().cache.megabytes, 1);
+    }
+
+    #[test]
+    fn info_level_filters_search_output() {
+        let mut engine = Engine::new();
+        let mut output = Vec::new();
+        engine.handle_dui(&mut output);
+        assert!(String::from_utf8(output).unwrap().contains(
+            "option name InfoLevel type combo default normal var quiet var normal var debug"
+        ));
+        assert_eq!(engine.rm_options().candidate_dump, 0);
+        engine.set_option("InfoLevel".into(), Some("debug".into()));
+        assert_eq!(engine.rm_options().candidate_dump, DEBUG_CANDIDATE_DUMP);
+
+        engine.set_option("InfoLevel".into(), Some("quiet".into()));
+        engine.set_option("SearchTime".into(), Some("200".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(
+            !output_str.lines().any(|l| l.starts_with("info ")),
+            "quiet output: {}",
+            output_str
+        );
+        assert!(output_str.contains("bestorders "));
+    }
+
+    #[test]
+    fn go_keeps_search_session_until_new_game() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("200".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        assert!(engine.top_order_sets(3).is_empty());
+
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        let top = engine.top_order_sets(3);
+        assert_eq!(top.len(), 3);
+        assert!(top.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        engine.new_game();
+        assert!(engine.top_order_sets(3).is_empty());
+    }
+
+    #[test]
+    fn illegal_outgoing_orders_are_corrected() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        let orders: Vec<Order> = ["A vie - gal", "F tri S A vie - war", "A bud - ser"]
+            .iter()
+            .map(|s| crate::protocol::dson::parse_order(s).unwrap())
+            .collect();
+
+        let mut output = Vec::new();
+        engine.write_search_output(&mut output, &[], &orders);
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(
+            output_str.contains("info string correction F tri S A vie - war -> F tri H (illegal)")
+        );
+        assert!(output_str.contains("bestorders A vie - gal ; F tri H ; A bud - ser"));
+    }
+
+    #[test]
+    fn locked_orders_are_played_and_cleared_with_the_position() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        engine.set_option("SearchTime".to_string(), Some("300".to_string()));
+        let parse = |s: &str| crate::protocol::dson::parse_order(s).unwrap();
+        engine.lock_orders(vec![parse("A vie - tyr"), parse("A mos H")]);
+        assert_eq!(engine.locked_orders(), &[parse("A vie - tyr")]);
+        assert_eq!(
+            engine.rm_options().constraints.pinned,
+            vec![parse("A vie - tyr")]
+        );
+
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        let output_str = String::from_utf8(output).unwrap();
+        let best = output_str
+            .lines()
+            .find_map(|l| l.strip_prefix("bestorders "))
+            .unwrap();
+        assert!(best.contains("A vie - tyr"), "lock not played: {}", best);
+        assert_eq!(best.matches("A vie").count(), 1);
+
+        engine.set_position(INITIAL_DFEN).unwrap();
+        assert_eq!(engine.locked_orders().len(), 1);
+        engine
+            .set_position("1901fm/Aavie,Aabud,Aftri/Avie,Abud,Atri/-")
+            .unwrap();
+        assert!(engine.locked_orders().is_empty());
+    }
+
+    #[test]
+    fn suggestions_join_the_candidate_pool_until_the_position_changes() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        let parse = |s: &str| crate::protocol::dson::parse_orders(s).unwrap();
+        engine.suggest(parse("A vie - tyr ; A bud - gal ; A mos H"));
+        engine.suggest(parse("A vie - tyr ; A bud - gal"));
+        assert_eq!(engine.suggestions(), &[parse("A vie - tyr ; A bud - gal")]);
+
+        let options = engine.rm_options();
+        let state = engine.position.clone().unwrap();
+        let pool =
+            crate::search::regret_matching::candidate_pool(Power::Austria, &state, 1, &options);
+        assert!(pool.iter().any(|c| parse("A vie - tyr ; A bud - gal")
+            .iter()
+            .all(|o| c.iter().any(|(co, _)| co == o))));
+
+        engine.set_position(INITIAL_DFEN).unwrap();
+        assert_eq!(engine.suggestions().len(), 1);
+        engine.set_power(Power::Italy);
+        assert!(engine.suggestions().is_empty());
+        engine.suggest(parse("A ven - tyr"));
+        engine.execute(
+            &mut std::io::sink(),
+            DuiCommand::Suggest { orders: Vec::new() },
+        );
+        assert!(engine.suggestions().is_empty());
+    }
+
+    #[test]
+    fn record_collects_positions_orders_and_resolutions() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        engine.set_option("SearchTime".to_string(), Some("100".to_string()));
+        engine.handle_go_sync(&mut std::io::sink());
+        let parse = |s: &str| crate::protocol::dson::parse_orders(s).unwrap();
+        engine.record_orders(Power::Italy, &parse("A ven H ; A rom H ; F nap H"));
+        let resolved: Vec<ResolvedOrder> = parse("A ven H ; A rom H ; F nap H")
+            .into_iter()
+            .map(|order| ResolvedOrder {
+                order,
+                power: Power::Italy,
+                result: crate::resolve::OrderResult::Succeeded,
+            })
+            .collect();
+        engine.record_resolution(&resolved);
+
+        let record = engine.record();
+        assert!(record.initial.is_some());
+        assert_eq!(record.phases.len(), 1);
+        let phase = &record.phases[0];
+        assert_eq!(phase.orders_of(Power::Austria).unwrap().len(), 3);
+        assert_eq!(phase.orders_of(Power::Italy).unwrap().len(), 3);
+        assert_eq!(phase.resolved, resolved);
+        assert_eq!(GameRecord::parse(&record.to_string()).unwrap(), *record);
+
+        engine.new_game();
+        assert_eq!(*engine.record(), GameRecord::new());
+    }
+
+    #[test]
+    fn map_option_selects_a_builtin_map() {
+        let mut engine = Engine::new();
+        engine.set_option("Map".to_string(), Some("pure".to_string()));
+        engine.execute(&mut std::io::sink(), DuiCommand::NewGame);
+        assert_eq!(engine.map.name(), "pure");
+
+        engine.set_option("Map".to_string(), Some("atlantis".to_string()));
+        engine.execute(&mut std::io::sink(), DuiCommand::NewGame);
+        assert!(engine.map.is_standard());
+    }
+
+    #[test]
+    fn unreadable_map_file_keeps_the_standard_map() {
+        let mut engine = Engine::new();
+        engine.set_option(
+            "MapFile".to_string(),
+            Some("/nonexistent/variant.json".to_string()),
+        );
+        engine.execute(&mut std::io::sink(), DuiCommand::NewGame);
+        assert!(engine.map.is_standard());
+        engine.set_position(INITIAL_DFEN).unwrap();
+    }
+
+    #[test]
+    fn daide_press_is_answered_and_agreed_terms_reach_the_search() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.execute(
+            &mut std::io::sink(),
+            DuiCommand::SetPower {
+                power: Power::Austria,
+            },
+        );
+        let parse = |s: &str| crate::protocol::dson::parse_order(s).unwrap();
+        engine.lock_orders(vec![parse("A vie - tyr")]);
+
+        let mut out = Vec::new();
+        engine.execute(
+            &mut out,
+            DuiCommand::Press {
+                raw: "italy daide PRP (AND (DMZ (AUS ITA) (TYR GAL)) (XDO ((ITA AMY VEN) HLD)))"
+                    .to_string(),
+            },
+        );
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "press_out italy daide YES (PRP (AND (DMZ (AUS ITA) (TYR GAL)) (XDO ((ITA AMY VEN) HLD))))\n"
+        );
+        let options = engine.rm_options();
+        // The lock into Tyrolia stands; the rest of the DMZ applies.
+        assert_eq!(options.constraints.pinned, vec![parse("A vie - tyr")]);
+        assert_eq!(options.constraints.forbidden, vec![Province::Gal]);
+        assert_eq!(options.agreements.len(), 1);
+        assert_eq!(options.agreements[0].0, Power::Italy);
+        assert_eq!(options.agreements[0].1.pinned, vec![parse("A ven H")]);
+
+        assert!(engine.handle_press("italy daide PRP (DRW)").is_none());
+        engine.execute(&mut std::io::sink(), DuiCommand::NewGame);
+        assert_eq!(engine.press.negotiations.deals().count(), 0);
+    }
+
+    fn unix_ms_from_now(offset_ms: u64) -> String {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        (now.as_millis() as u64 + offset_ms).to_string()
+    }
+
+    #[test]
+    fn host_deadline_applies_margin() {
+        let mut engine = Engine::new();
+        assert!(engine.host_deadline().is_none());
+
+        engine.set_option("Deadline".into(), Some(unix_ms_from_now(10_000)));
+        let left = engine.host_deadline().unwrap() - Instant::now();
+        assert!(left > Duration::from_millis(8_000) && left <= Duration::from_millis(9_000));
+
+        // A deadline already inside the margin is due now.
+        engine.set_option("DeadlineMargin".into(), Some("20000".into()));
+        assert!(engine.host_deadline().unwrap() <= Instant::now());
+    }
+
+    #[test]
+    fn deadline_stops_a_long_search() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("60000".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_option("DeadlineMargin".into(), Some("0".into()));
+        engine.set_option("Deadline".into(), Some(unix_ms_from_now(300)));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        let start = Instant::now();
+        let mut output = Vec::new();
+        engine.handle_go(&mut output, None);
+        while !engine.poll_search_done(&mut output) {
+            assert!(
+                start.elapsed() < Duration::from_secs(30),
+                "deadline ignored"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(output_str.contains("bestorders "), "{}", output_str);
+    }
+
+    #[test]
+    fn unresponsive_search_is_answered_at_deadline() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        let stop = Arc::clone(&engine.stop_flag);
+        engine.search_handle = Some(std::thread::spawn(|| {
+            // Ignores the stop flag entirely.
+            std::thread::sleep(Duration::from_secs(2));
+            SearchOutput {
+                info_buf: Vec::new(),
+                orders: Vec::new(),
+                whynot: None,
+                probabilities: Vec::new(),
+                opponents: Vec::new(),
+            }
+        }));
+        engine.deadline = Some(DeadlineTimer::start(Instant::now(), stop));
+
+        let mut output = Vec::new();
+        assert!(!engine.poll_search_done(&mut output));
+        std::thread::sleep(Duration::from_millis(DEADLINE_GRACE_MS + 50));
+        assert!(engine.poll_search_done(&mut output));
+        assert!(!engine.is_searching());
+
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(output_str.contains("info string error deadline reached"));
+        let orders = output_str
+            .lines()
+            .find_map(|l| l.strip_prefix("bestorders "))
+            .unwrap();
+        assert_eq!(orders.split(" ; ").count(), 3);
+        assert!(!engine.stop_flag.load(Ordering::Relaxed));
+    }
+
     #[test]
     fn book_loaded_from_inline_json() {
         let mut engine = Engine::new();
@@ -783,6 +3190,669 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handle_threatmap_uses_active_power() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Turkey);
+
+        let mut output = Vec::new();
+        engine.handle_threatmap(&mut output, None);
+        let output_str = String::from_utf8(output).unwrap();
+        let json = output_str.trim().strip_prefix("threatmap ").unwrap();
+        assert!(json.contains("\"power\":\"turkey\""), "{}", json);
+
+        let mut output = Vec::new();
+        engine.handle_threatmap(&mut output, Some(Power::Italy));
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(output_str.contains("\"power\":\"italy\""));
+    }
+
+    #[test]
+    fn handle_threatmap_without_position_is_silent() {
+        let engine = Engine::new();
+        let mut output = Vec::new();
+        engine.handle_threatmap(&mut output, Some(Power::Italy));
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn handle_dangers_reports_supported_attack() {
+        let mut engine = Engine::new();
+        engine
+            .set_position("1901fm/Aabud,Ragal,Rarum/Abud,Rrum/-")
+            .unwrap();
+        let mut output = Vec::new();
+        engine.handle_dangers(&mut output, None);
+        assert!(output.is_empty(), "no power set yet");
+
+        engine.set_power(Power::Austria);
+        engine.handle_dangers(&mut output, None);
+        let output_str = String::from_utf8(output).unwrap();
+        let json = output_str.trim().strip_prefix("dangers ").unwrap();
+        let v: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(v["power"], "austria");
+        assert_eq!(v["dangers"][0]["kind"], "supported_attack");
+        assert_eq!(v["dangers"][0]["province"], "bud");
+    }
+
+    #[test]
+    fn forecast_searches_once_and_annotates_dangers() {
+        let mut engine = Engine::new();
+        engine.set_option("BookPath".into(), Some(String::new()));
+        // Two units, so the search has more than one candidate to weigh.
+        engine
+            .set_position("1901sm/Aabud,Aftri,Ragal,Rarum/Abud,Atri,Rrum/-")
+            .unwrap();
+        engine.set_power(Power::Austria);
+        let mut output = Vec::new();
+        engine.execute(&mut output, DuiCommand::Forecast { power: None });
+        let iterations = engine.session.lock().unwrap().iterations();
+        assert!(iterations > 0, "forecast searched the position");
+        let text = String::from_utf8(output).unwrap();
+        let v: serde_json::Value =
+            serde_json::from_str(text.trim().strip_prefix("forecast ").unwrap()).unwrap();
+        assert_eq!(v["power"], "austria");
+        let top = &v["provinces"][0];
+        let province = top["province"].as_str().unwrap().to_string();
+        assert_eq!(top["attackers"][0]["power"], "russia");
+        let p = top["probability"].as_f64().unwrap();
+        assert!(p > 0.0 && p <= 1.0);
+
+        let mut output = Vec::new();
+        engine.execute(&mut output, DuiCommand::Forecast { power: None });
+        assert_eq!(engine.session.lock().unwrap().iterations(), iterations);
+        assert_eq!(String::from_utf8(output).unwrap(), text);
+
+        let mut output = Vec::new();
+        engine.handle_dangers(&mut output, None);
+        let text = String::from_utf8(output).unwrap();
+        let v: serde_json::Value =
+            serde_json::from_str(text.trim().strip_prefix("dangers ").unwrap()).unwrap();
+        let danger = v["dangers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["province"] == province.as_str())
+            .expect("danger to the most pressed province");
+        assert_eq!(danger["attacked"].as_f64(), Some(p));
+    }
+
+    #[test]
+    fn handle_territory_outputs_json() {
+        let mut engine = Engine::new();
+        engine.set_position(INITIAL_DFEN).unwrap();
+        let mut output = Vec::new();
+        engine.handle_territory(&mut output);
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(output_str.starts_with("territory {"), "{}", output_str);
+        assert!(output_str.contains("\"vie\":\"austria\""));
+    }
+
+    #[test]
+    fn observed_dislodgements_lower_trust() {
+        let mut engine = Engine::new();
+        engine.set_power(Power::Austria);
+        for _ in 0..2 {
+            engine
+                .set_position("1901fr/Ravie/Avie,Rwar/Aavie<gal")
+                .unwrap();
+        }
+        assert!(engine.press.trust.trust(Power::Russia) < 0.5);
+        assert_eq!(engine.press.trust.trust(Power::Turkey), 0.5);
+    }
+
+    #[test]
+    fn repeated_bounces_become_a_caution() {
+        let mut engine = Engine::new();
+        let gal = crate::protocol::dson::parse_orders("A vie - gal").unwrap();
+        for turn in ["1901s", "1901f", "1902s"] {
+            engine
+                .set_position(&format!("{}m/Aavie,Rawar/Avie,Rwar/-", turn))
+                .unwrap();
+            engine.record_orders(Power::Austria, &gal);
+        }
+        assert_eq!(engine.contention().get(Province::Gal).bounces, 2);
+        assert_eq!(engine.rm_options().contention, vec![(Province::Gal, 2)]);
+
+        engine.new_game();
+        assert!(engine.rm_options().contention.is_empty());
+    }
+
+    #[test]
+    fn recorded_attacks_open_a_front() {
+        let mut engine = Engine::new();
+        engine.set_power(Power::Austria);
+        engine
+            .set_position("1901sm/Aavie,Aatri,Iaven,Rawar/Atri,Avie,Iven,Rwar/-")
+            .unwrap();
+        engine.record_orders(
+            Power::Austria,
+            &crate::protocol::dson::parse_orders("A tri - ven ; A vie H").unwrap(),
+        );
+        engine
+            .set_position("1901fm/Aavie,Aatri,Iaven,Rawar/Atri,Avie,Iven,Rwar/-")
+            .unwrap();
+        assert_eq!(engine.rm_options().fronts, vec![Power::Italy]);
+
+        let mut output = Vec::new();
+        engine.execute(&mut output, DuiCommand::Alliances);
+        let output_str = String::from_utf8(output).unwrap();
+        let json = output_str.trim().strip_prefix("alliances ").unwrap();
+        let v: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(v["turns"], 1);
+        assert_eq!(v["pairs"][0]["powers"][1], "italy");
+        assert_eq!(v["pairs"][0]["relation"], "front");
+
+        engine.new_game();
+        assert!(engine.rm_options().fronts.is_empty());
+    }
+
+    #[test]
+    fn unreported_moves_open_a_front() {
+        let mut engine = Engine::new();
+        engine.set_power(Power::Austria);
+        engine
+            .set_position("1901fm/Aavie,Rarum/Abud,Avie,Rrum/-")
+            .unwrap();
+        engine
+            .set_position("1902sm/Aavie,Rabud/Avie,Rbud,Rrum/-")
+            .unwrap();
+        assert_eq!(engine.rm_options().fronts, vec![Power::Russia]);
+        assert!(engine.relations.score(Power::Austria, Power::Russia) < 0.0);
+    }
+
+    #[test]
+    fn consecutive_positions_establish_conventions() {
+        let mut engine = Engine::new();
+        for phase in ["1901sm", "1901fm", "1902sm"] {
+            engine
+                .set_position(&format!("{}/Aavie,Rawar/Avie,Rwar/-", phase))
+                .unwrap();
+        }
+        let conventions = engine.rm_options().conventions;
+        assert!(conventions
+            .iter()
+            .any(|c| c.province == crate::board::Province::Gal));
+
+        engine.new_game();
+        assert!(engine.rm_options().conventions.is_empty());
+    }
+
+    #[test]
+    fn setpower_auto_plays_the_focus_or_detected_power() {
+        let mut engine = Engine::new();
+        let mut out = Vec::new();
+        engine
+            .set_position("1901fb/Aavie,Eflon,Efedi,Rawar/Avie,Abud,Atri,Elon,Rwar/-")
+            .unwrap();
+        engine.execute(&mut out, DuiCommand::SetPowerAuto);
+        engine.execute(&mut out, DuiCommand::Go(GoParams::default()));
+        assert_eq!(engine.active_power, Some(Power::Austria));
+        let output = String::from_utf8(std::mem::take(&mut out)).unwrap();
+        assert!(
+            output.contains("info string setpower auto austria\n"),
+            "{}",
+            output
+        );
+        assert!(output.contains("bestorders "), "{}", output);
+
+        let focus = GoParams {
+            focus: Some(Power::England),
+            ..GoParams::default()
+        };
+        engine.execute(&mut out, DuiCommand::Go(focus.clone()));
+        assert_eq!(engine.active_power, Some(Power::England));
+
+        // Turkey has nothing left; a fixed power ignores the focus.
+        engine.execute(
+            &mut out,
+            DuiCommand::SetPower {
+                power: Power::Turkey,
+            },
+        );
+        let output = String::from_utf8(std::mem::take(&mut out)).unwrap();
+        assert!(
+            output.contains("info string warning setpower turkey: eliminated in this position\n"),
+            "{}",
+            output
+        );
+        engine.execute(&mut out, DuiCommand::Go(focus));
+        assert_eq!(engine.active_power, Some(Power::Turkey));
+
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        assert_eq!(detect_power(&state), Some(Power::Russia));
+    }
+
+    #[test]
+    fn votes_on_draw_proposals() {
+        let mut engine = Engine::new();
+        let mut output = Vec::new();
+        engine.handle_draw(&mut output, DrawMessage::Propose { powers: vec![] });
+        assert!(output.is_empty());
+
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        engine.handle_draw(&mut output, DrawMessage::Propose { powers: vec![] });
+        engine.handle_draw(
+            &mut output,
+            DrawMessage::Propose {
+                powers: vec![Power::England, Power::France],
+            },
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "draw accept\ndraw reject\n"
+        );
+
+        engine.handle_draw(&mut Vec::new(), DrawMessage::Accept);
+        assert_eq!(
+            engine.record.result,
+            Some(GameResult::Draw(vec![Power::England, Power::France]))
+        );
+        engine.handle_draw(&mut Vec::new(), DrawMessage::Reject);
+        assert!(engine.draw_proposal.is_none());
+    }
+
+    #[test]
+    fn repeated_positions_warn_of_stagnation() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("50".into()));
+        engine.set_option("Strength".into(), Some("10".into()));
+        engine.set_power(Power::Austria);
+        for year in 1905..=1907 {
+            for season in ['s', 'f'] {
+                engine
+                    .set_position(&format!("{}{}m/Aavie,Rawar/Avie,Rwar/-", year, season))
+                    .unwrap();
+            }
+        }
+        assert_eq!(engine.stagnant_years(), 2);
+        assert_eq!(engine.rm_options().stagnant_years, 2);
+
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains("info string stagnation years 2\n"),
+            "{}",
+            output
+        );
+
+        engine.new_game();
+        assert_eq!(engine.stagnant_years(), 0);
+    }
+
+    #[test]
+    fn ping_answers_mid_search_without_stopping_it() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("3600000".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        let mut output = Vec::new();
+        engine.handle_go(&mut output, None);
+        assert!(engine.is_searching());
+
+        let mut pong = Vec::new();
+        engine.handle_ping(&mut pong, Some("7"));
+        engine.handle_ping(&mut pong, None);
+        assert_eq!(String::from_utf8(pong).unwrap(), "pong 7\npong\n");
+        assert!(engine.is_searching());
+        assert!(!engine.stop_flag.load(Ordering::Relaxed));
+
+        engine.handle_stop(&mut output);
+        assert!(String::from_utf8(output).unwrap().contains("bestorders"));
+    }
+
+    #[test]
+    fn go_names_the_stalemate_lines_it_can_lock() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("100".into()));
+        engine.set_option("Strength".into(), Some("50".into()));
+        engine
+            .set_position(
+                "1910sm/Ifmao,Iagas,Iamar,Iapie,Iaven,Iatri,Tabud,Tarum,Taarm,Fabur,Fatyr,Fagal,Faukr,Famos/\
+                 Flon,Flvp,Fedi,Fbre,Fpar,Fbel,Fhol,Fkie,Fber,Fmun,Fden,Fnwy,Fswe,Fstp,Fmos,Fwar,\
+                 Ipor,Ispa,Imar,Iven,Irom,Inap,Itun,Itri,Tbud,Trum,Tsev,Tser,Tgre,Tbul,Tcon,Tank,Tsmy/-",
+            )
+            .unwrap();
+        engine.set_power(Power::Turkey);
+        let mut out = Vec::new();
+        engine.handle_go_sync(&mut out);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("info string stalemate lockable southern\n"));
+
+        engine.set_power(Power::Italy);
+        let mut out = Vec::new();
+        engine.handle_go_sync(&mut out);
+        assert!(!String::from_utf8(out).unwrap().contains("stalemate"));
+    }
+
+    #[test]
+    fn long_searches_report_their_current_best() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("3600000".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_option("CurrBestInterval".into(), Some("50".into()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        let mut output = Vec::new();
+        engine.handle_go(&mut output, None);
+        let start = Instant::now();
+        let mut interim = Vec::new();
+        while interim.is_empty() && start.elapsed() < Duration::from_secs(60) {
+            assert!(!engine.poll_search_done(&mut interim));
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let interim = String::from_utf8(interim).unwrap();
+        let orders = interim.strip_prefix("info currbest ").unwrap().trim();
+        assert_eq!(parse_orders(orders).unwrap().len(), 3);
+        engine.handle_stop(&mut output);
+        assert!(String::from_utf8(output).unwrap().contains("bestorders"));
+
+        engine.set_option("CurrBestInterval".into(), Some("0".into()));
+        engine.handle_go(&mut Vec::new(), None);
+        assert!(engine.currbest.is_none());
+        engine.abort_search();
+    }
+
+    fn pondering_engine() -> (Engine, Vec<u8>) {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("1000".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+        let mut out = Vec::new();
+        let ponder = GoParams {
+            ponder: true,
+            ..GoParams::default()
+        };
+        engine.execute(&mut out, DuiCommand::Go(ponder));
+        assert!(engine.is_searching());
+        (engine, out)
+    }
+
+    fn wait_for_answer(engine: &mut Engine, out: &mut Vec<u8>) -> String {
+        while !engine.poll_search_done(out) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        String::from_utf8(out.clone()).unwrap()
+    }
+
+    #[test]
+    fn ponderhit_answers_from_the_pondered_search() {
+        let (mut engine, mut out) = pondering_engine();
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(!engine.poll_search_done(&mut out));
+        engine.execute(&mut out, DuiCommand::IsReady);
+        assert!(engine.is_searching(), "isready leaves a ponder running");
+
+        engine.execute(&mut out, DuiCommand::PonderHit);
+        let output = wait_for_answer(&mut engine, &mut out);
+        let line = output
+            .lines()
+            .find(|l| l.starts_with("info string ponderhit "))
+            .expect("ponderhit line");
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        assert!(fields[4].parse::<u64>().unwrap() >= 300);
+        assert!(fields[6].parse::<u64>().unwrap() > 0);
+        assert!(fields[8].parse::<u64>().unwrap() <= 700);
+        assert_eq!(output.matches("bestorders").count(), 1);
+    }
+
+    #[test]
+    fn go_during_ponder_answers_once() {
+        let (mut engine, mut out) = pondering_engine();
+        let go = GoParams {
+            movetime: Some(200),
+            ..GoParams::default()
+        };
+        engine.execute(&mut out, DuiCommand::Go(go));
+        let output = wait_for_answer(&mut engine, &mut out);
+        assert!(output.contains("info string ponderhit "));
+        assert_eq!(output.matches("bestorders").count(), 1);
+    }
+
+    #[test]
+    fn ponder_ends_when_the_position_changes() {
+        let (mut engine, mut out) = pondering_engine();
+        let moved = INITIAL_DFEN.replacen("1901sm", "1901fm", 1);
+        engine.execute(&mut out, DuiCommand::Position { dfen: moved });
+        assert!(!engine.is_searching());
+        assert!(!String::from_utf8(out).unwrap().contains("bestorders"));
+    }
+
+    fn replay_blob(output: &str) -> ReplayBlob {
+        output
+            .lines()
+            .rev()
+            .find_map(|l| l.strip_prefix("info string replay v"))
+            .map(|rest| format!("v{rest}").parse().unwrap())
+            .expect("replay line")
+    }
+
+    #[test]
+    fn replay_search_repeats_the_search() {
+        let mut engine = Engine::new();
+        engine.set_option("SearchTime".into(), Some("300".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        // A second go resumes the first one's pools.
+        let mut out = Vec::new();
+        engine.handle_go_sync(&mut out);
+        out.clear();
+        engine.handle_go_sync(&mut out);
+        let output = String::from_utf8(out).unwrap();
+        let blob = replay_blob(&output);
+        assert_ne!(blob.pools, 0);
+        let best = output
+            .lines()
+            .find_map(|l| l.strip_prefix("bestorders "))
+            .expect("bestorders")
+            .to_string();
+
+        let mut out = Vec::new();
+        engine.execute(&mut out, DuiCommand::ReplaySearch { blob });
+        let replayed = String::from_utf8(out).unwrap();
+        assert_eq!(replay_blob(&replayed), blob);
+        let orders = replayed
+            .lines()
+            .find_map(|l| l.strip_prefix("info string replay orders "))
+            .expect("replay orders");
+        assert_eq!(orders, best);
+
+        let mut out = Vec::new();
+        engine.set_option("SearchTime".into(), Some("301".into()));
+        engine.set_option("LocalSearch".into(), Some("false".into()));
+        engine.execute(&mut out, DuiCommand::ReplaySearch { blob });
+        let refused = String::from_utf8(out).unwrap();
+        assert!(refused.starts_with("info string error replay-search: options hash"));
+    }
+
+    #[test]
+    fn observe_streams_all_powers_after_position() {
+        let mut engine = Engine::new();
+        engine.set_option("ObserveTime".into(), Some("200".into()));
+        engine.set_observe(true);
+        assert!(!engine.is_observing());
+        engine.set_position(INITIAL_DFEN).unwrap();
+        assert!(engine.is_observing());
+
+        let mut output = Vec::new();
+        while engine.is_observing() {
+            engine.poll_observer(&mut output);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 8, "{}", text);
+        assert!(lines[0].starts_with("info observe power austria score "));
+        assert!(lines[6].starts_with("info observe power turkey "));
+        assert!(lines.iter().take(7).all(|l| l.contains(" pv ")));
+        assert!(lines[7].starts_with("info observe done time "));
+
+        engine.set_observe(false);
+        engine.set_position(INITIAL_DFEN).unwrap();
+        assert!(!engine.is_observing());
+    }
+
+    #[test]
+    fn handle_whynot_reports_rejected_candidates() {
+        let mut engine = Engine::new();
+        engine.set_option("Introspection".into(), Some("true".into()));
+        engine.set_option("SearchTime".into(), Some("300".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        let mut output = Vec::new();
+        engine.handle_whynot(&mut output);
+        assert!(output.is_empty());
+
+        engine.handle_go_sync(&mut output);
+        let mut output = Vec::new();
+        engine.handle_whynot(&mut output);
+        let output_str = String::from_utf8(output).unwrap();
+        let json = output_str.strip_prefix("whynot ").expect(&output_str);
+        let v: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+        assert_eq!(v["power"], "austria");
+        assert!(v["rejected"].as_array().unwrap().len() <= 3);
+    }
+
+    #[test]
+    fn order_probabilities_annotate_bestorders_and_predictions() {
+        let mut engine = Engine::new();
+        engine.set_option("OrderProbabilities".into(), Some("true".into()));
+        engine.set_option("SearchTime".into(), Some("300".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        let output_str = String::from_utf8(output).unwrap();
+        let predictions: Vec<&str> = output_str
+            .lines()
+            .filter(|l| l.starts_with("info predict "))
+            .collect();
+        assert_eq!(predictions.len(), 6);
+        assert!(!predictions.iter().any(|l| l.contains(" austria ")));
+        let russia = predictions
+            .iter()
+            .find_map(|l| l.strip_prefix("info predict russia "))
+            .unwrap();
+        assert!(parse_annotated_orders(russia)
+            .unwrap()
+            .iter()
+            .all(|(_, p)| p.is_some()));
+
+        let best = output_str
+            .lines()
+            .find_map(|l| l.strip_prefix("bestorders "))
+            .unwrap();
+        let orders = parse_annotated_orders(best).unwrap();
+        assert_eq!(orders.len(), 3);
+        assert!(orders
+            .iter()
+            .all(|(_, p)| p.is_some_and(|p| p > 0.0 && p <= 1.0)));
+    }
+
+    #[test]
+    fn evalfile_writes_csv_and_reports_counts() {
+        let input = std::env::temp_dir().join(format!(
+            "realpolitik-engine-evalfile-{}.dfen",
+            std::process::id()
+        ));
+        std::fs::write(&input, format!("{}\nbogus\n", INITIAL_DFEN)).unwrap();
+        let mut engine = Engine::new();
+        let mut out = Vec::new();
+        engine.execute(
+            &mut out,
+            DuiCommand::EvalFile {
+                path: input.display().to_string(),
+                output: None,
+            },
+        );
+        let csv_path = format!("{}.csv", input.display());
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&csv_path);
+
+        let line = String::from_utf8(out).unwrap();
+        let report: serde_json::Value =
+            serde_json::from_str(line.trim().strip_prefix("evalfile ").unwrap()).unwrap();
+        assert_eq!(report["positions"], 1);
+        assert_eq!(report["errors"], 1);
+        assert_eq!(report["path"], csv_path.as_str());
+        assert_eq!(csv.lines().count(), 8);
+        assert!(csv.lines().nth(7).unwrap().starts_with("1,turkey,"));
+    }
+
+    #[test]
+    fn search_log_traces_rm_iterations() {
+        let path = std::env::temp_dir().join(format!(
+            "realpolitik-engine-search-log-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut engine = Engine::new();
+        engine.set_option("SearchLog".into(), Some(path.display().to_string()));
+        engine.set_option("SearchLogEvery".into(), Some("4".into()));
+        engine.set_option("SearchTime".into(), Some("300".into()));
+        engine.set_option("BookPath".into(), Some(String::new()));
+        engine.set_position(INITIAL_DFEN).unwrap();
+        engine.set_power(Power::Austria);
+
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["event"], "search");
+        assert_eq!(lines[0]["power"], "austria");
+        assert_eq!(lines.last().unwrap()["event"], "done");
+        let iterations: Vec<u64> = lines[1..lines.len() - 1]
+            .iter()
+            .map(|l| l["iteration"].as_u64().unwrap())
+            .collect();
+        assert!(!iterations.is_empty());
+        assert!(iterations.iter().all(|i| i % 4 == 0));
+        // The trace never reaches the protocol stream.
+        assert!(!String::from_utf8(output).unwrap().contains("\"event\""));
+    }
+
+    #[test]
+    fn handle_go_dispatches_small_endgames() {
+        let mut engine = Engine::new();
+        engine
+            .set_position("1910fm/Aabud,Aaser,Rarum/Abud,Aser,Rrum/-")
+            .unwrap();
+        engine.set_power(Power::Austria);
+
+        let mut output = Vec::new();
+        engine.handle_go_sync(&mut output);
+
+        let output_str = String::from_utf8(output).unwrap();
+        assert!(
+            output_str.contains("info string endgame depth"),
+            "{}",
+            output_str
+        );
+        let bestorders_line = output_str
+            .lines()
+            .find(|l| l.starts_with("bestorders "))
+            .unwrap();
+        assert_eq!(bestorders_line.split(" ; ").count(), 2);
+    }
+
     #[test]
     fn book_with_actual_file() {
         let path = std::path::Path::new(
@@ -0,0 +1,400 @@
+//! Async facade over the search.
+//!
+//! The DUI loop runs searches on a thread it joins by polling; a server
+//! embedding the engine (behind a WebSocket or gRPC front end) would rather
+//! await them. [`search`] starts a search on its own thread and returns a
+//! future resolving to its [`SearchOutput`]. A [`CancellationToken`] stops
+//! the search early, which still resolves the future with the best orders
+//! found so far, and a [`ProgressStream`] yields the `info` lines the search
+//! writes while it runs.
+//!
+//! Nothing here depends on a particular runtime: the futures are woken from
+//! the search thread, so they work under any executor.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use super::{guarded_search, SearchOutput};
+use crate::board::province::Power;
+use crate::board::state::{BoardState, Phase};
+use crate::eval::NeuralEvaluator;
+use crate::search::cartesian;
+use crate::search::endgame;
+use crate::search::{
+    heuristic_build_orders, heuristic_retreat_orders, regret_matching_search_with_options,
+    RmOptions,
+};
+
+/// A search to run.
+#[derive(Clone)]
+pub struct SearchRequest {
+    pub power: Power,
+    pub state: BoardState,
+    pub movetime: Duration,
+    /// 80 and above selects RM+; below, the cartesian search. Small
+    /// endgames go to the endgame search at any strength.
+    pub strength: u64,
+    pub options: RmOptions,
+    pub neural: Option<Arc<NeuralEvaluator>>,
+    /// Trust in each power, in `ALL_POWERS` order.
+    pub trust: Option<[f64; 7]>,
+}
+
+impl SearchRequest {
+    /// A full-strength RM+ search with default options and no network.
+    pub fn new(power: Power, state: BoardState, movetime: Duration) -> Self {
+        SearchRequest {
+            power,
+            state,
+            movetime,
+            strength: 100,
+            options: RmOptions::default(),
+            neural: None,
+            trust: None,
+        }
+    }
+}
+
+/// Stops the searches it was passed to. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks every search holding this token to stop and return its best
+    /// orders so far.
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+}
+
+/// Starts `request` on a search thread.
+///
+/// The future resolves once the search finishes, runs out of time or is
+/// cancelled through `cancel`. Dropping it cancels the search. Retreat and
+/// build phases are answered heuristically and resolve immediately. When
+/// `progress` is given, every `info` line the search writes is sent to it
+/// as it is written; the stream ends when the search does.
+pub fn search(
+    request: SearchRequest,
+    cancel: CancellationToken,
+    progress: Option<ProgressSender>,
+) -> impl Future<Output = SearchOutput> + Send {
+    let slot = Arc::new(Mutex::new(Slot::default()));
+    match answer_without_search(&request) {
+        Some(output) => slot.lock().unwrap().output = Some(output),
+        None => {
+            let shared = Arc::clone(&slot);
+            let stop = Arc::clone(&cancel.flag);
+            std::thread::spawn(move || {
+                let output = run(&request, &stop, progress);
+                let mut slot = shared.lock().unwrap();
+                slot.output = Some(output);
+                if let Some(waker) = slot.waker.take() {
+                    waker.wake();
+                }
+            });
+        }
+    }
+    SearchFuture { slot, cancel }
+}
+
+/// Runs `request` on the calling thread, for callers that already have a
+/// thread per request and nothing to await on.
+pub fn search_blocking(request: &SearchRequest, cancel: &CancellationToken) -> SearchOutput {
+    answer_without_search(request).unwrap_or_else(|| run(request, &cancel.flag, None))
+}
+
+/// Heuristic orders for retreat and build phases, which are not searched.
+fn answer_without_search(request: &SearchRequest) -> Option<SearchOutput> {
+    let state = &request.state;
+    let orders = match state.phase {
+        Phase::Movement => return None,
+        Phase::Retreat => heuristic_retreat_orders(request.power, state),
+        Phase::Build => heuristic_build_orders(request.power, state),
+    };
+    Some(SearchOutput {
+        info_buf: Vec::new(),
+        orders,
+        whynot: None,
+        probabilities: Vec::new(),
+        opponents: Vec::new(),
+    })
+}
+
+fn run(
+    request: &SearchRequest,
+    stop: &AtomicBool,
+    progress: Option<ProgressSender>,
+) -> SearchOutput {
+    guarded_search(
+        request.power,
+        &request.state,
+        request.movetime,
+        |info_buf| {
+            let mut out = ProgressWriter {
+                buf: info_buf,
+                line_start: 0,
+                progress,
+            };
+            if endgame::applies(request.power, &request.state) {
+                if let Some(result) = endgame::endgame_search(
+                    request.power,
+                    &request.state,
+                    request.movetime,
+                    &mut out,
+                    stop,
+                ) {
+                    return result;
+                }
+            }
+            if request.strength >= 80 {
+                regret_matching_search_with_options(
+                    request.power,
+                    &request.state,
+                    request.movetime,
+                    &mut out,
+                    request.neural.as_deref(),
+                    request.strength,
+                    request.trust.as_ref(),
+                    stop,
+                    &request.options,
+                )
+            } else {
+                cartesian::search(
+                    request.power,
+                    &request.state,
+                    request.movetime,
+                    &mut out,
+                    stop,
+                )
+            }
+        },
+    )
+}
+
+#[derive(Default)]
+struct Slot {
+    output: Option<SearchOutput>,
+    waker: Option<Waker>,
+}
+
+/// The future returned by [`search`].
+struct SearchFuture {
+    slot: Arc<Mutex<Slot>>,
+    cancel: CancellationToken,
+}
+
+impl Future for SearchFuture {
+    type Output = SearchOutput;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<SearchOutput> {
+        let mut slot = self.slot.lock().unwrap();
+        match slot.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for SearchFuture {
+    fn drop(&mut self) {
+        let done = self.slot.lock().map_or(true, |s| s.output.is_some());
+        if !done {
+            self.cancel.cancel();
+        }
+    }
+}
+
+/// Collects the search's output into its info buffer and forwards each
+/// complete line to the progress stream.
+struct ProgressWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    line_start: usize,
+    progress: Option<ProgressSender>,
+}
+
+impl Write for ProgressWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if let Some(sender) = &self.progress {
+            while let Some(nl) = self.buf[self.line_start..].iter().position(|&b| b == b'\n') {
+                let end = self.line_start + nl;
+                let line = String::from_utf8_lossy(&self.buf[self.line_start..end]);
+                sender.send(line.into_owned());
+                self.line_start = end + 1;
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    lines: VecDeque<String>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Creates a connected progress sender and stream.
+pub fn progress_channel() -> (ProgressSender, ProgressStream) {
+    let queue = Arc::new(Mutex::new(Queue::default()));
+    (
+        ProgressSender {
+            queue: Arc::clone(&queue),
+        },
+        ProgressStream { queue },
+    )
+}
+
+/// The sending half of a progress channel; the stream ends when it drops.
+pub struct ProgressSender {
+    queue: Arc<Mutex<Queue>>,
+}
+
+impl ProgressSender {
+    fn send(&self, line: String) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.lines.push_back(line);
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for ProgressSender {
+    fn drop(&mut self) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.closed = true;
+            if let Some(waker) = queue.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// The `info` lines a search writes, in order.
+pub struct ProgressStream {
+    queue: Arc<Mutex<Queue>>,
+}
+
+impl ProgressStream {
+    /// The next line, or None once the search has finished and every line
+    /// has been taken.
+    pub fn recv(&mut self) -> impl Future<Output = Option<String>> + '_ {
+        NextLine { stream: self }
+    }
+
+    /// The next line if one is waiting, without blocking.
+    pub fn try_recv(&mut self) -> Option<String> {
+        self.queue.lock().unwrap().lines.pop_front()
+    }
+}
+
+struct NextLine<'a> {
+    stream: &'a mut ProgressStream,
+}
+
+impl Future for NextLine<'_> {
+    type Output = Option<String>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<String>> {
+        let mut queue = self.stream.queue.lock().unwrap();
+        if let Some(line) = queue.lines.pop_front() {
+            Poll::Ready(Some(line))
+        } else if queue.closed {
+            Poll::Ready(None)
+        } else {
+            queue.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = std::pin::pin!(fut);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Ready(v) => return v,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn search_resolves_with_orders_and_streams_progress() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let request = SearchRequest::new(Power::Austria, state, Duration::from_millis(200));
+        let (tx, mut rx) = progress_channel();
+        let output = block_on(search(request, CancellationToken::new(), Some(tx)));
+        assert_eq!(output.orders.len(), 3);
+
+        let mut lines = Vec::new();
+        while let Some(line) = block_on(rx.recv()) {
+            lines.push(line);
+        }
+        assert!(lines.iter().any(|l| l.starts_with("info depth")));
+        assert!(rx.try_recv().is_none());
+    }
+
+    #[test]
+    fn cancelled_search_still_answers() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let request = SearchRequest::new(Power::France, state, Duration::from_secs(60));
+        let cancel = CancellationToken::new();
+        let fut = search(request, cancel.clone(), None);
+        cancel.cancel();
+        assert!(cancel.is_cancelled());
+        let output = block_on(fut);
+        assert_eq!(output.orders.len(), 3);
+    }
+
+    #[test]
+    fn build_phase_resolves_immediately() {
+        let state = parse_dfen("1901fb/Aavie/Avie,Abud/-").unwrap();
+        let request = SearchRequest::new(Power::Austria, state, Duration::from_secs(60));
+        let output = block_on(search(request, CancellationToken::new(), None));
+        assert_eq!(output.orders.len(), 1);
+    }
+}
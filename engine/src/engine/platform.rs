@@ -0,0 +1,139 @@
+//! Thread placement and priority.
+//!
+//! On a shared tournament machine, search threads that migrate between
+//! cores and a stdin reader competing with them for CPU time both show up
+//! as latency jitter. This module holds the engine's only OS-specific
+//! code: pinning the calling thread to a set of cores, and changing the
+//! scheduling priority of a thread by its id. Both are implemented on
+//! Linux; elsewhere they fail with [`io::ErrorKind::Unsupported`] and the
+//! engine carries on unpinned.
+
+use std::io;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Operating-system id of a thread, as [`current_thread_id`] returns it.
+pub type ThreadId = i64;
+
+/// Id of the thread reading commands, once it has registered.
+static READER_THREAD: AtomicI64 = AtomicI64::new(-1);
+
+/// Parses a core list such as `0-3,6` into core indices, ascending and
+/// without repeats. An empty list means no pinning.
+pub fn parse_cores(spec: &str) -> Result<Vec<usize>, String> {
+    let mut cores = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let bad = || format!("invalid core list '{}': expected e.g. 0-3,6", spec);
+        let (lo, hi) = match part.split_once('-') {
+            Some((lo, hi)) => (lo.trim(), hi.trim()),
+            None => (part, part),
+        };
+        let lo: usize = lo.parse().map_err(|_| bad())?;
+        let hi: usize = hi.parse().map_err(|_| bad())?;
+        if lo > hi {
+            return Err(bad());
+        }
+        cores.extend(lo..=hi);
+    }
+    cores.sort_unstable();
+    cores.dedup();
+    Ok(cores)
+}
+
+/// Restricts the calling thread to `cores`.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cores: &[usize]) -> io::Result<()> {
+    // SAFETY: cpu_set_t is plain data, zeroed is the empty set, and
+    // CPU_SET ignores indices past its capacity.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &core in cores {
+            libc::CPU_SET(core, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(cores: &[usize]) -> io::Result<()> {
+    let _ = cores;
+    Err(unsupported())
+}
+
+/// Id of the calling thread, if the platform has one to offer.
+#[cfg(target_os = "linux")]
+pub fn current_thread_id() -> Option<ThreadId> {
+    // SAFETY: gettid has no preconditions.
+    Some(unsafe { libc::gettid() } as ThreadId)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn current_thread_id() -> Option<ThreadId> {
+    None
+}
+
+/// Sets the nice value of `thread`: 0 is normal, 19 the lowest priority.
+/// Raising priority back after lowering it may need privileges.
+#[cfg(target_os = "linux")]
+pub fn set_thread_nice(thread: ThreadId, nice: i32) -> io::Result<()> {
+    // SAFETY: setpriority only reads its arguments.
+    let rc = unsafe { libc::setpriority(libc::PRIO_PROCESS, thread as libc::id_t, nice) };
+    if rc != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_thread_nice(thread: ThreadId, nice: i32) -> io::Result<()> {
+    let _ = (thread, nice);
+    Err(unsupported())
+}
+
+/// Records the calling thread as the command reader, for `ReaderNice`.
+pub fn register_reader_thread() {
+    if let Some(id) = current_thread_id() {
+        READER_THREAD.store(id, Ordering::Relaxed);
+    }
+}
+
+/// The registered command reader thread, if any.
+pub fn reader_thread() -> Option<ThreadId> {
+    let id = READER_THREAD.load(Ordering::Relaxed);
+    (id >= 0).then_some(id)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread placement is only supported on Linux",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_core_lists() {
+        assert_eq!(parse_cores(""), Ok(vec![]));
+        assert_eq!(parse_cores("0-3,6"), Ok(vec![0, 1, 2, 3, 6]));
+        assert_eq!(parse_cores(" 2, 1-2 "), Ok(vec![1, 2]));
+        assert!(parse_cores("3-1").is_err());
+        assert!(parse_cores("a").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pins_and_renices_a_thread() {
+        std::thread::spawn(|| {
+            pin_current_thread(&[0]).unwrap();
+            set_thread_nice(current_thread_id().unwrap(), 5).unwrap();
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -0,0 +1,346 @@
+//! Immediate threats against one power.
+//!
+//! The threat map counts enemy units next to every province; this module
+//! turns the same information into a short list of concrete dangers a
+//! player should look at this turn, each with a severity score:
+//!
+//! - **attack** / **supported attack**: a rival can take one of the power's
+//!   centers or dislodge one of its units, judged by the static exchange
+//!   from the attacker's side;
+//! - **convoy landing**: a rival army can be convoyed by its own fleets onto
+//!   a center or unit it cannot reach overland;
+//! - **cut support**: the power holds a province only with supporters that
+//!   enemy units not engaged there can attack, cutting the support.
+//!
+//! Severity is the attacker's spare strength plus the stake: two for a
+//! supply center, one for a unit that would be dislodged. Like the
+//! exchange itself, the analysis assumes rivals do not combine.
+
+use serde_json::json;
+
+use crate::board::province::{Coast, Power, Province, ProvinceType, MAX_PROVINCES};
+use crate::board::state::BoardState;
+use crate::board::unit::UnitType;
+use crate::eval::exchange::static_exchange;
+use crate::eval::heuristic::unit_can_reach;
+
+/// The kind of an immediate threat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DangerKind {
+    /// A single unit can walk in.
+    Attack,
+    /// A rival brings a mover plus at least one supporter and wins.
+    SupportedAttack,
+    /// A rival army lands by convoy on a province it cannot reach overland.
+    ConvoyLanding,
+    /// A won defence depends on supporters that can be cut.
+    CutSupport,
+}
+
+impl DangerKind {
+    /// The name used in JSON output.
+    pub fn name(self) -> &'static str {
+        match self {
+            DangerKind::Attack => "attack",
+            DangerKind::SupportedAttack => "supported_attack",
+            DangerKind::ConvoyLanding => "convoy_landing",
+            DangerKind::CutSupport => "cut_support",
+        }
+    }
+}
+
+/// One threat against a province the power owns or occupies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Danger {
+    pub kind: DangerKind,
+    /// The province at risk.
+    pub province: Province,
+    /// The rival behind the threat.
+    pub attacker: Power,
+    /// Units involved: the rival's units for attacks and landings (army
+    /// first, then fleets), the power's cuttable supporters for cut support.
+    pub via: Vec<Province>,
+    /// Higher is more urgent; always at least one.
+    pub severity: i32,
+}
+
+/// Lists the immediate threats against `power`, most severe first.
+pub fn dangers(power: Power, state: &BoardState) -> Vec<Danger> {
+    let mut out = Vec::new();
+    for &prov in state.map().provinces() {
+        let idx = prov.index();
+        let ours_here = state.units[idx].is_some_and(|(p, _)| p == power);
+        let owned = state.sc_owner[idx] == Some(power);
+        if !ours_here && !owned {
+            continue;
+        }
+        let stake = if owned { 2 } else { 0 } + i32::from(ours_here);
+
+        let ex = static_exchange(prov, power, state);
+        if let Some(rival) = ex.opponent {
+            let theirs = static_exchange(prov, rival, state);
+            if theirs.wins() {
+                out.push(Danger {
+                    kind: if theirs.ours >= 2 {
+                        DangerKind::SupportedAttack
+                    } else {
+                        DangerKind::Attack
+                    },
+                    province: prov,
+                    attacker: rival,
+                    via: reaching(prov, rival, state),
+                    severity: theirs.margin() + 1 + stake,
+                });
+            } else if ours_here {
+                if let Some(d) = cut_support(prov, power, rival, ex.theirs, state) {
+                    out.push(Danger {
+                        severity: d.severity + stake,
+                        ..d
+                    });
+                }
+            }
+        }
+
+        if state.map().province_type(prov) == ProvinceType::Coastal {
+            for (army, fleets) in convoy_landings(prov, power, state) {
+                let rival = state.units[army.index()].unwrap().0;
+                // The landed army adds one to whatever the rival brings overland.
+                let strength = static_exchange(prov, rival, state).ours + 1;
+                let wins = strength > ex.ours;
+                let mut via = vec![army];
+                via.extend(fleets);
+                out.push(Danger {
+                    kind: DangerKind::ConvoyLanding,
+                    province: prov,
+                    attacker: rival,
+                    via,
+                    severity: stake + i32::from(wins),
+                });
+            }
+        }
+    }
+    out.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(a.province.index().cmp(&b.province.index()))
+    });
+    out
+}
+
+/// Serializes the dangers for a power as a single-line JSON object.
+/// `attacked` gives, from a search forecast, the probability each province
+/// is moved into next turn; dangers to provinces it lists carry that
+/// probability as `attacked`. Pass an empty slice without a forecast.
+///
+/// Format: `{"power":"austria","dangers":[{"kind":"supported_attack","province":"bud","attacker":"russia","severity":4,"via":["gal","rum"],"attacked":0.41},...]}`
+pub fn dangers_json(power: Power, state: &BoardState, attacked: &[(Province, f64)]) -> String {
+    let list: Vec<serde_json::Value> = dangers(power, state)
+        .iter()
+        .map(|d| {
+            let mut v = json!({
+                "kind": d.kind.name(),
+                "province": state.map().abbr(d.province),
+                "attacker": d.attacker.name(),
+                "severity": d.severity,
+                "via": d.via.iter().map(|&p| state.map().abbr(p)).collect::<Vec<_>>(),
+            });
+            if let Some(&(_, p)) = attacked.iter().find(|(prov, _)| *prov == d.province) {
+                v["attacked"] = json!((p * 1000.0).round() / 1000.0);
+            }
+            v
+        })
+        .collect();
+    json!({ "power": power.name(), "dangers": list }).to_string()
+}
+
+/// Provinces of `power`'s units that can move into (or already hold) `target`.
+fn reaching(target: Province, power: Power, state: &BoardState) -> Vec<Province> {
+    state
+        .map()
+        .provinces()
+        .iter()
+        .copied()
+        .filter(|&prov| {
+            let i = prov.index();
+            match state.units[i] {
+                Some((p, ut)) if p == power => {
+                    let coast = state.fleet_coast[i].unwrap_or(Coast::None);
+                    prov == target || unit_can_reach(state.map(), prov, coast, ut, target)
+                }
+                _ => false,
+            }
+        })
+        .collect()
+}
+
+/// Checks whether `power` still holds `province` against `rival` once the
+/// supporters that free enemy units can attack are cut. Cutters must not be
+/// able to reach the province themselves, so cutting never weakens the
+/// attack. Returns the danger without the stake added.
+fn cut_support(
+    province: Province,
+    power: Power,
+    rival: Power,
+    theirs: i32,
+    state: &BoardState,
+) -> Option<Danger> {
+    let supporters: Vec<Province> = reaching(province, power, state)
+        .into_iter()
+        .filter(|&p| p != province)
+        .collect();
+    let mut used = [false; MAX_PROVINCES];
+    let mut cut = Vec::new();
+    for &sup in &supporters {
+        let cutter = state.map().provinces().iter().copied().find(|&prov| {
+            let i = prov.index();
+            match state.units[i] {
+                Some((p, ut)) if p != power && !used[i] => {
+                    let coast = state.fleet_coast[i].unwrap_or(Coast::None);
+                    unit_can_reach(state.map(), prov, coast, ut, sup)
+                        && !unit_can_reach(state.map(), prov, coast, ut, province)
+                }
+                _ => false,
+            }
+        });
+        if let Some(prov) = cutter {
+            used[prov.index()] = true;
+            cut.push(sup);
+        }
+    }
+    let remaining = 1 + supporters.len() as i32 - cut.len() as i32;
+    if cut.is_empty() || remaining >= theirs {
+        return None;
+    }
+    Some(Danger {
+        kind: DangerKind::CutSupport,
+        province,
+        attacker: rival,
+        via: cut,
+        severity: theirs - remaining,
+    })
+}
+
+/// Rival armies that can be convoyed onto `target` by fleets of their own
+/// power but cannot move there overland, with the fleets forming the chain.
+fn convoy_landings(
+    target: Province,
+    power: Power,
+    state: &BoardState,
+) -> Vec<(Province, Vec<Province>)> {
+    let mut out = Vec::new();
+    for &army in state.map().provinces() {
+        let rival = match state.units[army.index()] {
+            Some((p, UnitType::Army)) if p != power => p,
+            _ => continue,
+        };
+        if army == target || unit_can_reach(state.map(), army, Coast::None, UnitType::Army, target)
+        {
+            continue;
+        }
+        let is_rival_fleet_at_sea = |prov: Province| {
+            state.map().province_type(prov) == ProvinceType::Sea
+                && state.units[prov.index()] == Some((rival, UnitType::Fleet))
+        };
+        // Breadth-first search over the rival's fleets at sea.
+        let mut chain: Vec<Province> = state
+            .map()
+            .adj_from(army)
+            .iter()
+            .filter(|a| a.fleet_ok && is_rival_fleet_at_sea(a.to))
+            .map(|a| a.to)
+            .collect();
+        chain.dedup();
+        let mut i = 0;
+        let mut lands = false;
+        while i < chain.len() {
+            let sea = chain[i];
+            for adj in state.map().adj_from(sea).iter().filter(|a| a.fleet_ok) {
+                if adj.to == target {
+                    lands = true;
+                } else if is_rival_fleet_at_sea(adj.to) && !chain.contains(&adj.to) {
+                    chain.push(adj.to);
+                }
+            }
+            i += 1;
+        }
+        if lands {
+            out.push((army, chain));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+
+    #[test]
+    fn supported_attack_on_owned_center() {
+        // Russia brings gal and rum against Austria's lone A bud.
+        let state = parse_dfen("1901fm/Aabud,Ragal,Rarum/Abud,Rrum/-").unwrap();
+        let list = dangers(Power::Austria, &state);
+        assert_eq!(list.len(), 1);
+        let d = &list[0];
+        assert_eq!(d.kind, DangerKind::SupportedAttack);
+        assert_eq!(d.province, Province::Bud);
+        assert_eq!(d.attacker, Power::Russia);
+        assert_eq!(d.via, vec![Province::Gal, Province::Rum]);
+        // Margin 0, plus a center and a unit at stake.
+        assert_eq!(d.severity, 4);
+    }
+
+    #[test]
+    fn convoy_landing_onto_coastal_center() {
+        // England's A lon can be convoyed via nth and hel onto Germany's
+        // empty kie.
+        let state = parse_dfen("1901fm/Ealon,Efnth,Efhel/Gkie/-").unwrap();
+        let list = dangers(Power::Germany, &state);
+        let landing = list
+            .iter()
+            .find(|d| d.kind == DangerKind::ConvoyLanding)
+            .unwrap();
+        assert_eq!(landing.province, Province::Kie);
+        assert_eq!(landing.attacker, Power::England);
+        assert_eq!(landing.via[0], Province::Lon);
+        assert!(landing.via.contains(&Province::Hel));
+    }
+
+    #[test]
+    fn cuttable_supporter_is_reported() {
+        // Austria holds ser with bud's support against Turkey's bul and
+        // gre; Russia's A gal cannot reach ser but can cut bud.
+        let state = parse_dfen("1902sm/Aaser,Aabud,Tabul,Tagre,Ragal/Aser,Abud/-").unwrap();
+        let list = dangers(Power::Austria, &state);
+        let cut = list
+            .iter()
+            .find(|d| d.kind == DangerKind::CutSupport)
+            .unwrap();
+        assert_eq!(cut.province, Province::Ser);
+        assert_eq!(cut.attacker, Power::Turkey);
+        assert_eq!(cut.via, vec![Province::Bud]);
+        // Shortfall of one, plus a center and a unit at stake.
+        assert_eq!(cut.severity, 4);
+    }
+
+    #[test]
+    fn json_and_quiet_board() {
+        let state = parse_dfen("1901sm/Aavie/Avie/-").unwrap();
+        assert!(dangers(Power::Austria, &state).is_empty());
+
+        let state = parse_dfen("1901fm/Aabud,Ragal,Rarum/Abud,Rrum/-").unwrap();
+        let s = dangers_json(Power::Austria, &state, &[]);
+        let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(v["power"], "austria");
+        assert_eq!(v["dangers"][0]["kind"], "supported_attack");
+        assert_eq!(v["dangers"][0]["province"], "bud");
+        assert_eq!(v["dangers"][0]["attacker"], "russia");
+        assert_eq!(v["dangers"][0]["via"], serde_json::json!(["gal", "rum"]));
+        assert!(!s.contains('\n'));
+        assert!(v["dangers"][0].get("attacked").is_none());
+
+        let s = dangers_json(Power::Austria, &state, &[(Province::Bud, 0.6254)]);
+        let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(v["dangers"][0]["attacked"], 0.625);
+    }
+}
@@ -0,0 +1,245 @@
+//! Draw votes.
+//!
+//! When the host relays a draw proposal, the engine votes for the active
+//! power. [`draw_vote`] accepts a draw that includes it unless it can still
+//! hope to win: the value network gives it a real chance of a solo, or its
+//! center count is still climbing in a game that is not stagnant. A draw
+//! that excludes it is always rejected, and one that stops another power
+//! on the verge of a solo is always accepted.
+//!
+//! The center trend comes from [`CenterHistory`], which remembers each
+//! power's center count year by year from the positions the engine sees.
+
+use crate::board::province::{Power, ALL_POWERS};
+use crate::board::state::BoardState;
+use crate::eval::solo::solo_distance;
+use crate::search::repetition::STAGNATION_YEARS;
+
+/// A value-network win probability at or above which we play on.
+pub const MAX_DRAW_WIN_PROB: f32 = 0.1;
+
+/// Centers short of a solo at which another power's threat makes any draw
+/// welcome.
+pub const SOLO_THREAT_CENTERS: u32 = 2;
+
+/// Years over which the center trend is measured.
+pub const TREND_YEARS: i32 = 2;
+
+/// A vote on a proposed draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawVote {
+    Accept,
+    Reject,
+}
+
+impl DrawVote {
+    /// The vote's word in the `draw` reply.
+    pub fn name(self) -> &'static str {
+        match self {
+            DrawVote::Accept => "accept",
+            DrawVote::Reject => "reject",
+        }
+    }
+}
+
+/// What a vote was based on, beyond the position.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DrawSignals {
+    /// The value network's win probability for the voter, if loaded.
+    pub win_prob: Option<f32>,
+    /// Change in the voter's center count over [`TREND_YEARS`].
+    pub center_trend: i32,
+    /// Consecutive years the position has stood unchanged.
+    pub stagnant_years: u32,
+}
+
+/// Center counts per year, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct CenterHistory {
+    years: Vec<(i32, [u8; 7])>,
+}
+
+impl CenterHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets all observations.
+    pub fn reset(&mut self) {
+        self.years.clear();
+    }
+
+    /// Records the center counts of `state`. A later position of the same
+    /// year replaces the earlier; an earlier year (a rewind) drops the
+    /// years after it.
+    pub fn observe(&mut self, state: &BoardState) {
+        let year = state.year as i32;
+        let mut counts = [0u8; 7];
+        for owner in state.sc_owner.iter().flatten() {
+            counts[*owner as usize] += 1;
+        }
+        self.years.retain(|&(y, _)| y < year);
+        self.years.push((year, counts));
+    }
+
+    /// How many centers `power` gained (or, negative, lost) over the last
+    /// `years` years, measured from the oldest record when there are fewer.
+    pub fn trend(&self, power: Power, years: i32) -> i32 {
+        let Some(&(last, now)) = self.years.last() else {
+            return 0;
+        };
+        let before = self
+            .years
+            .iter()
+            .find(|&&(y, _)| y >= last - years)
+            .map_or(now, |&(_, counts)| counts);
+        now[power as usize] as i32 - before[power as usize] as i32
+    }
+}
+
+/// Powers still holding a center, the default party to a draw.
+pub fn survivors(state: &BoardState) -> Vec<Power> {
+    ALL_POWERS
+        .iter()
+        .copied()
+        .filter(|&p| state.sc_owner.contains(&Some(p)))
+        .collect()
+}
+
+/// How `power` votes on a draw shared by `parties` in `state`.
+pub fn draw_vote(
+    power: Power,
+    parties: &[Power],
+    state: &BoardState,
+    signals: &DrawSignals,
+) -> DrawVote {
+    if !parties.contains(&power) {
+        return DrawVote::Reject;
+    }
+    let threatened = ALL_POWERS
+        .iter()
+        .filter(|&&p| p != power)
+        .any(|&p| solo_distance(p, state).is_some_and(|d| d <= SOLO_THREAT_CENTERS));
+    if threatened {
+        return DrawVote::Accept;
+    }
+    if signals.win_prob.is_some_and(|w| w >= MAX_DRAW_WIN_PROB) {
+        return DrawVote::Reject;
+    }
+    let growing = signals.center_trend > 0 && signals.stagnant_years < STAGNATION_YEARS;
+    // Without a network, growth toward a reachable solo is our chance.
+    if growing && (signals.win_prob.is_some() || solo_distance(power, state).is_some()) {
+        return DrawVote::Reject;
+    }
+    DrawVote::Accept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::Province;
+    use crate::board::state::{Phase, Season};
+    use crate::protocol::dfen::parse_dfen;
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn trend_counts_centers_over_recent_years() {
+        let mut state = parse_dfen(INITIAL_DFEN).unwrap();
+        let mut history = CenterHistory::new();
+        history.observe(&state);
+        assert_eq!(history.trend(Power::Austria, TREND_YEARS), 0);
+
+        state.set_turn(1902, state.season, state.phase);
+        state.set_sc_owner(Province::Ser, Some(Power::Austria));
+        history.observe(&state);
+        state.set_turn(1903, state.season, state.phase);
+        state.set_sc_owner(Province::Rum, Some(Power::Austria));
+        state.set_sc_owner(Province::Vie, Some(Power::Russia));
+        history.observe(&state);
+        assert_eq!(history.trend(Power::Austria, 1), 0);
+        assert_eq!(history.trend(Power::Austria, TREND_YEARS), 1);
+        assert_eq!(history.trend(Power::Russia, TREND_YEARS), 1);
+
+        // A rewind forgets the years after it.
+        let mut rewound = parse_dfen(INITIAL_DFEN).unwrap();
+        rewound.set_turn(1902, rewound.season, rewound.phase);
+        history.observe(&rewound);
+        assert_eq!(history.trend(Power::Austria, TREND_YEARS), 0);
+        assert_eq!(history.trend(Power::Russia, TREND_YEARS), 0);
+    }
+
+    #[test]
+    fn votes_for_draws_it_cannot_improve_on() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let all = survivors(&state);
+        assert_eq!(all.len(), 7);
+        let flat = DrawSignals::default();
+        assert_eq!(
+            draw_vote(Power::Austria, &all, &state, &flat),
+            DrawVote::Accept
+        );
+        assert_eq!(
+            draw_vote(Power::Austria, &all[1..], &state, &flat),
+            DrawVote::Reject
+        );
+
+        let winning = DrawSignals {
+            win_prob: Some(0.3),
+            ..flat
+        };
+        assert_eq!(
+            draw_vote(Power::Austria, &all, &state, &winning),
+            DrawVote::Reject
+        );
+        let growing = DrawSignals {
+            win_prob: Some(0.05),
+            center_trend: 2,
+            ..flat
+        };
+        assert_eq!(
+            draw_vote(Power::Austria, &all, &state, &growing),
+            DrawVote::Reject
+        );
+        // Growth in a stagnant game is no reason to play on.
+        let stuck = DrawSignals {
+            stagnant_years: STAGNATION_YEARS,
+            ..growing
+        };
+        assert_eq!(
+            draw_vote(Power::Austria, &all, &state, &stuck),
+            DrawVote::Accept
+        );
+    }
+
+    #[test]
+    fn accepts_any_draw_that_stops_a_solo() {
+        let mut state = BoardState::empty(1910, Season::Spring, Phase::Movement);
+        let centers: Vec<Province> = crate::board::province::ALL_PROVINCES
+            .iter()
+            .copied()
+            .filter(|p| state.map().is_supply_center(*p))
+            .collect();
+        for &c in &centers[..17] {
+            state.set_sc_owner(c, Some(Power::France));
+        }
+        for &c in &centers[17..27] {
+            state.set_sc_owner(c, Some(Power::Italy));
+        }
+        let winning = DrawSignals {
+            win_prob: Some(0.5),
+            center_trend: 3,
+            stagnant_years: 0,
+        };
+        let parties = survivors(&state);
+        assert_eq!(solo_distance(Power::France, &state), Some(1));
+        assert_eq!(
+            draw_vote(Power::Italy, &parties, &state, &winning),
+            DrawVote::Accept
+        );
+        assert_eq!(
+            draw_vote(Power::France, &parties, &state, &winning),
+            DrawVote::Reject
+        );
+    }
+}
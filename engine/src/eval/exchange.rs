@@ -0,0 +1,157 @@
+//! Static exchange evaluation for contested provinces.
+//!
+//! Raw threat counts say how many enemy units touch a province, not who
+//! wins when both sides commit everything they have next to it. The
+//! exchange calculator answers that: it counts the strength each side can
+//! bring (a unit moving in or holding, plus every other unit that can
+//! support it) and reports whether `power` takes or keeps the province and
+//! how many units it must commit to do so.
+//!
+//! Opposing powers are assumed not to combine: the strongest single power
+//! is the opponent. Support cutting is ignored, so the result is the
+//! outcome when every supporter is free, as in a chess static exchange.
+
+use crate::board::province::{Coast, Power, Province, ALL_POWERS};
+use crate::board::state::BoardState;
+use crate::eval::heuristic::unit_can_reach;
+
+/// Result of a static exchange at one province.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exchange {
+    /// Whether `power` has a unit in the province (defends rather than attacks).
+    pub occupied: bool,
+    /// Strength `power` can bring: its occupant or one mover, plus supporters.
+    pub ours: i32,
+    /// Strength of the strongest other power at the province.
+    pub theirs: i32,
+    /// The opposing power with that strength, if any can reach it.
+    pub opponent: Option<Power>,
+}
+
+impl Exchange {
+    /// Returns true if `power` takes (or keeps) the province when both sides
+    /// commit fully. A defender holds on a tie; an attacker must exceed.
+    pub fn wins(&self) -> bool {
+        if self.occupied {
+            self.ours >= self.theirs
+        } else {
+            self.ours > 0 && self.ours > self.theirs
+        }
+    }
+
+    /// Returns true if any other power can contest the province.
+    pub fn contested(&self) -> bool {
+        self.theirs > 0
+    }
+
+    /// Units `power` must commit to win: the mover or occupant plus the
+    /// supporters needed. `None` when the exchange is lost.
+    pub fn cost(&self) -> Option<i32> {
+        if !self.wins() {
+            return None;
+        }
+        Some(if self.occupied {
+            self.theirs.max(1)
+        } else {
+            self.theirs + 1
+        })
+    }
+
+    /// Strength `power` has to spare after winning, or the shortfall
+    /// (negative) when losing.
+    pub fn margin(&self) -> i32 {
+        if self.occupied {
+            self.ours - self.theirs
+        } else {
+            self.ours - self.theirs - 1
+        }
+    }
+}
+
+/// Computes the static exchange at `province` from `power`'s side.
+pub fn static_exchange(province: Province, power: Power, state: &BoardState) -> Exchange {
+    let mut strength = [0i32; 7];
+    let occupant = state.units[province.index()].map(|(p, _)| p);
+
+    for (i, unit) in state.units.iter().enumerate() {
+        let Some((p, unit_type)) = *unit else {
+            continue;
+        };
+        let prov = Province::from_index(i);
+        let coast = state.fleet_coast[i].unwrap_or(Coast::None);
+        if prov == province || unit_can_reach(state.map(), prov, coast, unit_type, province) {
+            strength[p.index()] += 1;
+        }
+    }
+
+    let mut theirs = 0;
+    let mut opponent = None;
+    for &p in ALL_POWERS.iter() {
+        let s = strength[p.index()];
+        if p != power && s > theirs {
+            theirs = s;
+            opponent = Some(p);
+        }
+    }
+    // Occupants win ties among opponents: they hold, the others bounce.
+    if let Some(occ) = occupant.filter(|&o| o != power) {
+        if strength[occ.index()] == theirs {
+            opponent = Some(occ);
+        }
+    }
+
+    Exchange {
+        occupied: occupant == Some(power),
+        ours: strength[power.index()],
+        theirs,
+        opponent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+
+    #[test]
+    fn supported_attack_beats_lone_defender() {
+        // Austria brings ser and gre against Turkey's lone A bul.
+        let state = parse_dfen("1902sm/Aaser,Aagre,Tabul/Aser,Agre,Tbul/-").unwrap();
+        let ex = static_exchange(Province::Bul, Power::Austria, &state);
+        assert!(!ex.occupied);
+        assert_eq!((ex.ours, ex.theirs), (2, 1));
+        assert_eq!(ex.opponent, Some(Power::Turkey));
+        assert!(ex.wins());
+        assert_eq!(ex.cost(), Some(2));
+
+        let defence = static_exchange(Province::Bul, Power::Turkey, &state);
+        assert!(defence.occupied);
+        assert!(!defence.wins());
+        assert_eq!(defence.cost(), None);
+        assert_eq!(defence.margin(), -1);
+    }
+
+    #[test]
+    fn defender_holds_on_a_tie_and_attacker_needs_more() {
+        // Turkey holds bul with con behind it; Austria brings ser and gre.
+        let state = parse_dfen("1902sm/Aaser,Aagre,Tabul,Tacon/Aser,Agre,Tbul,Tcon/-").unwrap();
+        let ex = static_exchange(Province::Bul, Power::Austria, &state);
+        assert_eq!((ex.ours, ex.theirs), (2, 2));
+        assert!(!ex.wins());
+
+        let defence = static_exchange(Province::Bul, Power::Turkey, &state);
+        assert!(defence.wins());
+        assert_eq!(defence.cost(), Some(2));
+        assert_eq!(defence.margin(), 0);
+    }
+
+    #[test]
+    fn uncontested_province() {
+        let state = parse_dfen("1901sm/Aavie/Avie/-").unwrap();
+        let ex = static_exchange(Province::Gal, Power::Austria, &state);
+        assert!(!ex.contested());
+        assert!(ex.wins());
+        assert_eq!(ex.cost(), Some(1));
+        assert_eq!(ex.opponent, None);
+    }
+}
@@ -5,85 +5,15 @@
 //! threat/defense balance, and solo threat detection.
 //!
 //! Design: all hot-path evaluation functions operate on fixed-size arrays
-//! indexed by `Province as usize` and `Power as usize` -- no heap allocation.
-//! The BFS distance matrices are computed once via `LazyLock` and reused.
+//! indexed by `Province::index` and `Power as usize` -- no heap allocation.
+//! The BFS distance matrices are computed once per map and reused.
 
-use std::collections::VecDeque;
-use std::sync::LazyLock;
-
-use crate::board::adjacency::adj_from;
-use crate::board::province::{
-    Coast, Power, Province, ALL_POWERS, ALL_PROVINCES, PROVINCE_COUNT, SUPPLY_CENTER_COUNT,
-};
+use crate::board::map::GameMap;
+use crate::board::province::{Coast, Power, Province, ALL_POWERS, MAX_PROVINCES};
 use crate::board::state::{BoardState, Season};
 use crate::board::unit::UnitType;
-
-/// Pre-computed BFS distance matrix between all province pairs.
-pub(crate) struct DistMatrix {
-    dist: Box<[i16; PROVINCE_COUNT * PROVINCE_COUNT]>,
-    sc_indices: [u8; SUPPLY_CENTER_COUNT],
-}
-
-pub(crate) static ARMY_DIST: LazyLock<DistMatrix> = LazyLock::new(|| build_dist_matrix(false));
-pub(crate) static FLEET_DIST: LazyLock<DistMatrix> = LazyLock::new(|| build_dist_matrix(true));
-
-/// Builds a BFS distance matrix using either army or fleet adjacencies.
-fn build_dist_matrix(fleet: bool) -> DistMatrix {
-    let mut dist = vec![-1i16; PROVINCE_COUNT * PROVINCE_COUNT];
-
-    for i in 0..PROVINCE_COUNT {
-        dist[i * PROVINCE_COUNT + i] = 0;
-    }
-
-    let mut queue = VecDeque::with_capacity(PROVINCE_COUNT);
-
-    for src in 0..PROVINCE_COUNT {
-        queue.clear();
-        queue.push_back((src, 0i16));
-
-        while let Some((cur, d)) = queue.pop_front() {
-            let cur_prov = ALL_PROVINCES[cur];
-            for adj in adj_from(cur_prov) {
-                if fleet && !adj.fleet_ok {
-                    continue;
-                }
-                if !fleet && !adj.army_ok {
-                    continue;
-                }
-                let to_idx = adj.to as usize;
-                if dist[src * PROVINCE_COUNT + to_idx] == -1 {
-                    dist[src * PROVINCE_COUNT + to_idx] = d + 1;
-                    queue.push_back((to_idx, d + 1));
-                }
-            }
-        }
-    }
-
-    let mut sc_indices = [0u8; SUPPLY_CENTER_COUNT];
-    let mut sc_count = 0;
-    for (i, p) in ALL_PROVINCES.iter().enumerate() {
-        if p.is_supply_center() {
-            sc_indices[sc_count] = i as u8;
-            sc_count += 1;
-        }
-    }
-    debug_assert_eq!(sc_count, SUPPLY_CENTER_COUNT);
-
-    let boxed: Box<[i16; PROVINCE_COUNT * PROVINCE_COUNT]> =
-        dist.into_boxed_slice().try_into().unwrap();
-    DistMatrix {
-        dist: boxed,
-        sc_indices,
-    }
-}
-
-impl DistMatrix {
-    #[inline]
-    #[allow(dead_code)]
-    pub(crate) fn distance(&self, from: Province, to: Province) -> i16 {
-        self.dist[from as usize * PROVINCE_COUNT + to as usize]
-    }
-}
+use crate::eval::solo::SOLO_CENTERS;
+use crate::eval::stalemate::line_bonus;
 
 /// Returns the distance from a province to the nearest unowned SC,
 /// using the appropriate distance matrix for the unit type.
@@ -94,15 +24,19 @@ pub(crate) fn nearest_unowned_sc_dist(
     state: &BoardState,
     is_fleet: bool,
 ) -> i16 {
-    let dm = if is_fleet { &*FLEET_DIST } else { &*ARMY_DIST };
-    let pi = province as usize;
+    let dm = if is_fleet {
+        state.map().fleet_dist()
+    } else {
+        state.map().army_dist()
+    };
+    let pi = province.index();
     let mut best: i16 = -1;
 
     for &sci in dm.sc_indices.iter() {
         if state.sc_owner[sci as usize] == Some(power) {
             continue;
         }
-        let d = dm.dist[pi * PROVINCE_COUNT + sci as usize];
+        let d = dm.raw()[pi * MAX_PROVINCES + sci as usize];
         if d < 0 {
             continue;
         }
@@ -116,13 +50,14 @@ pub(crate) fn nearest_unowned_sc_dist(
 /// Returns true if the given unit can reach the target in one move.
 #[inline]
 pub(crate) fn unit_can_reach(
+    map: &GameMap,
     unit_prov: Province,
     unit_coast: Coast,
     unit_type: UnitType,
     target: Province,
 ) -> bool {
     let is_fleet = unit_type == UnitType::Fleet;
-    for adj in adj_from(unit_prov) {
+    for adj in map.adj_from(unit_prov) {
         if adj.to != target {
             continue;
         }
@@ -152,9 +87,9 @@ pub(crate) fn province_threat(province: Province, power: Power, state: &BoardSta
             if *p == power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
+            let prov = Province::from_index(i);
             let coast = state.fleet_coast[i].unwrap_or(Coast::None);
-            if unit_can_reach(prov, coast, *ut, province) {
+            if unit_can_reach(state.map(), prov, coast, *ut, province) {
                 count += 1;
             }
         }
@@ -171,12 +106,12 @@ pub(crate) fn province_defense(province: Province, power: Power, state: &BoardSt
             if *p != power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
+            let prov = Province::from_index(i);
             if prov == province {
                 continue;
             }
             let coast = state.fleet_coast[i].unwrap_or(Coast::None);
-            if unit_can_reach(prov, coast, *ut, province) {
+            if unit_can_reach(state.map(), prov, coast, *ut, province) {
                 count += 1;
             }
         }
@@ -184,6 +119,40 @@ pub(crate) fn province_defense(province: Province, power: Power, state: &BoardSt
     count
 }
 
+/// Counts own units that cannot reach the province themselves but can reach
+/// one of its [`province_defense`] supporters: second-order support, which
+/// keeps a defence standing when a supporter is cut or dislodged.
+pub(crate) fn province_chain_defense(province: Province, power: Power, state: &BoardState) -> i32 {
+    let own_units: Vec<(usize, Province, Coast, UnitType)> = state
+        .units
+        .iter()
+        .enumerate()
+        .filter_map(|(i, u)| match u {
+            Some((p, ut)) if *p == power && Province::from_index(i) != province => Some((
+                i,
+                Province::from_index(i),
+                state.fleet_coast[i].unwrap_or(Coast::None),
+                *ut,
+            )),
+            _ => None,
+        })
+        .collect();
+    let supporters: Vec<Province> = own_units
+        .iter()
+        .filter(|(_, prov, coast, ut)| unit_can_reach(state.map(), *prov, *coast, *ut, province))
+        .map(|(_, prov, _, _)| *prov)
+        .collect();
+    own_units
+        .iter()
+        .filter(|(_, prov, coast, ut)| {
+            !supporters.contains(prov)
+                && supporters
+                    .iter()
+                    .any(|&s| unit_can_reach(state.map(), *prov, *coast, *ut, s))
+        })
+        .count() as i32
+}
+
 /// Counts how many SCs a power owns.
 #[inline]
 pub(crate) fn count_scs(state: &BoardState, power: Power) -> i32 {
@@ -205,6 +174,85 @@ pub(crate) fn power_has_units(state: &BoardState, power: Power) -> bool {
         .any(|u| matches!(u, Some((p, _)) if *p == power))
 }
 
+/// Number of units of each power that can move into each province this turn.
+///
+/// Built in one pass over the units; answers the same questions as
+/// [`province_threat`], [`province_defense`] and [`province_chain_defense`]
+/// without rescanning the board.
+pub(crate) struct ReachTable {
+    counts: [[u8; 7]; MAX_PROVINCES],
+    /// Per province, a bit for each province whose unit can move into it.
+    reachers: [u128; MAX_PROVINCES],
+    /// Per power, a bit for each province its units stand in.
+    occupied: [u128; 7],
+}
+
+impl ReachTable {
+    pub(crate) fn new(state: &BoardState) -> Self {
+        let mut counts = [[0u8; 7]; MAX_PROVINCES];
+        let mut reachers = [0u128; MAX_PROVINCES];
+        let mut occupied = [0u128; 7];
+        for (i, unit_opt) in state.units.iter().enumerate() {
+            if let Some((p, ut)) = unit_opt {
+                occupied[*p as usize] |= 1u128 << i;
+                let coast = state.fleet_coast[i].unwrap_or(Coast::None);
+                let is_fleet = *ut == UnitType::Fleet;
+                let mut seen: u128 = 0;
+                for adj in state.map().adj_from(Province::from_index(i)) {
+                    if (is_fleet && !adj.fleet_ok) || (!is_fleet && !adj.army_ok) {
+                        continue;
+                    }
+                    if coast != Coast::None
+                        && adj.from_coast != Coast::None
+                        && adj.from_coast != coast
+                    {
+                        continue;
+                    }
+                    let bit = 1u128 << adj.to.index() as u32;
+                    if seen & bit == 0 {
+                        seen |= bit;
+                        counts[adj.to.index()][*p as usize] += 1;
+                        reachers[adj.to.index()] |= 1u128 << i;
+                    }
+                }
+            }
+        }
+        ReachTable {
+            counts,
+            reachers,
+            occupied,
+        }
+    }
+
+    /// Units of `power` that can reach `province`, as [`province_defense`].
+    #[inline]
+    pub(crate) fn defense(&self, province: Province, power: Power) -> i32 {
+        self.counts[province.index()][power as usize] as i32
+    }
+
+    /// Second-order support of `province`, as [`province_chain_defense`].
+    #[inline]
+    pub(crate) fn chain_defense(&self, province: Province, power: Power) -> i32 {
+        let own = self.occupied[power as usize] & !(1u128 << province.index() as u32);
+        let first = self.reachers[province.index()] & own;
+        let mut second = 0u128;
+        let mut rest = first;
+        while rest != 0 {
+            let s = rest.trailing_zeros() as usize;
+            rest &= rest - 1;
+            second |= self.reachers[s] & own;
+        }
+        (second & !first).count_ones() as i32
+    }
+
+    /// Units of other powers that can reach `province`, as [`province_threat`].
+    #[inline]
+    pub(crate) fn threat(&self, province: Province, power: Power) -> i32 {
+        let row = &self.counts[province.index()];
+        row.iter().map(|&c| c as i32).sum::<i32>() - row[power as usize] as i32
+    }
+}
+
 /// Evaluates a board position for the given power. Returns a score in centipawn-like units.
 ///
 /// Components (ported from Go `EvaluatePosition`):
@@ -216,6 +264,12 @@ pub(crate) fn power_has_units(state: &BoardState, power: Power) -> bool {
 /// - Enemy strength penalty (total + strongest enemy bonus)
 /// - Elimination bonus (fewer alive enemies)
 pub fn evaluate(power: Power, state: &BoardState) -> f32 {
+    evaluate_with(power, state, &ReachTable::new(state))
+}
+
+/// [`evaluate`] with a prebuilt reach table, so batch evaluation shares it.
+fn evaluate_with(power: Power, state: &BoardState, reach: &ReachTable) -> f32 {
+    crate::throughput::record_eval();
     let mut score: f32 = 0.0;
 
     let own_scs = count_scs(state, power);
@@ -244,9 +298,9 @@ pub fn evaluate(power: Power, state: &BoardState) -> f32 {
             }
             unit_count += 1;
 
-            let prov = ALL_PROVINCES[i];
+            let prov = Province::from_index(i);
 
-            if prov.is_supply_center() && state.sc_owner[i] != Some(power) {
+            if state.map().is_supply_center(prov) && state.sc_owner[i] != Some(power) {
                 score += pending_bonus;
             }
 
@@ -265,21 +319,16 @@ pub fn evaluate(power: Power, state: &BoardState) -> f32 {
         if *owner_opt != Some(power) {
             continue;
         }
-        let prov = ALL_PROVINCES[i];
-        if !prov.is_supply_center() {
+        let prov = Province::from_index(i);
+        if !state.map().is_supply_center(prov) {
             continue;
         }
-        let threat = province_threat(prov, power, state);
-        let defense = province_defense(prov, power, state);
-        if threat > defense {
-            let mut penalty = 2.0 * (threat - defense) as f32;
-            if own_scs >= 16 {
-                penalty *= 0.2;
-            } else if own_scs >= 14 {
-                penalty *= 0.5;
-            }
-            score -= penalty;
-        }
+        score -= vulnerability(
+            reach.threat(prov, power),
+            reach.defense(prov, power),
+            reach.chain_defense(prov, power),
+            own_scs,
+        );
     }
 
     let mut total_enemy: i32 = 0;
@@ -304,21 +353,137 @@ pub fn evaluate(power: Power, state: &BoardState) -> f32 {
     let eliminated_bonus = (6 - alive_enemies) as f32 * 8.0;
     score += eliminated_bonus;
 
+    if max_enemy >= SOLO_CENTERS as i32 - 2 {
+        score += line_bonus(power, state);
+    }
+
     score
 }
 
-/// Evaluates the position for all 7 powers.
-pub fn evaluate_all(state: &BoardState) -> [f32; 7] {
+/// Defensive worth of a second-order supporter, relative to a direct one.
+const SUPPORT_CHAIN_WEIGHT: f32 = 0.5;
+
+/// Penalty for an owned SC that `threat` enemy units can reach and
+/// `defense` of ours can support, with `chain` more able to support those
+/// supporters. Each second-order supporter backs at most one direct one,
+/// so a fortress with depth counts for more than the same line one deep.
+fn vulnerability(threat: i32, defense: i32, chain: i32, own_scs: i32) -> f32 {
+    let held = defense as f32 + SUPPORT_CHAIN_WEIGHT * chain.min(defense) as f32;
+    if threat as f32 <= held {
+        return 0.0;
+    }
+    let mut penalty = 2.0 * (threat as f32 - held);
+    if own_scs >= 16 {
+        penalty *= 0.2;
+    } else if own_scs >= 14 {
+        penalty *= 0.5;
+    }
+    penalty
+}
+
+/// A set of powers, one bit per power in `ALL_POWERS` order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PowerMask(u8);
+
+impl PowerMask {
+    /// Every power.
+    pub const ALL: PowerMask = PowerMask(0x7f);
+    /// No power.
+    pub const NONE: PowerMask = PowerMask(0);
+
+    /// A mask holding just `power`.
+    pub const fn only(power: Power) -> PowerMask {
+        PowerMask(1 << power as u8)
+    }
+
+    /// Returns the mask with `power` added.
+    pub const fn with(self, power: Power) -> PowerMask {
+        PowerMask(self.0 | 1 << power as u8)
+    }
+
+    /// Returns true if `power` is in the mask.
+    pub const fn contains(self, power: Power) -> bool {
+        self.0 & (1 << power as u8) != 0
+    }
+}
+
+impl FromIterator<Power> for PowerMask {
+    fn from_iter<I: IntoIterator<Item = Power>>(iter: I) -> Self {
+        iter.into_iter().fold(PowerMask::NONE, PowerMask::with)
+    }
+}
+
+/// Evaluation of several powers from one pass over the board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchEval {
+    /// Which powers were evaluated.
+    pub mask: PowerMask,
+    /// `evaluate(power, state)` for powers in the mask, 0.0 for the rest.
+    pub scores: [f32; 7],
+    /// Scores as shares of the masked powers' total: negative scores count
+    /// as zero and the shares sum to 1. Evenly split if no score is positive;
+    /// 0.0 outside the mask.
+    pub normalized: [f32; 7],
+    /// `threats[a][b]`: supply centers owned by power `b` that a unit of
+    /// power `a` can move into this turn. Filled for every pair.
+    pub threats: [[i32; 7]; 7],
+}
+
+/// Evaluates the powers in `mask` and the pairwise threat matrix, sharing
+/// one reach table across them.
+pub fn evaluate_powers(state: &BoardState, mask: PowerMask) -> BatchEval {
+    let reach = ReachTable::new(state);
+
     let mut scores = [0.0f32; 7];
-    for (i, &p) in ALL_POWERS.iter().enumerate() {
-        scores[i] = evaluate(p, state);
+    for &p in ALL_POWERS.iter() {
+        if mask.contains(p) {
+            scores[p as usize] = evaluate_with(p, state, &reach);
+        }
+    }
+
+    let members = ALL_POWERS.iter().filter(|&&p| mask.contains(p)).count();
+    let total: f32 = scores.iter().map(|s| s.max(0.0)).sum();
+    let mut normalized = [0.0f32; 7];
+    for &p in ALL_POWERS.iter() {
+        if !mask.contains(p) {
+            continue;
+        }
+        normalized[p as usize] = if total > 0.0 {
+            scores[p as usize].max(0.0) / total
+        } else {
+            1.0 / members as f32
+        };
+    }
+
+    let mut threats = [[0i32; 7]; 7];
+    for (i, owner) in state.sc_owner.iter().enumerate() {
+        if let Some(owner) = owner {
+            let prov = Province::from_index(i);
+            for &attacker in ALL_POWERS.iter() {
+                if attacker != *owner && reach.defense(prov, attacker) > 0 {
+                    threats[attacker as usize][*owner as usize] += 1;
+                }
+            }
+        }
     }
-    scores
+
+    BatchEval {
+        mask,
+        scores,
+        normalized,
+        threats,
+    }
+}
+
+/// Evaluates the position for all 7 powers.
+pub fn evaluate_all(state: &BoardState) -> [f32; 7] {
+    evaluate_powers(state, PowerMask::ALL).scores
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::province::ALL_PROVINCES;
     use crate::board::state::Phase;
     use crate::protocol::dfen::parse_dfen;
 
@@ -332,7 +497,8 @@ mod tests {
 
     #[test]
     fn army_dist_self_is_zero() {
-        let dm = &*ARMY_DIST;
+        let map = GameMap::standard();
+        let dm = map.army_dist();
         for p in ALL_PROVINCES.iter() {
             assert_eq!(dm.distance(*p, *p), 0);
         }
@@ -340,7 +506,8 @@ mod tests {
 
     #[test]
     fn fleet_dist_self_is_zero() {
-        let dm = &*FLEET_DIST;
+        let map = GameMap::standard();
+        let dm = map.fleet_dist();
         for p in ALL_PROVINCES.iter() {
             assert_eq!(dm.distance(*p, *p), 0);
         }
@@ -348,40 +515,46 @@ mod tests {
 
     #[test]
     fn army_dist_adjacent_is_one() {
-        let dm = &*ARMY_DIST;
+        let map = GameMap::standard();
+        let dm = map.army_dist();
         assert_eq!(dm.distance(Province::Vie, Province::Boh), 1);
         assert_eq!(dm.distance(Province::Boh, Province::Vie), 1);
     }
 
     #[test]
     fn army_dist_two_steps() {
-        let dm = &*ARMY_DIST;
+        let map = GameMap::standard();
+        let dm = map.army_dist();
         assert_eq!(dm.distance(Province::Vie, Province::Mun), 2);
     }
 
     #[test]
     fn fleet_dist_sea_to_sea() {
-        let dm = &*FLEET_DIST;
+        let map = GameMap::standard();
+        let dm = map.fleet_dist();
         assert_eq!(dm.distance(Province::Eng, Province::Nth), 1);
     }
 
     #[test]
     fn army_cannot_reach_sea() {
-        let dm = &*ARMY_DIST;
+        let map = GameMap::standard();
+        let dm = map.army_dist();
         assert_eq!(dm.distance(Province::Vie, Province::Adr), -1);
     }
 
     #[test]
     fn fleet_cannot_reach_inland() {
-        let dm = &*FLEET_DIST;
+        let map = GameMap::standard();
+        let dm = map.fleet_dist();
         assert_eq!(dm.distance(Province::Nth, Province::Mun), -1);
     }
 
     #[test]
     fn sc_indices_are_valid() {
-        let dm = &*ARMY_DIST;
+        let map = GameMap::standard();
+        let dm = map.army_dist();
         for &idx in dm.sc_indices.iter() {
-            assert!(ALL_PROVINCES[idx as usize].is_supply_center());
+            assert!(map.is_supply_center(ALL_PROVINCES[idx as usize]));
         }
     }
 
@@ -390,6 +563,7 @@ mod tests {
     #[test]
     fn army_can_reach_adjacent_land() {
         assert!(unit_can_reach(
+            &GameMap::standard(),
             Province::Vie,
             Coast::None,
             UnitType::Army,
@@ -400,6 +574,7 @@ mod tests {
     #[test]
     fn army_unit_cannot_reach_sea() {
         assert!(!unit_can_reach(
+            &GameMap::standard(),
             Province::Vie,
             Coast::None,
             UnitType::Army,
@@ -410,6 +585,7 @@ mod tests {
     #[test]
     fn fleet_can_reach_adjacent_sea() {
         assert!(unit_can_reach(
+            &GameMap::standard(),
             Province::Lon,
             Coast::None,
             UnitType::Fleet,
@@ -420,24 +596,28 @@ mod tests {
     #[test]
     fn fleet_coast_respects_from_coast() {
         assert!(unit_can_reach(
+            &GameMap::standard(),
             Province::Stp,
             Coast::South,
             UnitType::Fleet,
             Province::Bot
         ));
         assert!(!unit_can_reach(
+            &GameMap::standard(),
             Province::Stp,
             Coast::South,
             UnitType::Fleet,
             Province::Bar
         ));
         assert!(unit_can_reach(
+            &GameMap::standard(),
             Province::Stp,
             Coast::North,
             UnitType::Fleet,
             Province::Bar
         ));
         assert!(!unit_can_reach(
+            &GameMap::standard(),
             Province::Stp,
             Coast::North,
             UnitType::Fleet,
@@ -462,6 +642,33 @@ mod tests {
         assert!(defense >= 1);
     }
 
+    #[test]
+    fn second_order_support_counts_units_behind_the_supporters() {
+        // A bud supports vie and A rum can support bud; F tri and A sev
+        // reach neither.
+        let state = parse_dfen("1903sm/Aavie,Aabud,Aarum,Aftri,Aasev,Ragal,Raboh/Avie,Abud,Atri/-")
+            .unwrap();
+        assert_eq!(province_defense(Province::Vie, Power::Austria, &state), 1);
+        assert_eq!(
+            province_chain_defense(Province::Vie, Power::Austria, &state),
+            1
+        );
+        let reach = ReachTable::new(&state);
+        assert_eq!(reach.chain_defense(Province::Vie, Power::Austria), 1);
+        assert_eq!(reach.chain_defense(Province::Gal, Power::Russia), 0);
+    }
+
+    #[test]
+    fn depth_softens_the_vulnerability_penalty() {
+        assert_eq!(vulnerability(2, 2, 0, 5), 0.0);
+        let shallow = vulnerability(3, 1, 0, 5);
+        let deep = vulnerability(3, 1, 3, 5);
+        assert_eq!(shallow, 4.0);
+        // One second-order supporter counts, backing the one direct one.
+        assert_eq!(deep, 3.0);
+        assert_eq!(vulnerability(3, 1, 3, 16), 0.6);
+    }
+
     // --- count_scs tests ---
 
     #[test]
@@ -542,7 +749,7 @@ mod tests {
         let all_scs: Vec<Province> = ALL_PROVINCES
             .iter()
             .copied()
-            .filter(|p| p.is_supply_center())
+            .filter(|p| state.map().is_supply_center(*p))
             .collect();
         for (i, &sc) in all_scs.iter().enumerate() {
             if i < 18 {
@@ -572,7 +779,7 @@ mod tests {
         spring.place_unit(Province::Ser, Power::Austria, UnitType::Army, Coast::None);
 
         let mut fall = spring.clone();
-        fall.season = Season::Fall;
+        fall.set_turn(fall.year, Season::Fall, fall.phase);
 
         let spring_score = evaluate(Power::Austria, &spring);
         let fall_score = evaluate(Power::Austria, &fall);
@@ -598,8 +805,8 @@ mod tests {
         state1.place_unit(Province::Lon, Power::England, UnitType::Fleet, Coast::None);
 
         let mut state2 = state1.clone();
-        state2.sc_owner[Province::Par as usize] = Some(Power::Austria);
-        state2.units[Province::Par as usize] = None;
+        state2.set_sc_owner(Province::Par, Some(Power::Austria));
+        state2.remove_unit(Province::Par);
 
         let score_4_enemies = evaluate(Power::Austria, &state1);
         let score_3_enemies = evaluate(Power::Austria, &state2);
@@ -622,6 +829,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reach_table_matches_threat_and_defense() {
+        let state = initial_state();
+        let reach = ReachTable::new(&state);
+        for &prov in ALL_PROVINCES.iter() {
+            for &p in ALL_POWERS.iter() {
+                assert_eq!(reach.threat(prov, p), province_threat(prov, p, &state));
+                assert_eq!(reach.defense(prov, p), province_defense(prov, p, &state));
+                assert_eq!(
+                    reach.chain_defense(prov, p),
+                    province_chain_defense(prov, p, &state)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn masked_batch_matches_single_evaluation() {
+        let state = initial_state();
+        let mask = PowerMask::only(Power::Austria).with(Power::Turkey);
+        let batch = evaluate_powers(&state, mask);
+
+        assert_eq!(
+            batch.scores[Power::Austria as usize],
+            evaluate(Power::Austria, &state)
+        );
+        assert_eq!(
+            batch.scores[Power::Turkey as usize],
+            evaluate(Power::Turkey, &state)
+        );
+        assert_eq!(batch.scores[Power::France as usize], 0.0);
+        assert_eq!(batch.normalized[Power::France as usize], 0.0);
+        let total: f32 = batch.normalized.iter().sum();
+        assert!((total - 1.0).abs() < 1e-5, "shares sum to {}", total);
+
+        let all = evaluate_powers(&state, PowerMask::ALL);
+        assert_eq!(all.scores, evaluate_all(&state));
+        assert_eq!(
+            ALL_POWERS.iter().copied().collect::<PowerMask>(),
+            PowerMask::ALL
+        );
+    }
+
+    #[test]
+    fn threat_matrix_counts_reachable_enemy_centers() {
+        let mut state = BoardState::empty(1903, Season::Spring, Phase::Movement);
+        state.set_sc_owner(Province::War, Some(Power::Russia));
+        state.set_sc_owner(Province::Vie, Some(Power::Austria));
+        state.set_sc_owner(Province::Bud, Some(Power::Austria));
+        state.place_unit(Province::Gal, Power::Russia, UnitType::Army, Coast::None);
+
+        let batch = evaluate_powers(&state, PowerMask::NONE);
+        let t = &batch.threats;
+        assert_eq!(t[Power::Russia as usize][Power::Austria as usize], 2);
+        assert_eq!(t[Power::Austria as usize][Power::Russia as usize], 0);
+        assert_eq!(t[Power::Russia as usize][Power::Russia as usize], 0);
+        assert_eq!(batch.scores, [0.0; 7]);
+    }
+
     #[test]
     fn vulnerability_penalty_applied() {
         let mut defended = BoardState::empty(1903, Season::Spring, Phase::Movement);
@@ -6,8 +6,26 @@
 //! Ported from `api/internal/bot/search_util.go` (EvaluatePosition) and
 //! `api/internal/bot/eval.go` (distance matrices, threat/defense helpers).
 
+pub mod batch;
+pub mod dangers;
+pub mod draw;
+pub mod exchange;
 pub(crate) mod heuristic;
 pub mod neural;
+pub mod regions;
+pub mod solo;
+pub mod stalemate;
+pub mod territory;
+pub mod threatmap;
 
-pub use heuristic::{evaluate, evaluate_all};
+pub use batch::{evaluate_file, BatchSummary};
+pub use dangers::{dangers, dangers_json, Danger, DangerKind};
+pub use draw::{draw_vote, survivors, CenterHistory, DrawSignals, DrawVote};
+pub use exchange::{static_exchange, Exchange};
+pub use heuristic::{evaluate, evaluate_all, evaluate_powers, BatchEval, PowerMask};
 pub use neural::NeuralEvaluator;
+pub use regions::{theater_forces, theater_of, Theater, TheaterForces, ALL_THEATERS};
+pub use solo::{solo_distance, SOLO_CENTERS};
+pub use stalemate::{line_bonus, lockable_lines, solo_leader, StalemateLine, STALEMATE_LINES};
+pub use territory::{territory_counts, territory_json, territory_map, territory_score};
+pub use threatmap::{threat_map, threat_map_json, AreaThreat};
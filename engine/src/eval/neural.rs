@@ -1,7 +1,9 @@
 //! Neural network evaluation via ONNX Runtime.
 //!
 //! Loads policy and value ONNX models and runs inference using the `ort` crate.
-//! Falls back to heuristic evaluation when no model is available.
+//! Falls back to heuristic evaluation when no model is available, and on
+//! maps other than the standard one, which the networks' inputs and
+//! outputs are laid out for.
 
 #[cfg(feature = "neural")]
 use ort::session::{builder::GraphOptimizationLevel, Session};
@@ -93,12 +95,17 @@ impl NeuralEvaluator {
     /// Runs the policy network on a single position.
     ///
     /// Returns order logits as a flat f32 vector. Returns None if no
-    /// policy model is loaded or if inference fails.
+    /// policy model is loaded, the position is not on the standard map, or
+    /// inference fails.
     pub fn policy(&self, state: &BoardState, power: Power) -> Option<Vec<f32>> {
         #[cfg(feature = "neural")]
         {
+            if !state.map().is_standard() {
+                return None;
+            }
             let mutex = self.policy_session.as_ref()?;
             let mut session = mutex.lock().ok()?;
+            crate::throughput::record_inferences(1);
             run_policy_inference(&mut session, &self.adjacency, state, power)
         }
         #[cfg(not(feature = "neural"))]
@@ -111,12 +118,17 @@ impl NeuralEvaluator {
     /// Runs the value network on a single position.
     ///
     /// Returns [sc_share, win_prob, draw_prob, survival_prob] for the given power.
-    /// Returns None if no value model is loaded or if inference fails.
+    /// Returns None if no value model is loaded, the position is not on the
+    /// standard map, or inference fails.
     pub fn value(&self, state: &BoardState, power: Power) -> Option<[f32; VALUE_OUTPUT_SIZE]> {
         #[cfg(feature = "neural")]
         {
+            if !state.map().is_standard() {
+                return None;
+            }
             let mutex = self.value_session.as_ref()?;
             let mut session = mutex.lock().ok()?;
+            crate::throughput::record_inferences(1);
             run_value_inference(&mut session, &self.adjacency, state, power)
         }
         #[cfg(not(feature = "neural"))]
@@ -142,8 +154,12 @@ impl NeuralEvaluator {
     pub fn policy_batch(&self, states: &[(&BoardState, Power)]) -> Option<Vec<Vec<f32>>> {
         #[cfg(feature = "neural")]
         {
+            if states.iter().any(|(state, _)| !state.map().is_standard()) {
+                return None;
+            }
             let mutex = self.policy_session.as_ref()?;
             let mut session = mutex.lock().ok()?;
+            crate::throughput::record_inferences(states.len() as u64);
             run_policy_batch(&mut session, &self.adjacency, states)
         }
         #[cfg(not(feature = "neural"))]
@@ -160,8 +176,12 @@ impl NeuralEvaluator {
     ) -> Option<Vec<[f32; VALUE_OUTPUT_SIZE]>> {
         #[cfg(feature = "neural")]
         {
+            if states.iter().any(|(state, _)| !state.map().is_standard()) {
+                return None;
+            }
             let mutex = self.value_session.as_ref()?;
             let mut session = mutex.lock().ok()?;
+            crate::throughput::record_inferences(states.len() as u64);
             run_value_batch(&mut session, &self.adjacency, states)
         }
         #[cfg(not(feature = "neural"))]
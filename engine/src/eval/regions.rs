@@ -0,0 +1,213 @@
+//! Strategic theaters and the forces in them.
+//!
+//! Splits the board into six theaters (the same split the Go bot uses in
+//! `api/internal/bot/theater.go`) and counts, per theater, the armies and
+//! fleets a power has there against everyone else's. Distance heuristics
+//! see one unit and one center at a time; theater counts show when a power
+//! is outgunned at sea in the Mediterranean while holding its own on land,
+//! which is what the winter rebuild planner needs to pick unit types.
+
+use crate::board::province::{Power, Province};
+use crate::board::state::BoardState;
+use crate::board::unit::UnitType;
+
+/// A strategic region of the standard map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Theater {
+    /// France, Iberia, the Low Countries and the British Isles.
+    West,
+    /// Scandinavia and the northern seas.
+    Scan,
+    /// The Mediterranean, North Africa and Italy.
+    Med,
+    /// The Balkans, Turkey and the Black Sea.
+    Balkans,
+    /// Russia and Eastern Europe.
+    East,
+    /// Germany and the Austrian core.
+    Center,
+}
+
+/// Every theater, in declaration order.
+pub const ALL_THEATERS: [Theater; 6] = [
+    Theater::West,
+    Theater::Scan,
+    Theater::Med,
+    Theater::Balkans,
+    Theater::East,
+    Theater::Center,
+];
+
+impl Theater {
+    /// Lowercase name, as used by the Go bot and the opening book.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Theater::West => "west",
+            Theater::Scan => "scan",
+            Theater::Med => "med",
+            Theater::Balkans => "balkans",
+            Theater::East => "east",
+            Theater::Center => "center",
+        }
+    }
+
+    /// Parses a lowercase theater name.
+    pub fn from_name(name: &str) -> Option<Theater> {
+        ALL_THEATERS.iter().copied().find(|t| t.name() == name)
+    }
+
+    /// Index into [`ALL_THEATERS`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The theater a standard province belongs to; None for provinces off the
+/// standard board.
+#[allow(non_upper_case_globals)]
+pub fn theater_of(province: Province) -> Option<Theater> {
+    use crate::board::province::standard::*;
+    Some(match province {
+        Bre | Par | Mar | Gas | Bur | Pic | Spa | Por | Bel | Mao | Eng | Iri | Naf | Nao | Lon
+        | Lvp | Wal | Yor | Edi | Cly => Theater::West,
+        Nwy | Swe | Den | Ska | Nth | Nrg | Bar | Fin | Stp => Theater::Scan,
+        Tun | Tys | Wes | Gol | Ion | Aeg | Eas | Rom | Nap | Apu | Tus | Pie | Ven => Theater::Med,
+        Gre | Ser | Bul | Rum | Alb | Con | Smy | Ank | Arm | Syr | Bla | Adr => Theater::Balkans,
+        Mos | War | Ukr | Sev | Lvn | Pru | Sil | Gal | Bot => Theater::East,
+        Mun | Ber | Kie | Ruh | Hol | Tyr | Boh | Vie | Tri | Bud | Hel | Bal => Theater::Center,
+        _ => return None,
+    })
+}
+
+/// Armies and fleets in one theater, ours against everyone else's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TheaterForces {
+    pub theater: Theater,
+    pub own_armies: u32,
+    pub own_fleets: u32,
+    pub enemy_armies: u32,
+    pub enemy_fleets: u32,
+    /// Supply centers we own in the theater.
+    pub own_centers: u32,
+}
+
+impl TheaterForces {
+    fn empty(theater: Theater) -> Self {
+        TheaterForces {
+            theater,
+            own_armies: 0,
+            own_fleets: 0,
+            enemy_armies: 0,
+            enemy_fleets: 0,
+            own_centers: 0,
+        }
+    }
+
+    /// Our units of `unit_type` in the theater.
+    pub fn own(&self, unit_type: UnitType) -> u32 {
+        match unit_type {
+            UnitType::Army => self.own_armies,
+            UnitType::Fleet => self.own_fleets,
+        }
+    }
+
+    /// Other powers' units of `unit_type` in the theater.
+    pub fn enemy(&self, unit_type: UnitType) -> u32 {
+        match unit_type {
+            UnitType::Army => self.enemy_armies,
+            UnitType::Fleet => self.enemy_fleets,
+        }
+    }
+
+    /// Whether we have a center or a unit in the theater.
+    pub fn has_stake(&self) -> bool {
+        self.own_centers > 0 || self.own_armies + self.own_fleets > 0
+    }
+
+    /// Our units of `unit_type` over theirs, `None` if they have none.
+    pub fn ratio(&self, unit_type: UnitType) -> Option<f64> {
+        let enemy = self.enemy(unit_type);
+        (enemy > 0).then(|| self.own(unit_type) as f64 / enemy as f64)
+    }
+
+    /// How many more units of `unit_type` the others have here than we
+    /// do, zero where we have no stake.
+    pub fn shortfall(&self, unit_type: UnitType) -> u32 {
+        if !self.has_stake() {
+            return 0;
+        }
+        self.enemy(unit_type).saturating_sub(self.own(unit_type))
+    }
+
+    /// Records one unit in the theater.
+    pub fn add_unit(&mut self, own: bool, unit_type: UnitType) {
+        match (own, unit_type) {
+            (true, UnitType::Army) => self.own_armies += 1,
+            (true, UnitType::Fleet) => self.own_fleets += 1,
+            (false, UnitType::Army) => self.enemy_armies += 1,
+            (false, UnitType::Fleet) => self.enemy_fleets += 1,
+        }
+    }
+}
+
+/// Forces in every theater from `power`'s side, indexed by
+/// [`Theater::index`]. Theaters split the standard map; on others every
+/// count is zero.
+pub fn theater_forces(state: &BoardState, power: Power) -> [TheaterForces; 6] {
+    let mut forces = ALL_THEATERS.map(TheaterForces::empty);
+    if !state.map().is_standard() {
+        return forces;
+    }
+    for &prov in state.map().provinces() {
+        let Some(theater) = theater_of(prov) else {
+            continue;
+        };
+        let i = prov.index();
+        if let Some((p, ut)) = state.units[i] {
+            forces[theater.index()].add_unit(p == power, ut);
+        }
+        if state.sc_owner[i] == Some(power) {
+            forces[theater.index()].own_centers += 1;
+        }
+    }
+    forces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::ALL_PROVINCES;
+    use crate::protocol::dfen::parse_dfen;
+
+    #[test]
+    fn every_province_has_a_theater() {
+        let mut sizes = [0; 6];
+        for &p in ALL_PROVINCES.iter() {
+            sizes[theater_of(p).unwrap().index()] += 1;
+        }
+        assert_eq!(sizes, [20, 9, 13, 12, 9, 12]);
+        for t in ALL_THEATERS {
+            assert_eq!(Theater::from_name(t.name()), Some(t));
+        }
+    }
+
+    #[test]
+    fn counts_forces_by_theater() {
+        let state = parse_dfen(
+            "1902fb/Afgre,Aaser,Ifion,Ifaeg,Iaalb/Abud,Atri,Avie,Agre,Aser,Inap,Irom,Iven/-",
+        )
+        .unwrap();
+        let forces = theater_forces(&state, Power::Austria);
+        let balkans = forces[Theater::Balkans.index()];
+        assert_eq!((balkans.own_armies, balkans.own_fleets), (1, 1));
+        assert_eq!((balkans.enemy_armies, balkans.enemy_fleets), (1, 0));
+        assert_eq!(balkans.own_centers, 2);
+        let med = forces[Theater::Med.index()];
+        assert_eq!(med.enemy_fleets, 2);
+        assert!(!med.has_stake());
+        assert_eq!(med.shortfall(UnitType::Fleet), 0);
+        assert_eq!(forces[Theater::Center.index()].own_centers, 3);
+        assert_eq!(balkans.ratio(UnitType::Army), Some(1.0));
+        assert_eq!(balkans.ratio(UnitType::Fleet), None);
+    }
+}
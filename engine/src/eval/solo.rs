@@ -0,0 +1,137 @@
+//! Distance to a solo victory.
+//!
+//! A power's center count says how far it is from the 18 it needs to win
+//! outright (a majority of the map's centers), but not whether it
+//! can still get there: once the other powers man a stalemate line, the
+//! centers behind it are out of reach however many units the leader has. [`solo_distance`] estimates which centers a
+//! power can still take and returns how many more it needs, or None when
+//! the reachable ones fall short of a solo.
+//!
+//! Reach spreads from the power's units and unoccupied centers one
+//! province at a time. An empty province is reached as soon as it borders
+//! a reached one. A province held by a foreign unit is reached only if the
+//! power can bring more attackers against it than the defenders can put
+//! up: attackers are the reached provinces bordering it, no more than the
+//! power has units or centers, and defenders are the unit itself plus every
+//! other foreign unit, outside the reach, that could support it. Units
+//! that hold stop the spread, which is how a stalemate line shows up. The
+//! estimate is static and assumes every other power defends together,
+//! which suits a question about what a power can count on.
+
+use crate::board::province::{Coast, Power, Province, MAX_PROVINCES};
+use crate::board::state::BoardState;
+use crate::eval::heuristic::unit_can_reach;
+
+/// Supply centers needed for a solo victory on the standard map.
+pub const SOLO_CENTERS: u32 = 18;
+
+/// Additional supply centers `power` needs for a solo, or None if the
+/// centers it can still reach in `state` are too few.
+pub fn solo_distance(power: Power, state: &BoardState) -> Option<u32> {
+    let owned = state.sc_owner.iter().filter(|&&o| o == Some(power)).count() as u32;
+    let needed = (state.map().solo_centers() as u32).saturating_sub(owned);
+    if needed == 0 {
+        return Some(0);
+    }
+    let reach = reachable(power, state, owned);
+    let takeable = state
+        .map()
+        .provinces()
+        .iter()
+        .filter(|p| {
+            state.map().is_supply_center(**p)
+                && reach[p.index()]
+                && state.sc_owner[p.index()] != Some(power)
+        })
+        .count() as u32;
+    (takeable >= needed).then_some(needed)
+}
+
+/// Provinces `power` can reach when it owns `owned` centers.
+fn reachable(power: Power, state: &BoardState, owned: u32) -> [bool; MAX_PROVINCES] {
+    let units = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count() as u32;
+    let force = owned.max(units) as usize;
+    let foreign = |i: usize| matches!(state.units[i], Some((p, _)) if p != power);
+
+    let mut reach = [false; MAX_PROVINCES];
+    for (i, r) in reach.iter_mut().enumerate() {
+        let ours = matches!(state.units[i], Some((p, _)) if p == power);
+        *r = ours || (state.sc_owner[i] == Some(power) && !foreign(i));
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &prov in state.map().provinces() {
+            let i = prov.index();
+            if reach[i] {
+                continue;
+            }
+            let mut neighbours: Vec<usize> = state
+                .map()
+                .adj_from(prov)
+                .iter()
+                .filter(|a| a.army_ok || a.fleet_ok)
+                .map(|a| a.to.index())
+                .collect();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            let attackers = neighbours.iter().filter(|&&n| reach[n]).count();
+            if attackers == 0 {
+                continue;
+            }
+            let taken = !foreign(i) || {
+                let supporters = neighbours
+                    .iter()
+                    .filter(|&&n| !reach[n] && foreign(n))
+                    .filter(|&&n| {
+                        let (_, unit_type) = state.units[n].unwrap();
+                        let coast = state.fleet_coast[n].unwrap_or(Coast::None);
+                        unit_can_reach(state.map(), Province::from_index(n), coast, unit_type, prov)
+                    })
+                    .count();
+                attackers.min(force) > 1 + supporters
+            };
+            if taken {
+                reach[i] = true;
+                changed = true;
+            }
+        }
+    }
+    reach
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::Province;
+    use crate::protocol::dfen::parse_dfen;
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn open_board_counts_centers_still_needed() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        assert_eq!(solo_distance(Power::Austria, &state), Some(15));
+        assert_eq!(solo_distance(Power::Russia, &state), Some(14));
+    }
+
+    #[test]
+    fn a_held_ring_blocks_the_solo() {
+        let ringed =
+            parse_dfen("1905fm/Aavie,Gaboh,Ragal,Iatyr,Tatri,Tabud/Avie,Ttri,Tbud/-").unwrap();
+        assert_eq!(solo_distance(Power::Austria, &ringed), None);
+
+        // A gap in the ring lets Austria out to the open board.
+        let open = parse_dfen("1905fm/Aavie,Gaboh,Iatyr,Tatri,Tabud/Avie,Ttri,Tbud/-").unwrap();
+        assert_eq!(solo_distance(Power::Austria, &open), Some(17));
+
+        // Enough attackers break a lone unit.
+        let strong = parse_dfen("1905fm/Aavie,Aagal,Aaboh,Tabud/Avie,Tbud/-").unwrap();
+        assert!(reachable(Power::Austria, &strong, 1)[Province::Bud.index()]);
+        assert!(!reachable(Power::Austria, &open, 1)[Province::Bud.index()]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Stalemate lines of the standard map.
+//!
+//! A stalemate line is a chain of provinces that, manned and supporting
+//! one another, keeps an attacker away from the supply centers behind it.
+//! Against a power at 16 or 17 centers the lines worth manning shield 17
+//! of the 34 centers, leaving the leader at most the other 17: once one
+//! stands, the leader cannot solo. [`STALEMATE_LINES`] lists four, splitting
+//! the map south from north and west from east; the posts of each seal
+//! exactly its centers off from the rest of the board.
+//!
+//! [`lockable_lines`] finds the lines a power can complete this turn, its
+//! units filling the posts the other defenders leave open. [`line_bonus`]
+//! is the evaluation's reward for manned posts while a power threatens a
+//! solo, much the largest for a completed line. Both leave alone lines the
+//! leader has already broken: it owns a center behind the line or stands
+//! on one of its posts. Other maps have no lines.
+
+use crate::board::province::{Coast, Power, Province, ALL_POWERS};
+use crate::board::state::BoardState;
+use crate::board::unit::UnitType;
+use crate::eval::heuristic::unit_can_reach;
+use crate::eval::solo::SOLO_CENTERS;
+
+use crate::board::province::standard::*;
+
+/// Reward for a completed line while a power threatens a solo, about six
+/// centers' worth.
+pub const LINE_COMPLETE_BONUS: f32 = 60.0;
+
+/// Reward per manned post of a line not yet complete.
+pub const LINE_POST_BONUS: f32 = 2.0;
+
+/// A stalemate line: the posts to man and the centers they shield.
+#[derive(Debug, PartialEq, Eq)]
+pub struct StalemateLine {
+    pub name: &'static str,
+    /// Provinces the defenders must occupy.
+    pub posts: &'static [Province],
+    /// Supply centers behind the line, posts included.
+    pub centers: &'static [Province],
+}
+
+/// The standard map's lines shielding 17 centers.
+pub const STALEMATE_LINES: [StalemateLine; 4] = [
+    StalemateLine {
+        name: "southern",
+        posts: &[Mao, Gas, Mar, Pie, Ven, Tri, Bud, Rum, Sev],
+        centers: &[
+            Por, Spa, Mar, Tun, Rom, Nap, Ven, Tri, Bud, Ser, Rum, Bul, Gre, Con, Ank, Smy, Sev,
+        ],
+    },
+    StalemateLine {
+        name: "northern",
+        posts: &[Mao, Gas, Bur, Mun, Boh, Gal, Ukr, Sev],
+        centers: &[
+            Lon, Lvp, Edi, Bre, Par, Bel, Hol, Kie, Ber, Mun, Den, Nwy, Swe, Stp, Mos, War, Sev,
+        ],
+    },
+    StalemateLine {
+        name: "western",
+        posts: &[Nrg, Nth, Hel, Kie, Ber, Mun, Pie, Ven, Adr, Ion],
+        centers: &[
+            Lon, Lvp, Edi, Bre, Par, Mar, Spa, Por, Bel, Hol, Kie, Ber, Mun, Ven, Rom, Nap, Tun,
+        ],
+    },
+    StalemateLine {
+        name: "eastern",
+        posts: &[Bar, Nwy, Ska, Den, Bal, Pru, Sil, Boh, Tyr, Tri, Alb, Ion],
+        centers: &[
+            Nwy, Swe, Den, Stp, Mos, War, Sev, Vie, Bud, Tri, Ser, Rum, Bul, Gre, Con, Ank, Smy,
+        ],
+    },
+];
+
+impl StalemateLine {
+    /// Whether the line can still stop `leader`: it owns none of the
+    /// centers behind it and has no unit on its posts.
+    pub fn stands_against(&self, leader: Power, state: &BoardState) -> bool {
+        !self
+            .centers
+            .iter()
+            .any(|&c| state.sc_owner[c.index()] == Some(leader))
+            && !self
+                .posts
+                .iter()
+                .any(|&p| matches!(state.units[p.index()], Some((q, _)) if q == leader))
+    }
+
+    /// Posts occupied by a unit of some power other than `leader`.
+    pub fn manned(&self, leader: Power, state: &BoardState) -> usize {
+        self.posts
+            .iter()
+            .filter(|&&p| matches!(state.units[p.index()], Some((q, _)) if q != leader))
+            .count()
+    }
+
+    /// Whether `power` can complete the line this turn: each open post
+    /// reachable by a different unit of its own not already on a post.
+    pub fn can_lock(&self, power: Power, leader: Power, state: &BoardState) -> bool {
+        if power == leader || !self.stands_against(leader, state) {
+            return false;
+        }
+        let open: Vec<Province> = self
+            .posts
+            .iter()
+            .copied()
+            .filter(|&p| state.units[p.index()].is_none())
+            .collect();
+        let fillers: Vec<(Province, Coast, UnitType)> = state
+            .map()
+            .provinces()
+            .iter()
+            .filter(|prov| !self.posts.contains(prov))
+            .filter_map(|&prov| {
+                let i = prov.index();
+                match state.units[i] {
+                    Some((p, ut)) if p == power => {
+                        Some((prov, state.fleet_coast[i].unwrap_or(Coast::None), ut))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        let reaches = |f: usize, post: usize| {
+            let (prov, coast, ut) = fillers[f];
+            unit_can_reach(state.map(), prov, coast, ut, open[post])
+        };
+        // owner[f] is the open post filler f is matched to.
+        let mut owner: Vec<Option<usize>> = vec![None; fillers.len()];
+        (0..open.len()).all(|post| {
+            let mut seen = vec![false; fillers.len()];
+            fill(post, &reaches, &mut owner, &mut seen)
+        })
+    }
+}
+
+/// Tries to give open post `post` a filler along an augmenting path.
+fn fill(
+    post: usize,
+    reaches: &dyn Fn(usize, usize) -> bool,
+    owner: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for f in 0..owner.len() {
+        if seen[f] || !reaches(f, post) {
+            continue;
+        }
+        seen[f] = true;
+        if owner[f].is_none_or(|p| fill(p, reaches, owner, seen)) {
+            owner[f] = Some(post);
+            return true;
+        }
+    }
+    false
+}
+
+/// The power one or two centers short of a solo on the standard map, if
+/// any; the larger when two are.
+pub fn solo_leader(state: &BoardState) -> Option<Power> {
+    if !state.map().is_standard() {
+        return None;
+    }
+    let mut counts = [0u32; 7];
+    for owner in state.sc_owner.iter().flatten() {
+        counts[*owner as usize] += 1;
+    }
+    ALL_POWERS
+        .iter()
+        .copied()
+        .filter(|&p| (SOLO_CENTERS - 2..SOLO_CENTERS).contains(&counts[p as usize]))
+        .max_by_key(|&p| counts[p as usize])
+}
+
+/// Lines `power` can complete this turn against the power threatening a
+/// solo, if there is one and it is someone else.
+pub fn lockable_lines(power: Power, state: &BoardState) -> Vec<&'static StalemateLine> {
+    let Some(leader) = solo_leader(state).filter(|&l| l != power) else {
+        return Vec::new();
+    };
+    STALEMATE_LINES
+        .iter()
+        .filter(|line| line.manned(leader, state) < line.posts.len())
+        .filter(|line| line.can_lock(power, leader, state))
+        .collect()
+}
+
+/// Evaluation bonus for `power` from the best line standing against the
+/// power threatening a solo: [`LINE_COMPLETE_BONUS`] once complete,
+/// [`LINE_POST_BONUS`] per manned post before. Zero without such a power
+/// or for the leader itself.
+pub fn line_bonus(power: Power, state: &BoardState) -> f32 {
+    let Some(leader) = solo_leader(state).filter(|&l| l != power) else {
+        return 0.0;
+    };
+    STALEMATE_LINES
+        .iter()
+        .filter(|line| line.stands_against(leader, state))
+        .map(|line| {
+            let manned = line.manned(leader, state);
+            if manned == line.posts.len() {
+                LINE_COMPLETE_BONUS
+            } else {
+                LINE_POST_BONUS * manned as f32
+            }
+        })
+        .fold(0.0, f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::map::GameMap;
+    use crate::board::state::{Phase, Season};
+    use crate::eval::evaluate;
+
+    #[test]
+    fn lines_seal_exactly_their_centers() {
+        let map = GameMap::standard();
+        for line in &STALEMATE_LINES {
+            assert_eq!(line.centers.len(), 17, "{}", line.name);
+            // Flood from behind the line without crossing a post.
+            let seed = *line
+                .centers
+                .iter()
+                .find(|c| !line.posts.contains(c))
+                .unwrap();
+            let mut region = vec![seed];
+            let mut i = 0;
+            while i < region.len() {
+                for adj in map.adj_from(region[i]) {
+                    if !line.posts.contains(&adj.to) && !region.contains(&adj.to) {
+                        region.push(adj.to);
+                    }
+                }
+                i += 1;
+            }
+            let mut sealed: Vec<Province> = region
+                .iter()
+                .chain(line.posts)
+                .copied()
+                .filter(|p| map.is_supply_center(*p))
+                .collect();
+            let mut centers = line.centers.to_vec();
+            sealed.sort_by_key(|p| p.index());
+            centers.sort_by_key(|p| p.index());
+            assert_eq!(sealed, centers, "{}", line.name);
+        }
+    }
+
+    /// France at 16 centers; Italy and Turkey man the southern line but
+    /// for Sevastopol, which Turkey's army in Armenia can still reach.
+    fn besieged() -> BoardState {
+        let mut state = BoardState::empty(1910, Season::Spring, Phase::Movement);
+        for c in [
+            Lon, Lvp, Edi, Bre, Par, Bel, Hol, Kie, Ber, Mun, Den, Nwy, Swe, Stp, Mos, War,
+        ] {
+            state.set_sc_owner(c, Some(Power::France));
+        }
+        for c in [Por, Spa, Mar, Ven, Rom, Nap, Tun, Tri] {
+            state.set_sc_owner(c, Some(Power::Italy));
+        }
+        for c in [Bud, Rum, Sev, Ser, Gre, Bul, Con, Ank, Smy] {
+            state.set_sc_owner(c, Some(Power::Turkey));
+        }
+        state.place_unit(Mao, Power::Italy, UnitType::Fleet, Coast::None);
+        for p in [Gas, Mar, Pie, Ven, Tri] {
+            state.place_unit(p, Power::Italy, UnitType::Army, Coast::None);
+        }
+        for p in [Bud, Rum, Arm] {
+            state.place_unit(p, Power::Turkey, UnitType::Army, Coast::None);
+        }
+        for p in [Bur, Tyr, Gal, Ukr, Mos] {
+            state.place_unit(p, Power::France, UnitType::Army, Coast::None);
+        }
+        state
+    }
+
+    #[test]
+    fn detects_a_line_a_defender_can_lock() {
+        let mut state = besieged();
+        assert_eq!(solo_leader(&state), Some(Power::France));
+        let lockable = lockable_lines(Power::Turkey, &state);
+        assert_eq!(lockable.len(), 1);
+        assert_eq!(lockable[0].name, "southern");
+        assert!(lockable_lines(Power::Italy, &state).is_empty());
+        assert!(lockable_lines(Power::France, &state).is_empty());
+        assert_eq!(line_bonus(Power::Italy, &state), 8.0 * LINE_POST_BONUS);
+
+        let open = evaluate(Power::Turkey, &state);
+        state.remove_unit(Arm);
+        state.place_unit(Sev, Power::Turkey, UnitType::Army, Coast::None);
+        assert!(lockable_lines(Power::Turkey, &state).is_empty());
+        assert_eq!(line_bonus(Power::Italy, &state), LINE_COMPLETE_BONUS);
+        assert_eq!(line_bonus(Power::France, &state), 0.0);
+        assert!(evaluate(Power::Turkey, &state) > open + 30.0);
+
+        // A French army on a post breaks the line.
+        state.remove_unit(Mar);
+        state.place_unit(Mar, Power::France, UnitType::Army, Coast::None);
+        assert_eq!(line_bonus(Power::Italy, &state), 0.0);
+    }
+}
@@ -0,0 +1,162 @@
+//! Voronoi-style territory control.
+//!
+//! Partitions the board by nearest controller: each province is assigned to
+//! the power whose closest unit reaches it in the fewest moves, using the
+//! army or fleet BFS distance matrix depending on unit type. Provinces that
+//! are equidistant from units of two or more powers are contested, and
+//! provinces no unit can reach stay unassigned.
+//!
+//! SC count alone badly measures midgame positional dominance; territory
+//! control captures how much of the map a power can contest first.
+
+use serde_json::{json, Map, Value};
+
+use crate::board::province::{Power, Province, ALL_POWERS, MAX_PROVINCES};
+use crate::board::state::BoardState;
+use crate::board::unit::UnitType;
+
+/// Computes the nearest controlling power for every province.
+///
+/// Indexed by `Province::index`. `None` means contested (tie between
+/// powers) or unreachable by any unit.
+pub fn territory_map(state: &BoardState) -> [Option<Power>; MAX_PROVINCES] {
+    let mut best = [i16::MAX; MAX_PROVINCES];
+    let mut owner: [Option<Power>; MAX_PROVINCES] = [None; MAX_PROVINCES];
+    let mut contested = [false; MAX_PROVINCES];
+
+    for (i, unit_opt) in state.units.iter().enumerate() {
+        let (power, ut) = match unit_opt {
+            Some(u) => *u,
+            None => continue,
+        };
+        let dm = if ut == UnitType::Fleet {
+            state.map().fleet_dist()
+        } else {
+            state.map().army_dist()
+        };
+        let from = Province::from_index(i);
+        for &to in state.map().provinces() {
+            let t = to.index();
+            let d = dm.distance(from, to);
+            if d < 0 {
+                continue;
+            }
+            if d < best[t] {
+                best[t] = d;
+                owner[t] = Some(power);
+                contested[t] = false;
+            } else if d == best[t] && owner[t] != Some(power) {
+                contested[t] = true;
+            }
+        }
+    }
+
+    for (o, &c) in owner.iter_mut().zip(contested.iter()) {
+        if c {
+            *o = None;
+        }
+    }
+    owner
+}
+
+/// Counts controlled provinces per power, indexed by `Power as usize`.
+pub fn territory_counts(state: &BoardState) -> [i32; 7] {
+    let mut counts = [0i32; 7];
+    for p in territory_map(state).iter().flatten() {
+        counts[*p as usize] += 1;
+    }
+    counts
+}
+
+/// Territory control term for a power: the fraction of the board it controls.
+///
+/// Returns a value in [0, 1]. Not part of `evaluate` yet; exposed so search
+/// and tuning code can experiment with it as an additional eval feature.
+pub fn territory_score(power: Power, state: &BoardState) -> f32 {
+    territory_counts(state)[power as usize] as f32 / state.map().provinces().len() as f32
+}
+
+/// Serializes the territory partition as a single-line JSON object.
+///
+/// Format: `{"provinces":{"adr":"italy","alb":null,...},"counts":{"austria":9,...}}`.
+pub fn territory_json(state: &BoardState) -> String {
+    let map = territory_map(state);
+    let mut provinces = Map::new();
+    for &prov in state.map().provinces() {
+        let v = match map[prov.index()] {
+            Some(p) => Value::from(p.name()),
+            None => Value::Null,
+        };
+        provinces.insert(state.map().abbr(prov).to_string(), v);
+    }
+    let mut counts = Map::new();
+    let mut totals = [0i32; 7];
+    for p in map.iter().flatten() {
+        totals[*p as usize] += 1;
+    }
+    for &p in ALL_POWERS.iter() {
+        counts.insert(p.name().to_string(), Value::from(totals[p as usize]));
+    }
+    json!({ "provinces": provinces, "counts": counts }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::{Coast, Province, PROVINCE_COUNT};
+    use crate::board::state::{Phase, Season};
+    use crate::protocol::dfen::parse_dfen;
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn occupied_provinces_belong_to_occupant() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let map = territory_map(&state);
+        assert_eq!(map[Province::Vie.index()], Some(Power::Austria));
+        assert_eq!(map[Province::Lon.index()], Some(Power::England));
+        assert_eq!(map[Province::Smy.index()], Some(Power::Turkey));
+    }
+
+    #[test]
+    fn equidistant_province_is_contested() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let map = territory_map(&state);
+        // Galicia is one move from both Austrian Vie and Russian War.
+        assert_eq!(map[Province::Gal.index()], None);
+        // Tyrolia is one move from Vie, Ven, and Mun.
+        assert_eq!(map[Province::Tyr.index()], None);
+    }
+
+    #[test]
+    fn fleet_distances_used_for_fleets() {
+        let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Nth, Power::England, UnitType::Fleet, Coast::None);
+        let map = territory_map(&state);
+        assert_eq!(map[Province::Hol.index()], Some(Power::England));
+        // A lone fleet cannot reach inland provinces.
+        assert_eq!(map[Province::Mun.index()], None);
+    }
+
+    #[test]
+    fn counts_and_score_agree() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let counts = territory_counts(&state);
+        assert!(counts.iter().all(|&c| c > 0));
+        assert!(counts.iter().sum::<i32>() <= PROVINCE_COUNT as i32);
+        let score = territory_score(Power::Russia, &state);
+        assert!((score - counts[Power::Russia as usize] as f32 / 75.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn territory_json_is_parseable() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let v: Value = serde_json::from_str(&territory_json(&state)).unwrap();
+        assert_eq!(v["provinces"]["vie"], "austria");
+        assert!(v["provinces"]["gal"].is_null());
+        assert_eq!(
+            v["counts"]["austria"].as_i64().unwrap(),
+            territory_counts(&state)[Power::Austria as usize] as i64
+        );
+    }
+}
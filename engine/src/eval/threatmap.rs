@@ -0,0 +1,128 @@
+//! Per-province threat heat map.
+//!
+//! For a given power, computes threat minus defense for every province on
+//! the board using the same helpers the heuristic evaluator relies on.
+//! Positive values mark areas where enemies can bring more units to bear
+//! than the power can; negative values mark areas the power controls.
+//! The result is exported as JSON for GUI heat-map overlays, with each
+//! area's centroid when the map places its provinces.
+
+use serde_json::json;
+
+use crate::board::province::{Power, MAX_PROVINCES};
+use crate::board::state::BoardState;
+use crate::eval::heuristic::{province_chain_defense, province_defense, province_threat};
+
+/// Threat and defense counts for a single province.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AreaThreat {
+    /// Enemy units that can reach the province in one move.
+    pub threat: i32,
+    /// Own units (excluding an occupant) that can reach the province in one move.
+    pub defense: i32,
+    /// Own units that cannot reach the province but can reach one of the
+    /// defending units: support that can back the defenders.
+    pub chain: i32,
+}
+
+impl AreaThreat {
+    /// Returns threat minus defense.
+    #[inline]
+    pub fn net(self) -> i32 {
+        self.threat - self.defense
+    }
+}
+
+/// Computes threat and defense for every province, indexed by `Province::index`.
+pub fn threat_map(power: Power, state: &BoardState) -> [AreaThreat; MAX_PROVINCES] {
+    let mut map = [AreaThreat::default(); MAX_PROVINCES];
+    for &prov in state.map().provinces() {
+        let i = prov.index();
+        map[i] = AreaThreat {
+            threat: province_threat(prov, power, state),
+            defense: province_defense(prov, power, state),
+            chain: province_chain_defense(prov, power, state),
+        };
+    }
+    map
+}
+
+/// Serializes the threat map for a power as a single-line JSON object.
+///
+/// Format: `{"power":"austria","areas":[{"province":"adr","threat":1,"defense":0,"chain":0,"net":1,"centroid":[540,860]},...]}`
+/// with areas in canonical province order. `centroid` is omitted on maps
+/// that do not place their provinces.
+pub fn threat_map_json(power: Power, state: &BoardState) -> String {
+    let map = threat_map(power, state);
+    let areas: Vec<serde_json::Value> = state
+        .map()
+        .provinces()
+        .iter()
+        .map(|&prov| {
+            let area = &map[prov.index()];
+            let mut value = json!({
+                "province": state.map().abbr(prov),
+                "threat": area.threat,
+                "defense": area.defense,
+                "chain": area.chain,
+                "net": area.net(),
+            });
+            if let Some(geometry) = state.map().geometry(prov) {
+                value["centroid"] = json!(geometry.centroid);
+            }
+            value
+        })
+        .collect();
+    json!({ "power": power.name(), "areas": areas }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::{Province, PROVINCE_COUNT};
+    use crate::board::state::{Phase, Season};
+    use crate::protocol::dfen::parse_dfen;
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn threat_map_matches_helpers() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let map = threat_map(Power::Austria, &state);
+        // Galicia: reachable by Russian War and Austrian Vie/Bud.
+        let gal = map[Province::Gal.index()];
+        assert_eq!(gal.threat, 1);
+        assert_eq!(gal.defense, 2);
+        assert_eq!(gal.net(), -1);
+        // Tyrolia: reachable by Italian Ven and German Mun; only Vie defends.
+        let tyr = map[Province::Tyr.index()];
+        assert_eq!(tyr.threat, 2);
+        assert_eq!(tyr.defense, 1);
+        assert_eq!(tyr.net(), 1);
+        // Bud cannot reach Tyrolia but can support Vie.
+        assert_eq!(tyr.chain, 1);
+        assert_eq!(gal.chain, 0);
+    }
+
+    #[test]
+    fn threat_map_empty_board_is_zero() {
+        let state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        let map = threat_map(Power::France, &state);
+        assert!(map
+            .iter()
+            .all(|a| a.threat == 0 && a.defense == 0 && a.chain == 0));
+    }
+
+    #[test]
+    fn threat_map_json_lists_all_provinces() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let s = threat_map_json(Power::Austria, &state);
+        let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(v["power"], "austria");
+        let areas = v["areas"].as_array().unwrap();
+        assert_eq!(areas.len(), PROVINCE_COUNT);
+        assert_eq!(areas[0]["province"], "adr");
+        assert_eq!(areas[0]["centroid"], json!([540, 860]));
+        assert!(!s.contains('\n'));
+    }
+}
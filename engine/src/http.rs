@@ -0,0 +1,790 @@
+//! Minimal HTTP/JSON front end (`realpolitik --serve-http`).
+//!
+//! For scripts that want adjudication or a move without speaking DUI.
+//! Every endpoint takes a JSON body naming a DFEN position and answers
+//! with JSON; errors are `{"error": "..."}` with a 4xx status.
+//!
+//! | Endpoint | Body | Answer |
+//! |----------|------|--------|
+//! | `GET /health` | none | `{"status":"ok"}` |
+//! | `POST /adjudicate` | `position`, `orders` (power name to DSON orders, build-phase shorthand `W 2` and `D 1` included) | per-order `results`, `dislodged` (movement only), the `changes` it made (see [`diff`]), next `position` |
+//! | `POST /evaluate` | `position` | heuristic `scores` per power |
+//! | `POST /bestorders` | `position`, `power`, optional `movetime` (ms) and `strength` | `orders` |
+//! | `POST /sessions` | optional `options` (name to value) | a `session` token |
+//! | `GET /sessions` | none | open `sessions` and `max_sessions` |
+//! | `POST /sessions/<token>/commands` | `commands` (DUI lines) | the `output` lines; `go` waits for `bestorders`, at most the longest movetime |
+//! | `POST /sessions/<token>/stop` | none | whether a running search was `stopped` |
+//! | `POST /sessions/<token>/options` | `options` | how many were `set` |
+//! | `DELETE /sessions/<token>` | none | `closed` token |
+//! | `GET /metrics` | none | Prometheus text: searches, time use, NPS, cache hit rate, sessions |
+//!
+//! The stateless endpoints answer from the request alone, and share one
+//! server-wide rate. Sessions keep a whole engine per game between
+//! requests (see [`sessions`]); a request over a session's rate, the
+//! stateless rate or the session cap gets a 429. Given a
+//! [`SessionStore`], sessions survive restarts (see [`persist`]).
+//!
+//! The server is deliberately small: one thread per connection up to
+//! [`SessionLimits::max_connections`] (more get a 503), one request per
+//! connection, no TLS, and no neural network (`bestorders` searches with
+//! the heuristic evaluation). A request must arrive whole within
+//! [`REQUEST_TIMEOUT`], with lines of at most [`MAX_HEADER_LINE`] bytes
+//! and headers of at most [`MAX_HEADER_BYTES`] in all, so slow or
+//! oversized clients cannot hold a connection open. Put it behind a
+//! proper proxy if it faces anything other than localhost.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Map, Value};
+
+use crate::board::order::Order;
+use crate::board::province::{Power, ALL_POWERS};
+use crate::board::state::{BoardState, Phase};
+use crate::engine::async_api::{search_blocking, CancellationToken, SearchRequest};
+use crate::eval::evaluate_all;
+use crate::protocol::dfen::{diff, encode_dfen, parse_dfen};
+use crate::protocol::dson::{format_order, parse_orders_for};
+use crate::resolve::{
+    adjudicate_movement, advance_state, apply_builds, apply_resolution, apply_retreats,
+    resolve_builds, resolve_retreats, OrderResult,
+};
+use crate::throughput::{SearchTotals, WorkCounts};
+use sessions::RateLimiter;
+
+pub mod persist;
+pub mod sessions;
+
+#[cfg(feature = "sqlite")]
+pub use persist::SqliteStore;
+pub use persist::{DirStore, MemoryStore, SessionRecord, SessionStore};
+pub use sessions::{SessionCounts, SessionLimits, Sessions};
+
+/// Largest request body accepted.
+const MAX_BODY_BYTES: usize = 1 << 20;
+
+/// Longest request line or header line accepted.
+pub const MAX_HEADER_LINE: usize = 8 * 1024;
+
+/// Most bytes of request line and headers accepted together.
+pub const MAX_HEADER_BYTES: usize = 32 * 1024;
+
+/// Time a client has to send its whole request.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a client has to take the response.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Search time for `bestorders` when the request gives none.
+const DEFAULT_MOVETIME_MS: u64 = 1000;
+
+/// Longest search a request may ask for.
+const MAX_MOVETIME_MS: u64 = 60_000;
+
+/// An HTTP status and JSON body, or a plain-text body for `/metrics`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Value,
+    /// Sent as `text/plain` in place of `body` when set.
+    pub text: Option<String>,
+}
+
+impl HttpResponse {
+    fn ok(body: Value) -> Self {
+        HttpResponse {
+            status: 200,
+            body,
+            text: None,
+        }
+    }
+
+    fn text(text: String) -> Self {
+        HttpResponse {
+            status: 200,
+            body: Value::Null,
+            text: Some(text),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        HttpResponse {
+            status,
+            body: json!({ "error": message.into() }),
+            text: None,
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            429 => "Too Many Requests",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Error",
+        }
+    }
+}
+
+/// Server state shared by every connection.
+pub struct Server {
+    sessions: Sessions,
+    /// Shared by every request to a stateless endpoint.
+    stateless: Mutex<RateLimiter>,
+    max_connections: usize,
+    /// Connections being handled now.
+    connections: AtomicUsize,
+}
+
+impl Server {
+    /// A server whose sessions start with `defaults` applied as options.
+    pub fn new(limits: SessionLimits, defaults: Vec<(String, String)>) -> Self {
+        Server {
+            stateless: Mutex::new(RateLimiter::new(
+                limits.stateless_per_minute,
+                Instant::now(),
+            )),
+            max_connections: limits.max_connections,
+            connections: AtomicUsize::new(0),
+            sessions: Sessions::new(limits, defaults),
+        }
+    }
+
+    /// A server saving its sessions to `store`, reopening those saved
+    /// before.
+    pub fn with_store(
+        limits: SessionLimits,
+        defaults: Vec<(String, String)>,
+        store: Arc<dyn SessionStore>,
+    ) -> io::Result<Self> {
+        Ok(Server {
+            stateless: Mutex::new(RateLimiter::new(
+                limits.stateless_per_minute,
+                Instant::now(),
+            )),
+            max_connections: limits.max_connections,
+            connections: AtomicUsize::new(0),
+            sessions: Sessions::with_store(limits, defaults, store)?,
+        })
+    }
+
+    /// Answers one request, stateless or session.
+    pub fn route(&self, method: &str, path: &str, body: &str) -> HttpResponse {
+        let path = path.split('?').next().unwrap_or(path);
+        if path == "/sessions" || path.starts_with("/sessions/") {
+            self.sessions.route(method, path, body)
+        } else if path == "/metrics" {
+            match method {
+                "GET" => HttpResponse::text(self.metrics()),
+                _ => HttpResponse::error(405, format!("{} not allowed on {}", method, path)),
+            }
+        } else if path == "/health" {
+            route(method, path, body)
+        } else if !self.stateless.lock().unwrap().allow(Instant::now()) {
+            HttpResponse::error(429, "stateless request rate exceeded")
+        } else {
+            route(method, path, body)
+        }
+    }
+
+    /// Counts a connection in, unless `max_connections` are already open.
+    fn admit(self: &Arc<Self>) -> Option<ConnectionSlot> {
+        self.connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < self.max_connections).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| ConnectionSlot(Arc::clone(self)))
+    }
+
+    /// The Prometheus text exposition of process and session metrics.
+    pub fn metrics(&self) -> String {
+        let totals = SearchTotals::now();
+        let work = WorkCounts::now();
+        let counts = self.sessions.counts();
+        let totals_table = [
+            (
+                "searches_total",
+                "counter",
+                "Searches completed.",
+                totals.searches as f64,
+            ),
+            (
+                "search_nodes_total",
+                "counter",
+                "Nodes searched.",
+                totals.nodes as f64,
+            ),
+            (
+                "search_seconds_total",
+                "counter",
+                "Wall time spent searching.",
+                totals.elapsed.as_secs_f64(),
+            ),
+            (
+                "search_budget_seconds_total",
+                "counter",
+                "Time searches were given.",
+                totals.budget.as_secs_f64(),
+            ),
+            (
+                "movetime_utilization",
+                "gauge",
+                "Share of the time budget searches used.",
+                totals.utilization(),
+            ),
+            (
+                "nodes_per_second",
+                "gauge",
+                "Nodes per second of search time.",
+                totals.nps(),
+            ),
+            (
+                "cache_lookups_total",
+                "counter",
+                "Lookahead cache lookups.",
+                totals.cache_lookups as f64,
+            ),
+            (
+                "cache_hits_total",
+                "counter",
+                "Lookahead cache hits.",
+                totals.cache_hits as f64,
+            ),
+            (
+                "cache_hit_ratio",
+                "gauge",
+                "Fraction of lookahead cache lookups that hit.",
+                totals.cache_hit_rate(),
+            ),
+            (
+                "resolves_total",
+                "counter",
+                "Resolver invocations.",
+                work.resolves as f64,
+            ),
+            (
+                "evals_total",
+                "counter",
+                "Heuristic evaluations.",
+                work.evals as f64,
+            ),
+            (
+                "inferences_total",
+                "counter",
+                "Neural network inferences.",
+                work.inferences as f64,
+            ),
+            (
+                "sessions",
+                "gauge",
+                "Open game sessions.",
+                counts.len() as f64,
+            ),
+            (
+                "sessions_max",
+                "gauge",
+                "Session cap.",
+                self.sessions.max_sessions() as f64,
+            ),
+        ];
+        let mut out = String::new();
+        for (name, kind, help, value) in totals_table {
+            metric_header(&mut out, name, kind, help);
+            out.push_str(&format!("realpolitik_{} {}\n", name, value));
+        }
+        metric_header(
+            &mut out,
+            "session_requests_total",
+            "counter",
+            "Requests per session.",
+        );
+        for c in &counts {
+            out.push_str(&format!(
+                "realpolitik_session_requests_total{{session=\"{}\"}} {}\n",
+                c.id, c.requests
+            ));
+        }
+        metric_header(
+            &mut out,
+            "session_searches_total",
+            "counter",
+            "Searches per session.",
+        );
+        for c in &counts {
+            out.push_str(&format!(
+                "realpolitik_session_searches_total{{session=\"{}\"}} {}\n",
+                c.id, c.searches
+            ));
+        }
+        out
+    }
+}
+
+fn metric_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    out.push_str(&format!(
+        "# HELP realpolitik_{name} {help}\n# TYPE realpolitik_{name} {kind}\n"
+    ));
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server::new(SessionLimits::default(), Vec::new())
+    }
+}
+
+/// Answers one request to a stateless endpoint. Separate from the socket
+/// handling so it can be tested, and reused by other front ends.
+pub fn route(method: &str, path: &str, body: &str) -> HttpResponse {
+    let path = path.split('?').next().unwrap_or(path);
+    let handler: fn(&Value) -> Result<Value, String> = match (method, path) {
+        ("GET", "/health") => return HttpResponse::ok(json!({ "status": "ok" })),
+        ("POST", "/adjudicate") => adjudicate,
+        ("POST", "/evaluate") => evaluate,
+        ("POST", "/bestorders") => best_orders,
+        (_, "/health" | "/adjudicate" | "/evaluate" | "/bestorders") => {
+            return HttpResponse::error(405, format!("{} not allowed on {}", method, path))
+        }
+        _ => return HttpResponse::error(404, format!("no endpoint {}", path)),
+    };
+    let request: Value = match serde_json::from_str(body) {
+        Ok(v) => v,
+        Err(e) => return HttpResponse::error(400, format!("invalid JSON: {}", e)),
+    };
+    match handler(&request) {
+        Ok(v) => HttpResponse::ok(v),
+        Err(e) => HttpResponse::error(400, e),
+    }
+}
+
+fn position(request: &Value) -> Result<BoardState, String> {
+    let dfen = request["position"]
+        .as_str()
+        .ok_or("missing \"position\" (a DFEN string)")?;
+    parse_dfen(dfen).map_err(|e| format!("bad position: {}", e))
+}
+
+fn power(value: &Value) -> Result<Power, String> {
+    let name = value.as_str().ok_or("missing \"power\"")?;
+    Power::from_name(name).ok_or_else(|| format!("unknown power '{}'", name))
+}
+
+/// `{"austria": ["A vie - gal", ...], ...}` to tagged orders, expanding
+/// build-phase shorthand such as `W 2` against `state`.
+fn orders(value: &Value, state: &BoardState) -> Result<Vec<(Order, Power)>, String> {
+    let Some(by_power) = value.as_object() else {
+        return Err("\"orders\" must map power names to order lists".to_string());
+    };
+    let mut out = Vec::new();
+    for (name, list) in by_power {
+        let p = power(&Value::String(name.clone()))?;
+        let list = list
+            .as_array()
+            .ok_or_else(|| format!("orders for {} must be a list", name))?;
+        for text in list {
+            let text = text.as_str().ok_or("orders must be DSON strings")?;
+            let orders = parse_orders_for(text, p, state)
+                .map_err(|e| format!("bad order '{}': {}", text, e))?;
+            out.extend(orders.into_iter().map(|order| (order, p)));
+        }
+    }
+    Ok(out)
+}
+
+/// Resolves every power's orders for the position's phase and returns the
+/// results with the position that follows.
+fn adjudicate(request: &Value) -> Result<Value, String> {
+    let mut state = position(request)?;
+    let before = state.clone();
+    let orders = orders(&request["orders"], &state)?;
+    let order_json = |order: &Order, power: Power, result: OrderResult| {
+        json!({
+            "power": power.name(),
+            "order": format_order(order),
+            "result": result.name(),
+        })
+    };
+    let (results, dislodged) = match state.phase {
+        Phase::Movement => {
+            let (results, dislodged) = adjudicate_movement(&orders, &state);
+            let json: Vec<Value> = results
+                .iter()
+                .map(|r| order_json(&r.order, r.power, r.result))
+                .collect();
+            let dislodged_json: Vec<Value> = dislodged
+                .iter()
+                .map(|d| {
+                    json!({
+                        "power": d.power.name(),
+                        "province": d.province.abbr(),
+                        "attacker_from": d.attacker_from.abbr(),
+                    })
+                })
+                .collect();
+            apply_resolution(&mut state, &results, &dislodged);
+            let has_dislodged = state.dislodged.iter().any(|d| d.is_some());
+            advance_state(&mut state, has_dislodged);
+            (json, dislodged_json)
+        }
+        Phase::Retreat => {
+            let results = resolve_retreats(&orders, &state);
+            apply_retreats(&mut state, &results);
+            advance_state(&mut state, false);
+            let json = results
+                .iter()
+                .map(|r| order_json(&r.order, r.power, r.result))
+                .collect();
+            (json, Vec::new())
+        }
+        Phase::Build => {
+            let results = resolve_builds(&orders, &state);
+            apply_builds(&mut state, &results);
+            advance_state(&mut state, false);
+            let json = results
+                .iter()
+                .map(|r| order_json(&r.order, r.power, r.result))
+                .collect();
+            (json, Vec::new())
+        }
+    };
+    Ok(json!({
+        "results": results,
+        "dislodged": dislodged,
+        "changes": diff(&before, &state).to_json(before.map()),
+        "position": encode_dfen(&state),
+    }))
+}
+
+fn evaluate(request: &Value) -> Result<Value, String> {
+    let state = position(request)?;
+    let scores = evaluate_all(&state);
+    let mut map = Map::new();
+    for (&p, &score) in ALL_POWERS.iter().zip(scores.iter()) {
+        map.insert(p.name().to_string(), json!(score));
+    }
+    Ok(json!({ "scores": map }))
+}
+
+fn best_orders(request: &Value) -> Result<Value, String> {
+    let state = position(request)?;
+    let power = power(&request["power"])?;
+    let movetime = match &request["movetime"] {
+        Value::Null => DEFAULT_MOVETIME_MS,
+        v => v.as_u64().ok_or("\"movetime\" must be milliseconds")?,
+    };
+    let mut search = SearchRequest::new(
+        power,
+        state,
+        Duration::from_millis(movetime.clamp(1, MAX_MOVETIME_MS)),
+    );
+    if let Some(strength) = request["strength"].as_u64() {
+        search.strength = strength.min(100);
+    }
+    let output = search_blocking(&search, &CancellationToken::new());
+    let orders: Vec<String> = output.orders.iter().map(format_order).collect();
+    Ok(json!({ "power": power.name(), "orders": orders }))
+}
+
+/// Serves requests on `addr` until the listener fails.
+pub fn serve<A: ToSocketAddrs>(addr: A, server: Arc<Server>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("serving HTTP on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => match server.admit() {
+                Some(slot) => {
+                    std::thread::spawn(move || {
+                        if let Err(e) = handle_connection(&slot.0, stream) {
+                            eprintln!("http: {}", e);
+                        }
+                    });
+                }
+                // Turned away on the accept thread, without spawning.
+                None => {
+                    let busy = HttpResponse::error(503, "too many connections");
+                    let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                    let _ = write_response(&mut &stream, &busy);
+                }
+            },
+            Err(e) => eprintln!("http: accept failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// One of the server's `max_connections`, given back on drop.
+struct ConnectionSlot(Arc<Server>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.connections.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn handle_connection(server: &Server, stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut reader = BufReader::new(Deadline {
+        stream: &stream,
+        until: Instant::now() + REQUEST_TIMEOUT,
+    });
+    let response = match read_request(&mut reader)? {
+        Ok((method, path, body)) => server.route(&method, &path, &body),
+        Err(response) => response,
+    };
+    write_response(&mut &stream, &response)
+}
+
+/// Reads from a stream until a fixed instant, however the reads are
+/// spaced: a read timeout alone restarts with every byte received.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    until: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "request not received in time",
+            ));
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Reads one line of at most `MAX_HEADER_LINE` bytes, or `None` if it
+/// runs longer.
+fn read_capped_line<R: BufRead>(reader: &mut R, line: &mut String) -> io::Result<Option<usize>> {
+    let limit = MAX_HEADER_LINE as u64 + 1;
+    let n = Read::take(&mut *reader, limit).read_line(line)?;
+    if n > MAX_HEADER_LINE {
+        Ok(None)
+    } else {
+        Ok(Some(n))
+    }
+}
+
+/// Reads the request line, headers and body. Malformed requests come back
+/// as the response to send.
+fn read_request<R: BufRead>(
+    reader: &mut R,
+) -> io::Result<Result<(String, String, String), HttpResponse>> {
+    let too_large = || Ok(Err(HttpResponse::error(431, "request headers too large")));
+    let mut line = String::new();
+    let Some(mut total) = read_capped_line(reader, &mut line)? else {
+        return too_large();
+    };
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Ok(Err(HttpResponse::error(400, "malformed request line")));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0usize;
+    loop {
+        let mut header = String::new();
+        let Some(n) = read_capped_line(reader, &mut header)? else {
+            return too_large();
+        };
+        total += n;
+        if total > MAX_HEADER_BYTES {
+            return too_large();
+        }
+        if n == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = match value.trim().parse() {
+                    Ok(n) => n,
+                    Err(_) => return Ok(Err(HttpResponse::error(400, "bad Content-Length"))),
+                };
+            }
+        }
+    }
+    if length > MAX_BODY_BYTES {
+        return Ok(Err(HttpResponse::error(413, "request body too large")));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    match String::from_utf8(body) {
+        Ok(body) => Ok(Ok((method, path, body))),
+        Err(_) => Ok(Err(HttpResponse::error(400, "body is not UTF-8"))),
+    }
+}
+
+fn write_response<W: Write>(out: &mut W, response: &HttpResponse) -> io::Result<()> {
+    let (content_type, body) = match &response.text {
+        Some(text) => ("text/plain; version=0.0.4", text.clone()),
+        None => ("application/json", response.body.to_string()),
+    };
+    write!(
+        out,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        content_type,
+        body.len(),
+        body
+    )?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjudicates_a_bounce_and_advances() {
+        let body = json!({
+            "position": "1901sm/Aavie,Ragal/Avie/-",
+            "orders": { "austria": ["A vie - gal"], "russia": ["A gal H"] },
+        })
+        .to_string();
+        let resp = route("POST", "/adjudicate", &body);
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["results"][0]["result"], "bounced");
+        assert_eq!(resp.body["results"][1]["result"], "succeeded");
+        assert!(resp.body["position"]
+            .as_str()
+            .unwrap()
+            .starts_with("1901fm/"));
+        assert_eq!(resp.body["changes"]["moves"], json!([]));
+    }
+
+    #[test]
+    fn expands_defaulted_disbands() {
+        let body = json!({
+            "position": "1901fb/Aavie,Aagal,Aaukr/Avie/-",
+            "orders": { "austria": ["D 2"] },
+        })
+        .to_string();
+        let resp = route("POST", "/adjudicate", &body);
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["results"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            resp.body["changes"]["disbands"].as_array().unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn evaluates_and_suggests_orders() {
+        let resp = route("POST", "/evaluate", r#"{"position":"1901sm/Aavie/Avie/-"}"#);
+        assert_eq!(resp.status, 200);
+        assert!(resp.body["scores"]["austria"].as_f64().unwrap() > 0.0);
+
+        let body = r#"{"position":"1901sm/Aavie/Avie/-","power":"austria","movetime":50}"#;
+        let resp = route("POST", "/bestorders", body);
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["orders"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn errors_are_json() {
+        assert_eq!(route("GET", "/nope", "").status, 404);
+        assert_eq!(route("GET", "/evaluate", "").status, 405);
+        let resp = route("POST", "/evaluate", "{");
+        assert_eq!(resp.status, 400);
+        assert!(resp.body["error"]
+            .as_str()
+            .unwrap()
+            .contains("invalid JSON"));
+        let resp = route(
+            "POST",
+            "/bestorders",
+            r#"{"position":"1901sm/Aavie/Avie/-"}"#,
+        );
+        assert_eq!(resp.body["error"], "missing \"power\"");
+        assert_eq!(route("GET", "/health", "").body["status"], "ok");
+    }
+
+    #[test]
+    fn parses_and_writes_the_wire_format() {
+        let raw = "POST /evaluate HTTP/1.1\r\nHost: x\r\nContent-Length: 2\r\n\r\n{}";
+        let (method, path, body) = read_request(&mut raw.as_bytes()).unwrap().unwrap();
+        assert_eq!(
+            (method.as_str(), path.as_str(), body.as_str()),
+            ("POST", "/evaluate", "{}")
+        );
+
+        let mut out = Vec::new();
+        write_response(&mut out, &HttpResponse::ok(json!({"a": 1}))).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(text.ends_with("\r\n\r\n{\"a\":1}"));
+    }
+
+    #[test]
+    fn refuses_oversized_headers() {
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_HEADER_LINE));
+        let resp = read_request(&mut long_line.as_bytes())
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(resp.status, 431);
+
+        let header = format!("X-Pad: {}\r\n", "a".repeat(1000));
+        let many = format!("GET /health HTTP/1.1\r\n{}\r\n", header.repeat(40));
+        let resp = read_request(&mut many.as_bytes()).unwrap().unwrap_err();
+        assert_eq!(resp.status, 431);
+        assert_eq!(resp.reason(), "Request Header Fields Too Large");
+    }
+
+    #[test]
+    fn limits_stateless_requests_and_connections() {
+        let limits = SessionLimits {
+            stateless_per_minute: 2,
+            max_connections: 1,
+            ..SessionLimits::default()
+        };
+        let server = Arc::new(Server::new(limits, Vec::new()));
+        let body = r#"{"position":"1901sm/Aavie/Avie/-"}"#;
+        assert_eq!(server.route("POST", "/evaluate", body).status, 200);
+        assert_eq!(server.route("POST", "/evaluate", "").status, 400);
+        assert_eq!(server.route("POST", "/evaluate", body).status, 429);
+        assert_eq!(server.route("GET", "/health", "").status, 200);
+        assert_eq!(server.route("POST", "/sessions", "").status, 200);
+
+        let slot = server.admit().unwrap();
+        assert!(server.admit().is_none());
+        drop(slot);
+        assert!(server.admit().is_some());
+    }
+
+    #[test]
+    fn metrics_cover_searches_and_sessions() {
+        let server = Server::default();
+        let created = server.route("POST", "/sessions", "").body;
+        let token = created["session"].as_str().unwrap().to_string();
+        let body = r#"{"position":"1901sm/Aavie/Avie/-","power":"austria","movetime":20}"#;
+        server.route("POST", "/bestorders", body);
+
+        let resp = server.route("GET", "/metrics", "");
+        let text = resp.text.unwrap();
+        let value = |name: &str| -> f64 {
+            let line = text.lines().find(|l| l.starts_with(name)).unwrap();
+            line.rsplit(' ').next().unwrap().parse().unwrap()
+        };
+        assert!(value("realpolitik_searches_total ") >= 1.0);
+        assert_eq!(value("realpolitik_sessions "), 1.0);
+        assert!(text.contains(&format!("session=\"{}\"", created["id"])));
+        assert!(!text.contains(&token[..8]));
+        assert!(text.contains("# TYPE realpolitik_nodes_per_second gauge"));
+
+        let mut out = Vec::new();
+        write_response(&mut out, &HttpResponse::text("x 1\n".into())).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("Content-Type: text/plain"));
+    }
+}
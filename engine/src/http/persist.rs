@@ -0,0 +1,330 @@
+//! Persistence for HTTP game sessions.
+//!
+//! A session is stored as the option overrides it was opened with plus the
+//! state-changing DUI commands it has been sent (`setoption`, `newgame`,
+//! `position`, `setpower`, `press`, `lockorder`), in order. Replaying them
+//! into a fresh engine rebuilds everything the engine learns over a game:
+//! the position, trust from press, the relation and convention trackers.
+//! Searches are not replayed; the RM+ state they carry over is rebuilt by
+//! the next `go`. The record doubles as the game's history.
+//!
+//! A `newgame` resets everything but the options, so it compacts the
+//! record to the options then in force (see [`SessionRecord::push`]): a
+//! record holds one game, however many a session plays. Positions within
+//! a game are all kept, since each one feeds the trackers.
+//!
+//! Backends implement [`SessionStore`]. Three are built in: [`MemoryStore`];
+//! [`DirStore`], one JSON file per session in a directory; and, with the
+//! `sqlite` feature, [`SqliteStore`], one table row per session in a
+//! SQLite database.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::parser::{parse_command, Command};
+
+/// What is saved for one session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// Options given when the session was opened, over the server defaults.
+    pub options: Vec<(String, String)>,
+    /// State-changing command lines, oldest first.
+    pub history: Vec<String>,
+}
+
+impl SessionRecord {
+    /// Appends a replayed command line. A `newgame` first drops every
+    /// earlier line but the last `setoption` for each option.
+    pub fn push(&mut self, line: &str) {
+        if parse_command(line) == Some(Command::NewGame) {
+            let mut options: Vec<(String, String)> = Vec::new();
+            for old in self.history.drain(..) {
+                if let Some(Command::SetOption { name, .. }) = parse_command(&old) {
+                    options.retain(|(n, _)| *n != name);
+                    options.push((name, old));
+                }
+            }
+            self.history = options.into_iter().map(|(_, line)| line).collect();
+        }
+        self.history.push(line.to_string());
+    }
+}
+
+/// Returns true if `cmd` changes engine state that must survive a restart.
+pub fn is_replayed(cmd: &Command) -> bool {
+    matches!(
+        cmd,
+        Command::SetOption { .. }
+            | Command::NewGame
+            | Command::Position { .. }
+            | Command::SetPower { .. }
+            | Command::SetPowerAuto
+            | Command::Press { .. }
+            | Command::LockOrder { .. }
+            | Command::Suggest { .. }
+    )
+}
+
+/// Where session records live between restarts.
+pub trait SessionStore: Send + Sync {
+    /// Saves (replacing) the record for `token`.
+    fn save(&self, token: &str, record: &SessionRecord) -> io::Result<()>;
+    /// Forgets `token`. Removing an unknown token is not an error.
+    fn remove(&self, token: &str) -> io::Result<()>;
+    /// Every saved session.
+    fn load_all(&self) -> io::Result<Vec<(String, SessionRecord)>>;
+}
+
+/// Keeps records in memory: survives nothing, but lets sessions be rebuilt
+/// within a process and serves tests.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    records: Mutex<HashMap<String, SessionRecord>>,
+}
+
+impl SessionStore for MemoryStore {
+    fn save(&self, token: &str, record: &SessionRecord) -> io::Result<()> {
+        self.records
+            .lock()
+            .unwrap()
+            .insert(token.to_string(), record.clone());
+        Ok(())
+    }
+
+    fn remove(&self, token: &str) -> io::Result<()> {
+        self.records.lock().unwrap().remove(token);
+        Ok(())
+    }
+
+    fn load_all(&self) -> io::Result<Vec<(String, SessionRecord)>> {
+        let records = self.records.lock().unwrap();
+        Ok(records
+            .iter()
+            .map(|(t, r)| (t.clone(), r.clone()))
+            .collect())
+    }
+}
+
+/// One `<token>.json` file per session in a directory. Files are replaced
+/// through a temporary file, so a crash mid-write keeps the old record.
+#[derive(Debug, Clone)]
+pub struct DirStore {
+    dir: PathBuf,
+}
+
+impl DirStore {
+    /// Uses `dir`, creating it if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(DirStore { dir })
+    }
+
+    fn path(&self, token: &str) -> io::Result<PathBuf> {
+        // Tokens are hex; anything else must not escape the directory.
+        if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("bad session token '{}'", token),
+            ));
+        }
+        Ok(self.dir.join(format!("{}.json", token)))
+    }
+}
+
+impl SessionStore for DirStore {
+    fn save(&self, token: &str, record: &SessionRecord) -> io::Result<()> {
+        let path = self.path(token)?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(record)?)?;
+        fs::rename(tmp, path)
+    }
+
+    fn remove(&self, token: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(token)?) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            r => r,
+        }
+    }
+
+    fn load_all(&self) -> io::Result<Vec<(String, SessionRecord)>> {
+        let mut out = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(token) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match serde_json::from_slice(&fs::read(&path)?) {
+                Ok(record) => out.push((token.to_string(), record)),
+                Err(e) => eprintln!("skipping session file {}: {}", path.display(), e),
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// One row per session in a SQLite database, the record stored as JSON.
+#[cfg(feature = "sqlite")]
+pub struct SqliteStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Opens (creating if needed) the database at `path`.
+    pub fn open(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        let conn = rusqlite::Connection::open(path).map_err(io::Error::other)?;
+        Self::with_connection(conn)
+    }
+
+    /// A store in a private in-memory database.
+    pub fn in_memory() -> io::Result<Self> {
+        let conn = rusqlite::Connection::open_in_memory().map_err(io::Error::other)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: rusqlite::Connection) -> io::Result<Self> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sessions (token TEXT PRIMARY KEY, record TEXT NOT NULL)",
+            [],
+        )
+        .map_err(io::Error::other)?;
+        Ok(SqliteStore {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl SessionStore for SqliteStore {
+    fn save(&self, token: &str, record: &SessionRecord) -> io::Result<()> {
+        let json = serde_json::to_string(record)?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR REPLACE INTO sessions (token, record) VALUES (?1, ?2)",
+                rusqlite::params![token, json],
+            )
+            .map(drop)
+            .map_err(io::Error::other)
+    }
+
+    fn remove(&self, token: &str) -> io::Result<()> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM sessions WHERE token = ?1", [token])
+            .map(drop)
+            .map_err(io::Error::other)
+    }
+
+    fn load_all(&self) -> io::Result<Vec<(String, SessionRecord)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT token, record FROM sessions")
+            .map_err(io::Error::other)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(io::Error::other)?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (token, json) = row.map_err(io::Error::other)?;
+            match serde_json::from_str(&json) {
+                Ok(record) => out.push((token, record)),
+                Err(e) => eprintln!("skipping session row {}: {}", token, e),
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::parser::parse_command;
+
+    #[test]
+    fn dir_store_round_trips_and_rejects_bad_tokens() {
+        let dir = std::env::temp_dir().join(format!("realpolitik-sessions-{}", std::process::id()));
+        let store = DirStore::open(&dir).unwrap();
+        let record = SessionRecord {
+            options: vec![("Strength".into(), "60".into())],
+            history: vec!["setpower austria".into()],
+        };
+        store.save("abc123", &record).unwrap();
+        assert_eq!(
+            store.load_all().unwrap(),
+            vec![("abc123".to_string(), record)]
+        );
+        assert!(store.save("../x", &SessionRecord::default()).is_err());
+        store.remove("abc123").unwrap();
+        store.remove("abc123").unwrap();
+        assert!(store.load_all().unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newgame_compacts_the_history() {
+        let mut record = SessionRecord::default();
+        for line in [
+            "setoption name Strength value 40",
+            "newgame",
+            "setoption name MapFile value a.json",
+            "position 1901sm/Aavie/Avie/-",
+            "setpower austria",
+            "setoption name Strength value 60",
+            "newgame",
+            "position 1901sm/Aavie/Avie/-",
+        ] {
+            record.push(line);
+        }
+        assert_eq!(
+            record.history,
+            [
+                "setoption name MapFile value a.json",
+                "setoption name Strength value 60",
+                "newgame",
+                "position 1901sm/Aavie/Avie/-",
+            ]
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_store_round_trips() {
+        let store = SqliteStore::in_memory().unwrap();
+        let record = SessionRecord {
+            options: vec![("Strength".into(), "60".into())],
+            history: vec!["setpower austria".into()],
+        };
+        store.save("abc123", &record).unwrap();
+        store.save("abc123", &record).unwrap();
+        assert_eq!(
+            store.load_all().unwrap(),
+            vec![("abc123".to_string(), record)]
+        );
+        store.remove("abc123").unwrap();
+        store.remove("abc123").unwrap();
+        assert!(store.load_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_state_changes_are_replayed() {
+        let replayed = |line: &str| is_replayed(&parse_command(line).unwrap());
+        assert!(replayed("position 1901sm/Aavie/Avie/-"));
+        assert!(replayed("setoption name Strength value 50"));
+        assert!(!replayed("go movetime 100"));
+        assert!(!replayed("isready"));
+    }
+}
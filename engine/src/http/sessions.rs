@@ -0,0 +1,647 @@
+//! Game sessions for the HTTP server.
+//!
+//! Each session owns a full [`Engine`] (position, press and trust state,
+//! the RM+ session carried between searches) and is addressed by a
+//! random token, so one process can play many gunboat games at once.
+//! Tokens are drawn from the operating system's entropy source and never
+//! published; `/metrics` labels sessions with a separate number instead.
+//! A session is created with option overrides on top of the server's
+//! defaults and then driven with ordinary DUI command lines.
+//!
+//! Two limits keep one client from starving the rest: a cap on open
+//! sessions, and a per-session token bucket on requests. Sessions idle for
+//! longer than the idle timeout are dropped when room is needed.
+//!
+//! A `go` holds its session until the search answers, so session searches
+//! are bounded: each runs for at most the server's longest movetime, `go
+//! infinite` and `go ponder` are refused, and `POST
+//! /sessions/<token>/stop` ends a running search early without waiting
+//! for the session.
+//!
+//! With a [`SessionStore`] every session is saved after each request that
+//! changes it and reopened when the server starts (see [`super::persist`]).
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::persist::{is_replayed, SessionRecord, SessionStore};
+use super::{HttpResponse, MAX_MOVETIME_MS};
+use crate::engine::Engine;
+use crate::protocol::parser::{parse_command, Command};
+use crate::rng::secure_bytes;
+
+/// How often a session's running search is polled.
+const SEARCH_POLL: Duration = Duration::from_millis(10);
+
+/// Server-wide session and connection limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionLimits {
+    /// Sessions open at once.
+    pub max_sessions: usize,
+    /// Requests per session per minute, with bursts up to the same number.
+    pub requests_per_minute: u32,
+    /// Sessions unused for this long may be closed to make room.
+    pub idle_timeout: Duration,
+    /// Requests to the stateless endpoints per minute, all clients
+    /// together, with bursts up to the same number.
+    pub stateless_per_minute: u32,
+    /// Connections handled at once; more are turned away with a 503.
+    pub max_connections: usize,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        SessionLimits {
+            max_sessions: 64,
+            requests_per_minute: 120,
+            idle_timeout: Duration::from_secs(3600),
+            stateless_per_minute: 600,
+            max_connections: 128,
+        }
+    }
+}
+
+/// Token bucket refilled continuously at `per_minute` tokens a minute.
+#[derive(Debug, Clone)]
+pub(super) struct RateLimiter {
+    tokens: f64,
+    capacity: f64,
+    per_sec: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub(super) fn new(per_minute: u32, now: Instant) -> Self {
+        let capacity = f64::from(per_minute.max(1));
+        RateLimiter {
+            tokens: capacity,
+            capacity,
+            per_sec: capacity / 60.0,
+            last: now,
+        }
+    }
+
+    /// Takes a token if one is available.
+    pub(super) fn allow(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.capacity);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A session's engine and the record that rebuilds it.
+struct Live {
+    engine: Engine,
+    record: SessionRecord,
+}
+
+struct Session {
+    /// Non-secret number for `/metrics`.
+    id: u64,
+    live: Mutex<Live>,
+    limiter: Mutex<RateLimiter>,
+    last_used: Mutex<Instant>,
+    /// Requests answered since the session opened (or was reopened).
+    requests: AtomicU64,
+    /// `go` commands run.
+    searches: AtomicU64,
+    /// Stops the search in flight, if any; reachable while `live` is held.
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+/// Request counts for one session, for `/metrics`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionCounts {
+    /// The number the server gave the session when it opened it. Unlike
+    /// the token it is not a secret.
+    pub id: u64,
+    pub requests: u64,
+    pub searches: u64,
+}
+
+/// The open sessions of one server.
+pub struct Sessions {
+    limits: SessionLimits,
+    /// Options every new session starts with.
+    defaults: Vec<(String, String)>,
+    open: Mutex<HashMap<String, Arc<Session>>>,
+    store: Option<Arc<dyn SessionStore>>,
+    /// The id given to the next session opened.
+    next_id: AtomicU64,
+}
+
+impl Sessions {
+    pub fn new(limits: SessionLimits, defaults: Vec<(String, String)>) -> Self {
+        Sessions {
+            limits,
+            defaults,
+            open: Mutex::new(HashMap::new()),
+            store: None,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Sessions saved to `store`, reopening every session already in it.
+    pub fn with_store(
+        limits: SessionLimits,
+        defaults: Vec<(String, String)>,
+        store: Arc<dyn SessionStore>,
+    ) -> io::Result<Self> {
+        let mut sessions = Sessions::new(limits, defaults);
+        let now = Instant::now();
+        {
+            let mut open = sessions.open.lock().unwrap();
+            for (token, record) in store.load_all()? {
+                let mut engine = sessions.engine_with(&record.options);
+                for line in &record.history {
+                    if let Some(cmd) = parse_command(line) {
+                        engine.execute(&mut io::sink(), cmd);
+                    }
+                }
+                open.insert(token, Arc::new(sessions.session(engine, record, now)));
+            }
+        }
+        sessions.store = Some(store);
+        Ok(sessions)
+    }
+
+    /// A fresh engine with the server defaults and then `options` applied.
+    fn engine_with(&self, options: &[(String, String)]) -> Engine {
+        let mut engine = Engine::new();
+        for (name, value) in self.defaults.iter().chain(options) {
+            engine.set_option(name.clone(), Some(value.clone()));
+        }
+        engine
+    }
+
+    fn session(&self, engine: Engine, record: SessionRecord, now: Instant) -> Session {
+        Session {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            live: Mutex::new(Live { engine, record }),
+            limiter: Mutex::new(RateLimiter::new(self.limits.requests_per_minute, now)),
+            last_used: Mutex::new(now),
+            requests: AtomicU64::new(0),
+            searches: AtomicU64::new(0),
+            stop: Mutex::new(None),
+        }
+    }
+
+    /// Per-session request counts, ordered by id.
+    pub fn counts(&self) -> Vec<SessionCounts> {
+        let open = self.open.lock().unwrap();
+        let mut counts: Vec<SessionCounts> = open
+            .values()
+            .map(|s| SessionCounts {
+                id: s.id,
+                requests: s.requests.load(Ordering::Relaxed),
+                searches: s.searches.load(Ordering::Relaxed),
+            })
+            .collect();
+        counts.sort_by_key(|c| c.id);
+        counts
+    }
+
+    /// The session cap.
+    pub fn max_sessions(&self) -> usize {
+        self.limits.max_sessions
+    }
+
+    fn save(&self, token: &str, record: &SessionRecord) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(token, record) {
+                eprintln!("session store: save failed: {}", e);
+            }
+        }
+    }
+
+    fn forget(&self, token: &str) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.remove(token) {
+                eprintln!("session store: remove failed: {}", e);
+            }
+        }
+    }
+
+    /// Number of open sessions.
+    pub fn len(&self) -> usize {
+        self.open.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Answers a request under `/sessions`.
+    pub(super) fn route(&self, method: &str, path: &str, body: &str) -> HttpResponse {
+        let rest: Vec<&str> = path
+            .trim_start_matches("/sessions")
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        match (method, rest.as_slice()) {
+            ("POST", []) => self.create(body),
+            ("GET", []) => HttpResponse::ok(json!({
+                "sessions": self.len(),
+                "max_sessions": self.limits.max_sessions,
+            })),
+            ("DELETE", [token]) => match self.open.lock().unwrap().remove(*token) {
+                Some(_) => {
+                    self.forget(token);
+                    HttpResponse::ok(json!({ "closed": token }))
+                }
+                None => unknown(token),
+            },
+            ("POST", [token, "stop"]) => {
+                let Some(session) = self.open.lock().unwrap().get(*token).cloned() else {
+                    return unknown(token);
+                };
+                let stop = session.stop.lock().unwrap();
+                if let Some(flag) = stop.as_ref() {
+                    flag.store(true, Ordering::Relaxed);
+                }
+                HttpResponse::ok(json!({ "stopped": stop.is_some() }))
+            }
+            ("POST", [token, action @ ("commands" | "options")]) => {
+                let Some(session) = self.open.lock().unwrap().get(*token).cloned() else {
+                    return unknown(token);
+                };
+                let now = Instant::now();
+                if !session.limiter.lock().unwrap().allow(now) {
+                    return HttpResponse::error(429, "session rate limit exceeded");
+                }
+                *session.last_used.lock().unwrap() = now;
+                session.requests.fetch_add(1, Ordering::Relaxed);
+                let request: Value = match serde_json::from_str(body) {
+                    Ok(v) => v,
+                    Err(e) => return HttpResponse::error(400, format!("invalid JSON: {}", e)),
+                };
+                let mut live = session.live.lock().unwrap();
+                // A `newgame` can compact the history, so compare contents.
+                let before = live.record.history.clone();
+                let result = if *action == "commands" {
+                    run_commands(&mut live, &request["commands"], &session)
+                } else {
+                    option_pairs(&request["options"]).map(|pairs| {
+                        for (name, value) in &pairs {
+                            live.engine.set_option(name.clone(), Some(value.clone()));
+                            let line = format!("setoption name {} value {}", name, value);
+                            live.record.push(&line);
+                        }
+                        json!({ "set": pairs.len() })
+                    })
+                };
+                if live.record.history != before {
+                    self.save(token, &live.record);
+                }
+                match result {
+                    Ok(v) => HttpResponse::ok(v),
+                    Err(e) => HttpResponse::error(400, e),
+                }
+            }
+            (_, [] | [_] | [_, "commands" | "options" | "stop"]) => {
+                HttpResponse::error(405, format!("{} not allowed on {}", method, path))
+            }
+            _ => HttpResponse::error(404, format!("no endpoint {}", path)),
+        }
+    }
+
+    /// Opens a session with the server defaults plus the body's `options`.
+    fn create(&self, body: &str) -> HttpResponse {
+        let request: Value = if body.trim().is_empty() {
+            json!({})
+        } else {
+            match serde_json::from_str(body) {
+                Ok(v) => v,
+                Err(e) => return HttpResponse::error(400, format!("invalid JSON: {}", e)),
+            }
+        };
+        let options = match &request["options"] {
+            Value::Null => Vec::new(),
+            v => match option_pairs(v) {
+                Ok(pairs) => pairs,
+                Err(e) => return HttpResponse::error(400, e),
+            },
+        };
+        let engine = self.engine_with(&options);
+
+        let now = Instant::now();
+        let mut open = self.open.lock().unwrap();
+        if open.len() >= self.limits.max_sessions {
+            open.retain(|token, s| {
+                let idle = now.saturating_duration_since(*s.last_used.lock().unwrap())
+                    >= self.limits.idle_timeout;
+                if idle {
+                    self.forget(token);
+                }
+                !idle
+            });
+        }
+        if open.len() >= self.limits.max_sessions {
+            return HttpResponse::error(429, "too many open sessions");
+        }
+        let mut secret = [0u8; 16];
+        if let Err(e) = secure_bytes(&mut secret) {
+            return HttpResponse::error(503, format!("no secure randomness for a token: {}", e));
+        }
+        let token: String = secret.iter().map(|b| format!("{:02x}", b)).collect();
+        let record = SessionRecord {
+            options,
+            history: Vec::new(),
+        };
+        self.save(&token, &record);
+        let session = Arc::new(self.session(engine, record, now));
+        let id = session.id;
+        open.insert(token.clone(), session);
+        HttpResponse::ok(json!({ "session": token, "id": id }))
+    }
+}
+
+fn unknown(token: &str) -> HttpResponse {
+    HttpResponse::error(404, format!("no session {}", token))
+}
+
+/// Reads `{"Name": "value", ...}` as option name and value pairs.
+fn option_pairs(options: &Value) -> Result<Vec<(String, String)>, String> {
+    let Some(options) = options.as_object() else {
+        return Err("\"options\" must map option names to values".to_string());
+    };
+    options
+        .iter()
+        .map(|(name, value)| match value {
+            Value::String(s) => Ok((name.clone(), s.clone())),
+            Value::Number(_) | Value::Bool(_) => Ok((name.clone(), value.to_string())),
+            _ => Err(format!("option {} must be a string, number or bool", name)),
+        })
+        .collect()
+}
+
+/// Runs DUI command lines in order and returns everything they wrote. A
+/// `go` waits for its search, so the answer includes `bestorders`; it runs
+/// for at most [`MAX_MOVETIME_MS`], and `go infinite` and `go ponder` are
+/// refused. State changes are added to the session's record.
+fn run_commands(live: &mut Live, commands: &Value, session: &Session) -> Result<Value, String> {
+    let Some(commands) = commands.as_array() else {
+        return Err("\"commands\" must be a list of DUI command lines".to_string());
+    };
+    let mut out = Vec::new();
+    for line in commands {
+        let line = line.as_str().ok_or("commands must be strings")?;
+        let mut cmd = parse_command(line).ok_or_else(|| format!("bad command '{}'", line))?;
+        if let Command::Go(params) = &mut cmd {
+            if params.infinite || params.ponder {
+                return Err(format!(
+                    "'{}': sessions cannot search without a time limit",
+                    line
+                ));
+            }
+            let movetime = params.movetime.or_else(|| {
+                live.engine
+                    .options
+                    .get("SearchTime")
+                    .and_then(|v| v.parse().ok())
+            });
+            params.movetime = movetime.map(|ms| ms.min(MAX_MOVETIME_MS));
+        }
+        if is_replayed(&cmd) {
+            live.record.push(line);
+        }
+        let searching = matches!(cmd, Command::Go(_));
+        if searching {
+            session.searches.fetch_add(1, Ordering::Relaxed);
+        }
+        let engine = &mut live.engine;
+        engine.execute(&mut out, cmd);
+        if searching && engine.is_searching() {
+            *session.stop.lock().unwrap() = Some(engine.stop_handle());
+        }
+        while engine.is_searching() && !engine.poll_search_done(&mut out) {
+            std::thread::sleep(SEARCH_POLL);
+        }
+        *session.stop.lock().unwrap() = None;
+    }
+    let output: Vec<String> = String::from_utf8_lossy(&out)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    Ok(json!({ "output": output }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_refills_over_time() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(2, start);
+        assert!(limiter.allow(start));
+        assert!(limiter.allow(start));
+        assert!(!limiter.allow(start));
+        // Two a minute: one token back after thirty seconds.
+        assert!(limiter.allow(start + Duration::from_secs(30)));
+        assert!(!limiter.allow(start + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn sessions_keep_separate_engines() {
+        let sessions = Sessions::new(SessionLimits::default(), vec![]);
+        let open = |options: Value| {
+            let resp = sessions.route(
+                "POST",
+                "/sessions",
+                &json!({ "options": options }).to_string(),
+            );
+            resp.body["session"].as_str().unwrap().to_string()
+        };
+        let a = open(json!({ "Strength": 50 }));
+        let b = open(json!({}));
+        assert_ne!(a, b);
+        assert_eq!(sessions.len(), 2);
+
+        let body = json!({ "commands": [
+            "position 1901sm/Aavie/Avie/-",
+            "setpower austria",
+            "go movetime 50",
+        ]})
+        .to_string();
+        let resp = sessions.route("POST", &format!("/sessions/{}/commands", a), &body);
+        assert_eq!(resp.status, 200);
+        let output = resp.body["output"].as_array().unwrap();
+        assert!(output
+            .iter()
+            .any(|l| l.as_str().unwrap().starts_with("bestorders")));
+
+        let a_session = sessions.open.lock().unwrap()[&a].clone();
+        let b_session = sessions.open.lock().unwrap()[&b].clone();
+        let a_engine = &a_session.live.lock().unwrap().engine;
+        assert!(a_engine.position.is_some());
+        assert!(b_session.live.lock().unwrap().engine.position.is_none());
+        assert_eq!(
+            a_engine.options.get("Strength").map(String::as_str),
+            Some("50")
+        );
+
+        assert_eq!(
+            sessions
+                .route("DELETE", &format!("/sessions/{}", a), "")
+                .status,
+            200
+        );
+        assert_eq!(
+            sessions
+                .route("POST", &format!("/sessions/{}/commands", a), &body)
+                .status,
+            404
+        );
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let limits = SessionLimits {
+            max_sessions: 1,
+            requests_per_minute: 1,
+            ..SessionLimits::default()
+        };
+        let sessions = Sessions::new(limits, vec![("Strength".into(), "40".into())]);
+        let token = sessions.route("POST", "/sessions", "").body["session"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(sessions.route("POST", "/sessions", "").status, 429);
+
+        let path = format!("/sessions/{}/options", token);
+        let resp = sessions.route("POST", &path, r#"{"options":{"InfoLevel":"quiet"}}"#);
+        assert_eq!(resp.body["set"], 1);
+        assert_eq!(
+            sessions.route("POST", &path, r#"{"options":{}}"#).status,
+            429
+        );
+
+        let session = sessions.open.lock().unwrap()[&token].clone();
+        let engine = &session.live.lock().unwrap().engine;
+        assert_eq!(
+            engine.options.get("Strength").map(String::as_str),
+            Some("40")
+        );
+    }
+
+    #[test]
+    fn session_searches_are_bounded_and_stoppable() {
+        let sessions = Arc::new(Sessions::new(SessionLimits::default(), vec![]));
+        let token = sessions.route("POST", "/sessions", "").body["session"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let commands = format!("/sessions/{}/commands", token);
+        let stop = format!("/sessions/{}/stop", token);
+        for go in ["go infinite", "go ponder"] {
+            let body = json!({ "commands": ["position 1901sm/Aavie/Avie/-", go] });
+            let resp = sessions.route("POST", &commands, &body.to_string());
+            assert_eq!(resp.status, 400, "{}", go);
+        }
+        assert_eq!(sessions.route("POST", &stop, "").body["stopped"], false);
+
+        // An hour-long movetime is capped, and a stop ends it early.
+        let started = Instant::now();
+        let search = {
+            let (sessions, commands) = (Arc::clone(&sessions), commands.clone());
+            std::thread::spawn(move || {
+                let body = json!({ "commands": [
+                    "position 1901sm/Aavie/Avie/-",
+                    "setpower austria",
+                    "go movetime 3600000",
+                ]});
+                sessions.route("POST", &commands, &body.to_string())
+            })
+        };
+        while sessions.route("POST", &stop, "").body["stopped"] != true {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let resp = search.join().unwrap();
+        assert!(started.elapsed() < Duration::from_millis(MAX_MOVETIME_MS));
+        assert!(resp.body["output"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|l| l.as_str().unwrap().starts_with("bestorders")));
+        let session = sessions.open.lock().unwrap()[&token].clone();
+        let live = session.live.lock().unwrap();
+        assert_eq!(
+            live.engine.options.get("SearchTime").map(String::as_str),
+            Some("60000")
+        );
+    }
+
+    #[test]
+    fn sessions_are_rebuilt_from_the_store() {
+        let store: Arc<dyn SessionStore> = Arc::new(super::super::MemoryStore::default());
+        let sessions =
+            Sessions::with_store(SessionLimits::default(), vec![], Arc::clone(&store)).unwrap();
+        let token = sessions
+            .route("POST", "/sessions", r#"{"options":{"Strength":70}}"#)
+            .body["session"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let body = json!({ "commands": [
+            "position 1901sm/Aavie/Avie/-",
+            "setpower austria",
+            "isready",
+        ]})
+        .to_string();
+        sessions.route("POST", &format!("/sessions/{}/commands", token), &body);
+        let record = &store.load_all().unwrap()[0].1;
+        assert_eq!(
+            record.options,
+            vec![("Strength".to_string(), "70".to_string())]
+        );
+        assert_eq!(record.history.len(), 2);
+
+        // A restarted server picks the game up where it was.
+        let restarted =
+            Sessions::with_store(SessionLimits::default(), vec![], Arc::clone(&store)).unwrap();
+        let session = restarted.open.lock().unwrap()[&token].clone();
+        let live = session.live.lock().unwrap();
+        assert!(live.engine.position.is_some());
+        assert_eq!(live.engine.active_power, Some(crate::board::Power::Austria));
+        assert_eq!(
+            live.engine.options.get("Strength").map(String::as_str),
+            Some("70")
+        );
+        drop(live);
+
+        restarted.route("DELETE", &format!("/sessions/{}", token), "");
+        assert!(store.load_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn counts_requests_and_searches_per_session() {
+        let sessions = Sessions::new(SessionLimits::default(), vec![]);
+        let created = sessions.route("POST", "/sessions", "").body;
+        let token = created["session"].as_str().unwrap().to_string();
+        let body = json!({ "commands": [
+            "position 1901sm/Aavie/Avie/-",
+            "setpower austria",
+            "go movetime 20",
+        ]})
+        .to_string();
+        sessions.route("POST", &format!("/sessions/{}/commands", token), &body);
+        let counts = sessions.counts();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(Some(counts[0].id), created["id"].as_u64());
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!((counts[0].requests, counts[0].searches), (1, 1));
+    }
+}
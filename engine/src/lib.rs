@@ -2,15 +2,35 @@
 //!
 //! Exposes the board representation, resolver, move generation, and protocol
 //! modules for use by integration tests and the binary entry point.
+//!
+//! The `minimal` feature marks a build without rayon, OS entropy or ONNX
+//! (`cargo build --no-default-features --features minimal`): the board,
+//! move generation and resolver are unchanged, and search runs on one
+//! thread.
+
+#[cfg(all(
+    feature = "minimal",
+    any(feature = "parallel", feature = "entropy", feature = "neural")
+))]
+compile_error!(
+    "the `minimal` feature excludes `parallel`, `entropy` and `neural`; \
+     build with --no-default-features --features minimal"
+);
 
+pub mod analysis;
 pub mod board;
 pub mod engine;
 pub mod eval;
+pub mod http;
 pub mod movegen;
 pub mod nn;
 pub mod opening_book;
+pub(crate) mod parallel;
 pub mod press;
 pub mod protocol;
 pub mod resolve;
+pub mod rng;
+pub mod script;
 pub mod search;
 pub mod selfplay;
+pub mod throughput;
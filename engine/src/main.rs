@@ -6,19 +6,445 @@
 //! Stdin is read on a dedicated thread and commands are forwarded via
 //! an mpsc channel so that `go` search runs asynchronously and `stop`
 //! can interrupt it.
+//!
+//! Subcommands:
+//!   realpolitik convert --from <notation> --to <notation> [OPTIONS] [FILE]
+//!       Translates an order file (or stdin) between `dson`, `daide`, and
+//!       `human` notation, writing to stdout.
+//!       --power P        Power giving orders on lines without a `power:` prefix
+//!       --position DFEN  Position the orders were given in (DAIDE output only)
+//!   realpolitik checkmap <map.json>
+//!       Validates a variant map definition and prints every problem found.
+//!   realpolitik checkmap --print-standard
+//!       Prints the built-in standard map as a definition to start from.
+//!   realpolitik selfplay [OPTIONS]
+//!       Plays games against itself, writing each game record (JSONL) to
+//!       stdout or --output, then the run summary and each power's final
+//!       supply center statistics to stderr, for strength regression runs
+//!       and training data. Shares its runner with the `selfplay` binary;
+//!       `realpolitik selfplay --help` lists the options.
+//!   realpolitik simulate --opponent-script FILE [OPTIONS]
+//!       Plays one game in which the powers named in FILE play its fixed
+//!       orders and the engine plays the rest, printing every phase's
+//!       orders in the script's format and the final supply centers.
+//!       --movetime MS      Search time per power and phase (default 2000)
+//!       --strength N       Engine strength 1-100 (default 100)
+//!       --max-year Y       Last year played (default: the year after the
+//!                          script's last)
+//!       --seed N           Random seed (default 1)
+//!   realpolitik --serve-http [ADDR] [OPTIONS]
+//!       Serves adjudication, evaluation, best orders and game sessions as
+//!       JSON over HTTP on ADDR (default 127.0.0.1:8080) instead of speaking
+//!       DUI; see the `http` module for the endpoints.
+//!       --max-sessions N   Game sessions open at once (default 64)
+//!       --rate N           Requests per session per minute (default 120)
+//!       --stateless-rate N Stateless requests per minute, all clients
+//!                          together (default 600)
+//!       --max-connections N
+//!                          Connections handled at once (default 128)
+//!       --option K=V       Engine option every session starts with (repeatable)
+//!       --state-dir DIR    Save sessions in DIR and reopen them on restart
+//!       --state-db FILE    Same, in a SQLite database (`sqlite` feature)
+//!   realpolitik --daide HOST:PORT [OPTIONS]
+//!       Joins a game on a DAIDE server and plays it until the server ends
+//!       it, searching each phase with the DUI engine.
+//!       --name NAME        Name to join with (default realpolitik)
+//!       --movetime MS      Search time per phase (default: half the server's
+//!                          move time limit, else the SearchTime option)
+//!       --option K=V       Engine option to set before joining (repeatable)
 
-use std::io::{self, BufRead};
-use std::sync::mpsc;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::{mpsc, Arc};
 use std::time::Duration;
 
-use realpolitik::engine::Engine;
-use realpolitik::protocol::parser::{parse_command, Command};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use realpolitik::board::mapdef::{check_map, MapDef};
+use realpolitik::board::{Power, ALL_POWERS};
+use realpolitik::engine::{platform, Engine};
+use realpolitik::http::{DirStore, Server, SessionLimits, SessionStore};
+use realpolitik::protocol::convert::{convert_line, ConvertOptions, Notation};
+use realpolitik::protocol::daide::client::{self as daide, DaideConfig};
+use realpolitik::protocol::dfen::parse_dfen;
+use realpolitik::protocol::parser::parse_command;
+use realpolitik::script::OpponentScript;
+use realpolitik::selfplay::{self, SelfPlayConfig};
 
 /// Poll interval while a search is in flight (10 ms).
 const SEARCH_POLL_MS: u64 = 10;
 
+/// Listen address for `--serve-http` without one.
+const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:8080";
+
+/// Runs `realpolitik convert`. Returns the process exit code.
+fn run_convert(args: &[String]) -> i32 {
+    let mut from: Option<Notation> = None;
+    let mut to: Option<Notation> = None;
+    let mut opts = ConvertOptions::default();
+    let mut input: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).map(String::as_str);
+        let result: Result<(), String> = match (args[i].as_str(), value) {
+            ("--from", Some(v)) => v.parse().map(|n| from = Some(n)).map_err(|e| e.to_string()),
+            ("--to", Some(v)) => v.parse().map(|n| to = Some(n)).map_err(|e| e.to_string()),
+            ("--power", Some(v)) => Power::from_name(v)
+                .map(|p| opts.power = Some(p))
+                .ok_or_else(|| format!("unknown power '{}'", v)),
+            ("--position", Some(v)) => parse_dfen(v)
+                .map(|s| opts.state = Some(s))
+                .map_err(|e| format!("bad --position: {}", e)),
+            (flag, None) if flag.starts_with("--") => Err(format!("{} needs a value", flag)),
+            (flag, _) if flag.starts_with("--") => Err(format!("unknown option {}", flag)),
+            (path, _) => {
+                input = Some(path.to_string());
+                i += 1;
+                continue;
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("convert: {}", e);
+            return 2;
+        }
+        i += 2;
+    }
+    let (from, to) = match (from, to) {
+        (Some(f), Some(t)) => (f, t),
+        _ => {
+            eprintln!("usage: realpolitik convert --from <dson|daide|human> --to <dson|daide|human> [--power P] [--position DFEN] [FILE]");
+            return 2;
+        }
+    };
+
+    let reader: Box<dyn BufRead> = match input.as_deref() {
+        None | Some("-") => Box::new(BufReader::new(io::stdin())),
+        Some(path) => match File::open(path) {
+            Ok(f) => Box::new(BufReader::new(f)),
+            Err(e) => {
+                eprintln!("convert: {}: {}", path, e);
+                return 1;
+            }
+        },
+    };
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut failed = 0;
+    for (n, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("convert: {}", e);
+                return 1;
+            }
+        };
+        match convert_line(&line, from, to, &opts) {
+            Ok(converted) => writeln!(out, "{}", converted).unwrap(),
+            Err(e) => {
+                eprintln!("convert: line {}: {}", n + 1, e);
+                failed += 1;
+            }
+        }
+    }
+    out.flush().unwrap();
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Runs `realpolitik checkmap`. Returns the process exit code.
+fn run_checkmap(args: &[String]) -> i32 {
+    let path = match args.first() {
+        Some(p) if p == "--print-standard" => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&MapDef::standard()).unwrap()
+            );
+            return 0;
+        }
+        Some(p) => p,
+        None => {
+            eprintln!("usage: realpolitik checkmap <map.json> | --print-standard");
+            return 2;
+        }
+    };
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("checkmap: {}: {}", path, e);
+            return 2;
+        }
+    };
+    let def: MapDef = match serde_json::from_str(&text) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("checkmap: {}: invalid map definition: {}", path, e);
+            return 2;
+        }
+    };
+    let issues = check_map(&def);
+    for issue in &issues {
+        println!("error: {}", issue);
+    }
+    if issues.is_empty() {
+        println!(
+            "map {} ok: {} provinces, {} supply centers, {} adjacencies",
+            def.name,
+            def.provinces.len(),
+            def.supply_center_count(),
+            def.adjacencies.len()
+        );
+        0
+    } else {
+        println!("map {}: {} problem(s)", def.name, issues.len());
+        1
+    }
+}
+
+/// Runs `realpolitik --serve-http`. Returns the process exit code.
+fn run_serve_http(args: &[String]) -> i32 {
+    let mut addr = DEFAULT_HTTP_ADDR.to_string();
+    let mut limits = SessionLimits::default();
+    let mut defaults = Vec::new();
+    // The flag and path of the session store, if any.
+    let mut state: Option<(&str, String)> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).map(String::as_str);
+        let result: Result<(), String> = match (args[i].as_str(), value) {
+            ("--max-sessions", Some(v)) => v
+                .parse()
+                .map(|n| limits.max_sessions = n)
+                .map_err(|_| format!("bad --max-sessions '{}'", v)),
+            ("--rate", Some(v)) => v
+                .parse()
+                .map(|n| limits.requests_per_minute = n)
+                .map_err(|_| format!("bad --rate '{}'", v)),
+            ("--stateless-rate", Some(v)) => v
+                .parse()
+                .map(|n| limits.stateless_per_minute = n)
+                .map_err(|_| format!("bad --stateless-rate '{}'", v)),
+            ("--max-connections", Some(v)) => v
+                .parse()
+                .map(|n| limits.max_connections = n)
+                .map_err(|_| format!("bad --max-connections '{}'", v)),
+            ("--state-dir", Some(v)) => {
+                state = Some(("--state-dir", v.to_string()));
+                Ok(())
+            }
+            ("--state-db", Some(v)) => {
+                state = Some(("--state-db", v.to_string()));
+                Ok(())
+            }
+            ("--option", Some(v)) => match v.split_once('=') {
+                Some((name, value)) => {
+                    defaults.push((name.to_string(), value.to_string()));
+                    Ok(())
+                }
+                None => Err(format!("--option needs Name=Value, got '{}'", v)),
+            },
+            (flag, None) if flag.starts_with("--") => Err(format!("{} needs a value", flag)),
+            (flag, _) if flag.starts_with("--") => Err(format!("unknown option {}", flag)),
+            (a, _) => {
+                addr = a.to_string();
+                i += 1;
+                continue;
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("serve-http: {}", e);
+            return 2;
+        }
+        i += 2;
+    }
+
+    let server = match state {
+        None => Server::new(limits, defaults),
+        Some((flag, path)) => {
+            let opened = open_session_store(flag, &path)
+                .and_then(|store| Server::with_store(limits, defaults, store));
+            match opened {
+                Ok(server) => server,
+                Err(e) => {
+                    eprintln!("serve-http: {} {}: {}", flag, path, e);
+                    return 1;
+                }
+            }
+        }
+    };
+    let server = Arc::new(server);
+    match realpolitik::http::serve(addr.as_str(), server) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("serve-http: {}: {}", addr, e);
+            1
+        }
+    }
+}
+
+/// Opens the session store named by `--state-dir` or `--state-db`.
+fn open_session_store(flag: &str, path: &str) -> std::io::Result<Arc<dyn SessionStore>> {
+    if flag == "--state-dir" {
+        return Ok(Arc::new(DirStore::open(path)?));
+    }
+    #[cfg(feature = "sqlite")]
+    {
+        Ok(Arc::new(realpolitik::http::SqliteStore::open(path)?))
+    }
+    #[cfg(not(feature = "sqlite"))]
+    {
+        Err(std::io::Error::other(
+            "this build has no SQLite support (build with --features sqlite)",
+        ))
+    }
+}
+
+/// Runs `realpolitik simulate`. Returns the process exit code.
+fn run_simulate(args: &[String]) -> i32 {
+    let mut config = SelfPlayConfig {
+        num_games: 1,
+        temperature: 0.0,
+        threads: 1,
+        seed: 1,
+        quiet: true,
+        ..Default::default()
+    };
+    let mut script_path: Option<String> = None;
+    let mut max_year: Option<u16> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).map(String::as_str);
+        let number = |v: &str| v.parse().map_err(|_| format!("bad {} '{}'", args[i], v));
+        let result: Result<(), String> = match (args[i].as_str(), value) {
+            ("--opponent-script", Some(v)) => {
+                script_path = Some(v.to_string());
+                Ok(())
+            }
+            ("--movetime", Some(v)) => number(v).map(|n| config.movetime_ms = n),
+            ("--strength", Some(v)) => number(v).map(|n| config.strength = n),
+            ("--max-year", Some(v)) => number(v).map(|n| max_year = Some(n as u16)),
+            ("--seed", Some(v)) => number(v).map(|n| config.seed = n),
+            (flag, None) if flag.starts_with("--") => Err(format!("{} needs a value", flag)),
+            (flag, _) => Err(format!("unknown option {}", flag)),
+        };
+        if let Err(e) = result {
+            eprintln!("simulate: {}", e);
+            return 2;
+        }
+        i += 2;
+    }
+    let Some(path) = script_path else {
+        eprintln!("usage: realpolitik simulate --opponent-script FILE [--movetime MS] [--strength N] [--max-year Y] [--seed N]");
+        return 2;
+    };
+    let script = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| OpponentScript::parse(&text).map_err(|e| e.to_string()))
+    {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("simulate: {}: {}", path, e);
+            return 1;
+        }
+    };
+    config.max_year = max_year.unwrap_or_else(|| script.last_year().unwrap_or(1900) + 1);
+    config.script = Some(Arc::new(script));
+
+    let mut rng = SmallRng::seed_from_u64(config.seed);
+    let game = selfplay::play_game(&config, 0, &mut rng);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for phase in &game.phases {
+        let label = phase.dfen.split('/').next().unwrap_or_default();
+        for (power, orders) in &phase.orders {
+            let _ = writeln!(out, "{} {}: {}", label, power.name(), orders);
+        }
+    }
+    let centers: Vec<String> = ALL_POWERS
+        .iter()
+        .zip(game.final_sc_counts)
+        .map(|(p, n)| format!("{} {}", p.name(), n))
+        .collect();
+    let winner = game.winner.map_or("none", |p| p.name());
+    let _ = writeln!(
+        out,
+        "# final {}: {}; winner {}",
+        game.final_year,
+        centers.join(", "),
+        winner
+    );
+    0
+}
+
+/// Runs `realpolitik --daide`. Returns the process exit code.
+fn run_daide(args: &[String]) -> i32 {
+    let Some(addr) = args.first().filter(|a| !a.starts_with("--")) else {
+        eprintln!(
+            "usage: realpolitik --daide HOST:PORT [--name NAME] [--movetime MS] [--option K=V]..."
+        );
+        return 2;
+    };
+    let mut config = DaideConfig::default();
+    let mut engine = Engine::new();
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1).map(String::as_str);
+        let result: Result<(), String> = match (args[i].as_str(), value) {
+            ("--name", Some(v)) => {
+                config.name = v.to_string();
+                Ok(())
+            }
+            ("--movetime", Some(v)) => v
+                .parse()
+                .map(|ms| config.movetime = Some(Duration::from_millis(ms)))
+                .map_err(|_| format!("bad --movetime '{}'", v)),
+            ("--option", Some(v)) => match v.split_once('=') {
+                Some((name, value)) => {
+                    engine.set_option(name.to_string(), Some(value.to_string()));
+                    Ok(())
+                }
+                None => Err(format!("--option needs Name=Value, got '{}'", v)),
+            },
+            (flag, None) if flag.starts_with("--") => Err(format!("{} needs a value", flag)),
+            (flag, _) => Err(format!("unknown option {}", flag)),
+        };
+        if let Err(e) = result {
+            eprintln!("daide: {}", e);
+            return 2;
+        }
+        i += 2;
+    }
+
+    match daide::connect(addr, &mut engine, &config) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("daide: {}: {}", addr, e);
+            1
+        }
+    }
+}
+
 /// Runs the main DUI protocol loop with async go/stop support.
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("convert") => std::process::exit(run_convert(&args[2..])),
+        Some("checkmap") => std::process::exit(run_checkmap(&args[2..])),
+        Some("selfplay") => std::process::exit(selfplay::run_cli(&args[2..])),
+        Some("simulate") => std::process::exit(run_simulate(&args[2..])),
+        Some("--serve-http") => std::process::exit(run_serve_http(&args[2..])),
+        Some("--daide") => std::process::exit(run_daide(&args[2..])),
+        _ => {}
+    }
+
     let stdout = io::stdout();
     let mut out = io::BufWriter::new(stdout.lock());
     let mut engine = Engine::new();
@@ -26,6 +452,8 @@ fn main() {
     // Spawn a dedicated stdin reader thread.
     let (tx, rx) = mpsc::channel::<String>();
     std::thread::spawn(move || {
+        // Registered so `ReaderNice` can lower this thread's priority.
+        platform::register_reader_thread();
         let stdin = io::stdin();
         for line in stdin.lock().lines() {
             match line {
@@ -40,12 +468,15 @@ fn main() {
     });
 
     loop {
-        // Decide whether to block or poll based on search state.
-        let line = if engine.is_searching() {
+        engine.poll_observer(&mut out);
+
+        // Decide whether to block or poll based on search and observe state.
+        let line = if engine.is_searching() || engine.is_observing() {
             match rx.recv_timeout(Duration::from_millis(SEARCH_POLL_MS)) {
                 Ok(l) => Some(l),
                 Err(mpsc::RecvTimeoutError::Timeout) => {
-                    // Check if the search finished naturally.
+                    // Check if the search finished naturally; observe
+                    // output is drained at the top of the loop.
                     engine.poll_search_done(&mut out);
                     continue;
                 }
@@ -68,52 +499,8 @@ fn main() {
             None => continue,
         };
 
-        match cmd {
-            Command::Dui => {
-                engine.handle_dui(&mut out);
-            }
-            Command::IsReady => {
-                // If searching, wait for it to finish before responding.
-                if engine.is_searching() {
-                    engine.handle_stop(&mut out);
-                }
-                engine.handle_isready(&mut out);
-            }
-            Command::SetOption { name, value } => {
-                engine.set_option(name, value);
-            }
-            Command::NewGame => {
-                if engine.is_searching() {
-                    engine.handle_stop(&mut out);
-                }
-                engine.new_game();
-            }
-            Command::Position { dfen } => {
-                if let Err(e) = engine.set_position(&dfen) {
-                    eprintln!("{}", e);
-                }
-            }
-            Command::SetPower { power } => {
-                engine.set_power(power);
-            }
-            Command::Go(params) => {
-                engine.handle_go(&mut out, Some(&params));
-            }
-            Command::Stop => {
-                if engine.is_searching() {
-                    engine.handle_stop(&mut out);
-                }
-            }
-            Command::Press { raw } => {
-                engine.handle_press(&raw);
-            }
-            Command::Quit => {
-                // Flush any in-flight search results before exiting.
-                if engine.is_searching() {
-                    engine.handle_stop(&mut out);
-                }
-                break;
-            }
+        if !engine.execute(&mut out, cmd) {
+            break;
         }
     }
 }
@@ -4,14 +4,15 @@
 //! phase at the end of a game year.
 
 use crate::board::{
-    BoardState, Coast, Location, Order, OrderUnit, Power, ProvinceType, UnitType, ALL_PROVINCES,
-    PROVINCE_COUNT,
+    BoardState, Coast, Location, Order, OrderUnit, Power, Province, ProvinceType, UnitType,
+    MAX_PROVINCES,
 };
 
 /// Generates all legal build-phase orders for a given power.
 ///
 /// Compares SC count to unit count:
 /// - More SCs than units: can build in unoccupied home SCs (plus Waive).
+///   Fleet builds in split-coast provinces get one order per coast.
 /// - Fewer SCs than units: must disband own units.
 /// - Equal: no orders needed (empty vec).
 pub fn legal_builds(power: Power, state: &BoardState) -> Vec<Order> {
@@ -49,16 +50,16 @@ fn generate_build_orders(power: Power, state: &BoardState, _build_count: usize)
     orders.push(Order::Waive);
 
     // Can build in unoccupied home SCs that the power currently owns.
-    for prov in ALL_PROVINCES.iter() {
+    for prov in state.map().provinces() {
         // Must be a supply center with this power as home power.
-        if prov.home_power() != Some(power) {
+        if state.map().home_power(*prov) != Some(power) {
             continue;
         }
-        if !prov.is_supply_center() {
+        if !state.map().is_supply_center(*prov) {
             continue;
         }
 
-        let idx = *prov as usize;
+        let idx = prov.index();
 
         // Must currently be owned by this power.
         if state.sc_owner[idx] != Some(power) {
@@ -70,7 +71,7 @@ fn generate_build_orders(power: Power, state: &BoardState, _build_count: usize)
             continue;
         }
 
-        let prov_type = prov.province_type();
+        let prov_type = state.map().province_type(*prov);
 
         // Army can be built in land or coastal home SCs.
         if prov_type == ProvinceType::Land || prov_type == ProvinceType::Coastal {
@@ -84,9 +85,9 @@ fn generate_build_orders(power: Power, state: &BoardState, _build_count: usize)
 
         // Fleet can be built in coastal or sea home SCs.
         if prov_type == ProvinceType::Coastal || prov_type == ProvinceType::Sea {
-            if prov.has_coasts() {
+            if state.map().has_coasts(*prov) {
                 // Split-coast: generate one build per coast.
-                for coast in prov.coasts() {
+                for coast in state.map().coasts(*prov) {
                     orders.push(Order::Build {
                         unit: OrderUnit {
                             unit_type: UnitType::Fleet,
@@ -112,12 +113,12 @@ fn generate_build_orders(power: Power, state: &BoardState, _build_count: usize)
 fn generate_disband_orders(power: Power, state: &BoardState) -> Vec<Order> {
     let mut orders = Vec::new();
 
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some((p, unit_type)) = state.units[i] {
             if p != power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
+            let prov = Province::from_index(i);
             let coast = state.fleet_coast[i].unwrap_or(Coast::None);
             orders.push(Order::Disband {
                 unit: OrderUnit {
@@ -227,6 +228,22 @@ mod tests {
             )
             .collect();
         assert_eq!(stp_builds.len(), 3); // Army, Fleet NC, Fleet SC
+
+        // Every generated build names a coast where one is required and
+        // passes the resolver's validation.
+        for order in stp_builds {
+            assert_eq!(
+                crate::resolve::validate_build(order, Power::Russia, &state),
+                Ok(())
+            );
+        }
+        let coastless_fleet = Order::Build {
+            unit: OrderUnit {
+                unit_type: UnitType::Fleet,
+                location: Location::new(Province::Stp),
+            },
+        };
+        assert!(!orders.contains(&coastless_fleet));
     }
 
     #[test]
@@ -6,10 +6,15 @@
 pub mod build;
 pub mod movement;
 pub mod retreat;
+pub mod stats;
+pub mod validate;
 
 use rand::Rng;
 
-use crate::board::{BoardState, Order, Phase, Power, ALL_PROVINCES, PROVINCE_COUNT};
+pub use stats::{stats, MoveStats, PowerStats};
+pub use validate::{validate, Correction, CorrectionReason};
+
+use crate::board::{BoardState, Order, Phase, Power, Province, MAX_PROVINCES};
 
 /// Generates a set of random legal orders for the given power.
 ///
@@ -27,14 +32,15 @@ pub fn random_orders(power: Power, state: &BoardState, rng: &mut impl Rng) -> Ve
 /// Picks one random legal movement order for each of the power's units.
 fn random_movement_orders(power: Power, state: &BoardState, rng: &mut impl Rng) -> Vec<Order> {
     let mut orders = Vec::new();
+    let mut legal = Vec::new();
 
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some((p, _)) = state.units[i] {
             if p != power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
-            let legal = movement::legal_orders(prov, state);
+            let prov = Province::from_index(i);
+            movement::legal_orders_into(prov, state, &mut legal);
             if !legal.is_empty() {
                 let idx = rng.gen_range(0..legal.len());
                 orders.push(legal[idx]);
@@ -49,12 +55,12 @@ fn random_movement_orders(power: Power, state: &BoardState, rng: &mut impl Rng)
 fn random_retreat_orders(power: Power, state: &BoardState, rng: &mut impl Rng) -> Vec<Order> {
     let mut orders = Vec::new();
 
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some(d) = &state.dislodged[i] {
             if d.power != power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
+            let prov = Province::from_index(i);
             let legal = retreat::legal_retreats(prov, state);
             if !legal.is_empty() {
                 let idx = rng.gen_range(0..legal.len());
@@ -167,11 +173,11 @@ mod tests {
     use crate::board::{
         BoardState, Coast, DislodgedUnit, Phase, Power, Province, Season, UnitType,
     };
-    use rand::rngs::StdRng;
+    use rand::rngs::SmallRng;
     use rand::SeedableRng;
 
-    fn seeded_rng() -> StdRng {
-        StdRng::seed_from_u64(42)
+    fn seeded_rng() -> SmallRng {
+        SmallRng::seed_from_u64(42)
     }
 
     #[test]
@@ -250,8 +256,8 @@ mod tests {
         state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
         state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
 
-        let orders1 = random_orders(Power::Austria, &state, &mut StdRng::seed_from_u64(12345));
-        let orders2 = random_orders(Power::Austria, &state, &mut StdRng::seed_from_u64(12345));
+        let orders1 = random_orders(Power::Austria, &state, &mut SmallRng::seed_from_u64(12345));
+        let orders2 = random_orders(Power::Austria, &state, &mut SmallRng::seed_from_u64(12345));
         assert_eq!(orders1, orders2);
     }
 
@@ -272,17 +278,17 @@ mod tests {
 
         // Run many times to increase confidence.
         for seed in 0..50 {
-            let mut rng = StdRng::seed_from_u64(seed);
+            let mut rng = SmallRng::seed_from_u64(seed);
             let orders = random_orders(Power::Austria, &state, &mut rng);
             assert_eq!(orders.len(), 3);
 
             for order in &orders {
                 match order {
                     Order::Hold { unit } => {
-                        assert!(state.units[unit.location.province as usize].is_some());
+                        assert!(state.units[unit.location.province.index()].is_some());
                     }
-                    Order::Move { unit, dest } => {
-                        assert!(state.units[unit.location.province as usize].is_some());
+                    Order::Move { unit, dest, .. } => {
+                        assert!(state.units[unit.location.province.index()].is_some());
                         // Verify the move target is among legal orders for that unit
                         let legal = movement::legal_orders(unit.location.province, &state);
                         assert!(legal.contains(order), "Generated illegal move: {:?}", order);
@@ -2,12 +2,47 @@
 //!
 //! Enumerates legal hold, move, support, and convoy orders for each
 //! unit during a movement phase.
+//!
+//! Generation writes into a caller-supplied buffer and uses no heap
+//! allocation of its own, so hot loops can reuse one `Vec` across units
+//! and states with [`legal_orders_into`], or keep typical units entirely
+//! on the stack with [`legal_orders_small`].
+
+use smallvec::SmallVec;
 
+use crate::board::map::GameMap;
 use crate::board::{
-    fleet_coasts_to, provinces_adjacent_to, BoardState, Coast, Location, Order, OrderUnit,
-    Province, ProvinceType, UnitType, ALL_PROVINCES, PROVINCE_COUNT,
+    BoardState, Coast, Location, Order, OrderUnit, Province, ProvinceType, UnitType,
 };
 
+/// Inline capacity of [`OrderVec`]. Covers every unit without supports or
+/// convoys and most units with a few neighbours to support.
+pub const INLINE_ORDERS: usize = 32;
+
+/// Stack-allocated order list returned by [`legal_orders_small`]. Spills to
+/// the heap only for units with more than [`INLINE_ORDERS`] legal orders.
+pub type OrderVec = SmallVec<[Order; INLINE_ORDERS]>;
+
+/// Move targets of a single unit; no province has more than 16 neighbours.
+type Targets = SmallVec<[(Province, Coast); 16]>;
+
+/// A buffer generated orders are appended to.
+trait OrderSink {
+    fn push_order(&mut self, order: Order);
+}
+
+impl OrderSink for Vec<Order> {
+    fn push_order(&mut self, order: Order) {
+        self.push(order);
+    }
+}
+
+impl OrderSink for OrderVec {
+    fn push_order(&mut self, order: Order) {
+        self.push(order);
+    }
+}
+
 /// Returns whether the unit type can occupy the given province type.
 fn can_occupy(unit_type: UnitType, prov_type: ProvinceType) -> bool {
     match (unit_type, prov_type) {
@@ -19,7 +54,35 @@ fn can_occupy(unit_type: UnitType, prov_type: ProvinceType) -> bool {
 
 /// Returns the coast for a unit at the given province, reading from board state.
 fn unit_coast(province: Province, state: &BoardState) -> Coast {
-    state.fleet_coast[province as usize].unwrap_or(Coast::None)
+    state.fleet_coast[province.index()].unwrap_or(Coast::None)
+}
+
+/// Calls `f` once for each province adjacent to `prov` for the unit type,
+/// in the same order as `provinces_adjacent_to`, without allocating.
+fn for_each_adjacent(
+    map: &GameMap,
+    prov: Province,
+    coast: Coast,
+    is_fleet: bool,
+    mut f: impl FnMut(Province),
+) {
+    let mut seen: u128 = 0;
+    for adj in map.adj_from(prov) {
+        if is_fleet && !adj.fleet_ok {
+            continue;
+        }
+        if !is_fleet && !adj.army_ok {
+            continue;
+        }
+        if coast != Coast::None && adj.from_coast != Coast::None && adj.from_coast != coast {
+            continue;
+        }
+        let bit = 1u128 << adj.to.index() as u32;
+        if seen & bit == 0 {
+            seen |= bit;
+            f(adj.to);
+        }
+    }
 }
 
 /// Generates all legal movement-phase orders for the unit at the given province.
@@ -27,50 +90,23 @@ fn unit_coast(province: Province, state: &BoardState) -> Coast {
 /// Returns an empty vec if no unit exists at that province.
 /// The caller is responsible for ensuring this is called during a movement phase.
 pub fn legal_orders(province: Province, state: &BoardState) -> Vec<Order> {
-    let idx = province as usize;
-    let (_power, unit_type) = match state.units[idx] {
-        Some(pu) => pu,
-        None => return Vec::new(),
-    };
-
-    let coast = unit_coast(province, state);
-    let is_fleet = unit_type == UnitType::Fleet;
-    let unit = OrderUnit {
-        unit_type,
-        location: Location::with_coast(province, coast),
-    };
-
     let mut orders = Vec::new();
+    generate_orders(province, state, true, &mut orders);
+    orders
+}
 
-    // Hold is always legal.
-    orders.push(Order::Hold { unit });
-
-    // Moves to adjacent provinces.
-    let move_targets = generate_moves(province, coast, unit_type, is_fleet);
-    for (dest_prov, dest_coast) in &move_targets {
-        orders.push(Order::Move {
-            unit,
-            dest: Location::with_coast(*dest_prov, *dest_coast),
-        });
-    }
-
-    // Support hold and support move for every other unit on the board.
-    generate_supports(
-        province,
-        coast,
-        unit_type,
-        is_fleet,
-        unit,
-        state,
-        &move_targets,
-        &mut orders,
-    );
-
-    // Convoy orders: fleet in sea province can convoy armies.
-    if is_fleet && province.province_type() == ProvinceType::Sea {
-        generate_convoys(province, coast, unit, state, &mut orders);
-    }
+/// Like [`legal_orders`], but clears `out` and fills it instead of
+/// returning a new vec, so a loop over units can reuse one buffer.
+pub fn legal_orders_into(province: Province, state: &BoardState, out: &mut Vec<Order>) {
+    out.clear();
+    generate_orders(province, state, true, out);
+}
 
+/// Like [`legal_orders`], but returns the orders inline on the stack when
+/// they fit in [`INLINE_ORDERS`].
+pub fn legal_orders_small(province: Province, state: &BoardState) -> OrderVec {
+    let mut orders = OrderVec::new();
+    generate_orders(province, state, true, &mut orders);
     orders
 }
 
@@ -80,10 +116,23 @@ pub fn legal_orders(province: Province, state: &BoardState) -> Vec<Order> {
 /// and convoy generation. Used in lookahead where only the greedy top-1
 /// order matters and supports/convoys rarely win that selection.
 pub fn move_orders_only(province: Province, state: &BoardState) -> Vec<Order> {
-    let idx = province as usize;
+    let mut orders = Vec::new();
+    generate_orders(province, state, false, &mut orders);
+    orders
+}
+
+/// Appends the unit's hold and moves, plus supports and convoys when
+/// `full` is set.
+fn generate_orders(
+    province: Province,
+    state: &BoardState,
+    full: bool,
+    orders: &mut impl OrderSink,
+) {
+    let idx = province.index();
     let (_power, unit_type) = match state.units[idx] {
         Some(pu) => pu,
-        None => return Vec::new(),
+        None => return,
     };
 
     let coast = unit_coast(province, state);
@@ -93,48 +142,64 @@ pub fn move_orders_only(province: Province, state: &BoardState) -> Vec<Order> {
         location: Location::with_coast(province, coast),
     };
 
-    let mut orders = Vec::new();
-
     // Hold is always legal.
-    orders.push(Order::Hold { unit });
+    orders.push_order(Order::Hold { unit });
 
     // Moves to adjacent provinces.
-    let move_targets = generate_moves(province, coast, unit_type, is_fleet);
+    let move_targets = generate_moves(state.map(), province, coast, unit_type, is_fleet);
     for (dest_prov, dest_coast) in &move_targets {
-        orders.push(Order::Move {
+        orders.push_order(Order::Move {
             unit,
             dest: Location::with_coast(*dest_prov, *dest_coast),
+            via_convoy: false,
         });
     }
+    if !full {
+        return;
+    }
 
-    orders
+    // Support hold and support move for every other unit on the board.
+    generate_supports(province, unit, state, &move_targets, orders);
+
+    // Convoy orders: fleet in sea province can convoy armies.
+    if is_fleet && state.map().province_type(province) == ProvinceType::Sea {
+        generate_convoys(unit, state, orders);
+    }
 }
 
 /// Generates (destination_province, destination_coast) pairs for all move targets.
 fn generate_moves(
+    map: &GameMap,
     province: Province,
     coast: Coast,
     unit_type: UnitType,
     is_fleet: bool,
-) -> Vec<(Province, Coast)> {
-    let mut targets = Vec::new();
-    let adj = provinces_adjacent_to(province, coast, is_fleet);
+) -> Targets {
+    let mut targets = Targets::new();
 
-    for dest in adj {
-        let dest_type = dest.province_type();
-        if !can_occupy(unit_type, dest_type) {
-            continue;
+    for_each_adjacent(map, province, coast, is_fleet, |dest| {
+        if !can_occupy(unit_type, map.province_type(dest)) {
+            return;
         }
 
-        if is_fleet && dest.has_coasts() {
-            let coasts = fleet_coasts_to(province, coast, dest);
-            for c in coasts {
-                targets.push((dest, c));
+        if is_fleet && map.has_coasts(dest) {
+            // Every coast of `dest` the fleet reaches, as `fleet_coasts_to`.
+            for adj in map.adj_from(province) {
+                if adj.to != dest || !adj.fleet_ok {
+                    continue;
+                }
+                if coast != Coast::None && adj.from_coast != Coast::None && adj.from_coast != coast
+                {
+                    continue;
+                }
+                if !targets.contains(&(dest, adj.to_coast)) {
+                    targets.push((dest, adj.to_coast));
+                }
             }
         } else {
             targets.push((dest, Coast::None));
         }
-    }
+    });
 
     targets
 }
@@ -142,23 +207,23 @@ fn generate_moves(
 /// Generates support hold and support move orders for the given unit.
 fn generate_supports(
     province: Province,
-    _coast: Coast,
-    _unit_type: UnitType,
-    _is_fleet: bool,
     unit: OrderUnit,
     state: &BoardState,
     move_targets: &[(Province, Coast)],
-    orders: &mut Vec<Order>,
+    orders: &mut impl OrderSink,
 ) {
-    // Build set of provinces this unit can move to (for support-move validation).
-    let reachable: Vec<Province> = move_targets.iter().map(|(p, _)| *p).collect();
-
-    for i in 0..PROVINCE_COUNT {
+    // Provinces this unit can move to (for support-move validation).
+    let reachable: u128 = move_targets
+        .iter()
+        .fold(0, |acc, (p, _)| acc | 1u128 << p.index() as u32);
+    let can_reach = |p: Province| reachable & (1u128 << p.index() as u32) != 0;
+
+    for &other_prov in state.map().provinces() {
+        let i = other_prov.index();
         let (_other_power, other_type) = match state.units[i] {
             Some(pu) => pu,
             None => continue,
         };
-        let other_prov = ALL_PROVINCES[i];
         if other_prov == province {
             continue;
         }
@@ -170,44 +235,42 @@ fn generate_supports(
         };
 
         // Support hold: this unit must be able to move to the supported unit's province.
-        if reachable.contains(&other_prov) {
-            orders.push(Order::SupportHold { unit, supported });
+        if can_reach(other_prov) {
+            orders.push_order(Order::SupportHold { unit, supported });
         }
 
         // Support move: for each province the other unit could move to,
         // if this unit can also reach that province.
         let other_is_fleet = other_type == UnitType::Fleet;
-        let other_adj = provinces_adjacent_to(other_prov, other_coast, other_is_fleet);
-
-        for dest in other_adj {
-            if dest == province {
-                continue; // cannot support a move into own province
-            }
-            let dest_type = dest.province_type();
-            if !can_occupy(other_type, dest_type) {
-                continue;
-            }
-            if !reachable.contains(&dest) {
-                continue; // this unit cannot reach the destination
-            }
-            orders.push(Order::SupportMove {
-                unit,
-                supported,
-                dest: Location::new(dest),
-            });
-        }
+        for_each_adjacent(
+            state.map(),
+            other_prov,
+            other_coast,
+            other_is_fleet,
+            |dest| {
+                if dest == province {
+                    return; // cannot support a move into own province
+                }
+                if !can_occupy(other_type, state.map().province_type(dest)) {
+                    return;
+                }
+                if !can_reach(dest) {
+                    return; // this unit cannot reach the destination
+                }
+                orders.push_order(Order::SupportMove {
+                    unit,
+                    supported,
+                    dest: Location::new(dest),
+                });
+            },
+        );
     }
 }
 
 /// Generates convoy orders for a fleet in a sea province.
-fn generate_convoys(
-    _province: Province,
-    _coast: Coast,
-    unit: OrderUnit,
-    state: &BoardState,
-    orders: &mut Vec<Order>,
-) {
-    for i in 0..PROVINCE_COUNT {
+fn generate_convoys(unit: OrderUnit, state: &BoardState, orders: &mut impl OrderSink) {
+    for &army_prov in state.map().provinces() {
+        let i = army_prov.index();
         let (_, other_type) = match state.units[i] {
             Some(pu) => pu,
             None => continue,
@@ -216,28 +279,24 @@ fn generate_convoys(
             continue;
         }
 
-        let army_prov = ALL_PROVINCES[i];
-        let army_prov_type = army_prov.province_type();
-        if army_prov_type == ProvinceType::Sea {
+        if state.map().province_type(army_prov) == ProvinceType::Sea {
             continue; // armies can't be in sea provinces
         }
 
         // The army's possible destinations (coastal provinces reachable by army).
-        let army_adj = provinces_adjacent_to(army_prov, Coast::None, false);
-        for dest in army_adj {
+        for_each_adjacent(state.map(), army_prov, Coast::None, false, |dest| {
             if dest == army_prov {
-                continue;
+                return;
             }
-            let dest_type = dest.province_type();
-            if dest_type == ProvinceType::Sea {
-                continue; // army can't convoy to sea
+            if state.map().province_type(dest) == ProvinceType::Sea {
+                return; // army can't convoy to sea
             }
-            orders.push(Order::Convoy {
+            orders.push_order(Order::Convoy {
                 unit,
                 convoyed_from: Location::new(army_prov),
                 convoyed_to: Location::new(dest),
             });
-        }
+        });
     }
 }
 
@@ -524,4 +583,23 @@ mod tests {
         }).collect();
         assert_eq!(support_ven.len(), 1);
     }
+
+    #[test]
+    fn buffered_variants_match_legal_orders() {
+        let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Mao, Power::France, UnitType::Fleet, Coast::None);
+        state.place_unit(Province::Spa, Power::France, UnitType::Army, Coast::None);
+        state.place_unit(Province::Stp, Power::Russia, UnitType::Fleet, Coast::South);
+        state.place_unit(Province::Bre, Power::England, UnitType::Army, Coast::None);
+        state.place_unit(Province::Por, Power::England, UnitType::Army, Coast::None);
+
+        // One buffer reused across units, starting dirty.
+        let mut buf = vec![Order::Waive];
+        for prov in [Province::Mao, Province::Spa, Province::Stp, Province::Vie] {
+            let expected = legal_orders(prov, &state);
+            legal_orders_into(prov, &state, &mut buf);
+            assert_eq!(buf, expected, "{:?}", prov);
+            assert_eq!(legal_orders_small(prov, &state).as_slice(), &expected[..]);
+        }
+    }
 }
@@ -2,10 +2,7 @@
 //!
 //! Enumerates legal retreat and disband orders for dislodged units.
 
-use crate::board::{
-    fleet_coasts_to, provinces_adjacent_to, BoardState, Location, Order, OrderUnit, Province,
-    ProvinceType, UnitType,
-};
+use crate::board::{BoardState, Location, Order, OrderUnit, Province, ProvinceType, UnitType};
 
 /// Generates all legal retreat-phase orders for a dislodged unit at the given province.
 ///
@@ -16,7 +13,7 @@ use crate::board::{
 ///
 /// Returns an empty vec if no dislodged unit exists at the province.
 pub fn legal_retreats(province: Province, state: &BoardState) -> Vec<Order> {
-    let dislodged = match state.dislodged[province as usize] {
+    let dislodged = match state.dislodged[province.index()] {
         Some(d) => d,
         None => return Vec::new(),
     };
@@ -37,9 +34,9 @@ pub fn legal_retreats(province: Province, state: &BoardState) -> Vec<Order> {
     orders.push(Order::Disband { unit });
 
     // Retreats to adjacent provinces.
-    let adj = provinces_adjacent_to(province, coast, is_fleet);
+    let adj = state.map().provinces_adjacent_to(province, coast, is_fleet);
     for dest in adj {
-        let dest_type = dest.province_type();
+        let dest_type = state.map().province_type(dest);
 
         // Filter by unit type occupancy rules.
         match (unit_type, dest_type) {
@@ -54,13 +51,13 @@ pub fn legal_retreats(province: Province, state: &BoardState) -> Vec<Order> {
         }
 
         // Cannot retreat to an occupied province.
-        if state.units[dest as usize].is_some() {
+        if state.units[dest.index()].is_some() {
             continue;
         }
 
         // Handle split-coast destinations for fleets.
-        if is_fleet && dest.has_coasts() {
-            let coasts = fleet_coasts_to(province, coast, dest);
+        if is_fleet && state.map().has_coasts(dest) {
+            let coasts = state.map().fleet_coasts_to(province, coast, dest);
             for c in coasts {
                 orders.push(Order::Retreat {
                     unit,
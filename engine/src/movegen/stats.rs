@@ -0,0 +1,190 @@
+//! Legal-order count statistics.
+//!
+//! Summarizes how much choice each power has in a position: how many units
+//! it must order, how many legal orders those units have in total, and how
+//! many distinct order sets that allows. Useful for sizing candidate pools,
+//! for checking move generation against known counts, and for research
+//! scripts that chart branching over a game.
+
+use crate::board::{BoardState, Phase, Power, ALL_POWERS};
+
+use super::build::legal_builds;
+use super::movement::legal_orders;
+use super::retreat::legal_retreats;
+
+/// Legal-order counts for one power.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerStats {
+    pub power: Power,
+    /// Units that need an order this phase: units on the board in movement,
+    /// dislodged units in retreats, builds or disbands owed in adjustments.
+    pub units: usize,
+    /// Legal orders summed over those units. In adjustments, the number of
+    /// distinct build or disband options (including waive).
+    pub legal_orders: usize,
+    /// Number of distinct order sets: the product of per-unit order counts,
+    /// or in adjustments the ways to pick the owed builds or disbands from
+    /// the options. 1.0 when the power has nothing to order.
+    pub branching: f64,
+}
+
+impl PowerStats {
+    /// Mean legal orders per unit, or 0.0 with no units to order.
+    pub fn mean_orders(&self) -> f64 {
+        if self.units == 0 {
+            0.0
+        } else {
+            self.legal_orders as f64 / self.units as f64
+        }
+    }
+}
+
+/// Legal-order statistics for every power in a position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveStats {
+    pub phase: Phase,
+    /// One entry per power, in `ALL_POWERS` order.
+    pub powers: Vec<PowerStats>,
+}
+
+impl MoveStats {
+    /// Returns the entry for `power`.
+    pub fn power(&self, power: Power) -> &PowerStats {
+        &self.powers[ALL_POWERS.iter().position(|&p| p == power).unwrap()]
+    }
+
+    /// Total legal orders over all powers.
+    pub fn total_orders(&self) -> usize {
+        self.powers.iter().map(|s| s.legal_orders).sum()
+    }
+
+    /// Number of distinct joint order sets across all powers.
+    pub fn joint_branching(&self) -> f64 {
+        self.powers.iter().map(|s| s.branching).product()
+    }
+}
+
+/// Binomial coefficient as a float, for counting unordered selections.
+fn choose(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Counts legal orders for every power in the current phase.
+pub fn stats(state: &BoardState) -> MoveStats {
+    let mut units = [0usize; 7];
+    let mut legal = [0usize; 7];
+    let mut branching = [1.0f64; 7];
+    let slot = |power: Power| ALL_POWERS.iter().position(|&p| p == power).unwrap();
+
+    match state.phase {
+        Phase::Movement => {
+            for &prov in state.map().provinces() {
+                if let Some((power, _)) = state.units[prov.index()] {
+                    let n = legal_orders(prov, state).len();
+                    let s = slot(power);
+                    units[s] += 1;
+                    legal[s] += n;
+                    branching[s] *= n as f64;
+                }
+            }
+        }
+        Phase::Retreat => {
+            for &prov in state.map().provinces() {
+                if let Some(d) = state.dislodged[prov.index()] {
+                    let n = legal_retreats(prov, state).len();
+                    let s = slot(d.power);
+                    units[s] += 1;
+                    legal[s] += n;
+                    branching[s] *= n as f64;
+                }
+            }
+        }
+        Phase::Build => {
+            for (s, &power) in ALL_POWERS.iter().enumerate() {
+                let centers = state.sc_owner.iter().filter(|o| **o == Some(power)).count();
+                let on_board = state
+                    .units
+                    .iter()
+                    .filter(|u| matches!(u, Some((p, _)) if *p == power))
+                    .count();
+                let owed = centers.abs_diff(on_board);
+                if owed == 0 {
+                    continue;
+                }
+                let options = legal_builds(power, state).len();
+                units[s] = owed;
+                legal[s] = options;
+                branching[s] = choose(options, owed.min(options)).max(1.0);
+            }
+        }
+    }
+
+    MoveStats {
+        phase: state.phase,
+        powers: ALL_POWERS
+            .iter()
+            .enumerate()
+            .map(|(s, &power)| PowerStats {
+                power,
+                units: units[s],
+                legal_orders: legal[s],
+                branching: branching[s],
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::Province;
+    use crate::protocol::dfen::parse_dfen;
+
+    const INITIAL: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn movement_counts_match_legal_orders() {
+        let state = parse_dfen(INITIAL).unwrap();
+        let s = stats(&state);
+        assert_eq!(s.powers.len(), 7);
+
+        let russia = s.power(Power::Russia);
+        assert_eq!(russia.units, 4);
+        let per_unit: Vec<usize> = [Province::Stp, Province::Mos, Province::War, Province::Sev]
+            .iter()
+            .map(|&p| legal_orders(p, &state).len())
+            .collect();
+        assert_eq!(russia.legal_orders, per_unit.iter().sum::<usize>());
+        assert_eq!(
+            russia.branching,
+            per_unit.iter().map(|&n| n as f64).product::<f64>()
+        );
+        assert!(russia.mean_orders() > 1.0);
+        assert_eq!(
+            s.total_orders(),
+            s.powers.iter().map(|p| p.legal_orders).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn build_phase_counts_owed_adjustments() {
+        // Austria has four centers and one unit: three builds owed, with
+        // only vie and tri open (fleet and army in tri, army in vie) plus waive.
+        let state = parse_dfen("1901fb/Aabud,Rawar/Abud,Atri,Avie,Aser,Rwar/-").unwrap();
+        let s = stats(&state);
+        let austria = s.power(Power::Austria);
+        assert_eq!(austria.units, 3);
+        assert_eq!(
+            austria.legal_orders,
+            legal_builds(Power::Austria, &state).len()
+        );
+        assert_eq!(austria.branching, choose(austria.legal_orders, 3));
+
+        let russia = s.power(Power::Russia);
+        assert_eq!((russia.units, russia.legal_orders), (0, 0));
+        assert_eq!(russia.branching, 1.0);
+    }
+}
@@ -0,0 +1,282 @@
+//! Final legality check for outgoing order sets.
+//!
+//! Search only combines generated orders, but post-processing steps
+//! (coordinated supports, plan injection, book lines) can still leave an
+//! order that no longer fits the position, such as a support for a unit
+//! that has since been reassigned. An illegal order reaching the server
+//! may be rejected outright, leaving the unit, or the whole power,
+//! unordered. [`validate`] catches such artifacts before `bestorders`:
+//! each illegal order is replaced by the safest legal order for the same
+//! unit (a hold in movement, a disband in retreats) or dropped, and every
+//! change is reported so it can be logged.
+
+use crate::board::{
+    BoardState, Coast, GameMap, Location, Order, OrderUnit, Phase, Power, MAX_PROVINCES,
+};
+use crate::protocol::dson::format_order_on;
+use crate::resolve::is_legal_order;
+
+use super::build::legal_builds;
+use super::retreat::legal_retreats;
+
+/// Why an outgoing order was changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrectionReason {
+    /// The order is not legal for its unit in this position.
+    Illegal,
+    /// The order names a unit the power does not have here.
+    NoSuchUnit,
+    /// Another order for the same unit (or province) came first.
+    Duplicate,
+    /// More builds than the power may make this adjustment.
+    TooManyBuilds,
+}
+
+impl CorrectionReason {
+    /// Short name used in log lines.
+    pub fn name(self) -> &'static str {
+        match self {
+            CorrectionReason::Illegal => "illegal",
+            CorrectionReason::NoSuchUnit => "no_such_unit",
+            CorrectionReason::Duplicate => "duplicate",
+            CorrectionReason::TooManyBuilds => "too_many_builds",
+        }
+    }
+}
+
+/// One change [`validate`] made to an order set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Correction {
+    /// The order as submitted.
+    pub original: Order,
+    /// The order played instead, or `None` if it was dropped.
+    pub replacement: Option<Order>,
+    pub reason: CorrectionReason,
+}
+
+impl Correction {
+    /// Describes the change for log lines, naming provinces as `map` does:
+    /// `A vie - mos -> A vie H (illegal)` or `A mos H dropped (no_such_unit)`.
+    pub fn describe(&self, map: &GameMap) -> String {
+        match self.replacement {
+            Some(r) => format!(
+                "{} -> {} ({})",
+                format_order_on(map, &self.original),
+                format_order_on(map, &r),
+                self.reason.name()
+            ),
+            None => format!(
+                "{} dropped ({})",
+                format_order_on(map, &self.original),
+                self.reason.name()
+            ),
+        }
+    }
+}
+
+/// Checks `orders` for `power` against the current phase and returns the
+/// corrected order set along with the corrections made.
+///
+/// Legal orders pass through unchanged and in order. An empty correction
+/// list means the input was already legal.
+pub fn validate(
+    orders: &[Order],
+    power: Power,
+    state: &BoardState,
+) -> (Vec<Order>, Vec<Correction>) {
+    let mut out = Vec::with_capacity(orders.len());
+    let mut corrections = Vec::new();
+    let mut ordered = [false; MAX_PROVINCES];
+    let mut builds_left = build_allowance(power, state);
+    let builds = if state.phase == Phase::Build {
+        legal_builds(power, state)
+    } else {
+        Vec::new()
+    };
+
+    for &order in orders {
+        let mut fix = |replacement: Option<Order>, reason| {
+            corrections.push(Correction {
+                original: order,
+                replacement,
+                reason,
+            });
+            replacement
+        };
+
+        let unit = order.unit();
+        if let Some(unit) = unit {
+            let idx = unit.location.province.index();
+            if ordered[idx] {
+                fix(None, CorrectionReason::Duplicate);
+                continue;
+            }
+            ordered[idx] = true;
+        }
+
+        let kept = match state.phase {
+            Phase::Movement => {
+                let Some(unit) = unit else {
+                    fix(None, CorrectionReason::Illegal);
+                    continue;
+                };
+                let idx = unit.location.province.index();
+                match state.units[idx] {
+                    Some((p, unit_type)) if p == power => {
+                        if is_legal_order(&order, power, state) {
+                            Some(order)
+                        } else {
+                            let hold = Order::Hold {
+                                unit: OrderUnit {
+                                    unit_type,
+                                    location: Location::with_coast(
+                                        unit.location.province,
+                                        state.fleet_coast[idx].unwrap_or(Coast::None),
+                                    ),
+                                },
+                            };
+                            fix(Some(hold), CorrectionReason::Illegal)
+                        }
+                    }
+                    _ => fix(None, CorrectionReason::NoSuchUnit),
+                }
+            }
+            Phase::Retreat => {
+                let Some(unit) = unit else {
+                    fix(None, CorrectionReason::Illegal);
+                    continue;
+                };
+                let prov = unit.location.province;
+                match state.dislodged[prov.index()] {
+                    Some(d) if d.power == power => {
+                        let legal = legal_retreats(prov, state);
+                        if legal.contains(&order) {
+                            Some(order)
+                        } else {
+                            let disband = legal
+                                .into_iter()
+                                .find(|o| matches!(o, Order::Disband { .. }));
+                            fix(disband, CorrectionReason::Illegal)
+                        }
+                    }
+                    _ => fix(None, CorrectionReason::NoSuchUnit),
+                }
+            }
+            Phase::Build => {
+                if !builds.contains(&order) {
+                    fix(None, CorrectionReason::Illegal)
+                } else if matches!(order, Order::Build { .. }) {
+                    if builds_left == 0 {
+                        fix(None, CorrectionReason::TooManyBuilds)
+                    } else {
+                        builds_left -= 1;
+                        Some(order)
+                    }
+                } else {
+                    Some(order)
+                }
+            }
+        };
+        out.extend(kept);
+    }
+    (out, corrections)
+}
+
+/// Builds `power` may make this adjustment (0 outside build phases).
+fn build_allowance(power: Power, state: &BoardState) -> usize {
+    if state.phase != Phase::Build {
+        return 0;
+    }
+    let centers = state.sc_owner.iter().filter(|o| **o == Some(power)).count();
+    let units = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count();
+    centers.saturating_sub(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn order(s: &str) -> Order {
+        parse_order(s).unwrap()
+    }
+
+    #[test]
+    fn legal_movement_orders_pass_unchanged() {
+        let state = parse_dfen("1901sm/Aavie,Aabud,Aftri/Avie,Abud,Atri/-").unwrap();
+        let orders = vec![
+            order("A vie - gal"),
+            order("A bud S A vie - gal"),
+            order("F tri H"),
+        ];
+        let (fixed, corrections) = validate(&orders, Power::Austria, &state);
+        assert_eq!(fixed, orders);
+        assert!(corrections.is_empty());
+    }
+
+    #[test]
+    fn stale_support_becomes_hold() {
+        let state = parse_dfen("1901sm/Aavie,Aabud,Aftri/Avie,Abud,Atri/-").unwrap();
+        let orders = vec![
+            order("A vie - gal"),
+            order("F tri S A vie - war"), // tri cannot reach war
+            order("A mos H"),             // not our unit
+            order("A vie H"),             // vie already ordered
+        ];
+        let (fixed, corrections) = validate(&orders, Power::Austria, &state);
+        assert_eq!(fixed, vec![order("A vie - gal"), order("F tri H")]);
+        let reasons: Vec<_> = corrections.iter().map(|c| c.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                CorrectionReason::Illegal,
+                CorrectionReason::NoSuchUnit,
+                CorrectionReason::Duplicate,
+            ]
+        );
+        assert_eq!(
+            corrections[0].describe(state.map()),
+            "F tri S A vie - war -> F tri H (illegal)"
+        );
+        assert_eq!(
+            corrections[1].describe(state.map()),
+            "A mos H dropped (no_such_unit)"
+        );
+    }
+
+    #[test]
+    fn illegal_retreat_becomes_disband() {
+        let state = parse_dfen("1901fr/Ravie/Avie/Aavie<gal").unwrap();
+        let (fixed, corrections) = validate(&[order("A vie R gal")], Power::Austria, &state);
+        assert_eq!(fixed, vec![order("A vie D")]);
+        assert_eq!(corrections[0].reason, CorrectionReason::Illegal);
+
+        let (fixed, corrections) = validate(&[order("A vie R boh")], Power::Austria, &state);
+        assert_eq!(fixed, vec![order("A vie R boh")]);
+        assert!(corrections.is_empty());
+    }
+
+    #[test]
+    fn builds_are_capped_at_the_allowance() {
+        // Three centers and one unit: two builds.
+        let state = parse_dfen("1901fb/Aagal/Avie,Abud,Atri/-").unwrap();
+        let orders = vec![
+            order("A vie B"),
+            order("A mos B"),
+            order("A bud B"),
+            order("F tri B"),
+        ];
+        let (fixed, corrections) = validate(&orders, Power::Austria, &state);
+        assert_eq!(fixed, vec![order("A vie B"), order("A bud B")]);
+        let reasons: Vec<_> = corrections.iter().map(|c| c.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![CorrectionReason::Illegal, CorrectionReason::TooManyBuilds]
+        );
+    }
+}
@@ -18,6 +18,7 @@
 //!   [36:39] prev unit present: [army, fleet, empty]
 //!   [39:47] prev unit owner:   [A, E, F, G, I, R, T, none]
 
+use crate::board::map::GameMap;
 use crate::board::province::{
     Coast, Power, Province, ProvinceType, ALL_POWERS, ALL_PROVINCES, PROVINCE_COUNT,
 };
@@ -98,18 +99,18 @@ fn province_type_vec(area: usize) -> [f32; 3] {
 }
 
 /// Set of all supply center province indices.
-fn is_supply_center(prov_idx: usize) -> bool {
+fn is_supply_center(map: &GameMap, prov_idx: usize) -> bool {
     if prov_idx >= PROVINCE_COUNT {
         // Bicoastal variants: check the base province.
         let base = match prov_idx {
-            BUL_EC | BUL_SC => Province::Bul as usize,
-            SPA_NC | SPA_SC => Province::Spa as usize,
-            STP_NC | STP_SC => Province::Stp as usize,
+            BUL_EC | BUL_SC => Province::Bul.index(),
+            SPA_NC | SPA_SC => Province::Spa.index(),
+            STP_NC | STP_SC => Province::Stp.index(),
             _ => return false,
         };
-        ALL_PROVINCES[base].is_supply_center()
+        map.is_supply_center(ALL_PROVINCES[base])
     } else {
-        ALL_PROVINCES[prov_idx].is_supply_center()
+        map.is_supply_center(ALL_PROVINCES[prov_idx])
     }
 }
 
@@ -171,7 +172,7 @@ pub fn encode_board_state_with_prev(
     let mut owned_sc = [false; PROVINCE_COUNT];
     for i in 0..PROVINCE_COUNT {
         if let Some(power) = state.sc_owner[i] {
-            if !ALL_PROVINCES[i].is_supply_center() {
+            if !state.map().is_supply_center(ALL_PROVINCES[i]) {
                 continue;
             }
             owned_sc[i] = true;
@@ -196,14 +197,14 @@ pub fn encode_board_state_with_prev(
             area
         } else {
             match area {
-                BUL_EC | BUL_SC => Province::Bul as usize,
-                SPA_NC | SPA_SC => Province::Spa as usize,
-                STP_NC | STP_SC => Province::Stp as usize,
+                BUL_EC | BUL_SC => Province::Bul.index(),
+                SPA_NC | SPA_SC => Province::Spa.index(),
+                STP_NC | STP_SC => Province::Stp.index(),
                 _ => continue,
             }
         };
         let abase = area * NUM_FEATURES;
-        if is_supply_center(area) {
+        if is_supply_center(state.map(), area) {
             if !owned_sc[base_prov] {
                 tensor[abase + FEAT_SC_OWNER + NUM_POWERS] = 1.0; // neutral
             }
@@ -316,7 +317,7 @@ fn encode_build_disband(tensor: &mut [f32], state: &BoardState) {
             .sc_owner
             .iter()
             .enumerate()
-            .filter(|(i, o)| **o == Some(power) && ALL_PROVINCES[*i].is_supply_center())
+            .filter(|(i, o)| **o == Some(power) && state.map().is_supply_center(ALL_PROVINCES[*i]))
             .count();
 
         if num_scs > num_units {
@@ -329,8 +330,8 @@ fn encode_build_disband(tensor: &mut [f32], state: &BoardState) {
                 .collect();
             for i in 0..PROVINCE_COUNT {
                 let prov = ALL_PROVINCES[i];
-                if prov.home_power() == Some(power)
-                    && prov.is_supply_center()
+                if state.map().home_power(prov) == Some(power)
+                    && state.map().is_supply_center(prov)
                     && state.sc_owner[i] == Some(power)
                     && !occupied.contains(&i)
                 {
@@ -361,8 +362,8 @@ pub fn build_adjacency_matrix() -> Vec<f32> {
 
     // Add edges from the adjacency table (over base provinces only).
     for entry in ADJACENCIES.iter() {
-        let i = entry.from as usize;
-        let j = entry.to as usize;
+        let i = entry.from.index();
+        let j = entry.to.index();
         if i < PROVINCE_COUNT && j < PROVINCE_COUNT {
             adj[i * NUM_AREAS + j] = 1.0;
             adj[j * NUM_AREAS + i] = 1.0;
@@ -386,7 +387,7 @@ pub fn build_adjacency_matrix() -> Vec<f32> {
     ];
 
     for (base_prov, coasts) in &split_coasts {
-        let base_idx = *base_prov as usize;
+        let base_idx = base_prov.index();
         for &(_coast, var_idx) in *coasts {
             // Variant <-> base.
             adj[base_idx * NUM_AREAS + var_idx] = 1.0;
@@ -456,7 +457,7 @@ mod tests {
     fn vienna_has_austrian_army() {
         let state = initial_state();
         let tensor = encode_board_state(&state);
-        let vie_idx = Province::Vie as usize;
+        let vie_idx = Province::Vie.index();
         let base = vie_idx * NUM_FEATURES;
 
         // Unit type: army.
@@ -472,7 +473,7 @@ mod tests {
     fn london_has_english_fleet() {
         let state = initial_state();
         let tensor = encode_board_state(&state);
-        let lon_idx = Province::Lon as usize;
+        let lon_idx = Province::Lon.index();
         let base = lon_idx * NUM_FEATURES;
 
         assert_eq!(tensor[base + FEAT_UNIT_TYPE], 0.0);
@@ -494,7 +495,7 @@ mod tests {
         let tensor = encode_board_state(&state);
 
         // Base Stp province should have the fleet.
-        let stp_idx = Province::Stp as usize;
+        let stp_idx = Province::Stp.index();
         let base = stp_idx * NUM_FEATURES;
         assert_eq!(tensor[base + FEAT_UNIT_TYPE + 1], 1.0, "Stp has fleet");
         assert_eq!(
@@ -527,7 +528,7 @@ mod tests {
         let tensor = encode_board_state(&state);
 
         // Vienna is Austrian SC.
-        let vie_base = Province::Vie as usize * NUM_FEATURES;
+        let vie_base = Province::Vie.index() * NUM_FEATURES;
         assert_eq!(
             tensor[vie_base + FEAT_SC_OWNER],
             1.0,
@@ -535,7 +536,7 @@ mod tests {
         );
 
         // Serbia is neutral SC.
-        let ser_base = Province::Ser as usize * NUM_FEATURES;
+        let ser_base = Province::Ser.index() * NUM_FEATURES;
         assert_eq!(
             tensor[ser_base + FEAT_SC_OWNER + NUM_POWERS],
             1.0,
@@ -543,7 +544,7 @@ mod tests {
         );
 
         // Bohemia is not an SC.
-        let boh_base = Province::Boh as usize * NUM_FEATURES;
+        let boh_base = Province::Boh.index() * NUM_FEATURES;
         assert_eq!(
             tensor[boh_base + FEAT_SC_OWNER + NUM_POWERS + 1],
             1.0,
@@ -556,15 +557,15 @@ mod tests {
         let tensor = encode_board_state(&initial_state());
 
         // Bohemia is inland.
-        let boh_base = Province::Boh as usize * NUM_FEATURES;
+        let boh_base = Province::Boh.index() * NUM_FEATURES;
         assert_eq!(tensor[boh_base + FEAT_PROVINCE_TYPE], 1.0, "Boh is land");
 
         // North Sea is sea.
-        let nth_base = Province::Nth as usize * NUM_FEATURES;
+        let nth_base = Province::Nth.index() * NUM_FEATURES;
         assert_eq!(tensor[nth_base + FEAT_PROVINCE_TYPE + 1], 1.0, "Nth is sea");
 
         // London is coastal.
-        let lon_base = Province::Lon as usize * NUM_FEATURES;
+        let lon_base = Province::Lon.index() * NUM_FEATURES;
         assert_eq!(
             tensor[lon_base + FEAT_PROVINCE_TYPE + 2],
             1.0,
@@ -585,7 +586,7 @@ mod tests {
         let tensor = encode_board_state(&initial_state());
 
         // Galicia has no unit.
-        let gal_base = Province::Gal as usize * NUM_FEATURES;
+        let gal_base = Province::Gal.index() * NUM_FEATURES;
         assert_eq!(tensor[gal_base + FEAT_UNIT_TYPE + 2], 1.0, "Gal is empty");
         assert_eq!(
             tensor[gal_base + FEAT_UNIT_OWNER + NUM_POWERS],
@@ -647,8 +648,8 @@ mod tests {
     fn adjacency_known_edges() {
         let adj = build_adjacency_matrix();
         // Vienna <-> Bohemia should be connected.
-        let vie = Province::Vie as usize;
-        let boh = Province::Boh as usize;
+        let vie = Province::Vie.index();
+        let boh = Province::Boh.index();
         assert_eq!(
             adj[vie * NUM_AREAS + boh],
             1.0,
@@ -656,7 +657,7 @@ mod tests {
         );
 
         // Vienna <-> Venice should NOT be directly connected.
-        let ven = Province::Ven as usize;
+        let ven = Province::Ven.index();
         assert_eq!(
             adj[vie * NUM_AREAS + ven],
             0.0,
@@ -667,7 +668,7 @@ mod tests {
     #[test]
     fn bicoastal_variants_connected_to_base() {
         let adj = build_adjacency_matrix();
-        let bul = Province::Bul as usize;
+        let bul = Province::Bul.index();
         assert_eq!(adj[bul * NUM_AREAS + BUL_EC], 1.0);
         assert_eq!(adj[bul * NUM_AREAS + BUL_SC], 1.0);
     }
@@ -680,9 +681,9 @@ mod tests {
 
         // Austria has 3 units: Vie, Bud, Tri.
         let expected: Vec<i64> = vec![
-            Province::Bud as i64,
-            Province::Tri as i64,
-            Province::Vie as i64,
+            Province::Bud.index() as i64,
+            Province::Tri.index() as i64,
+            Province::Vie.index() as i64,
         ];
         let mut actual: Vec<i64> = indices[..3].to_vec();
         actual.sort();
@@ -710,17 +711,17 @@ mod tests {
 
         // Vie, Bud, Tri are home centers and unoccupied -> can build.
         assert_eq!(
-            tensor[Province::Vie as usize * NUM_FEATURES + FEAT_CAN_BUILD],
+            tensor[Province::Vie.index() * NUM_FEATURES + FEAT_CAN_BUILD],
             1.0,
             "Vie should be buildable"
         );
         assert_eq!(
-            tensor[Province::Bud as usize * NUM_FEATURES + FEAT_CAN_BUILD],
+            tensor[Province::Bud.index() * NUM_FEATURES + FEAT_CAN_BUILD],
             1.0,
             "Bud should be buildable"
         );
         assert_eq!(
-            tensor[Province::Tri as usize * NUM_FEATURES + FEAT_CAN_BUILD],
+            tensor[Province::Tri.index() * NUM_FEATURES + FEAT_CAN_BUILD],
             1.0,
             "Tri should be buildable"
         );
@@ -762,14 +763,14 @@ mod tests {
         let tensor = encode_board_state_with_prev(&current, Some(&prev));
 
         // Current: Ser has army, Bud is empty.
-        let ser_base = Province::Ser as usize * NUM_FEATURES;
+        let ser_base = Province::Ser.index() * NUM_FEATURES;
         assert_eq!(
             tensor[ser_base + FEAT_UNIT_TYPE],
             1.0,
             "Ser has current army"
         );
 
-        let bud_base = Province::Bud as usize * NUM_FEATURES;
+        let bud_base = Province::Bud.index() * NUM_FEATURES;
         assert_eq!(
             tensor[bud_base + FEAT_UNIT_TYPE + 2],
             1.0,
@@ -804,7 +805,7 @@ mod tests {
         let tensor = encode_board_state_with_prev(&current, Some(&prev));
 
         // Base province stp should have prev fleet.
-        let stp_base = Province::Stp as usize * NUM_FEATURES;
+        let stp_base = Province::Stp.index() * NUM_FEATURES;
         assert_eq!(
             tensor[stp_base + FEAT_PREV_UNIT_TYPE + 1],
             1.0,
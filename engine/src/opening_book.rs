@@ -15,6 +15,7 @@ use crate::board::order::{Location, Order, OrderUnit};
 use crate::board::province::{Coast, Power, Province, ALL_PROVINCES, PROVINCE_COUNT};
 use crate::board::state::{BoardState, Phase, Season};
 use crate::board::unit::UnitType;
+use crate::rng::entropy_rng;
 
 /// The full opening book parsed from JSON.
 #[derive(Debug, Clone, Deserialize)]
@@ -209,7 +210,7 @@ fn weighted_select<'a>(options: &[&'a BookOption]) -> Option<&'a BookOption> {
     if total <= 0.0 {
         return Some(options[0]);
     }
-    let mut rng = rand::thread_rng();
+    let mut rng = entropy_rng();
     let r = rng.gen::<f64>() * total;
     let mut cum = 0.0;
     for opt in options {
@@ -257,7 +258,7 @@ fn score_condition(
         let mut matched = 0;
         for sc_name in &cond.owned_scs {
             if let Some(prov) = Province::from_abbr(sc_name) {
-                if state.sc_owner[prov as usize] == Some(power) {
+                if state.sc_owner[prov.index()] == Some(power) {
                     matched += 1;
                 }
             }
@@ -341,7 +342,7 @@ fn score_condition(
 fn unit_key<'a>(state: &'a BoardState, power: Power) -> HashMap<&'a str, &'a str> {
     let mut map = HashMap::new();
     for prov in ALL_PROVINCES {
-        if let Some((p, ut)) = state.units[prov as usize] {
+        if let Some((p, ut)) = state.units[prov.index()] {
             if p == power {
                 let ut_str = match ut {
                     UnitType::Army => "army",
@@ -361,23 +362,21 @@ fn sc_count(state: &BoardState, power: Power) -> u32 {
 
 /// Counts enemy units adjacent to the given power's supply centers.
 fn border_pressure(state: &BoardState, power: Power) -> i32 {
-    use crate::board::adjacency::adj_from;
-
     // Collect our SCs.
     let mut our_scs = [false; PROVINCE_COUNT];
     for prov in ALL_PROVINCES {
-        if prov.is_supply_center() && state.sc_owner[prov as usize] == Some(power) {
-            our_scs[prov as usize] = true;
+        if state.map().is_supply_center(prov) && state.sc_owner[prov.index()] == Some(power) {
+            our_scs[prov.index()] = true;
         }
     }
 
     // Build border zone: provinces adjacent to our SCs that are not our SCs.
     let mut border_zone = [false; PROVINCE_COUNT];
     for prov in ALL_PROVINCES {
-        if our_scs[prov as usize] {
-            for adj in adj_from(prov) {
-                if !our_scs[adj.to as usize] {
-                    border_zone[adj.to as usize] = true;
+        if our_scs[prov.index()] {
+            for adj in state.map().adj_from(prov) {
+                if !our_scs[adj.to.index()] {
+                    border_zone[adj.to.index()] = true;
                 }
             }
         }
@@ -386,8 +385,8 @@ fn border_pressure(state: &BoardState, power: Power) -> i32 {
     // Count enemy units in the border zone.
     let mut count = 0;
     for prov in ALL_PROVINCES {
-        if border_zone[prov as usize] {
-            if let Some((p, _)) = state.units[prov as usize] {
+        if border_zone[prov.index()] {
+            if let Some((p, _)) = state.units[prov.index()] {
                 if p != power {
                     count += 1;
                 }
@@ -402,7 +401,7 @@ fn fleet_army_count(state: &BoardState, power: Power) -> (u32, u32) {
     let mut fleets = 0u32;
     let mut armies = 0u32;
     for prov in ALL_PROVINCES {
-        if let Some((p, ut)) = state.units[prov as usize] {
+        if let Some((p, ut)) = state.units[prov.index()] {
             if p == power {
                 match ut {
                     UnitType::Fleet => fleets += 1,
@@ -488,6 +487,7 @@ fn convert_single_order(input: &OrderInput, _power: Power) -> Option<Order> {
                     province: target_prov,
                     coast: target_coast,
                 },
+                via_convoy: false,
             })
         }
         "support" => {
@@ -526,6 +526,7 @@ fn convert_single_order(input: &OrderInput, _power: Power) -> Option<Order> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::orders_hash;
     use crate::board::province::ALL_POWERS;
 
     /// Minimal JSON for a Spring 1901 Austria entry.
@@ -645,8 +646,8 @@ mod tests {
 
         // Set initial SC ownership
         for prov in ALL_PROVINCES {
-            if prov.is_supply_center() {
-                if let Some(home) = prov.home_power() {
+            if state.map().is_supply_center(prov) {
+                if let Some(home) = state.map().home_power(prov) {
                     state.set_sc_owner(prov, Some(home));
                 }
             }
@@ -692,7 +693,7 @@ mod tests {
     fn no_match_for_wrong_year() {
         let book = load_book_from_str(test_json()).unwrap();
         let mut state = initial_state();
-        state.year = 1950;
+        state.set_turn(1950, state.season, state.phase);
         let cfg = BookMatchConfig::default();
 
         assert!(lookup_opening(&book, &state, Power::Austria, &cfg).is_none());
@@ -702,7 +703,7 @@ mod tests {
     fn no_match_for_retreat_phase() {
         let book = load_book_from_str(test_json()).unwrap();
         let mut state = initial_state();
-        state.phase = Phase::Retreat;
+        state.set_turn(state.year, state.season, Phase::Retreat);
         let cfg = BookMatchConfig::default();
 
         assert!(lookup_opening(&book, &state, Power::Austria, &cfg).is_none());
@@ -713,7 +714,7 @@ mod tests {
         let book = load_book_from_str(test_json()).unwrap();
         let mut state = initial_state();
         // Move England's army from lvp to yor (position mismatch).
-        state.units[Province::Lvp as usize] = None;
+        state.remove_unit(Province::Lvp);
         state.place_unit(Province::Yor, Power::England, UnitType::Army, Coast::None);
         let cfg = BookMatchConfig {
             mode: MatchMode::Exact,
@@ -880,9 +881,9 @@ mod tests {
         let mut seen = HashMap::new();
         for _ in 0..500 {
             let orders = lookup_opening(&book, &state, Power::Austria, &cfg).unwrap();
-            // Use first order's destination as key.
-            let key = format!("{:?}", orders[0]);
-            *seen.entry(key).or_insert(0) += 1;
+            let entries: Vec<(Order, Power)> =
+                orders.into_iter().map(|o| (o, Power::Austria)).collect();
+            *seen.entry(orders_hash(&entries)).or_insert(0) += 1;
         }
 
         assert!(
@@ -915,6 +916,7 @@ mod tests {
                     location: Location::new(Province::Vie),
                 },
                 dest: Location::new(Province::Gal),
+                via_convoy: false,
             }
         );
     }
@@ -1074,7 +1076,7 @@ mod tests {
             let unit_count = ALL_PROVINCES
                 .iter()
                 .filter(|p| {
-                    state.units[**p as usize]
+                    state.units[p.index()]
                         .map(|(pw, _)| pw == power)
                         .unwrap_or(false)
                 })
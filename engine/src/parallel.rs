@@ -0,0 +1,58 @@
+//! Data parallelism, or a sequential stand-in for it.
+//!
+//! The search evaluates independent profiles with rayon's parallel
+//! iterators. With the `parallel` feature off (the `minimal` build) the
+//! traits below give `par_iter`, `into_par_iter` and `map_init` on the same
+//! receivers but return ordinary iterators, so call sites compile unchanged and run on
+//! the calling thread.
+
+/// Import with `use crate::parallel::prelude::*` in place of
+/// `rayon::prelude::*`.
+pub(crate) mod prelude {
+    #[cfg(feature = "parallel")]
+    pub(crate) use rayon::prelude::*;
+
+    #[cfg(not(feature = "parallel"))]
+    pub(crate) use super::sequential::{
+        IntoParallelIterator, IntoParallelRefIterator, ParallelMapInit,
+    };
+}
+
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    /// Sequential `into_par_iter`.
+    pub(crate) trait IntoParallelIterator: IntoIterator + Sized {
+        fn into_par_iter(self) -> Self::IntoIter {
+            self.into_iter()
+        }
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {}
+
+    /// Sequential `par_iter` over slices (and so `Vec`s).
+    pub(crate) trait IntoParallelRefIterator<'a> {
+        type Iter: Iterator;
+        fn par_iter(&'a self) -> Self::Iter;
+    }
+
+    impl<'a, T: 'a> IntoParallelRefIterator<'a> for [T] {
+        type Iter = std::slice::Iter<'a, T>;
+        fn par_iter(&'a self) -> Self::Iter {
+            self.iter()
+        }
+    }
+
+    /// Sequential `map_init`: one `init` value serves the whole iteration.
+    pub(crate) trait ParallelMapInit: Iterator + Sized {
+        fn map_init<T, R>(
+            self,
+            init: impl FnOnce() -> T,
+            mut op: impl FnMut(&mut T, Self::Item) -> R,
+        ) -> impl Iterator<Item = R> {
+            let mut value = init();
+            self.map(move |item| op(&mut value, item))
+        }
+    }
+
+    impl<I: Iterator> ParallelMapInit for I {}
+}
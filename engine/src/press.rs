@@ -1,20 +1,42 @@
 //! Structured press (diplomacy) support.
 //!
 //! Handles parsing inbound press commands, storing received press messages,
-//! tracking per-power trust scores, and generating outbound press.
+//! tracking per-power trust scores, and generating outbound press. DAIDE
+//! press proposals are negotiated in [`negotiation`].
 
-use crate::board::province::Power;
+pub mod negotiation;
+
+use crate::analysis::relations::{Relation, RelationTracker};
+use crate::board::province::{Coast, Power, Province, ALL_POWERS};
+use crate::board::state::BoardState;
+use crate::eval::heuristic::unit_can_reach;
+use crate::search::forecast::ProvinceForecast;
+use negotiation::{format_daide_press, parse_daide_press, DaidePress, Negotiations};
 
 /// Message types for structured press.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PressType {
-    RequestSupport { from_prov: String, to_prov: String },
-    ProposeNonaggression { provinces: Vec<String> },
-    ProposeAlliance { against: Option<Power> },
-    Threaten { province: String },
-    OfferDeal { i_take: String, you_take: String },
+    RequestSupport {
+        from_prov: String,
+        to_prov: String,
+    },
+    ProposeNonaggression {
+        provinces: Vec<String>,
+    },
+    ProposeAlliance {
+        against: Option<Power>,
+    },
+    Threaten {
+        province: String,
+    },
+    OfferDeal {
+        i_take: String,
+        you_take: String,
+    },
     Accept,
     Reject,
+    /// DAIDE press (levels 10 to 30), e.g. `PRP (PCE (AUS GER))`.
+    Daide(DaidePress),
 }
 
 /// A structured press message received from another power.
@@ -66,6 +88,10 @@ const TRUST_BREAK_PENALTY: f64 = 0.12;
 /// Per-turn trust decay rate (trust drifts toward 0.5).
 const TRUST_DECAY_RATE: f64 = 0.02;
 
+/// Trust change per observed position for a power whose play marks it as
+/// allied with or hostile to us.
+const TRUST_RELATION_NUDGE: f64 = 0.03;
+
 impl TrustModel {
     /// Creates a new trust model with neutral trust for all powers.
     pub fn new() -> Self {
@@ -108,6 +134,23 @@ impl TrustModel {
         }
     }
 
+    /// Nudges trust toward powers `relations` infers as allied with
+    /// `our_power` and away from those it infers as hostile.
+    pub fn observe_relations(&mut self, our_power: Power, relations: &RelationTracker) {
+        for &p in ALL_POWERS.iter().filter(|&&p| p != our_power) {
+            let idx = p as usize;
+            match relations.relation(our_power, p) {
+                Relation::Allied => {
+                    self.scores[idx] = (self.scores[idx] + TRUST_RELATION_NUDGE).min(1.0)
+                }
+                Relation::Hostile => {
+                    self.scores[idx] = (self.scores[idx] - TRUST_RELATION_NUDGE).max(0.0)
+                }
+                Relation::Neutral => {}
+            }
+        }
+    }
+
     /// Resets all trust to neutral.
     pub fn reset(&mut self) {
         self.scores = [DEFAULT_TRUST; 7];
@@ -126,6 +169,8 @@ pub struct PressState {
     pub outbound: Vec<PressOut>,
     /// Current turn (year) for tracking commitment age.
     pub current_turn: u16,
+    /// DAIDE proposals and the deals in force.
+    pub negotiations: Negotiations,
 }
 
 impl PressState {
@@ -135,6 +180,7 @@ impl PressState {
             trust: TrustModel::new(),
             outbound: Vec::new(),
             current_turn: 1901,
+            negotiations: Negotiations::new(),
         }
     }
 
@@ -150,6 +196,31 @@ impl PressState {
         self.outbound.clear();
         self.trust.reset();
         self.current_turn = 1901;
+        self.negotiations.reset();
+    }
+
+    /// Lets the negotiations see a new position (see
+    /// [`Negotiations::observe`]).
+    pub fn observe(&mut self, state: &BoardState) {
+        self.negotiations.observe(state, &mut self.trust);
+    }
+
+    /// Answers DAIDE press from `from` to `us`, updating the proposals and
+    /// deals. Returns our reply, if any.
+    pub fn negotiate(
+        &mut self,
+        from: Power,
+        press: DaidePress,
+        us: Power,
+        state: Option<&BoardState>,
+    ) -> Option<PressOut> {
+        let reply = self
+            .negotiations
+            .receive(from, press, us, state, &mut self.trust)?;
+        Some(PressOut {
+            to: from,
+            press_type: PressType::Daide(reply),
+        })
     }
 
     /// Adds a received press message.
@@ -189,6 +260,7 @@ impl PressState {
 /// - `france propose_alliance against germany`
 /// - `russia request_support war gal`
 /// - `england accept`
+/// - `germany daide PRP (PCE (AUS GER))`
 pub fn parse_press_raw(raw: &str) -> Option<PressMessage> {
     let tokens: Vec<&str> = raw.split_whitespace().collect();
     if tokens.len() < 2 {
@@ -237,6 +309,7 @@ pub fn parse_press_raw(raw: &str) -> Option<PressMessage> {
         }
         "accept" => PressType::Accept,
         "reject" => PressType::Reject,
+        "daide" => PressType::Daide(parse_daide_press(&tokens[2..].join(" ")).ok()?),
         _ => return None,
     };
 
@@ -279,6 +352,9 @@ pub fn format_press_out(press: &PressOut) -> String {
         }
         PressType::Accept => format!("press_out {} accept", to),
         PressType::Reject => format!("press_out {} reject", to),
+        PressType::Daide(press) => {
+            format!("press_out {} daide {}", to, format_daide_press(press))
+        }
     }
 }
 
@@ -308,29 +384,32 @@ pub fn trust_cooperation_adjustments(trust: &TrustModel, our_power: Power) -> [f
 ///
 /// Looks at what the engine plans to do and generates contextually
 /// appropriate press messages (e.g., request support for planned moves,
-/// propose nonaggression with non-adjacent powers).
+/// propose nonaggression with non-adjacent powers). With a pressure
+/// `forecast` from the search, it first asks trusted neighbours to support
+/// our units where an attack is likely.
 pub fn generate_outbound_press(
     our_power: Power,
     planned_orders: &[crate::board::Order],
     state: &crate::board::state::BoardState,
     trust: &TrustModel,
+    forecast: &[ProvinceForecast],
 ) -> Vec<PressOut> {
-    use crate::board::province::{ALL_POWERS, PROVINCE_COUNT};
+    use crate::board::province::{ALL_POWERS, MAX_PROVINCES};
     use crate::board::Order;
 
-    let mut outbound = Vec::new();
+    let mut outbound = defensive_support_requests(our_power, state, trust, forecast);
 
     // Find powers we're attacking
     let mut attacking: [bool; 7] = [false; 7];
     for order in planned_orders {
         if let Order::Move { dest, .. } = order {
             let dst = dest.province;
-            if let Some(owner) = state.sc_owner[dst as usize] {
+            if let Some(owner) = state.sc_owner[dst.index()] {
                 if owner != our_power {
                     attacking[owner as usize] = true;
                 }
             }
-            if let Some((p, _)) = state.units[dst as usize] {
+            if let Some((p, _)) = state.units[dst.index()] {
                 if p != our_power {
                     attacking[p as usize] = true;
                 }
@@ -340,13 +419,11 @@ pub fn generate_outbound_press(
 
     // Find neighboring powers (powers with units adjacent to our units)
     let mut neighboring: [bool; 7] = [false; 7];
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some((p, _)) = state.units[i] {
             if p == our_power {
-                for adj in
-                    crate::board::adjacency::adj_from(crate::board::province::ALL_PROVINCES[i])
-                {
-                    if let Some((adj_p, _)) = state.units[adj.to as usize] {
+                for adj in state.map().adj_from(Province::from_index(i)) {
+                    if let Some((adj_p, _)) = state.units[adj.to.index()] {
                         if adj_p != our_power {
                             neighboring[adj_p as usize] = true;
                         }
@@ -382,13 +459,13 @@ pub fn generate_outbound_press(
         {
             let supported_prov = supported.location.province;
             // Check if the supported unit belongs to another power
-            if let Some((unit_power, _)) = state.units[supported_prov as usize] {
+            if let Some((unit_power, _)) = state.units[supported_prov.index()] {
                 if unit_power != our_power {
                     outbound.push(PressOut {
                         to: unit_power,
                         press_type: PressType::RequestSupport {
-                            from_prov: supported_prov.name().to_string(),
-                            to_prov: dest.province.name().to_string(),
+                            from_prov: state.map().province_name(supported_prov).to_string(),
+                            to_prov: state.map().province_name(dest.province).to_string(),
                         },
                     });
                 }
@@ -401,6 +478,61 @@ pub fn generate_outbound_press(
     outbound
 }
 
+/// Attack probability from which we ask for a supporting hold.
+const DEFENSE_REQUEST_MIN_PROBABILITY: f64 = 0.5;
+
+/// Trust a power needs before we ask it to support us.
+const DEFENSE_REQUEST_MIN_TRUST: f64 = 0.5;
+
+/// Support-hold requests ("I need your support in Gal") for our units
+/// where the forecast expects an attack: each goes to the most trusted
+/// power, not among the attackers, with a unit that could give it.
+fn defensive_support_requests(
+    our_power: Power,
+    state: &BoardState,
+    trust: &TrustModel,
+    forecast: &[ProvinceForecast],
+) -> Vec<PressOut> {
+    let mut requests = Vec::new();
+    for f in forecast {
+        let prov = f.province;
+        let ours_here = state.units[prov.index()].is_some_and(|(p, _)| p == our_power);
+        if f.probability < DEFENSE_REQUEST_MIN_PROBABILITY || !ours_here {
+            continue;
+        }
+        let helper = state
+            .map()
+            .provinces()
+            .iter()
+            .filter_map(|&from| {
+                let i = from.index();
+                let (p, ut) = state.units[i]?;
+                let coast = state.fleet_coast[i].unwrap_or(Coast::None);
+                let can_support = p != our_power
+                    && f.attackers.iter().all(|a| a.power != p)
+                    && trust.trust(p) >= DEFENSE_REQUEST_MIN_TRUST
+                    && unit_can_reach(state.map(), from, coast, ut, prov);
+                can_support.then_some(p)
+            })
+            .max_by(|a, b| {
+                trust
+                    .trust(*a)
+                    .partial_cmp(&trust.trust(*b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        if let Some(to) = helper {
+            requests.push(PressOut {
+                to,
+                press_type: PressType::RequestSupport {
+                    from_prov: state.map().abbr(prov).to_string(),
+                    to_prov: state.map().abbr(prov).to_string(),
+                },
+            });
+        }
+    }
+    requests
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -563,6 +695,28 @@ mod tests {
         assert!(trust.trust(Power::France) < before);
     }
 
+    #[test]
+    fn trust_model_follows_inferred_relations() {
+        use crate::protocol::dfen::parse_dfen;
+        use crate::protocol::dson::parse_order;
+
+        let state = parse_dfen("1901sm/Aavie,Ragal,Iaven/Avie/-").unwrap();
+        let mut relations = RelationTracker::new();
+        relations.observe_position(&state);
+        relations.observe_orders(
+            &state,
+            &[
+                (parse_order("A gal S A vie H").unwrap(), Power::Russia),
+                (parse_order("A ven - vie").unwrap(), Power::Italy),
+            ],
+        );
+        let mut trust = TrustModel::new();
+        trust.observe_relations(Power::Austria, &relations);
+        assert!(trust.trust(Power::Russia) > 0.5);
+        assert!(trust.trust(Power::Italy) < 0.5);
+        assert_eq!(trust.trust(Power::Turkey), 0.5);
+    }
+
     #[test]
     fn trust_model_capped_at_bounds() {
         let mut trust = TrustModel::new();
@@ -686,4 +840,42 @@ mod tests {
         let formatted = format_press_out(&out);
         assert_eq!(formatted, "press_out france propose_nonaggression");
     }
+
+    #[test]
+    fn likely_attack_asks_a_trusted_neighbour_for_support() {
+        use crate::board::province::Province;
+        use crate::protocol::dfen::parse_dfen;
+        use crate::search::forecast::AttackForecast;
+
+        let state = parse_dfen("1901sm/Aagal,Rawar,Gasil,Iaven/Avie,Rwar,Gber/-").unwrap();
+        let pressed = |probability| ProvinceForecast {
+            province: Province::Gal,
+            probability,
+            strength: probability,
+            attackers: vec![AttackForecast {
+                power: Power::Russia,
+                probability,
+                strength: 1.0,
+            }],
+        };
+        let mut trust = TrustModel::new();
+        trust.scores[Power::Russia as usize] = 0.9;
+        trust.scores[Power::Germany as usize] = 0.6;
+
+        let out = generate_outbound_press(Power::Austria, &[], &state, &trust, &[pressed(0.7)]);
+        assert_eq!(
+            format_press_out(&out[0]),
+            "press_out germany request_support gal gal"
+        );
+        let calm = generate_outbound_press(Power::Austria, &[], &state, &trust, &[pressed(0.3)]);
+        assert!(calm
+            .iter()
+            .all(|p| !matches!(p.press_type, PressType::RequestSupport { .. })));
+
+        trust.scores[Power::Germany as usize] = 0.3;
+        let out = generate_outbound_press(Power::Austria, &[], &state, &trust, &[pressed(0.7)]);
+        assert!(out
+            .iter()
+            .all(|p| !matches!(p.press_type, PressType::RequestSupport { .. })));
+    }
 }
@@ -0,0 +1,654 @@
+//! DAIDE press negotiation.
+//!
+//! DAIDE press levels 10 to 30 let powers propose arrangements with `PRP`,
+//! answer them with `YES` or `REJ`, and withdraw them with `CCL`. The
+//! arrangements understood here are peace (`PCE`), alliances (`ALY ... VSS`),
+//! demilitarized zones (`DMZ`), orders a power undertakes to give (`XDO`),
+//! and combinations of them with `AND` and `ORR`.
+//!
+//! [`Negotiations`] answers the proposals made to us, follows each one until
+//! every party has agreed, and keeps the deals in force. Deals reach the
+//! search as [`Constraints`]: a DMZ forbids its powers' moves into its
+//! provinces and an `XDO` pins the agreed order. Our own candidates keep to
+//! them; another power only gets extra candidates that keep to its side of
+//! the bargain, since it may still break it. Peace and alliances act through
+//! trust, which already lowers the search's appetite for attacking partners.
+
+use crate::board::order::Order;
+use crate::board::province::{Power, Province};
+use crate::board::state::{BoardState, Phase, Season};
+use crate::protocol::daide::{
+    format_daide_order, order_from_exprs, parse_exprs, power_from_token, power_token,
+    province_from_token, province_token, DaideError, Expr,
+};
+use crate::resolve::adjudicate::is_legal_order;
+use crate::search::Constraints;
+
+use super::TrustModel;
+
+/// Trust in the proposer needed to accept peace, an alliance or a DMZ.
+const ACCEPT_TRUST: f64 = 0.4;
+
+/// Trust in the proposer needed to commit one of our own units with `XDO`.
+const COMMIT_TRUST: f64 = 0.5;
+
+/// Something powers can agree to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arrangement {
+    /// `PCE (powers)`: the powers will not attack one another.
+    Peace(Vec<Power>),
+    /// `ALY (allies) VSS (enemies)`.
+    Alliance {
+        allies: Vec<Power>,
+        against: Vec<Power>,
+    },
+    /// `DMZ (powers) (provinces)`: the powers keep their units out of the
+    /// provinces.
+    Dmz {
+        powers: Vec<Power>,
+        provinces: Vec<Province>,
+    },
+    /// `XDO (order)`: the order's power gives the order this turn.
+    Order { order: Order, power: Power },
+    /// `AND (arrangement)...`: every part.
+    And(Vec<Arrangement>),
+    /// `ORR (arrangement)...`: one of the parts.
+    Or(Vec<Arrangement>),
+}
+
+/// A DAIDE press message about an arrangement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaidePress {
+    /// `PRP (arrangement)`.
+    Propose(Arrangement),
+    /// `YES (PRP (arrangement))`.
+    Accept(Arrangement),
+    /// `REJ (PRP (arrangement))`.
+    Reject(Arrangement),
+    /// `CCL (PRP (arrangement))`.
+    Cancel(Arrangement),
+}
+
+impl Arrangement {
+    /// The powers bound by the arrangement. Enemies named in an alliance
+    /// are not parties to it.
+    pub fn parties(&self) -> Vec<Power> {
+        let mut parties = Vec::new();
+        self.collect_parties(&mut parties);
+        parties
+    }
+
+    fn collect_parties(&self, out: &mut Vec<Power>) {
+        let powers: &[Power] = match self {
+            Arrangement::Peace(powers) => powers,
+            Arrangement::Alliance { allies, .. } => allies,
+            Arrangement::Dmz { powers, .. } => powers,
+            Arrangement::Order { power, .. } => std::slice::from_ref(power),
+            Arrangement::And(parts) | Arrangement::Or(parts) => {
+                for part in parts {
+                    part.collect_parties(out);
+                }
+                return;
+            }
+        };
+        for &p in powers {
+            if !out.contains(&p) {
+                out.push(p);
+            }
+        }
+    }
+
+    /// Returns true if the arrangement includes an `XDO`, which only binds
+    /// for the turn it was agreed in.
+    fn has_orders(&self) -> bool {
+        match self {
+            Arrangement::Order { .. } => true,
+            Arrangement::And(parts) | Arrangement::Or(parts) => {
+                parts.iter().any(Arrangement::has_orders)
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds what the arrangement asks of `power` to `out`. An `ORR` binds
+    /// no one to a particular part, so it adds nothing.
+    fn collect_terms(&self, power: Power, out: &mut Constraints) {
+        match self {
+            Arrangement::Dmz { powers, provinces } if powers.contains(&power) => {
+                out.merge(&Constraints {
+                    pinned: Vec::new(),
+                    forbidden: provinces.clone(),
+                })
+            }
+            Arrangement::Order { order, power: p } if *p == power => {
+                out.merge(&Constraints::pinning(&[*order]))
+            }
+            Arrangement::And(parts) => {
+                for part in parts {
+                    part.collect_terms(power, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Where a proposal made to us stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    /// We accepted; other parties have yet to.
+    Open,
+    /// Every party agreed: the deal is in force.
+    Agreed,
+    /// We or another party rejected it.
+    Rejected,
+}
+
+/// A proposal made to us and the answers to it so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    pub from: Power,
+    pub arrangement: Arrangement,
+    pub status: ProposalStatus,
+    /// Parties that have agreed, the proposer included and us excluded.
+    pub agreed: Vec<Power>,
+}
+
+/// Proposals received and the deals in force.
+#[derive(Debug, Clone, Default)]
+pub struct Negotiations {
+    proposals: Vec<Proposal>,
+    /// The phase last observed; `XDO` deals and open proposals end with it.
+    phase: Option<(u16, Season, Phase)>,
+}
+
+impl Negotiations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets every proposal and deal.
+    pub fn reset(&mut self) {
+        self.proposals.clear();
+        self.phase = None;
+    }
+
+    /// Every proposal received this game that is still tracked.
+    pub fn proposals(&self) -> &[Proposal] {
+        &self.proposals
+    }
+
+    /// The proposals `power` made to us.
+    pub fn proposals_from(&self, power: Power) -> impl Iterator<Item = &Proposal> {
+        self.proposals.iter().filter(move |p| p.from == power)
+    }
+
+    /// The arrangements in force.
+    pub fn deals(&self) -> impl Iterator<Item = &Arrangement> {
+        self.proposals
+            .iter()
+            .filter(|p| p.status == ProposalStatus::Agreed)
+            .map(|p| &p.arrangement)
+    }
+
+    /// What the deals in force ask of `power`: the DMZ provinces it agreed
+    /// to stay out of and the orders it agreed to give.
+    pub fn constraints_for(&self, power: Power) -> Constraints {
+        let mut constraints = Constraints::default();
+        for deal in self.deals() {
+            deal.collect_terms(power, &mut constraints);
+        }
+        constraints
+    }
+
+    /// Observes a new position.
+    ///
+    /// When the phase changes, open proposals and deals with `XDO` orders
+    /// lapse, and a party with a unit inside a DMZ it agreed to has broken
+    /// the deal: trust in it drops and the deal is void.
+    pub fn observe(&mut self, state: &BoardState, trust: &mut TrustModel) {
+        let phase = Some((state.year, state.season, state.phase));
+        if self.phase == phase {
+            return;
+        }
+        self.phase = phase;
+        self.proposals
+            .retain(|p| p.status == ProposalStatus::Agreed && !p.arrangement.has_orders());
+        self.proposals.retain(|p| {
+            let breakers = dmz_breakers(&p.arrangement, state);
+            for &power in &breakers {
+                trust.betray(power);
+            }
+            breakers.is_empty()
+        });
+    }
+
+    /// Handles press from `from` to `us` and returns our answer, if any.
+    ///
+    /// A proposal is accepted when every part is acceptable (see
+    /// [`acceptable`]); for an `ORR`, the first acceptable part is chosen
+    /// and accepted on its own. Peace and alliances coming into force raise
+    /// trust in the partners. `state` is the current position, used to
+    /// check proposed orders and DMZs.
+    pub fn receive(
+        &mut self,
+        from: Power,
+        press: DaidePress,
+        us: Power,
+        state: Option<&BoardState>,
+        trust: &mut TrustModel,
+    ) -> Option<DaidePress> {
+        match press {
+            DaidePress::Propose(arrangement) => {
+                let accepted = match &arrangement {
+                    Arrangement::Or(parts) => parts
+                        .iter()
+                        .find(|a| acceptable(a, from, us, state, trust))
+                        .cloned(),
+                    a => acceptable(a, from, us, state, trust).then(|| a.clone()),
+                };
+                let (status, reply) = match accepted {
+                    Some(a) => (ProposalStatus::Open, DaidePress::Accept(a)),
+                    None => (
+                        ProposalStatus::Rejected,
+                        DaidePress::Reject(arrangement.clone()),
+                    ),
+                };
+                let arrangement = match &reply {
+                    DaidePress::Accept(a) => a.clone(),
+                    _ => arrangement,
+                };
+                self.proposals.retain(|p| p.arrangement != arrangement);
+                self.proposals.push(Proposal {
+                    from,
+                    arrangement,
+                    status,
+                    agreed: if from == us { Vec::new() } else { vec![from] },
+                });
+                let last = self.proposals.len() - 1;
+                self.settle(last, us, trust);
+                Some(reply)
+            }
+            DaidePress::Accept(arrangement) => {
+                if let Some(i) = self.open(&arrangement) {
+                    if !self.proposals[i].agreed.contains(&from) {
+                        self.proposals[i].agreed.push(from);
+                    }
+                    self.settle(i, us, trust);
+                }
+                None
+            }
+            DaidePress::Reject(arrangement) => {
+                if let Some(i) = self.open(&arrangement) {
+                    self.proposals[i].status = ProposalStatus::Rejected;
+                }
+                None
+            }
+            DaidePress::Cancel(arrangement) => {
+                self.proposals
+                    .retain(|p| p.from != from || p.arrangement != arrangement);
+                None
+            }
+        }
+    }
+
+    /// The open proposal for `arrangement`, if any.
+    fn open(&self, arrangement: &Arrangement) -> Option<usize> {
+        self.proposals
+            .iter()
+            .position(|p| p.status == ProposalStatus::Open && p.arrangement == *arrangement)
+    }
+
+    /// Puts proposal `i` in force once every party other than us agreed.
+    fn settle(&mut self, i: usize, us: Power, trust: &mut TrustModel) {
+        let proposal = &mut self.proposals[i];
+        if proposal.status != ProposalStatus::Open {
+            return;
+        }
+        let parties = proposal.arrangement.parties();
+        if !parties
+            .iter()
+            .all(|p| *p == us || proposal.agreed.contains(p))
+        {
+            return;
+        }
+        proposal.status = ProposalStatus::Agreed;
+        if has_partnership(&proposal.arrangement) {
+            for &p in parties.iter().filter(|&&p| p != us) {
+                trust.fulfill(p);
+            }
+        }
+    }
+}
+
+/// Returns true if the arrangement contains peace or an alliance.
+fn has_partnership(arrangement: &Arrangement) -> bool {
+    match arrangement {
+        Arrangement::Peace(_) | Arrangement::Alliance { .. } => true,
+        Arrangement::And(parts) | Arrangement::Or(parts) => parts.iter().any(has_partnership),
+        _ => false,
+    }
+}
+
+/// Parties to DMZs in `arrangement` with a unit inside one in `state`.
+fn dmz_breakers(arrangement: &Arrangement, state: &BoardState) -> Vec<Power> {
+    match arrangement {
+        Arrangement::Dmz { powers, provinces } => provinces
+            .iter()
+            .filter_map(|&prov| state.units[prov.index()].map(|(p, _)| p))
+            .filter(|p| powers.contains(p))
+            .collect(),
+        Arrangement::And(parts) => parts.iter().flat_map(|a| dmz_breakers(a, state)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether we agree to `arrangement` proposed by `from`.
+///
+/// Peace, alliances and DMZs need `ACCEPT_TRUST` in the proposer; peace and
+/// alliances must include us, an alliance must not be aimed at a power we
+/// trust more than the proposer, and a DMZ we are party to must not contain
+/// one of our units. Proposed orders must be legal, and committing one of
+/// our own units needs `COMMIT_TRUST`. An `AND` needs every part, a nested
+/// `ORR` any part.
+pub fn acceptable(
+    arrangement: &Arrangement,
+    from: Power,
+    us: Power,
+    state: Option<&BoardState>,
+    trust: &TrustModel,
+) -> bool {
+    let trusted = trust.trust(from);
+    match arrangement {
+        Arrangement::Peace(powers) => powers.contains(&us) && trusted >= ACCEPT_TRUST,
+        Arrangement::Alliance { allies, against } => {
+            allies.contains(&us)
+                && !against.contains(&us)
+                && trusted >= ACCEPT_TRUST
+                && against.iter().all(|&p| trust.trust(p) <= trusted)
+        }
+        Arrangement::Dmz { powers, provinces } => {
+            let occupied = powers.contains(&us)
+                && state.is_some_and(|s| {
+                    provinces
+                        .iter()
+                        .any(|&prov| matches!(s.units[prov.index()], Some((p, _)) if p == us))
+                });
+            trusted >= ACCEPT_TRUST && !occupied
+        }
+        Arrangement::Order { order, power } => {
+            state.is_none_or(|s| is_legal_order(order, *power, s))
+                && (*power != us || trusted >= COMMIT_TRUST)
+        }
+        Arrangement::And(parts) => parts.iter().all(|a| acceptable(a, from, us, state, trust)),
+        Arrangement::Or(parts) => parts.iter().any(|a| acceptable(a, from, us, state, trust)),
+    }
+}
+
+/// Parses a DAIDE press message: `PRP (arrangement)`, or `YES`, `REJ` or
+/// `CCL` of `(PRP (arrangement))`.
+pub fn parse_daide_press(s: &str) -> Result<DaidePress, DaideError> {
+    let exprs = parse_exprs(s)?;
+    let [Expr::Token(head), Expr::List(body)] = exprs.as_slice() else {
+        return Err(if exprs.is_empty() {
+            DaideError::EmptyInput
+        } else {
+            DaideError::Malformed("press token and (arrangement)".to_string())
+        });
+    };
+    if head == "PRP" {
+        return Ok(DaidePress::Propose(parse_arrangement(body)?));
+    }
+    let arrangement = match body.as_slice() {
+        [Expr::Token(prp), Expr::List(inner)] if prp == "PRP" => parse_arrangement(inner)?,
+        _ => return Err(DaideError::Malformed("(PRP (arrangement))".to_string())),
+    };
+    match head.as_str() {
+        "YES" => Ok(DaidePress::Accept(arrangement)),
+        "REJ" => Ok(DaidePress::Reject(arrangement)),
+        "CCL" => Ok(DaidePress::Cancel(arrangement)),
+        other => Err(DaideError::UnknownToken(other.to_string())),
+    }
+}
+
+fn parse_powers(expr: &Expr) -> Result<Vec<Power>, DaideError> {
+    let Expr::List(items) = expr else {
+        return Err(DaideError::Malformed("(powers)".to_string()));
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            Expr::Token(t) => {
+                power_from_token(t).ok_or_else(|| DaideError::UnknownPower(t.clone()))
+            }
+            Expr::List(_) => Err(DaideError::Malformed("power token".to_string())),
+        })
+        .collect()
+}
+
+fn parse_provinces(expr: &Expr) -> Result<Vec<Province>, DaideError> {
+    let Expr::List(items) = expr else {
+        return Err(DaideError::Malformed("(provinces)".to_string()));
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            Expr::Token(t) => {
+                province_from_token(t).ok_or_else(|| DaideError::UnknownProvince(t.clone()))
+            }
+            Expr::List(_) => Err(DaideError::Malformed("province token".to_string())),
+        })
+        .collect()
+}
+
+fn parse_arrangement(exprs: &[Expr]) -> Result<Arrangement, DaideError> {
+    let Some(Expr::Token(head)) = exprs.first() else {
+        return Err(DaideError::Malformed("arrangement token".to_string()));
+    };
+    match (head.as_str(), &exprs[1..]) {
+        ("PCE", [powers]) => Ok(Arrangement::Peace(parse_powers(powers)?)),
+        ("ALY", [allies, Expr::Token(vss), against]) if vss == "VSS" => Ok(Arrangement::Alliance {
+            allies: parse_powers(allies)?,
+            against: parse_powers(against)?,
+        }),
+        ("DMZ", [powers, provinces]) => Ok(Arrangement::Dmz {
+            powers: parse_powers(powers)?,
+            provinces: parse_provinces(provinces)?,
+        }),
+        ("XDO", [Expr::List(order)]) => {
+            let (order, power) = order_from_exprs(order)?;
+            Ok(Arrangement::Order { order, power })
+        }
+        ("AND" | "ORR", parts) if parts.len() >= 2 => {
+            let parts = parts
+                .iter()
+                .map(|part| match part {
+                    Expr::List(items) => parse_arrangement(items),
+                    Expr::Token(_) => Err(DaideError::Malformed("(arrangement)".to_string())),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(if head == "AND" {
+                Arrangement::And(parts)
+            } else {
+                Arrangement::Or(parts)
+            })
+        }
+        ("PCE" | "ALY" | "DMZ" | "XDO" | "AND" | "ORR", _) => {
+            Err(DaideError::Malformed(format!("arguments to {}", head)))
+        }
+        (other, _) => Err(DaideError::UnknownToken(other.to_string())),
+    }
+}
+
+fn format_powers(powers: &[Power]) -> String {
+    let tokens: Vec<&str> = powers.iter().map(|&p| power_token(p)).collect();
+    format!("({})", tokens.join(" "))
+}
+
+/// Formats an arrangement in DAIDE notation, e.g. `PCE (AUS GER)`.
+pub fn format_arrangement(arrangement: &Arrangement) -> String {
+    match arrangement {
+        Arrangement::Peace(powers) => format!("PCE {}", format_powers(powers)),
+        Arrangement::Alliance { allies, against } => format!(
+            "ALY {} VSS {}",
+            format_powers(allies),
+            format_powers(against)
+        ),
+        Arrangement::Dmz { powers, provinces } => {
+            let tokens: Vec<String> = provinces.iter().map(|&p| province_token(p)).collect();
+            format!("DMZ {} ({})", format_powers(powers), tokens.join(" "))
+        }
+        Arrangement::Order { order, power } => {
+            format!("XDO ({})", format_daide_order(order, *power, None))
+        }
+        Arrangement::And(parts) | Arrangement::Or(parts) => {
+            let head = if matches!(arrangement, Arrangement::And(_)) {
+                "AND"
+            } else {
+                "ORR"
+            };
+            let parts: Vec<String> = parts
+                .iter()
+                .map(|a| format!("({})", format_arrangement(a)))
+                .collect();
+            format!("{} {}", head, parts.join(" "))
+        }
+    }
+}
+
+/// Formats a press message in DAIDE notation, e.g. `YES (PRP (PCE (AUS GER)))`.
+pub fn format_daide_press(press: &DaidePress) -> String {
+    let (head, arrangement) = match press {
+        DaidePress::Propose(a) => return format!("PRP ({})", format_arrangement(a)),
+        DaidePress::Accept(a) => ("YES", a),
+        DaidePress::Reject(a) => ("REJ", a),
+        DaidePress::Cancel(a) => ("CCL", a),
+    };
+    format!("{} (PRP ({}))", head, format_arrangement(arrangement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn press(s: &str) -> DaidePress {
+        parse_daide_press(s).unwrap()
+    }
+
+    #[test]
+    fn daide_press_roundtrips() {
+        for text in [
+            "PRP (PCE (AUS GER))",
+            "PRP (ALY (AUS GER) VSS (RUS))",
+            "PRP (DMZ (AUS ITA) (TYR TRI))",
+            "YES (PRP (XDO ((AUS AMY VIE) MTO GAL)))",
+            "REJ (PRP (AND (PCE (AUS ITA)) (DMZ (AUS ITA) (TYR))))",
+            "CCL (PRP (ORR (XDO ((ITA FLT NAP) HLD)) (PCE (AUS ITA))))",
+        ] {
+            assert_eq!(format_daide_press(&press(text)), text);
+        }
+        assert_eq!(
+            press("prp ( xdo ( ( aus amy vie ) mto gal ) )"),
+            DaidePress::Propose(Arrangement::Order {
+                order: parse_order("A vie - gal").unwrap(),
+                power: Power::Austria,
+            })
+        );
+        assert!(parse_daide_press("").is_err());
+        assert!(parse_daide_press("PRP (DRW)").is_err());
+        assert!(parse_daide_press("YES (PCE (AUS GER))").is_err());
+        assert!(parse_daide_press("PRP (AND (PCE (AUS GER)))").is_err());
+        assert!(parse_daide_press("PRP (DMZ (AUS) (XYZ))").is_err());
+    }
+
+    #[test]
+    fn agreed_deals_constrain_their_parties() {
+        let state = parse_dfen(
+            "1901sm/Aavie,Aabud,Aftri,Iaven,Iarom,Ifnap,Rawar/Abud,Atri,Avie,Iven,Irom,Inap,Rwar/-",
+        )
+        .unwrap();
+        let mut trust = TrustModel::new();
+        let mut talks = Negotiations::new();
+        talks.observe(&state, &mut trust);
+
+        let reply = talks.receive(
+            Power::Italy,
+            press("PRP (AND (DMZ (AUS ITA) (TYR)) (XDO ((ITA AMY VEN) HLD)))"),
+            Power::Austria,
+            Some(&state),
+            &mut trust,
+        );
+        assert!(matches!(reply, Some(DaidePress::Accept(_))));
+        let ours = talks.constraints_for(Power::Austria);
+        assert_eq!(ours.forbidden, vec![Province::Tyr]);
+        assert!(ours.pinned.is_empty());
+        let theirs = talks.constraints_for(Power::Italy);
+        assert_eq!(theirs.pinned, vec![parse_order("A ven H").unwrap()]);
+        assert!(talks.constraints_for(Power::Russia).is_empty());
+
+        // Committing our own unit needs more trust than Russia has.
+        trust.betray(Power::Russia);
+        let reply = talks.receive(
+            Power::Russia,
+            press("PRP (XDO ((AUS AMY BUD) MTO GAL))"),
+            Power::Austria,
+            Some(&state),
+            &mut trust,
+        );
+        assert!(matches!(reply, Some(DaidePress::Reject(_))));
+        assert_eq!(talks.proposals_from(Power::Russia).count(), 1);
+
+        // The orders lapse with the phase; the DMZ part goes with them.
+        let next = parse_dfen(
+            "1901fm/Aavie,Aabud,Aftri,Iaven,Iarom,Ifnap,Rawar/Abud,Atri,Avie,Iven,Irom,Inap,Rwar/-",
+        )
+        .unwrap();
+        talks.observe(&next, &mut trust);
+        assert_eq!(talks.deals().count(), 0);
+    }
+
+    #[test]
+    fn multi_party_deals_wait_for_every_party() {
+        let mut trust = TrustModel::new();
+        let mut talks = Negotiations::new();
+        let peace = "PCE (AUS GER RUS)";
+        let reply = talks.receive(
+            Power::Germany,
+            press(&format!("PRP ({})", peace)),
+            Power::Austria,
+            None,
+            &mut trust,
+        );
+        assert_eq!(
+            reply.map(|r| format_daide_press(&r)),
+            Some(format!("YES (PRP ({}))", peace))
+        );
+        assert_eq!(talks.proposals()[0].status, ProposalStatus::Open);
+        talks.receive(
+            Power::Russia,
+            press(&format!("YES (PRP ({}))", peace)),
+            Power::Austria,
+            None,
+            &mut trust,
+        );
+        assert_eq!(talks.proposals()[0].status, ProposalStatus::Agreed);
+        assert!(trust.trust(Power::Russia) > 0.5);
+
+        // A DMZ its party walks into is void, and costs trust.
+        talks.receive(
+            Power::Germany,
+            press("PRP (ORR (PCE (ENG FRA)) (DMZ (GER) (BOH)))"),
+            Power::Austria,
+            None,
+            &mut trust,
+        );
+        assert_eq!(talks.deals().count(), 2);
+        let before = trust.trust(Power::Germany);
+        let state = parse_dfen("1902sm/Gaboh/-/-").unwrap();
+        talks.observe(&state, &mut trust);
+        assert_eq!(talks.deals().count(), 1);
+        assert!(trust.trust(Power::Germany) < before);
+    }
+}
@@ -0,0 +1,258 @@
+//! Order notation conversion for `realpolitik convert`.
+//!
+//! Translates order files line by line between DSON, DAIDE, and human
+//! notation, and gives the engine its `SecondaryNotation` output. A line holds one or more orders separated by `;` and may start
+//! with a `<power>:` prefix naming who gives them; the prefix is kept in the
+//! output. Blank lines and `#` comments pass through unchanged, so annotated
+//! game files survive a round trip.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::board::order::Order;
+use crate::board::province::Power;
+use crate::board::state::BoardState;
+use crate::protocol::daide::{format_daide_order, parse_daide_order, DaideError};
+use crate::protocol::dson::{format_order, format_order_on, parse_order, DsonError};
+use crate::protocol::human::{format_human_order, parse_human_order};
+
+/// An order notation the converter reads and writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    Dson,
+    Daide,
+    Human,
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Notation::Dson => "dson",
+            Notation::Daide => "daide",
+            Notation::Human => "human",
+        })
+    }
+}
+
+impl FromStr for Notation {
+    type Err = ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dson" => Ok(Notation::Dson),
+            "daide" => Ok(Notation::Daide),
+            "human" => Ok(Notation::Human),
+            _ => Err(ConvertError::UnknownNotation(s.to_string())),
+        }
+    }
+}
+
+/// Errors that can occur while converting a line.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConvertError {
+    #[error("unknown notation '{0}' (expected dson, daide, or human)")]
+    UnknownNotation(String),
+
+    #[error("unknown power '{0}'")]
+    UnknownPower(String),
+
+    #[error("DAIDE output needs the ordering power: add a '<power>:' prefix or pass --power")]
+    MissingPower,
+
+    #[error("{0}")]
+    Dson(#[from] DsonError),
+
+    #[error("{0}")]
+    Daide(#[from] DaideError),
+}
+
+/// Context for a conversion.
+#[derive(Debug, Clone, Default)]
+pub struct ConvertOptions {
+    /// Power giving orders on lines without a prefix (needed for DAIDE output).
+    pub power: Option<Power>,
+    /// Position the orders were given in. Supplies the owners of supported
+    /// and convoyed units and the phase for DAIDE output.
+    pub state: Option<BoardState>,
+}
+
+/// Splits an optional `<power>:` prefix off a line.
+fn split_power(line: &str) -> Result<(Option<Power>, &str), ConvertError> {
+    match line.split_once(':') {
+        Some((prefix, rest)) if !prefix.trim().contains(char::is_whitespace) => {
+            let name = prefix.trim();
+            let power = Power::from_name(&name.to_ascii_lowercase())
+                .ok_or_else(|| ConvertError::UnknownPower(name.to_string()))?;
+            Ok((Some(power), rest))
+        }
+        _ => Ok((None, line)),
+    }
+}
+
+fn parse_one(text: &str, from: Notation) -> Result<(Order, Option<Power>), ConvertError> {
+    Ok(match from {
+        Notation::Dson => (parse_order(text)?, None),
+        Notation::Human => (parse_human_order(text)?, None),
+        Notation::Daide => {
+            let (order, power) = parse_daide_order(text)?;
+            (order, Some(power))
+        }
+    })
+}
+
+fn format_one(
+    order: &Order,
+    power: Option<Power>,
+    to: Notation,
+    opts: &ConvertOptions,
+) -> Result<String, ConvertError> {
+    Ok(match to {
+        Notation::Dson => format_order(order),
+        Notation::Human => format_human_order(order),
+        Notation::Daide => {
+            let power = power.ok_or(ConvertError::MissingPower)?;
+            format_daide_order(order, power, opts.state.as_ref())
+        }
+    })
+}
+
+/// Formats an order given by `power` in `to` notation. `state` supplies
+/// the owners of other units for DAIDE (see [`format_daide_order`]).
+pub fn format_order_as(
+    order: &Order,
+    power: Power,
+    to: Notation,
+    state: Option<&BoardState>,
+) -> String {
+    match to {
+        Notation::Dson => match state {
+            Some(state) => format_order_on(state.map(), order),
+            None => format_order(order),
+        },
+        Notation::Human => format_human_order(order),
+        Notation::Daide => format_daide_order(order, power, state),
+    }
+}
+
+/// Formats `power`'s orders in `to` notation, separated by ` ; ` as in
+/// DSON.
+pub fn format_orders_as(
+    orders: &[Order],
+    power: Power,
+    to: Notation,
+    state: Option<&BoardState>,
+) -> String {
+    orders
+        .iter()
+        .map(|o| format_order_as(o, power, to, state))
+        .collect::<Vec<_>>()
+        .join(" ; ")
+}
+
+/// Converts one line of an order file from `from` to `to` notation.
+pub fn convert_line(
+    line: &str,
+    from: Notation,
+    to: Notation,
+    opts: &ConvertOptions,
+) -> Result<String, ConvertError> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return Ok(line.to_string());
+    }
+    let (prefix, body) = split_power(trimmed)?;
+    let line_power = prefix.or(opts.power);
+
+    let mut out = Vec::new();
+    for part in body.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (order, parsed_power) = parse_one(part, from)?;
+        out.push(format_one(&order, parsed_power.or(line_power), to, opts)?);
+    }
+    let orders = out.join(" ; ");
+    Ok(match prefix {
+        Some(p) => format!("{}: {}", p.name(), orders),
+        None => orders,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+
+    #[test]
+    fn converts_between_all_notations() {
+        let opts = ConvertOptions::default();
+        let dson = "austria: A vie - gal ; F tri S A vie - gal";
+        let daide = convert_line(dson, Notation::Dson, Notation::Daide, &opts).unwrap();
+        assert_eq!(
+            daide,
+            "austria: (AUS AMY VIE) MTO GAL ; (AUS FLT TRI) SUP (AUS AMY VIE) MTO GAL"
+        );
+        let human = convert_line(&daide, Notation::Daide, Notation::Human, &opts).unwrap();
+        assert_eq!(
+            human,
+            "austria: A Vienna - Galicia ; F Trieste S A Vienna - Galicia"
+        );
+        assert_eq!(
+            convert_line(&human, Notation::Human, Notation::Dson, &opts).unwrap(),
+            dson
+        );
+    }
+
+    #[test]
+    fn formats_a_power_s_orders() {
+        let orders = crate::protocol::dson::parse_orders("A vie - gal ; F tri H").unwrap();
+        assert_eq!(
+            format_orders_as(&orders, Power::Austria, Notation::Daide, None),
+            "(AUS AMY VIE) MTO GAL ; (AUS FLT TRI) HLD"
+        );
+        assert_eq!(
+            format_orders_as(&orders, Power::Austria, Notation::Human, None),
+            "A Vienna - Galicia ; F Trieste H"
+        );
+    }
+
+    #[test]
+    fn daide_output_takes_power_and_owners_from_options() {
+        let line = "A vie S A ven H";
+        assert_eq!(
+            convert_line(
+                line,
+                Notation::Dson,
+                Notation::Daide,
+                &ConvertOptions::default()
+            ),
+            Err(ConvertError::MissingPower)
+        );
+        let opts = ConvertOptions {
+            power: Some(Power::Austria),
+            state: Some(parse_dfen("1901sm/Aavie,Iaven/Avie,Iven/-").unwrap()),
+        };
+        assert_eq!(
+            convert_line(line, Notation::Dson, Notation::Daide, &opts).unwrap(),
+            "(AUS AMY VIE) SUP (ITA AMY VEN)"
+        );
+    }
+
+    #[test]
+    fn comments_pass_through_and_errors_surface() {
+        let opts = ConvertOptions::default();
+        assert_eq!(
+            convert_line("# 1901 spring", Notation::Dson, Notation::Human, &opts).unwrap(),
+            "# 1901 spring"
+        );
+        assert_eq!(
+            convert_line("A xyz H", Notation::Dson, Notation::Human, &opts),
+            Err(ConvertError::Dson(DsonError::UnknownProvince("xyz".into())))
+        );
+        assert_eq!(
+            convert_line("prussia: A vie H", Notation::Dson, Notation::Human, &opts),
+            Err(ConvertError::UnknownPower("prussia".into()))
+        );
+        assert_eq!("DAIDE".parse::<Notation>().unwrap(), Notation::Daide);
+        assert!("xml".parse::<Notation>().is_err());
+    }
+}
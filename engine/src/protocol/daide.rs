@@ -0,0 +1,492 @@
+//! DAIDE order notation.
+//!
+//! DAIDE (Diplomacy AI Development Environment) writes orders as
+//! parenthesized token lists with uppercase three-letter tokens:
+//! `(AUS AMY VIE) MTO GAL`, `(RUS FLT (STP SCS)) HLD`, `AUS WVE`. Unlike
+//! DSON, every unit carries its owning power, and build-phase disbands
+//! (`REM`) are distinct from retreat-phase disbands (`DSB`).
+//!
+//! Province tokens match our IDs in uppercase except for three seas:
+//! `ECH` (eng), `GOB` (bot), and `NWG` (nrg). Convoy routes (`CTO ... VIA`)
+//! are accepted but not kept, since an `Order` only records that the army
+//! travels by convoy.
+//!
+//! [`wire`] carries DAIDE messages over TCP and [`client`] plays a game on
+//! a DAIDE server with them.
+
+pub mod client;
+pub mod wire;
+
+use thiserror::Error;
+
+use crate::board::order::{Location, Order, OrderUnit};
+use crate::board::province::{Coast, Power, Province};
+use crate::board::state::{BoardState, Phase};
+use crate::board::unit::UnitType;
+
+/// Errors that can occur when parsing DAIDE order strings.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DaideError {
+    #[error("empty input")]
+    EmptyInput,
+
+    #[error("unbalanced parentheses")]
+    Unbalanced,
+
+    #[error("unknown power '{0}'")]
+    UnknownPower(String),
+
+    #[error("unknown unit type '{0}'")]
+    UnknownUnitType(String),
+
+    #[error("unknown province '{0}'")]
+    UnknownProvince(String),
+
+    #[error("unknown coast '{0}'")]
+    UnknownCoast(String),
+
+    #[error("unknown order type '{0}'")]
+    UnknownAction(String),
+
+    #[error("malformed order, expected {0}")]
+    Malformed(String),
+
+    #[error("unknown token '{0}'")]
+    UnknownToken(String),
+}
+
+/// A parsed DAIDE expression: a bare token or a parenthesized list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Expr {
+    Token(String),
+    List(Vec<Expr>),
+}
+
+/// Splits DAIDE text into a sequence of top-level expressions.
+pub(crate) fn parse_exprs(s: &str) -> Result<Vec<Expr>, DaideError> {
+    let spaced = s.replace('(', " ( ").replace(')', " ) ");
+    let mut stack: Vec<Vec<Expr>> = vec![Vec::new()];
+    for tok in spaced.split_whitespace() {
+        match tok {
+            "(" => stack.push(Vec::new()),
+            ")" => {
+                let list = stack.pop().ok_or(DaideError::Unbalanced)?;
+                stack
+                    .last_mut()
+                    .ok_or(DaideError::Unbalanced)?
+                    .push(Expr::List(list));
+            }
+            _ => stack
+                .last_mut()
+                .ok_or(DaideError::Unbalanced)?
+                .push(Expr::Token(tok.to_ascii_uppercase())),
+        }
+    }
+    if stack.len() != 1 {
+        return Err(DaideError::Unbalanced);
+    }
+    Ok(stack.pop().unwrap())
+}
+
+/// Returns the DAIDE token for a power.
+pub fn power_token(power: Power) -> &'static str {
+    match power {
+        Power::Austria => "AUS",
+        Power::England => "ENG",
+        Power::France => "FRA",
+        Power::Germany => "GER",
+        Power::Italy => "ITA",
+        Power::Russia => "RUS",
+        Power::Turkey => "TUR",
+    }
+}
+
+/// Parses a power from its DAIDE token.
+pub fn power_from_token(token: &str) -> Option<Power> {
+    match token {
+        "AUS" => Some(Power::Austria),
+        "ENG" => Some(Power::England),
+        "FRA" => Some(Power::France),
+        "GER" => Some(Power::Germany),
+        "ITA" => Some(Power::Italy),
+        "RUS" => Some(Power::Russia),
+        "TUR" => Some(Power::Turkey),
+        _ => None,
+    }
+}
+
+/// Returns the DAIDE token for a province.
+pub fn province_token(province: Province) -> String {
+    match province {
+        Province::Eng => "ECH".to_string(),
+        Province::Bot => "GOB".to_string(),
+        Province::Nrg => "NWG".to_string(),
+        p => p.abbr().to_ascii_uppercase(),
+    }
+}
+
+/// Parses a province from its DAIDE token.
+pub fn province_from_token(token: &str) -> Option<Province> {
+    match token {
+        "ECH" => Some(Province::Eng),
+        "GOB" => Some(Province::Bot),
+        "NWG" => Some(Province::Nrg),
+        // Our IDs for these three are not DAIDE tokens.
+        "ENG" | "BOT" | "NRG" => None,
+        t => Province::from_abbr(&t.to_ascii_lowercase()),
+    }
+}
+
+fn coast_token(coast: Coast) -> &'static str {
+    match coast {
+        Coast::North => "NCS",
+        Coast::South => "SCS",
+        Coast::East => "ECS",
+        Coast::None => "",
+    }
+}
+
+fn coast_from_token(token: &str) -> Option<Coast> {
+    match token {
+        "NCS" => Some(Coast::North),
+        "SCS" => Some(Coast::South),
+        "ECS" => Some(Coast::East),
+        _ => None,
+    }
+}
+
+fn parse_power(expr: &Expr) -> Result<Power, DaideError> {
+    match expr {
+        Expr::Token(t) => power_from_token(t).ok_or_else(|| DaideError::UnknownPower(t.clone())),
+        Expr::List(_) => Err(DaideError::Malformed("power token".to_string())),
+    }
+}
+
+/// Parses `PRO` or `(PRO CST)`.
+fn parse_location(expr: &Expr) -> Result<Location, DaideError> {
+    let province =
+        |t: &str| province_from_token(t).ok_or_else(|| DaideError::UnknownProvince(t.to_string()));
+    match expr {
+        Expr::Token(t) => Ok(Location::new(province(t)?)),
+        Expr::List(items) => match items.as_slice() {
+            [Expr::Token(p), Expr::Token(c)] => {
+                let coast =
+                    coast_from_token(c).ok_or_else(|| DaideError::UnknownCoast(c.clone()))?;
+                Ok(Location::with_coast(province(p)?, coast))
+            }
+            _ => Err(DaideError::Malformed("(province coast)".to_string())),
+        },
+    }
+}
+
+/// Parses `(POW UNT LOC)` into the unit and its owner.
+fn parse_unit(expr: &Expr) -> Result<(OrderUnit, Power), DaideError> {
+    let items = match expr {
+        Expr::List(items) if items.len() == 3 => items,
+        _ => {
+            return Err(DaideError::Malformed(
+                "(power unit_type location)".to_string(),
+            ))
+        }
+    };
+    let power = parse_power(&items[0])?;
+    let unit_type = match &items[1] {
+        Expr::Token(t) if t == "AMY" => UnitType::Army,
+        Expr::Token(t) if t == "FLT" => UnitType::Fleet,
+        Expr::Token(t) => return Err(DaideError::UnknownUnitType(t.clone())),
+        Expr::List(_) => return Err(DaideError::Malformed("AMY or FLT".to_string())),
+    };
+    let location = parse_location(&items[2])?;
+    Ok((
+        OrderUnit {
+            unit_type,
+            location,
+        },
+        power,
+    ))
+}
+
+fn token(expr: Option<&Expr>) -> Option<&str> {
+    match expr {
+        Some(Expr::Token(t)) => Some(t.as_str()),
+        _ => None,
+    }
+}
+
+fn required<'a>(expr: Option<&'a Expr>, expected: &str) -> Result<&'a Expr, DaideError> {
+    expr.ok_or_else(|| DaideError::Malformed(expected.to_string()))
+}
+
+/// Parses a single DAIDE order into an `Order` and the power giving it.
+///
+/// Accepts the order bare (`(AUS AMY VIE) MTO GAL`) or wrapped in one
+/// extra pair of parentheses, as DAIDE `SUB` messages list them.
+pub fn parse_daide_order(s: &str) -> Result<(Order, Power), DaideError> {
+    let mut exprs = parse_exprs(s)?;
+    if let [Expr::List(inner)] = exprs.as_slice() {
+        let wrapped = matches!(inner.first(), Some(Expr::List(_)))
+            || matches!(inner.as_slice(), [Expr::Token(_), Expr::Token(w)] if w == "WVE");
+        if wrapped {
+            exprs = inner.clone();
+        }
+    }
+    order_from_exprs(&exprs)
+}
+
+/// Parses an order already split into expressions, such as the body of a
+/// press `XDO`.
+pub(crate) fn order_from_exprs(exprs: &[Expr]) -> Result<(Order, Power), DaideError> {
+    if exprs.is_empty() {
+        return Err(DaideError::EmptyInput);
+    }
+    // Waive: POW WVE
+    if let [Expr::Token(p), Expr::Token(w)] = exprs {
+        if w == "WVE" {
+            let power = power_from_token(p).ok_or_else(|| DaideError::UnknownPower(p.clone()))?;
+            return Ok((Order::Waive, power));
+        }
+    }
+
+    let (unit, power) = parse_unit(&exprs[0])?;
+    let action = token(exprs.get(1))
+        .ok_or_else(|| DaideError::Malformed("order type after unit".to_string()))?;
+    let order = match action {
+        "HLD" => Order::Hold { unit },
+        "MTO" => Order::Move {
+            unit,
+            dest: parse_location(required(exprs.get(2), "destination")?)?,
+            via_convoy: false,
+        },
+        "CTO" => Order::Move {
+            unit,
+            dest: parse_location(required(exprs.get(2), "destination")?)?,
+            via_convoy: true,
+        },
+        "SUP" => {
+            let (supported, _) = parse_unit(required(exprs.get(2), "supported unit")?)?;
+            match token(exprs.get(3)) {
+                None => Order::SupportHold { unit, supported },
+                Some("MTO") => Order::SupportMove {
+                    unit,
+                    supported,
+                    dest: parse_location(required(exprs.get(4), "support destination")?)?,
+                },
+                Some(other) => return Err(DaideError::UnknownAction(other.to_string())),
+            }
+        }
+        "CVY" => {
+            let (army, _) = parse_unit(required(exprs.get(2), "convoyed army")?)?;
+            if token(exprs.get(3)) != Some("CTO") {
+                return Err(DaideError::Malformed("CTO after convoyed army".to_string()));
+            }
+            Order::Convoy {
+                unit,
+                convoyed_from: army.location,
+                convoyed_to: parse_location(required(exprs.get(4), "convoy destination")?)?,
+            }
+        }
+        "RTO" => Order::Retreat {
+            unit,
+            dest: parse_location(required(exprs.get(2), "retreat destination")?)?,
+        },
+        "DSB" | "REM" => Order::Disband { unit },
+        "BLD" => Order::Build { unit },
+        other => return Err(DaideError::UnknownAction(other.to_string())),
+    };
+    Ok((order, power))
+}
+
+fn format_location(loc: &Location) -> String {
+    if loc.coast == Coast::None {
+        province_token(loc.province)
+    } else {
+        format!(
+            "({} {})",
+            province_token(loc.province),
+            coast_token(loc.coast)
+        )
+    }
+}
+
+fn format_unit(unit: &OrderUnit, power: Power) -> String {
+    let unit_type = match unit.unit_type {
+        UnitType::Army => "AMY",
+        UnitType::Fleet => "FLT",
+    };
+    format!(
+        "({} {} {})",
+        power_token(power),
+        unit_type,
+        format_location(&unit.location)
+    )
+}
+
+/// Formats an order given by `power` in DAIDE notation.
+///
+/// DSON does not record who owns a supported or convoyed unit, nor whether
+/// a disband is a retreat or a build-phase removal. With a board state those
+/// come from the position; without one, the other unit is assumed to belong
+/// to `power` and disbands are written as `DSB`.
+pub fn format_daide_order(order: &Order, power: Power, state: Option<&BoardState>) -> String {
+    let owner = |unit: &OrderUnit| {
+        state
+            .and_then(|s| s.units[unit.location.province.index()])
+            .map_or(power, |(p, _)| p)
+    };
+    match order {
+        Order::Hold { unit } => format!("{} HLD", format_unit(unit, power)),
+        Order::Move {
+            unit,
+            dest,
+            via_convoy,
+        } => format!(
+            "{} {} {}",
+            format_unit(unit, power),
+            if *via_convoy { "CTO" } else { "MTO" },
+            format_location(dest)
+        ),
+        Order::SupportHold { unit, supported } => format!(
+            "{} SUP {}",
+            format_unit(unit, power),
+            format_unit(supported, owner(supported))
+        ),
+        Order::SupportMove {
+            unit,
+            supported,
+            dest,
+        } => format!(
+            "{} SUP {} MTO {}",
+            format_unit(unit, power),
+            format_unit(supported, owner(supported)),
+            province_token(dest.province)
+        ),
+        Order::Convoy {
+            unit,
+            convoyed_from,
+            convoyed_to,
+        } => {
+            let army = OrderUnit {
+                unit_type: UnitType::Army,
+                location: *convoyed_from,
+            };
+            format!(
+                "{} CVY {} CTO {}",
+                format_unit(unit, power),
+                format_unit(&army, owner(&army)),
+                province_token(convoyed_to.province)
+            )
+        }
+        Order::Retreat { unit, dest } => {
+            format!("{} RTO {}", format_unit(unit, power), format_location(dest))
+        }
+        Order::Disband { unit } => {
+            let keyword = match state.map(|s| s.phase) {
+                Some(Phase::Build) => "REM",
+                _ => "DSB",
+            };
+            format!("{} {}", format_unit(unit, power), keyword)
+        }
+        Order::Build { unit } => format!("{} BLD", format_unit(unit, power)),
+        Order::Waive => format!("{} WVE", power_token(power)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn roundtrip(daide: &str, dson: &str, power: Power) {
+        let (order, parsed_power) = parse_daide_order(daide).unwrap();
+        assert_eq!(order, parse_order(dson).unwrap(), "{}", daide);
+        assert_eq!(parsed_power, power);
+        assert_eq!(format_daide_order(&order, power, None), daide);
+    }
+
+    #[test]
+    fn daide_orders_roundtrip() {
+        roundtrip("(AUS AMY VIE) HLD", "A vie H", Power::Austria);
+        roundtrip("(AUS AMY VIE) MTO GAL", "A vie - gal", Power::Austria);
+        roundtrip(
+            "(RUS FLT SEV) MTO (BUL ECS)",
+            "F sev - bul/ec",
+            Power::Russia,
+        );
+        roundtrip(
+            "(RUS FLT (STP SCS)) MTO GOB",
+            "F stp/sc - bot",
+            Power::Russia,
+        );
+        roundtrip(
+            "(AUS FLT TRI) SUP (AUS AMY VIE) MTO GAL",
+            "F tri S A vie - gal",
+            Power::Austria,
+        );
+        roundtrip(
+            "(AUS AMY BUD) SUP (AUS AMY VIE)",
+            "A bud S A vie H",
+            Power::Austria,
+        );
+        roundtrip(
+            "(ENG FLT ECH) CVY (ENG AMY LON) CTO BEL",
+            "F eng C A lon - bel",
+            Power::England,
+        );
+        roundtrip(
+            "(ENG AMY LON) CTO BEL",
+            "A lon - bel via convoy",
+            Power::England,
+        );
+        roundtrip("(AUS AMY VIE) RTO BOH", "A vie R boh", Power::Austria);
+        roundtrip("(AUS AMY VIE) DSB", "A vie D", Power::Austria);
+        roundtrip("(RUS FLT (STP SCS)) BLD", "F stp/sc B", Power::Russia);
+        roundtrip("TUR WVE", "W", Power::Turkey);
+    }
+
+    #[test]
+    fn parse_accepts_wrapped_orders_routes_and_removals() {
+        let (order, _) = parse_daide_order("((AUS AMY VIE) MTO GAL)").unwrap();
+        assert_eq!(order, parse_order("A vie - gal").unwrap());
+        let (order, _) = parse_daide_order("(ENG AMY LON) CTO NWY VIA (NTH)").unwrap();
+        assert_eq!(order, parse_order("A lon - nwy via convoy").unwrap());
+        let (order, _) = parse_daide_order("(GER AMY MUN) REM").unwrap();
+        assert_eq!(order, parse_order("A mun D").unwrap());
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        assert_eq!(parse_daide_order(""), Err(DaideError::EmptyInput));
+        assert_eq!(
+            parse_daide_order("(AUS AMY VIE HLD"),
+            Err(DaideError::Unbalanced)
+        );
+        assert_eq!(
+            parse_daide_order("(XXX AMY VIE) HLD"),
+            Err(DaideError::UnknownPower("XXX".to_string()))
+        );
+        assert_eq!(
+            parse_daide_order("(ENG FLT ENG) HLD"),
+            Err(DaideError::UnknownProvince("ENG".to_string()))
+        );
+        assert_eq!(
+            parse_daide_order("(AUS AMY VIE) JMP GAL"),
+            Err(DaideError::UnknownAction("JMP".to_string()))
+        );
+    }
+
+    #[test]
+    fn format_uses_position_for_owners_and_removals() {
+        let state = parse_dfen("1901fb/Aavie,Iaven/Avie,Iven/-").unwrap();
+        let support = parse_order("A vie S A ven H").unwrap();
+        assert_eq!(
+            format_daide_order(&support, Power::Austria, Some(&state)),
+            "(AUS AMY VIE) SUP (ITA AMY VEN)"
+        );
+        let disband = parse_order("A vie D").unwrap();
+        assert_eq!(
+            format_daide_order(&disband, Power::Austria, Some(&state)),
+            "(AUS AMY VIE) REM"
+        );
+    }
+}
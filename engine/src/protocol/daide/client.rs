@@ -0,0 +1,813 @@
+//! Plays a game on a DAIDE server.
+//!
+//! The client sends `NME` to join, accepts the standard map, and then
+//! follows the server: `HLO` assigns our power, `SCO` and `NOW` describe
+//! the position, and every `NOW` in which we have units to order (or
+//! dislodged units, or builds) is searched and answered with `SUB`. The
+//! search goes through the same [`Engine`] the DUI loop drives, so options,
+//! the opening book and press state all apply. The game is over at `OFF`.
+//!
+//! Press (`FRM`) goes to the engine's DAIDE negotiation (see
+//! `press::negotiation`) and its answers go back with `SND`; when a deal
+//! comes into force during a movement phase, the phase is searched again
+//! and the new orders replace the old. Retreats are checked against the options
+//! the server lists with `MRT`, since `NOW` does not say where the attack
+//! came from; any other retreat becomes a disband. Convoyed moves name the
+//! fleets of ours convoying them as the `VIA` route.
+//!
+//! Every `NOW` is set as the engine's position, ours to order or not, and
+//! the adjudicated orders the server reports with `ORD` are recorded for
+//! it (see [`Engine::record_resolution`]), so everything the engine learns
+//! from the game's history sees every power's orders.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+use super::wire::{initial_body, read_message, write_message, MessageKind, TokenTable};
+use super::{
+    format_daide_order, order_from_exprs, parse_exprs, parse_unit, power_from_token, power_token,
+    province_from_token, province_token, DaideError, Expr,
+};
+use crate::board::map::GameMap;
+use crate::board::order::{Location, Order};
+use crate::board::province::{Coast, Power, Province, MAX_PROVINCES};
+use crate::board::state::{BoardState, DislodgedUnit, Phase, Season};
+use crate::engine::Engine;
+use crate::movegen::random_orders;
+use crate::protocol::dfen::encode_dfen;
+use crate::protocol::dson::parse_annotated_orders;
+use crate::protocol::parser::{Command, GoParams};
+use crate::resolve::{OrderResult, ResolvedOrder};
+use crate::rng::entropy_rng;
+
+/// How often a running search is polled.
+const SEARCH_POLL: Duration = Duration::from_millis(10);
+
+/// How the client introduces itself and paces its searches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaideConfig {
+    /// Name sent with `NME`.
+    pub name: String,
+    /// Version sent with `NME`.
+    pub version: String,
+    /// Search time per phase. Without one, half the server's move time
+    /// limit (`MTL`) is used, or the engine's `SearchTime` if it has none.
+    pub movetime: Option<Duration>,
+}
+
+impl Default for DaideConfig {
+    fn default() -> Self {
+        DaideConfig {
+            name: "realpolitik".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            movetime: None,
+        }
+    }
+}
+
+/// Connects to the DAIDE server at `addr` and plays one game with `engine`.
+pub fn connect(addr: &str, engine: &mut Engine, config: &DaideConfig) -> io::Result<()> {
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_nodelay(true)?;
+    play(&mut stream, engine, config)
+}
+
+/// Plays one game over an established connection. Returns once the server
+/// sends `OFF` or closes the connection cleanly.
+pub fn play<S: Read + Write>(
+    stream: &mut S,
+    engine: &mut Engine,
+    config: &DaideConfig,
+) -> io::Result<()> {
+    write_message(stream, MessageKind::Initial, &initial_body())?;
+    let mut client = Client {
+        engine,
+        config,
+        tokens: TokenTable::standard(),
+        power: None,
+        sc_owner: [None; MAX_PROVINCES],
+        move_limit: None,
+        now: None,
+        resolved: Vec::new(),
+    };
+    let mut joined = false;
+    loop {
+        let msg = read_message(stream)?;
+        let replies = match msg.kind {
+            MessageKind::Representation => {
+                client
+                    .tokens
+                    .apply_representation(&msg.body)
+                    .map_err(invalid)?;
+                if joined {
+                    Vec::new()
+                } else {
+                    joined = true;
+                    vec![format!(
+                        "NME ( '{}' ) ( '{}' )",
+                        config.name, config.version
+                    )]
+                }
+            }
+            MessageKind::Diplomacy => {
+                let text = client.tokens.decode(&msg.body).map_err(invalid)?;
+                match client.handle(&text).map_err(invalid)? {
+                    Step::Reply(replies) => replies,
+                    Step::Done => break,
+                }
+            }
+            MessageKind::Final => break,
+            MessageKind::Error => {
+                let code = msg
+                    .body
+                    .get(..2)
+                    .map_or(0, |b| u16::from_be_bytes([b[0], b[1]]));
+                return Err(io::Error::other(format!(
+                    "DAIDE server reported error 0x{:02X}",
+                    code
+                )));
+            }
+            MessageKind::Initial => {
+                return Err(invalid(DaideError::Malformed(
+                    "no initial message from the server".to_string(),
+                )))
+            }
+        };
+        for reply in replies {
+            let body = client.tokens.encode(&reply).map_err(invalid)?;
+            write_message(stream, MessageKind::Diplomacy, &body)?;
+        }
+    }
+    write_message(stream, MessageKind::Final, &[])
+}
+
+fn invalid(e: DaideError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// What to do after a server message.
+enum Step {
+    Reply(Vec<String>),
+    Done,
+}
+
+struct Client<'a> {
+    engine: &'a mut Engine,
+    config: &'a DaideConfig,
+    tokens: TokenTable,
+    power: Option<Power>,
+    sc_owner: [Option<Power>; MAX_PROVINCES],
+    /// The server's move time limit, from `HLO`.
+    move_limit: Option<Duration>,
+    /// Arguments of the last `NOW`, to search again after press.
+    now: Option<Vec<Expr>>,
+    /// The `ORD` results received for the last `NOW`.
+    resolved: Vec<ResolvedOrder>,
+}
+
+impl Client<'_> {
+    fn handle(&mut self, text: &str) -> Result<Step, DaideError> {
+        let exprs = parse_exprs(text)?;
+        let Some(Expr::Token(command)) = exprs.first() else {
+            return Err(DaideError::Malformed("a command token".to_string()));
+        };
+        let args = &exprs[1..];
+        let replies = match command.as_str() {
+            "MAP" => {
+                let name = text
+                    .split('\'')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_ascii_uppercase();
+                if name == "STANDARD" {
+                    vec![format!("YES ( {} )", text)]
+                } else {
+                    eprintln!("daide: unsupported map '{}'", name);
+                    vec![format!("REJ ( {} )", text)]
+                }
+            }
+            "HLO" => {
+                self.hello(args)?;
+                Vec::new()
+            }
+            "SCO" => {
+                self.supply_centers(args)?;
+                Vec::new()
+            }
+            "NOW" => {
+                self.now = Some(args.to_vec());
+                self.resolved.clear();
+                self.turn(args)?.into_iter().collect()
+            }
+            "ORD" => {
+                self.order_result(args)?;
+                Vec::new()
+            }
+            "FRM" => self.press(args)?,
+            "OFF" => return Ok(Step::Done),
+            "REJ" => {
+                eprintln!("daide: server rejected {}", text);
+                Vec::new()
+            }
+            "HUH" => {
+                eprintln!("daide: server could not parse {}", text);
+                Vec::new()
+            }
+            "THX" => {
+                if !text.contains("MBV") {
+                    eprintln!("daide: order not accepted: {}", text);
+                }
+                Vec::new()
+            }
+            "MIS" | "SLO" | "DRW" | "SMR" => {
+                eprintln!("daide: {}", text);
+                Vec::new()
+            }
+            // YES, TME, CCD, NOT and others need no answer.
+            _ => Vec::new(),
+        };
+        Ok(Step::Reply(replies))
+    }
+
+    /// `HLO (power) (passcode) (variant)`.
+    fn hello(&mut self, args: &[Expr]) -> Result<(), DaideError> {
+        let power = match args.first() {
+            Some(Expr::List(items)) => match items.as_slice() {
+                [Expr::Token(t)] => {
+                    power_from_token(t).ok_or_else(|| DaideError::UnknownPower(t.clone()))?
+                }
+                _ => return Err(DaideError::Malformed("(power)".to_string())),
+            },
+            _ => return Err(DaideError::Malformed("(power)".to_string())),
+        };
+        self.move_limit = None;
+        if let Some(Expr::List(variant)) = args.get(2) {
+            for option in variant {
+                if let Expr::List(items) = option {
+                    if let [Expr::Token(k), Expr::Token(v)] = items.as_slice() {
+                        if k == "MTL" {
+                            self.move_limit = v.parse().ok().map(Duration::from_secs);
+                        }
+                    }
+                }
+            }
+        }
+        self.power = Some(power);
+        self.engine.execute(&mut io::sink(), Command::NewGame);
+        self.engine
+            .execute(&mut io::sink(), Command::SetPower { power });
+        Ok(())
+    }
+
+    /// `SCO (power centre...)... (UNO centre...)`.
+    fn supply_centers(&mut self, args: &[Expr]) -> Result<(), DaideError> {
+        self.sc_owner = [None; MAX_PROVINCES];
+        for group in args {
+            let Expr::List(items) = group else {
+                return Err(DaideError::Malformed("(power centres)".to_string()));
+            };
+            let Some(Expr::Token(owner)) = items.first() else {
+                return Err(DaideError::Malformed("(power centres)".to_string()));
+            };
+            let owner = match owner.as_str() {
+                "UNO" => None,
+                t => Some(power_from_token(t).ok_or_else(|| DaideError::UnknownPower(t.into()))?),
+            };
+            for centre in &items[1..] {
+                let Expr::Token(t) = centre else {
+                    return Err(DaideError::Malformed("supply centre".to_string()));
+                };
+                let province =
+                    province_from_token(t).ok_or_else(|| DaideError::UnknownProvince(t.clone()))?;
+                self.sc_owner[province.index()] = owner;
+            }
+        }
+        Ok(())
+    }
+
+    /// `FRM (sender) (recipients) (press)`: negotiates through the engine
+    /// and answers with `SND`. A deal coming into force during a movement
+    /// phase also brings a new `SUB`.
+    fn press(&mut self, args: &[Expr]) -> Result<Vec<String>, DaideError> {
+        let from = match args.first() {
+            Some(Expr::List(items)) => match items.first() {
+                Some(Expr::Token(t)) => {
+                    power_from_token(t).ok_or_else(|| DaideError::UnknownPower(t.clone()))?
+                }
+                _ => return Err(DaideError::Malformed("(sender)".to_string())),
+            },
+            _ => return Err(DaideError::Malformed("(sender)".to_string())),
+        };
+        let Some(Expr::List(message)) = args.get(2) else {
+            return Err(DaideError::Malformed("(press message)".to_string()));
+        };
+        let text: Vec<String> = message.iter().map(render).collect();
+        let before: Vec<_> = self.engine.press.negotiations.deals().cloned().collect();
+        let mut out = Vec::new();
+        self.engine.execute(
+            &mut out,
+            Command::Press {
+                raw: format!("{} daide {}", from.name(), text.join(" ")),
+            },
+        );
+        let mut replies: Vec<String> = String::from_utf8_lossy(&out)
+            .lines()
+            .filter_map(|l| {
+                let (to, press) = l.strip_prefix("press_out ")?.split_once(" daide ")?;
+                let to = Power::from_name(to)?;
+                Some(format!("SND ( {} ) ( {} )", power_token(to), press))
+            })
+            .collect();
+        let changed = !self.engine.press.negotiations.deals().eq(before.iter());
+        if changed {
+            if let Some(now) = self.now.clone() {
+                let (state, _) = parse_position(&now, &self.sc_owner)?;
+                if state.phase == Phase::Movement {
+                    replies.extend(self.turn(&now)?);
+                }
+            }
+        }
+        Ok(replies)
+    }
+
+    /// `ORD (season year) (order) (result)`: records another adjudicated
+    /// order of the last `NOW` with the engine. Results for any other turn
+    /// are ignored.
+    fn order_result(&mut self, args: &[Expr]) -> Result<(), DaideError> {
+        let [turn, Expr::List(order), Expr::List(result)] = args else {
+            return Err(DaideError::Malformed("(turn) (order) (result)".to_string()));
+        };
+        let (order, power) = order_from_exprs(order)?;
+        let tokens: Vec<&str> = result
+            .iter()
+            .filter_map(|e| match e {
+                Expr::Token(t) => Some(t.as_str()),
+                Expr::List(_) => None,
+            })
+            .collect();
+        let result = if tokens.contains(&"RET") {
+            OrderResult::Dislodged
+        } else {
+            match tokens.first().copied() {
+                Some("SUC") => OrderResult::Succeeded,
+                Some("BNC") => OrderResult::Bounced,
+                Some("CUT") => OrderResult::Cut,
+                Some("DSR") => OrderResult::NoConvoy,
+                Some("NSO") => OrderResult::Void,
+                _ => OrderResult::Failed,
+            }
+        };
+        let current = match self.now.as_deref() {
+            Some([now, ..]) => parse_turn(now)?,
+            _ => return Ok(()),
+        };
+        if parse_turn(turn)? != current {
+            return Ok(());
+        }
+        self.resolved.push(ResolvedOrder {
+            order,
+            power,
+            result,
+        });
+        let orders: Vec<Order> = self
+            .resolved
+            .iter()
+            .filter(|r| r.power == power)
+            .map(|r| r.order)
+            .collect();
+        self.engine.record_orders(power, &orders);
+        self.engine.record_resolution(&self.resolved);
+        Ok(())
+    }
+
+    /// `NOW (season year) (unit)...`: sets the position, then searches and
+    /// returns the `SUB` if we have anything to order.
+    fn turn(&mut self, args: &[Expr]) -> Result<Option<String>, DaideError> {
+        let (state, retreats) = parse_position(args, &self.sc_owner)?;
+        self.engine.execute(
+            &mut io::sink(),
+            Command::Position {
+                dfen: encode_dfen(&state),
+            },
+        );
+        let Some(power) = self.power else {
+            return Ok(None);
+        };
+        if !has_orders(power, &state) {
+            return Ok(None);
+        }
+        let mut orders = self.search(power, &state);
+        for order in &mut orders {
+            if let Order::Retreat { unit, dest } = *order {
+                let allowed = retreats
+                    .iter()
+                    .find(|(p, _)| *p == unit.location.province)
+                    .is_some_and(|(_, dests)| dests.iter().any(|d| d.province == dest.province));
+                if !allowed {
+                    *order = Order::Disband { unit };
+                }
+            }
+        }
+        if orders.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(submission(power, &state, &orders)))
+    }
+
+    /// Runs a DUI search on `state`, already the engine's position, and
+    /// returns its best orders.
+    fn search(&mut self, power: Power, state: &BoardState) -> Vec<Order> {
+        let mut out = Vec::new();
+        let engine = &mut *self.engine;
+        engine.execute(&mut io::sink(), Command::SetPower { power });
+        let movetime = self
+            .config
+            .movetime
+            .or(self.move_limit.map(|limit| limit / 2))
+            .map(|t| t.as_millis().max(1) as u64);
+        engine.execute(
+            &mut out,
+            Command::Go(GoParams {
+                movetime,
+                ..GoParams::default()
+            }),
+        );
+        while engine.is_searching() && !engine.poll_search_done(&mut out) {
+            thread::sleep(SEARCH_POLL);
+        }
+        let best = String::from_utf8_lossy(&out)
+            .lines()
+            .find_map(|l| l.strip_prefix("bestorders ").map(str::to_string));
+        match best.as_deref().map(parse_annotated_orders) {
+            Some(Ok(orders)) => orders.into_iter().map(|(o, _)| o).collect(),
+            _ => {
+                eprintln!("daide: search gave no orders; playing random ones");
+                random_orders(power, state, &mut entropy_rng())
+            }
+        }
+    }
+}
+
+/// DAIDE text for an expression.
+fn render(expr: &Expr) -> String {
+    match expr {
+        Expr::Token(t) => t.clone(),
+        Expr::List(items) => {
+            let inner: Vec<String> = items.iter().map(render).collect();
+            format!("({})", inner.join(" "))
+        }
+    }
+}
+
+/// Returns true if `power` has anything to order in `state`.
+fn has_orders(power: Power, state: &BoardState) -> bool {
+    let ours = |u: &Option<(Power, _)>| matches!(u, Some((p, _)) if *p == power);
+    match state.phase {
+        Phase::Movement => state.units.iter().any(ours),
+        Phase::Retreat => state
+            .dislodged
+            .iter()
+            .any(|d| d.is_some_and(|d| d.power == power)),
+        Phase::Build => {
+            let units = state.units.iter().filter(|u| ours(u)).count();
+            let centers = state.sc_owner.iter().filter(|o| **o == Some(power)).count();
+            units != centers
+        }
+    }
+}
+
+/// Retreat options per dislodged unit, from `MRT`.
+type RetreatOptions = Vec<(Province, Vec<Location>)>;
+
+/// The year, season and phase of a `(season year)` turn.
+fn parse_turn(expr: &Expr) -> Result<(u16, Season, Phase), DaideError> {
+    let Expr::List(items) = expr else {
+        return Err(DaideError::Malformed("(season year)".to_string()));
+    };
+    let [Expr::Token(s), Expr::Token(y)] = items.as_slice() else {
+        return Err(DaideError::Malformed("(season year)".to_string()));
+    };
+    let (season, phase) = match s.as_str() {
+        "SPR" => (Season::Spring, Phase::Movement),
+        "SUM" => (Season::Spring, Phase::Retreat),
+        "FAL" => (Season::Fall, Phase::Movement),
+        "AUT" => (Season::Fall, Phase::Retreat),
+        "WIN" => (Season::Fall, Phase::Build),
+        _ => return Err(DaideError::Malformed("season".to_string())),
+    };
+    let year = y
+        .parse()
+        .map_err(|_| DaideError::Malformed("year".to_string()))?;
+    Ok((year, season, phase))
+}
+
+/// Builds the position described by `NOW` arguments.
+fn parse_position(
+    args: &[Expr],
+    sc_owner: &[Option<Power>; MAX_PROVINCES],
+) -> Result<(BoardState, RetreatOptions), DaideError> {
+    let Some(turn) = args.first() else {
+        return Err(DaideError::Malformed("(season year)".to_string()));
+    };
+    let (year, season, phase) = parse_turn(turn)?;
+    let mut state = BoardState::empty(year, season, phase);
+    for (idx, &owner) in sc_owner.iter().enumerate() {
+        state.set_sc_owner(Province::from_index(idx), owner);
+    }
+    let mut retreats = Vec::new();
+    for expr in &args[1..] {
+        let Expr::List(items) = expr else {
+            return Err(DaideError::Malformed("(unit)".to_string()));
+        };
+        if items.len() < 3 {
+            return Err(DaideError::Malformed(
+                "(power unit_type location)".to_string(),
+            ));
+        }
+        let (unit, owner) = parse_unit(&Expr::List(items[..3].to_vec()))?;
+        let loc = unit.location;
+        match &items[3..] {
+            [] => {
+                state.place_unit(loc.province, owner, unit.unit_type, loc.coast);
+            }
+            [Expr::Token(mrt), Expr::List(options)] if mrt == "MRT" => {
+                let dests = options
+                    .iter()
+                    .map(super::parse_location)
+                    .collect::<Result<Vec<_>, _>>()?;
+                // NOW does not say where the attack came from; the MRT list
+                // already excludes it.
+                state.set_dislodged(
+                    loc.province,
+                    DislodgedUnit {
+                        power: owner,
+                        unit_type: unit.unit_type,
+                        coast: loc.coast,
+                        attacker_from: loc.province,
+                    },
+                );
+                retreats.push((loc.province, dests));
+            }
+            _ => return Err(DaideError::Malformed("MRT (retreat options)".to_string())),
+        }
+    }
+    Ok((state, retreats))
+}
+
+/// The `SUB` message for `orders`.
+fn submission(power: Power, state: &BoardState, orders: &[Order]) -> String {
+    let mut text = String::from("SUB");
+    for order in orders {
+        let mut daide = format_daide_order(order, power, Some(state));
+        if let Order::Move {
+            unit,
+            dest,
+            via_convoy: true,
+        } = order
+        {
+            let route = convoy_route(state.map(), unit.location.province, dest.province, orders);
+            if !route.is_empty() {
+                let seas: Vec<String> = route.into_iter().map(province_token).collect();
+                daide.push_str(&format!(" VIA ( {} )", seas.join(" ")));
+            }
+        }
+        text.push_str(&format!(" ( {} )", daide));
+    }
+    text
+}
+
+/// The shortest chain of our fleets convoying the army at `from` to `to`,
+/// in order along the route. Empty if our own orders do not complete one.
+fn convoy_route(map: &GameMap, from: Province, to: Province, orders: &[Order]) -> Vec<Province> {
+    let fleets: Vec<Province> = orders
+        .iter()
+        .filter_map(|o| match o {
+            Order::Convoy {
+                unit,
+                convoyed_from,
+                convoyed_to,
+            } if convoyed_from.province == from && convoyed_to.province == to => {
+                Some(unit.location.province)
+            }
+            _ => None,
+        })
+        .collect();
+    let adjacent = |a: Province, b: Province| map.is_adjacent(a, Coast::None, b, Coast::None, true);
+    // Breadth-first over the fleets; `parent[i]` is the fleet before `i`.
+    let mut parent: Vec<Option<Option<usize>>> = vec![None; fleets.len()];
+    let mut queue = std::collections::VecDeque::new();
+    for (i, &f) in fleets.iter().enumerate() {
+        if adjacent(from, f) {
+            parent[i] = Some(None);
+            queue.push_back(i);
+        }
+    }
+    while let Some(i) = queue.pop_front() {
+        if adjacent(fleets[i], to) {
+            let mut route = vec![fleets[i]];
+            let mut at = i;
+            while let Some(Some(prev)) = parent[at] {
+                route.push(fleets[prev]);
+                at = prev;
+            }
+            route.reverse();
+            return route;
+        }
+        for j in 0..fleets.len() {
+            if parent[j].is_none() && adjacent(fleets[i], fleets[j]) {
+                parent[j] = Some(Some(i));
+                queue.push_back(j);
+            }
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_daide_order;
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+    use std::net::TcpListener;
+
+    const INITIAL_NOW: &str = "NOW ( SPR 1901 ) ( AUS AMY BUD ) ( AUS AMY VIE ) ( AUS FLT TRI ) ( ENG FLT EDI ) ( ENG FLT LON ) ( ENG AMY LVP ) ( FRA FLT BRE ) ( FRA AMY MAR ) ( FRA AMY PAR ) ( GER FLT KIE ) ( GER AMY BER ) ( GER AMY MUN ) ( ITA FLT NAP ) ( ITA AMY ROM ) ( ITA AMY VEN ) ( RUS AMY WAR ) ( RUS AMY MOS ) ( RUS FLT SEV ) ( RUS FLT ( STP SCS ) ) ( TUR FLT ANK ) ( TUR AMY CON ) ( TUR AMY SMY )";
+    const INITIAL_SCO: &str = "SCO ( AUS BUD TRI VIE ) ( ENG EDI LON LVP ) ( FRA BRE MAR PAR ) ( GER BER KIE MUN ) ( ITA NAP ROM VEN ) ( RUS MOS SEV STP WAR ) ( TUR ANK CON SMY ) ( UNO BEL BUL DEN GRE HOL NWY POR RUM SER SPA SWE TUN )";
+
+    fn sc_owners(sco: &str) -> [Option<Power>; MAX_PROVINCES] {
+        let mut engine = Engine::new();
+        let config = DaideConfig::default();
+        let mut client = Client {
+            engine: &mut engine,
+            config: &config,
+            tokens: TokenTable::standard(),
+            power: None,
+            sc_owner: [None; MAX_PROVINCES],
+            move_limit: None,
+            now: None,
+            resolved: Vec::new(),
+        };
+        client
+            .supply_centers(&parse_exprs(sco).unwrap()[1..])
+            .unwrap();
+        client.sc_owner
+    }
+
+    #[test]
+    fn parses_now_and_sco_into_a_position() {
+        let client_sc = sc_owners(INITIAL_SCO);
+        let exprs = parse_exprs(INITIAL_NOW).unwrap();
+        let (state, retreats) = parse_position(&exprs[1..], &client_sc).unwrap();
+        assert!(retreats.is_empty());
+        assert_eq!(
+            state,
+            parse_dfen("1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-").unwrap()
+        );
+
+        let exprs = parse_exprs("NOW ( SUM 1901 ) ( AUS AMY SER MRT ( ALB GRE ) ) ( TUR AMY SER )")
+            .unwrap();
+        let (state, retreats) = parse_position(&exprs[1..], &client_sc).unwrap();
+        assert_eq!(state.phase, Phase::Retreat);
+        assert!(has_orders(Power::Austria, &state));
+        assert!(!has_orders(Power::Russia, &state));
+        assert_eq!(retreats[0].0, Province::Ser);
+        assert_eq!(retreats[0].1.len(), 2);
+    }
+
+    #[test]
+    fn records_other_powers_adjudicated_orders() {
+        let mut engine = Engine::new();
+        let config = DaideConfig::default();
+        let mut client = Client {
+            engine: &mut engine,
+            config: &config,
+            tokens: TokenTable::standard(),
+            power: Some(Power::Austria),
+            sc_owner: sc_owners(INITIAL_SCO),
+            move_limit: None,
+            now: None,
+            resolved: Vec::new(),
+        };
+        for text in [
+            "NOW ( SPR 1901 ) ( GER AMY MUN ) ( FRA AMY BUR ) ( FRA AMY MAR )",
+            "ORD ( SPR 1901 ) ( ( GER AMY MUN ) MTO BUR ) ( BNC )",
+            "ORD ( SPR 1901 ) ( ( FRA AMY BUR ) HLD ) ( SUC )",
+            "ORD ( SPR 1901 ) ( ( FRA AMY MAR ) SUP ( FRA AMY BUR ) ) ( SUC )",
+            "ORD ( FAL 1900 ) ( ( FRA AMY MAR ) HLD ) ( SUC )",
+        ] {
+            client.handle(text).unwrap();
+        }
+
+        let phase = engine
+            .record()
+            .phase((1901, Season::Spring, Phase::Movement))
+            .unwrap();
+        assert_eq!(phase.resolved.len(), 3);
+        assert_eq!(phase.resolved[0].power, Power::Germany);
+        assert_eq!(phase.resolved[0].result, OrderResult::Bounced);
+        let french = phase.orders.iter().find(|(p, _)| *p == Power::France);
+        assert_eq!(french.map(|(_, o)| o.len()), Some(2));
+    }
+
+    #[test]
+    fn convoyed_moves_name_their_route() {
+        let state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        let orders: Vec<Order> = [
+            "A lon - nwy via convoy",
+            "F eng C A lon - nwy",
+            "F nth C A lon - nwy",
+        ]
+        .iter()
+        .map(|s| parse_order(s).unwrap())
+        .collect();
+        let sub = submission(Power::England, &state, &orders);
+        assert!(
+            sub.starts_with("SUB ( (ENG AMY LON) CTO NWY VIA ( NTH ) )"),
+            "{}",
+            sub
+        );
+        assert!(TokenTable::standard().encode(&sub).is_ok());
+    }
+
+    fn send(stream: &mut TcpStream, tokens: &TokenTable, text: &str) {
+        write_message(
+            stream,
+            MessageKind::Diplomacy,
+            &tokens.encode(text).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn receive(stream: &mut TcpStream, tokens: &TokenTable) -> String {
+        let msg = read_message(stream).unwrap();
+        assert_eq!(msg.kind, MessageKind::Diplomacy);
+        tokens.decode(&msg.body).unwrap()
+    }
+
+    #[test]
+    fn plays_a_phase_against_a_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let tokens = TokenTable::standard();
+            let (mut s, _) = listener.accept().unwrap();
+            let im = read_message(&mut s).unwrap();
+            assert_eq!(im.kind, MessageKind::Initial);
+            assert_eq!(im.body, initial_body());
+            write_message(&mut s, MessageKind::Representation, &[]).unwrap();
+            assert!(receive(&mut s, &tokens).starts_with("NME ( 'realpolitik' )"));
+            send(&mut s, &tokens, "MAP ( 'STANDARD' )");
+            assert_eq!(receive(&mut s, &tokens), "YES ( MAP ( 'STANDARD' ) )");
+            send(
+                &mut s,
+                &tokens,
+                "HLO ( AUS ) ( 1234 ) ( ( LVL 0 ) ( MTL 60 ) )",
+            );
+            send(&mut s, &tokens, INITIAL_SCO);
+            send(&mut s, &tokens, INITIAL_NOW);
+            let sub = receive(&mut s, &tokens);
+            send(
+                &mut s,
+                &tokens,
+                "FRM ( GER ) ( AUS ) ( PRP ( DMZ ( AUS GER ) ( GAL BOH ) ) )",
+            );
+            let snd = receive(&mut s, &tokens);
+            let resub = receive(&mut s, &tokens);
+            send(&mut s, &tokens, "OFF");
+            let fm = read_message(&mut s).unwrap();
+            assert_eq!(fm.kind, MessageKind::Final);
+            (sub, snd, resub)
+        });
+
+        let mut engine = Engine::new();
+        engine.set_option("BookPath".into(), Some(String::new()));
+        let config = DaideConfig {
+            movetime: Some(Duration::from_millis(200)),
+            ..DaideConfig::default()
+        };
+        connect(&addr, &mut engine, &config).unwrap();
+
+        let (sub, snd, resub) = server.join().unwrap();
+        assert_eq!(
+            snd,
+            "SND ( GER ) ( YES ( PRP ( DMZ ( AUS GER ) ( GAL BOH ) ) ) )"
+        );
+        let exprs = parse_exprs(&resub).unwrap();
+        assert_eq!(exprs.len(), 4, "{}", resub);
+        assert!(
+            !resub.contains("GAL") && !resub.contains("BOH"),
+            "{}",
+            resub
+        );
+        let exprs = parse_exprs(&sub).unwrap();
+        assert_eq!(exprs[0], Expr::Token("SUB".to_string()));
+        assert_eq!(exprs.len(), 4, "{}", sub);
+        for order in &exprs[1..] {
+            let (_, power) = parse_daide_order(&render(order)).unwrap();
+            assert_eq!(power, Power::Austria);
+        }
+        assert!(sub.contains("( AUS AMY VIE )"), "{}", sub);
+    }
+}
@@ -0,0 +1,510 @@
+//! DAIDE client-server framing and token encoding.
+//!
+//! A DAIDE connection carries messages with a 4-byte header: message type,
+//! a pad byte, and the body length (big-endian). Diplomacy messages (`DM`)
+//! are sequences of 16-bit tokens. This module converts those bodies to and
+//! from the text form the rest of [`super`] parses, with tokens separated by
+//! spaces, `(` and `)` for brackets, integers in decimal, and text in single
+//! quotes: `NOW ( SPR 1901 ) ( AUS AMY VIE )`, `NME ( 'realpolitik' )`.
+//!
+//! Token codes follow the DAIDE message syntax for the standard map. A
+//! server's representation message (`RM`) can rename or add province and
+//! power tokens; [`TokenTable::apply_representation`] takes it into account.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use super::DaideError;
+
+/// Protocol version sent in the initial message.
+const PROTOCOL_VERSION: u16 = 1;
+
+/// Magic number that identifies DAIDE (and its byte order).
+const MAGIC: u16 = 0xDA10;
+
+/// Message types of the client-server protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    /// `IM`: first message from the client.
+    Initial,
+    /// `RM`: first message from the server, naming the map's tokens.
+    Representation,
+    /// `DM`: a diplomacy message in tokens.
+    Diplomacy,
+    /// `FM`: the sender is closing the connection.
+    Final,
+    /// `EM`: the sender saw a protocol error and is closing.
+    Error,
+}
+
+impl MessageKind {
+    fn code(self) -> u8 {
+        match self {
+            MessageKind::Initial => 0,
+            MessageKind::Representation => 1,
+            MessageKind::Diplomacy => 2,
+            MessageKind::Final => 3,
+            MessageKind::Error => 4,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(MessageKind::Initial),
+            1 => Some(MessageKind::Representation),
+            2 => Some(MessageKind::Diplomacy),
+            3 => Some(MessageKind::Final),
+            4 => Some(MessageKind::Error),
+            _ => None,
+        }
+    }
+}
+
+/// One framed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub kind: MessageKind,
+    pub body: Vec<u8>,
+}
+
+/// Reads one message.
+pub fn read_message<R: Read>(r: &mut R) -> io::Result<Message> {
+    let mut header = [0u8; 4];
+    r.read_exact(&mut header)?;
+    let kind = MessageKind::from_code(header[0]).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown DAIDE message type {}", header[0]),
+        )
+    })?;
+    let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+    let mut body = vec![0u8; len];
+    r.read_exact(&mut body)?;
+    Ok(Message { kind, body })
+}
+
+/// Writes one message and flushes.
+pub fn write_message<W: Write>(w: &mut W, kind: MessageKind, body: &[u8]) -> io::Result<()> {
+    let len = u16::try_from(body.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "DAIDE message too long"))?;
+    let mut frame = Vec::with_capacity(4 + body.len());
+    frame.push(kind.code());
+    frame.push(0);
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(body);
+    w.write_all(&frame)?;
+    w.flush()
+}
+
+/// Body of the client's initial message.
+pub fn initial_body() -> Vec<u8> {
+    let mut body = PROTOCOL_VERSION.to_be_bytes().to_vec();
+    body.extend_from_slice(&MAGIC.to_be_bytes());
+    body
+}
+
+const BRA: u16 = 0x4000;
+const KET: u16 = 0x4001;
+/// High byte of a text token; the low byte is an ASCII character.
+const TEXT: u16 = 0x4B00;
+
+/// Named tokens other than provinces.
+const NAMED_TOKENS: &[(&str, u16)] = &[
+    // Powers
+    ("AUS", 0x4100),
+    ("ENG", 0x4101),
+    ("FRA", 0x4102),
+    ("GER", 0x4103),
+    ("ITA", 0x4104),
+    ("RUS", 0x4105),
+    ("TUR", 0x4106),
+    // Unit types
+    ("AMY", 0x4200),
+    ("FLT", 0x4201),
+    // Orders
+    ("CTO", 0x4320),
+    ("CVY", 0x4321),
+    ("HLD", 0x4322),
+    ("MTO", 0x4323),
+    ("SUP", 0x4324),
+    ("VIA", 0x4325),
+    ("DSB", 0x4340),
+    ("RTO", 0x4341),
+    ("BLD", 0x4380),
+    ("REM", 0x4381),
+    ("WVE", 0x4382),
+    // Order notes
+    ("MBV", 0x4400),
+    ("BPR", 0x4401),
+    ("CST", 0x4402),
+    ("ESC", 0x4403),
+    ("FAR", 0x4404),
+    ("HSC", 0x4405),
+    ("NAS", 0x4406),
+    ("NMB", 0x4407),
+    ("NMR", 0x4408),
+    ("NRN", 0x4409),
+    ("NRS", 0x440A),
+    ("NSA", 0x440B),
+    ("NSC", 0x440C),
+    ("NSF", 0x440D),
+    ("NSP", 0x440E),
+    ("NST", 0x440F),
+    ("NSU", 0x4410),
+    ("NVR", 0x4411),
+    ("NYU", 0x4412),
+    ("YSC", 0x4413),
+    // Order results
+    ("SUC", 0x4500),
+    ("BNC", 0x4501),
+    ("CUT", 0x4502),
+    ("DSR", 0x4503),
+    ("FLD", 0x4504),
+    ("NSO", 0x4505),
+    ("RET", 0x4506),
+    // Coasts
+    ("NCS", 0x4600),
+    ("NEC", 0x4602),
+    ("ECS", 0x4604),
+    ("SEC", 0x4606),
+    ("SCS", 0x4608),
+    ("SWC", 0x460A),
+    ("WCS", 0x460C),
+    ("NWC", 0x460E),
+    // Seasons
+    ("SPR", 0x4700),
+    ("SUM", 0x4701),
+    ("FAL", 0x4702),
+    ("AUT", 0x4703),
+    ("WIN", 0x4704),
+    // Commands
+    ("CCD", 0x4800),
+    ("DRW", 0x4801),
+    ("FRM", 0x4802),
+    ("GOF", 0x4803),
+    ("HLO", 0x4804),
+    ("HST", 0x4805),
+    ("HUH", 0x4806),
+    ("IAM", 0x4807),
+    ("LOD", 0x4808),
+    ("MAP", 0x4809),
+    ("MDF", 0x480A),
+    ("MIS", 0x480B),
+    ("NME", 0x480C),
+    ("NOT", 0x480D),
+    ("NOW", 0x480E),
+    ("OBS", 0x480F),
+    ("OFF", 0x4810),
+    ("ORD", 0x4811),
+    ("OUT", 0x4812),
+    ("PRN", 0x4813),
+    ("REJ", 0x4814),
+    ("SCO", 0x4815),
+    ("SLO", 0x4816),
+    ("SND", 0x4817),
+    ("SUB", 0x4818),
+    ("SVE", 0x4819),
+    ("THX", 0x481A),
+    ("TME", 0x481B),
+    ("YES", 0x481C),
+    ("ADM", 0x481D),
+    ("SMR", 0x481E),
+    // Parameters
+    ("AOA", 0x4900),
+    ("BTL", 0x4901),
+    ("ERR", 0x4902),
+    ("LVL", 0x4903),
+    ("MRT", 0x4904),
+    ("MTL", 0x4905),
+    ("NPB", 0x4906),
+    ("NPR", 0x4907),
+    ("PDA", 0x4908),
+    ("PTL", 0x4909),
+    ("RTL", 0x490A),
+    ("UNO", 0x490B),
+    ("DSD", 0x490D),
+    // Press
+    ("ALY", 0x4A00),
+    ("AND", 0x4A01),
+    ("BWX", 0x4A02),
+    ("DMZ", 0x4A03),
+    ("ELS", 0x4A04),
+    ("EXP", 0x4A05),
+    ("FWD", 0x4A06),
+    ("FCT", 0x4A07),
+    ("FOR", 0x4A08),
+    ("HOW", 0x4A09),
+    ("IDK", 0x4A0A),
+    ("IFF", 0x4A0B),
+    ("INS", 0x4A0C),
+    ("OCC", 0x4A0E),
+    ("ORR", 0x4A0F),
+    ("PCE", 0x4A10),
+    ("POB", 0x4A11),
+    ("PRP", 0x4A13),
+    ("QRY", 0x4A14),
+    ("SCD", 0x4A15),
+    ("SRY", 0x4A16),
+    ("SUG", 0x4A17),
+    ("THK", 0x4A18),
+    ("THN", 0x4A19),
+    ("TRY", 0x4A1A),
+    ("VSS", 0x4A1C),
+    ("WHT", 0x4A1D),
+    ("WHY", 0x4A1E),
+    ("XDO", 0x4A1F),
+    ("XOY", 0x4A20),
+    ("YDO", 0x4A21),
+    ("CHO", 0x4A22),
+    ("BCC", 0x4A23),
+    ("UNT", 0x4A24),
+];
+
+/// Standard-map provinces by token category, each alphabetical. Province
+/// tokens are numbered consecutively across the categories.
+const PROVINCE_CATEGORIES: &[(u8, &[&str])] = &[
+    // Inland, not a supply center
+    (0x50, &["BOH", "BUR", "GAL", "RUH", "SIL", "TYR", "UKR"]),
+    // Inland supply centers
+    (0x51, &["BUD", "MOS", "MUN", "PAR", "SER", "VIE", "WAR"]),
+    // Seas
+    (
+        0x52,
+        &[
+            "ADR", "AEG", "BAL", "BAR", "BLA", "EAS", "ECH", "GOB", "GOL", "HEL", "ION", "IRI",
+            "MAO", "NAO", "NTH", "NWG", "SKA", "TYS", "WES",
+        ],
+    ),
+    // Coastal, not a supply center
+    (
+        0x54,
+        &[
+            "ALB", "APU", "ARM", "CLY", "FIN", "GAS", "LVN", "NAF", "PIC", "PIE", "PRU", "SYR",
+            "TUS", "WAL", "YOR",
+        ],
+    ),
+    // Coastal supply centers
+    (
+        0x55,
+        &[
+            "ANK", "BEL", "BER", "BRE", "CON", "DEN", "EDI", "GRE", "HOL", "KIE", "LON", "LVP",
+            "MAR", "NAP", "NWY", "POR", "ROM", "RUM", "SEV", "SMY", "SWE", "TRI", "TUN", "VEN",
+        ],
+    ),
+    // Supply centers with two coasts
+    (0x57, &["BUL", "SPA", "STP"]),
+];
+
+/// Two-way map between token codes and their three-letter names.
+#[derive(Debug, Clone)]
+pub struct TokenTable {
+    names: HashMap<u16, String>,
+    codes: HashMap<String, u16>,
+}
+
+impl Default for TokenTable {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl TokenTable {
+    /// The tokens of the standard map.
+    pub fn standard() -> Self {
+        let mut table = TokenTable {
+            names: HashMap::new(),
+            codes: HashMap::new(),
+        };
+        for &(name, code) in NAMED_TOKENS {
+            table.insert(name, code);
+        }
+        let mut index = 0u16;
+        for &(category, names) in PROVINCE_CATEGORIES {
+            for name in names {
+                table.insert(name, ((category as u16) << 8) | index);
+                index += 1;
+            }
+        }
+        table
+    }
+
+    fn insert(&mut self, name: &str, code: u16) {
+        if let Some(old) = self.names.insert(code, name.to_string()) {
+            self.codes.remove(&old);
+        }
+        self.codes.insert(name.to_string(), code);
+    }
+
+    /// Adds the tokens named by a representation message: 6-byte entries
+    /// of a token code and a NUL-terminated three-letter name.
+    pub fn apply_representation(&mut self, body: &[u8]) -> Result<(), DaideError> {
+        if !body.len().is_multiple_of(6) {
+            return Err(DaideError::Malformed(
+                "representation entries of 6 bytes".to_string(),
+            ));
+        }
+        for entry in body.chunks_exact(6) {
+            let code = u16::from_be_bytes([entry[0], entry[1]]);
+            let name = String::from_utf8_lossy(&entry[2..5]).to_ascii_uppercase();
+            self.insert(&name, code);
+        }
+        Ok(())
+    }
+
+    /// Decodes a diplomacy message body to text.
+    pub fn decode(&self, body: &[u8]) -> Result<String, DaideError> {
+        if !body.len().is_multiple_of(2) {
+            return Err(DaideError::Malformed("whole 16-bit tokens".to_string()));
+        }
+        let mut words: Vec<String> = Vec::new();
+        let mut text: Option<String> = None;
+        for pair in body.chunks_exact(2) {
+            let code = u16::from_be_bytes([pair[0], pair[1]]);
+            if code & 0xFF00 == TEXT {
+                text.get_or_insert_with(String::new)
+                    .push(char::from(code as u8));
+                continue;
+            }
+            if let Some(t) = text.take() {
+                words.push(format!("'{}'", t));
+            }
+            let word = match code {
+                BRA => "(".to_string(),
+                KET => ")".to_string(),
+                c if c < 0x4000 => {
+                    // 14-bit two's complement.
+                    let value = if c & 0x2000 != 0 {
+                        c as i32 - 0x4000
+                    } else {
+                        c as i32
+                    };
+                    value.to_string()
+                }
+                c => self
+                    .names
+                    .get(&c)
+                    .cloned()
+                    .ok_or_else(|| DaideError::UnknownToken(format!("0x{:04X}", c)))?,
+            };
+            words.push(word);
+        }
+        if let Some(t) = text {
+            words.push(format!("'{}'", t));
+        }
+        Ok(words.join(" "))
+    }
+
+    /// Encodes text (as written by [`TokenTable::decode`]) to a diplomacy
+    /// message body. Brackets need not be separated by spaces.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, DaideError> {
+        let mut codes: Vec<u16> = Vec::new();
+        let mut chars = text.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                '(' => {
+                    chars.next();
+                    codes.push(BRA);
+                }
+                ')' => {
+                    chars.next();
+                    codes.push(KET);
+                }
+                '\'' => {
+                    chars.next();
+                    loop {
+                        match chars.next() {
+                            Some('\'') => break,
+                            Some(c) if c.is_ascii() => codes.push(TEXT | c as u16),
+                            Some(c) => return Err(DaideError::UnknownToken(c.to_string())),
+                            None => return Err(DaideError::Malformed("closing quote".to_string())),
+                        }
+                    }
+                }
+                _ => {
+                    let mut word = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() || c == '(' || c == ')' || c == '\'' {
+                            break;
+                        }
+                        word.push(c);
+                        chars.next();
+                    }
+                    codes.push(self.word_code(&word)?);
+                }
+            }
+        }
+        Ok(codes.iter().flat_map(|c| c.to_be_bytes()).collect())
+    }
+
+    fn word_code(&self, word: &str) -> Result<u16, DaideError> {
+        if let Ok(n) = word.parse::<i32>() {
+            if !(-0x2000..0x2000).contains(&n) {
+                return Err(DaideError::UnknownToken(word.to_string()));
+            }
+            return Ok((n & 0x3FFF) as u16);
+        }
+        self.codes
+            .get(&word.to_ascii_uppercase())
+            .copied()
+            .ok_or_else(|| DaideError::UnknownToken(word.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_tokens_have_daide_codes() {
+        let table = TokenTable::standard();
+        let code = |w: &str| table.word_code(w).unwrap();
+        assert_eq!(code("BOH"), 0x5000);
+        assert_eq!(code("ADR"), 0x520E);
+        assert_eq!(code("YOR"), 0x542F);
+        assert_eq!(code("ANK"), 0x5530);
+        assert_eq!(code("STP"), 0x574A);
+        assert_eq!(code("SUB"), 0x4818);
+        assert_eq!(table.codes.len(), NAMED_TOKENS.len() + 75);
+    }
+
+    #[test]
+    fn text_round_trips_through_tokens() {
+        let table = TokenTable::standard();
+        let text = "NOW ( SPR 1901 ) ( RUS FLT ( STP SCS ) ) NME ( 'realpolitik' ) ( '0.1' ) -3";
+        let body = table.encode(text).unwrap();
+        assert_eq!(&body[..4], &[0x48, 0x0E, 0x40, 0x00]);
+        assert_eq!(table.decode(&body).unwrap(), text);
+        assert_eq!(
+            table.encode("SUB((AUS AMY VIE) HLD)").unwrap(),
+            table.encode("SUB ( ( AUS AMY VIE ) HLD )").unwrap()
+        );
+        assert_eq!(
+            table.encode("XYZ"),
+            Err(DaideError::UnknownToken("XYZ".to_string()))
+        );
+    }
+
+    #[test]
+    fn representation_renames_tokens() {
+        let mut table = TokenTable::standard();
+        let mut rm = 0x5000u16.to_be_bytes().to_vec();
+        rm.extend_from_slice(b"BOX\0");
+        table.apply_representation(&rm).unwrap();
+        assert_eq!(table.decode(&[0x50, 0x00]).unwrap(), "BOX");
+        assert!(table.encode("BOH").is_err());
+        assert!(table.apply_representation(&[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn messages_are_framed_with_big_endian_lengths() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, MessageKind::Initial, &initial_body()).unwrap();
+        assert_eq!(buf, vec![0, 0, 0, 4, 0, 1, 0xDA, 0x10]);
+        let msg = read_message(&mut buf.as_slice()).unwrap();
+        assert_eq!(msg.kind, MessageKind::Initial);
+        assert_eq!(msg.body, initial_body());
+    }
+}
@@ -6,12 +6,20 @@
 //!
 //! Format: `<phase_info>/<units>/<supply_centers>/<dislodged>`
 //!
-//! See DUI_PROTOCOL.md section 2 for the full specification.
+//! See DUI_PROTOCOL.md section 2 for the full specification. [`diff`]
+//! compares two positions.
 
-use crate::board::province::{Coast, Power, Province, ALL_POWERS, ALL_PROVINCES};
+use std::sync::Arc;
+
+use crate::board::map::GameMap;
+use crate::board::province::{Coast, Power, Province, ALL_POWERS, MAX_PROVINCES};
 use crate::board::state::{BoardState, DislodgedUnit, Phase, Season};
 use crate::board::unit::UnitType;
 
+pub mod diff;
+
+pub use diff::{diff, DfenDiff, Dislodgement, ScChange, UnitChange, UnitMove};
+
 /// Errors that can occur during DFEN parsing.
 #[derive(Debug, thiserror::Error)]
 pub enum DfenError {
@@ -42,6 +50,9 @@ pub enum DfenError {
     #[error("duplicate unit at province '{0}'")]
     DuplicateUnit(String),
 
+    #[error("'{0}' is not a supply center on this map")]
+    NotSupplyCenter(String),
+
     #[error("duplicate SC entry for province '{0}'")]
     DuplicateSc(String),
 
@@ -76,9 +87,9 @@ fn parse_power(c: char) -> Result<Power, DfenError> {
     Power::from_dui_char(c).ok_or(DfenError::InvalidPower(c))
 }
 
-/// Parses a location string like "vie", "stp.sc", "bul.ec".
-/// Returns (Province, Coast).
-fn parse_location(s: &str) -> Result<(Province, Coast), DfenError> {
+/// Parses a location string like "vie", "stp.sc", "bul.ec" naming a
+/// province of `map`. Returns (Province, Coast).
+fn parse_location(map: &GameMap, s: &str) -> Result<(Province, Coast), DfenError> {
     let (prov_str, coast) = if let Some(dot_pos) = s.find('.') {
         let prov_part = &s[..dot_pos];
         let coast_part = &s[dot_pos + 1..];
@@ -92,14 +103,15 @@ fn parse_location(s: &str) -> Result<(Province, Coast), DfenError> {
         (s, Coast::None)
     };
 
-    let province = Province::from_abbr(prov_str)
+    let province = map
+        .province(prov_str)
         .ok_or_else(|| DfenError::UnknownProvince(prov_str.to_string()))?;
 
     Ok((province, coast))
 }
 
 /// Parses the phase info section (e.g., "1901sm").
-fn parse_phase_info(s: &str) -> Result<(u16, Season, Phase), DfenError> {
+pub fn parse_phase_info(s: &str) -> Result<(u16, Season, Phase), DfenError> {
     if s.len() < 3 {
         return Err(DfenError::PhaseInfoTooShort(s.to_string()));
     }
@@ -139,17 +151,16 @@ fn parse_units(s: &str, state: &mut BoardState) -> Result<(), DfenError> {
         let power = parse_power(power_char)?;
         let unit_type =
             UnitType::from_dui_char(unit_char).ok_or(DfenError::InvalidUnitType(unit_char))?;
-        let (province, coast) = parse_location(&location_str)?;
+        let (province, coast) = parse_location(state.map(), &location_str)?;
 
-        let idx = province as usize;
+        let idx = province.index();
         if state.units[idx].is_some() {
-            return Err(DfenError::DuplicateUnit(province.abbr().to_string()));
+            return Err(DfenError::DuplicateUnit(
+                state.map().abbr(province).to_string(),
+            ));
         }
 
-        state.units[idx] = Some((power, unit_type));
-        if coast != Coast::None {
-            state.fleet_coast[idx] = Some(coast);
-        }
+        state.place_unit(province, power, unit_type, coast);
     }
 
     Ok(())
@@ -157,6 +168,11 @@ fn parse_units(s: &str, state: &mut BoardState) -> Result<(), DfenError> {
 
 /// Parses the supply centers section (comma-separated entries, all 34 listed).
 fn parse_supply_centers(s: &str, state: &mut BoardState) -> Result<(), DfenError> {
+    // Only a redacted view can have no visible supply centers.
+    if s == "-" {
+        return Ok(());
+    }
+
     for entry in s.split(',') {
         if entry.len() < 4 {
             return Err(DfenError::InvalidScEntry(entry.to_string()));
@@ -167,16 +183,21 @@ fn parse_supply_centers(s: &str, state: &mut BoardState) -> Result<(), DfenError
         let prov_str: String = chars.collect();
 
         let owner = parse_power_or_neutral(power_char)?;
-        let province = Province::from_abbr(&prov_str)
+        let province = state
+            .map()
+            .province(&prov_str)
             .ok_or_else(|| DfenError::UnknownProvince(prov_str.to_string()))?;
+        if !state.map().is_supply_center(province) {
+            return Err(DfenError::NotSupplyCenter(prov_str));
+        }
 
-        let idx = province as usize;
+        let idx = province.index();
         if state.sc_owner[idx].is_some() {
-            return Err(DfenError::DuplicateSc(province.abbr().to_string()));
+            return Err(DfenError::DuplicateSc(prov_str));
         }
 
         // Only set owner for non-neutral; None means neutral SC
-        state.sc_owner[idx] = owner;
+        state.set_sc_owner(province, owner);
     }
 
     Ok(())
@@ -209,13 +230,17 @@ fn parse_dislodged(s: &str, state: &mut BoardState) -> Result<(), DfenError> {
         let power = parse_power(power_char)?;
         let unit_type =
             UnitType::from_dui_char(unit_char).ok_or(DfenError::InvalidUnitType(unit_char))?;
-        let (province, coast) = parse_location(&location_str)?;
-        let attacker_from = Province::from_abbr(attacker_prov_str)
+        let (province, coast) = parse_location(state.map(), &location_str)?;
+        let attacker_from = state
+            .map()
+            .province(attacker_prov_str)
             .ok_or_else(|| DfenError::UnknownProvince(attacker_prov_str.to_string()))?;
 
-        let idx = province as usize;
+        let idx = province.index();
         if state.dislodged[idx].is_some() {
-            return Err(DfenError::DuplicateDislodged(province.abbr().to_string()));
+            return Err(DfenError::DuplicateDislodged(
+                state.map().abbr(province).to_string(),
+            ));
         }
 
         state.dislodged[idx] = Some(DislodgedUnit {
@@ -229,17 +254,23 @@ fn parse_dislodged(s: &str, state: &mut BoardState) -> Result<(), DfenError> {
     Ok(())
 }
 
-/// Parses a DFEN string into a BoardState.
+/// Parses a DFEN string into a BoardState on the standard map.
 ///
 /// Format: `<phase_info>/<units>/<supply_centers>/<dislodged>`
 pub fn parse_dfen(s: &str) -> Result<BoardState, DfenError> {
+    parse_dfen_on(GameMap::standard(), s)
+}
+
+/// Parses a DFEN position played on `map`. Locations must name provinces
+/// of that map, and supply center entries its supply centers.
+pub fn parse_dfen_on(map: Arc<GameMap>, s: &str) -> Result<BoardState, DfenError> {
     let sections: Vec<&str> = s.split('/').collect();
     if sections.len() != 4 {
         return Err(DfenError::WrongSectionCount(sections.len()));
     }
 
     let (year, season, phase) = parse_phase_info(sections[0])?;
-    let mut state = BoardState::empty(year, season, phase);
+    let mut state = BoardState::empty_on(map, year, season, phase);
 
     parse_units(sections[1], &mut state)?;
     parse_supply_centers(sections[2], &mut state)?;
@@ -249,8 +280,8 @@ pub fn parse_dfen(s: &str) -> Result<BoardState, DfenError> {
 }
 
 /// Encodes a location (province + optional coast) for DFEN output.
-fn encode_location(province: Province, coast: Coast) -> String {
-    let abbr = province.abbr();
+fn encode_location(map: &GameMap, province: Province, coast: Coast) -> String {
+    let abbr = map.abbr(province);
     if coast != Coast::None {
         format!("{}.{}", abbr, coast.abbr())
     } else {
@@ -261,10 +292,45 @@ fn encode_location(province: Province, coast: Coast) -> String {
 /// Encodes a BoardState into a canonical DFEN string.
 ///
 /// The output is deterministic: units and dislodged entries are grouped by power
-/// (A, E, F, G, I, R, T) and sorted by province enum index within each group.
+/// (A, E, F, G, I, R, T) and sorted by province index within each group.
 /// Supply centers follow the same power ordering plus neutral (N) at the end,
-/// sorted alphabetically by province abbreviation within each group.
+/// in the same province order within each group. On the standard map that
+/// order is alphabetical by abbreviation.
 pub fn encode_dfen(state: &BoardState) -> String {
+    encode_visible(state, &[true; MAX_PROVINCES])
+}
+
+/// Provinces a power can see under fog-of-war rules: those holding its
+/// units (including dislodged ones) or its supply centers, plus every
+/// province adjacent to one of those.
+pub fn fog_visibility(state: &BoardState, viewer: Power) -> [bool; MAX_PROVINCES] {
+    let mut visible = [false; MAX_PROVINCES];
+    for &prov in state.map().provinces() {
+        let idx = prov.index();
+        let has_unit = matches!(state.units[idx], Some((p, _)) if p == viewer)
+            || matches!(state.dislodged[idx], Some(d) if d.power == viewer);
+        if has_unit || state.sc_owner[idx] == Some(viewer) {
+            visible[idx] = true;
+            for adj in state.map().adj_from(prov) {
+                visible[adj.to.index()] = true;
+            }
+        }
+    }
+    visible
+}
+
+/// Encodes the limited-information DFEN `viewer` sees in fog variants.
+///
+/// Units, dislodged units, and supply centers outside
+/// [`fog_visibility`] are omitted, so a hidden center parses as unowned
+/// and a hidden province as empty. The SC section is `-` when nothing is
+/// visible.
+pub fn encode_dfen_redacted(state: &BoardState, viewer: Power) -> String {
+    encode_visible(state, &fog_visibility(state, viewer))
+}
+
+/// Encodes the parts of a BoardState in `visible` provinces.
+fn encode_visible(state: &BoardState, visible: &[bool; MAX_PROVINCES]) -> String {
     let mut result = String::with_capacity(512);
 
     // Phase info
@@ -278,19 +344,19 @@ pub fn encode_dfen(state: &BoardState) -> String {
     result.push('/');
 
     // Units section
-    let unit_str = encode_units(state);
+    let unit_str = encode_units(state, visible);
     result.push_str(&unit_str);
 
     result.push('/');
 
     // Supply centers section
-    let sc_str = encode_supply_centers(state);
+    let sc_str = encode_supply_centers(state, visible);
     result.push_str(&sc_str);
 
     result.push('/');
 
     // Dislodged section
-    let dis_str = encode_dislodged(state);
+    let dis_str = encode_dislodged(state, visible);
     result.push_str(&dis_str);
 
     result
@@ -299,19 +365,21 @@ pub fn encode_dfen(state: &BoardState) -> String {
 /// Encodes the units section of the DFEN string.
 ///
 /// Units are grouped by power in standard order (A, E, F, G, I, R, T),
-/// and within each power, sorted by province enum index (which is alphabetical
-/// by abbreviation).
-fn encode_units(state: &BoardState) -> String {
+/// and within each power, sorted by province index (which is alphabetical
+/// by abbreviation on the standard map).
+fn encode_units(state: &BoardState, visible: &[bool; MAX_PROVINCES]) -> String {
     let mut entries: Vec<String> = Vec::new();
 
     for power in ALL_POWERS.iter() {
-        // ALL_PROVINCES is already in alphabetical/index order
-        for &prov in ALL_PROVINCES.iter() {
-            let idx = prov as usize;
+        for &prov in state.map().provinces() {
+            let idx = prov.index();
+            if !visible[idx] {
+                continue;
+            }
             if let Some((p, ut)) = state.units[idx] {
                 if p == *power {
                     let coast = state.fleet_coast[idx].unwrap_or(Coast::None);
-                    let loc = encode_location(prov, coast);
+                    let loc = encode_location(state.map(), prov, coast);
                     entries.push(format!("{}{}{}", power.dui_char(), ut.dui_char(), loc));
                 }
             }
@@ -328,18 +396,17 @@ fn encode_units(state: &BoardState) -> String {
 /// Encodes the supply centers section of the DFEN string.
 ///
 /// SCs are grouped by power in standard order (A, E, F, G, I, R, T, N),
-/// and within each group sorted alphabetically by province abbreviation.
-fn encode_supply_centers(state: &BoardState) -> String {
+/// and within each group sorted by province index.
+fn encode_supply_centers(state: &BoardState, visible: &[bool; MAX_PROVINCES]) -> String {
     let mut entries: Vec<String> = Vec::new();
 
     // Owned SCs grouped by power in standard order
     for power in ALL_POWERS.iter() {
-        // ALL_PROVINCES is already alphabetical
-        for &prov in ALL_PROVINCES.iter() {
-            if prov.is_supply_center() {
-                if let Some(owner) = state.sc_owner[prov as usize] {
+        for &prov in state.map().provinces() {
+            if state.map().is_supply_center(prov) && visible[prov.index()] {
+                if let Some(owner) = state.sc_owner[prov.index()] {
                     if owner == *power {
-                        entries.push(format!("{}{}", power.dui_char(), prov.abbr()));
+                        entries.push(format!("{}{}", power.dui_char(), state.map().abbr(prov)));
                     }
                 }
             }
@@ -347,33 +414,41 @@ fn encode_supply_centers(state: &BoardState) -> String {
     }
 
     // Neutral SCs (owner is None and province is a supply center)
-    for &prov in ALL_PROVINCES.iter() {
-        if prov.is_supply_center() && state.sc_owner[prov as usize].is_none() {
-            entries.push(format!("N{}", prov.abbr()));
+    for &prov in state.map().provinces() {
+        let idx = prov.index();
+        if state.map().is_supply_center(prov) && visible[idx] && state.sc_owner[idx].is_none() {
+            entries.push(format!("N{}", state.map().abbr(prov)));
         }
     }
 
-    entries.join(",")
+    if entries.is_empty() {
+        "-".to_string()
+    } else {
+        entries.join(",")
+    }
 }
 
 /// Encodes the dislodged units section of the DFEN string.
 ///
 /// Dislodged units are grouped by power in standard order (A, E, F, G, I, R, T),
-/// and within each power, sorted by province enum index.
-fn encode_dislodged(state: &BoardState) -> String {
+/// and within each power, sorted by province index.
+fn encode_dislodged(state: &BoardState, visible: &[bool; MAX_PROVINCES]) -> String {
     let mut entries: Vec<String> = Vec::new();
 
     for power in ALL_POWERS.iter() {
-        for &prov in ALL_PROVINCES.iter() {
-            if let Some(ref d) = state.dislodged[prov as usize] {
+        for &prov in state.map().provinces() {
+            if !visible[prov.index()] {
+                continue;
+            }
+            if let Some(ref d) = state.dislodged[prov.index()] {
                 if d.power == *power {
-                    let loc = encode_location(prov, d.coast);
+                    let loc = encode_location(state.map(), prov, d.coast);
                     entries.push(format!(
                         "{}{}{}<{}",
                         d.power.dui_char(),
                         d.unit_type.dui_char(),
                         loc,
-                        d.attacker_from.abbr()
+                        state.map().abbr(d.attacker_from)
                     ));
                 }
             }
@@ -390,7 +465,7 @@ fn encode_dislodged(state: &BoardState) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::province::PROVINCE_COUNT;
+    use crate::board::province::{ALL_PROVINCES, PROVINCE_COUNT};
 
     /// The initial position DFEN from the spec (section 7.1).
     const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
@@ -411,50 +486,47 @@ mod tests {
 
         // Austrian units
         assert_eq!(
-            state.units[Province::Vie as usize],
+            state.units[Province::Vie.index()],
             Some((Power::Austria, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Bud as usize],
+            state.units[Province::Bud.index()],
             Some((Power::Austria, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Tri as usize],
+            state.units[Province::Tri.index()],
             Some((Power::Austria, UnitType::Fleet))
         );
 
         // English units
         assert_eq!(
-            state.units[Province::Lon as usize],
+            state.units[Province::Lon.index()],
             Some((Power::England, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Edi as usize],
+            state.units[Province::Edi.index()],
             Some((Power::England, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Lvp as usize],
+            state.units[Province::Lvp.index()],
             Some((Power::England, UnitType::Army))
         );
 
         // Russian fleet on south coast of StP
         assert_eq!(
-            state.units[Province::Stp as usize],
+            state.units[Province::Stp.index()],
             Some((Power::Russia, UnitType::Fleet))
         );
-        assert_eq!(
-            state.fleet_coast[Province::Stp as usize],
-            Some(Coast::South)
-        );
+        assert_eq!(state.fleet_coast[Province::Stp.index()], Some(Coast::South));
 
         // SC ownership
-        assert_eq!(state.sc_owner[Province::Vie as usize], Some(Power::Austria));
-        assert_eq!(state.sc_owner[Province::Lon as usize], Some(Power::England));
-        assert_eq!(state.sc_owner[Province::Mos as usize], Some(Power::Russia));
+        assert_eq!(state.sc_owner[Province::Vie.index()], Some(Power::Austria));
+        assert_eq!(state.sc_owner[Province::Lon.index()], Some(Power::England));
+        assert_eq!(state.sc_owner[Province::Mos.index()], Some(Power::Russia));
 
         // Neutral SCs
-        assert_eq!(state.sc_owner[Province::Bel as usize], None);
-        assert_eq!(state.sc_owner[Province::Bul as usize], None);
+        assert_eq!(state.sc_owner[Province::Bel.index()], None);
+        assert_eq!(state.sc_owner[Province::Bul.index()], None);
 
         // No dislodged units
         assert!(state.dislodged.iter().all(|d| d.is_none()));
@@ -474,19 +546,19 @@ mod tests {
 
         // Austria expanded: A bud, A rum, F gre, A vie
         assert_eq!(
-            state.units[Province::Bud as usize],
+            state.units[Province::Bud.index()],
             Some((Power::Austria, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Rum as usize],
+            state.units[Province::Rum.index()],
             Some((Power::Austria, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Gre as usize],
+            state.units[Province::Gre.index()],
             Some((Power::Austria, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Vie as usize],
+            state.units[Province::Vie.index()],
             Some((Power::Austria, UnitType::Army))
         );
 
@@ -528,18 +600,45 @@ mod tests {
         assert_eq!(dislodged_count, 2);
 
         // Austrian army at Serbia dislodged from Bulgaria
-        let d_ser = state.dislodged[Province::Ser as usize].unwrap();
+        let d_ser = state.dislodged[Province::Ser.index()].unwrap();
         assert_eq!(d_ser.power, Power::Austria);
         assert_eq!(d_ser.unit_type, UnitType::Army);
         assert_eq!(d_ser.attacker_from, Province::Bul);
 
         // Russian fleet at Sevastopol dislodged from Black Sea
-        let d_sev = state.dislodged[Province::Sev as usize].unwrap();
+        let d_sev = state.dislodged[Province::Sev.index()].unwrap();
         assert_eq!(d_sev.power, Power::Russia);
         assert_eq!(d_sev.unit_type, UnitType::Fleet);
         assert_eq!(d_sev.attacker_from, Province::Bla);
     }
 
+    #[test]
+    fn resolved_retreat_position_round_trips() {
+        use crate::board::order::Order;
+        use crate::protocol::dson::parse_order;
+        use crate::resolve::{advance_state, apply_resolution, Resolver};
+
+        // A supported attack dislodges the fleet in Sevastopol; the
+        // position after adjudication carries it and its attacker.
+        let mut state = parse_dfen("1901fm/Rfsev,Tfbla,Taarm/Rsev,Tank/-").unwrap();
+        let orders: Vec<(Order, Power)> = [
+            ("F bla - sev", Power::Turkey),
+            ("A arm S F bla - sev", Power::Turkey),
+            ("F sev H", Power::Russia),
+        ]
+        .iter()
+        .map(|&(o, p)| (parse_order(o).unwrap(), p))
+        .collect();
+        let (results, dislodged) = Resolver::new(64).resolve(&orders, &state);
+        apply_resolution(&mut state, &results, &dislodged);
+        advance_state(&mut state, true);
+        assert_eq!(state.phase, Phase::Retreat);
+
+        let encoded = encode_dfen(&state);
+        assert!(encoded.ends_with("/Rfsev<bla"), "{}", encoded);
+        assert_eq!(parse_dfen(&encoded).unwrap(), state);
+    }
+
     #[test]
     fn roundtrip_canonical_form() {
         // Parse from spec, encode to canonical form, then verify re-parsing
@@ -644,16 +743,13 @@ mod tests {
     fn all_coast_types_covered() {
         // South coast: stp.sc in initial position
         let state = parse_dfen(INITIAL_DFEN).expect("failed to parse");
-        assert_eq!(
-            state.fleet_coast[Province::Stp as usize],
-            Some(Coast::South)
-        );
+        assert_eq!(state.fleet_coast[Province::Stp.index()], Some(Coast::South));
 
         // North coast
         let nc_dfen = "1901sm/Rfstp.nc/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
         let state_nc = parse_dfen(nc_dfen).expect("failed to parse NC");
         assert_eq!(
-            state_nc.fleet_coast[Province::Stp as usize],
+            state_nc.fleet_coast[Province::Stp.index()],
             Some(Coast::North)
         );
 
@@ -661,7 +757,7 @@ mod tests {
         let ec_dfen = "1901sm/Tfbul.ec/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
         let state_ec = parse_dfen(ec_dfen).expect("failed to parse EC");
         assert_eq!(
-            state_ec.fleet_coast[Province::Bul as usize],
+            state_ec.fleet_coast[Province::Bul.index()],
             Some(Coast::East)
         );
     }
@@ -678,7 +774,7 @@ mod tests {
     fn empty_dislodged_dash() {
         let state = parse_dfen(INITIAL_DFEN).expect("failed to parse");
         let dislodged_count = state.dislodged.iter().filter(|d| d.is_none()).count();
-        assert_eq!(dislodged_count, PROVINCE_COUNT);
+        assert_eq!(dislodged_count, MAX_PROVINCES);
     }
 
     #[test]
@@ -793,7 +889,7 @@ mod tests {
 
         let neutral_count = ALL_PROVINCES
             .iter()
-            .filter(|p| p.is_supply_center() && state.sc_owner[**p as usize].is_none())
+            .filter(|p| state.map().is_supply_center(**p) && state.sc_owner[p.index()].is_none())
             .count();
         assert_eq!(neutral_count, 12);
     }
@@ -811,7 +907,7 @@ mod tests {
         // A fleet at stp.sc dislodged from bot
         let dfen = "1902fr/Ramos/Rmos,Rsev,Rstp,Rwar,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/Rfstp.sc<bot";
         let state = parse_dfen(dfen).expect("failed to parse");
-        let d = state.dislodged[Province::Stp as usize].unwrap();
+        let d = state.dislodged[Province::Stp.index()].unwrap();
         assert_eq!(d.power, Power::Russia);
         assert_eq!(d.unit_type, UnitType::Fleet);
         assert_eq!(d.coast, Coast::South);
@@ -821,8 +917,8 @@ mod tests {
         let encoded = encode_dfen(&state);
         let reparsed = parse_dfen(&encoded).expect("failed to reparse");
         assert_eq!(
-            reparsed.dislodged[Province::Stp as usize],
-            state.dislodged[Province::Stp as usize]
+            reparsed.dislodged[Province::Stp.index()],
+            state.dislodged[Province::Stp.index()]
         );
     }
 
@@ -839,8 +935,8 @@ mod tests {
         // Only a few SCs listed (non-standard but parseable)
         let dfen = "1901sm/-/Avie,Nbel/-";
         let state = parse_dfen(dfen).expect("failed to parse");
-        assert_eq!(state.sc_owner[Province::Vie as usize], Some(Power::Austria));
-        assert_eq!(state.sc_owner[Province::Bel as usize], None); // N = neutral = None
+        assert_eq!(state.sc_owner[Province::Vie.index()], Some(Power::Austria));
+        assert_eq!(state.sc_owner[Province::Bel.index()], None); // N = neutral = None
     }
 
     #[test]
@@ -853,15 +949,15 @@ mod tests {
     fn turkey_units_parse_correctly() {
         let state = parse_dfen(INITIAL_DFEN).expect("failed to parse");
         assert_eq!(
-            state.units[Province::Ank as usize],
+            state.units[Province::Ank.index()],
             Some((Power::Turkey, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Con as usize],
+            state.units[Province::Con.index()],
             Some((Power::Turkey, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Smy as usize],
+            state.units[Province::Smy.index()],
             Some((Power::Turkey, UnitType::Army))
         );
     }
@@ -870,15 +966,15 @@ mod tests {
     fn france_units_parse_correctly() {
         let state = parse_dfen(INITIAL_DFEN).expect("failed to parse");
         assert_eq!(
-            state.units[Province::Bre as usize],
+            state.units[Province::Bre.index()],
             Some((Power::France, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Par as usize],
+            state.units[Province::Par.index()],
             Some((Power::France, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Mar as usize],
+            state.units[Province::Mar.index()],
             Some((Power::France, UnitType::Army))
         );
     }
@@ -887,15 +983,15 @@ mod tests {
     fn germany_units_parse_correctly() {
         let state = parse_dfen(INITIAL_DFEN).expect("failed to parse");
         assert_eq!(
-            state.units[Province::Kie as usize],
+            state.units[Province::Kie.index()],
             Some((Power::Germany, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Ber as usize],
+            state.units[Province::Ber.index()],
             Some((Power::Germany, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Mun as usize],
+            state.units[Province::Mun.index()],
             Some((Power::Germany, UnitType::Army))
         );
     }
@@ -904,15 +1000,15 @@ mod tests {
     fn italy_units_parse_correctly() {
         let state = parse_dfen(INITIAL_DFEN).expect("failed to parse");
         assert_eq!(
-            state.units[Province::Nap as usize],
+            state.units[Province::Nap.index()],
             Some((Power::Italy, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Rom as usize],
+            state.units[Province::Rom.index()],
             Some((Power::Italy, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Ven as usize],
+            state.units[Province::Ven.index()],
             Some((Power::Italy, UnitType::Army))
         );
     }
@@ -921,19 +1017,19 @@ mod tests {
     fn russia_units_parse_correctly() {
         let state = parse_dfen(INITIAL_DFEN).expect("failed to parse");
         assert_eq!(
-            state.units[Province::Stp as usize],
+            state.units[Province::Stp.index()],
             Some((Power::Russia, UnitType::Fleet))
         );
         assert_eq!(
-            state.units[Province::Mos as usize],
+            state.units[Province::Mos.index()],
             Some((Power::Russia, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::War as usize],
+            state.units[Province::War.index()],
             Some((Power::Russia, UnitType::Army))
         );
         assert_eq!(
-            state.units[Province::Sev as usize],
+            state.units[Province::Sev.index()],
             Some((Power::Russia, UnitType::Fleet))
         );
     }
@@ -988,6 +1084,12 @@ mod tests {
         assert!(matches!(err, DfenError::InvalidUnitEntry(_)));
     }
 
+    #[test]
+    fn error_sc_entry_for_non_center() {
+        let err = parse_dfen("1901sm/-/Atyr/-").unwrap_err();
+        assert!(matches!(err, DfenError::NotSupplyCenter(p) if p == "tyr"));
+    }
+
     #[test]
     fn error_duplicate_sc() {
         let err = parse_dfen("1901sm/-/Avie,Gvie/-").unwrap_err();
@@ -1041,13 +1143,55 @@ mod tests {
     fn all_powers_can_own_scs() {
         let dfen = "1901sm/-/Avie,Elon,Fpar,Gber,Irom,Rmos,Tank,Nbel/-";
         let state = parse_dfen(dfen).expect("failed to parse");
-        assert_eq!(state.sc_owner[Province::Vie as usize], Some(Power::Austria));
-        assert_eq!(state.sc_owner[Province::Lon as usize], Some(Power::England));
-        assert_eq!(state.sc_owner[Province::Par as usize], Some(Power::France));
-        assert_eq!(state.sc_owner[Province::Ber as usize], Some(Power::Germany));
-        assert_eq!(state.sc_owner[Province::Rom as usize], Some(Power::Italy));
-        assert_eq!(state.sc_owner[Province::Mos as usize], Some(Power::Russia));
-        assert_eq!(state.sc_owner[Province::Ank as usize], Some(Power::Turkey));
-        assert_eq!(state.sc_owner[Province::Bel as usize], None); // Neutral
+        assert_eq!(state.sc_owner[Province::Vie.index()], Some(Power::Austria));
+        assert_eq!(state.sc_owner[Province::Lon.index()], Some(Power::England));
+        assert_eq!(state.sc_owner[Province::Par.index()], Some(Power::France));
+        assert_eq!(state.sc_owner[Province::Ber.index()], Some(Power::Germany));
+        assert_eq!(state.sc_owner[Province::Rom.index()], Some(Power::Italy));
+        assert_eq!(state.sc_owner[Province::Mos.index()], Some(Power::Russia));
+        assert_eq!(state.sc_owner[Province::Ank.index()], Some(Power::Turkey));
+        assert_eq!(state.sc_owner[Province::Bel.index()], None); // Neutral
+    }
+
+    #[test]
+    fn redacted_dfen_hides_distant_units_and_centers() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let view = encode_dfen_redacted(&state, Power::England);
+        assert_eq!(
+            view, "1901sm/Efedi,Eflon,Ealvp/Eedi,Elon,Elvp/-",
+            "England sees only its home area"
+        );
+        let parsed = parse_dfen(&view).unwrap();
+        assert!(parsed.units[Province::Bre.index()].is_none());
+        assert_eq!(parsed.sc_owner[Province::Lon.index()], Some(Power::England));
+
+        // Germany's view reaches the neighbours of Kie, Ber, and Mun.
+        let german = parse_dfen(&encode_dfen_redacted(&state, Power::Germany)).unwrap();
+        assert!(german.units[Province::Bur.index()].is_none());
+        assert_eq!(
+            german.sc_owner[Province::Den.index()],
+            None,
+            "neutral Denmark is visible from Kiel"
+        );
+        assert!(encode_dfen_redacted(&state, Power::Germany).contains("Nden"));
+        assert!(encode_dfen_redacted(&state, Power::Germany).contains("Nhol"));
+    }
+
+    #[test]
+    fn redacted_dfen_filters_dislodged_units() {
+        let state = parse_dfen("1901fr/Aagal,Eflon/Avie,Elon/Rawar<gal").unwrap();
+        assert_eq!(
+            encode_dfen_redacted(&state, Power::Russia),
+            "1901fr/Aagal/Nmos,Nwar/Rawar<gal"
+        );
+        assert_eq!(
+            encode_dfen_redacted(&state, Power::England),
+            "1901fr/Eflon/Elon/-"
+        );
+
+        // An eliminated power sees nothing and the view still parses.
+        let empty = encode_dfen_redacted(&state, Power::Turkey);
+        assert_eq!(empty, "1901fr/-/-/-");
+        assert!(parse_dfen(&empty).is_ok());
     }
 }
@@ -0,0 +1,384 @@
+//! Structured differences between two positions.
+//!
+//! [`diff`] says what changed from one position to another, usually from
+//! one phase to the next: which units moved or retreated, which were built
+//! or disbanded, which supply centers changed hands and which units were
+//! dislodged. Positions carry no orders, so moves are inferred: a unit
+//! that left a province is matched to a unit of the same power and type
+//! that appeared in a neighbouring one, or, for armies leaving a movement
+//! phase, anywhere (a convoy). A unit left unmatched was disbanded and a
+//! unit that appeared unmatched was built. Nothing moves out of a build
+//! phase, and only dislodged units move out of a retreat phase. Two units
+//! of one power and type trading places look like no change at all.
+//!
+//! The self-play recorder writes the diff from each phase to the next, the
+//! game summary finds captured centers with it, and the HTTP `/adjudicate`
+//! endpoint answers with the diff its resolution made.
+
+use serde_json::{json, Value};
+
+use crate::board::map::GameMap;
+use crate::board::order::Location;
+use crate::board::province::{Coast, Power, Province};
+use crate::board::state::{BoardState, Phase};
+use crate::board::unit::UnitType;
+
+/// A unit that moved, or retreated, from one province to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitMove {
+    pub power: Power,
+    pub unit_type: UnitType,
+    pub from: Location,
+    pub to: Location,
+    /// Whether the unit was dislodged and this is its retreat.
+    pub retreat: bool,
+}
+
+/// A unit that was built or disbanded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitChange {
+    pub power: Power,
+    pub unit_type: UnitType,
+    pub location: Location,
+}
+
+/// A supply center whose owner changed. `None` is neutral.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScChange {
+    pub province: Province,
+    pub from: Option<Power>,
+    pub to: Option<Power>,
+}
+
+/// A unit newly dislodged in the second position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dislodgement {
+    pub power: Power,
+    pub unit_type: UnitType,
+    pub location: Location,
+    pub attacker_from: Province,
+}
+
+/// Everything that changed between two positions, each list in province
+/// order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DfenDiff {
+    pub moves: Vec<UnitMove>,
+    pub builds: Vec<UnitChange>,
+    pub disbands: Vec<UnitChange>,
+    pub sc_changes: Vec<ScChange>,
+    pub dislodged: Vec<Dislodgement>,
+}
+
+impl DfenDiff {
+    /// Whether the positions hold the same units, owners and dislodged
+    /// units.
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+            && self.builds.is_empty()
+            && self.disbands.is_empty()
+            && self.sc_changes.is_empty()
+            && self.dislodged.is_empty()
+    }
+
+    /// The diff as a JSON object, naming provinces as `map` does.
+    ///
+    /// Format: `{"moves":[{"power":"austria","unit":"A","from":"vie","to":"gal","retreat":false}],"builds":[{"power":"russia","unit":"F","location":"stp/sc"}],"disbands":[...],"centers":[{"province":"ser","from":null,"to":"austria"}],"dislodged":[{"power":"turkey","unit":"A","location":"rum","attacker_from":"bud"}]}`
+    pub fn to_json(&self, map: &GameMap) -> Value {
+        let changes = |list: &[UnitChange]| -> Vec<Value> {
+            list.iter()
+                .map(|c| {
+                    json!({
+                        "power": c.power.name(),
+                        "unit": c.unit_type.dson_char().to_string(),
+                        "location": location_name(map, c.location),
+                    })
+                })
+                .collect()
+        };
+        let moves: Vec<Value> = self
+            .moves
+            .iter()
+            .map(|m| {
+                json!({
+                    "power": m.power.name(),
+                    "unit": m.unit_type.dson_char().to_string(),
+                    "from": location_name(map, m.from),
+                    "to": location_name(map, m.to),
+                    "retreat": m.retreat,
+                })
+            })
+            .collect();
+        let centers: Vec<Value> = self
+            .sc_changes
+            .iter()
+            .map(|c| {
+                json!({
+                    "province": map.abbr(c.province),
+                    "from": c.from.map(Power::name),
+                    "to": c.to.map(Power::name),
+                })
+            })
+            .collect();
+        let dislodged: Vec<Value> = self
+            .dislodged
+            .iter()
+            .map(|d| {
+                json!({
+                    "power": d.power.name(),
+                    "unit": d.unit_type.dson_char().to_string(),
+                    "location": location_name(map, d.location),
+                    "attacker_from": map.abbr(d.attacker_from),
+                })
+            })
+            .collect();
+        json!({
+            "moves": moves,
+            "builds": changes(&self.builds),
+            "disbands": changes(&self.disbands),
+            "centers": centers,
+            "dislodged": dislodged,
+        })
+    }
+}
+
+/// A unit in one of the two positions, on the board or dislodged.
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    power: Power,
+    unit_type: UnitType,
+    location: Location,
+    dislodged: bool,
+}
+
+impl Piece {
+    fn change(&self) -> UnitChange {
+        UnitChange {
+            power: self.power,
+            unit_type: self.unit_type,
+            location: self.location,
+        }
+    }
+}
+
+/// Computes what changed from `a` to `b`. See the module docs for how
+/// moves are told from builds and disbands.
+pub fn diff(a: &BoardState, b: &BoardState) -> DfenDiff {
+    let mut out = DfenDiff::default();
+    let mut left = Vec::new();
+    let mut arrived = Vec::new();
+    for &prov in a.map().provinces() {
+        let idx = prov.index();
+        let before = a.units[idx];
+        let after = b.units[idx];
+        let kept = after == before || b.dislodged[idx].map(|d| (d.power, d.unit_type)) == before;
+        if let (Some((power, unit_type)), false) = (before, kept) {
+            left.push(Piece {
+                power,
+                unit_type,
+                location: Location::with_coast(prov, a.fleet_coast[idx].unwrap_or(Coast::None)),
+                dislodged: false,
+            });
+        }
+        if let Some(d) = a.dislodged[idx].filter(|&d| b.dislodged[idx] != Some(d)) {
+            left.push(Piece {
+                power: d.power,
+                unit_type: d.unit_type,
+                location: Location::with_coast(prov, d.coast),
+                dislodged: true,
+            });
+        }
+        if let (Some((power, unit_type)), true) = (after, after != before) {
+            arrived.push(Piece {
+                power,
+                unit_type,
+                location: Location::with_coast(prov, b.fleet_coast[idx].unwrap_or(Coast::None)),
+                dislodged: false,
+            });
+        }
+        if let Some(d) = b.dislodged[idx].filter(|&d| a.dislodged[idx] != Some(d)) {
+            out.dislodged.push(Dislodgement {
+                power: d.power,
+                unit_type: d.unit_type,
+                location: Location::with_coast(prov, d.coast),
+                attacker_from: d.attacker_from,
+            });
+        }
+        if a.sc_owner[idx] != b.sc_owner[idx] {
+            out.sc_changes.push(ScChange {
+                province: prov,
+                from: a.sc_owner[idx],
+                to: b.sc_owner[idx],
+            });
+        }
+    }
+
+    let can_move = |l: &Piece| match a.phase {
+        Phase::Movement => true,
+        Phase::Retreat => l.dislodged,
+        Phase::Build => false,
+    };
+    let same = |l: &Piece, r: &Piece| l.power == r.power && l.unit_type == r.unit_type;
+    let step = |l: usize, r: usize| {
+        let (l, r) = (&left[l], &arrived[r]);
+        can_move(l)
+            && same(l, r)
+            && a.map().is_adjacent(
+                l.location.province,
+                l.location.coast,
+                r.location.province,
+                r.location.coast,
+                l.unit_type == UnitType::Fleet,
+            )
+    };
+    // owner[r] is the left unit matched to arrived unit r.
+    let mut owner: Vec<Option<usize>> = vec![None; arrived.len()];
+    for l in 0..left.len() {
+        let mut seen = vec![false; arrived.len()];
+        augment(l, &step, &mut owner, &mut seen);
+    }
+    let mut matched: Vec<Option<usize>> = vec![None; left.len()];
+    for (r, o) in owner.iter().enumerate() {
+        if let Some(l) = *o {
+            matched[l] = Some(r);
+        }
+    }
+    // Armies leaving a movement phase may have been convoyed anywhere.
+    if a.phase == Phase::Movement {
+        for (l, piece) in left.iter().enumerate() {
+            if matched[l].is_some() || piece.unit_type != UnitType::Army {
+                continue;
+            }
+            if let Some(r) =
+                (0..arrived.len()).find(|&r| owner[r].is_none() && same(piece, &arrived[r]))
+            {
+                owner[r] = Some(l);
+                matched[l] = Some(r);
+            }
+        }
+    }
+
+    for (piece, m) in left.iter().zip(&matched) {
+        match m {
+            Some(r) => out.moves.push(UnitMove {
+                power: piece.power,
+                unit_type: piece.unit_type,
+                from: piece.location,
+                to: arrived[*r].location,
+                retreat: piece.dislodged,
+            }),
+            None => out.disbands.push(piece.change()),
+        }
+    }
+    out.builds = arrived
+        .iter()
+        .zip(&owner)
+        .filter(|(_, o)| o.is_none())
+        .map(|(piece, _)| piece.change())
+        .collect();
+    out
+}
+
+/// Tries to match left unit `l` along an augmenting path (Kuhn's
+/// bipartite matching), so that as many units as possible count as moved.
+fn augment(
+    l: usize,
+    edge: &dyn Fn(usize, usize) -> bool,
+    owner: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for r in 0..owner.len() {
+        if seen[r] || !edge(l, r) {
+            continue;
+        }
+        seen[r] = true;
+        if owner[r].is_none_or(|o| augment(o, edge, owner, seen)) {
+            owner[r] = Some(l);
+            return true;
+        }
+    }
+    false
+}
+
+/// A location as DSON writes it, e.g. `vie` or `stp/sc`.
+fn location_name(map: &GameMap, loc: Location) -> String {
+    if loc.coast == Coast::None {
+        map.abbr(loc.province).to_string()
+    } else {
+        format!("{}/{}", map.abbr(loc.province), loc.coast.abbr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+
+    fn position(s: &str) -> BoardState {
+        parse_dfen(s).unwrap()
+    }
+
+    #[test]
+    fn movement_with_a_dislodgement() {
+        let a = position("1901fm/Aavie,Aabud,Tarum,Tabul/Abud,Avie,Trum/-");
+        let b = position("1901fr/Aagal,Aarum,Tabul/Abud,Avie,Trum/Tarum<bud");
+        let d = diff(&a, &b);
+        assert_eq!(d.moves.len(), 2);
+        assert_eq!(d.moves[0].from.province, Province::Bud);
+        assert_eq!(d.moves[0].to.province, Province::Rum);
+        assert_eq!(d.moves[1].from.province, Province::Vie);
+        assert_eq!(d.moves[1].to.province, Province::Gal);
+        assert!(d.builds.is_empty() && d.disbands.is_empty());
+        assert_eq!(d.dislodged.len(), 1);
+        assert_eq!(d.dislodged[0].power, Power::Turkey);
+        assert_eq!(d.dislodged[0].attacker_from, Province::Bud);
+    }
+
+    #[test]
+    fn retreats_captures_builds_and_convoys() {
+        let a = position("1901fr/Aagal,Aarum,Tabul/Abud,Avie,Trum/Tarum<bud");
+        let retreated = position("1901fb/Aagal,Aarum,Tabul,Taser/Abud,Avie,Arum/-");
+        let d = diff(&a, &retreated);
+        assert_eq!(d.moves.len(), 1);
+        assert!(d.moves[0].retreat);
+        assert_eq!(d.moves[0].to.province, Province::Ser);
+        assert!(d.dislodged.is_empty() && d.disbands.is_empty());
+        assert_eq!(
+            d.sc_changes,
+            vec![ScChange {
+                province: Province::Rum,
+                from: Some(Power::Turkey),
+                to: Some(Power::Austria),
+            }]
+        );
+
+        // Nothing moves out of a build phase.
+        let built = position("1902sm/Aagal,Aarum,Aavie,Tabul/Abud,Avie,Arum/-");
+        let d = diff(&retreated, &built);
+        assert!(d.moves.is_empty());
+        assert_eq!(d.builds.len(), 1);
+        assert_eq!(d.builds[0].location.province, Province::Vie);
+        assert_eq!(d.disbands.len(), 1);
+        assert_eq!(d.disbands[0].location.province, Province::Ser);
+
+        let convoy_from = position("1901sm/Ealon,Efnth/Elon/-");
+        let convoy_to = position("1901fm/Eabel,Efnth/Elon/-");
+        let d = diff(&convoy_from, &convoy_to);
+        assert_eq!(d.moves.len(), 1);
+        assert_eq!(d.moves[0].to.province, Province::Bel);
+        assert!(diff(&convoy_to, &convoy_to).is_empty());
+    }
+
+    #[test]
+    fn json_lists_every_change() {
+        let a = position("1901fm/Aavie,Aabud,Tarum,Tabul/Abud,Avie,Trum/-");
+        let b = position("1901fr/Aagal,Aarum,Tabul/Abud,Avie,Trum/Tarum<bud");
+        let v = diff(&a, &b).to_json(a.map());
+        assert_eq!(v["moves"][1]["from"], "vie");
+        assert_eq!(v["moves"][0]["unit"], "A");
+        assert_eq!(v["dislodged"][0]["location"], "rum");
+        assert_eq!(v["dislodged"][0]["attacker_from"], "bud");
+        assert_eq!(v["centers"].as_array().unwrap().len(), 0);
+        assert!(!v.to_string().contains('\n'));
+    }
+}
@@ -3,13 +3,18 @@
 //! DSON is a compact text notation for Diplomacy orders, used in the
 //! `bestorders` response and `info` lines of the DUI protocol.
 //! Coast separator is `/` (slash), province IDs are 3-letter lowercase,
-//! and unit types are uppercase A/F.
+//! and unit types are uppercase A/F. The plain functions read and write
+//! orders on the standard map; the `_on` variants take the map whose
+//! province IDs to use.
 
 use thiserror::Error;
 
+use crate::board::map::GameMap;
 use crate::board::order::{Location, Order, OrderUnit};
-use crate::board::province::{Coast, Province};
+use crate::board::province::{Coast, Power};
+use crate::board::state::BoardState;
 use crate::board::unit::UnitType;
+use crate::resolve::default_disbands;
 
 /// Errors that can occur when parsing DSON order strings.
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -34,12 +39,28 @@ pub enum DsonError {
 
     #[error("unexpected token '{found}', expected {expected}")]
     UnexpectedToken { expected: String, found: String },
+
+    #[error("invalid probability '{0}', expected a number from 0 to 1")]
+    InvalidProbability(String),
+
+    #[error("invalid count '{0}', expected a positive whole number")]
+    InvalidCount(String),
+
+    #[error("defaulted disbands '{0}' need the position to expand")]
+    NeedsPosition(String),
 }
 
 /// Parses a single DSON order string into an `Order`.
 ///
 /// Accepts canonical DSON forms like `A vie H`, `F nrg - stp/nc`, `W`, etc.
+/// A move may end in `via convoy` to request a convoy even between adjacent
+/// provinces (`A lon - bel via convoy`).
 pub fn parse_order(s: &str) -> Result<Order, DsonError> {
+    parse_order_on(&GameMap::standard(), s)
+}
+
+/// Parses a single DSON order naming provinces of `map`.
+pub fn parse_order_on(map: &GameMap, s: &str) -> Result<Order, DsonError> {
     let s = s.trim();
     if s.is_empty() {
         return Err(DsonError::EmptyInput);
@@ -56,7 +77,7 @@ pub fn parse_order(s: &str) -> Result<Order, DsonError> {
     }
 
     // All other orders start with a unit: unit_char location
-    let unit = parse_unit(&tokens, 0)?;
+    let unit = parse_unit(map, &tokens, 0)?;
     let pos = 2; // consumed unit_char and location
 
     if pos >= tokens.len() {
@@ -69,14 +90,31 @@ pub fn parse_order(s: &str) -> Result<Order, DsonError> {
         "H" => Ok(Order::Hold { unit }),
 
         "-" => {
-            // Move: unit - location
-            let dest = parse_location(&tokens, pos + 1)?;
-            Ok(Order::Move { unit, dest })
+            // Move: unit - location [via convoy]
+            let dest = parse_location(map, &tokens, pos + 1)?;
+            let via_convoy = tokens.get(pos + 2) == Some(&"via");
+            if via_convoy {
+                match tokens.get(pos + 3) {
+                    Some(&"convoy") => {}
+                    Some(other) => {
+                        return Err(DsonError::UnexpectedToken {
+                            expected: "convoy".to_string(),
+                            found: other.to_string(),
+                        })
+                    }
+                    None => return Err(DsonError::UnexpectedEnd("convoy".to_string())),
+                }
+            }
+            Ok(Order::Move {
+                unit,
+                dest,
+                via_convoy,
+            })
         }
 
         "S" => {
             // Support: unit S supported_unit (H | - location)
-            let supported = parse_unit(&tokens, pos + 1)?;
+            let supported = parse_unit(map, &tokens, pos + 1)?;
             let sup_pos = pos + 3; // past S, unit_char, location
 
             if sup_pos >= tokens.len() {
@@ -88,7 +126,7 @@ pub fn parse_order(s: &str) -> Result<Order, DsonError> {
             match tokens[sup_pos] {
                 "H" => Ok(Order::SupportHold { unit, supported }),
                 "-" => {
-                    let dest = parse_location(&tokens, sup_pos + 1)?;
+                    let dest = parse_location(map, &tokens, sup_pos + 1)?;
                     Ok(Order::SupportMove {
                         unit,
                         supported,
@@ -115,7 +153,7 @@ pub fn parse_order(s: &str) -> Result<Order, DsonError> {
                     found: tokens[pos + 1].to_string(),
                 });
             }
-            let from = parse_location(&tokens, pos + 2)?;
+            let from = parse_location(map, &tokens, pos + 2)?;
 
             let dash_pos = pos + 3;
             if dash_pos >= tokens.len() || tokens[dash_pos] != "-" {
@@ -130,7 +168,7 @@ pub fn parse_order(s: &str) -> Result<Order, DsonError> {
                 });
             }
 
-            let to = parse_location(&tokens, dash_pos + 1)?;
+            let to = parse_location(map, &tokens, dash_pos + 1)?;
             Ok(Order::Convoy {
                 unit,
                 convoyed_from: from,
@@ -140,7 +178,7 @@ pub fn parse_order(s: &str) -> Result<Order, DsonError> {
 
         "R" => {
             // Retreat: unit R location
-            let dest = parse_location(&tokens, pos + 1)?;
+            let dest = parse_location(map, &tokens, pos + 1)?;
             Ok(Order::Retreat { unit, dest })
         }
 
@@ -155,29 +193,114 @@ pub fn parse_order(s: &str) -> Result<Order, DsonError> {
 /// Parses a semicolon-separated list of DSON orders.
 ///
 /// Orders are separated by ` ; ` (space-semicolon-space). A single order
-/// without separators is valid.
+/// without separators is valid. `W <n>` stands for `n` waives; defaulted
+/// disbands (`D <n>`) depend on the position and are rejected with
+/// [`DsonError::NeedsPosition`], see [`parse_orders_for`].
 pub fn parse_orders(s: &str) -> Result<Vec<Order>, DsonError> {
+    parse_orders_on(&GameMap::standard(), s)
+}
+
+/// Parses a semicolon-separated list of DSON orders naming provinces of
+/// `map`.
+pub fn parse_orders_on(map: &GameMap, s: &str) -> Result<Vec<Order>, DsonError> {
     let s = s.trim();
     if s.is_empty() {
         return Err(DsonError::EmptyInput);
     }
 
-    s.split(" ; ")
-        .map(|part| parse_order(part.trim()))
-        .collect()
+    let mut orders = Vec::new();
+    for part in s.split(" ; ").map(str::trim) {
+        match parse_shorthand(part)? {
+            Some(Shorthand::Waives(n)) => orders.extend(std::iter::repeat_n(Order::Waive, n)),
+            Some(Shorthand::Disbands(_)) => return Err(DsonError::NeedsPosition(part.to_string())),
+            None => orders.push(parse_order_on(map, part)?),
+        }
+    }
+    Ok(orders)
+}
+
+/// Parses `power`'s orders in `state`, expanding the build-phase
+/// shorthand: `W <n>` into `n` waives and `D <n>` into the `n` disbands
+/// civil disorder would choose, passing over units the list disbands
+/// explicitly. Provinces are those of the position's map.
+pub fn parse_orders_for(
+    s: &str,
+    power: Power,
+    state: &BoardState,
+) -> Result<Vec<Order>, DsonError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(DsonError::EmptyInput);
+    }
+
+    let mut orders = Vec::new();
+    let mut defaulted = 0;
+    for part in s.split(" ; ").map(str::trim) {
+        match parse_shorthand(part)? {
+            Some(Shorthand::Waives(n)) => orders.extend(std::iter::repeat_n(Order::Waive, n)),
+            Some(Shorthand::Disbands(n)) => defaulted += n,
+            None => orders.push(parse_order_on(state.map(), part)?),
+        }
+    }
+    let disbands = default_disbands(power, defaulted, state, &orders);
+    orders.extend(disbands);
+    Ok(orders)
+}
+
+/// A build-phase order standing for several.
+enum Shorthand {
+    Waives(usize),
+    Disbands(usize),
+}
+
+/// Recognizes `W <n>` and `D <n>`; `None` for any other order.
+fn parse_shorthand(s: &str) -> Result<Option<Shorthand>, DsonError> {
+    let (waives, count) = match s.split(' ').collect::<Vec<_>>()[..] {
+        ["W", count] => (true, count),
+        ["D", count] => (false, count),
+        _ => return Ok(None),
+    };
+    let n = match count.parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => return Err(DsonError::InvalidCount(count.to_string())),
+    };
+    Ok(Some(if waives {
+        Shorthand::Waives(n)
+    } else {
+        Shorthand::Disbands(n)
+    }))
 }
 
 /// Formats a single `Order` as a canonical DSON string.
 pub fn format_order(order: &Order) -> String {
+    format_order_on(&GameMap::standard(), order)
+}
+
+/// Formats an order naming provinces by their IDs on `map`.
+pub fn format_order_on(map: &GameMap, order: &Order) -> String {
     match order {
         Order::Hold { unit } => {
-            format!("{} H", format_unit(unit))
+            format!("{} H", format_unit(map, unit))
         }
-        Order::Move { unit, dest } => {
-            format!("{} - {}", format_unit(unit), format_location(dest))
+        Order::Move {
+            unit,
+            dest,
+            via_convoy,
+        } => {
+            let suffix = if *via_convoy { " via convoy" } else { "" };
+            format!(
+                "{} - {}{}",
+                format_unit(map, unit),
+                format_location(map, dest),
+                suffix
+            )
         }
         Order::SupportHold { unit, supported } => {
-            format!("{} S {} H", format_unit(unit), format_unit(supported))
+            format!(
+                "{} S {} H",
+                format_unit(map, unit),
+                format_unit(map, supported)
+            )
         }
         Order::SupportMove {
             unit,
@@ -186,9 +309,9 @@ pub fn format_order(order: &Order) -> String {
         } => {
             format!(
                 "{} S {} - {}",
-                format_unit(unit),
-                format_unit(supported),
-                format_location(dest)
+                format_unit(map, unit),
+                format_unit(map, supported),
+                format_location(map, dest)
             )
         }
         Order::Convoy {
@@ -198,19 +321,23 @@ pub fn format_order(order: &Order) -> String {
         } => {
             format!(
                 "{} C A {} - {}",
-                format_unit(unit),
-                format_location(convoyed_from),
-                format_location(convoyed_to)
+                format_unit(map, unit),
+                format_location(map, convoyed_from),
+                format_location(map, convoyed_to)
             )
         }
         Order::Retreat { unit, dest } => {
-            format!("{} R {}", format_unit(unit), format_location(dest))
+            format!(
+                "{} R {}",
+                format_unit(map, unit),
+                format_location(map, dest)
+            )
         }
         Order::Disband { unit } => {
-            format!("{} D", format_unit(unit))
+            format!("{} D", format_unit(map, unit))
         }
         Order::Build { unit } => {
-            format!("{} B", format_unit(unit))
+            format!("{} B", format_unit(map, unit))
         }
         Order::Waive => "W".to_string(),
     }
@@ -218,15 +345,77 @@ pub fn format_order(order: &Order) -> String {
 
 /// Formats a slice of orders as a ` ; `-separated DSON string.
 pub fn format_orders(orders: &[Order]) -> String {
+    format_orders_on(&GameMap::standard(), orders)
+}
+
+/// Formats orders on `map` as a ` ; `-separated DSON string.
+pub fn format_orders_on(map: &GameMap, orders: &[Order]) -> String {
+    orders
+        .iter()
+        .map(|o| format_order_on(map, o))
+        .collect::<Vec<_>>()
+        .join(" ; ")
+}
+
+/// Formats an order with its probability in the annotated DSON form,
+/// `A vie - gal @0.812`.
+pub fn format_annotated_order(order: &Order, probability: f32) -> String {
+    format_annotated_order_on(&GameMap::standard(), order, probability)
+}
+
+fn format_annotated_order_on(map: &GameMap, order: &Order, probability: f32) -> String {
+    format!("{} @{:.3}", format_order_on(map, order), probability)
+}
+
+/// Formats orders, each with an optional probability, as a ` ; `-separated
+/// annotated DSON string. Orders without a probability are written plain.
+pub fn format_annotated_orders(orders: &[(Order, Option<f32>)]) -> String {
+    format_annotated_orders_on(&GameMap::standard(), orders)
+}
+
+/// Formats annotated orders on `map`.
+pub fn format_annotated_orders_on(map: &GameMap, orders: &[(Order, Option<f32>)]) -> String {
     orders
         .iter()
-        .map(|o| format_order(o))
+        .map(|(o, p)| match p {
+            Some(p) => format_annotated_order_on(map, o, *p),
+            None => format_order_on(map, o),
+        })
         .collect::<Vec<_>>()
         .join(" ; ")
 }
 
+/// Parses an order that may carry an `@<probability>` suffix.
+///
+/// Plain DSON is accepted and returns no probability, so readers can take
+/// either form.
+pub fn parse_annotated_order(s: &str) -> Result<(Order, Option<f32>), DsonError> {
+    let s = s.trim();
+    match s.rsplit_once(" @") {
+        Some((order, p)) => {
+            let probability = p
+                .parse::<f32>()
+                .ok()
+                .filter(|p| (0.0..=1.0).contains(p))
+                .ok_or_else(|| DsonError::InvalidProbability(p.to_string()))?;
+            Ok((parse_order(order)?, Some(probability)))
+        }
+        None => Ok((parse_order(s)?, None)),
+    }
+}
+
+/// Parses a ` ; `-separated list of orders in plain or annotated DSON.
+pub fn parse_annotated_orders(s: &str) -> Result<Vec<(Order, Option<f32>)>, DsonError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(DsonError::EmptyInput);
+    }
+
+    s.split(" ; ").map(parse_annotated_order).collect()
+}
+
 /// Parses a unit (unit_char + location) from token slice at given index.
-fn parse_unit(tokens: &[&str], idx: usize) -> Result<OrderUnit, DsonError> {
+fn parse_unit(map: &GameMap, tokens: &[&str], idx: usize) -> Result<OrderUnit, DsonError> {
     if idx >= tokens.len() {
         return Err(DsonError::UnexpectedEnd("unit type (A or F)".to_string()));
     }
@@ -237,7 +426,7 @@ fn parse_unit(tokens: &[&str], idx: usize) -> Result<OrderUnit, DsonError> {
         other => return Err(DsonError::UnknownUnitType(other.to_string())),
     };
 
-    let location = parse_location(tokens, idx + 1)?;
+    let location = parse_location(map, tokens, idx + 1)?;
 
     Ok(OrderUnit {
         unit_type,
@@ -246,7 +435,7 @@ fn parse_unit(tokens: &[&str], idx: usize) -> Result<OrderUnit, DsonError> {
 }
 
 /// Parses a location (prov_id or prov_id/coast) from token slice at given index.
-fn parse_location(tokens: &[&str], idx: usize) -> Result<Location, DsonError> {
+fn parse_location(map: &GameMap, tokens: &[&str], idx: usize) -> Result<Location, DsonError> {
     if idx >= tokens.len() {
         return Err(DsonError::UnexpectedEnd("province location".to_string()));
     }
@@ -258,40 +447,43 @@ fn parse_location(tokens: &[&str], idx: usize) -> Result<Location, DsonError> {
         let prov_str = &token[..slash_pos];
         let coast_str = &token[slash_pos + 1..];
 
-        let province = Province::from_abbr(prov_str)
+        let province = map
+            .province(prov_str)
             .ok_or_else(|| DsonError::UnknownProvince(prov_str.to_string()))?;
         let coast = Coast::from_abbr(coast_str)
             .ok_or_else(|| DsonError::UnknownCoast(coast_str.to_string()))?;
 
         Ok(Location::with_coast(province, coast))
     } else {
-        let province = Province::from_abbr(token)
+        let province = map
+            .province(token)
             .ok_or_else(|| DsonError::UnknownProvince(token.to_string()))?;
         Ok(Location::new(province))
     }
 }
 
 /// Formats a unit reference as "A prov" or "F prov/coast".
-fn format_unit(unit: &OrderUnit) -> String {
+fn format_unit(map: &GameMap, unit: &OrderUnit) -> String {
     format!(
         "{} {}",
         unit.unit_type.dson_char(),
-        format_location(&unit.location)
+        format_location(map, &unit.location)
     )
 }
 
 /// Formats a location as "prov" or "prov/coast".
-fn format_location(loc: &Location) -> String {
+fn format_location(map: &GameMap, loc: &Location) -> String {
     if loc.coast == Coast::None {
-        loc.province.abbr().to_string()
+        map.abbr(loc.province).to_string()
     } else {
-        format!("{}/{}", loc.province.abbr(), loc.coast.abbr())
+        format!("{}/{}", map.abbr(loc.province), loc.coast.abbr())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::province::Province;
 
     // -- Helper constructors --
 
@@ -344,7 +536,8 @@ mod tests {
             order,
             Order::Move {
                 unit: army(Province::Bud),
-                dest: loc(Province::Rum)
+                dest: loc(Province::Rum),
+                via_convoy: false,
             }
         );
     }
@@ -356,7 +549,8 @@ mod tests {
             order,
             Order::Move {
                 unit: fleet(Province::Tri),
-                dest: loc(Province::Adr)
+                dest: loc(Province::Adr),
+                via_convoy: false,
             }
         );
     }
@@ -369,10 +563,32 @@ mod tests {
             Order::Move {
                 unit: fleet(Province::Nrg),
                 dest: loc_coast(Province::Stp, Coast::North),
+                via_convoy: false,
             }
         );
     }
 
+    #[test]
+    fn parse_move_via_convoy() {
+        let order = parse_order("A lon - bel via convoy").unwrap();
+        assert_eq!(
+            order,
+            Order::Move {
+                unit: army(Province::Lon),
+                dest: loc(Province::Bel),
+                via_convoy: true,
+            }
+        );
+        assert_eq!(
+            parse_order("A lon - bel via"),
+            Err(DsonError::UnexpectedEnd("convoy".to_string()))
+        );
+        assert!(matches!(
+            parse_order("A lon - bel via land"),
+            Err(DsonError::UnexpectedToken { .. })
+        ));
+    }
+
     #[test]
     fn parse_support_hold() {
         let order = parse_order("A tyr S A vie H").unwrap();
@@ -525,21 +741,24 @@ mod tests {
             orders[0],
             Order::Move {
                 unit: army(Province::Vie),
-                dest: loc(Province::Tri)
+                dest: loc(Province::Tri),
+                via_convoy: false,
             }
         );
         assert_eq!(
             orders[1],
             Order::Move {
                 unit: army(Province::Bud),
-                dest: loc(Province::Ser)
+                dest: loc(Province::Ser),
+                via_convoy: false,
             }
         );
         assert_eq!(
             orders[2],
             Order::Move {
                 unit: fleet(Province::Tri),
-                dest: loc(Province::Alb)
+                dest: loc(Province::Alb),
+                via_convoy: false,
             }
         );
     }
@@ -600,6 +819,47 @@ mod tests {
         assert_eq!(orders[0], Order::Waive);
     }
 
+    #[test]
+    fn parse_waive_shorthand() {
+        let orders = parse_orders("A vie B ; W 2").unwrap();
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[1..], [Order::Waive, Order::Waive]);
+        assert_eq!(
+            parse_orders("W 0"),
+            Err(DsonError::InvalidCount("0".to_string()))
+        );
+        assert_eq!(
+            parse_orders("W two"),
+            Err(DsonError::InvalidCount("two".to_string()))
+        );
+        assert_eq!(
+            parse_orders("A vie D ; D 1"),
+            Err(DsonError::NeedsPosition("D 1".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_defaulted_disbands_against_the_position() {
+        use crate::board::state::{Phase, Season};
+        let mut state = BoardState::empty(1902, Season::Fall, Phase::Build);
+        for (prov, ut) in [
+            (Province::Vie, UnitType::Army),
+            (Province::Gal, UnitType::Army),
+            (Province::Ukr, UnitType::Army),
+            (Province::Ion, UnitType::Fleet),
+        ] {
+            state.place_unit(prov, Power::Austria, ut, Coast::None);
+        }
+        // Ionian and Ukraine lie furthest from home; Ionian is disbanded
+        // explicitly, so the default passes over it.
+        let orders = parse_orders_for("F ion D ; D 1", Power::Austria, &state).unwrap();
+        assert_eq!(format_orders(&orders), "F ion D ; A ukr D");
+        let orders = parse_orders_for("D 2", Power::Austria, &state).unwrap();
+        assert_eq!(orders.len(), 2);
+        let orders = parse_orders_for("W 2", Power::Austria, &state).unwrap();
+        assert_eq!(orders, [Order::Waive, Order::Waive]);
+    }
+
     // -- Format tests --
 
     #[test]
@@ -615,6 +875,7 @@ mod tests {
         let s = format_order(&Order::Move {
             unit: army(Province::Bud),
             dest: loc(Province::Rum),
+            via_convoy: false,
         });
         assert_eq!(s, "A bud - rum");
     }
@@ -624,6 +885,7 @@ mod tests {
         let s = format_order(&Order::Move {
             unit: fleet(Province::Nrg),
             dest: loc_coast(Province::Stp, Coast::North),
+            via_convoy: false,
         });
         assert_eq!(s, "F nrg - stp/nc");
     }
@@ -711,14 +973,17 @@ mod tests {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: loc(Province::Tri),
+                via_convoy: false,
             },
             Order::Move {
                 unit: army(Province::Bud),
                 dest: loc(Province::Ser),
+                via_convoy: false,
             },
             Order::Move {
                 unit: fleet(Province::Tri),
                 dest: loc(Province::Alb),
+                via_convoy: false,
             },
         ];
         let s = format_orders(&orders);
@@ -739,6 +1004,12 @@ mod tests {
         assert_eq!(format_order(&parse_order(input).unwrap()), input);
     }
 
+    #[test]
+    fn roundtrip_move_via_convoy() {
+        let input = "A lon - nwy via convoy";
+        assert_eq!(format_order(&parse_order(input).unwrap()), input);
+    }
+
     #[test]
     fn roundtrip_move_coast() {
         let input = "F nrg - stp/nc";
@@ -1035,4 +1306,33 @@ mod tests {
             "F bre - mao ; A par - bur ; A mar S A par - bur"
         );
     }
+
+    // -- Annotated orders --
+
+    #[test]
+    fn annotated_orders_round_trip() {
+        let orders = vec![
+            (parse_order("A vie - gal").unwrap(), Some(0.8124)),
+            (parse_order("F tri H").unwrap(), None),
+            (parse_order("F nrg - stp/nc").unwrap(), Some(1.0)),
+        ];
+        let text = format_annotated_orders(&orders);
+        assert_eq!(text, "A vie - gal @0.812 ; F tri H ; F nrg - stp/nc @1.000");
+        let parsed = parse_annotated_orders(&text).unwrap();
+        assert_eq!(parsed[0], (orders[0].0, Some(0.812)));
+        assert_eq!(parsed[1], orders[1]);
+        assert_eq!(parsed[2], orders[2]);
+    }
+
+    #[test]
+    fn annotated_order_rejects_bad_probabilities() {
+        assert_eq!(
+            parse_annotated_order("A vie H @1.5"),
+            Err(DsonError::InvalidProbability("1.5".into()))
+        );
+        assert_eq!(
+            parse_annotated_order("A vie H @x"),
+            Err(DsonError::InvalidProbability("x".into()))
+        );
+    }
 }
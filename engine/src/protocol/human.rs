@@ -0,0 +1,258 @@
+//! Human-readable order notation.
+//!
+//! The same shape as DSON with full province names in place of IDs:
+//! `A Vienna - Galicia`, `F St. Petersburg (sc) - Gulf of Bothnia`,
+//! `F Trieste S A Vienna H`. Parsing is forgiving about what people
+//! actually type: names and IDs in any case, spelled-out keywords (`Hold`,
+//! `Supports`, `->`), coasts as `(sc)`, `(south coast)` or `/sc`, a support
+//! hold without the trailing `H`, and builds written `Build A Vienna`.
+//! Input is rewritten to DSON and parsed with the DSON parser.
+
+use crate::board::order::{Location, Order, OrderUnit};
+use crate::board::province::{Coast, Province, ALL_PROVINCES};
+use crate::protocol::dson::{parse_order, DsonError};
+
+/// Maps a spelled-out keyword to its DSON token.
+fn keyword(word: &str) -> Option<&'static str> {
+    let kw = match word {
+        "A" => "A",
+        "F" => "F",
+        "H" => "H",
+        "S" => "S",
+        "C" => "C",
+        "R" => "R",
+        "D" => "D",
+        "B" => "B",
+        "W" => "W",
+        "-" | "->" | "=>" => "-",
+        _ => match word.to_ascii_lowercase().as_str() {
+            "army" => "A",
+            "fleet" => "F",
+            "hold" | "holds" => "H",
+            "support" | "supports" => "S",
+            "convoy" | "convoys" => "C",
+            "retreat" | "retreats" => "R",
+            "disband" | "disbands" | "remove" => "D",
+            "build" | "builds" => "B",
+            "waive" | "waives" => "W",
+            "via" => "via",
+            _ => return None,
+        },
+    };
+    Some(kw)
+}
+
+/// Parses a coast written as `sc`, `south`, or `south coast`.
+fn parse_coast(text: &str) -> Option<Coast> {
+    let text = text.trim().to_ascii_lowercase();
+    let text = text.strip_suffix(" coast").unwrap_or(&text);
+    match text {
+        "nc" | "north" => Some(Coast::North),
+        "sc" | "south" => Some(Coast::South),
+        "ec" | "east" => Some(Coast::East),
+        _ => None,
+    }
+}
+
+/// Resolves a province name or ID, ignoring case and periods.
+fn find_province(text: &str) -> Option<Province> {
+    let norm = |s: &str| s.to_ascii_lowercase().replace('.', "");
+    let wanted = norm(text.trim());
+    ALL_PROVINCES
+        .iter()
+        .copied()
+        .find(|p| norm(p.name()) == wanted || p.abbr() == wanted)
+}
+
+/// Converts a location phrase to a DSON location token.
+fn location_token(phrase: &str) -> Result<String, DsonError> {
+    let (name, coast) = if let Some((name, rest)) = phrase.split_once('(') {
+        let coast_text = rest.trim_end().trim_end_matches(')');
+        let coast = parse_coast(coast_text)
+            .ok_or_else(|| DsonError::UnknownCoast(coast_text.to_string()))?;
+        (name, coast)
+    } else if let Some((name, coast_text)) = phrase.rsplit_once('/') {
+        let coast = parse_coast(coast_text)
+            .ok_or_else(|| DsonError::UnknownCoast(coast_text.to_string()))?;
+        (name, coast)
+    } else {
+        (phrase, Coast::None)
+    };
+    let province =
+        find_province(name).ok_or_else(|| DsonError::UnknownProvince(name.trim().to_string()))?;
+    Ok(if coast == Coast::None {
+        province.abbr().to_string()
+    } else {
+        format!("{}/{}", province.abbr(), coast.abbr())
+    })
+}
+
+/// Parses a single order in human notation.
+pub fn parse_human_order(s: &str) -> Result<Order, DsonError> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut phrase: Vec<&str> = Vec::new();
+    let mut words = s.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        // "via convoy" is a move suffix, not a convoy order.
+        let kw = match keyword(word) {
+            Some("via") => {
+                if words.peek().is_some_and(|w| keyword(w) == Some("C")) {
+                    words.next();
+                }
+                Some("via convoy")
+            }
+            kw => kw,
+        };
+        match kw {
+            Some(kw) => {
+                if !phrase.is_empty() {
+                    tokens.push(location_token(&phrase.join(" "))?);
+                    phrase.clear();
+                }
+                tokens.push(kw.to_string());
+            }
+            None => phrase.push(word),
+        }
+    }
+    if !phrase.is_empty() {
+        tokens.push(location_token(&phrase.join(" "))?);
+    }
+    if tokens.is_empty() {
+        return Err(DsonError::EmptyInput);
+    }
+
+    // "Build A Vienna" -> "A vie B"
+    if tokens[0] == "B" && tokens.len() > 1 {
+        tokens.remove(0);
+        tokens.push("B".to_string());
+    }
+    // "A Budapest S A Vienna" -> support hold
+    if tokens.len() == 5 && tokens[2] == "S" {
+        tokens.push("H".to_string());
+    }
+    parse_order(&tokens.join(" "))
+}
+
+fn format_location(loc: &Location) -> String {
+    if loc.coast == Coast::None {
+        loc.province.name().to_string()
+    } else {
+        format!("{} ({})", loc.province.name(), loc.coast.abbr())
+    }
+}
+
+fn format_unit(unit: &OrderUnit) -> String {
+    format!(
+        "{} {}",
+        unit.unit_type.dson_char(),
+        format_location(&unit.location)
+    )
+}
+
+/// Formats an order in human notation.
+pub fn format_human_order(order: &Order) -> String {
+    match order {
+        Order::Hold { unit } => format!("{} H", format_unit(unit)),
+        Order::Move {
+            unit,
+            dest,
+            via_convoy,
+        } => format!(
+            "{} - {}{}",
+            format_unit(unit),
+            format_location(dest),
+            if *via_convoy { " via convoy" } else { "" }
+        ),
+        Order::SupportHold { unit, supported } => {
+            format!("{} S {} H", format_unit(unit), format_unit(supported))
+        }
+        Order::SupportMove {
+            unit,
+            supported,
+            dest,
+        } => format!(
+            "{} S {} - {}",
+            format_unit(unit),
+            format_unit(supported),
+            format_location(dest)
+        ),
+        Order::Convoy {
+            unit,
+            convoyed_from,
+            convoyed_to,
+        } => format!(
+            "{} C A {} - {}",
+            format_unit(unit),
+            format_location(convoyed_from),
+            format_location(convoyed_to)
+        ),
+        Order::Retreat { unit, dest } => {
+            format!("{} R {}", format_unit(unit), format_location(dest))
+        }
+        Order::Disband { unit } => format!("{} D", format_unit(unit)),
+        Order::Build { unit } => format!("{} B", format_unit(unit)),
+        Order::Waive => "W".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dson(s: &str) -> Order {
+        parse_order(s).unwrap()
+    }
+
+    #[test]
+    fn human_orders_roundtrip() {
+        for (human, d) in [
+            ("A Vienna H", "A vie H"),
+            ("A Vienna - Galicia", "A vie - gal"),
+            ("F St. Petersburg (sc) - Gulf of Bothnia", "F stp/sc - bot"),
+            ("F Trieste S A Vienna - Galicia", "F tri S A vie - gal"),
+            ("A Budapest S A Vienna H", "A bud S A vie H"),
+            ("F North Sea C A London - Norway", "F nth C A lon - nwy"),
+            ("A London - Belgium via convoy", "A lon - bel via convoy"),
+            ("A Vienna R Bohemia", "A vie R boh"),
+            ("A Vienna D", "A vie D"),
+            ("F St. Petersburg (nc) B", "F stp/nc B"),
+            ("W", "W"),
+        ] {
+            assert_eq!(parse_human_order(human).unwrap(), dson(d), "{}", human);
+            assert_eq!(format_human_order(&dson(d)), human);
+        }
+    }
+
+    #[test]
+    fn parse_accepts_informal_spellings() {
+        assert_eq!(
+            parse_human_order("army vienna -> GALICIA").unwrap(),
+            dson("A vie - gal")
+        );
+        assert_eq!(
+            parse_human_order("F St Petersburg (south coast) Holds").unwrap(),
+            dson("F stp/sc H")
+        );
+        assert_eq!(
+            parse_human_order("Fleet Spain/nc supports Fleet mao").unwrap(),
+            dson("F spa/nc S F mao H")
+        );
+        assert_eq!(
+            parse_human_order("Build A Vienna").unwrap(),
+            dson("A vie B")
+        );
+    }
+
+    #[test]
+    fn parse_reports_unknown_places() {
+        assert_eq!(
+            parse_human_order("A Atlantis - Vienna"),
+            Err(DsonError::UnknownProvince("Atlantis".to_string()))
+        );
+        assert_eq!(
+            parse_human_order("F Spain (west coast) H"),
+            Err(DsonError::UnknownCoast("west coast".to_string()))
+        );
+        assert_eq!(parse_human_order("  "), Err(DsonError::EmptyInput));
+    }
+}
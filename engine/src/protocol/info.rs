@@ -0,0 +1,309 @@
+//! Formatting of search results as DUI `info` lines.
+//!
+//! Searches report progress (`info depth ...`) while they run; everything
+//! known only once a search finishes lives on [`SearchResult`] and is
+//! rendered here, so the search core stays free of wire formats.
+//!
+//! The `InfoLevel` option decides which of those lines reach the server:
+//! each line is classed by [`line_level`] and written only when the
+//! configured level is at least that verbose.
+
+use std::io::{self, Write};
+
+use serde_json::{json, Value};
+
+use crate::board::map::GameMap;
+use crate::protocol::dson::{format_order_on, format_orders_on};
+use crate::search::SearchResult;
+
+/// How much `info` output the engine sends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InfoLevel {
+    /// Errors and order corrections only.
+    Quiet,
+    /// Search progress, throughput and result summaries.
+    #[default]
+    Normal,
+    /// Also cache statistics and candidate dumps.
+    Debug,
+}
+
+impl InfoLevel {
+    /// Parses an `InfoLevel` option value, ignoring case.
+    pub fn parse(s: &str) -> Option<InfoLevel> {
+        match s.to_ascii_lowercase().as_str() {
+            "quiet" => Some(InfoLevel::Quiet),
+            "normal" => Some(InfoLevel::Normal),
+            "debug" => Some(InfoLevel::Debug),
+            _ => None,
+        }
+    }
+
+    /// The option value naming this level.
+    pub fn name(self) -> &'static str {
+        match self {
+            InfoLevel::Quiet => "quiet",
+            InfoLevel::Normal => "normal",
+            InfoLevel::Debug => "debug",
+        }
+    }
+
+    /// Returns true if `line` should be sent at this level.
+    pub fn allows(self, line: &str) -> bool {
+        line_level(line) <= self
+    }
+}
+
+/// The least verbose level at which an output line is sent. Lines that
+/// are not `info` lines are always sent, and so are `info orders` lines,
+/// which a host that asked for them reads as orders.
+pub fn line_level(line: &str) -> InfoLevel {
+    let Some(rest) = line.strip_prefix("info ") else {
+        return InfoLevel::Quiet;
+    };
+    if rest.starts_with("orders ") {
+        return InfoLevel::Quiet;
+    }
+    match rest.strip_prefix("string ") {
+        Some(text) if text.starts_with("error ") || text.starts_with("correction ") => {
+            InfoLevel::Quiet
+        }
+        Some(text)
+            if text.starts_with("future_cache ")
+                || text.starts_with("greedy_cache ")
+                || text.starts_with("eval_cache ")
+                || text.starts_with("candidate ") =>
+        {
+            InfoLevel::Debug
+        }
+        _ => InfoLevel::Normal,
+    }
+}
+
+/// Copies buffered output lines to `out`, dropping those `level` hides.
+pub fn write_filtered<W: Write>(out: &mut W, buf: &[u8], level: InfoLevel) -> io::Result<()> {
+    if level == InfoLevel::Debug {
+        return out.write_all(buf);
+    }
+    for line in String::from_utf8_lossy(buf).lines() {
+        if level.allows(line) {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Renders the equilibrium summary of a result as the JSON payload of an
+/// `info equilibrium` line, or `None` when the search did not mix
+/// strategies. Orders name provinces as `map` does.
+pub fn equilibrium_json(map: &GameMap, result: &SearchResult) -> Option<String> {
+    if result.confidence.is_empty() && result.opponents.is_empty() {
+        return None;
+    }
+    let round = |x: f32| (x as f64 * 1000.0).round() / 1000.0;
+    let confidence: Vec<Value> = result
+        .orders
+        .iter()
+        .zip(&result.confidence)
+        .map(|(order, &c)| json!({ "order": format_order_on(map, order), "p": round(c) }))
+        .collect();
+    let opponents: Vec<Value> = result
+        .opponents
+        .iter()
+        .map(|o| {
+            json!({
+                "power": o.power.name(),
+                "orders": format_orders_on(map, &o.orders),
+                "p": round(o.probability),
+                "candidates": o.candidates,
+            })
+        })
+        .collect();
+    Some(
+        json!({
+            "iterations": result.iterations,
+            "time": result.elapsed.as_millis() as u64,
+            "confidence": confidence,
+            "opponents": opponents,
+        })
+        .to_string(),
+    )
+}
+
+/// Writes the post-search `info` lines for a result: early convergence,
+/// cache counters, the equilibrium summary and the ranked order sets, each
+/// only when the search produced them. Orders name provinces as `map` does.
+pub fn write_result_info<W: Write>(
+    out: &mut W,
+    map: &GameMap,
+    result: &SearchResult,
+) -> io::Result<()> {
+    if result.converged {
+        writeln!(
+            out,
+            "info string converged iterations {} time {}",
+            result.iterations,
+            result.elapsed.as_millis()
+        )?;
+    }
+    if let Some(cache) = result.cache {
+        writeln!(
+            out,
+            "info string future_cache lookups {} hits {} dedup {:.3} evictions {}",
+            cache.lookups,
+            cache.hits,
+            cache.dedup_rate(),
+            cache.evictions
+        )?;
+        writeln!(
+            out,
+            "info string greedy_cache entries {} evictions {} budget_mb {}",
+            cache.greedy_entries, cache.greedy_evictions, cache.budget_mb
+        )?;
+        if cache.eval_lookups > 0 {
+            writeln!(
+                out,
+                "info string eval_cache entries {} lookups {} hits {}",
+                cache.eval_entries, cache.eval_lookups, cache.eval_hits
+            )?;
+        }
+    }
+    if let Some(json) = equilibrium_json(map, result) {
+        writeln!(out, "info equilibrium {}", json)?;
+    }
+    for (rank, set) in result.order_sets.iter().enumerate() {
+        write!(
+            out,
+            "info multiorders {} weight {:.3}",
+            rank + 1,
+            set.weight
+        )?;
+        if let Some(score) = set.score {
+            write!(out, " score {:.2}", score)?;
+        }
+        writeln!(out, " pv {}", format_orders_on(map, &set.orders))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Power;
+    use crate::protocol::dson::parse_order;
+    use crate::search::{CacheStats, OpponentSummary, RankedOrderSet};
+    use std::time::Duration;
+
+    #[test]
+    fn levels_filter_info_lines() {
+        let buf = b"info depth 1 nodes 10 score 0 time 5\n\
+info string error search panicked: boom; playing fallback orders\n\
+info string correction F tri S A vie - war -> F tri H (illegal)\n\
+info string future_cache lookups 1 hits 0 dedup 0.000 evictions 0\n\
+info string candidate 1 weight 0.500 pv A vie H\n\
+info trajectory {}\n";
+        let run = |level| {
+            let mut out = Vec::new();
+            write_filtered(&mut out, buf, level).unwrap();
+            String::from_utf8(out).unwrap().lines().count()
+        };
+        assert_eq!(run(InfoLevel::Quiet), 2);
+        assert_eq!(run(InfoLevel::Normal), 4);
+        assert_eq!(run(InfoLevel::Debug), 6);
+
+        assert_eq!(line_level("bestorders A vie H"), InfoLevel::Quiet);
+        assert_eq!(line_level("info orders human A Vienna H"), InfoLevel::Quiet);
+        assert_eq!(InfoLevel::parse("DEBUG"), Some(InfoLevel::Debug));
+        assert_eq!(InfoLevel::parse("loud"), None);
+        assert_eq!(InfoLevel::default().name(), "normal");
+    }
+
+    #[test]
+    fn reports_early_convergence() {
+        let result = SearchResult {
+            converged: true,
+            iterations: 96,
+            elapsed: Duration::from_millis(1250),
+            ..SearchResult::new(Vec::new(), 0.0, 0)
+        };
+        let mut out = Vec::new();
+        write_result_info(&mut out, &GameMap::standard(), &result).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "info string converged iterations 96 time 1250\n"
+        );
+    }
+
+    #[test]
+    fn bare_result_writes_nothing() {
+        let result = SearchResult::new(vec![parse_order("A vie H").unwrap()], 0.0, 1);
+        let mut out = Vec::new();
+        write_result_info(&mut out, &GameMap::standard(), &result).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn writes_cache_and_equilibrium_lines() {
+        let result = SearchResult {
+            confidence: vec![0.75],
+            opponents: vec![OpponentSummary {
+                power: Power::Russia,
+                orders: vec![parse_order("A war - gal").unwrap()],
+                probability: 0.5,
+                confidence: vec![0.5],
+                candidates: 4,
+            }],
+            iterations: 120,
+            elapsed: Duration::from_millis(900),
+            cache: Some(CacheStats {
+                lookups: 10,
+                hits: 4,
+                ..CacheStats::default()
+            }),
+            ..SearchResult::new(vec![parse_order("A vie - gal").unwrap()], 12.0, 300)
+        };
+        let mut out = Vec::new();
+        write_result_info(&mut out, &GameMap::standard(), &result).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(!text.contains("converged"));
+        assert!(lines[0].starts_with("info string future_cache lookups 10 hits 4 dedup 0.400"));
+
+        let json = lines[2].strip_prefix("info equilibrium ").unwrap();
+        let v: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(v["iterations"], 120);
+        assert_eq!(v["time"], 900);
+        assert_eq!(v["confidence"][0]["order"], "A vie - gal");
+        assert_eq!(v["confidence"][0]["p"], 0.75);
+        assert_eq!(v["opponents"][0]["power"], "russia");
+        assert_eq!(v["opponents"][0]["orders"], "A war - gal");
+        assert_eq!(v["opponents"][0]["candidates"], 4);
+    }
+
+    #[test]
+    fn writes_one_line_per_order_set() {
+        let result = SearchResult {
+            order_sets: vec![
+                RankedOrderSet {
+                    orders: vec![parse_order("A vie - gal").unwrap()],
+                    weight: 0.4123,
+                    score: Some(21.456),
+                },
+                RankedOrderSet {
+                    orders: vec![parse_order("A vie H").unwrap()],
+                    weight: 0.0,
+                    score: None,
+                },
+            ],
+            ..SearchResult::new(Vec::new(), 0.0, 0)
+        };
+        let mut out = Vec::new();
+        write_result_info(&mut out, &GameMap::standard(), &result).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "info multiorders 1 weight 0.412 score 21.46 pv A vie - gal\n\
+             info multiorders 2 weight 0.000 pv A vie H\n"
+        );
+    }
+}
@@ -4,10 +4,19 @@
 //! Universal Interface) protocol, including DFEN position encoding, DSON
 //! structured notation for orders, and the command parser for the main loop.
 
+pub mod convert;
+pub mod daide;
 pub mod dfen;
 pub mod dson;
+pub mod human;
+pub mod info;
 pub mod parser;
+pub mod record;
 
-pub use dfen::{encode_dfen, parse_dfen, DfenError};
-pub use dson::{format_order, format_orders, parse_order, parse_orders, DsonError};
+pub use dfen::{encode_dfen, parse_dfen, parse_dfen_on, DfenError};
+pub use dson::{
+    format_order, format_order_on, format_orders, format_orders_on, parse_order, parse_order_on,
+    parse_orders, parse_orders_for, parse_orders_on, DsonError,
+};
+pub use info::{equilibrium_json, line_level, write_filtered, write_result_info, InfoLevel};
 pub use parser::{parse_command, Command, GoParams};
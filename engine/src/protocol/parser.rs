@@ -4,6 +4,9 @@
 //! `Command` variants that the engine main loop can dispatch on.
 
 use crate::board::province::Power;
+use crate::board::Order;
+use crate::protocol::dson::parse_orders;
+use crate::search::replay::ReplayBlob;
 
 /// Search constraints passed with the `go` command.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,6 +15,11 @@ pub struct GoParams {
     pub depth: Option<u32>,
     pub nodes: Option<u64>,
     pub infinite: bool,
+    /// Think about the position while other players enter orders, without
+    /// answering until `ponderhit`, `go` or `stop`.
+    pub ponder: bool,
+    /// Under `setpower auto`, the power to play: `go focus <power>`.
+    pub focus: Option<Power>,
 }
 
 impl Default for GoParams {
@@ -21,10 +29,23 @@ impl Default for GoParams {
             depth: None,
             nodes: None,
             infinite: false,
+            ponder: false,
+            focus: None,
         }
     }
 }
 
+/// A `draw` command's message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawMessage {
+    /// A draw is proposed among these powers; empty means all survivors.
+    Propose { powers: Vec<Power> },
+    /// The proposed draw was agreed and the game is over.
+    Accept,
+    /// The proposed draw was turned down.
+    Reject,
+}
+
 /// A parsed server-to-engine DUI command.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Command {
@@ -46,15 +67,77 @@ pub enum Command {
     /// Set the active power for the current position.
     SetPower { power: Power },
 
+    /// `setpower auto`: let each `go` pick the power, by its `focus`
+    /// parameter or from the position.
+    SetPowerAuto,
+
     /// Begin calculating orders with optional search constraints.
     Go(GoParams),
 
     /// Interrupt the current search immediately.
     Stop,
 
+    /// The position pondered with `go ponder` is the one to play: answer
+    /// quickly from the pondered search.
+    PonderHit,
+
     /// Deliver a diplomatic press message (structured intent).
     Press { raw: String },
 
+    /// Export a per-province threat heat map as JSON for the given power
+    /// (or the active power when omitted).
+    ThreatMap { power: Option<Power> },
+
+    /// List immediate threats against the given power (or the active power
+    /// when omitted) with severity scores.
+    Dangers { power: Option<Power> },
+
+    /// Forecast how likely each province of the given power (or the active
+    /// power when omitted) is to be attacked next turn, and how strongly.
+    Forecast { power: Option<Power> },
+
+    /// Export the nearest-controller territory partition as JSON.
+    Territory,
+
+    /// Export the alliance and front matrix inferred from recent orders.
+    Alliances,
+
+    /// Explain why the last search rejected its top alternative candidates.
+    WhyNot,
+
+    /// Liveness check: `ping [<token>]`; engine replies `pong [<token>]`
+    /// at once, even mid-search.
+    Ping { token: Option<String> },
+
+    /// Fix orders for some of our units before `go`: `lockorder <dson>`.
+    /// `lockorder clear` parses to an empty list and drops all locks.
+    LockOrder { orders: Vec<Order> },
+
+    /// Offer an order set for our units to the next search: `suggest
+    /// <dson>`. `suggest clear` parses to an empty list and drops all
+    /// suggestions.
+    Suggest { orders: Vec<Order> },
+
+    /// Evaluate every DFEN in a file and write the scores as CSV:
+    /// `evalfile <path> [<csv_path>]`.
+    EvalFile {
+        path: String,
+        output: Option<String>,
+    },
+
+    /// Rerun the RM+ search a reproducibility blob describes, for bug
+    /// reports: `replay-search <blob>`.
+    ReplaySearch { blob: ReplayBlob },
+
+    /// Toggle observe mode: `observe on|off`. While on, every `position`
+    /// starts a background analysis streaming lines for all seven powers.
+    Observe { enabled: bool },
+
+    /// Draw voting: `draw propose [<power> ...]` asks the engine to vote on
+    /// a draw among the listed powers (all survivors when none are
+    /// listed); `draw accept` and `draw reject` report the vote's outcome.
+    Draw(DrawMessage),
+
     /// Terminate the engine process.
     Quit,
 }
@@ -80,12 +163,28 @@ pub fn parse_command(line: &str) -> Option<Command> {
         "quit" => Some(Command::Quit),
         "newgame" => Some(Command::NewGame),
         "stop" => Some(Command::Stop),
+        "ponderhit" => Some(Command::PonderHit),
+        "territory" => Some(Command::Territory),
+        "alliances" => Some(Command::Alliances),
+        "whynot" => Some(Command::WhyNot),
+        "ping" => Some(Command::Ping {
+            token: (tokens.len() > 1).then(|| tokens[1..].join(" ")),
+        }),
 
         "setoption" => parse_setoption(&tokens),
         "position" => parse_position(&tokens),
         "setpower" => parse_setpower(&tokens),
         "go" => parse_go(&tokens),
         "press" => parse_press(&tokens, trimmed),
+        "threatmap" => optional_power(&tokens).map(|power| Command::ThreatMap { power }),
+        "dangers" => optional_power(&tokens).map(|power| Command::Dangers { power }),
+        "forecast" => optional_power(&tokens).map(|power| Command::Forecast { power }),
+        "observe" => parse_observe(&tokens),
+        "draw" => parse_draw(&tokens),
+        "lockorder" => parse_lockorder(&tokens, trimmed),
+        "suggest" => parse_suggest(&tokens, trimmed),
+        "evalfile" => parse_evalfile(&tokens),
+        "replay-search" => parse_replay_search(&tokens),
 
         other => {
             eprintln!("unknown command: {}", other);
@@ -145,9 +244,12 @@ fn parse_position(tokens: &[&str]) -> Option<Command> {
 /// Parses `setpower <power>`.
 fn parse_setpower(tokens: &[&str]) -> Option<Command> {
     if tokens.len() < 2 {
-        eprintln!("malformed setpower: expected 'setpower <power>'");
+        eprintln!("malformed setpower: expected 'setpower <power>|auto'");
         return None;
     }
+    if tokens[1] == "auto" {
+        return Some(Command::SetPowerAuto);
+    }
     match Power::from_name(tokens[1]) {
         Some(power) => Some(Command::SetPower { power }),
         None => {
@@ -157,7 +259,79 @@ fn parse_setpower(tokens: &[&str]) -> Option<Command> {
     }
 }
 
-/// Parses `go [movetime <ms>] [depth <n>] [nodes <n>] [infinite]`.
+/// Parses `observe on|off`.
+fn parse_observe(tokens: &[&str]) -> Option<Command> {
+    match tokens.get(1).copied() {
+        Some("on") => Some(Command::Observe { enabled: true }),
+        Some("off") => Some(Command::Observe { enabled: false }),
+        _ => {
+            eprintln!("malformed observe: expected 'observe on|off'");
+            None
+        }
+    }
+}
+
+/// Parses `draw propose [<power> ...]`, `draw accept` or `draw reject`.
+fn parse_draw(tokens: &[&str]) -> Option<Command> {
+    let message = match tokens {
+        [_, "propose", powers @ ..] => {
+            let mut parties = Vec::new();
+            for name in powers {
+                match Power::from_name(name) {
+                    Some(power) if !parties.contains(&power) => parties.push(power),
+                    Some(_) => {}
+                    None => {
+                        eprintln!("unknown power: '{}'", name);
+                        return None;
+                    }
+                }
+            }
+            DrawMessage::Propose { powers: parties }
+        }
+        [_, "accept"] => DrawMessage::Accept,
+        [_, "reject"] => DrawMessage::Reject,
+        _ => {
+            eprintln!("malformed draw: expected 'draw propose [<power> ...]', 'draw accept' or 'draw reject'");
+            return None;
+        }
+    };
+    Some(Command::Draw(message))
+}
+
+/// Parses `evalfile <path> [<csv_path>]`.
+fn parse_evalfile(tokens: &[&str]) -> Option<Command> {
+    match tokens {
+        [_, path] => Some(Command::EvalFile {
+            path: path.to_string(),
+            output: None,
+        }),
+        [_, path, output] => Some(Command::EvalFile {
+            path: path.to_string(),
+            output: Some(output.to_string()),
+        }),
+        _ => {
+            eprintln!("malformed evalfile: expected 'evalfile <path> [<csv_path>]'");
+            None
+        }
+    }
+}
+
+/// Parses `replay-search <blob>`.
+fn parse_replay_search(tokens: &[&str]) -> Option<Command> {
+    let [_, blob] = tokens else {
+        eprintln!("malformed replay-search: expected 'replay-search <blob>'");
+        return None;
+    };
+    match blob.parse() {
+        Ok(blob) => Some(Command::ReplaySearch { blob }),
+        Err(e) => {
+            eprintln!("malformed replay-search: {}", e);
+            None
+        }
+    }
+}
+
+/// Parses `go [movetime <ms>] [depth <n>] [nodes <n>] [infinite] [ponder]`.
 fn parse_go(tokens: &[&str]) -> Option<Command> {
     let mut params = GoParams::default();
     let mut i = 1;
@@ -200,6 +374,20 @@ fn parse_go(tokens: &[&str]) -> Option<Command> {
             "infinite" => {
                 params.infinite = true;
             }
+            "ponder" => {
+                params.ponder = true;
+            }
+            "focus" => {
+                i += 1;
+                if i < tokens.len() {
+                    match Power::from_name(tokens[i]) {
+                        Some(power) => params.focus = Some(power),
+                        None => {
+                            eprintln!("unknown power: '{}'", tokens[i]);
+                        }
+                    }
+                }
+            }
             other => {
                 eprintln!("unknown go parameter: '{}'", other);
             }
@@ -226,6 +414,59 @@ fn parse_press(tokens: &[&str], full_line: &str) -> Option<Command> {
     Some(Command::Press { raw })
 }
 
+/// Parses `lockorder <dson>` or `lockorder clear`.
+fn parse_lockorder(tokens: &[&str], full_line: &str) -> Option<Command> {
+    if tokens.len() < 2 {
+        eprintln!("malformed lockorder: expected 'lockorder <orders>' or 'lockorder clear'");
+        return None;
+    }
+    if tokens.len() == 2 && tokens[1] == "clear" {
+        return Some(Command::LockOrder { orders: Vec::new() });
+    }
+    let dson = full_line.trim().strip_prefix("lockorder").unwrap_or("");
+    match parse_orders(dson) {
+        Ok(orders) => Some(Command::LockOrder { orders }),
+        Err(e) => {
+            eprintln!("malformed lockorder: {}", e);
+            None
+        }
+    }
+}
+
+/// Parses `suggest <dson>` or `suggest clear`.
+fn parse_suggest(tokens: &[&str], full_line: &str) -> Option<Command> {
+    if tokens.len() < 2 {
+        eprintln!("malformed suggest: expected 'suggest <orders>' or 'suggest clear'");
+        return None;
+    }
+    if tokens.len() == 2 && tokens[1] == "clear" {
+        return Some(Command::Suggest { orders: Vec::new() });
+    }
+    let dson = full_line.trim().strip_prefix("suggest").unwrap_or("");
+    match parse_orders(dson) {
+        Ok(orders) => Some(Command::Suggest { orders }),
+        Err(e) => {
+            eprintln!("malformed suggest: {}", e);
+            None
+        }
+    }
+}
+
+/// Parses the optional power argument of `threatmap`, `dangers` and
+/// `forecast`.
+fn optional_power(tokens: &[&str]) -> Option<Option<Power>> {
+    if tokens.len() < 2 {
+        return Some(None);
+    }
+    match Power::from_name(tokens[1]) {
+        Some(power) => Some(Some(power)),
+        None => {
+            eprintln!("unknown power: '{}'", tokens[1]);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,6 +583,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_setpower_auto_and_go_focus() {
+        assert_eq!(parse_command("setpower auto"), Some(Command::SetPowerAuto));
+        assert_eq!(
+            parse_command("go focus turkey movetime 500"),
+            Some(Command::Go(GoParams {
+                movetime: Some(500),
+                focus: Some(Power::Turkey),
+                ..GoParams::default()
+            }))
+        );
+        assert_eq!(
+            parse_command("go focus narnia"),
+            Some(Command::Go(GoParams::default()))
+        );
+    }
+
     #[test]
     fn parse_setpower_unknown_returns_none() {
         assert_eq!(parse_command("setpower narnia"), None);
@@ -412,8 +670,84 @@ mod tests {
                 depth: Some(3),
                 nodes: Some(100000),
                 infinite: false,
+                ponder: false,
+                focus: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_go_ponder_and_ponderhit() {
+        assert_eq!(
+            parse_command("go ponder movetime 800"),
+            Some(Command::Go(GoParams {
+                movetime: Some(800),
+                ponder: true,
+                ..GoParams::default()
+            }))
+        );
+        assert_eq!(parse_command("ponderhit"), Some(Command::PonderHit));
+    }
+
+    #[test]
+    fn parse_lockorder_command() {
+        let cmd = parse_command("lockorder A vie - gal ; F tri H").unwrap();
+        let Command::LockOrder { orders } = cmd else {
+            panic!("expected LockOrder, got {:?}", cmd);
+        };
+        assert_eq!(orders.len(), 2);
+        assert_eq!(
+            parse_command("lockorder clear"),
+            Some(Command::LockOrder { orders: Vec::new() })
+        );
+        assert_eq!(parse_command("lockorder"), None);
+        assert_eq!(parse_command("lockorder A vie jumps"), None);
+    }
+
+    #[test]
+    fn parse_suggest_command() {
+        let Some(Command::Suggest { orders }) = parse_command("suggest A vie - tyr ; F tri - adr")
+        else {
+            panic!("expected Suggest");
+        };
+        assert_eq!(orders.len(), 2);
+        assert_eq!(
+            parse_command("suggest clear"),
+            Some(Command::Suggest { orders: Vec::new() })
+        );
+        assert_eq!(parse_command("suggest"), None);
+    }
+
+    #[test]
+    fn parse_replay_search_command() {
+        let Some(Command::ReplaySearch { blob }) =
+            parse_command("replay-search v1.2a.ff.10.1440.7.0")
+        else {
+            panic!("expected replay-search");
+        };
+        assert_eq!((blob.seed, blob.iterations, blob.powers), (42, 1440, 7));
+        assert_eq!(parse_command("replay-search v1.2a"), None);
+        assert_eq!(parse_command("replay-search"), None);
+    }
+
+    #[test]
+    fn parse_evalfile_command() {
+        assert_eq!(
+            parse_command("evalfile positions.txt"),
+            Some(Command::EvalFile {
+                path: "positions.txt".to_string(),
+                output: None,
+            })
+        );
+        assert_eq!(
+            parse_command("evalfile positions.txt scores.csv"),
+            Some(Command::EvalFile {
+                path: "positions.txt".to_string(),
+                output: Some("scores.csv".to_string()),
             })
         );
+        assert_eq!(parse_command("evalfile"), None);
+        assert_eq!(parse_command("evalfile a b c"), None);
     }
 
     #[test]
@@ -432,6 +766,107 @@ mod tests {
         assert_eq!(parse_command("press"), None);
     }
 
+    #[test]
+    fn parse_threatmap_command() {
+        assert_eq!(
+            parse_command("threatmap"),
+            Some(Command::ThreatMap { power: None })
+        );
+        assert_eq!(
+            parse_command("threatmap france"),
+            Some(Command::ThreatMap {
+                power: Some(Power::France)
+            })
+        );
+        assert_eq!(parse_command("threatmap narnia"), None);
+    }
+
+    #[test]
+    fn parse_dangers_command() {
+        assert_eq!(
+            parse_command("dangers"),
+            Some(Command::Dangers { power: None })
+        );
+        assert_eq!(
+            parse_command("dangers turkey"),
+            Some(Command::Dangers {
+                power: Some(Power::Turkey)
+            })
+        );
+        assert_eq!(parse_command("dangers narnia"), None);
+        assert_eq!(
+            parse_command("forecast russia"),
+            Some(Command::Forecast {
+                power: Some(Power::Russia)
+            })
+        );
+        assert_eq!(
+            parse_command("forecast"),
+            Some(Command::Forecast { power: None })
+        );
+    }
+
+    #[test]
+    fn parse_territory_command() {
+        assert_eq!(parse_command("territory"), Some(Command::Territory));
+        assert_eq!(parse_command("alliances"), Some(Command::Alliances));
+    }
+
+    #[test]
+    fn parse_whynot_command() {
+        assert_eq!(parse_command("whynot"), Some(Command::WhyNot));
+    }
+
+    #[test]
+    fn parse_ping_command() {
+        assert_eq!(parse_command("ping"), Some(Command::Ping { token: None }));
+        assert_eq!(
+            parse_command("ping 42"),
+            Some(Command::Ping {
+                token: Some("42".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn parse_observe_command() {
+        assert_eq!(
+            parse_command("observe on"),
+            Some(Command::Observe { enabled: true })
+        );
+        assert_eq!(
+            parse_command("observe off"),
+            Some(Command::Observe { enabled: false })
+        );
+        assert_eq!(parse_command("observe"), None);
+        assert_eq!(parse_command("observe maybe"), None);
+    }
+
+    #[test]
+    fn parse_draw_commands() {
+        assert_eq!(
+            parse_command("draw propose"),
+            Some(Command::Draw(DrawMessage::Propose { powers: vec![] }))
+        );
+        assert_eq!(
+            parse_command("draw propose france turkey france"),
+            Some(Command::Draw(DrawMessage::Propose {
+                powers: vec![Power::France, Power::Turkey]
+            }))
+        );
+        assert_eq!(
+            parse_command("draw accept"),
+            Some(Command::Draw(DrawMessage::Accept))
+        );
+        assert_eq!(
+            parse_command("draw reject"),
+            Some(Command::Draw(DrawMessage::Reject))
+        );
+        assert_eq!(parse_command("draw"), None);
+        assert_eq!(parse_command("draw propose atlantis"), None);
+        assert_eq!(parse_command("draw accept now"), None);
+    }
+
     #[test]
     fn parse_with_leading_trailing_whitespace() {
         assert_eq!(parse_command("  dui  "), Some(Command::Dui));
@@ -0,0 +1,361 @@
+//! Game records.
+//!
+//! A [`GameRecord`] keeps a whole game in one place: the DFEN it started
+//! from, every phase's orders in DSON with their resolutions, and the final
+//! result. It is what replay, debugging and training-data export read. The
+//! text form is line-oriented, one fact per line:
+//!
+//! ```text
+//! initial 1901sm/Aavie,...,Ntun/-
+//! phase 1901sm
+//! orders austria: A vie - gal ; A bud - ser ; F tri - alb
+//! resolved austria: A vie - gal = bounced ; A bud - ser = succeeded ; F tri - alb = succeeded
+//! phase 1901fm
+//! orders austria: A vie H ; A ser - gre ; F alb S A ser - gre
+//! result solo austria
+//! ```
+//!
+//! `orders` lines hold what a power submitted and `resolved` lines what the
+//! adjudicator made of it, each order followed by its [`OrderResult`] name.
+//! Either may be missing for a power, e.g. when the engine only knows its
+//! own orders. Blank lines and lines starting with `#` are skipped.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::board::order::Order;
+use crate::board::province::Power;
+use crate::board::state::{BoardState, Phase, Season};
+use crate::protocol::dfen::{encode_dfen, parse_dfen, parse_phase_info};
+use crate::protocol::dson::{format_order, format_orders, parse_order, parse_orders};
+use crate::resolve::{OrderResult, ResolvedOrder};
+
+/// A problem with one line of a game record.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct RecordError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+/// Year, season and phase of a recorded phase.
+pub type PhaseKey = (u16, Season, Phase);
+
+/// Formats a phase key the way a DFEN does, e.g. `1901sm`.
+fn format_phase(key: PhaseKey) -> String {
+    format!("{}{}{}", key.0, key.1.dfen_char(), key.2.dfen_char())
+}
+
+/// How a finished game ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    Solo(Power),
+    /// A draw shared by the listed powers.
+    Draw(Vec<Power>),
+}
+
+/// The orders and resolutions of one phase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedPhase {
+    pub phase: PhaseKey,
+    /// Orders submitted per power, in order of recording.
+    pub orders: Vec<(Power, Vec<Order>)>,
+    /// Adjudicated orders, empty until the phase is resolved.
+    pub resolved: Vec<ResolvedOrder>,
+}
+
+impl RecordedPhase {
+    fn new(phase: PhaseKey) -> Self {
+        RecordedPhase {
+            phase,
+            orders: Vec::new(),
+            resolved: Vec::new(),
+        }
+    }
+
+    /// The orders recorded for `power`, if any.
+    pub fn orders_of(&self, power: Power) -> Option<&[Order]> {
+        self.orders
+            .iter()
+            .find(|(p, _)| *p == power)
+            .map(|(_, o)| o.as_slice())
+    }
+}
+
+/// A full game: initial position, phases and result.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameRecord {
+    /// DFEN of the first position, once one is known.
+    pub initial: Option<String>,
+    pub phases: Vec<RecordedPhase>,
+    /// None while the game is unfinished.
+    pub result: Option<GameResult>,
+}
+
+impl GameRecord {
+    /// An empty record.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Notes that play reached `state`: the first position becomes the
+    /// initial one, and a phase other than the last recorded one starts a
+    /// new phase.
+    pub fn observe_position(&mut self, state: &BoardState) {
+        if self.initial.is_none() {
+            self.initial = Some(encode_dfen(state));
+        }
+        self.phase_mut((state.year, state.season, state.phase));
+    }
+
+    /// Records `power`'s orders for the phase of `state`, replacing any
+    /// recorded earlier.
+    pub fn add_orders(&mut self, state: &BoardState, power: Power, orders: &[Order]) {
+        let phase = self.phase_mut((state.year, state.season, state.phase));
+        match phase.orders.iter_mut().find(|(p, _)| *p == power) {
+            Some((_, o)) => *o = orders.to_vec(),
+            None => phase.orders.push((power, orders.to_vec())),
+        }
+    }
+
+    /// Records the adjudication of the phase of `state`, replacing any
+    /// recorded earlier.
+    pub fn add_resolution(&mut self, state: &BoardState, resolved: &[ResolvedOrder]) {
+        self.phase_mut((state.year, state.season, state.phase))
+            .resolved = resolved.to_vec();
+    }
+
+    /// The recorded phase `key`, if any.
+    pub fn phase(&self, key: PhaseKey) -> Option<&RecordedPhase> {
+        self.phases.iter().rev().find(|p| p.phase == key)
+    }
+
+    /// The last phase if it is `key`, otherwise a new phase appended.
+    fn phase_mut(&mut self, key: PhaseKey) -> &mut RecordedPhase {
+        if self.phases.last().map(|p| p.phase) != Some(key) {
+            self.phases.push(RecordedPhase::new(key));
+        }
+        self.phases.last_mut().unwrap()
+    }
+
+    /// Parses a record; see the module docs for the format.
+    pub fn parse(text: &str) -> Result<Self, RecordError> {
+        let mut record = GameRecord::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| RecordError {
+                line: i + 1,
+                message,
+            };
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            match keyword {
+                "initial" => {
+                    parse_dfen(rest).map_err(|e| err(e.to_string()))?;
+                    record.initial = Some(rest.to_string());
+                }
+                "phase" => {
+                    let key = parse_phase_info(rest).map_err(|e| err(e.to_string()))?;
+                    record.phases.push(RecordedPhase::new(key));
+                }
+                "orders" | "resolved" => {
+                    let phase = record
+                        .phases
+                        .last_mut()
+                        .ok_or_else(|| err(format!("'{}' before any phase", keyword)))?;
+                    let (power, list) = rest
+                        .split_once(':')
+                        .ok_or_else(|| err(format!("expected '{} <power>: ...'", keyword)))?;
+                    let power = Power::from_name(power.trim())
+                        .ok_or_else(|| err(format!("unknown power '{}'", power.trim())))?;
+                    if keyword == "orders" {
+                        let list = list.trim();
+                        let orders = if list.is_empty() {
+                            Vec::new()
+                        } else {
+                            parse_orders(list).map_err(|e| err(e.to_string()))?
+                        };
+                        phase.orders.push((power, orders));
+                    } else {
+                        for entry in list.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+                            let (order, result) = entry.rsplit_once('=').ok_or_else(|| {
+                                err(format!("expected '<order> = <result>', got '{}'", entry))
+                            })?;
+                            let order =
+                                parse_order(order.trim()).map_err(|e| err(e.to_string()))?;
+                            let result =
+                                OrderResult::from_name(result.trim()).ok_or_else(|| {
+                                    err(format!("unknown result '{}'", result.trim()))
+                                })?;
+                            phase.resolved.push(ResolvedOrder {
+                                order,
+                                power,
+                                result,
+                            });
+                        }
+                    }
+                }
+                "result" => {
+                    let mut words = rest.split_whitespace();
+                    let powers = |words: std::str::SplitWhitespace| {
+                        words
+                            .map(|w| {
+                                Power::from_name(w)
+                                    .ok_or_else(|| err(format!("unknown power '{}'", w)))
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    };
+                    record.result = Some(match words.next() {
+                        Some("solo") => match powers(words)?.as_slice() {
+                            &[p] => GameResult::Solo(p),
+                            _ => return Err(err("a solo names one power".to_string())),
+                        },
+                        Some("draw") => GameResult::Draw(powers(words)?),
+                        _ => return Err(err("expected 'result solo|draw <powers>'".to_string())),
+                    });
+                }
+                _ => return Err(err(format!("unknown keyword '{}'", keyword))),
+            }
+        }
+        Ok(record)
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(initial) = &self.initial {
+            writeln!(f, "initial {}", initial)?;
+        }
+        for phase in &self.phases {
+            writeln!(f, "phase {}", format_phase(phase.phase))?;
+            for (power, orders) in &phase.orders {
+                writeln!(f, "orders {}: {}", power.name(), format_orders(orders))?;
+            }
+            // Resolutions are grouped by power in order of first appearance.
+            let mut powers: Vec<Power> = Vec::new();
+            for r in &phase.resolved {
+                if !powers.contains(&r.power) {
+                    powers.push(r.power);
+                }
+            }
+            for power in powers {
+                let entries: Vec<String> = phase
+                    .resolved
+                    .iter()
+                    .filter(|r| r.power == power)
+                    .map(|r| format!("{} = {}", format_order(&r.order), r.result.name()))
+                    .collect();
+                writeln!(f, "resolved {}: {}", power.name(), entries.join(" ; "))?;
+            }
+        }
+        match &self.result {
+            Some(GameResult::Solo(p)) => writeln!(f, "result solo {}", p.name())?,
+            Some(GameResult::Draw(powers)) => {
+                write!(f, "result draw")?;
+                for p in powers {
+                    write!(f, " {}", p.name())?;
+                }
+                writeln!(f)?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GameRecord::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolve::Resolver;
+
+    const DFEN: &str = "1901sm/Aavie,Aabud,Rawar/Avie,Abud,Rwar,Nrum,Nser/-";
+
+    fn played_record() -> GameRecord {
+        let state = parse_dfen(DFEN).unwrap();
+        let mut record = GameRecord::new();
+        record.observe_position(&state);
+        let austria = parse_orders("A vie - gal ; A bud - ser").unwrap();
+        let russia = parse_orders("A war - gal").unwrap();
+        record.add_orders(&state, Power::Austria, &austria);
+        record.add_orders(&state, Power::Russia, &russia);
+
+        let all: Vec<_> = austria
+            .iter()
+            .map(|&o| (o, Power::Austria))
+            .chain(russia.iter().map(|&o| (o, Power::Russia)))
+            .collect();
+        let (resolved, _) = Resolver::new(64).resolve(&all, &state);
+        record.add_resolution(&state, &resolved);
+
+        let fall = BoardState::empty(1901, Season::Fall, Phase::Movement);
+        record.observe_position(&fall);
+        record.result = Some(GameResult::Draw(vec![Power::Austria, Power::Russia]));
+        record
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let record = played_record();
+        let text = record.to_string();
+        let initial = encode_dfen(&parse_dfen(DFEN).unwrap());
+        assert!(text.starts_with(&format!("initial {}\nphase 1901sm\n", initial)));
+        assert!(text.contains("orders austria: A vie - gal ; A bud - ser\n"));
+        assert!(text.contains("resolved russia: A war - gal = bounced\n"));
+        assert!(text.ends_with("phase 1901fm\nresult draw austria russia\n"));
+        assert_eq!(GameRecord::parse(&text).unwrap(), record);
+    }
+
+    #[test]
+    fn orders_replace_earlier_ones_in_the_same_phase() {
+        let mut record = played_record();
+        let state = parse_dfen(DFEN).unwrap();
+        record.add_orders(&state, Power::Russia, &parse_orders("A war H").unwrap());
+        // The phase is no longer the last one, so it is recorded again.
+        assert_eq!(record.phases.len(), 3);
+        record.add_orders(&state, Power::Russia, &parse_orders("A war - sil").unwrap());
+        let last = record.phases.last().unwrap();
+        assert_eq!(
+            format_orders(last.orders_of(Power::Russia).unwrap()),
+            "A war - sil"
+        );
+        assert_eq!(
+            record
+                .phase((1901, Season::Fall, Phase::Movement))
+                .unwrap()
+                .orders
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn reports_the_bad_line() {
+        let cases = [
+            ("orders austria: A vie H", 1),
+            ("phase 1901sm\norders prussia: A ber H", 2),
+            ("phase 1901sm\nresolved austria: A vie H", 2),
+            ("phase 1901sm\nresolved austria: A vie H = won", 2),
+            ("# ok\nresult solo austria russia", 2),
+            ("initial 1901sm/Zavie/-/-", 1),
+            ("moves 1901sm", 1),
+        ];
+        for (text, line) in cases {
+            let err = GameRecord::parse(text).unwrap_err();
+            assert_eq!(err.line, line, "{}", text);
+        }
+    }
+}
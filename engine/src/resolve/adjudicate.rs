@@ -0,0 +1,309 @@
+//! Rulebook adjudication of submitted movement orders.
+//!
+//! The resolver assumes every order it is given is legal, which holds for
+//! the engine's own search but not for orders typed by people. When the
+//! engine serves as a rules backend, an illegal order is handled the way
+//! the rulebook says: the unit holds instead, and the order is reported as
+//! [`OrderResult::Invalid`] so a client can tell "you cannot order that"
+//! apart from "your order failed". A unit holding in place of an invalid
+//! order can still be supported and dislodged; if it is, it appears in the
+//! dislodged list while its order keeps the `Invalid` result.
+//!
+//! Orders for units the power does not have are also `Invalid`, and take no
+//! part in resolution. When a unit is ordered twice, the last order counts
+//! and the earlier ones are `Invalid`, matching [`super::sanitize_orders`].
+
+use crate::board::map::GameMap;
+use crate::board::{
+    BoardState, Coast, Location, Order, OrderUnit, Power, Province, ProvinceType, UnitType,
+    MAX_PROVINCES,
+};
+
+use super::kruijswijk::{DislodgedUnit, OrderResult, ResolvedOrder, Resolver};
+
+/// Returns true if the ordered unit can reach `dest` for support purposes.
+/// Coasts do not matter: a fleet on either coast supports into the province.
+fn can_reach(
+    map: &GameMap,
+    province: Province,
+    coast: Coast,
+    unit_type: UnitType,
+    dest: Province,
+) -> bool {
+    map.provinces_adjacent_to(province, coast, unit_type == UnitType::Fleet)
+        .contains(&dest)
+}
+
+/// Returns true if an army could be convoyed between the two provinces.
+fn convoyable(map: &GameMap, from: Province, to: Province) -> bool {
+    map.province_type(from) == ProvinceType::Coastal
+        && map.province_type(to) == ProvinceType::Coastal
+}
+
+/// Returns true if the unit at `province` could legally be ordered to move
+/// to `dest`, by land or sea for armies.
+fn can_move(
+    map: &GameMap,
+    province: Province,
+    coast: Coast,
+    unit_type: UnitType,
+    dest: Location,
+    via_convoy: bool,
+) -> bool {
+    if dest.province == province {
+        return false;
+    }
+    match unit_type {
+        UnitType::Army => {
+            map.province_type(dest.province) != ProvinceType::Sea
+                && (map.is_adjacent(province, coast, dest.province, Coast::None, false)
+                    || convoyable(map, province, dest.province))
+        }
+        UnitType::Fleet => {
+            if via_convoy || map.province_type(dest.province) == ProvinceType::Land {
+                return false;
+            }
+            let coasts = map.fleet_coasts_to(province, coast, dest.province);
+            if dest.coast == Coast::None {
+                // An unnamed coast is only legal when it is unambiguous.
+                !coasts.is_empty() && (!map.has_coasts(dest.province) || coasts.len() == 1)
+            } else {
+                coasts.contains(&dest.coast)
+            }
+        }
+    }
+}
+
+/// Returns true if `unit` names a unit on the board.
+fn unit_present(unit: &OrderUnit, state: &BoardState) -> bool {
+    matches!(state.units[unit.location.province.index()], Some((_, t)) if t == unit.unit_type)
+}
+
+/// Returns true if `order` is a legal movement-phase order for `power`.
+///
+/// Legality follows the rulebook, not the outcome: a move that will bounce,
+/// a convoyed move with no fleets in place, or a support for a move the
+/// supported unit was not ordered to make are all legal.
+pub fn is_legal_order(order: &Order, power: Power, state: &BoardState) -> bool {
+    let unit = match order {
+        Order::Hold { unit }
+        | Order::Move { unit, .. }
+        | Order::SupportHold { unit, .. }
+        | Order::SupportMove { unit, .. }
+        | Order::Convoy { unit, .. } => *unit,
+        _ => return false,
+    };
+    let province = unit.location.province;
+    if state.units[province.index()] != Some((power, unit.unit_type)) {
+        return false;
+    }
+    let coast = state.fleet_coast[province.index()].unwrap_or(Coast::None);
+    if unit.location.coast != Coast::None && unit.location.coast != coast {
+        return false;
+    }
+
+    match *order {
+        Order::Hold { .. } => true,
+        Order::Move {
+            dest, via_convoy, ..
+        } => can_move(
+            state.map(),
+            province,
+            coast,
+            unit.unit_type,
+            dest,
+            via_convoy,
+        ),
+        Order::SupportHold { supported, .. } => {
+            let target = supported.location.province;
+            target != province
+                && unit_present(&supported, state)
+                && can_reach(state.map(), province, coast, unit.unit_type, target)
+        }
+        Order::SupportMove {
+            supported, dest, ..
+        } => {
+            let from = supported.location.province;
+            let from_coast = state.fleet_coast[from.index()].unwrap_or(Coast::None);
+            from != province
+                && dest.province != province
+                && unit_present(&supported, state)
+                && can_reach(state.map(), province, coast, unit.unit_type, dest.province)
+                && can_move(
+                    state.map(),
+                    from,
+                    from_coast,
+                    supported.unit_type,
+                    Location::new(dest.province),
+                    false,
+                )
+        }
+        Order::Convoy {
+            convoyed_from,
+            convoyed_to,
+            ..
+        } => {
+            unit.unit_type == UnitType::Fleet
+                && state.map().province_type(province) == ProvinceType::Sea
+                && convoyed_from.province != convoyed_to.province
+                && state.units[convoyed_from.province.index()]
+                    .is_some_and(|(_, t)| t == UnitType::Army)
+                && convoyable(state.map(), convoyed_from.province, convoyed_to.province)
+        }
+        _ => false,
+    }
+}
+
+/// Adjudicates submitted movement orders under rulebook legality.
+///
+/// Returns one result per input order, in input order, plus the dislodged
+/// units. Legal orders resolve exactly as with [`super::resolve_orders`];
+/// illegal ones are replaced by a hold for resolution and reported as
+/// [`OrderResult::Invalid`].
+pub fn adjudicate_movement(
+    orders: &[(Order, Power)],
+    state: &BoardState,
+) -> (Vec<ResolvedOrder>, Vec<DislodgedUnit>) {
+    // Index of the last order for each unit the power actually has.
+    let mut last = [None::<usize>; MAX_PROVINCES];
+    for (i, (order, power)) in orders.iter().enumerate() {
+        if let Some(unit) = order.unit() {
+            let idx = unit.location.province.index();
+            if matches!(state.units[idx], Some((p, _)) if p == *power) {
+                last[idx] = Some(i);
+            }
+        }
+    }
+
+    // The order set actually resolved, and where each input order went.
+    let mut resolved_input = Vec::with_capacity(orders.len());
+    let mut slot = vec![None; orders.len()];
+    let mut legal = vec![false; orders.len()];
+    for (i, &(order, power)) in orders.iter().enumerate() {
+        let Some(unit) = order.unit() else { continue };
+        let idx = unit.location.province.index();
+        if last[idx] != Some(i) {
+            continue;
+        }
+        legal[i] = is_legal_order(&order, power, state);
+        let effective = if legal[i] {
+            order
+        } else {
+            let (_, unit_type) = state.units[idx].expect("last order names a present unit");
+            Order::Hold {
+                unit: OrderUnit {
+                    unit_type,
+                    location: Location::with_coast(
+                        unit.location.province,
+                        state.fleet_coast[idx].unwrap_or(Coast::None),
+                    ),
+                },
+            }
+        };
+        slot[i] = Some(resolved_input.len());
+        resolved_input.push((effective, power));
+    }
+
+    let mut resolver = Resolver::new(resolved_input.len());
+    let (resolved, dislodged) = resolver.resolve(&resolved_input, state);
+
+    let results = orders
+        .iter()
+        .enumerate()
+        .map(|(i, &(order, power))| match slot[i] {
+            Some(j) if legal[i] => resolved[j],
+            _ => ResolvedOrder {
+                order,
+                power,
+                result: OrderResult::Invalid,
+            },
+        })
+        .collect();
+    (results, dislodged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn order(s: &str) -> Order {
+        parse_order(s).unwrap()
+    }
+
+    #[test]
+    fn legality_follows_the_rulebook() {
+        let state = parse_dfen(
+            "1901sm/Aavie,Aabud,Aftri,Eflon,Eayor,Ffmao,Rfstp.sc,Tacon/Avie,Atri,Elon,Rstp,Tcon/-",
+        )
+        .unwrap();
+        let legal = |s: &str, p: Power| is_legal_order(&order(s), p, &state);
+
+        assert!(legal("A vie - gal", Power::Austria));
+        assert!(legal("F tri H", Power::Austria));
+        assert!(legal("A yor - nwy", Power::England)); // convoyable
+        assert!(legal("F stp/sc - bot", Power::Russia));
+        assert!(legal("A vie S A bud - gal", Power::Austria)); // unordered but legal
+        assert!(legal("F mao C A yor - bre", Power::France)); // no fleet chain, still legal
+
+        assert!(!legal("A vie - war", Power::Austria)); // not adjacent, inland
+        assert!(!legal("F lon C A yor - bel", Power::England)); // lon is not a sea
+        assert!(!legal("A vie - gal", Power::Russia)); // not Russia's unit
+        assert!(!legal("A tri H", Power::Austria)); // wrong unit type
+        assert!(!legal("F stp/nc - bar", Power::Russia)); // wrong coast
+        assert!(!legal("F mao - spa", Power::France)); // ambiguous coast
+        assert!(!legal("F tri S A vie - war", Power::Austria)); // tri cannot reach war
+        assert!(!legal("A con S A smy H", Power::Turkey)); // nothing in smy
+        assert!(!legal("A vie R boh", Power::Austria)); // not a movement order
+    }
+
+    #[test]
+    fn illegal_orders_hold_and_report_invalid() {
+        let state = parse_dfen("1901sm/Aavie,Aabud,Rawar,Ragal/Avie,Abud,Rwar/-").unwrap();
+        let orders = vec![
+            (order("A vie - war"), Power::Austria), // illegal: holds in vie
+            (order("A bud S A vie H"), Power::Austria),
+            (order("A gal - vie"), Power::Russia),
+            (order("A war S A gal - vie"), Power::Russia), // illegal: war cannot reach vie
+            (order("A mos H"), Power::Russia),             // no unit
+        ];
+        let (results, dislodged) = adjudicate_movement(&orders, &state);
+
+        assert_eq!(results.len(), orders.len());
+        let outcome: Vec<_> = results.iter().map(|r| r.result).collect();
+        assert_eq!(
+            outcome,
+            vec![
+                OrderResult::Invalid,
+                OrderResult::Succeeded,
+                OrderResult::Bounced,
+                OrderResult::Invalid,
+                OrderResult::Invalid,
+            ]
+        );
+        assert_eq!(
+            results[0].order, orders[0].0,
+            "the submitted order is reported"
+        );
+        assert!(dislodged.is_empty(), "the supported hold in vie stands");
+    }
+
+    #[test]
+    fn earlier_duplicate_is_invalid_and_invalid_unit_can_be_dislodged() {
+        let state = parse_dfen("1901sm/Aavie,Ragal,Rabud/Avie/-").unwrap();
+        let orders = vec![
+            (order("A vie - tyr"), Power::Austria),
+            (order("A vie - mos"), Power::Austria), // last order wins, and is illegal
+            (order("A gal - vie"), Power::Russia),
+            (order("A bud S A gal - vie"), Power::Russia),
+        ];
+        let (results, dislodged) = adjudicate_movement(&orders, &state);
+
+        assert_eq!(results[0].result, OrderResult::Invalid);
+        assert_eq!(results[1].result, OrderResult::Invalid);
+        assert_eq!(results[2].result, OrderResult::Succeeded);
+        assert_eq!(dislodged.len(), 1);
+        assert_eq!(dislodged[0].province, Province::Vie);
+    }
+}
@@ -4,13 +4,41 @@
 //! Handles civil disorder (auto-disband units furthest from home when
 //! insufficient disband orders are submitted).
 
+use thiserror::Error;
+
+use crate::board::map::GameMap;
 use crate::board::{
-    BoardState, Coast, Location, Order, OrderUnit, Power, Province, UnitType, ALL_POWERS,
-    ALL_PROVINCES, PROVINCE_COUNT,
+    BoardState, Coast, Delta, Location, Order, OrderUnit, Power, Province, ProvinceType, UnitType,
+    ALL_POWERS, MAX_PROVINCES,
 };
 
 use super::kruijswijk::OrderResult;
 
+/// Reasons a build order is illegal.
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum BuildError {
+    #[error("not a build order")]
+    NotABuild,
+
+    #[error("{} is not a home supply center of the building power", .0.abbr())]
+    NotHomeCenter(Province),
+
+    #[error("{} is not currently owned by the building power", .0.abbr())]
+    NotOwned(Province),
+
+    #[error("{} is occupied", .0.abbr())]
+    Occupied(Province),
+
+    #[error("cannot build a fleet in inland {}", .0.abbr())]
+    FleetInland(Province),
+
+    #[error("fleet build in {} must name a coast", .0.abbr())]
+    MissingCoast(Province),
+
+    #[error("{} has no coast '{}' for this build", .0.abbr(), .1.abbr())]
+    InvalidCoast(Province, Coast),
+}
+
 /// The result of resolving a build/disband order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BuildResult {
@@ -51,7 +79,7 @@ pub fn resolve_builds(orders: &[(Order, Power)], state: &BoardState) -> Vec<Buil
                             });
                             continue;
                         }
-                        if validate_build(&order, power, state) {
+                        if validate_build(&order, power, state).is_ok() {
                             results.push(BuildResult {
                                 order,
                                 power,
@@ -144,40 +172,50 @@ pub fn resolve_builds(orders: &[(Order, Power)], state: &BoardState) -> Vec<Buil
 }
 
 /// Validates a build order against the board state.
-fn validate_build(order: &Order, power: Power, state: &BoardState) -> bool {
+///
+/// Fleets built in a split-coast province (Stp) must name one of its coasts;
+/// armies, and fleets elsewhere, must not name a coast.
+pub fn validate_build(order: &Order, power: Power, state: &BoardState) -> Result<(), BuildError> {
     let unit = match order {
         Order::Build { unit } => unit,
-        _ => return false,
+        _ => return Err(BuildError::NotABuild),
     };
 
     let prov = unit.location.province;
-    let idx = prov as usize;
+    let coast = unit.location.coast;
+    let idx = prov.index();
 
     // Must be a home supply center for this power.
-    if prov.home_power() != Some(power) {
-        return false;
-    }
-    if !prov.is_supply_center() {
-        return false;
+    if state.map().home_power(prov) != Some(power) || !state.map().is_supply_center(prov) {
+        return Err(BuildError::NotHomeCenter(prov));
     }
 
     // Must be currently owned by this power.
     if state.sc_owner[idx] != Some(power) {
-        return false;
+        return Err(BuildError::NotOwned(prov));
     }
 
     // Must be unoccupied.
     if state.units[idx].is_some() {
-        return false;
+        return Err(BuildError::Occupied(prov));
     }
 
-    // Fleet cannot be built in inland province.
-    if unit.unit_type == UnitType::Fleet && prov.province_type() == crate::board::ProvinceType::Land
-    {
-        return false;
+    match unit.unit_type {
+        UnitType::Fleet if state.map().province_type(prov) == ProvinceType::Land => {
+            Err(BuildError::FleetInland(prov))
+        }
+        UnitType::Fleet if state.map().has_coasts(prov) => {
+            if coast == Coast::None {
+                Err(BuildError::MissingCoast(prov))
+            } else if !state.map().coasts(prov).contains(&coast) {
+                Err(BuildError::InvalidCoast(prov, coast))
+            } else {
+                Ok(())
+            }
+        }
+        _ if coast != Coast::None => Err(BuildError::InvalidCoast(prov, coast)),
+        _ => Ok(()),
     }
-
-    true
 }
 
 /// Validates a disband order against the board state.
@@ -188,7 +226,7 @@ fn validate_disband(order: &Order, power: Power, state: &BoardState) -> bool {
     };
 
     let prov = unit.location.province;
-    let idx = prov as usize;
+    let idx = prov.index();
 
     // Must have a unit of this power at the location.
     match state.units[idx] {
@@ -204,27 +242,50 @@ fn civil_disorder(
     state: &BoardState,
     existing_results: &[BuildResult],
 ) -> Vec<BuildResult> {
+    // Orders already disbanding units.
+    let submitted: Vec<Order> = existing_results
+        .iter()
+        .filter(|r| r.power == power && r.result == OrderResult::Succeeded)
+        .map(|r| r.order)
+        .collect();
+    default_disbands(power, count, state, &submitted)
+        .into_iter()
+        .map(|order| BuildResult {
+            order,
+            power,
+            result: OrderResult::Succeeded,
+        })
+        .collect()
+}
+
+/// The disbands civil disorder would order for `power`: its `count` units
+/// furthest from a home supply center, passing over the units `submitted`
+/// already disbands. Fewer when it has fewer units left.
+pub fn default_disbands(
+    power: Power,
+    count: usize,
+    state: &BoardState,
+    submitted: &[Order],
+) -> Vec<Order> {
     // Collect provinces already being disbanded by submitted orders.
-    let mut already_disbanded = [false; PROVINCE_COUNT];
-    for r in existing_results {
-        if r.power == power && r.result == OrderResult::Succeeded {
-            if let Order::Disband { unit } = r.order {
-                already_disbanded[unit.location.province as usize] = true;
-            }
+    let mut already_disbanded = [false; MAX_PROVINCES];
+    for order in submitted {
+        if let Order::Disband { unit } = order {
+            already_disbanded[unit.location.province.index()] = true;
         }
     }
 
     // Collect the power's units that aren't already being disbanded.
     let mut unit_dists: Vec<(Province, UnitType, Coast, i32)> = Vec::new();
-    for i in 0..PROVINCE_COUNT {
+    for &prov in state.map().provinces() {
+        let i = prov.index();
         if already_disbanded[i] {
             continue;
         }
         if let Some((p, ut)) = state.units[i] {
             if p == power {
-                let prov = ALL_PROVINCES[i];
                 let coast = state.fleet_coast[i].unwrap_or(Coast::None);
-                let dist = min_distance_to_home(prov, power);
+                let dist = min_distance_to_home(state.map(), prov, power);
                 unit_dists.push((prov, ut, coast, dist));
             }
         }
@@ -232,43 +293,37 @@ fn civil_disorder(
 
     // Sort by distance descending (disband furthest first),
     // then by province index for determinism.
-    unit_dists.sort_by(|a, b| b.3.cmp(&a.3).then_with(|| (b.0 as u8).cmp(&(a.0 as u8))));
+    unit_dists.sort_by(|a, b| b.3.cmp(&a.3).then_with(|| b.0.index().cmp(&a.0.index())));
 
-    let mut results = Vec::new();
-    for i in 0..count.min(unit_dists.len()) {
-        let (prov, ut, coast, _) = unit_dists[i];
-        results.push(BuildResult {
-            order: Order::Disband {
-                unit: OrderUnit {
-                    unit_type: ut,
-                    location: Location::with_coast(prov, coast),
-                },
+    unit_dists
+        .iter()
+        .take(count)
+        .map(|&(prov, ut, coast, _)| Order::Disband {
+            unit: OrderUnit {
+                unit_type: ut,
+                location: Location::with_coast(prov, coast),
             },
-            power,
-            result: OrderResult::Succeeded,
-        });
-    }
-
-    results
+        })
+        .collect()
 }
 
 /// Computes minimum BFS distance from a province to any home supply center of the power.
-fn min_distance_to_home(from: Province, power: Power) -> i32 {
+fn min_distance_to_home(map: &GameMap, from: Province, power: Power) -> i32 {
     // Collect home SCs.
-    let mut is_home = [false; PROVINCE_COUNT];
-    for prov in &ALL_PROVINCES {
-        if prov.is_supply_center() && prov.home_power() == Some(power) {
-            is_home[*prov as usize] = true;
+    let mut is_home = [false; MAX_PROVINCES];
+    for prov in map.provinces() {
+        if map.is_supply_center(*prov) && map.home_power(*prov) == Some(power) {
+            is_home[prov.index()] = true;
         }
     }
 
-    if is_home[from as usize] {
+    if is_home[from.index()] {
         return 0;
     }
 
     // BFS using all adjacencies (army-passable).
-    let mut visited = [false; PROVINCE_COUNT];
-    visited[from as usize] = true;
+    let mut visited = [false; MAX_PROVINCES];
+    visited[from.index()] = true;
     let mut queue: Vec<Province> = vec![from];
     let mut dist = 0;
 
@@ -277,18 +332,15 @@ fn min_distance_to_home(from: Province, power: Power) -> i32 {
         let mut next_queue = Vec::new();
         for prov in &queue {
             // Use both army and fleet adjacencies for distance calculation.
-            for adj in crate::board::ADJACENCIES.iter() {
-                if adj.from != *prov {
-                    continue;
-                }
+            for adj in map.adj_from(*prov) {
                 let to = adj.to;
-                if visited[to as usize] {
+                if visited[to.index()] {
                     continue;
                 }
-                if is_home[to as usize] {
+                if is_home[to.index()] {
                     return dist;
                 }
-                visited[to as usize] = true;
+                visited[to.index()] = true;
                 next_queue.push(to);
             }
         }
@@ -300,6 +352,12 @@ fn min_distance_to_home(from: Province, power: Power) -> i32 {
 
 /// Applies resolved build results to the board state.
 pub fn apply_builds(state: &mut BoardState, results: &[BuildResult]) {
+    let _ = state.apply(&build_delta(state, results));
+}
+
+/// The changes [`apply_builds`] makes, to apply and undo in place.
+pub fn build_delta(state: &BoardState, results: &[BuildResult]) -> Delta {
+    let mut delta = Delta::new();
     for r in results {
         if r.result != OrderResult::Succeeded {
             continue;
@@ -307,15 +365,15 @@ pub fn apply_builds(state: &mut BoardState, results: &[BuildResult]) {
         match r.order {
             Order::Build { unit } => {
                 let dst = unit.location.province;
-                state.units[dst as usize] = Some((r.power, unit.unit_type));
-                if unit.location.coast != Coast::None {
-                    state.fleet_coast[dst as usize] = Some(unit.location.coast);
-                }
+                let coast = if unit.location.coast != Coast::None {
+                    Some(unit.location.coast)
+                } else {
+                    delta.unit_at(state, dst).1
+                };
+                delta.set_unit(dst, Some((r.power, unit.unit_type)), coast);
             }
             Order::Disband { unit } => {
-                let src = unit.location.province;
-                state.units[src as usize] = None;
-                state.fleet_coast[src as usize] = None;
+                delta.set_unit(unit.location.province, None, None);
             }
             Order::Waive => {
                 // No board state change.
@@ -323,6 +381,7 @@ pub fn apply_builds(state: &mut BoardState, results: &[BuildResult]) {
             _ => {}
         }
     }
+    delta
 }
 
 /// Counts supply centers owned by the given power.
@@ -569,7 +628,7 @@ mod tests {
 
         apply_builds(&mut state, &results);
         assert_eq!(
-            state.units[Province::Vie as usize],
+            state.units[Province::Vie.index()],
             Some((Power::Austria, UnitType::Army))
         );
     }
@@ -591,7 +650,7 @@ mod tests {
         }];
 
         apply_builds(&mut state, &results);
-        assert!(state.units[Province::Bud as usize].is_none());
+        assert!(state.units[Province::Bud.index()].is_none());
     }
 
     #[test]
@@ -612,12 +671,65 @@ mod tests {
 
         apply_builds(&mut state, &results);
         assert_eq!(
-            state.units[Province::Stp as usize],
+            state.units[Province::Stp.index()],
             Some((Power::Russia, UnitType::Fleet))
         );
+        assert_eq!(state.fleet_coast[Province::Stp.index()], Some(Coast::South));
+    }
+
+    #[test]
+    fn fleet_build_in_stp_requires_coast() {
+        let mut state = build_state();
+        state.set_sc_owner(Province::Stp, Some(Power::Russia));
+        let fleet = |coast| Order::Build {
+            unit: OrderUnit {
+                unit_type: UnitType::Fleet,
+                location: Location::with_coast(Province::Stp, coast),
+            },
+        };
+
+        assert_eq!(
+            validate_build(&fleet(Coast::None), Power::Russia, &state),
+            Err(BuildError::MissingCoast(Province::Stp))
+        );
+        assert_eq!(
+            validate_build(&fleet(Coast::East), Power::Russia, &state),
+            Err(BuildError::InvalidCoast(Province::Stp, Coast::East))
+        );
         assert_eq!(
-            state.fleet_coast[Province::Stp as usize],
-            Some(Coast::South)
+            validate_build(&fleet(Coast::North), Power::Russia, &state),
+            Ok(())
+        );
+
+        let results = resolve_builds(&[(fleet(Coast::None), Power::Russia)], &state);
+        assert_eq!(results[0].result, OrderResult::Failed);
+        let results = resolve_builds(&[(fleet(Coast::South), Power::Russia)], &state);
+        assert_eq!(results[0].result, OrderResult::Succeeded);
+    }
+
+    #[test]
+    fn coast_rejected_where_none_applies() {
+        let mut state = build_state();
+        setup_austria_sc(&mut state);
+        let army = Order::Build {
+            unit: OrderUnit {
+                unit_type: UnitType::Army,
+                location: Location::with_coast(Province::Tri, Coast::North),
+            },
+        };
+        assert_eq!(
+            validate_build(&army, Power::Austria, &state),
+            Err(BuildError::InvalidCoast(Province::Tri, Coast::North))
+        );
+        let fleet = Order::Build {
+            unit: OrderUnit {
+                unit_type: UnitType::Fleet,
+                location: Location::new(Province::Vie),
+            },
+        };
+        assert_eq!(
+            validate_build(&fleet, Power::Austria, &state),
+            Err(BuildError::FleetInland(Province::Vie))
         );
     }
 
@@ -640,11 +752,17 @@ mod tests {
     #[test]
     fn min_distance_to_home_works() {
         // Vienna is an Austrian home SC.
-        assert_eq!(min_distance_to_home(Province::Vie, Power::Austria), 0);
+        assert_eq!(
+            min_distance_to_home(&GameMap::standard(), Province::Vie, Power::Austria),
+            0
+        );
         // Boh is adjacent to Vie.
-        assert_eq!(min_distance_to_home(Province::Boh, Power::Austria), 1);
+        assert_eq!(
+            min_distance_to_home(&GameMap::standard(), Province::Boh, Power::Austria),
+            1
+        );
         // Greece is far from Austrian home.
-        let gre_dist = min_distance_to_home(Province::Gre, Power::Austria);
+        let gre_dist = min_distance_to_home(&GameMap::standard(), Province::Gre, Power::Austria);
         assert!(gre_dist >= 2);
     }
 }
@@ -0,0 +1,225 @@
+//! Two-power duels on the standard map.
+//!
+//! A duel keeps two powers on the board and removes the other five: their
+//! units are taken off and their centers become neutral. It gives resolver
+//! and search behaviour a simple adversarial setting to study and test,
+//! without five other powers moving the position around. A duel ends when
+//! one side is eliminated (no units and no centers) or holds
+//! [`Duel::centers`] supply centers, which defaults to the standard 18.
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::board::province::{Power, Province, MAX_PROVINCES};
+use crate::board::state::BoardState;
+
+/// Supply centers a duellist needs to win by default.
+pub const DUEL_CENTERS: u32 = 18;
+
+/// Why a duel could not be set up.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DuelError {
+    #[error("a duel needs two different powers")]
+    SamePower,
+
+    #[error("unknown power '{0}'")]
+    UnknownPower(String),
+
+    #[error("expected two powers as <power>,<power>, got '{0}'")]
+    Format(String),
+}
+
+/// The two powers of a duel and its victory threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Duel {
+    powers: [Power; 2],
+    centers: u32,
+}
+
+impl Duel {
+    /// A duel between `a` and `b`, won with [`DUEL_CENTERS`] centers.
+    pub fn new(a: Power, b: Power) -> Result<Self, DuelError> {
+        if a == b {
+            return Err(DuelError::SamePower);
+        }
+        Ok(Duel {
+            powers: [a, b],
+            centers: DUEL_CENTERS,
+        })
+    }
+
+    /// The same duel, won with `centers` supply centers.
+    pub fn with_centers(mut self, centers: u32) -> Self {
+        self.centers = centers.max(1);
+        self
+    }
+
+    /// The two duellists.
+    pub fn powers(&self) -> [Power; 2] {
+        self.powers
+    }
+
+    /// Supply centers a duellist needs to win.
+    pub fn centers(&self) -> u32 {
+        self.centers
+    }
+
+    /// Whether `power` takes part in the duel.
+    pub fn includes(&self, power: Power) -> bool {
+        self.powers.contains(&power)
+    }
+
+    /// Removes every other power from `state`: their units and dislodged
+    /// units are taken off and their centers become neutral.
+    pub fn restrict(&self, state: &mut BoardState) {
+        for idx in 0..MAX_PROVINCES {
+            let prov = Province::from_index(idx);
+            if matches!(state.units[idx], Some((p, _)) if !self.includes(p)) {
+                state.remove_unit(prov);
+            }
+            if matches!(state.dislodged[idx], Some(d) if !self.includes(d.power)) {
+                state.dislodged[idx] = None;
+            }
+            if matches!(state.sc_owner[idx], Some(p) if !self.includes(p)) {
+                state.set_sc_owner(prov, None);
+            }
+        }
+    }
+
+    /// The duel's winner in `state`, if it is decided: the duellist holding
+    /// [`Duel::centers`] centers, or the survivor once the other has
+    /// neither units nor centers.
+    pub fn winner(&self, state: &BoardState) -> Option<Power> {
+        let centers = |p: Power| state.sc_owner.iter().filter(|&&o| o == Some(p)).count() as u32;
+        let alive = |p: Power| {
+            centers(p) > 0
+                || state
+                    .units
+                    .iter()
+                    .any(|u| matches!(u, Some((o, _)) if *o == p))
+        };
+        let [a, b] = self.powers;
+        if let Some(&p) = self.powers.iter().find(|&&p| centers(p) >= self.centers) {
+            return Some(p);
+        }
+        match (alive(a), alive(b)) {
+            (true, false) => Some(a),
+            (false, true) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Duel {
+    type Err = DuelError;
+
+    /// Parses `<power>,<power>`, e.g. `austria,russia`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (a, b) = s
+            .split_once(',')
+            .ok_or_else(|| DuelError::Format(s.to_string()))?;
+        let power = |name: &str| {
+            let name = name.trim();
+            Power::from_name(name).ok_or_else(|| DuelError::UnknownPower(name.to_string()))
+        };
+        Duel::new(power(a)?, power(b)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::order::Order;
+    use crate::board::province::{Province, ALL_PROVINCES};
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+    use crate::resolve::{apply_resolution, OrderResult, Resolver};
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    fn duel_start() -> (Duel, BoardState) {
+        let duel: Duel = "austria,russia".parse().unwrap();
+        let mut state = parse_dfen(INITIAL_DFEN).unwrap();
+        duel.restrict(&mut state);
+        (duel, state)
+    }
+
+    #[test]
+    fn parses_two_distinct_powers() {
+        let duel: Duel = " austria , turkey".parse().unwrap();
+        assert_eq!(duel.powers(), [Power::Austria, Power::Turkey]);
+        assert_eq!(duel.centers(), DUEL_CENTERS);
+        assert_eq!("austria,austria".parse::<Duel>(), Err(DuelError::SamePower));
+        assert_eq!(
+            "austria,prussia".parse::<Duel>(),
+            Err(DuelError::UnknownPower("prussia".to_string()))
+        );
+        assert!(matches!(
+            "austria".parse::<Duel>(),
+            Err(DuelError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn restrict_removes_the_other_powers() {
+        let (duel, state) = duel_start();
+        let units: Vec<Power> = state.units.iter().flatten().map(|&(p, _)| p).collect();
+        assert_eq!(units.len(), 7);
+        assert!(units.iter().all(|&p| duel.includes(p)));
+        let owners: Vec<Power> = state.sc_owner.iter().flatten().copied().collect();
+        assert_eq!(owners.len(), 7);
+        assert!(owners.iter().all(|&p| duel.includes(p)));
+        assert_eq!(state.sc_owner[Province::Ber.index()], None);
+        assert_eq!(duel.winner(&state), None);
+    }
+
+    #[test]
+    fn supported_attack_wins_gal_in_a_duel() {
+        let (_, mut state) = duel_start();
+        let orders: Vec<_> = [
+            ("A bud - gal", Power::Austria),
+            ("A vie S A bud - gal", Power::Austria),
+            ("A war - gal", Power::Russia),
+        ]
+        .iter()
+        .map(|&(o, p)| (parse_order(o).unwrap(), p))
+        .collect();
+        let mut resolver = Resolver::new(64);
+        let (results, dislodged) = resolver.resolve(&orders, &state);
+        let result_of = |p: Power| {
+            results
+                .iter()
+                .find(|r| r.power == p && matches!(r.order, Order::Move { .. }))
+                .map(|r| r.result)
+        };
+        assert_eq!(result_of(Power::Austria), Some(OrderResult::Succeeded));
+        assert_eq!(result_of(Power::Russia), Some(OrderResult::Bounced));
+        apply_resolution(&mut state, &results, &dislodged);
+        assert!(matches!(
+            state.units[Province::Gal.index()],
+            Some((Power::Austria, _))
+        ));
+    }
+
+    #[test]
+    fn winner_by_centers_or_elimination() {
+        let (duel, mut state) = duel_start();
+        let small = duel.with_centers(5);
+        assert_eq!(small.winner(&state), None);
+        state.set_sc_owner(Province::Ser, Some(Power::Austria));
+        state.set_sc_owner(Province::Rum, Some(Power::Austria));
+        assert_eq!(small.winner(&state), Some(Power::Austria));
+        assert_eq!(duel.winner(&state), None);
+
+        for &prov in ALL_PROVINCES.iter() {
+            if matches!(state.units[prov.index()], Some((Power::Austria, _))) {
+                state.remove_unit(prov);
+            }
+            if state.sc_owner[prov.index()] == Some(Power::Austria) {
+                state.set_sc_owner(prov, Some(Power::Russia));
+            }
+        }
+        assert_eq!(duel.winner(&state), Some(Power::Russia));
+    }
+}
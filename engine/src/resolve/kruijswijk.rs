@@ -4,20 +4,66 @@
 //! Uses an optimistic initial guess (all moves succeed) and iterates until
 //! a consistent resolution is found.
 
-use crate::board::adjacency::is_adjacent_fast as is_adjacent;
-use crate::board::order::Order;
-use crate::board::province::{Coast, Power, Province, ProvinceType, PROVINCE_COUNT};
-use crate::board::state::{BoardState, DislodgedUnit as StateDislodgedUnit};
+use smallvec::SmallVec;
+
+use crate::board::map::GameMap;
+use crate::board::order::{Location, Order};
+use crate::board::province::{Coast, Power, Province, ProvinceType, MAX_PROVINCES};
+use crate::board::state::{BoardState, Delta, DislodgedUnit as StateDislodgedUnit};
 use crate::board::unit::UnitType;
 
 /// The outcome of resolving an order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OrderResult {
     Succeeded,
+    /// A convoy whose fleet stayed put but did not carry the army, or a
+    /// build-phase or retreat order that could not be carried out.
     Failed,
     Dislodged,
+    /// A move that lost to a stronger or equal hold, attack, or prevent.
     Bounced,
+    /// A support that was cut by an attack on the supporting unit.
     Cut,
+    /// The order was illegal and the unit held instead (see
+    /// [`super::adjudicate_movement`]). Never produced by the resolver itself.
+    Invalid,
+    /// A legal support or convoy that matches no order of the unit it names:
+    /// a support for a move that was not ordered, a support hold for a unit
+    /// that moved, or a convoy for an army that did not move along it.
+    Void,
+    /// A move that needed a convoy and had no unbroken chain of fleets.
+    NoConvoy,
+}
+
+impl OrderResult {
+    /// Short lowercase label for logs and JSON records.
+    pub fn name(self) -> &'static str {
+        match self {
+            OrderResult::Succeeded => "succeeded",
+            OrderResult::Failed => "failed",
+            OrderResult::Dislodged => "dislodged",
+            OrderResult::Bounced => "bounced",
+            OrderResult::Cut => "cut",
+            OrderResult::Invalid => "invalid",
+            OrderResult::Void => "void",
+            OrderResult::NoConvoy => "no_convoy",
+        }
+    }
+
+    /// Parses a label written by [`OrderResult::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "succeeded" => OrderResult::Succeeded,
+            "failed" => OrderResult::Failed,
+            "dislodged" => OrderResult::Dislodged,
+            "bounced" => OrderResult::Bounced,
+            "cut" => OrderResult::Cut,
+            "invalid" => OrderResult::Invalid,
+            "void" => OrderResult::Void,
+            "no_convoy" => OrderResult::NoConvoy,
+            _ => return None,
+        })
+    }
 }
 
 /// A resolved order paired with its result.
@@ -72,16 +118,18 @@ const NONE_IDX: u8 = u8::MAX;
 /// The returned `Vec`s are freshly allocated each call; the internal
 /// lookup table and buffer are reused.
 pub struct Resolver {
-    lookup: [i16; PROVINCE_COUNT],
+    lookup: [i16; MAX_PROVINCES],
     adj_buf: Vec<AdjResult>,
+    convoy_routes: Vec<(Province, Vec<Province>)>,
 }
 
 impl Resolver {
     /// Creates a new resolver with the given initial capacity hint.
     pub fn new(capacity: usize) -> Self {
         Resolver {
-            lookup: [-1; PROVINCE_COUNT],
+            lookup: [-1; MAX_PROVINCES],
             adj_buf: Vec::with_capacity(capacity),
+            convoy_routes: Vec::new(),
         }
     }
 
@@ -94,6 +142,7 @@ impl Resolver {
         orders: &[(Order, Power)],
         state: &BoardState,
     ) -> (Vec<ResolvedOrder>, Vec<DislodgedUnit>) {
+        crate::throughput::record_resolve();
         self.init(orders);
         self.adjudicate_all(state);
         self.build_results(orders, state)
@@ -200,7 +249,7 @@ impl Resolver {
         let ar = self.adj_buf[idx];
 
         // Check convoy requirement.
-        if self.needs_convoy(&ar) && !self.has_convoy_path(&ar, state) {
+        if self.needs_convoy(state.map(), &ar) && !self.has_convoy_path(&ar, state) {
             return false;
         }
 
@@ -212,10 +261,8 @@ impl Resolver {
         }
 
         // Head-to-head battle check.
-        if let Some(defender) = self.order_at(ar.target_idx) {
-            let defender_target = defender.target_idx;
-            let is_move = matches!(defender.order, Order::Move { .. });
-            if is_move && defender_target == prov_idx {
+        if let Some(defender) = self.order_at(ar.target_idx).copied() {
+            if self.is_head_to_head(state.map(), &ar, &defender) {
                 let defend_attack = self.attack_strength(ar.target_idx, state);
                 if attack_str <= defend_attack {
                     return false;
@@ -269,7 +316,7 @@ impl Resolver {
             }
 
             // For a convoyed attack, the convoy must succeed for the cut.
-            if self.needs_convoy(&other) && !self.adjudicate(other.prov_idx, state) {
+            if self.needs_convoy(state.map(), &other) && !self.adjudicate(other.prov_idx, state) {
                 continue;
             }
 
@@ -308,7 +355,7 @@ impl Resolver {
         // A unit cannot attack a province occupied by a unit of the same power
         // UNLESS the occupying unit is moving away.
         if let Some(target_prov) = target_prov {
-            if let Some((occ_power, _)) = state.units[target_prov as usize] {
+            if let Some((occ_power, _)) = state.units[target_prov.index()] {
                 if occ_power == attacker_power {
                     if let Some(occ_ar) = self.order_at(ar.target_idx) {
                         let is_move = matches!(occ_ar.order, Order::Move { .. });
@@ -316,8 +363,12 @@ impl Resolver {
                         if !is_move {
                             return 0;
                         }
-                        // If occupier is moving back to our province, strength is 0.
-                        if occ_target == prov_idx {
+                        // If occupier is moving back to our province over land
+                        // (head-to-head), strength is 0.
+                        if occ_target == prov_idx
+                            && !self.needs_convoy(state.map(), &ar)
+                            && !self.needs_convoy(state.map(), occ_ar)
+                        {
                             return 0;
                         }
                     } else {
@@ -400,10 +451,8 @@ impl Resolver {
 
         // Head-to-head: if defender is moving toward us, our prevent strength
         // depends on whether our move succeeds.
-        if let Some(defender) = self.order_at(ar.target_idx) {
-            let is_move = matches!(defender.order, Order::Move { .. });
-            let def_target = defender.target_idx;
-            if is_move && def_target == prov_idx {
+        if let Some(defender) = self.order_at(ar.target_idx).copied() {
+            if self.is_head_to_head(state.map(), &ar, &defender) {
                 if !self.adjudicate(prov_idx, state) {
                     return 0;
                 }
@@ -428,20 +477,40 @@ impl Resolver {
         strength
     }
 
-    /// Returns true if the move requires a convoy chain (army moving to non-adjacent province).
-    fn needs_convoy(&self, ar: &AdjResult) -> bool {
+    /// Returns true if two orders are moves into each other's province over
+    /// land. A pair where either side travels by convoy is not a head-to-head
+    /// battle and the units may swap.
+    fn is_head_to_head(&self, map: &GameMap, a: &AdjResult, b: &AdjResult) -> bool {
+        matches!(a.order, Order::Move { .. })
+            && matches!(b.order, Order::Move { .. })
+            && a.target_idx == b.prov_idx
+            && b.target_idx == a.prov_idx
+            && !self.needs_convoy(map, a)
+            && !self.needs_convoy(map, b)
+    }
+
+    /// Returns true if the move requires a convoy chain: an army moving to a
+    /// non-adjacent province, or any army move ordered `via convoy`.
+    fn needs_convoy(&self, map: &GameMap, ar: &AdjResult) -> bool {
         let unit = match ar.order {
-            Order::Move { unit, dest } => {
+            Order::Move {
+                unit,
+                dest,
+                via_convoy,
+            } => {
                 if unit.unit_type != UnitType::Army {
                     return false;
                 }
+                if via_convoy {
+                    return true;
+                }
                 (unit, dest)
             }
             _ => return false,
         };
 
         let (unit_ou, dest) = unit;
-        !is_adjacent(
+        !map.is_adjacent(
             unit_ou.location.province,
             unit_ou.location.coast,
             dest.province,
@@ -450,17 +519,70 @@ impl Resolver {
         )
     }
 
+    /// Returns true if the support or convoy `ar` matches what the unit it
+    /// names was actually ordered to do. Supports and convoys that match
+    /// nothing are void: they add no strength and carry no army.
+    fn matches_supported_order(&self, map: &GameMap, ar: &AdjResult) -> bool {
+        let other = match self.order_at(ar.aux_loc_idx) {
+            Some(other) => other,
+            None => return false,
+        };
+        match ar.order {
+            Order::SupportHold { .. } => !matches!(other.order, Order::Move { .. }),
+            Order::SupportMove { .. } => {
+                matches!(other.order, Order::Move { .. }) && other.target_idx == ar.aux_target_idx
+            }
+            Order::Convoy { .. } => {
+                matches!(other.order, Order::Move { .. })
+                    && other.target_idx == ar.aux_target_idx
+                    && self.needs_convoy(map, other)
+            }
+            _ => true,
+        }
+    }
+
     /// Checks if there's a successful convoy chain for the given move.
     fn has_convoy_path(&mut self, ar: &AdjResult, state: &BoardState) -> bool {
+        let mut parent = [NONE_IDX; MAX_PROVINCES];
+        self.convoy_path_end(ar, state, &mut parent).is_some()
+    }
+
+    /// Returns the fleets of a successful convoy chain for the move, from
+    /// the army's side to the destination side.
+    fn convoy_route(&mut self, ar: &AdjResult, state: &BoardState) -> Option<Vec<Province>> {
+        let mut parent = [NONE_IDX; MAX_PROVINCES];
+        let mut current = self.convoy_path_end(ar, state, &mut parent)?;
+        let mut route = vec![Province::from_u8(current)?];
+        while parent[current as usize] != NONE_IDX {
+            current = parent[current as usize];
+            route.push(Province::from_u8(current)?);
+        }
+        route.reverse();
+        Some(route)
+    }
+
+    /// Breadth-first search over the successful convoy orders for the move.
+    ///
+    /// Only fleets that are not dislodged are expanded, so with several
+    /// disjoint chains the army still arrives as long as one chain is
+    /// intact. Returns the last fleet of a complete chain (adjacent to the
+    /// destination), with `parent` linking each reached fleet back toward
+    /// the army; first-hop fleets keep `NONE_IDX`.
+    fn convoy_path_end(
+        &mut self,
+        ar: &AdjResult,
+        state: &BoardState,
+        parent: &mut [u8; MAX_PROVINCES],
+    ) -> Option<u8> {
         let (src_prov, dst_prov) = match ar.order {
-            Order::Move { unit, dest } => (unit.location.province, dest.province),
-            _ => return false,
+            Order::Move { unit, dest, .. } => (unit.location.province, dest.province),
+            _ => return None,
         };
 
-        let src_idx = src_prov as u8;
-        let tgt_idx = dst_prov as u8;
+        let src_idx = src_prov.index() as u8;
+        let tgt_idx = dst_prov.index() as u8;
 
-        let mut visited = [false; PROVINCE_COUNT];
+        let mut visited = [false; MAX_PROVINCES];
         // Use a fixed-size queue (max 19 sea provinces can be convoy waypoints).
         let mut queue = [0u8; 19];
         let mut queue_head = 0usize;
@@ -482,10 +604,13 @@ impl Resolver {
                 continue;
             }
             let cp = convoy_prov.unwrap();
-            if cp.province_type() != ProvinceType::Sea {
+            if state.map().province_type(cp) != ProvinceType::Sea {
                 continue;
             }
-            if is_adjacent(src_prov, Coast::None, cp, Coast::None, true) {
+            if state
+                .map()
+                .is_adjacent(src_prov, Coast::None, cp, Coast::None, true)
+            {
                 if self.adjudicate(convoy.prov_idx, state) {
                     visited[convoy.prov_idx as usize] = true;
                     queue[queue_tail] = convoy.prov_idx;
@@ -502,8 +627,11 @@ impl Resolver {
             let current_prov = Province::from_u8(current).unwrap();
 
             // Check if current convoy province is adjacent to destination.
-            if is_adjacent(current_prov, Coast::None, dst_prov, Coast::None, true) {
-                return true;
+            if state
+                .map()
+                .is_adjacent(current_prov, Coast::None, dst_prov, Coast::None, true)
+            {
+                return Some(current);
             }
 
             for i in 0..n {
@@ -522,12 +650,16 @@ impl Resolver {
                     continue;
                 }
                 let cp = convoy_prov.unwrap();
-                if cp.province_type() != ProvinceType::Sea {
+                if state.map().province_type(cp) != ProvinceType::Sea {
                     continue;
                 }
-                if is_adjacent(current_prov, Coast::None, cp, Coast::None, true) {
+                if state
+                    .map()
+                    .is_adjacent(current_prov, Coast::None, cp, Coast::None, true)
+                {
                     if self.adjudicate(convoy.prov_idx, state) {
                         visited[convoy.prov_idx as usize] = true;
+                        parent[convoy.prov_idx as usize] = current;
                         if queue_tail < queue.len() {
                             queue[queue_tail] = convoy.prov_idx;
                             queue_tail += 1;
@@ -537,39 +669,74 @@ impl Resolver {
             }
         }
 
-        false
+        None
+    }
+
+    /// Returns the convoy chain each successful convoyed move travelled
+    /// along in the last `resolve` call, as `(army province, fleets)` with
+    /// fleets ordered from the army's side to the destination side.
+    ///
+    /// When an army has several disjoint chains and only some are broken,
+    /// the recorded route is one of the intact ones.
+    pub fn convoy_routes(&self) -> &[(Province, Vec<Province>)] {
+        &self.convoy_routes
     }
 
     /// Converts internal adjudication state to the external result format.
     fn build_results(
-        &self,
+        &mut self,
         orders: &[(Order, Power)],
-        _state: &BoardState,
+        state: &BoardState,
     ) -> (Vec<ResolvedOrder>, Vec<DislodgedUnit>) {
         let mut results = Vec::with_capacity(orders.len());
         let mut dislodged = Vec::new();
 
+        self.convoy_routes.clear();
+        for i in 0..self.adj_buf.len() {
+            let ar = self.adj_buf[i];
+            if matches!(ar.order, Order::Move { .. })
+                && ar.resolution
+                && self.needs_convoy(state.map(), &ar)
+            {
+                if let (Some(army), Some(route)) = (
+                    Province::from_u8(ar.prov_idx),
+                    self.convoy_route(&ar, state),
+                ) {
+                    self.convoy_routes.push((army, route));
+                }
+            }
+        }
+
         // Build map of successful moves: target -> source province index.
-        let mut successful_move_from = [NONE_IDX; PROVINCE_COUNT];
+        let mut successful_move_from = [NONE_IDX; MAX_PROVINCES];
         for ar in &self.adj_buf {
             if matches!(ar.order, Order::Move { .. }) && ar.resolution {
-                if (ar.target_idx as usize) < PROVINCE_COUNT {
+                if (ar.target_idx as usize) < MAX_PROVINCES {
                     successful_move_from[ar.target_idx as usize] = ar.prov_idx;
                 }
             }
         }
 
         for (i, (order, power)) in orders.iter().enumerate() {
-            let ar = &self.adj_buf[i];
+            let ar = self.adj_buf[i];
 
             let mut result = match ar.order {
                 Order::Move { .. } => {
                     if ar.resolution {
                         OrderResult::Succeeded
+                    } else if self.needs_convoy(state.map(), &ar)
+                        && !self.has_convoy_path(&ar, state)
+                    {
+                        OrderResult::NoConvoy
                     } else {
                         OrderResult::Bounced
                     }
                 }
+                Order::SupportHold { .. } | Order::SupportMove { .. } | Order::Convoy { .. }
+                    if !self.matches_supported_order(state.map(), &ar) =>
+                {
+                    OrderResult::Void
+                }
                 Order::SupportHold { .. } | Order::SupportMove { .. } => {
                     if ar.resolution {
                         OrderResult::Succeeded
@@ -624,57 +791,80 @@ pub fn apply_resolution(
     results: &[ResolvedOrder],
     dislodged: &[DislodgedUnit],
 ) {
+    let _ = state.apply(&resolution_delta(state, results, dislodged));
+}
+
+/// The changes [`apply_resolution`] makes, to apply and undo in place.
+pub fn resolution_delta(
+    state: &BoardState,
+    results: &[ResolvedOrder],
+    dislodged: &[DislodgedUnit],
+) -> Delta {
+    let mut delta = Delta::new();
     // First, remove dislodged units from the board so they don't block incoming moves.
     for d in dislodged {
-        state.units[d.province as usize] = None;
-        state.fleet_coast[d.province as usize] = None;
-        state.dislodged[d.province as usize] = Some(StateDislodgedUnit {
-            power: d.power,
-            unit_type: d.unit_type,
-            coast: d.coast,
-            attacker_from: d.attacker_from,
-        });
+        delta.set_unit(d.province, None, None);
+        delta.set_dislodged(
+            d.province,
+            Some(StateDislodgedUnit {
+                power: d.power,
+                unit_type: d.unit_type,
+                coast: d.coast,
+                attacker_from: d.attacker_from,
+            }),
+        );
     }
 
-    // Then apply successful moves.
+    // Then apply successful moves. Lift every moving unit off the board
+    // before placing any, so chains (A -> B while B -> C) don't overwrite
+    // a unit that is itself moving out.
+    let mut lifted: SmallVec<[(Location, (Power, UnitType)); 32]> = SmallVec::new();
     for ro in results {
         if ro.result != OrderResult::Succeeded {
             continue;
         }
-        if let Order::Move { unit, dest } = ro.order {
+        if let Order::Move { unit, dest, .. } = ro.order {
             let src = unit.location.province;
-            let dst = dest.province;
-
-            // Move the unit.
-            if let Some(unit_data) = state.units[src as usize].take() {
-                state.units[dst as usize] = Some(unit_data);
-            }
-
-            // Update fleet coast.
-            state.fleet_coast[src as usize] = None;
-            if dest.coast != Coast::None {
-                state.fleet_coast[dst as usize] = Some(dest.coast);
-            } else if !dst.has_coasts() {
-                state.fleet_coast[dst as usize] = None;
+            if let (Some(unit_data), _) = delta.unit_at(state, src) {
+                delta.set_unit(src, None, None);
+                lifted.push((dest, unit_data));
             }
         }
     }
+    for (dest, unit_data) in lifted {
+        let dst = dest.province;
+        // Update fleet coast.
+        let coast = if dest.coast != Coast::None {
+            Some(dest.coast)
+        } else if !state.map().has_coasts(dst) {
+            None
+        } else {
+            delta.unit_at(state, dst).1
+        };
+        delta.set_unit(dst, Some(unit_data), coast);
+    }
+    delta
 }
 
 /// Extracts province indices from an Order enum for the internal lookup table.
 fn order_indices(order: &Order) -> (u8, u8, u8, u8) {
     match *order {
-        Order::Hold { unit } => (unit.location.province as u8, NONE_IDX, NONE_IDX, NONE_IDX),
-        Order::Move { unit, dest } => (
-            unit.location.province as u8,
-            dest.province as u8,
+        Order::Hold { unit } => (
+            unit.location.province.index() as u8,
+            NONE_IDX,
+            NONE_IDX,
+            NONE_IDX,
+        ),
+        Order::Move { unit, dest, .. } => (
+            unit.location.province.index() as u8,
+            dest.province.index() as u8,
             NONE_IDX,
             NONE_IDX,
         ),
         Order::SupportHold { unit, supported } => (
-            unit.location.province as u8,
+            unit.location.province.index() as u8,
             NONE_IDX,
-            supported.location.province as u8,
+            supported.location.province.index() as u8,
             NONE_IDX,
         ),
         Order::SupportMove {
@@ -682,20 +872,20 @@ fn order_indices(order: &Order) -> (u8, u8, u8, u8) {
             supported,
             dest,
         } => (
-            unit.location.province as u8,
+            unit.location.province.index() as u8,
             NONE_IDX,
-            supported.location.province as u8,
-            dest.province as u8,
+            supported.location.province.index() as u8,
+            dest.province.index() as u8,
         ),
         Order::Convoy {
             unit,
             convoyed_from,
             convoyed_to,
         } => (
-            unit.location.province as u8,
+            unit.location.province.index() as u8,
             NONE_IDX,
-            convoyed_from.province as u8,
-            convoyed_to.province as u8,
+            convoyed_from.province.index() as u8,
+            convoyed_to.province.index() as u8,
         ),
         _ => (NONE_IDX, NONE_IDX, NONE_IDX, NONE_IDX),
     }
@@ -719,12 +909,7 @@ fn order_unit_info(order: &Order) -> (UnitType, Coast) {
 impl Province {
     /// Converts a u8 index back to a Province, returning None if out of range.
     pub fn from_u8(idx: u8) -> Option<Province> {
-        if (idx as usize) < PROVINCE_COUNT {
-            // Safety: Province is repr(u8) and we checked bounds.
-            Some(unsafe { std::mem::transmute(idx) })
-        } else {
-            None
-        }
+        ((idx as usize) < MAX_PROVINCES).then(|| Province::from_index(idx as usize))
     }
 }
 
@@ -818,6 +1003,7 @@ mod tests {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: Location::new(Province::Bud),
+                via_convoy: false,
             },
             Power::Austria,
         )];
@@ -837,6 +1023,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Vie),
                     dest: Location::new(Province::Bud),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -875,6 +1062,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Tyr),
                     dest: Location::new(Province::Ven),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -922,6 +1110,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Tri),
                     dest: Location::new(Province::Ven),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -947,6 +1136,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Boh),
                     dest: Location::new(Province::Mun),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -954,6 +1144,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Mun),
                     dest: Location::new(Province::Sil),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -961,6 +1152,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Sil),
                     dest: Location::new(Province::Boh),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -987,6 +1179,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Boh),
                     dest: Location::new(Province::Mun),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -994,6 +1187,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Mun),
                     dest: Location::new(Province::Sil),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -1001,6 +1195,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Sil),
                     dest: Location::new(Province::Boh),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -1047,6 +1242,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Rum),
                     dest: Location::new(Province::Bud),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -1085,6 +1281,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Rum),
                     dest: Location::new(Province::Bud),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -1092,6 +1289,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Bul),
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -1126,6 +1324,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Bud),
                     dest: Location::new(Province::Rum),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -1139,6 +1338,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Bul),
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 Power::Turkey,
             ),
@@ -1177,6 +1377,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Pru),
                     dest: Location::new(Province::Ber),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -1209,6 +1410,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Sil),
                     dest: Location::new(Province::Boh),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -1216,6 +1418,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::War),
                     dest: Location::new(Province::Sil),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -1223,6 +1426,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Boh),
                     dest: Location::new(Province::Mun),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -1246,6 +1450,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Rom),
                     dest: Location::new(Province::Ven),
+                    via_convoy: false,
                 },
                 Power::Italy,
             ),
@@ -1253,6 +1458,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Ven),
                     dest: Location::new(Province::Rom),
+                    via_convoy: false,
                 },
                 Power::Italy,
             ),
@@ -1285,6 +1491,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Tyr),
                     dest: Location::new(Province::Ven),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -1292,6 +1499,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Ven),
                     dest: Location::new(Province::Tyr),
+                    via_convoy: false,
                 },
                 Power::Italy,
             ),
@@ -1323,6 +1531,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Bur),
                     dest: Location::new(Province::Mun),
+                    via_convoy: false,
                 },
                 Power::France,
             ),
@@ -1330,6 +1539,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Tyr),
                     dest: Location::new(Province::Mun),
+                    via_convoy: false,
                 },
                 Power::Italy,
             ),
@@ -1354,6 +1564,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Lon),
                     dest: Location::new(Province::Nwy),
+                    via_convoy: false,
                 },
                 Power::England,
             ),
@@ -1386,6 +1597,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Lon),
                     dest: Location::new(Province::Nwy),
+                    via_convoy: false,
                 },
                 Power::England,
             ),
@@ -1401,6 +1613,7 @@ mod tests {
                 Order::Move {
                     unit: fleet(Province::Eng),
                     dest: Location::new(Province::Nth),
+                    via_convoy: false,
                 },
                 Power::France,
             ),
@@ -1416,7 +1629,59 @@ mod tests {
 
         let (results, _) = resolve_orders(&orders, &state);
         assert_eq!(result_for(&results, Province::Nth), OrderResult::Dislodged);
+        assert_eq!(result_for(&results, Province::Lon), OrderResult::NoConvoy);
+    }
+
+    #[test]
+    fn unmatched_supports_and_convoys_are_void() {
+        let mut state = empty_state();
+        state.place_unit(Province::Lon, Power::England, UnitType::Army, Coast::None);
+        state.place_unit(Province::Nth, Power::England, UnitType::Fleet, Coast::None);
+        state.place_unit(Province::Yor, Power::England, UnitType::Army, Coast::None);
+        state.place_unit(Province::Wal, Power::England, UnitType::Army, Coast::None);
+
+        let orders = vec![
+            // Lon moves over land; the convoy names a different destination.
+            (
+                Order::Move {
+                    unit: army(Province::Lon),
+                    dest: Location::new(Province::Wal),
+                    via_convoy: false,
+                },
+                Power::England,
+            ),
+            (
+                Order::Convoy {
+                    unit: fleet(Province::Nth),
+                    convoyed_from: Location::new(Province::Lon),
+                    convoyed_to: Location::new(Province::Bel),
+                },
+                Power::England,
+            ),
+            // Support hold for a unit that moves.
+            (
+                Order::SupportHold {
+                    unit: army(Province::Yor),
+                    supported: army(Province::Lon),
+                },
+                Power::England,
+            ),
+            // Support for a move Wal was not ordered to make.
+            (
+                Order::SupportMove {
+                    unit: army(Province::Wal),
+                    supported: army(Province::Yor),
+                    dest: Location::new(Province::Lon),
+                },
+                Power::England,
+            ),
+        ];
+
+        let (results, _) = resolve_orders(&orders, &state);
         assert_eq!(result_for(&results, Province::Lon), OrderResult::Bounced);
+        assert_eq!(result_for(&results, Province::Nth), OrderResult::Void);
+        assert_eq!(result_for(&results, Province::Yor), OrderResult::Void);
+        assert_eq!(result_for(&results, Province::Wal), OrderResult::Void);
     }
 
     // === Chained moves (regression from Go tests) ===
@@ -1432,6 +1697,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Par),
                     dest: Location::new(Province::Bre),
+                    via_convoy: false,
                 },
                 Power::France,
             ),
@@ -1439,6 +1705,7 @@ mod tests {
                 Order::Move {
                     unit: fleet(Province::Bre),
                     dest: Location::new(Province::Gas),
+                    via_convoy: false,
                 },
                 Power::England,
             ),
@@ -1464,6 +1731,7 @@ mod tests {
                 Order::Move {
                     unit: fleet(Province::Bre),
                     dest: Location::new(Province::Eng),
+                    via_convoy: false,
                 },
                 Power::France,
             ),
@@ -1471,6 +1739,7 @@ mod tests {
                 Order::Move {
                     unit: fleet(Province::Eng),
                     dest: Location::new(Province::Mao),
+                    via_convoy: false,
                 },
                 Power::England,
             ),
@@ -1478,6 +1747,7 @@ mod tests {
                 Order::Move {
                     unit: fleet(Province::Mao),
                     dest: Location::new(Province::Bre),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -1500,6 +1770,7 @@ mod tests {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: Location::new(Province::Bud),
+                via_convoy: false,
             },
             Power::Austria,
         )];
@@ -1507,13 +1778,58 @@ mod tests {
         let (results, dislodged) = resolve_orders(&orders, &state);
         apply_resolution(&mut state, &results, &dislodged);
 
-        assert!(state.units[Province::Vie as usize].is_none());
+        assert!(state.units[Province::Vie.index()].is_none());
         assert_eq!(
-            state.units[Province::Bud as usize],
+            state.units[Province::Bud.index()],
             Some((Power::Austria, UnitType::Army))
         );
     }
 
+    #[test]
+    fn apply_resolution_moves_chain_in_either_order() {
+        // A bul - ser while A ser - bud: both succeed regardless of order.
+        for reversed in [false, true] {
+            let mut state = empty_state();
+            state.place_unit(Province::Bul, Power::Turkey, UnitType::Army, Coast::None);
+            state.place_unit(Province::Ser, Power::Austria, UnitType::Army, Coast::None);
+
+            let mut orders = vec![
+                (
+                    Order::Move {
+                        unit: army(Province::Bul),
+                        dest: Location::new(Province::Ser),
+                        via_convoy: false,
+                    },
+                    Power::Turkey,
+                ),
+                (
+                    Order::Move {
+                        unit: army(Province::Ser),
+                        dest: Location::new(Province::Bud),
+                        via_convoy: false,
+                    },
+                    Power::Austria,
+                ),
+            ];
+            if reversed {
+                orders.reverse();
+            }
+
+            let (results, dislodged) = resolve_orders(&orders, &state);
+            apply_resolution(&mut state, &results, &dislodged);
+
+            assert!(state.units[Province::Bul.index()].is_none());
+            assert_eq!(
+                state.units[Province::Ser.index()],
+                Some((Power::Turkey, UnitType::Army))
+            );
+            assert_eq!(
+                state.units[Province::Bud.index()],
+                Some((Power::Austria, UnitType::Army))
+            );
+        }
+    }
+
     #[test]
     fn apply_resolution_dislodges_unit() {
         let mut state = empty_state();
@@ -1534,6 +1850,7 @@ mod tests {
                 Order::Move {
                     unit: army(Province::Tyr),
                     dest: Location::new(Province::Ven),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -1550,16 +1867,60 @@ mod tests {
 
         // Austria should be in Venice now.
         assert_eq!(
-            state.units[Province::Ven as usize],
+            state.units[Province::Ven.index()],
             Some((Power::Austria, UnitType::Army))
         );
         // Italy should be dislodged.
-        assert!(state.dislodged[Province::Ven as usize].is_some());
-        let d = state.dislodged[Province::Ven as usize].unwrap();
+        assert!(state.dislodged[Province::Ven.index()].is_some());
+        let d = state.dislodged[Province::Ven.index()].unwrap();
         assert_eq!(d.power, Power::Italy);
         assert_eq!(d.attacker_from, Province::Tyr);
     }
 
+    #[test]
+    fn resolution_delta_is_undone_in_place() {
+        let mut state = BoardState::empty(1901, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Tyr, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Tri, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Ven, Power::Italy, UnitType::Army, Coast::None);
+        state.set_sc_owner(Province::Ven, Some(Power::Italy));
+        let orders = vec![
+            (
+                Order::SupportMove {
+                    unit: army(Province::Tri),
+                    supported: army(Province::Tyr),
+                    dest: Location::new(Province::Ven),
+                },
+                Power::Austria,
+            ),
+            (
+                Order::Move {
+                    unit: army(Province::Tyr),
+                    dest: Location::new(Province::Ven),
+                    via_convoy: false,
+                },
+                Power::Austria,
+            ),
+        ];
+        let (results, dislodged) = resolve_orders(&orders, &state);
+        let mut expected = state.clone();
+        apply_resolution(&mut expected, &results, &dislodged);
+        crate::resolve::advance_state(&mut expected, true);
+
+        let mut board = state.clone();
+        let resolved = board.apply(&resolution_delta(&board, &results, &dislodged));
+        let advanced = board.apply(&crate::resolve::advance_delta(&board, true));
+        assert_eq!(board, expected);
+        assert_eq!(board.zobrist(), expected.zobrist());
+        assert_eq!(board.phase, Phase::Retreat);
+        assert_eq!(board.sc_owner[Province::Ven.index()], Some(Power::Austria));
+
+        board.undo(advanced);
+        board.undo(resolved);
+        assert_eq!(board, state);
+        assert_eq!(board.zobrist(), state.zobrist());
+    }
+
     // === Reusable resolver ===
 
     #[test]
@@ -1573,6 +1934,7 @@ mod tests {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: Location::new(Province::Bud),
+                via_convoy: false,
             },
             Power::Austria,
         )];
@@ -2,19 +2,37 @@
 //!
 //! Resolves a set of simultaneous orders into outcomes (succeeds, fails,
 //! dislodged) using the Kruijswijk algorithm. Also handles retreat-phase
-//! and build-phase resolution, plus phase sequencing.
+//! and build-phase resolution, phase sequencing, rulebook adjudication
+//! of submitted orders that may be illegal, and two-power duels.
 
+pub mod adjudicate;
 pub mod build;
+pub mod duel;
 pub mod kruijswijk;
 pub mod phase;
 pub mod retreat;
+pub mod sanitize;
+
+pub use adjudicate::{adjudicate_movement, is_legal_order};
 
 pub use kruijswijk::{
-    apply_resolution, resolve_orders, DislodgedUnit, OrderResult, ResolvedOrder, Resolver,
+    apply_resolution, resolution_delta, resolve_orders, DislodgedUnit, OrderResult, ResolvedOrder,
+    Resolver,
+};
+
+pub use retreat::{
+    apply_retreats, destroyed_units, resolve_retreats, retreat_delta, Destruction, RetreatResult,
 };
 
-pub use retreat::{apply_retreats, resolve_retreats, RetreatResult};
+pub use sanitize::{sanitize_orders, SanitizeChange, SanitizeReport};
 
-pub use build::{apply_builds, resolve_builds, BuildResult};
+pub use duel::{Duel, DuelError, DUEL_CENTERS};
 
-pub use phase::{advance_state, is_game_over, needs_build_phase, next_phase, update_sc_ownership};
+pub use build::{
+    apply_builds, build_delta, default_disbands, resolve_builds, validate_build, BuildError,
+    BuildResult,
+};
+
+pub use phase::{
+    advance_delta, advance_state, is_game_over, needs_build_phase, next_phase, update_sc_ownership,
+};
@@ -3,7 +3,7 @@
 //! Determines the next phase in the Diplomacy game year and advances
 //! the board state accordingly. Ported from Go's `phase.go`.
 
-use crate::board::{BoardState, Phase, Power, Season, ALL_POWERS, ALL_PROVINCES, PROVINCE_COUNT};
+use crate::board::{BoardState, Delta, Phase, Power, Season, ALL_POWERS};
 
 /// Computes the next (season, phase) given the current state and whether dislodgements occurred.
 ///
@@ -52,13 +52,21 @@ pub fn needs_build_phase(state: &BoardState) -> bool {
 /// Updates supply center ownership: SCs are captured by the power whose unit occupies them.
 /// This should be called after Fall movement or Fall retreat resolution.
 pub fn update_sc_ownership(state: &mut BoardState) {
-    for prov in &ALL_PROVINCES {
-        if !prov.is_supply_center() {
+    let mut delta = Delta::new();
+    sc_ownership_edits(state, &mut delta);
+    let _ = state.apply(&delta);
+}
+
+/// Adds to `delta` the captures [`update_sc_ownership`] makes.
+fn sc_ownership_edits(state: &BoardState, delta: &mut Delta) {
+    for &prov in state.map().provinces() {
+        if !state.map().is_supply_center(prov) {
             continue;
         }
-        let idx = *prov as usize;
-        if let Some((power, _)) = state.units[idx] {
-            state.sc_owner[idx] = Some(power);
+        if let Some((power, _)) = state.units[prov.index()] {
+            if state.sc_owner[prov.index()] != Some(power) {
+                delta.set_sc_owner(prov, Some(power));
+            }
         }
         // If no unit present, ownership stays with current owner.
     }
@@ -71,34 +79,47 @@ pub fn update_sc_ownership(state: &mut BoardState) {
 /// - Year increment when transitioning to Spring
 /// - Clearing dislodged units when not entering retreat phase
 pub fn advance_state(state: &mut BoardState, has_dislodgements: bool) {
+    let _ = state.apply(&advance_delta(state, has_dislodgements));
+}
+
+/// The changes [`advance_state`] makes, to apply and undo in place.
+pub fn advance_delta(state: &BoardState, has_dislodgements: bool) -> Delta {
     let (next_season, next_phase) = next_phase(state, has_dislodgements);
+    let mut delta = Delta::new();
 
     // Update SC ownership after Fall movement or Fall retreat.
     if state.season == Season::Fall
         && (state.phase == Phase::Movement || state.phase == Phase::Retreat)
     {
-        update_sc_ownership(state);
+        sc_ownership_edits(state, &mut delta);
     }
 
     // Increment year when entering Spring movement.
-    if next_season == Season::Spring && next_phase == Phase::Movement {
-        state.year += 1;
-    }
-
-    state.season = next_season;
-    state.phase = next_phase;
+    let year = if next_season == Season::Spring && next_phase == Phase::Movement {
+        state.year + 1
+    } else {
+        state.year
+    };
+    delta.set_turn(year, next_season, next_phase);
 
     // Clear dislodged units unless entering retreat phase.
     if next_phase != Phase::Retreat {
-        state.dislodged = [None; PROVINCE_COUNT];
+        for &prov in state.map().provinces() {
+            if state.dislodged[prov.index()].is_some() {
+                delta.set_dislodged(prov, None);
+            }
+        }
     }
+    delta
 }
 
-/// Returns true if any single power controls 18+ supply centers (solo victory).
+/// Returns the power holding a majority of the supply centers (18 on the
+/// standard map), which wins solo.
 pub fn is_game_over(state: &BoardState) -> Option<Power> {
+    let solo = state.map().solo_centers();
     for &power in &ALL_POWERS {
         let sc = state.sc_owner.iter().filter(|o| **o == Some(power)).count();
-        if sc >= 18 {
+        if sc >= solo {
             return Some(power);
         }
     }
@@ -202,7 +223,7 @@ mod tests {
         advance_state(&mut state, true);
         assert_eq!(state.phase, Phase::Retreat);
         // Dislodged should be preserved.
-        assert!(state.dislodged[Province::Ser as usize].is_some());
+        assert!(state.dislodged[Province::Ser.index()].is_some());
     }
 
     #[test]
@@ -237,7 +258,7 @@ mod tests {
         // Bul is a supply center per province data.
         update_sc_ownership(&mut state);
 
-        assert_eq!(state.sc_owner[Province::Bul as usize], Some(Power::Turkey));
+        assert_eq!(state.sc_owner[Province::Bul.index()], Some(Power::Turkey));
     }
 
     #[test]
@@ -248,7 +269,7 @@ mod tests {
 
         update_sc_ownership(&mut state);
         // Ownership should stay with Austria.
-        assert_eq!(state.sc_owner[Province::Vie as usize], Some(Power::Austria));
+        assert_eq!(state.sc_owner[Province::Vie.index()], Some(Power::Austria));
     }
 
     #[test]
@@ -372,6 +393,6 @@ mod tests {
 
         advance_state(&mut state, false);
         // After Fall movement, SC ownership updates.
-        assert_eq!(state.sc_owner[Province::Bul as usize], Some(Power::Turkey));
+        assert_eq!(state.sc_owner[Province::Bul.index()], Some(Power::Turkey));
     }
 }
@@ -2,19 +2,48 @@
 //!
 //! Resolves retreat orders: if two dislodged units retreat to the same province,
 //! both are disbanded. Unordered dislodged units are auto-disbanded (civil disorder).
+//! Every result that removes a unit from the board records why.
 
 use crate::board::{
-    BoardState, Coast, Location, Order, OrderUnit, Province, ALL_PROVINCES, PROVINCE_COUNT,
+    BoardState, Coast, Delta, Location, Order, OrderUnit, Power, Province, MAX_PROVINCES,
 };
+use crate::movegen::retreat::legal_retreats;
 
 use super::kruijswijk::OrderResult;
 
+/// Why a dislodged unit was removed from the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destruction {
+    /// It retreated to the same province as another unit.
+    Bounced { dest: Province },
+    /// Every neighbour was occupied, contested, or the attacker's origin.
+    NoLegalRetreat,
+    /// It could have retreated but its power submitted no order.
+    CivilDisorder,
+    /// Its power ordered it to disband while a retreat was available.
+    Disbanded,
+}
+
+impl Destruction {
+    /// Short lowercase label for logs and JSON records.
+    pub fn name(self) -> &'static str {
+        match self {
+            Destruction::Bounced { .. } => "bounced",
+            Destruction::NoLegalRetreat => "no_retreat",
+            Destruction::CivilDisorder => "civil_disorder",
+            Destruction::Disbanded => "disbanded",
+        }
+    }
+}
+
 /// The result of resolving a retreat order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RetreatResult {
     pub order: Order,
-    pub power: crate::board::Power,
+    pub power: Power,
     pub result: OrderResult,
+    /// Set when the order's unit leaves the board this phase.
+    pub destroyed: Option<Destruction>,
 }
 
 /// Resolves retreat-phase orders and returns results for each.
@@ -24,25 +53,22 @@ pub struct RetreatResult {
 /// - If two units retreat to the same province, both are disbanded (bounced).
 /// - Disband orders always succeed.
 /// - Invalid retreat orders cause the unit to be disbanded.
-pub fn resolve_retreats(
-    orders: &[(Order, crate::board::Power)],
-    state: &BoardState,
-) -> Vec<RetreatResult> {
+pub fn resolve_retreats(orders: &[(Order, Power)], state: &BoardState) -> Vec<RetreatResult> {
     let mut results = Vec::new();
 
     // Track which dislodged provinces have been given an order.
-    let mut has_order = [false; PROVINCE_COUNT];
+    let mut has_order = [false; MAX_PROVINCES];
     for (order, _) in orders {
         if let Some(prov) = order_province(order) {
-            has_order[prov as usize] = true;
+            has_order[prov.index()] = true;
         }
     }
 
     // Civil disorder: auto-disband unordered dislodged units.
-    for i in 0..PROVINCE_COUNT {
+    for &prov in state.map().provinces() {
+        let i = prov.index();
         if let Some(d) = &state.dislodged[i] {
             if !has_order[i] {
-                let prov = ALL_PROVINCES[i];
                 results.push(RetreatResult {
                     order: Order::Disband {
                         unit: OrderUnit {
@@ -52,55 +78,74 @@ pub fn resolve_retreats(
                     },
                     power: d.power,
                     result: OrderResult::Succeeded,
+                    destroyed: Some(if can_retreat(prov, state) {
+                        Destruction::CivilDisorder
+                    } else {
+                        Destruction::NoLegalRetreat
+                    }),
                 });
             }
         }
     }
 
     // Count retreat targets to detect conflicts.
-    let mut target_count = [0u8; PROVINCE_COUNT];
+    let mut target_count = [0u8; MAX_PROVINCES];
     for (order, _) in orders {
         if let Order::Retreat { dest, .. } = order {
-            target_count[dest.province as usize] += 1;
+            target_count[dest.province.index()] += 1;
         }
     }
 
     // Process submitted orders.
     for (order, power) in orders {
         match order {
-            Order::Disband { .. } => {
+            Order::Disband { unit } => {
+                let prov = unit.location.province;
+                let destroyed = if state.dislodged[prov.index()].is_none() {
+                    None
+                } else if can_retreat(prov, state) {
+                    Some(Destruction::Disbanded)
+                } else {
+                    Some(Destruction::NoLegalRetreat)
+                };
                 results.push(RetreatResult {
                     order: *order,
                     power: *power,
                     result: OrderResult::Succeeded,
+                    destroyed,
                 });
             }
             Order::Retreat { unit, dest } => {
                 // Validate: there must be a dislodged unit at the source province.
                 let src = unit.location.province;
-                let dislodged = state.dislodged[src as usize];
+                let dislodged = state.dislodged[src.index()];
                 if dislodged.is_none() {
                     // Invalid: no dislodged unit here, treat as void -> disband.
                     results.push(RetreatResult {
                         order: *order,
                         power: *power,
                         result: OrderResult::Failed,
+                        destroyed: None,
                     });
                     continue;
                 }
 
                 // Conflict: two units retreating to same province -> both bounce.
-                if target_count[dest.province as usize] > 1 {
+                if target_count[dest.province.index()] > 1 {
                     results.push(RetreatResult {
                         order: *order,
                         power: *power,
                         result: OrderResult::Bounced,
+                        destroyed: Some(Destruction::Bounced {
+                            dest: dest.province,
+                        }),
                     });
                 } else {
                     results.push(RetreatResult {
                         order: *order,
                         power: *power,
                         result: OrderResult::Succeeded,
+                        destroyed: None,
                     });
                 }
             }
@@ -110,6 +155,7 @@ pub fn resolve_retreats(
                     order: *order,
                     power: *power,
                     result: OrderResult::Failed,
+                    destroyed: None,
                 });
             }
         }
@@ -118,11 +164,38 @@ pub fn resolve_retreats(
     results
 }
 
+/// Lists every unit the retreat phase removes, with its owner, the province
+/// it was dislodged from, and the cause.
+pub fn destroyed_units(results: &[RetreatResult]) -> Vec<(Power, Province, Destruction)> {
+    results
+        .iter()
+        .filter_map(|r| {
+            let cause = r.destroyed?;
+            let prov = order_province(&r.order)?;
+            Some((r.power, prov, cause))
+        })
+        .collect()
+}
+
+/// Returns true if the dislodged unit in `province` has at least one legal
+/// retreat destination.
+fn can_retreat(province: Province, state: &BoardState) -> bool {
+    legal_retreats(province, state)
+        .iter()
+        .any(|o| matches!(o, Order::Retreat { .. }))
+}
+
 /// Applies resolved retreat results to the board state.
 ///
 /// Successful retreats move the unit to its destination.
 /// All dislodged units are cleared after application.
 pub fn apply_retreats(state: &mut BoardState, results: &[RetreatResult]) {
+    let _ = state.apply(&retreat_delta(state, results));
+}
+
+/// The changes [`apply_retreats`] makes, to apply and undo in place.
+pub fn retreat_delta(state: &BoardState, results: &[RetreatResult]) -> Delta {
+    let mut delta = Delta::new();
     for r in results {
         if r.result != OrderResult::Succeeded {
             continue;
@@ -130,21 +203,22 @@ pub fn apply_retreats(state: &mut BoardState, results: &[RetreatResult]) {
         if let Order::Retreat { unit, dest } = r.order {
             let dst = dest.province;
             let coast = if dest.coast != Coast::None {
-                dest.coast
+                Some(dest.coast)
             } else {
-                Coast::None
+                delta.unit_at(state, dst).1
             };
-
-            state.units[dst as usize] = Some((r.power, unit.unit_type));
-            if coast != Coast::None {
-                state.fleet_coast[dst as usize] = Some(coast);
-            }
+            delta.set_unit(dst, Some((r.power, unit.unit_type)), coast);
         }
         // Disband orders: unit simply isn't placed back.
     }
 
     // Clear all dislodged units.
-    state.dislodged = [None; PROVINCE_COUNT];
+    for &prov in state.map().provinces() {
+        if state.dislodged[prov.index()].is_some() {
+            delta.set_dislodged(prov, None);
+        }
+    }
+    delta
 }
 
 /// Extracts the source province from an order (the unit's current location).
@@ -192,6 +266,7 @@ mod tests {
         let results = resolve_retreats(&orders, &state);
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].result, OrderResult::Succeeded);
+        assert_eq!(results[0].destroyed, Some(Destruction::Disbanded));
     }
 
     #[test]
@@ -221,6 +296,7 @@ mod tests {
         let results = resolve_retreats(&orders, &state);
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].result, OrderResult::Succeeded);
+        assert!(destroyed_units(&results).is_empty());
     }
 
     #[test]
@@ -272,6 +348,63 @@ mod tests {
         let results = resolve_retreats(&orders, &state);
         assert_eq!(results.len(), 2);
         assert!(results.iter().all(|r| r.result == OrderResult::Bounced));
+        assert!(results.iter().all(|r| r.destroyed
+            == Some(Destruction::Bounced {
+                dest: Province::Alb
+            })));
+
+        let destroyed = destroyed_units(&results);
+        assert_eq!(destroyed.len(), 2);
+        assert!(destroyed.contains(&(
+            Power::Italy,
+            Province::Gre,
+            Destruction::Bounced {
+                dest: Province::Alb
+            }
+        )));
+    }
+
+    #[test]
+    fn surrounded_unit_reports_no_legal_retreat() {
+        let mut state = retreat_state();
+        state.set_dislodged(
+            Province::Ser,
+            DislodgedUnit {
+                power: Power::Austria,
+                unit_type: UnitType::Army,
+                coast: Coast::None,
+                attacker_from: Province::Bul,
+            },
+        );
+        for prov in [
+            Province::Ser,
+            Province::Bud,
+            Province::Tri,
+            Province::Alb,
+            Province::Gre,
+            Province::Rum,
+        ] {
+            state.place_unit(prov, Power::Turkey, UnitType::Army, Coast::None);
+        }
+
+        // Unordered and ordered disbands both record the real cause.
+        let results = resolve_retreats(&[], &state);
+        assert_eq!(results[0].destroyed, Some(Destruction::NoLegalRetreat));
+
+        let orders = vec![(
+            Order::Disband {
+                unit: OrderUnit {
+                    unit_type: UnitType::Army,
+                    location: Location::new(Province::Ser),
+                },
+            },
+            Power::Austria,
+        )];
+        let results = resolve_retreats(&orders, &state);
+        assert_eq!(
+            destroyed_units(&results),
+            vec![(Power::Austria, Province::Ser, Destruction::NoLegalRetreat)]
+        );
     }
 
     #[test]
@@ -293,6 +426,7 @@ mod tests {
         assert_eq!(results[0].result, OrderResult::Succeeded);
         assert!(matches!(results[0].order, Order::Disband { .. }));
         assert_eq!(results[0].power, Power::Austria);
+        assert_eq!(results[0].destroyed, Some(Destruction::CivilDisorder));
     }
 
     #[test]
@@ -318,12 +452,13 @@ mod tests {
             },
             power: Power::Austria,
             result: OrderResult::Succeeded,
+            destroyed: None,
         }];
 
         apply_retreats(&mut state, &results);
 
         assert_eq!(
-            state.units[Province::Alb as usize],
+            state.units[Province::Alb.index()],
             Some((Power::Austria, UnitType::Army))
         );
         // Dislodged should be cleared.
@@ -353,12 +488,15 @@ mod tests {
             },
             power: Power::Austria,
             result: OrderResult::Bounced,
+            destroyed: Some(Destruction::Bounced {
+                dest: Province::Alb,
+            }),
         }];
 
         apply_retreats(&mut state, &results);
 
         // Unit should NOT be at Alb.
-        assert!(state.units[Province::Alb as usize].is_none());
+        assert!(state.units[Province::Alb.index()].is_none());
         // Dislodged should still be cleared.
         assert!(state.dislodged.iter().all(|d| d.is_none()));
     }
@@ -386,18 +524,16 @@ mod tests {
             },
             power: Power::Turkey,
             result: OrderResult::Succeeded,
+            destroyed: None,
         }];
 
         apply_retreats(&mut state, &results);
 
         assert_eq!(
-            state.units[Province::Bul as usize],
+            state.units[Province::Bul.index()],
             Some((Power::Turkey, UnitType::Fleet))
         );
-        assert_eq!(
-            state.fleet_coast[Province::Bul as usize],
-            Some(Coast::South)
-        );
+        assert_eq!(state.fleet_coast[Province::Bul.index()], Some(Coast::South));
     }
 
     #[test]
@@ -0,0 +1,217 @@
+//! Order-set sanitization.
+//!
+//! Imported games, human clients, and the adjudicator service all hand the
+//! engine order sets that can be messy: a unit ordered twice, orders for
+//! units that are not on the board, or units with no order at all. The
+//! sanitizer normalizes a set the same way everywhere and reports each
+//! change it made:
+//! - Duplicate orders for the same unit: the last one wins.
+//! - Orders for a unit the power does not have (wrong province, wrong unit
+//!   type, or another power's unit) are removed.
+//! - In movement phases, every unit left without an order gets a hold.
+
+use crate::board::{
+    BoardState, Coast, Location, Order, OrderUnit, Phase, Power, Province, MAX_PROVINCES,
+};
+
+/// One change made by [`sanitize_orders`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanitizeChange {
+    /// An earlier order for the same unit was dropped in favour of a later one.
+    ReplacedDuplicate { dropped: Order, power: Power },
+    /// The order names a unit the power does not have.
+    RemovedNoUnit { order: Order, power: Power },
+    /// The unit had no order, so a hold was added.
+    AddedHold { order: Order, power: Power },
+}
+
+/// The sanitized order set and the changes made to produce it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizeReport {
+    pub orders: Vec<(Order, Power)>,
+    pub changes: Vec<SanitizeChange>,
+}
+
+impl SanitizeReport {
+    /// Returns true if the input needed no changes.
+    pub fn is_clean(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Returns true if `power` has the ordered unit in the phase's unit set:
+/// dislodged units in retreat phases, units on the board otherwise. Builds
+/// and waives name no existing unit and always pass.
+fn unit_exists(order: &Order, power: Power, state: &BoardState) -> bool {
+    let unit = match order {
+        Order::Build { .. } | Order::Waive => return state.phase == Phase::Build,
+        _ => match order.unit() {
+            Some(u) => u,
+            None => return false,
+        },
+    };
+    let idx = unit.location.province.index();
+    match state.phase {
+        Phase::Retreat => {
+            matches!(state.dislodged[idx], Some(d) if d.power == power && d.unit_type == unit.unit_type)
+        }
+        Phase::Movement | Phase::Build => state.units[idx] == Some((power, unit.unit_type)),
+    }
+}
+
+/// Sanitizes an order set against the board state.
+///
+/// Surviving orders keep their input order (a replaced duplicate takes the
+/// position of the order that replaced it); added holds follow in province
+/// order.
+pub fn sanitize_orders(orders: &[(Order, Power)], state: &BoardState) -> SanitizeReport {
+    let mut changes = Vec::new();
+
+    // Index of the last order for each unit province.
+    let mut last = [None::<usize>; MAX_PROVINCES];
+    for (i, (order, power)) in orders.iter().enumerate() {
+        if !unit_exists(order, *power, state) {
+            continue;
+        }
+        if let Some(unit) = order.unit() {
+            last[unit.location.province.index()] = Some(i);
+        }
+    }
+
+    let mut kept = Vec::with_capacity(orders.len());
+    let mut ordered = [false; MAX_PROVINCES];
+    for (i, &(order, power)) in orders.iter().enumerate() {
+        if !unit_exists(&order, power, state) {
+            changes.push(SanitizeChange::RemovedNoUnit { order, power });
+            continue;
+        }
+        if let Some(unit) = order.unit() {
+            let idx = unit.location.province.index();
+            if last[idx] != Some(i) {
+                changes.push(SanitizeChange::ReplacedDuplicate {
+                    dropped: order,
+                    power,
+                });
+                continue;
+            }
+            ordered[idx] = true;
+        }
+        kept.push((order, power));
+    }
+
+    if state.phase == Phase::Movement {
+        for (i, unit) in state.units.iter().enumerate() {
+            if let Some((power, unit_type)) = *unit {
+                if ordered[i] {
+                    continue;
+                }
+                let coast = state.fleet_coast[i].unwrap_or(Coast::None);
+                let order = Order::Hold {
+                    unit: OrderUnit {
+                        unit_type,
+                        location: Location::with_coast(Province::from_index(i), coast),
+                    },
+                };
+                kept.push((order, power));
+                changes.push(SanitizeChange::AddedHold { order, power });
+            }
+        }
+    }
+
+    SanitizeReport {
+        orders: kept,
+        changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn order(s: &str) -> Order {
+        parse_order(s).unwrap()
+    }
+
+    #[test]
+    fn clean_set_is_unchanged() {
+        let state = parse_dfen("1901sm/Aavie,Aftri/Avie,Atri/-").unwrap();
+        let orders = vec![
+            (order("A vie - gal"), Power::Austria),
+            (order("F tri H"), Power::Austria),
+        ];
+        let report = sanitize_orders(&orders, &state);
+        assert!(report.is_clean());
+        assert_eq!(report.orders, orders);
+    }
+
+    #[test]
+    fn duplicates_keep_last_and_missing_units_hold() {
+        let state = parse_dfen("1901sm/Aavie,Aftri,Rawar/Avie,Atri,Rwar/-").unwrap();
+        let orders = vec![
+            (order("A vie - gal"), Power::Austria),
+            (order("A vie - boh"), Power::Austria),
+            // Wrong unit type, missing unit, and another power's unit.
+            (order("A tri H"), Power::Austria),
+            (order("A bud H"), Power::Austria),
+            (order("A war - gal"), Power::Austria),
+        ];
+        let report = sanitize_orders(&orders, &state);
+
+        assert_eq!(
+            report.orders,
+            vec![
+                (order("A vie - boh"), Power::Austria),
+                (order("F tri H"), Power::Austria),
+                (order("A war H"), Power::Russia),
+            ]
+        );
+        assert_eq!(
+            report.changes,
+            vec![
+                SanitizeChange::ReplacedDuplicate {
+                    dropped: order("A vie - gal"),
+                    power: Power::Austria
+                },
+                SanitizeChange::RemovedNoUnit {
+                    order: order("A tri H"),
+                    power: Power::Austria
+                },
+                SanitizeChange::RemovedNoUnit {
+                    order: order("A bud H"),
+                    power: Power::Austria
+                },
+                SanitizeChange::RemovedNoUnit {
+                    order: order("A war - gal"),
+                    power: Power::Austria
+                },
+                SanitizeChange::AddedHold {
+                    order: order("F tri H"),
+                    power: Power::Austria
+                },
+                SanitizeChange::AddedHold {
+                    order: order("A war H"),
+                    power: Power::Russia
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn retreat_phase_checks_dislodged_units_and_adds_no_holds() {
+        let state = parse_dfen("1901fr/Aagal/Avie/Rawar<gal").unwrap();
+        let orders = vec![
+            (order("A war R ukr"), Power::Russia),
+            (order("A war D"), Power::Russia),
+            (order("A gal R boh"), Power::Austria),
+        ];
+        let report = sanitize_orders(&orders, &state);
+        assert_eq!(report.orders, vec![(order("A war D"), Power::Russia)]);
+        assert_eq!(report.changes.len(), 2);
+        assert!(report
+            .changes
+            .iter()
+            .all(|c| !matches!(c, SanitizeChange::AddedHold { .. })));
+    }
+}
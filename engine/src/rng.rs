@@ -0,0 +1,80 @@
+//! Seeding for the engine's random number generators.
+//!
+//! Searches, random fallbacks, book selection and self-play all draw from a
+//! `SmallRng`. With the `entropy` feature (on by default) unseeded generators
+//! come from the operating system's entropy source. Without it, as in the
+//! `minimal` build, they are seeded from the clock and a process-wide
+//! counter, which is enough to vary play between searches and needs nothing
+//! from the platform beyond `std::time`.
+//!
+//! None of these generators are fit for secrets. [`secure_bytes`] reads
+//! the operating system's entropy source directly, in every build, and
+//! fails rather than fall back to the clock.
+
+use std::io;
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+/// A generator with an unpredictable seed.
+#[cfg(feature = "entropy")]
+pub fn entropy_rng() -> SmallRng {
+    SmallRng::from_entropy()
+}
+
+/// A generator with an unpredictable seed.
+#[cfg(not(feature = "entropy"))]
+pub fn entropy_rng() -> SmallRng {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    // Spread consecutive counter values far apart so two generators made in
+    // the same clock tick still diverge.
+    SmallRng::seed_from_u64(nanos ^ n.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// Fills `buf` with unguessable bytes from the operating system, for
+/// secrets such as session tokens.
+#[cfg(feature = "entropy")]
+pub fn secure_bytes(buf: &mut [u8]) -> io::Result<()> {
+    use rand::RngCore;
+    rand::rngs::OsRng
+        .try_fill_bytes(buf)
+        .map_err(io::Error::other)
+}
+
+/// Fills `buf` with unguessable bytes from the operating system, for
+/// secrets such as session tokens.
+#[cfg(not(feature = "entropy"))]
+pub fn secure_bytes(buf: &mut [u8]) -> io::Result<()> {
+    use std::io::Read;
+    std::fs::File::open("/dev/urandom")?.read_exact(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn successive_generators_differ() {
+        let a: u64 = entropy_rng().gen();
+        let b: u64 = entropy_rng().gen();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn secure_bytes_fill_the_buffer() {
+        let mut a = [0u8; 16];
+        let mut b = [0u8; 16];
+        secure_bytes(&mut a).unwrap();
+        secure_bytes(&mut b).unwrap();
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,156 @@
+//! Scripted opponents.
+//!
+//! An opponent script fixes what some powers play, phase by phase, so a
+//! scenario such as a known opening trap can be replayed against the
+//! engine the same way every run. Each line gives one power's orders for
+//! one phase, with the phase written as in a DFEN and the orders in DSON:
+//!
+//! ```text
+//! 1901sm austria: A vie - gal ; A bud - ser ; F tri - alb
+//! 1901fm austria: A gal - war ; A ser S A gal - war ; F alb - gre
+//! ```
+//!
+//! Blank lines and lines starting with `#` are skipped. Every power named
+//! in a script is scripted for the whole game: in a phase the script has
+//! no line for, its units hold and it takes the heuristic retreats and
+//! builds. Orders are only checked for syntax: one for a unit the power
+//! no longer has is dropped, and one that does not fit the position is
+//! adjudicated like any other illegal order.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::board::order::Order;
+use crate::board::province::Power;
+use crate::board::state::{BoardState, Phase, Season};
+use crate::protocol::dfen::parse_phase_info;
+use crate::protocol::dson::parse_orders;
+
+/// A problem with one line of an opponent script.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct ScriptError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+/// The phase key of a script line.
+type PhaseKey = (u16, Season, Phase);
+
+/// Fixed orders for some powers, phase by phase.
+#[derive(Debug, Clone, Default)]
+pub struct OpponentScript {
+    /// Scripted powers, in order of first appearance.
+    powers: Vec<Power>,
+    orders: HashMap<(PhaseKey, Power), Vec<Order>>,
+}
+
+impl OpponentScript {
+    /// Parses a script; see the module docs for the format.
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut script = OpponentScript::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| ScriptError {
+                line: i + 1,
+                message,
+            };
+            let (head, orders) = line
+                .split_once(':')
+                .ok_or_else(|| err("expected '<phase> <power>: <orders>'".to_string()))?;
+            let mut fields = head.split_whitespace();
+            let (Some(phase), Some(power), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(err("expected '<phase> <power>: <orders>'".to_string()));
+            };
+            let key = parse_phase_info(phase).map_err(|e| err(e.to_string()))?;
+            let power =
+                Power::from_name(power).ok_or_else(|| err(format!("unknown power '{}'", power)))?;
+            let orders = parse_orders(orders.trim()).map_err(|e| err(e.to_string()))?;
+            if script.orders.insert((key, power), orders).is_some() {
+                return Err(err(format!(
+                    "second line for {} in {}",
+                    power.name(),
+                    phase
+                )));
+            }
+            if !script.powers.contains(&power) {
+                script.powers.push(power);
+            }
+        }
+        Ok(script)
+    }
+
+    /// The scripted powers, in order of first appearance.
+    pub fn powers(&self) -> &[Power] {
+        &self.powers
+    }
+
+    /// Whether `power` is scripted.
+    pub fn is_scripted(&self, power: Power) -> bool {
+        self.powers.contains(&power)
+    }
+
+    /// The script's orders for `power` in the phase of `state`, or None if
+    /// the script has no line for it.
+    pub fn orders(&self, power: Power, state: &BoardState) -> Option<&[Order]> {
+        self.orders
+            .get(&((state.year, state.season, state.phase), power))
+            .map(Vec::as_slice)
+    }
+
+    /// The last year the script has orders for, or None if it is empty.
+    pub fn last_year(&self) -> Option<u16> {
+        self.orders.keys().map(|((year, _, _), _)| *year).max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dson::format_orders;
+
+    const SCRIPT: &str = "\
+# Lepanto opening for Italy
+1901sm italy: A ven H ; A rom - apu ; F nap - ion
+
+1901fm italy: F ion C A apu - tun ; A apu - tun ; A ven H
+1901sm austria: A vie H
+";
+
+    #[test]
+    fn parses_orders_per_phase_and_power() {
+        let script = OpponentScript::parse(SCRIPT).unwrap();
+        assert_eq!(script.powers(), &[Power::Italy, Power::Austria]);
+        assert!(script.is_scripted(Power::Austria));
+        assert!(!script.is_scripted(Power::Turkey));
+        assert_eq!(script.last_year(), Some(1901));
+
+        let spring = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        let italy = script.orders(Power::Italy, &spring).unwrap();
+        assert_eq!(format_orders(italy), "A ven H ; A rom - apu ; F nap - ion");
+        let fall = BoardState::empty(1901, Season::Fall, Phase::Movement);
+        assert_eq!(script.orders(Power::Italy, &fall).unwrap().len(), 3);
+        assert!(script.orders(Power::Austria, &fall).is_none());
+    }
+
+    #[test]
+    fn reports_the_bad_line() {
+        let cases = [
+            ("1901sm italy A ven H", 1),
+            ("# ok\n1901xm italy: A ven H", 2),
+            ("1901sm prussia: A ber H", 1),
+            ("1901sm italy: A ven jumps", 1),
+            ("1901sm italy: A ven H\n1901sm italy: A ven - tyr", 2),
+        ];
+        for (text, line) in cases {
+            let err = OpponentScript::parse(text).unwrap_err();
+            assert_eq!(err.line, line, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Agreement of the played orders with the raw per-unit favourites.
+//!
+//! The search blends policy and heuristic scores, mixes candidates and
+//! best-responds to an equilibrium, so its orders are expected to differ
+//! from what either scorer ranks first for a unit. How often they differ is
+//! still a useful health signal: a policy model that suddenly loses all
+//! agreement has likely regressed or been fed the wrong inputs, and a
+//! search that always matches the heuristic is probably not using the
+//! network at all. [`AgreementReport`] compares one order set per unit
+//! with the policy's and the heuristic's top order, and
+//! [`AgreementTotals`] keeps the running counts for a game.
+
+use serde_json::{json, Value};
+
+use crate::board::order::Order;
+use crate::board::province::{Power, Province};
+use crate::board::state::BoardState;
+use crate::eval::NeuralEvaluator;
+use crate::protocol::dson::format_order;
+use crate::search::neural_candidates::neural_top_k_per_unit;
+use crate::search::regret_matching::heuristic_top_orders;
+
+/// One unit's played order beside each scorer's favourite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitAgreement {
+    pub province: Province,
+    pub chosen: Order,
+    /// The policy's top order, or None without a policy model.
+    pub policy: Option<Order>,
+    /// The heuristic's top order.
+    pub heuristic: Option<Order>,
+}
+
+/// Per-unit agreement of one order set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgreementReport {
+    pub power: Power,
+    pub units: Vec<UnitAgreement>,
+    /// Whether a policy model scored the units.
+    pub has_policy: bool,
+}
+
+impl AgreementReport {
+    /// Compares `orders` for `power` with the top policy and heuristic
+    /// order of each unit. `prior_scale` weights the heuristic's opening
+    /// priors as in the search.
+    pub fn measure(
+        power: Power,
+        state: &BoardState,
+        orders: &[Order],
+        neural: Option<&NeuralEvaluator>,
+        prior_scale: f32,
+    ) -> Self {
+        let province_of = |o: &Order| o.unit().map(|u| u.location.province);
+        let policy: Option<Vec<Order>> = neural
+            .and_then(|n| neural_top_k_per_unit(n, power, state, 1))
+            .map(|per_unit| {
+                per_unit
+                    .iter()
+                    .filter_map(|u| u.first())
+                    .map(|s| s.order)
+                    .collect()
+            });
+        let heuristic = heuristic_top_orders(power, state, prior_scale);
+        let units = orders
+            .iter()
+            .filter_map(|chosen| {
+                let province = province_of(chosen)?;
+                let find = |list: &[Order]| {
+                    list.iter()
+                        .find(|o| province_of(o) == Some(province))
+                        .copied()
+                };
+                Some(UnitAgreement {
+                    province,
+                    chosen: *chosen,
+                    policy: policy.as_deref().and_then(find),
+                    heuristic: find(&heuristic),
+                })
+            })
+            .collect();
+        AgreementReport {
+            power,
+            units,
+            has_policy: policy.is_some(),
+        }
+    }
+
+    /// Units whose played order is the policy's top order, or None without
+    /// a policy model.
+    pub fn policy_matches(&self) -> Option<usize> {
+        self.has_policy.then(|| {
+            self.units
+                .iter()
+                .filter(|u| u.policy == Some(u.chosen))
+                .count()
+        })
+    }
+
+    /// Units whose played order is the heuristic's top order.
+    pub fn heuristic_matches(&self) -> usize {
+        self.units
+            .iter()
+            .filter(|u| u.heuristic == Some(u.chosen))
+            .count()
+    }
+
+    /// Renders the report, with the game's running `totals`, as the JSON
+    /// payload of an `info agreement` line.
+    pub fn to_json(&self, totals: &AgreementTotals) -> String {
+        let name = |o: Option<Order>| o.map_or(Value::Null, |o| Value::from(format_order(&o)));
+        let differing: Vec<Value> = self
+            .units
+            .iter()
+            .filter(|u| {
+                u.heuristic != Some(u.chosen) || (self.has_policy && u.policy != Some(u.chosen))
+            })
+            .map(|u| {
+                json!({
+                    "chosen": format_order(&u.chosen),
+                    "policy": name(u.policy),
+                    "heuristic": name(u.heuristic),
+                })
+            })
+            .collect();
+        json!({
+            "power": self.power.name(),
+            "units": self.units.len(),
+            "policy": self.policy_matches(),
+            "heuristic": self.heuristic_matches(),
+            "differing": differing,
+            "game": {
+                "units": totals.units,
+                "policy_units": totals.policy_units,
+                "policy": totals.policy,
+                "heuristic": totals.heuristic,
+            },
+        })
+        .to_string()
+    }
+}
+
+/// Running agreement counts over the order sets of a game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AgreementTotals {
+    /// Units compared.
+    pub units: usize,
+    /// Units compared while a policy model was loaded.
+    pub policy_units: usize,
+    /// Units that played the policy's top order.
+    pub policy: usize,
+    /// Units that played the heuristic's top order.
+    pub heuristic: usize,
+}
+
+impl AgreementTotals {
+    /// Adds one report's counts.
+    pub fn add(&mut self, report: &AgreementReport) {
+        self.units += report.units.len();
+        self.heuristic += report.heuristic_matches();
+        if let Some(policy) = report.policy_matches() {
+            self.policy_units += report.units.len();
+            self.policy += policy;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+
+    #[test]
+    fn counts_units_matching_the_heuristic_favourite() {
+        let state = parse_dfen("1901fm/Aavie,Aabud,Rawar/Avie,Abud,Rwar/-").unwrap();
+        let favourite = heuristic_top_orders(Power::Austria, &state, 0.0);
+        assert_eq!(favourite.len(), 2);
+
+        let hold = Order::Hold {
+            unit: favourite[1].unit().unwrap(),
+        };
+        let orders = [favourite[0], hold];
+        let report = AgreementReport::measure(Power::Austria, &state, &orders, None, 0.0);
+        assert_eq!(report.units.len(), 2);
+        assert_eq!(report.policy_matches(), None);
+        let expected = usize::from(hold == favourite[1]) + 1;
+        assert_eq!(report.heuristic_matches(), expected);
+
+        let mut totals = AgreementTotals::default();
+        totals.add(&report);
+        totals.add(&report);
+        assert_eq!(totals.units, 4);
+        assert_eq!(totals.heuristic, 2 * expected);
+        assert_eq!(totals.policy_units, 0);
+
+        let json: Value = serde_json::from_str(&report.to_json(&totals)).unwrap();
+        assert_eq!(json["power"], "austria");
+        assert!(json["policy"].is_null());
+        assert_eq!(json["game"]["units"], 4);
+    }
+}
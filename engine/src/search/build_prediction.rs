@@ -0,0 +1,156 @@
+//! Opponent build and disband prediction.
+//!
+//! A Fall move is judged partly by what the year's adjustments do to the
+//! board: a center taken is worth less if its former owner disbands the
+//! unit we needed to dislodge anyway, and a rival who builds a fleet in
+//! its home port changes next Spring. The lookahead used to play every
+//! power's winter with the build heuristic alone. During Fall the search
+//! now predicts each opponent's adjustments once, from the winter the
+//! current position points to (every unit holding its ground) and the
+//! policy network when one is loaded, and the lookahead plays those
+//! predictions whenever they still fit the simulated winter.
+
+use crate::board::order::Order;
+use crate::board::province::{Power, ALL_POWERS};
+use crate::board::state::{BoardState, Phase, Season};
+use crate::eval::NeuralEvaluator;
+use crate::movegen::build::legal_builds;
+use crate::resolve::advance_state;
+use crate::search::cartesian::heuristic_build_orders;
+use crate::search::neural_candidates::neural_adjustment_orders;
+
+/// Predicted adjustment orders for one power.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildPrediction {
+    pub power: Power,
+    pub orders: Vec<Order>,
+}
+
+/// The Winter board a Fall movement position leads to if every unit holds:
+/// occupied centers change hands and the phase becomes the adjustment
+/// phase. Returns None for other phases or when nobody adjusts.
+pub fn projected_winter(state: &BoardState) -> Option<BoardState> {
+    if state.season != Season::Fall || state.phase != Phase::Movement {
+        return None;
+    }
+    let mut winter = state.clone();
+    advance_state(&mut winter, false);
+    let adjusts = ALL_POWERS
+        .iter()
+        .any(|&p| adjustment_count(p, &winter) != 0);
+    adjusts.then_some(winter)
+}
+
+/// Predicts a power's adjustment orders on a Build-phase board: the policy
+/// network's choice when available, otherwise the build heuristic.
+pub fn predict_adjustments(
+    power: Power,
+    state: &BoardState,
+    neural: Option<&NeuralEvaluator>,
+) -> Vec<Order> {
+    let count = adjustment_count(power, state).unsigned_abs() as usize;
+    if count == 0 {
+        return Vec::new();
+    }
+    neural
+        .and_then(|n| neural_adjustment_orders(n, power, state, &legal_builds(power, state), count))
+        .filter(|orders| orders.len() == count)
+        .unwrap_or_else(|| heuristic_build_orders(power, state))
+}
+
+/// Predicts every opponent's adjustments for the Winter following a Fall
+/// movement position. Powers with nothing to adjust are omitted.
+pub fn predict_opponent_builds(
+    our_power: Power,
+    state: &BoardState,
+    neural: Option<&NeuralEvaluator>,
+) -> Vec<BuildPrediction> {
+    let Some(winter) = projected_winter(state) else {
+        return Vec::new();
+    };
+    ALL_POWERS
+        .iter()
+        .copied()
+        .filter(|&p| p != our_power)
+        .map(|power| BuildPrediction {
+            power,
+            orders: predict_adjustments(power, &winter, neural),
+        })
+        .filter(|b| !b.orders.is_empty())
+        .collect()
+}
+
+/// Returns true if `orders` are a complete, legal adjustment for `power`
+/// on the given Build-phase board.
+pub(crate) fn fits(orders: &[Order], power: Power, state: &BoardState) -> bool {
+    let count = adjustment_count(power, state).unsigned_abs() as usize;
+    if orders.len() != count {
+        return false;
+    }
+    let legal = legal_builds(power, state);
+    orders.iter().all(|o| legal.contains(o))
+}
+
+/// Centers minus units: positive for builds, negative for disbands.
+fn adjustment_count(power: Power, state: &BoardState) -> i32 {
+    let centers = state.sc_owner.iter().filter(|o| **o == Some(power)).count() as i32;
+    let units = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count() as i32;
+    centers - units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    #[test]
+    fn fall_occupation_predicts_build_and_disband() {
+        // Russia sits in Austria's bud: Russia gains a build, Austria must
+        // disband one of its two units.
+        let state = parse_dfen("1901fm/Aavie,Aagal,Rabud,Rfsev/Avie,Abud,Rsev,Rmos/-").unwrap();
+        let winter = projected_winter(&state).unwrap();
+        assert_eq!(winter.phase, Phase::Build);
+
+        let preds = predict_opponent_builds(Power::Austria, &state, None);
+        assert_eq!(preds.len(), 1);
+        assert_eq!(preds[0].power, Power::Russia);
+        assert_eq!(preds[0].orders.len(), 1);
+        assert!(matches!(preds[0].orders[0], Order::Build { .. }));
+
+        let preds = predict_opponent_builds(Power::Russia, &state, None);
+        assert_eq!(preds[0].power, Power::Austria);
+        assert!(matches!(preds[0].orders[0], Order::Disband { .. }));
+        assert!(fits(&preds[0].orders, Power::Austria, &winter));
+    }
+
+    #[test]
+    fn predictions_only_apply_to_fall_movement() {
+        let spring = parse_dfen("1901sm/Aavie/Avie,Abud/-").unwrap();
+        assert!(projected_winter(&spring).is_none());
+        assert!(predict_opponent_builds(Power::Russia, &spring, None).is_empty());
+
+        // Balanced powers have no adjustments at all.
+        let fall = parse_dfen("1901fm/Aavie/Avie/-").unwrap();
+        assert!(projected_winter(&fall).is_none());
+    }
+
+    #[test]
+    fn stale_predictions_do_not_fit() {
+        let winter = parse_dfen("1901fb/Aavie/Avie,Abud/-").unwrap();
+        let build = parse_order("A bud B").unwrap();
+        assert!(fits(&[build], Power::Austria, &winter));
+        // Wrong count, or a build where a unit now stands.
+        assert!(!fits(&[], Power::Austria, &winter));
+        assert!(!fits(
+            &[parse_order("A vie B").unwrap()],
+            Power::Austria,
+            &winter
+        ));
+        assert_eq!(predict_adjustments(Power::Austria, &winter, None).len(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+//! Memory budget for search caches.
+//!
+//! A single `CacheSize` option (in megabytes) is split across the caches an
+//! RM+ search keeps: the future memo, which stores evaluated futures per
+//! (candidate, opponent profile, depth), and the two halves of the
+//! transposition table, which hold greedy lookahead orders and board
+//! evaluations per position. Entry counts are derived from estimated per-entry footprints, so
+//! the engine stays within the budget in tight container limits.
+
+use std::mem::size_of;
+
+use crate::board::order::Order;
+use crate::board::province::Power;
+
+/// Default total cache budget in megabytes.
+pub const DEFAULT_CACHE_MB: usize = 16;
+
+/// Largest accepted budget in megabytes.
+pub const MAX_CACHE_MB: usize = 4096;
+
+/// Share of the budget given to the future memo.
+const FUTURE_SHARE: f64 = 0.65;
+
+/// Share of the budget given to cached board evaluations; the rest goes to
+/// the greedy order cache.
+const EVAL_SHARE: f64 = 0.1;
+
+/// Hash map overhead per entry: control byte plus load-factor slack.
+const MAP_OVERHEAD: usize = 8;
+
+/// Typical number of orders in a cached greedy order set (all powers).
+const TYPICAL_GREEDY_ORDERS: usize = 24;
+
+/// Fewest entries either cache is allowed, however small the budget.
+const MIN_ENTRIES: usize = 64;
+
+/// Entry capacities for each search cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheBudget {
+    /// Total budget the capacities were derived from.
+    pub megabytes: usize,
+    /// Maximum entries in the future memo.
+    pub future_entries: usize,
+    /// Maximum entries in the greedy lookahead order cache.
+    pub greedy_entries: usize,
+    /// Maximum board evaluations in the transposition table.
+    pub eval_entries: usize,
+}
+
+/// Estimated bytes per future memo entry.
+fn future_entry_bytes() -> usize {
+    size_of::<((usize, u64, usize), f64)>() + MAP_OVERHEAD
+}
+
+/// Estimated bytes per cached board evaluation.
+fn eval_entry_bytes() -> usize {
+    size_of::<(u64, f64)>() + MAP_OVERHEAD
+}
+
+/// Estimated bytes per greedy cache entry, including the order vector.
+fn greedy_entry_bytes() -> usize {
+    size_of::<(u64, Vec<(Order, Power)>)>()
+        + MAP_OVERHEAD
+        + TYPICAL_GREEDY_ORDERS * size_of::<(Order, Power)>()
+}
+
+impl CacheBudget {
+    /// Splits `megabytes` (clamped to `1..=MAX_CACHE_MB`) across the caches.
+    pub fn from_mb(megabytes: usize) -> Self {
+        let megabytes = megabytes.clamp(1, MAX_CACHE_MB);
+        let bytes = (megabytes * 1024 * 1024) as f64;
+        let future_bytes = bytes * FUTURE_SHARE;
+        let eval_bytes = bytes * EVAL_SHARE;
+        let greedy_bytes = bytes - future_bytes - eval_bytes;
+        CacheBudget {
+            megabytes,
+            future_entries: ((future_bytes as usize) / future_entry_bytes()).max(MIN_ENTRIES),
+            greedy_entries: ((greedy_bytes as usize) / greedy_entry_bytes()).max(MIN_ENTRIES),
+            eval_entries: ((eval_bytes as usize) / eval_entry_bytes()).max(MIN_ENTRIES),
+        }
+    }
+}
+
+impl Default for CacheBudget {
+    fn default() -> Self {
+        CacheBudget::from_mb(DEFAULT_CACHE_MB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_scales_and_stays_within_limit() {
+        let small = CacheBudget::from_mb(4);
+        let large = CacheBudget::from_mb(64);
+        assert!(large.future_entries > small.future_entries);
+        assert!(large.greedy_entries > small.greedy_entries);
+        assert!(large.eval_entries > small.eval_entries);
+
+        let used = large.future_entries * future_entry_bytes()
+            + large.greedy_entries * greedy_entry_bytes()
+            + large.eval_entries * eval_entry_bytes();
+        assert!(used <= 64 * 1024 * 1024);
+    }
+
+    #[test]
+    fn budget_is_clamped() {
+        assert_eq!(CacheBudget::from_mb(0).megabytes, 1);
+        assert_eq!(CacheBudget::from_mb(1 << 20).megabytes, MAX_CACHE_MB);
+        let tiny = CacheBudget::from_mb(1);
+        assert!(tiny.future_entries >= MIN_ENTRIES && tiny.greedy_entries >= MIN_ENTRIES);
+        assert!(tiny.eval_entries >= MIN_ENTRIES);
+    }
+}
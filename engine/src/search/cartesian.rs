@@ -8,16 +8,20 @@ use std::io::Write;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
-use crate::board::province::{Power, Province, ALL_POWERS, ALL_PROVINCES, PROVINCE_COUNT};
-use crate::board::state::{BoardState, Season};
+use crate::board::province::{Power, Province, ALL_POWERS, MAX_PROVINCES};
+use crate::board::state::BoardState;
 use crate::board::unit::UnitType;
 use crate::board::Order;
 use crate::eval::evaluate;
 use crate::eval::heuristic::{
     count_scs, nearest_unowned_sc_dist, province_defense, province_threat,
 };
-use crate::movegen::movement::legal_orders;
+use crate::movegen::movement::legal_orders_into;
 use crate::resolve::{apply_resolution, Resolver};
+use crate::search::introspect::WhyNotReport;
+use crate::search::rebuild::plan_builds;
+use crate::search::season::SeasonScorer;
+use crate::throughput::{per_second, WorkCounts};
 
 /// Search statistics emitted via `info` lines.
 pub struct SearchInfo {
@@ -27,29 +31,120 @@ pub struct SearchInfo {
     pub elapsed_ms: u64,
 }
 
+/// An opponent's most likely order set in the search equilibrium.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpponentSummary {
+    pub power: Power,
+    /// The opponent candidate with the highest average strategy weight.
+    pub orders: Vec<Order>,
+    /// Average strategy probability of that candidate.
+    pub probability: f32,
+    /// Per order of `orders`: strategy weight on candidates giving that order.
+    pub confidence: Vec<f32>,
+    /// Number of candidates the opponent's strategy mixed over.
+    pub candidates: usize,
+}
+
+/// One of our order sets as the search ranked it, for multi-set output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedOrderSet {
+    pub orders: Vec<Order>,
+    /// Average strategy weight of the set.
+    pub weight: f64,
+    /// Mean blended value of the futures the set was scored in, `None` if
+    /// the search never scored it.
+    pub score: Option<f64>,
+}
+
+/// Search cache counters, for searches that memoize.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    /// Lookups of memoized lookahead values.
+    pub lookups: u64,
+    /// Lookups answered from the cache.
+    pub hits: u64,
+    /// Lookahead values dropped to stay within capacity.
+    pub evictions: u64,
+    /// Greedy opponent order sets held at the end of search.
+    pub greedy_entries: usize,
+    /// Greedy order sets dropped to stay within capacity.
+    pub greedy_evictions: u64,
+    /// Board evaluations held at the end of search.
+    pub eval_entries: usize,
+    /// Lookups of cached board evaluations.
+    pub eval_lookups: u64,
+    /// Evaluation lookups answered from the cache.
+    pub eval_hits: u64,
+    /// Cache memory budget the search ran with, in megabytes.
+    pub budget_mb: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups answered from the cache.
+    pub fn dedup_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.lookups as f64
+        }
+    }
+}
+
 /// Result of a search: the best order set and associated info.
 pub struct SearchResult {
     pub orders: Vec<Order>,
     pub score: f32,
     pub nodes: u64,
+    /// Rejected-candidate introspection, when the search recorded it.
+    pub whynot: Option<WhyNotReport>,
+    /// Per-order confidence in 0..=1, parallel to `orders`: the share of
+    /// the final strategy on order sets containing that order. Empty when
+    /// the search does not mix strategies.
+    pub confidence: Vec<f32>,
+    /// Each opponent's likeliest order set, in `ALL_POWERS` order. Empty
+    /// when the search does not model opponents as strategies.
+    pub opponents: Vec<OpponentSummary>,
+    /// Our heaviest order sets, the chosen one first, when the search was
+    /// asked for them (`RmOptions::multi_order_sets`).
+    pub order_sets: Vec<RankedOrderSet>,
+    /// Search iterations completed (RM+ iterations or Cartesian widenings).
+    pub iterations: u64,
+    /// Wall time the search took.
+    pub elapsed: Duration,
+    /// Cache counters, when the search used caches.
+    pub cache: Option<CacheStats>,
+    /// Whether the search stopped early because its strategy converged.
+    pub converged: bool,
 }
 
-/// Returns the number of unoccupied home SCs for a power (potential build slots).
-fn unoccupied_home_sc_count(power: Power, state: &BoardState) -> i32 {
-    let mut count = 0i32;
-    for (i, p) in ALL_PROVINCES.iter().enumerate() {
-        if p.is_supply_center()
-            && p.home_power() == Some(power)
-            && state.sc_owner[i] == Some(power)
-            && state.units[i].is_none()
-        {
-            count += 1;
+impl SearchResult {
+    /// A result carrying only orders, score and node count, as returned by
+    /// the search's early exits.
+    pub fn new(orders: Vec<Order>, score: f32, nodes: u64) -> Self {
+        SearchResult {
+            orders,
+            score,
+            nodes,
+            whynot: None,
+            confidence: Vec::new(),
+            opponents: Vec::new(),
+            order_sets: Vec::new(),
+            iterations: 0,
+            elapsed: Duration::ZERO,
+            cache: None,
+            converged: false,
         }
     }
-    count
+
+    /// Time left of `budget` when the search returned; nonzero mostly when
+    /// it converged early.
+    pub fn unused(&self, budget: Duration) -> Duration {
+        budget.saturating_sub(self.elapsed)
+    }
 }
 
-/// Scores a single movement order using heuristic features.
+/// Scores a single movement order using heuristic features, plus the
+/// [`SeasonScorer`] hold and movement terms.
 /// Higher score = more promising move.
 fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
     match *order {
@@ -57,7 +152,7 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
             let prov = unit.location.province;
             let mut score: f32 = 0.0;
             // Holding on an owned SC under threat is good
-            if prov.is_supply_center() && state.sc_owner[prov as usize] == Some(power) {
+            if state.map().is_supply_center(prov) && state.sc_owner[prov.index()] == Some(power) {
                 let threat = province_threat(prov, power, state);
                 if threat > 0 {
                     score += 3.0 + threat as f32;
@@ -65,39 +160,17 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
             }
             // Small penalty for holding otherwise (prefer action)
             score -= 1.0;
-
-            // Fall penalty: holding on a home SC when we need builds blocks construction
-            if state.season == Season::Fall
-                && prov.is_supply_center()
-                && prov.home_power() == Some(power)
-                && state.sc_owner[prov as usize] == Some(power)
-            {
-                let sc_count = count_scs(state, power);
-                let unit_count = state
-                    .units
-                    .iter()
-                    .filter(|u| matches!(u, Some((p, _)) if *p == power))
-                    .count() as i32;
-                let pending_builds = sc_count - unit_count;
-                if pending_builds > 0 {
-                    let free_homes = unoccupied_home_sc_count(power, state);
-                    if free_homes < pending_builds {
-                        score -= 8.0;
-                    }
-                }
-            }
-
-            score
+            score + SeasonScorer::for_state(state).hold(prov, power, state)
         }
-        Order::Move { unit, dest } => {
+        Order::Move { unit, dest, .. } => {
             let src = unit.location.province;
             let dst = dest.province;
             let is_fleet = unit.unit_type == UnitType::Fleet;
             let mut score: f32 = 0.0;
 
             // SC capture value
-            if dst.is_supply_center() {
-                let owner = state.sc_owner[dst as usize];
+            if state.map().is_supply_center(dst) {
+                let owner = state.sc_owner[dst.index()];
                 match owner {
                     None => score += 10.0, // neutral SC
                     Some(o) if o != power => {
@@ -112,37 +185,8 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 }
             }
 
-            // Fall departure penalty: don't leave unowned SCs
-            if state.season == Season::Fall
-                && src.is_supply_center()
-                && state.sc_owner[src as usize] != Some(power)
-            {
-                score -= 12.0;
-            }
-
-            // Fall home SC vacating bonus: move off home SCs to make room for builds
-            if state.season == Season::Fall
-                && src.is_supply_center()
-                && src.home_power() == Some(power)
-                && state.sc_owner[src as usize] == Some(power)
-            {
-                let sc_count = count_scs(state, power);
-                let unit_count = state
-                    .units
-                    .iter()
-                    .filter(|u| matches!(u, Some((p, _)) if *p == power))
-                    .count() as i32;
-                let pending_builds = sc_count - unit_count;
-                if pending_builds > 0 {
-                    let free_homes = unoccupied_home_sc_count(power, state);
-                    if free_homes < pending_builds {
-                        score += 8.0;
-                    }
-                }
-            }
-
             // Threat awareness: penalize leaving an owned SC with enemies nearby
-            if src.is_supply_center() && state.sc_owner[src as usize] == Some(power) {
+            if state.map().is_supply_center(src) && state.sc_owner[src.index()] == Some(power) {
                 let threat = province_threat(src, power, state);
                 if threat > 0 {
                     let defense = province_defense(src, power, state);
@@ -154,7 +198,7 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
             }
 
             // Collision penalty: moving to a province occupied by own unit
-            if let Some((p, _)) = state.units[dst as usize] {
+            if let Some((p, _)) = state.units[dst.index()] {
                 if p == power {
                     score -= 15.0;
                 }
@@ -168,13 +212,7 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 score += 3.0 / dist as f32;
             }
 
-            // Spring positioning: prefer provinces adjacent to unowned SCs
-            if state.season == Season::Spring && dst.is_supply_center() {
-                let owner = state.sc_owner[dst as usize];
-                if owner != Some(power) {
-                    score += 4.0;
-                }
-            }
+            score += SeasonScorer::for_state(state).movement(src, dst, power, state);
 
             score
         }
@@ -185,7 +223,8 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 -2.0 // No threat = waste of a move
             } else {
                 let mut score: f32 = 1.0;
-                if prov.is_supply_center() && state.sc_owner[prov as usize] == Some(power) {
+                if state.map().is_supply_center(prov) && state.sc_owner[prov.index()] == Some(power)
+                {
                     score += 4.0 + threat as f32;
                 }
                 score
@@ -193,7 +232,7 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
         }
         Order::SupportMove { dest, .. } => {
             let dst = dest.province;
-            let has_enemy_unit = matches!(state.units[dst as usize], Some((p, _)) if p != power);
+            let has_enemy_unit = matches!(state.units[dst.index()], Some((p, _)) if p != power);
             let threat = province_threat(dst, power, state);
 
             // If destination has no enemy unit AND no adjacent enemies that could
@@ -204,8 +243,8 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
 
             let mut score: f32 = 2.0;
             // Supporting moves into unowned SCs is valuable
-            if dst.is_supply_center() {
-                let owner = state.sc_owner[dst as usize];
+            if state.map().is_supply_center(dst) {
+                let owner = state.sc_owner[dst.index()];
                 if owner.is_none() {
                     score += 6.0;
                 } else if owner != Some(power) {
@@ -218,7 +257,7 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 // Dislodge-for-capture: supporting a move into an SC occupied by
                 // an enemy is very high value — the support enables both the
                 // dislodge and the SC flip.
-                if dst.is_supply_center() && state.sc_owner[dst as usize] != Some(power) {
+                if state.map().is_supply_center(dst) && state.sc_owner[dst.index()] != Some(power) {
                     score += 6.0;
                 }
             }
@@ -231,29 +270,30 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
 
 /// A scored candidate order for a single unit.
 #[derive(Clone, Copy)]
-struct ScoredOrder {
-    order: Order,
-    score: f32,
+pub(crate) struct ScoredOrder {
+    pub(crate) order: Order,
+    pub(crate) score: f32,
 }
 
 /// Generates top-K orders per unit, sorted descending by heuristic score.
-fn top_k_per_unit(power: Power, state: &BoardState, k: usize) -> Vec<Vec<ScoredOrder>> {
+pub(crate) fn top_k_per_unit(power: Power, state: &BoardState, k: usize) -> Vec<Vec<ScoredOrder>> {
     let mut per_unit: Vec<Vec<ScoredOrder>> = Vec::new();
+    let mut legal = Vec::new();
 
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some((p, _)) = state.units[i] {
             if p != power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
-            let legal = legal_orders(prov, state);
+            let prov = Province::from_index(i);
+            legal_orders_into(prov, state, &mut legal);
             if legal.is_empty() {
                 continue;
             }
 
             let mut scored: Vec<ScoredOrder> = legal
-                .into_iter()
-                .map(|o| ScoredOrder {
+                .iter()
+                .map(|&o| ScoredOrder {
                     order: o,
                     score: score_order(&o, power, state),
                 })
@@ -275,6 +315,7 @@ fn top_k_per_unit(power: Power, state: &BoardState, k: usize) -> Vec<Vec<ScoredO
 /// Predicts opponent orders: each enemy unit plays its highest-scored move.
 pub(crate) fn predict_opponent_orders(power: Power, state: &BoardState) -> Vec<(Order, Power)> {
     let mut orders: Vec<(Order, Power)> = Vec::new();
+    let mut legal = Vec::new();
 
     for &p in ALL_POWERS.iter() {
         if p == power {
@@ -289,20 +330,20 @@ pub(crate) fn predict_opponent_orders(power: Power, state: &BoardState) -> Vec<(
             continue;
         }
 
-        for i in 0..PROVINCE_COUNT {
+        for i in 0..MAX_PROVINCES {
             if let Some((up, _)) = state.units[i] {
                 if up != p {
                     continue;
                 }
-                let prov = ALL_PROVINCES[i];
-                let legal = legal_orders(prov, state);
+                let prov = Province::from_index(i);
+                legal_orders_into(prov, state, &mut legal);
                 if legal.is_empty() {
                     continue;
                 }
 
                 // Pick the highest-scored order
-                let best = legal
-                    .into_iter()
+                let best = *legal
+                    .iter()
                     .max_by(|a, b| {
                         let sa = score_order(a, p, state);
                         let sb = score_order(b, p, state);
@@ -330,6 +371,7 @@ pub fn search<W: Write>(
     stop: &AtomicBool,
 ) -> SearchResult {
     let start = Instant::now();
+    let work_start = WorkCounts::now();
 
     // Predict opponent orders once
     let opponent_orders = predict_opponent_orders(power, state);
@@ -340,6 +382,7 @@ pub fn search<W: Write>(
 
     // Reusable resolver to minimize allocations
     let mut resolver = Resolver::new(64);
+    let mut iterations: u64 = 0;
 
     // Iterative deepening: K=2, 3, 4, 5
     for k in 2..=5 {
@@ -381,17 +424,22 @@ pub fn search<W: Write>(
         );
 
         total_nodes += nodes;
+        iterations += 1;
 
         if score > best_score {
             best_score = score;
             best_orders = orders;
         }
 
-        let elapsed_ms = start.elapsed().as_millis() as u64;
+        let elapsed = start.elapsed();
         let _ = writeln!(
             out,
-            "info depth {} nodes {} score {} time {}",
-            k, total_nodes, best_score as i32, elapsed_ms
+            "info depth {} nodes {} nps {} score {} time {}",
+            k,
+            total_nodes,
+            per_second(total_nodes, elapsed),
+            best_score as i32,
+            elapsed.as_millis() as u64
         );
 
         // If we enumerated all combos quickly, keep going
@@ -400,11 +448,19 @@ pub fn search<W: Write>(
         }
     }
 
+    let _ = writeln!(
+        out,
+        "info string {}",
+        WorkCounts::now()
+            .since(work_start)
+            .rates_line(start.elapsed())
+    );
+
     // Fallback: if search found nothing (no units?), return empty
     SearchResult {
-        orders: best_orders,
-        score: best_score,
-        nodes: total_nodes,
+        iterations,
+        elapsed: start.elapsed(),
+        ..SearchResult::new(best_orders, best_score, total_nodes)
     }
 }
 
@@ -506,12 +562,12 @@ pub fn heuristic_retreat_orders(power: Power, state: &BoardState) -> Vec<Order>
 
     let mut orders = Vec::new();
 
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some(d) = &state.dislodged[i] {
             if d.power != power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
+            let prov = Province::from_index(i);
             let legal = legal_retreats(prov, state);
             if legal.is_empty() {
                 continue;
@@ -541,13 +597,13 @@ fn score_retreat(order: &Order, power: Power, state: &BoardState) -> f32 {
             let mut score: f32 = 0.0;
 
             // Prefer own SCs (defend them)
-            if dst.is_supply_center() && state.sc_owner[dst as usize] == Some(power) {
+            if state.map().is_supply_center(dst) && state.sc_owner[dst.index()] == Some(power) {
                 score += 6.0;
             }
 
             // Prefer unowned SCs
-            if dst.is_supply_center() {
-                let owner = state.sc_owner[dst as usize];
+            if state.map().is_supply_center(dst) {
+                let owner = state.sc_owner[dst.index()];
                 if owner.is_none() {
                     score += 4.0;
                 } else if owner != Some(power) {
@@ -596,10 +652,11 @@ pub fn heuristic_build_orders(power: Power, state: &BoardState) -> Vec<Order> {
     }
 }
 
-/// Picks the best builds from available options.
+/// Picks the best builds from available options, leaving the choice to the
+/// theater rebuild planner.
 fn heuristic_builds(power: Power, state: &BoardState, legal: &[Order], count: usize) -> Vec<Order> {
     // Score each build option
-    let mut scored: Vec<(Order, f32)> = legal
+    let scored: Vec<(Order, f32)> = legal
         .iter()
         .filter_map(|o| match o {
             Order::Build { unit } => {
@@ -637,34 +694,12 @@ fn heuristic_builds(power: Power, state: &BoardState, legal: &[Order], count: us
         })
         .collect();
 
-    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-    let mut orders = Vec::new();
-    let mut used_provs: Vec<Province> = Vec::new();
-
-    for (order, _score) in &scored {
-        if orders.len() >= count {
-            break;
-        }
-        if let Order::Build { unit } = order {
-            if used_provs.contains(&unit.location.province) {
-                continue;
-            }
-            used_provs.push(unit.location.province);
-            orders.push(*order);
-        }
-    }
-
-    // If we couldn't fill all builds, waive the rest
-    while orders.len() < count {
-        orders.push(Order::Waive);
-    }
-
-    orders
+    plan_builds(power, state, &scored, count)
 }
 
-/// Picks the best disbands from available options.
-fn heuristic_disbands(
+/// Picks the best disbands from available options, least valuable unit
+/// first.
+pub(crate) fn heuristic_disbands(
     power: Power,
     state: &BoardState,
     legal: &[Order],
@@ -686,7 +721,8 @@ fn heuristic_disbands(
                 }
 
                 // Units on own SCs under threat are valuable
-                if prov.is_supply_center() && state.sc_owner[prov as usize] == Some(power) {
+                if state.map().is_supply_center(prov) && state.sc_owner[prov.index()] == Some(power)
+                {
                     value += 3.0;
                     if province_threat(prov, power, state) > 0 {
                         value += 4.0;
@@ -708,8 +744,8 @@ fn heuristic_disbands(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::province::Coast;
-    use crate::board::state::Phase;
+    use crate::board::province::{Coast, Province};
+    use crate::board::state::{Phase, Season};
     use crate::protocol::dfen::parse_dfen;
 
     const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
@@ -755,7 +791,7 @@ mod tests {
         match result.orders[0] {
             Order::Move { dest, .. } => {
                 assert!(
-                    dest.province.is_supply_center(),
+                    state.map().is_supply_center(dest.province),
                     "Should move to an unowned SC, got {:?}",
                     dest.province
                 );
@@ -855,7 +891,7 @@ mod tests {
         match orders[0] {
             Order::Retreat { dest, .. } => {
                 assert!(
-                    dest.province.is_supply_center()
+                    state.map().is_supply_center(dest.province)
                         || dest.province == Province::Alb
                         || dest.province == Province::Tri,
                     "Should prefer retreating to SCs or useful provinces"
@@ -0,0 +1,257 @@
+//! Coalition-proof order selection for the late game.
+//!
+//! RM+ treats every opponent as playing its own equilibrium strategy,
+//! independently of the others. With three or four powers left that is
+//! optimistic: two of them can agree to move against the third, and the
+//! orders that do best against each opponent on its own may collapse when
+//! the others coordinate. [`coalition_proof`] enumerates, for each of our
+//! heaviest candidates, every joint profile of the opponents' heaviest
+//! candidates, which covers every coalition: the powers that leave their
+//! modal candidate for one that hurts us more form the coalition, and the
+//! rest keep to equilibrium. The set with the best worst case is chosen.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::board::order::Order;
+use crate::board::province::Power;
+use crate::search::regret_matching::PowerCandidates;
+
+/// Most powers left, ourselves included, for the enumeration to run.
+pub(crate) const COALITION_MAX_POWERS: usize = 4;
+
+/// Fewest powers left for coalitions to matter.
+const COALITION_MIN_POWERS: usize = 3;
+
+/// Our heaviest candidates compared.
+const OUR_SETS: usize = 6;
+
+/// Heaviest candidates per opponent in the joint profiles.
+const OPPONENT_SETS: usize = 4;
+
+/// The set [`coalition_proof`] chose and what it guards against.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CoalitionChoice {
+    /// Index of the chosen set among our candidates.
+    pub index: usize,
+    /// Rank of the chosen set by RM+ weight, 0 for the equilibrium pick.
+    pub rank: usize,
+    /// Value of the chosen set against its worst joint profile.
+    pub worst: f64,
+    /// Value of the chosen set when every opponent plays its modal
+    /// candidate.
+    pub equilibrium: f64,
+    /// Opponents that leave their modal candidate in the worst profile.
+    pub coalition: Vec<Power>,
+    /// Profiles scored.
+    pub evaluated: usize,
+}
+
+/// Whether enough powers are left for coalitions to matter and few enough
+/// to enumerate them.
+pub(crate) fn applies(powers: usize) -> bool {
+    (COALITION_MIN_POWERS..=COALITION_MAX_POWERS).contains(&powers)
+}
+
+/// Candidate indices, heaviest first, at most `n` of them.
+fn heaviest(weights: &[f64], n: usize) -> Vec<usize> {
+    let mut idx: Vec<usize> = (0..weights.len()).collect();
+    idx.sort_by(|&a, &b| {
+        weights[b]
+            .partial_cmp(&weights[a])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    idx.truncate(n);
+    idx
+}
+
+/// Picks among our heaviest candidates the one whose worst value over all
+/// joint profiles of the opponents' heaviest candidates is highest. `value`
+/// scores our set against one joint profile. Ties keep the heavier set.
+/// Returns `None` if `deadline` or `stop` cut the search before the
+/// equilibrium pick was fully scored.
+pub(crate) fn coalition_proof<F>(
+    power_candidates: &[PowerCandidates],
+    weights: &[Vec<f64>],
+    our_idx: usize,
+    deadline: Instant,
+    stop: &AtomicBool,
+    mut value: F,
+) -> Option<CoalitionChoice>
+where
+    F: FnMut(&[(Order, Power)], &[(Order, Power)]) -> f64,
+{
+    let ours = heaviest(&weights[our_idx], OUR_SETS);
+    let opponents: Vec<(usize, Vec<usize>)> = (0..power_candidates.len())
+        .filter(|&pi| pi != our_idx)
+        .map(|pi| (pi, heaviest(&weights[pi], OPPONENT_SETS)))
+        .collect();
+
+    let mut best: Option<CoalitionChoice> = None;
+    let mut evaluated = 0;
+    let mut profile = Vec::with_capacity(32);
+    'sets: for (rank, &ci) in ours.iter().enumerate() {
+        let set = &power_candidates[our_idx].1[ci];
+        let mut choice = vec![0usize; opponents.len()];
+        let mut worst = f64::INFINITY;
+        let mut worst_choice = choice.clone();
+        let mut equilibrium = 0.0;
+        loop {
+            if stop.load(Ordering::Relaxed) || Instant::now() >= deadline {
+                break 'sets;
+            }
+            profile.clear();
+            for (k, (pi, top)) in opponents.iter().enumerate() {
+                profile.extend_from_slice(&power_candidates[*pi].1[top[choice[k]]]);
+            }
+            let v = value(set, &profile);
+            evaluated += 1;
+            if choice.iter().all(|&c| c == 0) {
+                equilibrium = v;
+            }
+            if v < worst {
+                worst = v;
+                worst_choice.clone_from(&choice);
+            }
+            if !next_profile(&mut choice, &opponents) {
+                break;
+            }
+        }
+        if best.as_ref().is_none_or(|b| worst > b.worst) {
+            best = Some(CoalitionChoice {
+                index: ci,
+                rank,
+                worst,
+                equilibrium,
+                coalition: opponents
+                    .iter()
+                    .zip(&worst_choice)
+                    .filter(|(_, &c)| c != 0)
+                    .map(|((pi, _), _)| power_candidates[*pi].0)
+                    .collect(),
+                evaluated: 0,
+            });
+        }
+    }
+    best.map(|b| CoalitionChoice { evaluated, ..b })
+}
+
+/// Steps `choice` to the next joint profile, mixed-radix; false once every
+/// profile has been visited.
+fn next_profile(choice: &mut [usize], opponents: &[(usize, Vec<usize>)]) -> bool {
+    for (k, (_, top)) in opponents.iter().enumerate() {
+        choice[k] += 1;
+        if choice[k] < top.len() {
+            return true;
+        }
+        choice[k] = 0;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dson::parse_orders;
+    use std::time::Duration;
+
+    fn set(s: &str, power: Power) -> Vec<(Order, Power)> {
+        parse_orders(s)
+            .unwrap()
+            .into_iter()
+            .map(|o| (o, power))
+            .collect()
+    }
+
+    /// Austria can press into Galicia, which is worth most unless Russia
+    /// and Turkey both turn on it, or sit tight.
+    fn three_powers() -> (Vec<PowerCandidates>, Vec<Vec<f64>>) {
+        let cands = vec![
+            (
+                Power::Austria,
+                vec![
+                    set("A vie - gal", Power::Austria),
+                    set("A vie H", Power::Austria),
+                ],
+            ),
+            (
+                Power::Russia,
+                vec![
+                    set("A war H", Power::Russia),
+                    set("A war - gal", Power::Russia),
+                ],
+            ),
+            (
+                Power::Turkey,
+                vec![
+                    set("A bul H", Power::Turkey),
+                    set("A bul - ser", Power::Turkey),
+                ],
+            ),
+        ];
+        let weights = vec![vec![0.7, 0.3], vec![0.8, 0.2], vec![0.9, 0.1]];
+        (cands, weights)
+    }
+
+    fn score(ours: &[(Order, Power)], profile: &[(Order, Power)]) -> f64 {
+        let attacks = profile
+            .iter()
+            .filter(|(o, _)| matches!(o, Order::Move { .. }))
+            .count();
+        let pressing = matches!(ours[0].0, Order::Move { .. });
+        match (pressing, attacks) {
+            (true, 2) => -3.0,
+            (true, _) => 2.0,
+            (false, n) => -(n as f64) * 0.5,
+        }
+    }
+
+    fn run(cands: &[PowerCandidates], weights: &[Vec<f64>]) -> Option<CoalitionChoice> {
+        coalition_proof(
+            cands,
+            weights,
+            0,
+            Instant::now() + Duration::from_secs(10),
+            &AtomicBool::new(false),
+            score,
+        )
+    }
+
+    #[test]
+    fn guards_against_the_worst_coalition() {
+        let (cands, weights) = three_powers();
+        let choice = run(&cands, &weights).unwrap();
+        assert_eq!(choice.index, 1);
+        assert_eq!(choice.rank, 1);
+        assert_eq!(choice.worst, -1.0);
+        assert_eq!(choice.equilibrium, 0.0);
+        assert_eq!(choice.coalition, vec![Power::Russia, Power::Turkey]);
+        assert_eq!(choice.evaluated, 8);
+    }
+
+    #[test]
+    fn keeps_the_equilibrium_pick_when_it_is_safe() {
+        let (mut cands, weights) = three_powers();
+        // Turkey has no attack to join.
+        cands[2].1[1] = set("A bul H", Power::Turkey);
+        let choice = run(&cands, &weights).unwrap();
+        assert_eq!((choice.index, choice.rank), (0, 0));
+        assert_eq!(choice.worst, 2.0);
+        assert!(choice.coalition.is_empty());
+    }
+
+    #[test]
+    fn applies_only_with_three_or_four_powers() {
+        assert!(!applies(2));
+        assert!(applies(3) && applies(4));
+        assert!(!applies(5));
+    }
+
+    #[test]
+    fn stops_without_a_choice() {
+        let (cands, weights) = three_powers();
+        let stop = AtomicBool::new(true);
+        let choice = coalition_proof(&cands, &weights, 0, Instant::now(), &stop, score);
+        assert_eq!(choice, None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Constraints on generated candidate order sets.
+//!
+//! Several callers need candidates for only part of a turn: centaur play,
+//! where the host fixes some units' orders with `lockorder` and the search
+//! chooses the rest; agreements to keep out of a province; and probes that
+//! ask what a power does if a given unit plays a given order. A
+//! [`Constraints`] value pins orders for some units and forbids moves into
+//! some provinces. Candidate generation honours it per unit, so the free
+//! units are chosen around the pinned ones (supports for a pinned move,
+//! say), and [`Constraints::enforce`] re-applies it to the finished pool,
+//! which covers injected and carried-over candidates.
+//!
+//! [`apply_locks`] applies pins to a single order set; the engine uses it on
+//! its final orders, which covers searches and phases that never build a
+//! pool (cartesian search, book hits, retreats, builds).
+
+use std::collections::HashSet;
+
+use crate::board::order::Order;
+use crate::board::province::{Power, Province};
+
+/// Pinned orders and forbidden destinations for one power's candidates.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Constraints {
+    /// Orders fixed for some units, at most one per unit.
+    pub pinned: Vec<Order>,
+    /// Provinces no candidate may move into, or support or convoy a move
+    /// into.
+    pub forbidden: Vec<Province>,
+}
+
+impl Constraints {
+    /// Constraints pinning `orders`.
+    pub fn pinning(orders: &[Order]) -> Self {
+        Constraints {
+            pinned: orders.to_vec(),
+            forbidden: Vec::new(),
+        }
+    }
+
+    /// Returns true if nothing is constrained.
+    pub fn is_empty(&self) -> bool {
+        self.pinned.is_empty() && self.forbidden.is_empty()
+    }
+
+    /// The pinned order for the unit in `province`, if any.
+    pub fn pinned_for(&self, province: Province) -> Option<Order> {
+        self.pinned
+            .iter()
+            .copied()
+            .find(|o| o.unit().is_some_and(|u| u.location.province == province))
+    }
+
+    /// Returns true unless `order` moves into, or supports or convoys a move
+    /// into, a forbidden province.
+    pub fn allows(&self, order: &Order) -> bool {
+        target(order).is_none_or(|dest| !self.forbidden.contains(&dest))
+    }
+
+    /// Adds `other`'s pins for units not already pinned, then its forbidden
+    /// provinces that no pinned order targets. What `self` already holds
+    /// wins, so host locks can be merged with agreed terms without
+    /// leaving a unit nothing to play.
+    pub fn merge(&mut self, other: &Constraints) {
+        for order in &other.pinned {
+            let free = order
+                .unit()
+                .is_some_and(|u| self.pinned_for(u.location.province).is_none());
+            if free {
+                self.pinned.push(*order);
+            }
+        }
+        for &province in &other.forbidden {
+            let targeted = self.pinned.iter().any(|o| target(o) == Some(province));
+            if !targeted && !self.forbidden.contains(&province) {
+                self.forbidden.push(province);
+            }
+        }
+    }
+
+    /// Applies the pins to each of `power`'s candidate order sets, then
+    /// drops candidates with a forbidden order and duplicates.
+    pub(crate) fn enforce(&self, cands: &mut Vec<Vec<(Order, Power)>>, power: Power) {
+        if self.is_empty() {
+            return;
+        }
+        let mut seen: HashSet<Vec<Order>> = HashSet::new();
+        cands.retain_mut(|cand| {
+            let orders: Vec<Order> = cand.iter().map(|(o, _)| *o).collect();
+            let pinned = apply_locks(&orders, &self.pinned);
+            if !pinned.iter().all(|o| self.allows(o)) {
+                return false;
+            }
+            let mut key = pinned.clone();
+            key.sort_by_key(|o| o.unit().map(|u| u.location.province.index()));
+            *cand = pinned.into_iter().map(|o| (o, power)).collect();
+            seen.insert(key)
+        });
+    }
+}
+
+/// The province `order` moves into, or supports or convoys a move into.
+fn target(order: &Order) -> Option<Province> {
+    match *order {
+        Order::Move { dest, .. } | Order::Retreat { dest, .. } => Some(dest.province),
+        Order::SupportMove { dest, .. } => Some(dest.province),
+        Order::Convoy { convoyed_to, .. } => Some(convoyed_to.province),
+        _ => None,
+    }
+}
+
+/// Returns `orders` with every order for a locked unit replaced by its lock.
+/// Locks for units missing from `orders`, and locked waives, are appended.
+pub fn apply_locks(orders: &[Order], locked: &[Order]) -> Vec<Order> {
+    let mut out = orders.to_vec();
+    for lock in locked {
+        let slot = lock.unit().and_then(|lu| {
+            out.iter().position(|o| {
+                o.unit()
+                    .is_some_and(|u| u.location.province == lu.location.province)
+            })
+        });
+        match slot {
+            Some(i) => out[i] = *lock,
+            None => out.push(*lock),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dson::parse_order;
+
+    fn orders(list: &[&str]) -> Vec<Order> {
+        list.iter().map(|s| parse_order(s).unwrap()).collect()
+    }
+
+    #[test]
+    fn locks_replace_matching_units() {
+        let base = orders(&["A vie - gal", "A bud - ser", "F tri H"]);
+        let locked = orders(&["A bud - rum", "A war H"]);
+        assert_eq!(
+            apply_locks(&base, &locked),
+            orders(&["A vie - gal", "A bud - rum", "F tri H", "A war H"])
+        );
+        assert_eq!(apply_locks(&base, &[]), base);
+    }
+
+    #[test]
+    fn forbidden_destinations_cover_supports_and_convoys() {
+        let c = Constraints {
+            pinned: Vec::new(),
+            forbidden: vec![Province::Gal],
+        };
+        assert!(!c.allows(&parse_order("A vie - gal").unwrap()));
+        assert!(!c.allows(&parse_order("A bud S A vie - gal").unwrap()));
+        assert!(c.allows(&parse_order("A bud S A gal H").unwrap()));
+        assert!(c.allows(&parse_order("A vie - tyr").unwrap()));
+        assert!(Constraints::pinning(&[]).is_empty());
+        assert!(!c.is_empty());
+    }
+
+    #[test]
+    fn merge_keeps_existing_pins_and_their_targets() {
+        let mut c = Constraints::pinning(&orders(&["A vie - gal"]));
+        c.merge(&Constraints {
+            pinned: orders(&["A vie H", "A bud - ser"]),
+            forbidden: vec![Province::Gal, Province::Tyr],
+        });
+        assert_eq!(c.pinned, orders(&["A vie - gal", "A bud - ser"]));
+        assert_eq!(c.forbidden, vec![Province::Tyr]);
+    }
+
+    #[test]
+    fn pinned_candidates_are_deduplicated() {
+        let set = |list: &[&str]| -> Vec<(Order, Power)> {
+            orders(list)
+                .into_iter()
+                .map(|o| (o, Power::Austria))
+                .collect()
+        };
+        let mut cands = vec![
+            set(&["A vie - gal", "A bud - ser"]),
+            set(&["A vie - gal", "A bud - rum"]),
+            set(&["A vie - tyr", "A bud - ser"]),
+        ];
+        let c = Constraints {
+            pinned: orders(&["A bud H"]),
+            forbidden: vec![Province::Tyr],
+        };
+        c.enforce(&mut cands, Power::Austria);
+        assert_eq!(cands, vec![set(&["A vie - gal", "A bud H"])]);
+    }
+}
@@ -0,0 +1,239 @@
+//! Gunboat convention memory.
+//!
+//! Without press, powers signal intent by what they repeatedly do not do.
+//! The classic case is a border DMZ: two neighbours who could each walk into
+//! a province leave it empty turn after turn. The tracker watches the
+//! sequence of movement positions the engine is given, records each empty
+//! province both sides of a pair could have entered, and counts how many
+//! consecutive turns the pair kept out. Once a streak is long enough the
+//! convention is established and RM+ charges our candidates a penalty for
+//! breaking it. Any unit entering the province ends the convention.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::board::order::Order;
+use crate::board::province::{Coast, Power, Province, ALL_POWERS};
+use crate::board::state::{BoardState, Phase};
+use crate::eval::heuristic::unit_can_reach;
+
+/// Consecutive empty turns before a DMZ counts as a convention.
+pub const CONVENTION_MIN_STREAK: u32 = 2;
+
+/// Penalty per order that breaks an established convention.
+const CONVENTION_PENALTY: f64 = 1.0;
+
+/// An observed demilitarized zone between two powers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Convention {
+    pub province: Province,
+    /// The two neighbours keeping out, in `ALL_POWERS` order.
+    pub powers: (Power, Power),
+    /// Consecutive movement turns the province stayed empty.
+    pub streak: u32,
+}
+
+impl Convention {
+    /// Returns true if the power is a party to the convention.
+    pub fn involves(&self, power: Power) -> bool {
+        self.powers.0 == power || self.powers.1 == power
+    }
+}
+
+/// Tracks DMZ streaks across consecutive movement positions.
+#[derive(Debug, Clone, Default)]
+pub struct ConventionTracker {
+    streaks: HashMap<(Province, Power, Power), u32>,
+    last: Option<BoardState>,
+}
+
+/// Empty provinces with the pairs of powers that could each move in.
+pub(crate) fn dmz_candidates(state: &BoardState) -> Vec<(Province, Power, Power)> {
+    let mut out = Vec::new();
+    for &prov in state.map().provinces() {
+        if state.units[prov.index()].is_some() {
+            continue;
+        }
+        let mut reach = [false; 7];
+        for (i, unit) in state.units.iter().enumerate() {
+            if let Some((p, ut)) = unit {
+                let coast = state.fleet_coast[i].unwrap_or(Coast::None);
+                if unit_can_reach(state.map(), Province::from_index(i), coast, *ut, prov) {
+                    reach[*p as usize] = true;
+                }
+            }
+        }
+        for a in 0..7 {
+            for b in (a + 1)..7 {
+                if reach[a] && reach[b] {
+                    out.push((prov, ALL_POWERS[a], ALL_POWERS[b]));
+                }
+            }
+        }
+    }
+    out
+}
+
+impl ConventionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets all observations.
+    pub fn reset(&mut self) {
+        self.streaks.clear();
+        self.last = None;
+    }
+
+    /// Observes a new position.
+    ///
+    /// Only movement positions count. A position that does not directly
+    /// follow the previous one (new game, rewind, skipped turn) restarts
+    /// tracking from scratch.
+    pub fn observe(&mut self, state: &BoardState) {
+        if state.phase != Phase::Movement {
+            return;
+        }
+        let prev = match self.last.take() {
+            Some(prev) if state.turn_index() == prev.turn_index() + 1 => prev,
+            _ => {
+                self.streaks.clear();
+                self.last = Some(state.clone());
+                return;
+            }
+        };
+
+        let kept: HashSet<(Province, Power, Power)> = dmz_candidates(&prev)
+            .into_iter()
+            .filter(|&(prov, _, _)| state.units[prov.index()].is_none())
+            .collect();
+        let old = std::mem::take(&mut self.streaks);
+        self.streaks = kept
+            .into_iter()
+            .map(|key| (key, old.get(&key).copied().unwrap_or(0) + 1))
+            .collect();
+        self.last = Some(state.clone());
+    }
+
+    /// Returns conventions with a streak of at least `CONVENTION_MIN_STREAK`,
+    /// in province order.
+    pub fn established(&self) -> Vec<Convention> {
+        let mut out: Vec<Convention> = self
+            .streaks
+            .iter()
+            .filter(|(_, &streak)| streak >= CONVENTION_MIN_STREAK)
+            .map(|(&(province, a, b), &streak)| Convention {
+                province,
+                powers: (a, b),
+                streak,
+            })
+            .collect();
+        out.sort_by_key(|c| (c.province.index(), c.powers.0 as usize, c.powers.1 as usize));
+        out
+    }
+}
+
+/// Penalty for a candidate that moves (or supports a move) into a DMZ the
+/// power has been honoring.
+pub fn convention_penalty(
+    orders: &[(Order, Power)],
+    power: Power,
+    conventions: &[Convention],
+) -> f64 {
+    let mut penalty = 0.0;
+    for &(order, _) in orders {
+        let target = match order {
+            Order::Move { dest, .. } => dest.province,
+            Order::SupportMove { dest, .. } => dest.province,
+            _ => continue,
+        };
+        if conventions
+            .iter()
+            .any(|c| c.province == target && c.involves(power))
+        {
+            penalty += CONVENTION_PENALTY;
+        }
+    }
+    penalty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::order::{Location, OrderUnit};
+    use crate::board::unit::UnitType;
+    use crate::protocol::dfen::parse_dfen;
+
+    // Austria in Vie and Russia in War both border Galicia.
+    fn border(year: u16, season: char, extra: &str) -> BoardState {
+        parse_dfen(&format!(
+            "{}{}m/Aavie,Rawar{}/Avie,Rwar/-",
+            year, season, extra
+        ))
+        .unwrap()
+    }
+
+    fn galicia_dmz(tracker: &ConventionTracker) -> Option<Convention> {
+        tracker
+            .established()
+            .into_iter()
+            .find(|c| c.province == Province::Gal)
+    }
+
+    #[test]
+    fn repeated_empty_border_becomes_convention() {
+        let mut tracker = ConventionTracker::new();
+        tracker.observe(&border(1901, 's', ""));
+        tracker.observe(&border(1901, 'f', ""));
+        assert!(galicia_dmz(&tracker).is_none());
+        tracker.observe(&border(1902, 's', ""));
+        let c = galicia_dmz(&tracker).expect("Galicia DMZ");
+        assert_eq!(c.powers, (Power::Austria, Power::Russia));
+        assert_eq!(c.streak, 2);
+    }
+
+    #[test]
+    fn entering_the_province_breaks_convention() {
+        let mut tracker = ConventionTracker::new();
+        for (y, s) in [(1901, 's'), (1901, 'f'), (1902, 's')] {
+            tracker.observe(&border(y, s, ""));
+        }
+        tracker.observe(&border(1902, 'f', ",Ragal"));
+        assert!(galicia_dmz(&tracker).is_none());
+    }
+
+    #[test]
+    fn non_consecutive_position_restarts_tracking() {
+        let mut tracker = ConventionTracker::new();
+        for (y, s) in [(1901, 's'), (1901, 'f'), (1902, 's')] {
+            tracker.observe(&border(y, s, ""));
+        }
+        tracker.observe(&border(1905, 's', ""));
+        assert!(tracker.established().is_empty());
+    }
+
+    #[test]
+    fn penalty_applies_to_parties_only() {
+        let conventions = [Convention {
+            province: Province::Gal,
+            powers: (Power::Austria, Power::Russia),
+            streak: 3,
+        }];
+        let into_gal = Order::Move {
+            unit: OrderUnit {
+                unit_type: UnitType::Army,
+                location: Location::new(Province::Vie),
+            },
+            dest: Location::new(Province::Gal),
+            via_convoy: false,
+        };
+        let orders = [(into_gal, Power::Austria)];
+        assert_eq!(
+            convention_penalty(&orders, Power::Austria, &conventions),
+            1.0
+        );
+        assert_eq!(
+            convention_penalty(&orders, Power::Turkey, &conventions),
+            0.0
+        );
+    }
+}
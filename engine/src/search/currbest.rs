@@ -0,0 +1,101 @@
+//! Interim best orders of a running search.
+//!
+//! A long search that crashes or runs past a host's patience leaves the
+//! host with nothing to play. RM+ and MCTS searches therefore offer their
+//! leading candidate to a [`CurrentBest`] after every iteration, and the
+//! engine reports each new leader as `info currbest <dson>` while the
+//! search runs, much as chess engines report `currmove`. A host can play
+//! the last one it saw if the final `bestorders` never comes.
+//!
+//! A leader is published when it differs from the last one published and
+//! the interval has passed since then, so a search flipping between two
+//! candidates does not flood the host. The first leader is published at
+//! once.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::board::order::Order;
+
+/// Shared slot a search publishes its leading candidate into.
+#[derive(Debug)]
+pub struct CurrentBest {
+    interval: Duration,
+    slot: Mutex<Slot>,
+}
+
+#[derive(Debug, Default)]
+struct Slot {
+    /// Candidate index of the last published leader.
+    leader: Option<usize>,
+    published: Option<Instant>,
+    /// Published orders not yet taken.
+    fresh: Option<Vec<Order>>,
+}
+
+impl CurrentBest {
+    /// A slot publishing at most one new leader per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        CurrentBest {
+            interval,
+            slot: Mutex::new(Slot::default()),
+        }
+    }
+
+    /// Offers candidate `index` as the search's leader; `orders` gives its
+    /// orders and is only called when the leader is published.
+    pub fn offer(&self, index: usize, orders: impl FnOnce() -> Vec<Order>) {
+        let Ok(mut slot) = self.slot.lock() else {
+            return;
+        };
+        if slot.leader == Some(index)
+            || slot
+                .published
+                .is_some_and(|at| at.elapsed() < self.interval)
+        {
+            return;
+        }
+        slot.leader = Some(index);
+        slot.published = Some(Instant::now());
+        slot.fresh = Some(orders());
+    }
+
+    /// The leader published since the last call, if any.
+    pub fn take(&self) -> Option<Vec<Order>> {
+        self.slot.lock().ok()?.fresh.take()
+    }
+}
+
+/// Slots compare equal only to themselves, so options holding one compare
+/// by the search they report.
+impl PartialEq for CurrentBest {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dson::parse_orders;
+
+    #[test]
+    fn publishes_new_leaders_once_per_interval() {
+        let best = CurrentBest::new(Duration::from_secs(3600));
+        assert_eq!(best.take(), None);
+        let hold = parse_orders("A vie H").unwrap();
+        best.offer(2, || hold.clone());
+        assert_eq!(best.take(), Some(hold));
+        assert_eq!(best.take(), None);
+        // A new leader within the interval waits.
+        best.offer(5, || parse_orders("A vie - gal").unwrap());
+        assert_eq!(best.take(), None);
+
+        let eager = CurrentBest::new(Duration::ZERO);
+        eager.offer(1, || parse_orders("A vie H").unwrap());
+        eager.offer(1, || panic!("an unchanged leader is not published again"));
+        assert!(eager.take().is_some());
+        eager.offer(0, || parse_orders("A vie - gal").unwrap());
+        assert_eq!(eager.take(), Some(parse_orders("A vie - gal").unwrap()));
+    }
+}
@@ -0,0 +1,402 @@
+//! Joint-order minimax for two- and three-power endgames.
+//!
+//! With only two or three powers left the search heuristics are at their
+//! weakest: RM+ and the cartesian search assume each opponent plays its own
+//! likely orders, while the last powers on the board simply coordinate
+//! against the leader or hold a line. [`endgame_search`] treats the other
+//! powers as one side and searches the candidate matrix, every one of our
+//! order sets against every joint order set of theirs, as a minimax with
+//! alpha-beta cutoffs: a set is abandoned as soon as one reply holds it
+//! below a set already found. The matrix takes every legal order per unit
+//! when it is small enough and the heuristic's top orders otherwise. The
+//! search deepens one movement phase at a time, playing retreats and builds
+//! with the heuristic orders in between, until the clock or the node budget
+//! runs out.
+//!
+//! Leaves are scored with the heuristic evaluation, which already rewards
+//! the defenders for manning a stalemate line against a power threatening
+//! a solo (see [`crate::eval::stalemate`]), plus the other side of it: when
+//! we are that power, a completed line standing against us means no
+//! further progress can be forced, and the leaf is worth less.
+//!
+//! The engine's search dispatch, whichever search it would otherwise run,
+//! comes here first for positions that [`applies`] accepts. The result is a
+//! scored best set rather than a proven outcome.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::board::province::{Power, ALL_POWERS};
+use crate::board::state::{BoardState, Phase};
+use crate::board::Order;
+use crate::eval::evaluate;
+use crate::eval::heuristic::power_has_units;
+use crate::eval::stalemate::{solo_leader, STALEMATE_LINES};
+use crate::resolve::{
+    advance_state, apply_builds, apply_resolution, apply_retreats, resolve_builds,
+    resolve_retreats, Resolver,
+};
+use crate::search::cartesian::{
+    heuristic_build_orders, heuristic_retreat_orders, top_k_per_unit, ScoredOrder, SearchResult,
+};
+use crate::throughput::per_second;
+
+/// Most powers with units, ourselves included, for the endgame search.
+pub const ENDGAME_MAX_POWERS: usize = 3;
+
+/// Most units on the board for the endgame search.
+pub const ENDGAME_MAX_UNITS: usize = 10;
+
+/// Most joint profiles (our sets times theirs) in one phase's matrix.
+const MAX_MATRIX: usize = 4_096;
+
+/// Orders kept per unit, widest first, until the matrix fits.
+const WIDTHS: [usize; 5] = [usize::MAX, 4, 3, 2, 1];
+
+/// Deepest search in movement phases.
+const MAX_DEPTH: u32 = 3;
+
+/// Resolutions one search may spend.
+const MAX_NODES: u64 = 300_000;
+
+/// Leaf penalty for a completed stalemate line standing against us.
+const STALEMATE_LINE_VALUE: f32 = 8.0;
+
+/// Leaf value of eliminating every other power, or of being eliminated.
+const ELIMINATION_VALUE: f32 = 1_000.0;
+
+/// Whether `state` is a movement phase with 2-3 powers, us among them,
+/// and few enough units for the endgame search.
+pub fn applies(power: Power, state: &BoardState) -> bool {
+    if state.phase != Phase::Movement || !power_has_units(state, power) {
+        return false;
+    }
+    let units = state.units.iter().filter(|u| u.is_some()).count();
+    let powers = ALL_POWERS
+        .iter()
+        .filter(|&&p| power_has_units(state, p))
+        .count();
+    units <= ENDGAME_MAX_UNITS && (2..=ENDGAME_MAX_POWERS).contains(&powers)
+}
+
+/// Whether some stalemate line that can still stop `leader` has every
+/// post manned.
+fn line_held_against(leader: Power, state: &BoardState) -> bool {
+    STALEMATE_LINES.iter().any(|line| {
+        line.stands_against(leader, state) && line.manned(leader, state) == line.posts.len()
+    })
+}
+
+/// Searches the endgame for `power`. Returns `None` if not even one
+/// movement phase could be searched in the time and node budget.
+pub fn endgame_search<W: Write>(
+    power: Power,
+    state: &BoardState,
+    movetime: Duration,
+    out: &mut W,
+    stop: &AtomicBool,
+) -> Option<SearchResult> {
+    let start = Instant::now();
+    let mut solver = Solver {
+        power,
+        resolver: Resolver::new(64),
+        nodes: 0,
+        deadline: start + movetime,
+        stop,
+    };
+    let (ours, theirs) = matrix(power, state);
+    let mut best: Option<(f32, Vec<Order>, u32)> = None;
+    for depth in 1..=MAX_DEPTH {
+        let Some((score, orders)) = solver.max_node(state, depth, f32::NEG_INFINITY, f32::INFINITY)
+        else {
+            break;
+        };
+        let elapsed = start.elapsed();
+        let _ = writeln!(
+            out,
+            "info depth {} nodes {} nps {} score {} time {}",
+            depth,
+            solver.nodes,
+            per_second(solver.nodes, elapsed),
+            score as i32,
+            elapsed.as_millis() as u64
+        );
+        best = Some((score, orders, depth));
+    }
+    let (score, orders, depth) = best?;
+    let _ = writeln!(
+        out,
+        "info string endgame depth {} matrix {}x{} line {}",
+        depth,
+        ours.len(),
+        theirs.len(),
+        match solo_leader(state) {
+            None => "none",
+            Some(leader) if line_held_against(leader, state) => "held",
+            Some(_) => "open",
+        }
+    );
+    Some(SearchResult {
+        iterations: depth as u64,
+        elapsed: start.elapsed(),
+        ..SearchResult::new(orders, score, solver.nodes)
+    })
+}
+
+/// Alpha-beta minimax over joint order sets within one search's budget.
+struct Solver<'a> {
+    power: Power,
+    resolver: Resolver,
+    nodes: u64,
+    deadline: Instant,
+    stop: &'a AtomicBool,
+}
+
+impl Solver<'_> {
+    /// Our best set and its value against the others' best joint reply,
+    /// `depth` movement phases deep. Values outside (`alpha`, `beta`) are
+    /// only bounds. `None` once the budget runs out.
+    fn max_node(
+        &mut self,
+        state: &BoardState,
+        depth: u32,
+        alpha: f32,
+        beta: f32,
+    ) -> Option<(f32, Vec<Order>)> {
+        let (ours, theirs) = matrix(self.power, state);
+        let mut best = f32::NEG_INFINITY;
+        let mut best_orders: Vec<Order> = ours[0].iter().map(|(o, _)| *o).collect();
+        let mut joint = Vec::new();
+        for our_set in &ours {
+            let floor = alpha.max(best);
+            let mut worst = f32::INFINITY;
+            for their_set in &theirs {
+                self.nodes += 1;
+                if self.nodes > MAX_NODES
+                    || (self.nodes & 63 == 0
+                        && (self.stop.load(Ordering::Relaxed) || Instant::now() >= self.deadline))
+                {
+                    return None;
+                }
+                joint.clear();
+                joint.extend_from_slice(our_set);
+                joint.extend_from_slice(their_set);
+                let (results, dislodged) = self.resolver.resolve(&joint, state);
+                let mut next = state.clone();
+                apply_resolution(&mut next, &results, &dislodged);
+                let v = self.child_value(next, depth - 1, floor, worst)?;
+                worst = worst.min(v);
+                // The others can already hold this set to no better than
+                // one we have.
+                if worst <= floor {
+                    break;
+                }
+            }
+            if worst > best {
+                best = worst;
+                best_orders = our_set.iter().map(|(o, _)| *o).collect();
+                if best >= beta {
+                    break;
+                }
+            }
+        }
+        Some((best, best_orders))
+    }
+
+    /// Value of a resolved movement phase: the leaf score at depth 0,
+    /// otherwise the search of the next movement phase.
+    fn child_value(
+        &mut self,
+        mut next: BoardState,
+        depth: u32,
+        alpha: f32,
+        beta: f32,
+    ) -> Option<f32> {
+        if depth == 0 {
+            return Some(self.leaf(&next));
+        }
+        let has_dislodged = next.dislodged.iter().any(|d| d.is_some());
+        advance_state(&mut next, has_dislodged);
+        while next.phase != Phase::Movement {
+            next = play_heuristic_phase(&next);
+        }
+        if let Some(v) = self.terminal(&next) {
+            return Some(v);
+        }
+        self.max_node(&next, depth, alpha, beta).map(|(v, _)| v)
+    }
+
+    fn leaf(&self, state: &BoardState) -> f32 {
+        if let Some(v) = self.terminal(state) {
+            return v;
+        }
+        let mut value = evaluate(self.power, state);
+        if solo_leader(state) == Some(self.power) && line_held_against(self.power, state) {
+            value -= STALEMATE_LINE_VALUE;
+        }
+        value
+    }
+
+    fn terminal(&self, state: &BoardState) -> Option<f32> {
+        if !power_has_units(state, self.power) {
+            return Some(-ELIMINATION_VALUE);
+        }
+        let others = ALL_POWERS
+            .iter()
+            .any(|&p| p != self.power && power_has_units(state, p));
+        (!others).then_some(ELIMINATION_VALUE)
+    }
+}
+
+/// Our order sets and the other powers' joint order sets, each best-first
+/// by summed heuristic score, at the widest per-unit width whose matrix
+/// fits in `MAX_MATRIX`.
+#[allow(clippy::type_complexity)]
+fn matrix(
+    power: Power,
+    state: &BoardState,
+) -> (Vec<Vec<(Order, Power)>>, Vec<Vec<(Order, Power)>>) {
+    let size = |units: &[(Power, Vec<ScoredOrder>)]| {
+        units
+            .iter()
+            .fold(1usize, |n, (_, c)| n.saturating_mul(c.len()))
+    };
+    let (mut ours, mut theirs) = (Vec::new(), Vec::new());
+    // One order per unit always fits.
+    for &k in WIDTHS.iter() {
+        ours = per_unit(state, k, |p| p == power);
+        theirs = per_unit(state, k, |p| p != power);
+        if size(&ours).saturating_mul(size(&theirs)) <= MAX_MATRIX {
+            break;
+        }
+    }
+    (joint_sets(&ours), joint_sets(&theirs))
+}
+
+/// The top `k` orders of each unit whose owner matches `include`.
+fn per_unit(
+    state: &BoardState,
+    k: usize,
+    include: impl Fn(Power) -> bool,
+) -> Vec<(Power, Vec<ScoredOrder>)> {
+    ALL_POWERS
+        .iter()
+        .filter(|&&p| include(p) && power_has_units(state, p))
+        .flat_map(|&p| top_k_per_unit(p, state, k).into_iter().map(move |c| (p, c)))
+        .collect()
+}
+
+/// Every combination of one order per unit, best summed score first.
+fn joint_sets(units: &[(Power, Vec<ScoredOrder>)]) -> Vec<Vec<(Order, Power)>> {
+    let mut sets: Vec<(f32, Vec<(Order, Power)>)> = vec![(0.0, Vec::new())];
+    for (power, cands) in units {
+        let mut expanded = Vec::with_capacity(sets.len() * cands.len());
+        for (score, set) in &sets {
+            for c in cands {
+                let mut s = set.clone();
+                s.push((c.order, *power));
+                expanded.push((score + c.score, s));
+            }
+        }
+        sets = expanded;
+    }
+    sets.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    sets.into_iter().map(|(_, s)| s).collect()
+}
+
+/// Plays a retreat or build phase with heuristic orders for every power.
+fn play_heuristic_phase(state: &BoardState) -> BoardState {
+    let mut next = state.clone();
+    match state.phase {
+        Phase::Retreat => {
+            let mut orders: Vec<(Order, Power)> = Vec::new();
+            for &p in ALL_POWERS.iter() {
+                orders.extend(
+                    heuristic_retreat_orders(p, state)
+                        .into_iter()
+                        .map(|o| (o, p)),
+                );
+            }
+            let results = resolve_retreats(&orders, state);
+            apply_retreats(&mut next, &results);
+        }
+        Phase::Build => {
+            let mut orders: Vec<(Order, Power)> = Vec::new();
+            for &p in ALL_POWERS.iter() {
+                orders.extend(heuristic_build_orders(p, state).into_iter().map(|o| (o, p)));
+            }
+            let results = resolve_builds(&orders, state);
+            apply_builds(&mut next, &results);
+        }
+        Phase::Movement => return next,
+    }
+    advance_state(&mut next, false);
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::standard::*;
+    use crate::board::province::Coast;
+    use crate::board::state::Season;
+    use crate::board::unit::UnitType;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::format_orders;
+
+    #[test]
+    fn applies_to_small_two_and_three_power_positions() {
+        let duel = parse_dfen("1910sm/Aabud,Aaser,Rarum/Abud,Aser,Rrum/-").unwrap();
+        assert!(applies(Power::Austria, &duel));
+        assert!(!applies(Power::Turkey, &duel));
+        let build = parse_dfen("1910fb/Aabud,Aaser,Rarum/Abud,Aser,Rrum/-").unwrap();
+        assert!(!applies(Power::Austria, &build));
+        let four = parse_dfen("1910sm/Aabud,Rarum,Tacon,Iaven/Abud,Rrum,Tcon,Iven/-").unwrap();
+        assert!(!applies(Power::Austria, &four));
+    }
+
+    #[test]
+    fn takes_a_center_with_support() {
+        let state = parse_dfen("1910fm/Aabud,Aaser,Rarum/Abud,Aser,Rrum/-").unwrap();
+        let mut out = Vec::new();
+        let stop = AtomicBool::new(false);
+        let result = endgame_search(
+            Power::Austria,
+            &state,
+            Duration::from_secs(5),
+            &mut out,
+            &stop,
+        )
+        .unwrap();
+        let orders = format_orders(&result.orders);
+        let supported = (orders.contains("A bud - rum") && orders.contains("A ser S A bud - rum"))
+            || (orders.contains("A ser - rum") && orders.contains("A bud S A ser - rum"));
+        assert!(supported, "played {}", orders);
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("info string endgame depth "), "{}", text);
+    }
+
+    #[test]
+    fn a_manned_line_holds_against_the_leader() {
+        // France at 16 centers; Italy and Turkey man the southern posts.
+        let mut state = BoardState::empty(1910, Season::Spring, Phase::Movement);
+        for c in [
+            Lon, Lvp, Edi, Bre, Par, Bel, Hol, Kie, Ber, Mun, Den, Nwy, Swe, Stp, Mos, War,
+        ] {
+            state.set_sc_owner(c, Some(Power::France));
+        }
+        state.place_unit(Mao, Power::Italy, UnitType::Fleet, Coast::None);
+        for post in [Gas, Mar, Pie, Ven, Tri] {
+            state.place_unit(post, Power::Italy, UnitType::Army, Coast::None);
+        }
+        for post in [Bud, Rum, Sev] {
+            state.place_unit(post, Power::Turkey, UnitType::Army, Coast::None);
+        }
+        assert_eq!(solo_leader(&state), Some(Power::France));
+        assert!(line_held_against(Power::France, &state));
+
+        state.set_unit(Sev, None, None);
+        assert!(!line_held_against(Power::France, &state));
+    }
+}
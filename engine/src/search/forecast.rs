@@ -0,0 +1,272 @@
+//! Province-level pressure forecast.
+//!
+//! `dangers` lists what rivals *can* do to us this turn; an RM+ search also
+//! knows what they are *likely* to do. [`forecast`] reads the opponents'
+//! equilibrium strategies from a finished search and gives, for each
+//! province we own or occupy, the probability that some rival moves into
+//! it next turn and with what strength (the move plus the rival's own
+//! supports for it). Rivals are taken to choose independently, as in the
+//! equilibrium itself.
+//!
+//! The forecast annotates `dangers`, is reported by the `forecast`
+//! command, and tells the press composer where to ask a friendly power for
+//! a supporting hold.
+
+use serde_json::json;
+
+use crate::board::map::GameMap;
+use crate::board::order::Order;
+use crate::board::province::{Power, Province};
+use crate::board::state::BoardState;
+use crate::search::regret_matching::PowerCandidates;
+
+/// One rival's pressure on a province.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttackForecast {
+    pub power: Power,
+    /// Equilibrium probability that the rival moves into the province.
+    pub probability: f64,
+    /// Mean strength of its move when it comes: one plus its supports.
+    pub strength: f64,
+}
+
+/// The pressure on one of our provinces next turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvinceForecast {
+    pub province: Province,
+    /// Probability that at least one rival moves in.
+    pub probability: f64,
+    /// Expected attacking strength, summed over rivals.
+    pub strength: f64,
+    /// The rivals that might move in, likeliest first.
+    pub attackers: Vec<AttackForecast>,
+}
+
+/// Forecasts the pressure on `power`'s provinces from the other powers'
+/// candidates and their RM+ strategy weights. Provinces no rival moves
+/// into are left out; the rest come most threatened first.
+pub fn forecast(
+    power: Power,
+    state: &BoardState,
+    candidates: &[PowerCandidates],
+    weights: &[Vec<f64>],
+) -> Vec<ProvinceForecast> {
+    let mut out = Vec::new();
+    for &prov in state.map().provinces() {
+        let idx = prov.index();
+        let ours =
+            state.units[idx].is_some_and(|(p, _)| p == power) || state.sc_owner[idx] == Some(power);
+        if !ours {
+            continue;
+        }
+        let mut attackers = Vec::new();
+        for ((rival, cands), w) in candidates.iter().zip(weights) {
+            if *rival == power {
+                continue;
+            }
+            let total: f64 = w.iter().sum();
+            let mut probability = 0.0;
+            let mut strength = 0.0;
+            for (cand, &cw) in cands.iter().zip(w) {
+                let p = if total > 0.0 {
+                    cw / total
+                } else {
+                    1.0 / cands.len() as f64
+                };
+                if let Some(s) = attack_strength(cand, prov) {
+                    probability += p;
+                    strength += p * s as f64;
+                }
+            }
+            if probability > 0.0 {
+                attackers.push(AttackForecast {
+                    power: *rival,
+                    probability,
+                    strength: strength / probability,
+                });
+            }
+        }
+        if attackers.is_empty() {
+            continue;
+        }
+        attackers.sort_by(|a, b| {
+            b.probability
+                .partial_cmp(&a.probability)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let spared: f64 = attackers.iter().map(|a| 1.0 - a.probability).product();
+        out.push(ProvinceForecast {
+            province: prov,
+            probability: 1.0 - spared,
+            strength: attackers.iter().map(|a| a.probability * a.strength).sum(),
+            attackers,
+        });
+    }
+    out.sort_by(|a, b| {
+        b.probability
+            .partial_cmp(&a.probability)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.province.index().cmp(&b.province.index()))
+    });
+    out
+}
+
+/// Strength of the strongest move into `target` in one order set: the
+/// move plus the set's supports for it. `None` if nothing moves there.
+fn attack_strength(orders: &[(Order, Power)], target: Province) -> Option<u32> {
+    orders
+        .iter()
+        .filter_map(|(order, _)| match order {
+            Order::Move { unit, dest, .. } if dest.province == target => {
+                Some(unit.location.province)
+            }
+            _ => None,
+        })
+        .map(|from| {
+            1 + orders
+                .iter()
+                .filter(|(o, _)| {
+                    matches!(o, Order::SupportMove { supported, dest, .. }
+                        if supported.location.province == from && dest.province == target)
+                })
+                .count() as u32
+        })
+        .max()
+}
+
+/// Serializes a forecast as a single-line JSON object, naming provinces as
+/// `map` does.
+///
+/// Format: `{"power":"austria","provinces":[{"province":"gal","probability":0.62,"strength":1.4,"attackers":[{"power":"russia","probability":0.62,"strength":2.25}]},...]}`
+pub fn forecast_json(map: &GameMap, power: Power, forecast: &[ProvinceForecast]) -> String {
+    let round = |x: f64| (x * 1000.0).round() / 1000.0;
+    let provinces: Vec<serde_json::Value> = forecast
+        .iter()
+        .map(|f| {
+            let attackers: Vec<serde_json::Value> = f
+                .attackers
+                .iter()
+                .map(|a| {
+                    json!({
+                        "power": a.power.name(),
+                        "probability": round(a.probability),
+                        "strength": round(a.strength),
+                    })
+                })
+                .collect();
+            json!({
+                "province": map.abbr(f.province),
+                "probability": round(f.probability),
+                "strength": round(f.strength),
+                "attackers": attackers,
+            })
+        })
+        .collect();
+    json!({ "power": power.name(), "provinces": provinces }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_orders;
+
+    fn set(s: &str, power: Power) -> Vec<(Order, Power)> {
+        parse_orders(s)
+            .unwrap()
+            .into_iter()
+            .map(|o| (o, power))
+            .collect()
+    }
+
+    /// Russia presses Galicia with support half the time; Turkey walks
+    /// into Serbia a quarter of the time.
+    fn pressed() -> (BoardState, Vec<PowerCandidates>, Vec<Vec<f64>>) {
+        let state =
+            parse_dfen("1901sm/Aagal,Aaser,Rawar,Raukr,Tabul/Abud,Avie,Aser,Rwar,Tbul/-").unwrap();
+        let cands = vec![
+            (
+                Power::Austria,
+                vec![set("A gal H ; A ser H", Power::Austria)],
+            ),
+            (
+                Power::Russia,
+                vec![
+                    set("A war - gal ; A ukr S A war - gal", Power::Russia),
+                    set("A war - gal ; A ukr H", Power::Russia),
+                    set("A war H ; A ukr H", Power::Russia),
+                ],
+            ),
+            (
+                Power::Turkey,
+                vec![
+                    set("A bul - ser", Power::Turkey),
+                    set("A bul H", Power::Turkey),
+                ],
+            ),
+        ];
+        let weights = vec![vec![1.0], vec![3.0, 1.0, 4.0], vec![1.0, 3.0]];
+        (state, cands, weights)
+    }
+
+    #[test]
+    fn forecasts_attack_probability_and_strength() {
+        let (state, cands, weights) = pressed();
+        let list = forecast(Power::Austria, &state, &cands, &weights);
+        assert_eq!(list.len(), 2);
+        let gal = &list[0];
+        assert_eq!(gal.province, Province::Gal);
+        assert!((gal.probability - 0.5).abs() < 1e-9);
+        // Supported three times out of four it comes.
+        assert!((gal.attackers[0].strength - 1.75).abs() < 1e-9);
+        assert!((gal.strength - 0.875).abs() < 1e-9);
+        let ser = &list[1];
+        assert_eq!(ser.province, Province::Ser);
+        assert!((ser.probability - 0.25).abs() < 1e-9);
+        assert_eq!(ser.attackers[0].power, Power::Turkey);
+    }
+
+    #[test]
+    fn independent_rivals_combine() {
+        let state = parse_dfen("1901sm/Aagal,Rawar,Tarum/Avie/-").unwrap();
+        let cands = vec![
+            (
+                Power::Russia,
+                vec![
+                    set("A war - gal", Power::Russia),
+                    set("A war H", Power::Russia),
+                ],
+            ),
+            (
+                Power::Turkey,
+                vec![
+                    set("A rum - gal", Power::Turkey),
+                    set("A rum H", Power::Turkey),
+                ],
+            ),
+        ];
+        let even = vec![vec![1.0, 1.0], vec![1.0, 1.0]];
+        let list = forecast(Power::Austria, &state, &cands, &even);
+        assert_eq!(list.len(), 1);
+        let gal = &list[0];
+        assert!((gal.probability - 0.75).abs() < 1e-9);
+        assert_eq!(gal.attackers.len(), 2);
+        assert!((gal.strength - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn json_is_one_line() {
+        let (state, cands, weights) = pressed();
+        let s = forecast_json(
+            state.map(),
+            Power::Austria,
+            &forecast(Power::Austria, &state, &cands, &weights),
+        );
+        let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(v["power"], "austria");
+        assert_eq!(v["provinces"][0]["province"], "gal");
+        assert_eq!(v["provinces"][0]["attackers"][0]["power"], "russia");
+        assert_eq!(v["provinces"][0]["attackers"][0]["strength"], 1.75);
+        assert!(!s.contains('\n'));
+    }
+}
@@ -0,0 +1,641 @@
+//! Beam search over multi-turn plans for small unit groups.
+//!
+//! The heuristic scorer and RM+ both look a single movement phase ahead, so
+//! maneuvers that need several turns of setup (walking three units up to a
+//! defended SC before attacking it with support, or filing units into a
+//! stalemate line) rarely show up among the candidates. This planner takes a
+//! group of 2-4 units and a strategic goal, beam-searches 3-4 turn move
+//! sequences with the rest of the board frozen in place, and returns the plan
+//! whose final position best achieves the goal. The RM+ search injects the
+//! first-turn orders of the best plans as extra candidates.
+
+use crate::board::map::GameMap;
+use crate::board::order::{Location, Order, OrderUnit};
+use crate::board::province::{Coast, Power, Province, MAX_PROVINCES};
+use crate::board::state::{BoardState, Phase};
+use crate::board::unit::UnitType;
+use crate::eval::heuristic::{province_threat, unit_can_reach};
+use crate::movegen::movement::move_orders_only;
+
+/// Number of partial plans kept after each turn.
+pub const BEAM_WIDTH: usize = 8;
+
+/// Maximum number of turns a plan looks ahead.
+pub const PLAN_DEPTH: usize = 4;
+
+/// Smallest group worth planning for.
+pub const MIN_GROUP_SIZE: usize = 2;
+
+/// Largest group the planner coordinates.
+pub const MAX_GROUP_SIZE: usize = 4;
+
+/// Moves kept per unit when expanding a beam node.
+const MOVES_PER_UNIT: usize = 3;
+
+/// Score of a plan that reaches its goal, minus one per turn used.
+const ACHIEVED_SCORE: f32 = 100.0;
+
+/// Distance charged for a unit that can never reach the goal.
+const UNREACHABLE_DIST: f32 = 10.0;
+
+/// A strategic objective for a unit group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanGoal {
+    /// Take a supply center, attacking with enough support to beat the
+    /// occupant and every foreign unit that could defend it.
+    TakeSc(Province),
+    /// Occupy every province of a defensive (stalemate) line.
+    HoldLine(Vec<Province>),
+}
+
+/// The best plan found for a unit group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupPlan {
+    pub goal: PlanGoal,
+    /// Starting provinces of the planned units.
+    pub units: Vec<Province>,
+    /// Orders for each turn of the plan; `turns[0]` is playable now.
+    pub turns: Vec<Vec<Order>>,
+    pub score: f32,
+    /// True if the goal is reached within the planning horizon.
+    pub achieved: bool,
+}
+
+impl GroupPlan {
+    /// Returns the orders to play this turn.
+    pub fn first_orders(&self) -> &[Order] {
+        self.turns.first().map_or(&[], |t| t.as_slice())
+    }
+}
+
+/// A group unit's hypothetical position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PlanUnit {
+    prov: Province,
+    coast: Coast,
+    unit_type: UnitType,
+}
+
+impl PlanUnit {
+    fn order_unit(self) -> OrderUnit {
+        OrderUnit {
+            unit_type: self.unit_type,
+            location: Location::with_coast(self.prov, self.coast),
+        }
+    }
+}
+
+/// A partial plan in the beam.
+#[derive(Debug, Clone)]
+struct Node {
+    units: Vec<PlanUnit>,
+    turns: Vec<Vec<Order>>,
+    score: f32,
+    achieved: bool,
+}
+
+/// Distance in moves between two provinces for a unit type, or -1.
+#[inline]
+fn unit_distance(map: &GameMap, unit_type: UnitType, from: Province, to: Province) -> i16 {
+    match unit_type {
+        UnitType::Army => map.army_dist().distance(from, to),
+        UnitType::Fleet => map.fleet_dist().distance(from, to),
+    }
+}
+
+#[inline]
+fn distance_cost(map: &GameMap, unit_type: UnitType, from: Province, to: Province) -> f32 {
+    match unit_distance(map, unit_type, from, to) {
+        d if d < 0 => UNREACHABLE_DIST,
+        d => d as f32,
+    }
+}
+
+/// Per-unit distance from a position to the goal (lower is better).
+fn goal_distance(map: &GameMap, goal: &PlanGoal, unit: PlanUnit, units: &[PlanUnit]) -> f32 {
+    match goal {
+        PlanGoal::TakeSc(target) => distance_cost(map, unit.unit_type, unit.prov, *target),
+        PlanGoal::HoldLine(line) => {
+            if line.contains(&unit.prov) {
+                return 0.0;
+            }
+            line.iter()
+                .filter(|p| !units.iter().any(|u| u.prov == **p))
+                .map(|&p| distance_cost(map, unit.unit_type, unit.prov, p))
+                .fold(UNREACHABLE_DIST, f32::min)
+        }
+    }
+}
+
+/// Units (occupant plus foreign units in reach) that can oppose an attack.
+fn resistance(target: Province, power: Power, state: &BoardState) -> i32 {
+    let occupant = match state.units[target.index()] {
+        Some((p, _)) if p != power => 1,
+        _ => 0,
+    };
+    occupant + province_threat(target, power, state)
+}
+
+/// Group units that can move to or support into the target this turn.
+fn attackers(map: &GameMap, target: Province, units: &[PlanUnit]) -> Vec<usize> {
+    (0..units.len())
+        .filter(|&i| {
+            unit_can_reach(
+                map,
+                units[i].prov,
+                units[i].coast,
+                units[i].unit_type,
+                target,
+            )
+        })
+        .collect()
+}
+
+/// Scores a (non-final) plan position against its goal.
+fn position_score(goal: &PlanGoal, units: &[PlanUnit], power: Power, state: &BoardState) -> f32 {
+    let dist: f32 = units
+        .iter()
+        .map(|&u| goal_distance(state.map(), goal, u, units))
+        .sum();
+    match goal {
+        PlanGoal::TakeSc(target) => {
+            let needed = resistance(*target, power, state) + 1;
+            let adjacent = attackers(state.map(), *target, units).len() as i32;
+            2.0 * adjacent.min(needed) as f32 - dist
+        }
+        PlanGoal::HoldLine(line) => {
+            let occupied = line
+                .iter()
+                .filter(|p| units.iter().any(|u| u.prov == **p))
+                .count();
+            10.0 * occupied as f32 - dist
+        }
+    }
+}
+
+/// Builds the board with group units moved to their planned positions.
+fn hypothetical_state(frozen: &BoardState, power: Power, units: &[PlanUnit]) -> BoardState {
+    let mut state = frozen.clone();
+    for u in units {
+        state.place_unit(u.prov, power, u.unit_type, u.coast);
+    }
+    state
+}
+
+/// Returns this turn's orders if the group can take the target right now:
+/// one unit moves in and every other attacker supports it.
+fn attack_orders(
+    target: Province,
+    units: &[PlanUnit],
+    power: Power,
+    state: &BoardState,
+) -> Option<Vec<Order>> {
+    let adjacent = attackers(state.map(), target, units);
+    if (adjacent.len() as i32) <= resistance(target, power, state) {
+        return None;
+    }
+    // Prefer an army as the mover so fleets stay free to support.
+    let mover_idx = adjacent
+        .iter()
+        .copied()
+        .find(|&i| units[i].unit_type == UnitType::Army)
+        .unwrap_or(adjacent[0]);
+    let mover = units[mover_idx];
+    let mover_order = move_orders_only(mover.prov, state)
+        .into_iter()
+        .find(|o| matches!(o, Order::Move { dest, .. } if dest.province == target))?;
+
+    let orders = units
+        .iter()
+        .enumerate()
+        .map(|(i, u)| {
+            if i == mover_idx {
+                mover_order
+            } else if adjacent.contains(&i) {
+                Order::SupportMove {
+                    unit: u.order_unit(),
+                    supported: mover.order_unit(),
+                    dest: Location::new(target),
+                }
+            } else {
+                Order::Hold {
+                    unit: u.order_unit(),
+                }
+            }
+        })
+        .collect();
+    Some(orders)
+}
+
+/// Returns the best hold/move options for one unit with its resulting position.
+fn unit_options(
+    unit: PlanUnit,
+    goal: &PlanGoal,
+    units: &[PlanUnit],
+    frozen: &BoardState,
+    state: &BoardState,
+) -> Vec<(Order, PlanUnit)> {
+    let mut options: Vec<(Order, PlanUnit, f32)> = move_orders_only(unit.prov, state)
+        .into_iter()
+        .filter_map(|order| {
+            let next = match order {
+                Order::Hold { .. } => unit,
+                Order::Move { dest, .. } => {
+                    // Frozen units never move out of the way.
+                    if frozen.units[dest.province.index()].is_some() {
+                        return None;
+                    }
+                    // The target itself is only entered by a supported attack.
+                    if matches!(goal, PlanGoal::TakeSc(t) if *t == dest.province) {
+                        return None;
+                    }
+                    PlanUnit {
+                        prov: dest.province,
+                        coast: dest.coast,
+                        unit_type: unit.unit_type,
+                    }
+                }
+                _ => return None,
+            };
+            Some((order, next, goal_distance(state.map(), goal, next, units)))
+        })
+        .collect();
+    options.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+    options.truncate(MOVES_PER_UNIT);
+    options.into_iter().map(|(o, u, _)| (o, u)).collect()
+}
+
+/// Expands a node by one turn of plain movement.
+fn expand_moves(
+    node: &Node,
+    goal: &PlanGoal,
+    power: Power,
+    frozen: &BoardState,
+    state: &BoardState,
+    out: &mut Vec<Node>,
+) {
+    let options: Vec<Vec<(Order, PlanUnit)>> = node
+        .units
+        .iter()
+        .map(|&u| unit_options(u, goal, &node.units, frozen, state))
+        .collect();
+    if options.iter().any(|o| o.is_empty()) {
+        return;
+    }
+
+    // Cartesian product over per-unit options via a mixed-radix counter.
+    let mut idx = vec![0usize; options.len()];
+    loop {
+        let next_units: Vec<PlanUnit> = idx
+            .iter()
+            .zip(options.iter())
+            .map(|(&i, o)| o[i].1)
+            .collect();
+        let collides = (0..next_units.len()).any(|a| {
+            (a + 1..next_units.len()).any(|b| {
+                next_units[a].prov == next_units[b].prov
+                    || (next_units[a].prov == node.units[b].prov
+                        && next_units[b].prov == node.units[a].prov)
+            })
+        });
+        if !collides {
+            let child_state = hypothetical_state(frozen, power, &next_units);
+            let score = position_score(goal, &next_units, power, &child_state);
+            let mut turns = node.turns.clone();
+            turns.push(
+                idx.iter()
+                    .zip(options.iter())
+                    .map(|(&i, o)| o[i].0)
+                    .collect(),
+            );
+            out.push(Node {
+                units: next_units,
+                turns,
+                score,
+                achieved: false,
+            });
+        }
+
+        let mut d = 0;
+        while d < idx.len() {
+            idx[d] += 1;
+            if idx[d] < options[d].len() {
+                break;
+            }
+            idx[d] = 0;
+            d += 1;
+        }
+        if d == idx.len() {
+            break;
+        }
+    }
+}
+
+/// Beam-searches a multi-turn plan for the given units toward a goal.
+///
+/// Units outside the group stay where they are for the whole plan. Returns
+/// `None` if the group is not 2-4 of `power`'s units, the position is not a
+/// movement phase, or no plan could be built.
+pub fn plan_group(
+    power: Power,
+    state: &BoardState,
+    group: &[Province],
+    goal: &PlanGoal,
+) -> Option<GroupPlan> {
+    if state.phase != Phase::Movement
+        || group.len() < MIN_GROUP_SIZE
+        || group.len() > MAX_GROUP_SIZE
+    {
+        return None;
+    }
+
+    let mut frozen = state.clone();
+    let mut units = Vec::with_capacity(group.len());
+    for &prov in group {
+        let idx = prov.index();
+        match state.units[idx] {
+            Some((p, unit_type)) if p == power => {
+                units.push(PlanUnit {
+                    prov,
+                    coast: state.fleet_coast[idx].unwrap_or(Coast::None),
+                    unit_type,
+                });
+                frozen.remove_unit(prov);
+            }
+            _ => return None,
+        }
+    }
+
+    let mut beam = vec![Node {
+        score: position_score(goal, &units, power, state),
+        units,
+        turns: Vec::new(),
+        achieved: false,
+    }];
+
+    for turn in 0..PLAN_DEPTH {
+        let mut next: Vec<Node> = Vec::new();
+        for node in &beam {
+            if node.achieved {
+                next.push(node.clone());
+                continue;
+            }
+            let current = hypothetical_state(&frozen, power, &node.units);
+            if let PlanGoal::TakeSc(target) = goal {
+                if let Some(orders) = attack_orders(*target, &node.units, power, &current) {
+                    let mut turns = node.turns.clone();
+                    turns.push(orders);
+                    next.push(Node {
+                        units: node.units.clone(),
+                        turns,
+                        score: ACHIEVED_SCORE - turn as f32,
+                        achieved: true,
+                    });
+                    continue;
+                }
+            }
+            expand_moves(node, goal, power, &frozen, &current, &mut next);
+        }
+
+        if let PlanGoal::HoldLine(line) = goal {
+            for node in next.iter_mut().filter(|n| !n.achieved) {
+                if line.iter().all(|p| node.units.iter().any(|u| u.prov == *p)) {
+                    node.achieved = true;
+                    node.score = ACHIEVED_SCORE - turn as f32;
+                }
+            }
+        }
+
+        next.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        // Distinct end positions only: different routes to the same squares
+        // would otherwise crowd the beam.
+        let mut kept: Vec<Node> = Vec::with_capacity(BEAM_WIDTH);
+        for n in next {
+            if kept.len() >= BEAM_WIDTH {
+                break;
+            }
+            if !kept.iter().any(|k| k.units == n.units && !k.achieved) {
+                kept.push(n);
+            }
+        }
+        if kept.is_empty() {
+            break;
+        }
+        beam = kept;
+    }
+
+    let best = beam.into_iter().next()?;
+    if best.turns.is_empty() {
+        return None;
+    }
+    Some(GroupPlan {
+        goal: goal.clone(),
+        units: group.to_vec(),
+        turns: best.turns,
+        score: best.score,
+        achieved: best.achieved,
+    })
+}
+
+/// Picks SC targets worth planning for and the units to send at each.
+///
+/// Targets are SCs not owned by `power` and not already occupied by it.
+/// Each gets the up-to-4 nearest own units that can reach it within the
+/// plan horizon; targets with fewer than two such units are skipped.
+/// Occupied (defended) SCs are ranked ahead of empty ones, then by the
+/// group's total distance.
+pub fn select_goals(
+    power: Power,
+    state: &BoardState,
+    max: usize,
+) -> Vec<(PlanGoal, Vec<Province>)> {
+    let mut goals: Vec<(bool, f32, PlanGoal, Vec<Province>)> = Vec::new();
+    for &target in state.map().provinces() {
+        let t = target.index();
+        if !state.map().is_supply_center(target) || state.sc_owner[t] == Some(power) {
+            continue;
+        }
+        if matches!(state.units[t], Some((p, _)) if p == power) {
+            continue;
+        }
+
+        let mut near: Vec<(i16, Province)> = (0..MAX_PROVINCES)
+            .filter_map(|i| match state.units[i] {
+                Some((p, ut)) if p == power => {
+                    let d = unit_distance(state.map(), ut, Province::from_index(i), target);
+                    (d > 0 && d as usize <= PLAN_DEPTH).then_some((d, Province::from_index(i)))
+                }
+                _ => None,
+            })
+            .collect();
+        if near.len() < MIN_GROUP_SIZE {
+            continue;
+        }
+        near.sort_by_key(|&(d, p)| (d, p.index()));
+        near.truncate(MAX_GROUP_SIZE);
+        let total: f32 = near.iter().map(|&(d, _)| d as f32).sum();
+        let defended = state.units[t].is_some();
+        goals.push((
+            defended,
+            total,
+            PlanGoal::TakeSc(target),
+            near.into_iter().map(|(_, p)| p).collect(),
+        ));
+    }
+
+    goals.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    goals.truncate(max);
+    goals.into_iter().map(|(_, _, g, u)| (g, u)).collect()
+}
+
+/// Plans for up to `max_plans` automatically selected goals.
+///
+/// Only plans whose first turn moves at least one unit are returned, best
+/// score first.
+pub fn plan_candidates(power: Power, state: &BoardState, max_plans: usize) -> Vec<GroupPlan> {
+    if state.phase != Phase::Movement {
+        return Vec::new();
+    }
+    let mut plans: Vec<GroupPlan> = select_goals(power, state, max_plans)
+        .into_iter()
+        .filter_map(|(goal, group)| plan_group(power, state, &group, &goal))
+        .filter(|plan| {
+            plan.first_orders()
+                .iter()
+                .any(|o| !matches!(o, Order::Hold { .. }))
+        })
+        .collect();
+    plans.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    plans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::state::Season;
+
+    fn order_dest(order: &Order) -> Option<Province> {
+        match order {
+            Order::Move { dest, .. } => Some(dest.province),
+            _ => None,
+        }
+    }
+
+    /// Two Austrian armies two moves from a Serbia held by Turkey.
+    fn serbia_walk_state() -> BoardState {
+        let mut state = BoardState::empty(1902, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Tri, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Ser, Power::Turkey, UnitType::Army, Coast::None);
+        state.set_sc_owner(Province::Ser, Some(Power::Turkey));
+        state
+    }
+
+    #[test]
+    fn plan_walks_group_into_supported_attack() {
+        let state = serbia_walk_state();
+        let goal = PlanGoal::TakeSc(Province::Ser);
+        let plan = plan_group(
+            Power::Austria,
+            &state,
+            &[Province::Vie, Province::Tri],
+            &goal,
+        )
+        .expect("plan");
+        assert!(plan.achieved);
+        assert_eq!(plan.turns.len(), 2);
+
+        // Turn 1: Vie closes in on Serbia (Tri is already adjacent).
+        assert!(plan
+            .first_orders()
+            .iter()
+            .any(|o| matches!(order_dest(o), Some(Province::Bud))));
+        // Final turn: one unit moves in with the other supporting.
+        let last = plan.turns.last().unwrap();
+        assert_eq!(
+            last.iter()
+                .filter(|o| order_dest(o) == Some(Province::Ser))
+                .count(),
+            1
+        );
+        assert!(last.iter().any(
+            |o| matches!(o, Order::SupportMove { dest, .. } if dest.province == Province::Ser)
+        ));
+    }
+
+    #[test]
+    fn plan_does_not_attack_without_enough_support() {
+        let mut state = serbia_walk_state();
+        // Turkish support from Bulgaria and Albania outnumbers two attackers.
+        state.place_unit(Province::Bul, Power::Turkey, UnitType::Army, Coast::None);
+        state.place_unit(Province::Alb, Power::Turkey, UnitType::Army, Coast::None);
+        let goal = PlanGoal::TakeSc(Province::Ser);
+        let plan = plan_group(
+            Power::Austria,
+            &state,
+            &[Province::Vie, Province::Tri],
+            &goal,
+        )
+        .expect("plan");
+        assert!(!plan.achieved);
+        assert!(plan
+            .turns
+            .iter()
+            .flatten()
+            .all(|o| order_dest(o) != Some(Province::Ser)));
+    }
+
+    #[test]
+    fn plan_fills_defensive_line() {
+        let mut state = BoardState::empty(1905, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Mun, Power::Germany, UnitType::Army, Coast::None);
+        state.place_unit(Province::Ber, Power::Germany, UnitType::Army, Coast::None);
+        let goal = PlanGoal::HoldLine(vec![Province::Tyr, Province::Boh]);
+        let plan = plan_group(
+            Power::Germany,
+            &state,
+            &[Province::Mun, Province::Ber],
+            &goal,
+        )
+        .expect("plan");
+        assert!(plan.achieved);
+        assert!(plan.turns.len() <= 2);
+    }
+
+    #[test]
+    fn plan_rejects_foreign_or_oversized_groups() {
+        let state = serbia_walk_state();
+        let goal = PlanGoal::TakeSc(Province::Ser);
+        assert!(plan_group(
+            Power::Austria,
+            &state,
+            &[Province::Vie, Province::Ser],
+            &goal
+        )
+        .is_none());
+        assert!(plan_group(Power::Austria, &state, &[Province::Vie], &goal).is_none());
+    }
+
+    #[test]
+    fn select_goals_prefers_defended_scs() {
+        let state = serbia_walk_state();
+        let goals = select_goals(Power::Austria, &state, 3);
+        assert!(!goals.is_empty());
+        assert_eq!(goals[0].0, PlanGoal::TakeSc(Province::Ser));
+        assert_eq!(goals[0].1.len(), 2);
+
+        let plans = plan_candidates(Power::Austria, &state, 3);
+        assert!(!plans.is_empty());
+    }
+}
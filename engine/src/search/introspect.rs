@@ -0,0 +1,269 @@
+//! Search introspection: why a candidate was rejected.
+//!
+//! In debug mode the RM+ search traces, for each of its own candidates, the
+//! opponent profiles that produced the worst regret deltas (counterfactual
+//! value minus the value of the candidate actually sampled that iteration).
+//! After search the top non-chosen candidates by average strategy weight are
+//! reported together with their punishing profiles, so a surprising move can
+//! be explained by the `whynot` command.
+
+use serde_json::{json, Map, Value};
+
+use crate::board::province::Power;
+use crate::board::Order;
+use crate::protocol::dson::format_orders;
+use crate::search::regret_matching::PowerCandidates;
+
+/// Non-chosen candidates included in a report.
+pub const WHYNOT_CANDIDATES: usize = 3;
+
+/// Punishing opponent profiles kept per candidate.
+pub const WHYNOT_PUNISHERS: usize = 3;
+
+/// An opponent profile that punished a candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Punisher {
+    /// Regret delta against this profile (negative = worse than the sampled
+    /// candidate).
+    pub delta: f64,
+    /// Orders each opponent played in the profile.
+    pub profile: Vec<(Power, Vec<Order>)>,
+}
+
+/// A candidate the search considered but did not play.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedCandidate {
+    pub orders: Vec<Order>,
+    /// Share of the accumulated RM+ strategy weight.
+    pub weight: f64,
+    /// Mean regret delta over all iterations it was evaluated as a counterfactual.
+    pub mean_delta: f64,
+    /// Worst opponent profiles, most punishing first.
+    pub punishers: Vec<Punisher>,
+}
+
+/// Introspection report for one search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhyNotReport {
+    pub power: Power,
+    pub chosen: Vec<Order>,
+    pub chosen_weight: f64,
+    pub rejected: Vec<RejectedCandidate>,
+}
+
+fn round3(x: f64) -> f64 {
+    (x * 1000.0).round() / 1000.0
+}
+
+impl WhyNotReport {
+    /// Serializes the report as a single-line JSON object.
+    ///
+    /// Orders are DSON strings; each punisher's `profile` maps opponent
+    /// power names to their DSON orders.
+    pub fn to_json(&self) -> String {
+        let rejected: Vec<Value> = self
+            .rejected
+            .iter()
+            .map(|r| {
+                let punishers: Vec<Value> = r
+                    .punishers
+                    .iter()
+                    .map(|p| {
+                        let mut profile = Map::new();
+                        for (power, orders) in &p.profile {
+                            profile.insert(power.name().to_string(), json!(format_orders(orders)));
+                        }
+                        json!({ "delta": round3(p.delta), "profile": profile })
+                    })
+                    .collect();
+                json!({
+                    "orders": format_orders(&r.orders),
+                    "weight": round3(r.weight),
+                    "mean_delta": round3(r.mean_delta),
+                    "punishers": punishers,
+                })
+            })
+            .collect();
+        json!({
+            "power": self.power.name(),
+            "chosen": format_orders(&self.chosen),
+            "chosen_weight": round3(self.chosen_weight),
+            "rejected": rejected,
+        })
+        .to_string()
+    }
+}
+
+/// Per-candidate regret trace collected during RM+ iterations.
+pub(crate) struct RegretTrace {
+    /// Worst (delta, sampled profile indices) per candidate, ascending delta.
+    worst: Vec<Vec<(f64, Vec<usize>)>>,
+    delta_sum: Vec<f64>,
+    delta_count: Vec<u64>,
+}
+
+impl RegretTrace {
+    pub(crate) fn new(candidates: usize) -> Self {
+        RegretTrace {
+            worst: vec![Vec::new(); candidates],
+            delta_sum: vec![0.0; candidates],
+            delta_count: vec![0; candidates],
+        }
+    }
+
+    /// Records a candidate's regret delta against the sampled profile.
+    pub(crate) fn record(&mut self, ci: usize, delta: f64, sampled: &[usize]) {
+        self.delta_sum[ci] += delta;
+        self.delta_count[ci] += 1;
+
+        let worst = &mut self.worst[ci];
+        if let Some(existing) = worst.iter_mut().find(|(_, s)| s == sampled) {
+            existing.0 = existing.0.min(delta);
+        } else if worst.len() < WHYNOT_PUNISHERS {
+            worst.push((delta, sampled.to_vec()));
+        } else if delta < worst[worst.len() - 1].0 {
+            let last = worst.len() - 1;
+            worst[last] = (delta, sampled.to_vec());
+        } else {
+            return;
+        }
+        worst.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    /// Builds the report for the chosen candidate and the top non-chosen ones.
+    pub(crate) fn report(
+        &self,
+        power_candidates: &[PowerCandidates],
+        our_idx: usize,
+        weights: &[f64],
+        best_idx: usize,
+    ) -> WhyNotReport {
+        let total: f64 = weights.iter().sum();
+        let share = |w: f64| if total > 0.0 { w / total } else { 0.0 };
+        let orders_of = |pi: usize, ci: usize| -> Vec<Order> {
+            power_candidates[pi].1[ci].iter().map(|(o, _)| *o).collect()
+        };
+
+        let mut others: Vec<usize> = (0..weights.len()).filter(|&i| i != best_idx).collect();
+        others.sort_by(|&a, &b| {
+            weights[b]
+                .partial_cmp(&weights[a])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        others.truncate(WHYNOT_CANDIDATES);
+
+        let rejected = others
+            .into_iter()
+            .map(|ci| RejectedCandidate {
+                orders: orders_of(our_idx, ci),
+                weight: share(weights[ci]),
+                mean_delta: if self.delta_count[ci] > 0 {
+                    self.delta_sum[ci] / self.delta_count[ci] as f64
+                } else {
+                    0.0
+                },
+                punishers: self.worst[ci]
+                    .iter()
+                    .map(|(delta, sampled)| Punisher {
+                        delta: *delta,
+                        profile: sampled
+                            .iter()
+                            .enumerate()
+                            .filter(|&(pi, _)| pi != our_idx)
+                            .map(|(pi, &si)| (power_candidates[pi].0, orders_of(pi, si)))
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        WhyNotReport {
+            power: power_candidates[our_idx].0,
+            chosen: orders_of(our_idx, best_idx),
+            chosen_weight: share(weights[best_idx]),
+            rejected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::order::{Location, OrderUnit};
+    use crate::board::province::Province;
+    use crate::board::unit::UnitType;
+
+    fn hold(prov: Province) -> Order {
+        Order::Hold {
+            unit: OrderUnit {
+                unit_type: UnitType::Army,
+                location: Location::new(prov),
+            },
+        }
+    }
+
+    fn two_power_candidates() -> Vec<PowerCandidates> {
+        vec![
+            (
+                Power::Austria,
+                vec![
+                    vec![(hold(Province::Vie), Power::Austria)],
+                    vec![(hold(Province::Bud), Power::Austria)],
+                    vec![(hold(Province::Tri), Power::Austria)],
+                ],
+            ),
+            (
+                Power::Turkey,
+                vec![
+                    vec![(hold(Province::Con), Power::Turkey)],
+                    vec![(hold(Province::Smy), Power::Turkey)],
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn record_keeps_most_punishing_profiles() {
+        let mut trace = RegretTrace::new(2);
+        for (delta, opp) in [(-1.0, 0), (-5.0, 1), (2.0, 2), (-3.0, 3), (-4.0, 4)] {
+            trace.record(1, delta, &[0, opp]);
+        }
+        // Repeating a profile keeps its worst delta rather than duplicating it.
+        trace.record(1, -6.0, &[0, 1]);
+        let deltas: Vec<f64> = trace.worst[1].iter().map(|(d, _)| *d).collect();
+        assert_eq!(deltas, vec![-6.0, -4.0, -3.0]);
+        assert_eq!(trace.delta_count[1], 6);
+    }
+
+    #[test]
+    fn report_ranks_rejected_by_weight_and_names_opponents() {
+        let cands = two_power_candidates();
+        let mut trace = RegretTrace::new(3);
+        trace.record(2, -2.5, &[0, 1]);
+        let report = trace.report(&cands, 0, &[6.0, 1.0, 3.0], 0);
+
+        assert_eq!(report.chosen, vec![hold(Province::Vie)]);
+        assert!((report.chosen_weight - 0.6).abs() < 1e-9);
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].orders, vec![hold(Province::Tri)]);
+        let punisher = &report.rejected[0].punishers[0];
+        assert_eq!(punisher.delta, -2.5);
+        assert_eq!(
+            punisher.profile,
+            vec![(Power::Turkey, vec![hold(Province::Smy)])]
+        );
+    }
+
+    #[test]
+    fn report_json_is_parseable() {
+        let cands = two_power_candidates();
+        let mut trace = RegretTrace::new(3);
+        trace.record(1, -1.25, &[0, 0]);
+        let json = trace.report(&cands, 0, &[1.0, 2.0, 0.0], 1).to_json();
+        let v: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(v["power"], "austria");
+        assert_eq!(v["chosen"], "A bud H");
+        assert_eq!(v["rejected"][0]["orders"], "A vie H");
+        assert!(!json.contains('\n'));
+    }
+}
@@ -0,0 +1,366 @@
+//! Local-search refinement of the chosen order set.
+//!
+//! RM+ picks among the candidate sets it generated, so a set that is right
+//! but for one order stays one order off: a support for the wrong move, a
+//! move into the wrong neighbour, a unit holding next to an enemy
+//! supporter it could cut. After the search, [`refine`] hill-climbs from
+//! the chosen set with three mutation operators, each changing one unit's
+//! order:
+//!
+//! - swap a support to another unit or move it can support,
+//! - redirect a move to another destination,
+//! - insert a cut: move a unit into a province whose foreign unit supports
+//!   in the opponents' equilibrium.
+//!
+//! A mutation is kept when it scores better against a fixed sample of
+//! opponent profiles drawn from the RM+ average strategies
+//! ([`equilibrium_profiles`]), so every set is judged against the same
+//! opponents. Pinned units are never changed and forbidden destinations
+//! are never introduced.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::order::Order;
+use crate::board::province::{Power, Province};
+use crate::board::state::BoardState;
+use crate::movegen::movement::legal_orders;
+use crate::resolve::{advance_state, apply_resolution, Resolver};
+use crate::search::constraints::Constraints;
+use crate::search::regret_matching::PowerCandidates;
+
+/// Opponent profiles a refined set is scored against.
+pub(crate) const REFINE_PROFILES: usize = 6;
+
+/// Full passes over our units before giving up on further improvement.
+const MAX_PASSES: usize = 3;
+
+/// Smallest gain that counts as an improvement.
+const MIN_GAIN: f64 = 1e-6;
+
+/// Fixed seed for sampling profiles, so a search refines reproducibly.
+const PROFILE_SEED: u64 = 0x7265_6669_6e65;
+
+/// The outcome of [`refine`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Refinement {
+    /// The refined set, equal to the starting set if nothing improved it.
+    pub orders: Vec<(Order, Power)>,
+    /// Mean value of the starting set against the profiles.
+    pub before: f64,
+    /// Mean value of the refined set.
+    pub after: f64,
+    /// Mutations kept.
+    pub accepted: usize,
+    /// Sets scored, the starting one included.
+    pub evaluated: usize,
+}
+
+/// Opponent profiles to score our sets against: every opponent's heaviest
+/// candidate, then `n - 1` joint samples from the average strategies
+/// (`weights`, unnormalized, indexed like `power_candidates`).
+pub(crate) fn equilibrium_profiles(
+    power_candidates: &[PowerCandidates],
+    weights: &[Vec<f64>],
+    our_idx: usize,
+    n: usize,
+) -> Vec<Vec<(Order, Power)>> {
+    let opponents: Vec<usize> = (0..power_candidates.len())
+        .filter(|&pi| pi != our_idx)
+        .collect();
+    let mut rng = SmallRng::seed_from_u64(PROFILE_SEED);
+    let mut profiles = Vec::with_capacity(n);
+    for i in 0..n.max(1) {
+        let mut profile = Vec::new();
+        for &pi in &opponents {
+            let w = &weights[pi];
+            let ci = if i == 0 {
+                heaviest(w)
+            } else {
+                sample(w, &mut rng)
+            };
+            profile.extend_from_slice(&power_candidates[pi].1[ci]);
+        }
+        profiles.push(profile);
+    }
+    profiles
+}
+
+/// Index of the largest weight.
+fn heaviest(weights: &[f64]) -> usize {
+    weights
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Samples an index in proportion to `weights`, uniformly if they are all
+/// zero.
+fn sample(weights: &[f64], rng: &mut SmallRng) -> usize {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return rng.gen_range(0..weights.len());
+    }
+    let mut r = rng.gen::<f64>() * total;
+    for (i, &w) in weights.iter().enumerate() {
+        if r < w {
+            return i;
+        }
+        r -= w;
+    }
+    weights.len() - 1
+}
+
+/// Hill-climbs from `chosen` with the mutation operators, keeping each
+/// mutation that raises the mean of `value` over `profiles`. `value` gets
+/// our orders and the position after they were resolved with a profile's
+/// and the phase advanced. Stops after [`MAX_PASSES`] passes, a pass
+/// without improvement, at `deadline`, or when `stop` is set.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refine<F>(
+    power: Power,
+    state: &BoardState,
+    chosen: &[(Order, Power)],
+    profiles: &[Vec<(Order, Power)>],
+    constraints: &Constraints,
+    deadline: Instant,
+    stop: &AtomicBool,
+    value: F,
+) -> Refinement
+where
+    F: Fn(&[(Order, Power)], &BoardState) -> f64,
+{
+    let mut resolver = Resolver::new(64);
+    let mut combined = Vec::with_capacity(32);
+    let mut score = |orders: &[(Order, Power)]| -> f64 {
+        let mut total = 0.0;
+        for profile in profiles {
+            combined.clear();
+            combined.extend_from_slice(orders);
+            combined.extend_from_slice(profile);
+            let after = outcome(&mut resolver, &combined, state);
+            total += value(orders, &after);
+        }
+        total / profiles.len().max(1) as f64
+    };
+
+    let mut orders = chosen.to_vec();
+    let before = score(&orders);
+    let mut best = before;
+    let mut accepted = 0;
+    let mut evaluated = 1;
+    let cut_targets = supporting_units(profiles, power);
+
+    'passes: for _ in 0..MAX_PASSES {
+        let mut improved = false;
+        for i in 0..orders.len() {
+            let current = orders[i].0;
+            for mutation in mutations(&current, state, &cut_targets, constraints) {
+                if stop.load(Ordering::Relaxed) || Instant::now() >= deadline {
+                    break 'passes;
+                }
+                orders[i].0 = mutation;
+                let v = score(&orders);
+                evaluated += 1;
+                if v > best + MIN_GAIN {
+                    best = v;
+                    accepted += 1;
+                    improved = true;
+                    break;
+                }
+                orders[i].0 = current;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    Refinement {
+        orders,
+        before,
+        after: best,
+        accepted,
+        evaluated,
+    }
+}
+
+/// The position after `orders` are resolved in `state` and the phase is
+/// advanced.
+pub(crate) fn outcome(
+    resolver: &mut Resolver,
+    orders: &[(Order, Power)],
+    state: &BoardState,
+) -> BoardState {
+    let (results, dislodged) = resolver.resolve(orders, state);
+    let mut after = state.clone();
+    apply_resolution(&mut after, &results, &dislodged);
+    let has_dislodged = after.dislodged.iter().any(|d| d.is_some());
+    advance_state(&mut after, has_dislodged);
+    after
+}
+
+/// Provinces of other powers' units that give a support order in any
+/// profile.
+fn supporting_units(profiles: &[Vec<(Order, Power)>], power: Power) -> HashSet<Province> {
+    profiles
+        .iter()
+        .flatten()
+        .filter(|(o, p)| {
+            *p != power && matches!(o, Order::SupportHold { .. } | Order::SupportMove { .. })
+        })
+        .filter_map(|(o, _)| o.unit().map(|u| u.location.province))
+        .collect()
+}
+
+/// The single-order changes the operators propose for the unit given
+/// `order`, none of them for a pinned unit.
+fn mutations(
+    order: &Order,
+    state: &BoardState,
+    cut_targets: &HashSet<Province>,
+    constraints: &Constraints,
+) -> Vec<Order> {
+    let Some(unit) = order.unit() else {
+        return Vec::new();
+    };
+    let prov = unit.location.province;
+    if constraints.pinned_for(prov).is_some() {
+        return Vec::new();
+    }
+    legal_orders(prov, state)
+        .into_iter()
+        .filter(|alt| alt != order && constraints.allows(alt))
+        .filter(|alt| match (order, alt) {
+            // Swap a support target.
+            (
+                Order::SupportHold { .. } | Order::SupportMove { .. },
+                Order::SupportHold { .. } | Order::SupportMove { .. },
+            ) => true,
+            // Redirect a move.
+            (Order::Move { .. }, Order::Move { .. }) => true,
+            // Insert a cut on a supporting unit.
+            (_, Order::Move { dest, .. }) => cut_targets.contains(&dest.province),
+            _ => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::{format_orders, parse_orders};
+    use std::time::Duration;
+
+    /// Austria attacks Russian-held Rumania with one support while Ukraine
+    /// supports it to hold; the army in Galicia could cut that support.
+    const DFEN: &str =
+        "1901fm/Aabud,Aaser,Aagal,Rarum,Raukr/Abud,Aser,Atri,Avie,Rrum,Rmos,Nbul,Ngre/-";
+
+    fn with_power(s: &str, power: Power) -> Vec<(Order, Power)> {
+        parse_orders(s)
+            .unwrap()
+            .into_iter()
+            .map(|o| (o, power))
+            .collect()
+    }
+
+    fn austrian_centers(_: &[(Order, Power)], state: &BoardState) -> f64 {
+        state
+            .sc_owner
+            .iter()
+            .filter(|&&o| o == Some(Power::Austria))
+            .count() as f64
+    }
+
+    fn run(chosen: &str, constraints: &Constraints) -> Refinement {
+        let state = parse_dfen(DFEN).unwrap();
+        let profiles = vec![with_power("A rum H ; A ukr S A rum H", Power::Russia)];
+        refine(
+            Power::Austria,
+            &state,
+            &with_power(chosen, Power::Austria),
+            &profiles,
+            constraints,
+            Instant::now() + Duration::from_secs(10),
+            &AtomicBool::new(false),
+            austrian_centers,
+        )
+    }
+
+    #[test]
+    fn inserts_a_cut_on_the_supporting_unit() {
+        let refined = run(
+            "A bud - rum ; A ser S A bud - rum ; A gal H",
+            &Constraints::default(),
+        );
+        assert_eq!(refined.before, 4.0);
+        assert_eq!(refined.after, 5.0);
+        assert_eq!(refined.accepted, 1);
+        assert_eq!(
+            format_orders(&refined.orders.iter().map(|(o, _)| *o).collect::<Vec<_>>()),
+            "A bud - rum ; A ser S A bud - rum ; A gal - ukr"
+        );
+    }
+
+    #[test]
+    fn swaps_a_void_support() {
+        // The support names a move nobody ordered, so the attack on
+        // Rumania goes in alone and bounces.
+        let refined = run(
+            "A bud - rum ; A ser S A gal - bud ; A gal - ukr",
+            &Constraints::default(),
+        );
+        assert_eq!(refined.before, 4.0);
+        assert_eq!(refined.after, 5.0);
+        assert!(refined
+            .orders
+            .contains(&with_power("A ser S A bud - rum", Power::Austria)[0]));
+    }
+
+    #[test]
+    fn redirects_a_move() {
+        let refined = run(
+            "A bud - gal ; A ser S A bud - rum ; A gal - ukr",
+            &Constraints::default(),
+        );
+        assert_eq!(refined.after, 5.0);
+        assert!(refined
+            .orders
+            .contains(&with_power("A bud - rum", Power::Austria)[0]));
+    }
+
+    #[test]
+    fn leaves_pinned_units_alone() {
+        let pinned = Constraints::pinning(&parse_orders("A gal H").unwrap());
+        let refined = run("A bud - rum ; A ser S A bud - rum ; A gal H", &pinned);
+        assert_eq!(refined.accepted, 0);
+        assert_eq!(refined.after, refined.before);
+        assert_eq!(refined.orders[2].0, parse_orders("A gal H").unwrap()[0]);
+    }
+
+    #[test]
+    fn profiles_start_with_each_opponents_heaviest_candidate() {
+        let cands: Vec<PowerCandidates> = vec![
+            (Power::Austria, vec![with_power("A vie H", Power::Austria)]),
+            (
+                Power::Russia,
+                vec![
+                    with_power("A war H", Power::Russia),
+                    with_power("A war - gal", Power::Russia),
+                ],
+            ),
+        ];
+        let weights = vec![vec![1.0], vec![0.2, 0.8]];
+        let profiles = equilibrium_profiles(&cands, &weights, 0, 4);
+        assert_eq!(profiles.len(), 4);
+        assert_eq!(profiles[0], with_power("A war - gal", Power::Russia));
+        assert_eq!(profiles, equilibrium_profiles(&cands, &weights, 0, 4));
+    }
+}
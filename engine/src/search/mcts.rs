@@ -0,0 +1,612 @@
+//! Monte Carlo Tree Search over joint order sets.
+//!
+//! An alternative to RM+ for the movement-phase search, selected with the
+//! `SearchMode` option. The tree is decoupled UCT: each node is a movement
+//! position holding a candidate pool and visit statistics per power, and
+//! every simulation lets each power pick its own candidate by PUCT from
+//! those statistics alone. The picks together are the joint order set
+//! that leads to a child, which is the next movement position after the
+//! orders resolve and the retreats and builds in between are played
+//! greedily. Simulations run one after another from the root.
+//!
+//! A child is evaluated when first reached and expanded, with candidate
+//! pools of its own, when reached again, up to `MAX_DEPTH` turns deep.
+//! Leaves are scored for every power with the RM+ evaluation after a short
+//! greedy lookahead, and each power's statistics back up that power's own
+//! value. Values are normalized per power by the range seen in the tree
+//! before they meet the exploration term. With a policy network loaded,
+//! candidates are scored by it and the scores become the PUCT priors;
+//! without one the priors are uniform.
+//!
+//! Our candidates at the root are generated and constrained as for RM+ and
+//! pay the same cooperation, convention and stagnation penalties. The
+//! orders played are our most visited root candidate.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::analysis::contention::contention_penalty;
+use crate::board::order::Order;
+use crate::board::province::{Power, ALL_POWERS};
+use crate::board::state::{BoardState, Phase};
+use crate::eval::heuristic::power_has_units;
+use crate::eval::NeuralEvaluator;
+use crate::resolve::{advance_state, apply_resolution, is_game_over, Resolver};
+use crate::rng::entropy_rng;
+use crate::search::build_prediction::predict_opponent_builds;
+use crate::search::cartesian::predict_opponent_orders;
+use crate::search::conventions::convention_penalty;
+use crate::search::regret_matching::{
+    cooperation_penalty, opponent_summaries, order_confidence, policy_guided_init,
+    rm_evaluate_blended, search_candidates, simulate_n_phases, step_phase_greedy, GreedyOrderCache,
+    PowerCandidates, RmOptions,
+};
+use crate::search::repetition::stagnation_penalty;
+use crate::search::transposition::TranspositionTable;
+use crate::search::SearchResult;
+use crate::throughput::{per_second, WorkCounts};
+
+/// Share of the movetime spent on candidate generation and simulations.
+const BUDGET: f64 = 0.75;
+
+/// Simulations run even when the time budget is already spent.
+const MIN_SIMULATIONS: u64 = 32;
+
+/// Weight of the PUCT exploration term.
+const EXPLORATION: f64 = 1.4;
+
+/// Movement turns the tree extends below the root.
+const MAX_DEPTH: usize = 3;
+
+/// Phases of greedy play before a leaf is evaluated.
+const LEAF_LOOKAHEAD: usize = 1;
+
+/// Nodes the tree may hold; once full, new positions are evaluated without
+/// being added, so an infinite search runs in bounded memory.
+const MAX_NODES: usize = 50_000;
+
+/// Which algorithm the movement-phase search runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Smooth Regret Matching+ (see `search::regret_matching`).
+    #[default]
+    Rm,
+    /// Decoupled-UCT Monte Carlo Tree Search (this module).
+    Mcts,
+}
+
+impl SearchMode {
+    /// Parses a `SearchMode` option value, ignoring case.
+    pub fn parse(s: &str) -> Option<SearchMode> {
+        match s.to_ascii_lowercase().as_str() {
+            "rm" => Some(SearchMode::Rm),
+            "mcts" => Some(SearchMode::Mcts),
+            _ => None,
+        }
+    }
+
+    /// The option value naming this mode.
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchMode::Rm => "rm",
+            SearchMode::Mcts => "mcts",
+        }
+    }
+}
+
+/// Visit statistics of one candidate of one power at a node.
+#[derive(Debug, Clone, Copy)]
+struct Arm {
+    prior: f64,
+    visits: u32,
+    /// Sum of the values backed up through this candidate.
+    total: f64,
+    /// Subtracted from every value backed up through this candidate.
+    penalty: f64,
+}
+
+/// A movement position in the tree.
+struct Node {
+    state: BoardState,
+    /// Candidate pools, empty until the node is expanded.
+    powers: Vec<PowerCandidates>,
+    /// Per pool, one arm per candidate.
+    arms: Vec<Vec<Arm>>,
+    visits: u32,
+    /// Per-power values of the position, once evaluated.
+    value: Option<[f64; 7]>,
+    /// Child node by the candidate index each pool picked.
+    children: HashMap<Vec<usize>, usize>,
+}
+
+impl Node {
+    fn new(state: BoardState) -> Self {
+        Node {
+            state,
+            powers: Vec::new(),
+            arms: Vec::new(),
+            visits: 0,
+            value: None,
+            children: HashMap::new(),
+        }
+    }
+
+    fn is_expanded(&self) -> bool {
+        !self.powers.is_empty()
+    }
+
+    /// Each pool's PUCT pick.
+    fn select(&self, bounds: &[ValueBounds; 7]) -> Vec<usize> {
+        let explore = EXPLORATION * (self.visits.max(1) as f64).sqrt();
+        self.powers
+            .iter()
+            .zip(&self.arms)
+            .map(|((p, _), arms)| {
+                let bounds = &bounds[power_index(*p)];
+                let score = |a: &Arm| {
+                    let q = if a.visits == 0 {
+                        1.0
+                    } else {
+                        bounds.normalize(a.total / a.visits as f64 - a.penalty)
+                    };
+                    q + explore * a.prior / (1.0 + a.visits as f64)
+                };
+                arms.iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| score(a).total_cmp(&score(b)))
+                    .map_or(0, |(i, _)| i)
+            })
+            .collect()
+    }
+
+    /// Each pool's share of the visits per candidate.
+    fn visit_shares(&self) -> Vec<Vec<f64>> {
+        self.arms
+            .iter()
+            .map(|arms| {
+                let total: u32 = arms.iter().map(|a| a.visits).sum();
+                arms.iter()
+                    .map(|a| a.visits as f64 / total.max(1) as f64)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Range of values seen for one power, for normalizing them to 0..=1.
+#[derive(Debug, Clone, Copy)]
+struct ValueBounds {
+    min: f64,
+    max: f64,
+}
+
+impl Default for ValueBounds {
+    fn default() -> Self {
+        ValueBounds {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl ValueBounds {
+    fn update(&mut self, v: f64) {
+        self.min = self.min.min(v);
+        self.max = self.max.max(v);
+    }
+
+    fn normalize(&self, v: f64) -> f64 {
+        if self.max > self.min {
+            ((v - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.5
+        }
+    }
+}
+
+fn power_index(power: Power) -> usize {
+    ALL_POWERS.iter().position(|&p| p == power).unwrap_or(0)
+}
+
+/// Shared state of one search.
+struct Tree<'a> {
+    nodes: Vec<Node>,
+    bounds: [ValueBounds; 7],
+    power: Power,
+    start_year: u16,
+    neural: Option<&'a NeuralEvaluator>,
+    neural_weight: f32,
+    /// Options for pools below the root: no plans, locks or deals, which
+    /// only hold for the current turn.
+    child_options: RmOptions,
+    resolver: Resolver,
+    greedy_cache: GreedyOrderCache,
+    table: Arc<TranspositionTable>,
+    evaluations: u64,
+    max_depth: usize,
+}
+
+impl Tree<'_> {
+    /// Gives `node` a candidate pool and arms for every power with units.
+    fn expand(&mut self, node: usize, pools: Vec<PowerCandidates>) {
+        let state = &self.nodes[node].state;
+        let arms = pools
+            .iter()
+            .map(|(p, cands)| {
+                let uniform = 1.0 / cands.len() as f64;
+                let priors = self
+                    .neural
+                    .and_then(|n| policy_guided_init(n, *p, state, cands))
+                    .filter(|w| w.len() == cands.len())
+                    .map(|w| {
+                        let sum: f64 = w.iter().sum();
+                        w.iter().map(|x| x / sum.max(f64::MIN_POSITIVE)).collect()
+                    })
+                    .unwrap_or_else(|| vec![uniform; cands.len()]);
+                priors
+                    .into_iter()
+                    .map(|prior| Arm {
+                        prior,
+                        visits: 0,
+                        total: 0.0,
+                        penalty: 0.0,
+                    })
+                    .collect()
+            })
+            .collect();
+        let node = &mut self.nodes[node];
+        node.powers = pools;
+        node.arms = arms;
+    }
+
+    /// Candidate pools for a position below the root.
+    fn child_pools(&mut self, state: &BoardState) -> Vec<PowerCandidates> {
+        let mut rng = entropy_rng();
+        ALL_POWERS
+            .iter()
+            .filter(|&&p| power_has_units(state, p))
+            .map(|&p| {
+                let cands = search_candidates(
+                    p,
+                    self.power,
+                    state,
+                    self.neural,
+                    self.neural_weight,
+                    &self.child_options,
+                    &mut rng,
+                );
+                (p, cands)
+            })
+            .filter(|(_, cands)| !cands.is_empty())
+            .collect()
+    }
+
+    /// Plays the picked candidates at `node` and greedily on to the next
+    /// movement phase.
+    fn play(&mut self, node: usize, picks: &[usize]) -> BoardState {
+        let from = &self.nodes[node];
+        let orders: Vec<(Order, Power)> = from
+            .powers
+            .iter()
+            .zip(picks)
+            .flat_map(|((_, cands), &i)| cands[i].iter().copied())
+            .collect();
+        let (results, dislodged) = self.resolver.resolve(&orders, &from.state);
+        let mut next = from.state.clone();
+        apply_resolution(&mut next, &results, &dislodged);
+        let has_dislodged = next.dislodged.iter().any(|d| d.is_some());
+        advance_state(&mut next, has_dislodged);
+        while next.phase != Phase::Movement {
+            step_phase_greedy(&mut next, &mut self.resolver, &mut self.greedy_cache);
+        }
+        next
+    }
+
+    /// Every power's value of leaf `node`, evaluating it on first use,
+    /// and counts the visit.
+    fn visit_leaf(&mut self, node: usize) -> [f64; 7] {
+        self.nodes[node].visits += 1;
+        if let Some(v) = self.nodes[node].value {
+            return v;
+        }
+        let v = self.evaluate(&self.nodes[node].state.clone());
+        self.nodes[node].value = Some(v);
+        v
+    }
+
+    /// Every power's value of `state` after a short greedy lookahead.
+    fn evaluate(&mut self, state: &BoardState) -> [f64; 7] {
+        let mut rng = entropy_rng();
+        let future = simulate_n_phases(
+            state,
+            self.power,
+            &mut self.resolver,
+            LEAF_LOOKAHEAD,
+            self.start_year,
+            &mut rng,
+            &mut self.greedy_cache,
+        );
+        let mut v = [0.0; 7];
+        for (i, &p) in ALL_POWERS.iter().enumerate() {
+            v[i] = self
+                .table
+                .evaluate(p, &future, || rm_evaluate_blended(p, &future, self.neural));
+        }
+        self.evaluations += 1;
+        v
+    }
+
+    /// Runs one simulation from the root.
+    fn simulate(&mut self) {
+        let mut path: Vec<(usize, Vec<usize>)> = Vec::with_capacity(MAX_DEPTH);
+        let mut node = 0;
+        let values = loop {
+            let picks = self.nodes[node].select(&self.bounds);
+            let child = match self.nodes[node].children.get(&picks) {
+                Some(&child) => child,
+                None if self.nodes.len() >= MAX_NODES => {
+                    let state = self.play(node, &picks);
+                    path.push((node, picks));
+                    break self.evaluate(&state);
+                }
+                None => {
+                    let state = self.play(node, &picks);
+                    self.nodes.push(Node::new(state));
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(picks.clone(), child);
+                    path.push((node, picks));
+                    break self.visit_leaf(child);
+                }
+            };
+            path.push((node, picks));
+            let depth = path.len();
+            self.max_depth = self.max_depth.max(depth);
+            let open = depth < MAX_DEPTH && is_game_over(&self.nodes[child].state).is_none();
+            if !open {
+                break self.visit_leaf(child);
+            }
+            if !self.nodes[child].is_expanded() {
+                let state = self.nodes[child].state.clone();
+                let pools = self.child_pools(&state);
+                if pools.is_empty() {
+                    break self.visit_leaf(child);
+                }
+                self.expand(child, pools);
+            }
+            node = child;
+        };
+        self.max_depth = self.max_depth.max(path.len());
+
+        for (i, v) in values.iter().enumerate() {
+            self.bounds[i].update(*v);
+        }
+        for (node, picks) in path {
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+            for ((p, _), (arms, &pick)) in node.powers.iter().zip(node.arms.iter_mut().zip(&picks))
+            {
+                let arm = &mut arms[pick];
+                arm.visits += 1;
+                arm.total += values[power_index(*p)];
+            }
+        }
+    }
+}
+
+/// Runs an MCTS search for `power`'s movement orders.
+///
+/// Takes the same inputs as the RM+ search and returns the same result:
+/// `iterations` counts simulations, and order confidences and opponent
+/// summaries come from the root visit shares.
+#[allow(clippy::too_many_arguments)]
+pub fn mcts_search<W: Write>(
+    power: Power,
+    state: &BoardState,
+    movetime: Duration,
+    out: &mut W,
+    neural: Option<&NeuralEvaluator>,
+    strength: u64,
+    trust_scores: Option<&[f64; 7]>,
+    stop: &AtomicBool,
+    options: &RmOptions,
+) -> SearchResult {
+    let start = Instant::now();
+    let work_start = WorkCounts::now();
+    let deadline = start + Duration::from_nanos((movetime.as_nanos() as f64 * BUDGET) as u64);
+    let mut rng = entropy_rng();
+    let neural_weight = (strength as f32 / 100.0).clamp(0.0, 1.0);
+
+    let mut pools: Vec<PowerCandidates> = Vec::new();
+    for &p in ALL_POWERS.iter().filter(|&&p| power_has_units(state, p)) {
+        let mut cands =
+            search_candidates(p, power, state, neural, neural_weight, options, &mut rng);
+        if p == power {
+            options.constraints.enforce(&mut cands, power);
+        }
+        if !cands.is_empty() {
+            pools.push((p, cands));
+        }
+    }
+    let Some(our_idx) = pools.iter().position(|(p, _)| *p == power) else {
+        let predicted = predict_opponent_orders(power, state);
+        return SearchResult::new(predicted.iter().map(|(o, _)| *o).collect(), 0.0, 0);
+    };
+    if pools[our_idx].1.len() == 1 {
+        let orders: Vec<Order> = pools[our_idx].1[0].iter().map(|(o, _)| *o).collect();
+        let confidence = vec![1.0; orders.len()];
+        return SearchResult {
+            confidence,
+            elapsed: start.elapsed(),
+            ..SearchResult::new(orders, 0.0, 1)
+        };
+    }
+
+    let builds = predict_opponent_builds(power, state, neural);
+    let table = Arc::new(TranspositionTable::new(
+        options.cache.greedy_entries,
+        options.cache.eval_entries,
+    ));
+    let mut tree = Tree {
+        nodes: vec![Node::new(state.clone())],
+        bounds: [ValueBounds::default(); 7],
+        power,
+        start_year: state.year,
+        neural,
+        neural_weight,
+        child_options: RmOptions {
+            group_plans: false,
+            buffer_states: false,
+            ..RmOptions::default()
+        },
+        resolver: Resolver::new(64),
+        greedy_cache: GreedyOrderCache::new(Arc::clone(&table), options.lookahead_supports)
+            .with_builds(builds),
+        table,
+        evaluations: 0,
+        max_depth: 0,
+    };
+    let penalties: Vec<f64> = pools[our_idx]
+        .1
+        .iter()
+        .map(|cand| {
+            cooperation_penalty(cand, state, power, trust_scores)
+                + convention_penalty(cand, power, &options.conventions)
+                + stagnation_penalty(cand, power, options.stagnant_years)
+                + contention_penalty(cand, &options.contention)
+        })
+        .collect();
+    tree.expand(0, pools);
+    for (arm, penalty) in tree.nodes[0].arms[our_idx].iter_mut().zip(penalties) {
+        arm.penalty = penalty;
+    }
+
+    let mut simulations: u64 = 0;
+    while !stop.load(Ordering::Relaxed)
+        && (simulations < MIN_SIMULATIONS || Instant::now() < deadline)
+    {
+        tree.simulate();
+        simulations += 1;
+        if let Some(currbest) = &options.currbest {
+            let arms = &tree.nodes[0].arms[our_idx];
+            let leader = (0..arms.len()).max_by_key(|&i| arms[i].visits).unwrap_or(0);
+            currbest.offer(leader, || {
+                tree.nodes[0].powers[our_idx].1[leader]
+                    .iter()
+                    .map(|(o, _)| *o)
+                    .collect()
+            });
+        }
+    }
+
+    let root = &tree.nodes[0];
+    let shares = root.visit_shares();
+    let best_idx = root.arms[our_idx]
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, a)| a.visits)
+        .map_or(0, |(i, _)| i);
+    let candidates = &root.powers[our_idx].1;
+    let orders: Vec<Order> = candidates[best_idx].iter().map(|(o, _)| *o).collect();
+    let confidence = order_confidence(candidates, &shares[our_idx], best_idx);
+    let opponents = opponent_summaries(&root.powers, &shares, our_idx);
+    let score = rm_evaluate_blended(power, state, neural) as f32;
+
+    let nodes = tree.evaluations;
+    let elapsed = start.elapsed();
+    let _ = writeln!(
+        out,
+        "info depth {} nodes {} nps {} score {} time {} iterations {} value_net {}",
+        tree.max_depth,
+        nodes,
+        per_second(nodes, elapsed),
+        score as i32,
+        elapsed.as_millis() as u64,
+        simulations,
+        neural.is_some_and(|n| n.has_value())
+    );
+    let _ = writeln!(
+        out,
+        "info string {}",
+        WorkCounts::now().since(work_start).rates_line(elapsed)
+    );
+
+    SearchResult {
+        orders,
+        score,
+        nodes,
+        whynot: None,
+        confidence,
+        opponents,
+        order_sets: Vec::new(),
+        iterations: simulations,
+        elapsed: start.elapsed(),
+        cache: None,
+        converged: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::format_order;
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn parses_search_modes() {
+        assert_eq!(SearchMode::parse("MCTS"), Some(SearchMode::Mcts));
+        assert_eq!(SearchMode::parse("rm"), Some(SearchMode::Rm));
+        assert_eq!(SearchMode::parse("alphabeta"), None);
+        assert_eq!(SearchMode::Mcts.name(), "mcts");
+    }
+
+    #[test]
+    fn searches_the_opening_for_every_unit() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let mut out = Vec::new();
+        let result = mcts_search(
+            Power::Austria,
+            &state,
+            Duration::from_millis(500),
+            &mut out,
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+            &RmOptions::default(),
+        );
+        assert_eq!(result.orders.len(), 3);
+        assert!(result.iterations >= MIN_SIMULATIONS);
+        assert_eq!(result.confidence.len(), 3);
+        assert_eq!(result.opponents.len(), 6);
+        let info = String::from_utf8(out).unwrap();
+        assert!(info.starts_with("info depth "));
+    }
+
+    #[test]
+    fn takes_an_undefended_center() {
+        let state = parse_dfen("1901fm/Aavie,Rawar/Avie,Rwar/-").unwrap();
+        let mut out = Vec::new();
+        let result = mcts_search(
+            Power::Austria,
+            &state,
+            Duration::from_millis(300),
+            &mut out,
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+            &RmOptions::default(),
+        );
+        let orders: Vec<String> = result.orders.iter().map(format_order).collect();
+        assert!(
+            ["A vie - bud", "A vie - tri"].contains(&orders[0].as_str()),
+            "{:?}",
+            orders
+        );
+    }
+}
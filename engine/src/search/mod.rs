@@ -3,11 +3,60 @@
 //! Explores the space of possible order sets to find strong moves,
 //! using evaluation heuristics and neural network guidance.
 
+pub mod agreement;
+pub mod build_prediction;
+pub mod cache_budget;
 pub mod cartesian;
+pub(crate) mod coalition;
+pub mod constraints;
+pub mod conventions;
+pub mod currbest;
+pub mod endgame;
+pub mod forecast;
+pub mod group_plan;
+pub mod introspect;
+pub(crate) mod local_search;
+pub mod mcts;
 pub mod neural_candidates;
+pub mod observe;
+pub mod opening_priors;
+pub(crate) mod rebuild;
 pub mod regret_matching;
+pub mod repetition;
+pub mod replay;
+pub mod search_log;
+pub(crate) mod season;
+pub mod session;
+pub mod time_budget;
+pub mod trajectory;
+pub mod transposition;
+pub mod winter;
 
+pub use agreement::{AgreementReport, AgreementTotals};
+pub use build_prediction::{predict_opponent_builds, BuildPrediction};
+pub use cache_budget::CacheBudget;
 pub use cartesian::{
-    heuristic_build_orders, heuristic_retreat_orders, search, SearchInfo, SearchResult,
+    heuristic_build_orders, heuristic_retreat_orders, search, CacheStats, OpponentSummary,
+    RankedOrderSet, SearchInfo, SearchResult,
 };
-pub use regret_matching::regret_matching_search;
+pub use constraints::{apply_locks, Constraints};
+pub use conventions::{Convention, ConventionTracker};
+pub use currbest::CurrentBest;
+pub use endgame::endgame_search;
+pub use forecast::{forecast, forecast_json, AttackForecast, ProvinceForecast};
+pub use introspect::WhyNotReport;
+pub use mcts::{mcts_search, SearchMode};
+pub use observe::{observe_position, PowerOutlook};
+pub use regret_matching::{
+    candidate_pool, generate_candidates_constrained, greedy_lookahead_orders,
+    regret_matching_search, regret_matching_search_in_session, regret_matching_search_with_options,
+    RmOptions,
+};
+pub use repetition::RepetitionTracker;
+pub use replay::{options_hash, ReplayBlob, ReplayBlobError};
+pub use search_log::SearchLogConfig;
+pub use session::SearchSession;
+pub use time_budget::split_movetime;
+pub use trajectory::ScTrajectory;
+pub use transposition::TranspositionTable;
+pub use winter::winter_search;
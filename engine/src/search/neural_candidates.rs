@@ -18,9 +18,7 @@ const ORDER_TYPE_SUPPORT: usize = 2;
 const ORDER_TYPE_CONVOY: usize = 3;
 #[allow(dead_code)]
 const ORDER_TYPE_RETREAT: usize = 4;
-#[allow(dead_code)]
 const ORDER_TYPE_BUILD: usize = 5;
-#[allow(dead_code)]
 const ORDER_TYPE_DISBAND: usize = 6;
 
 const NUM_ORDER_TYPES: usize = 7;
@@ -45,7 +43,7 @@ fn province_to_area(province: Province, coast: Coast) -> usize {
         (Province::Spa, Coast::South) => 78,
         (Province::Stp, Coast::North) => 79,
         (Province::Stp, Coast::South) => 80,
-        _ => province as usize,
+        _ => province.index(),
     }
 }
 
@@ -74,7 +72,7 @@ fn score_order_neural(order: &Order, logits: &[f32]) -> f32 {
             let src_score = logits[SRC_OFFSET + unit_source_area(unit)];
             type_score + src_score
         }
-        Order::Move { ref unit, dest } => {
+        Order::Move { ref unit, dest, .. } => {
             let type_score = logits[ORDER_TYPE_MOVE];
             let src_score = logits[SRC_OFFSET + unit_source_area(unit)];
             let dst_score = logits[DST_OFFSET + location_to_area(dest)];
@@ -103,6 +101,12 @@ fn score_order_neural(order: &Order, logits: &[f32]) -> f32 {
             let dst_score = logits[DST_OFFSET + location_to_area(convoyed_to)];
             type_score + src_score + dst_score
         }
+        Order::Build { ref unit } => {
+            logits[ORDER_TYPE_BUILD] + logits[SRC_OFFSET + unit_source_area(unit)]
+        }
+        Order::Disband { ref unit } => {
+            logits[ORDER_TYPE_DISBAND] + logits[SRC_OFFSET + unit_source_area(unit)]
+        }
         _ => 0.0,
     }
 }
@@ -195,6 +199,52 @@ pub fn neural_top_k_per_unit(
     Some(per_unit)
 }
 
+/// Picks `count` adjustment orders for a power from its legal builds or
+/// disbands, highest policy score first, at most one per province.
+///
+/// Adjustments are scored with the first unit slot's logits. Returns None
+/// if the policy network is unavailable or inference fails.
+pub fn neural_adjustment_orders(
+    evaluator: &NeuralEvaluator,
+    power: Power,
+    state: &BoardState,
+    legal: &[Order],
+    count: usize,
+) -> Option<Vec<Order>> {
+    if !evaluator.has_policy() {
+        return None;
+    }
+    let logits = evaluator.policy(state, power)?;
+    let slot = &logits[..ORDER_VOCAB_SIZE.min(logits.len())];
+
+    let mut scored: Vec<(Order, f32)> = legal
+        .iter()
+        .filter(|o| !matches!(o, Order::Waive))
+        .map(|&o| (o, score_order_neural(&o, slot)))
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut orders: Vec<Order> = Vec::with_capacity(count);
+    for (order, _) in scored {
+        if orders.len() >= count {
+            break;
+        }
+        let prov = order.unit().map(|u| u.location.province);
+        if orders
+            .iter()
+            .any(|o| o.unit().map(|u| u.location.province) == prov)
+        {
+            continue;
+        }
+        orders.push(order);
+    }
+    // Builds the policy cannot place are waived.
+    if legal.contains(&Order::Waive) {
+        orders.resize(count, Order::Waive);
+    }
+    Some(orders)
+}
+
 /// Converts neural scores to probability weights via softmax.
 pub fn softmax_weights(scores: &[f32]) -> Vec<f64> {
     if scores.is_empty() {
@@ -246,7 +296,7 @@ mod tests {
         // Logits with high values for hold type and Vie source.
         let mut logits = vec![0.0f32; ORDER_VOCAB_SIZE];
         logits[ORDER_TYPE_HOLD] = 5.0;
-        logits[SRC_OFFSET + Province::Vie as usize] = 3.0;
+        logits[SRC_OFFSET + Province::Vie.index()] = 3.0;
 
         let score = score_order_neural(&order, &logits);
         assert!((score - 8.0).abs() < 0.001, "Expected 8.0, got {}", score);
@@ -261,12 +311,13 @@ mod tests {
         let order = Order::Move {
             unit,
             dest: Location::new(Province::Ser),
+            via_convoy: false,
         };
 
         let mut logits = vec![0.0f32; ORDER_VOCAB_SIZE];
         logits[ORDER_TYPE_MOVE] = 4.0;
-        logits[SRC_OFFSET + Province::Bud as usize] = 2.0;
-        logits[DST_OFFSET + Province::Ser as usize] = 6.0;
+        logits[SRC_OFFSET + Province::Bud.index()] = 2.0;
+        logits[DST_OFFSET + Province::Ser.index()] = 6.0;
 
         let score = score_order_neural(&order, &logits);
         assert!((score - 12.0).abs() < 0.001, "Expected 12.0, got {}", score);
@@ -290,8 +341,8 @@ mod tests {
 
         let mut logits = vec![0.0f32; ORDER_VOCAB_SIZE];
         logits[ORDER_TYPE_SUPPORT] = 3.0;
-        logits[SRC_OFFSET + Province::Gal as usize] = 1.0;
-        logits[DST_OFFSET + Province::Rum as usize] = 5.0;
+        logits[SRC_OFFSET + Province::Gal.index()] = 1.0;
+        logits[DST_OFFSET + Province::Rum.index()] = 5.0;
 
         let score = score_order_neural(&order, &logits);
         assert!((score - 9.0).abs() < 0.001, "Expected 9.0, got {}", score);
@@ -329,8 +380,8 @@ mod tests {
 
         let mut logits = vec![0.0f32; ORDER_VOCAB_SIZE];
         logits[ORDER_TYPE_CONVOY] = 7.0;
-        logits[SRC_OFFSET + Province::Mao as usize] = 2.5;
-        logits[DST_OFFSET + Province::Spa as usize] = 4.5;
+        logits[SRC_OFFSET + Province::Mao.index()] = 2.5;
+        logits[DST_OFFSET + Province::Spa.index()] = 4.5;
 
         let score = score_order_neural(&order, &logits);
         assert!((score - 14.0).abs() < 0.001, "Expected 14.0, got {}", score);
@@ -350,9 +401,9 @@ mod tests {
 
         let mut logits = vec![0.0f32; ORDER_VOCAB_SIZE];
         logits[ORDER_TYPE_SUPPORT] = 2.0;
-        logits[SRC_OFFSET + Province::Tyr as usize] = 1.5;
+        logits[SRC_OFFSET + Province::Tyr.index()] = 1.5;
         // SupportHold uses supported unit's source as destination.
-        logits[DST_OFFSET + Province::Vie as usize] = 3.5;
+        logits[DST_OFFSET + Province::Vie.index()] = 3.5;
 
         let score = score_order_neural(&order, &logits);
         assert!((score - 7.0).abs() < 0.001, "Expected 7.0, got {}", score);
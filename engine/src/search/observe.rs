@@ -0,0 +1,183 @@
+//! Spectator analysis of a position for all seven powers.
+//!
+//! Observe mode runs a short search for every power after each position and
+//! streams one line per power (heuristic score, supply centers, distance
+//! to a solo, predicted orders) so broadcast and spectator tools can show a live read of the
+//! board without playing for anyone. Movement phases split the budget with
+//! [`split_movetime`] and use the cartesian search, which gives sensible
+//! orders within a few hundred milliseconds; retreat and build phases use
+//! the heuristic order generators.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::board::map::GameMap;
+use crate::board::order::Order;
+use crate::board::province::{Power, ALL_POWERS};
+use crate::board::state::{BoardState, Phase};
+use crate::eval::{evaluate, solo_distance};
+use crate::protocol::dson::format_orders_on;
+use crate::search::cartesian::{heuristic_build_orders, heuristic_retreat_orders, search};
+use crate::search::time_budget::split_movetime;
+
+/// Default total analysis budget per position in milliseconds.
+pub const DEFAULT_OBSERVE_MS: u64 = 500;
+
+/// One power's slice of an observe analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerOutlook {
+    pub power: Power,
+    /// Heuristic evaluation from the power's perspective.
+    pub score: f32,
+    pub centers: usize,
+    /// Further centers needed for a solo; None if stalemate lines keep the
+    /// power short of one (see `eval::solo`).
+    pub solo: Option<u32>,
+    /// Predicted orders; empty if the power has nothing to order.
+    pub orders: Vec<Order>,
+}
+
+impl PowerOutlook {
+    /// Formats the outlook as an `info observe ...` line (without newline).
+    /// Orders name provinces as `map` does.
+    pub fn info_line(&self, map: &GameMap) -> String {
+        let mut line = format!(
+            "info observe power {} score {} centers {}",
+            self.power.name(),
+            self.score as i32,
+            self.centers
+        );
+        if let Some(solo) = self.solo {
+            line.push_str(&format!(" solo {}", solo));
+        }
+        if !self.orders.is_empty() {
+            line.push_str(" pv ");
+            line.push_str(&format_orders_on(map, &self.orders));
+        }
+        line
+    }
+}
+
+/// Counts the supply centers a power owns.
+fn center_count(power: Power, state: &BoardState) -> usize {
+    state.sc_owner.iter().filter(|&&o| o == Some(power)).count()
+}
+
+/// Returns true if the power has something to order in this phase.
+fn has_orders(power: Power, state: &BoardState) -> bool {
+    let units = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count();
+    match state.phase {
+        Phase::Movement => units > 0,
+        Phase::Retreat => state
+            .dislodged
+            .iter()
+            .any(|d| matches!(d, Some(d) if d.power == power)),
+        Phase::Build => units != center_count(power, state),
+    }
+}
+
+/// Predicts one power's orders within `movetime`.
+fn predict_orders(
+    power: Power,
+    state: &BoardState,
+    movetime: Duration,
+    stop: &AtomicBool,
+) -> Vec<Order> {
+    match state.phase {
+        Phase::Movement => {
+            // Search progress lines are not part of the observe stream.
+            let mut sink = std::io::sink();
+            search(power, state, movetime, &mut sink, stop).orders
+        }
+        Phase::Retreat => heuristic_retreat_orders(power, state),
+        Phase::Build => heuristic_build_orders(power, state),
+    }
+}
+
+/// Analyzes the position for all seven powers, calling `emit` with each
+/// power's outlook as soon as it is ready, in `ALL_POWERS` order.
+///
+/// Stops early (skipping the remaining powers) once `stop` is set. Returns
+/// the number of powers emitted.
+pub fn observe_position<F>(
+    state: &BoardState,
+    budget: Duration,
+    stop: &AtomicBool,
+    mut emit: F,
+) -> usize
+where
+    F: FnMut(&PowerOutlook),
+{
+    let active: Vec<Power> = ALL_POWERS
+        .iter()
+        .copied()
+        .filter(|&p| has_orders(p, state))
+        .collect();
+    let budgets = split_movetime(state, &active, budget);
+
+    let mut emitted = 0;
+    for &power in ALL_POWERS.iter() {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let orders = match budgets.iter().find(|(p, _)| *p == power) {
+            Some(&(_, movetime)) => predict_orders(power, state, movetime, stop),
+            None => Vec::new(),
+        };
+        // A stop during the search leaves partial orders; drop the line.
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        emit(&PowerOutlook {
+            power,
+            score: evaluate(power, state),
+            centers: center_count(power, state),
+            solo: solo_distance(power, state),
+            orders,
+        });
+        emitted += 1;
+    }
+    emitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+
+    #[test]
+    fn observe_reports_every_power_in_order() {
+        let state = parse_dfen("1901sm/Aavie,Aabud,Rawar,Rfsev/Avie,Abud,Rwar,Rsev/-").unwrap();
+        let stop = AtomicBool::new(false);
+        let mut seen = Vec::new();
+        let n = observe_position(&state, Duration::from_millis(200), &stop, |o| {
+            seen.push(o.clone())
+        });
+        assert_eq!(n, 7);
+        let powers: Vec<Power> = seen.iter().map(|o| o.power).collect();
+        assert_eq!(powers, ALL_POWERS.to_vec());
+        assert_eq!(seen[0].orders.len(), 2);
+        assert_eq!(seen[5].orders.len(), 2);
+        assert!(seen[1].orders.is_empty());
+        assert!(seen[0]
+            .info_line(state.map())
+            .starts_with("info observe power austria score "));
+        assert!(seen[0]
+            .info_line(state.map())
+            .contains(" centers 2 solo 16 pv "));
+        assert!(!seen[1].info_line(state.map()).contains(" pv"));
+        assert!(!seen[1].info_line(state.map()).contains(" solo"));
+    }
+
+    #[test]
+    fn observe_stops_when_flag_is_set() {
+        let state = parse_dfen("1901sm/Aavie,Rawar/Avie,Rwar/-").unwrap();
+        let stop = AtomicBool::new(true);
+        let n = observe_position(&state, Duration::from_millis(200), &stop, |_| {});
+        assert_eq!(n, 0);
+    }
+}
@@ -0,0 +1,359 @@
+//! Power-specific opening priors for early-game candidate scoring.
+//!
+//! Encodes standard opening knowledge as score bonuses on candidate orders
+//! in 1901-1902 movement phases: France heads for Iberia, Russia splits
+//! north (Sweden) and south (Rumania), Austria plays hedgehog defenses
+//! around its home centers, and so on. The heuristic scorer is otherwise
+//! blind to these theater-level plans because it only sees one move ahead.
+//!
+//! Priors are added to the per-unit heuristic scores before the top-K cut,
+//! scaled by the caller. The RM+ search scales them by `1 - neural_weight`
+//! when a policy network is loaded, so they fade out as the network takes
+//! over candidate selection.
+//!
+//! The corner powers also take an [`OpeningStyle`]. England and Turkey can
+//! sit out the first years behind their corners or strike early, and a
+//! tournament field plays more varied games when engines differ in which
+//! they do. Through 1903, [`corner_style_penalty`] charges a consolidating
+//! corner power's candidates for each order aimed past its corner and pays
+//! an aggressive one for them; the default style leaves them alone.
+
+use std::fmt;
+
+use crate::board::province::{Power, Province};
+use crate::board::state::{BoardState, Phase};
+use crate::board::Order;
+
+/// Last game year in which opening priors apply.
+pub const OPENING_PRIOR_LAST_YEAR: u16 = 1902;
+
+/// Bonus for supporting a home SC to hold (Austria's hedgehog).
+const HEDGEHOG_SUPPORT_BONUS: f32 = 2.0;
+
+/// Bonus for holding on a home SC (Austria's hedgehog).
+const HEDGEHOG_HOLD_BONUS: f32 = 1.0;
+
+/// Last game year in which a corner power's opening style applies.
+pub const CORNER_STYLE_LAST_YEAR: u16 = 1903;
+
+/// Penalty per forward order under `Consolidate`, bonus under `Aggressive`.
+const CORNER_STYLE_WEIGHT: f64 = 0.5;
+
+/// How a corner power (England or Turkey) plays its first years.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpeningStyle {
+    /// No bias either way.
+    #[default]
+    Standard,
+    /// Secure the corner and the neutrals next to it before engaging.
+    Consolidate,
+    /// Press a neighbour from the first turns.
+    Aggressive,
+}
+
+impl OpeningStyle {
+    /// Parses an opening style name, ignoring case.
+    pub fn parse(s: &str) -> Option<OpeningStyle> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Some(OpeningStyle::Standard),
+            "consolidate" => Some(OpeningStyle::Consolidate),
+            "aggressive" => Some(OpeningStyle::Aggressive),
+            _ => None,
+        }
+    }
+
+    /// The name of this style.
+    pub fn name(self) -> &'static str {
+        match self {
+            OpeningStyle::Standard => "standard",
+            OpeningStyle::Consolidate => "consolidate",
+            OpeningStyle::Aggressive => "aggressive",
+        }
+    }
+}
+
+impl fmt::Display for OpeningStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Provinces past a corner power's corner, where an order presses a
+/// neighbour: the Channel coast and the German and Russian borders for
+/// England, the Black Sea, the Russian border and the western Balkans for
+/// Turkey. Empty for the other powers.
+fn forward_provinces(power: Power) -> &'static [Province] {
+    match power {
+        Power::England => &[
+            Province::Eng,
+            Province::Pic,
+            Province::Bre,
+            Province::Hel,
+            Province::Hol,
+            Province::Den,
+            Province::Stp,
+        ],
+        Power::Turkey => &[
+            Province::Bla,
+            Province::Sev,
+            Province::Ukr,
+            Province::Ser,
+            Province::Alb,
+            Province::Ion,
+        ],
+        _ => &[],
+    }
+}
+
+/// Per-power destination bonuses: (province, bonus).
+fn destination_priors(power: Power) -> &'static [(Province, f32)] {
+    match power {
+        Power::Austria => &[
+            (Province::Ser, 4.0),
+            (Province::Gal, 3.0),
+            (Province::Alb, 2.0),
+            (Province::Gre, 2.0),
+        ],
+        Power::England => &[
+            (Province::Nwy, 4.0),
+            (Province::Nth, 3.0),
+            (Province::Eng, 2.0),
+            (Province::Bel, 2.0),
+        ],
+        Power::France => &[
+            (Province::Spa, 4.0),
+            (Province::Por, 4.0),
+            (Province::Mao, 3.0),
+            (Province::Gas, 2.0),
+            (Province::Bur, 1.0),
+        ],
+        Power::Germany => &[
+            (Province::Den, 4.0),
+            (Province::Hol, 4.0),
+            (Province::Ruh, 2.0),
+            (Province::Bel, 1.0),
+        ],
+        Power::Italy => &[
+            (Province::Tun, 4.0),
+            (Province::Ion, 3.0),
+            (Province::Apu, 2.0),
+        ],
+        Power::Russia => &[
+            (Province::Rum, 4.0),
+            (Province::Swe, 4.0),
+            (Province::Bot, 3.0),
+            (Province::Ukr, 2.0),
+            (Province::Bla, 1.0),
+        ],
+        Power::Turkey => &[
+            (Province::Bul, 4.0),
+            (Province::Bla, 3.0),
+            (Province::Aeg, 2.0),
+            (Province::Gre, 2.0),
+            (Province::Arm, 1.0),
+        ],
+    }
+}
+
+/// Returns true if opening priors apply to this position. The priors name
+/// standard-board provinces, so they are off on other maps.
+#[inline]
+pub fn priors_active(state: &BoardState) -> bool {
+    state.phase == Phase::Movement
+        && state.year <= OPENING_PRIOR_LAST_YEAR
+        && state.map().is_standard()
+}
+
+/// Returns the unscaled opening prior bonus for an order, or 0.0 outside
+/// the opening.
+pub fn opening_prior(order: &Order, power: Power, state: &BoardState) -> f32 {
+    if !priors_active(state) {
+        return 0.0;
+    }
+    match *order {
+        Order::Move { dest, .. } => destination_priors(power)
+            .iter()
+            .find(|(p, _)| *p == dest.province)
+            .map(|(_, b)| *b)
+            .unwrap_or(0.0),
+        Order::Hold { unit }
+            if power == Power::Austria
+                && state.map().home_power(unit.location.province) == Some(Power::Austria) =>
+        {
+            HEDGEHOG_HOLD_BONUS
+        }
+        Order::SupportHold { supported, .. }
+            if power == Power::Austria
+                && state.map().home_power(supported.location.province) == Some(Power::Austria) =>
+        {
+            HEDGEHOG_SUPPORT_BONUS
+        }
+        _ => 0.0,
+    }
+}
+
+/// What `power`'s opening style charges an order set, per move or
+/// supported move into a forward province: positive under `Consolidate`,
+/// negative (a bonus) under `Aggressive`. Zero for the standard style, for
+/// powers outside a corner and outside 1901-1903 movement phases.
+pub fn corner_style_penalty(
+    orders: &[(Order, Power)],
+    power: Power,
+    state: &BoardState,
+    style: OpeningStyle,
+) -> f64 {
+    let sign = match style {
+        OpeningStyle::Standard => return 0.0,
+        OpeningStyle::Consolidate => 1.0,
+        OpeningStyle::Aggressive => -1.0,
+    };
+    if state.phase != Phase::Movement
+        || state.year > CORNER_STYLE_LAST_YEAR
+        || !state.map().is_standard()
+    {
+        return 0.0;
+    }
+    let forward = forward_provinces(power);
+    let count = orders
+        .iter()
+        .filter(|(order, _)| match order {
+            Order::Move { dest, .. } | Order::SupportMove { dest, .. } => {
+                forward.contains(&dest.province)
+            }
+            _ => false,
+        })
+        .count();
+    sign * CORNER_STYLE_WEIGHT * count as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::order::{Location, OrderUnit};
+    use crate::board::state::Season;
+    use crate::board::unit::UnitType;
+
+    fn army_move(from: Province, to: Province) -> Order {
+        Order::Move {
+            unit: OrderUnit {
+                unit_type: UnitType::Army,
+                location: Location::new(from),
+            },
+            dest: Location::new(to),
+            via_convoy: false,
+        }
+    }
+
+    #[test]
+    fn france_prefers_iberia() {
+        let state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        let spa = opening_prior(
+            &army_move(Province::Mar, Province::Spa),
+            Power::France,
+            &state,
+        );
+        let pie = opening_prior(
+            &army_move(Province::Mar, Province::Pie),
+            Power::France,
+            &state,
+        );
+        assert!(spa > pie);
+    }
+
+    #[test]
+    fn russia_splits_north_and_south() {
+        let state = BoardState::empty(1901, Season::Fall, Phase::Movement);
+        assert!(
+            opening_prior(
+                &army_move(Province::Sev, Province::Rum),
+                Power::Russia,
+                &state
+            ) > 0.0
+        );
+        assert!(
+            opening_prior(
+                &army_move(Province::Fin, Province::Swe),
+                Power::Russia,
+                &state
+            ) > 0.0
+        );
+    }
+
+    #[test]
+    fn austria_hedgehog_supports() {
+        let state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        let unit = OrderUnit {
+            unit_type: UnitType::Army,
+            location: Location::new(Province::Bud),
+        };
+        let supported = OrderUnit {
+            unit_type: UnitType::Army,
+            location: Location::new(Province::Vie),
+        };
+        let support = Order::SupportHold { unit, supported };
+        assert!(opening_prior(&support, Power::Austria, &state) > 0.0);
+        assert_eq!(opening_prior(&support, Power::Germany, &state), 0.0);
+    }
+
+    #[test]
+    fn priors_inactive_after_opening() {
+        let state = BoardState::empty(1903, Season::Spring, Phase::Movement);
+        assert_eq!(
+            opening_prior(
+                &army_move(Province::Mar, Province::Spa),
+                Power::France,
+                &state
+            ),
+            0.0
+        );
+        let build = BoardState::empty(1901, Season::Fall, Phase::Build);
+        assert!(!priors_active(&build));
+    }
+
+    #[test]
+    fn corner_style_weighs_forward_orders() {
+        let state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        let channel = [(army_move(Province::Lon, Province::Eng), Power::England)];
+        let north = [(army_move(Province::Edi, Province::Nth), Power::England)];
+        assert_eq!(
+            corner_style_penalty(&channel, Power::England, &state, OpeningStyle::Standard),
+            0.0
+        );
+        assert!(
+            corner_style_penalty(&channel, Power::England, &state, OpeningStyle::Consolidate) > 0.0
+        );
+        assert!(
+            corner_style_penalty(&channel, Power::England, &state, OpeningStyle::Aggressive) < 0.0
+        );
+        assert_eq!(
+            corner_style_penalty(&north, Power::England, &state, OpeningStyle::Consolidate),
+            0.0
+        );
+        // France is not a corner power.
+        assert_eq!(
+            corner_style_penalty(&channel, Power::France, &state, OpeningStyle::Aggressive),
+            0.0
+        );
+        let later = BoardState::empty(1904, Season::Spring, Phase::Movement);
+        assert_eq!(
+            corner_style_penalty(&channel, Power::England, &later, OpeningStyle::Aggressive),
+            0.0
+        );
+    }
+
+    #[test]
+    fn opening_style_names_round_trip() {
+        for style in [
+            OpeningStyle::Standard,
+            OpeningStyle::Consolidate,
+            OpeningStyle::Aggressive,
+        ] {
+            assert_eq!(OpeningStyle::parse(style.name()), Some(style));
+        }
+        assert_eq!(
+            OpeningStyle::parse("Aggressive"),
+            Some(OpeningStyle::Aggressive)
+        );
+        assert_eq!(OpeningStyle::parse("turtle"), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Winter rebuild planning by theater.
+//!
+//! The build heuristic scores each build site by its distance to the
+//! nearest center it could take, which says where to build but says little
+//! about what: after losing two fleets in the Mediterranean, the nearest
+//! unowned center may well be an inland one, and the heuristic rebuilds
+//! armies while the enemy fleets keep the sea. [`plan_builds`] adds the
+//! force balance from [`crate::eval::regions`]: a build is worth more for
+//! each unit of its type the other powers have beyond ours in a theater the
+//! new unit can act in, and each build chosen counts toward that balance
+//! before the next one is picked.
+
+use crate::board::map::GameMap;
+use crate::board::order::Order;
+use crate::board::province::Power;
+use crate::board::state::BoardState;
+use crate::board::unit::UnitType;
+use crate::eval::regions::{theater_forces, theater_of, Theater, TheaterForces};
+
+/// Score per unit of shortfall in a theater the build serves.
+const SHORTFALL_WEIGHT: f32 = 2.5;
+
+/// Shortfall beyond this in one theater does not raise the score further.
+const MAX_SHORTFALL: u32 = 3;
+
+/// Chooses `count` builds from `scored` (build orders with their site
+/// scores), adding the theater shortfall bonus and updating the balance
+/// after each choice. Sites are used once; slots left unfilled are
+/// waived.
+pub(crate) fn plan_builds(
+    power: Power,
+    state: &BoardState,
+    scored: &[(Order, f32)],
+    count: usize,
+) -> Vec<Order> {
+    let mut forces = theater_forces(state, power);
+    let mut orders = Vec::with_capacity(count);
+    let mut used = Vec::with_capacity(count);
+
+    while orders.len() < count {
+        let mut best: Option<(Order, f32)> = None;
+        for &(order, score) in scored {
+            let Order::Build { unit } = order else {
+                continue;
+            };
+            if used.contains(&unit.location.province) {
+                continue;
+            }
+            let total = score + shortfall_bonus(state.map(), &forces, &order);
+            if best.is_none_or(|(_, b)| total > b) {
+                best = Some((order, total));
+            }
+        }
+        let Some((order, _)) = best else {
+            break;
+        };
+        if let Order::Build { unit } = order {
+            used.push(unit.location.province);
+            for t in served_theaters(state.map(), &order) {
+                forces[t.index()].add_unit(true, unit.unit_type);
+            }
+        }
+        orders.push(order);
+    }
+
+    while orders.len() < count {
+        orders.push(Order::Waive);
+    }
+    orders
+}
+
+/// Bonus for a build from the shortfall of its unit type in the theaters
+/// it serves.
+pub(crate) fn shortfall_bonus(map: &GameMap, forces: &[TheaterForces; 6], order: &Order) -> f32 {
+    let Order::Build { unit } = order else {
+        return 0.0;
+    };
+    served_theaters(map, order)
+        .into_iter()
+        .map(|t| {
+            forces[t.index()]
+                .shortfall(unit.unit_type)
+                .min(MAX_SHORTFALL) as f32
+        })
+        .sum::<f32>()
+        * SHORTFALL_WEIGHT
+}
+
+/// Theaters a built unit can act in next turn: its own and those of the
+/// provinces it can move to.
+fn served_theaters(map: &GameMap, order: &Order) -> Vec<Theater> {
+    let Order::Build { unit } = order else {
+        return Vec::new();
+    };
+    let prov = unit.location.province;
+    let is_fleet = unit.unit_type == UnitType::Fleet;
+    let mut theaters: Vec<Theater> = theater_of(prov).into_iter().collect();
+    for adj in map.adj_from(prov) {
+        let ok = if is_fleet {
+            adj.fleet_ok && (unit.location.coast == adj.from_coast || !map.has_coasts(prov))
+        } else {
+            adj.army_ok
+        };
+        match theater_of(adj.to) {
+            Some(t) if ok && !theaters.contains(&t) => theaters.push(t),
+            _ => {}
+        }
+    }
+    theaters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::{format_orders, parse_order};
+    use crate::search::cartesian::heuristic_build_orders;
+
+    /// Italy is down to one fleet against three French ones in the western
+    /// Mediterranean. By distance alone it would build an army in Venice
+    /// for Trieste.
+    const SEA_LOST: &str =
+        "1902fb/Iapie,Ifion,Ffgol,Fftys,Ffwes,Famar/Inap,Irom,Iven,Itun,Fmar,Fbre,Fpar,Fspa/-";
+
+    #[test]
+    fn rebuilds_fleets_where_the_sea_was_lost() {
+        let state = parse_dfen(SEA_LOST).unwrap();
+        let orders = heuristic_build_orders(Power::Italy, &state);
+        assert_eq!(orders.len(), 2);
+        let fleets = orders
+            .iter()
+            .filter(|o| matches!(o, Order::Build { unit } if unit.unit_type == UnitType::Fleet))
+            .count();
+        assert_eq!(fleets, 2, "built {}", format_orders(&orders));
+    }
+
+    #[test]
+    fn each_build_counts_toward_the_balance() {
+        let state = parse_dfen(SEA_LOST).unwrap();
+        let mut forces = theater_forces(&state, Power::Italy);
+        let fleet = parse_order("F nap B").unwrap();
+        let before = shortfall_bonus(state.map(), &forces, &fleet);
+        assert!(before > 0.0);
+        for t in served_theaters(state.map(), &fleet) {
+            forces[t.index()].add_unit(true, UnitType::Fleet);
+        }
+        assert!(shortfall_bonus(state.map(), &forces, &fleet) < before);
+    }
+}
@@ -10,32 +10,58 @@ use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
-use rayon::prelude::*;
 
-use crate::board::adjacency::adj_from;
-use crate::board::order::{Location, OrderUnit};
-use crate::board::province::{
-    Coast, Power, Province, ProvinceType, ALL_POWERS, ALL_PROVINCES, PROVINCE_COUNT,
-};
+use crate::analysis::contention::contention_penalty;
+use crate::analysis::relations::new_front_penalty;
+use crate::board::map::GameMap;
+use crate::board::order::{canonical_orders, orders_equal, Location, OrderUnit};
+use crate::board::province::{Coast, Power, Province, ProvinceType, ALL_POWERS, MAX_PROVINCES};
 use crate::board::state::{BoardState, Phase, Season};
 use crate::board::unit::UnitType;
 use crate::board::Order;
-use crate::eval::evaluate;
 use crate::eval::heuristic::{
     count_scs, nearest_unowned_sc_dist, power_has_units, province_defense, province_threat,
+    unit_can_reach,
 };
 use crate::eval::NeuralEvaluator;
-use crate::movegen::movement::legal_orders;
-use crate::resolve::{advance_state, apply_resolution, needs_build_phase, Resolver};
+use crate::eval::{evaluate, solo_distance, static_exchange};
+use crate::movegen::movement::{legal_orders, legal_orders_into};
+use crate::parallel::prelude::*;
+use crate::protocol::dson::format_orders_on;
+use crate::resolve::{
+    advance_delta, advance_state, apply_resolution, needs_build_phase, resolution_delta, Resolver,
+};
+use crate::rng::entropy_rng;
+use crate::search::build_prediction::{fits, predict_opponent_builds, BuildPrediction};
+use crate::search::cache_budget::CacheBudget;
 use crate::search::cartesian::{
     heuristic_build_orders, heuristic_retreat_orders, predict_opponent_orders,
 };
+use crate::search::coalition::{self, coalition_proof};
+use crate::search::constraints::Constraints;
+use crate::search::conventions::{convention_penalty, Convention};
+use crate::search::currbest::CurrentBest;
+use crate::search::group_plan::plan_candidates;
+use crate::search::introspect::RegretTrace;
+use crate::search::local_search::{equilibrium_profiles, outcome, refine, REFINE_PROFILES};
 use crate::search::neural_candidates::{neural_top_k_per_unit, softmax_weights};
-use crate::search::SearchResult;
+use crate::search::opening_priors::{
+    corner_style_penalty, opening_prior, priors_active, OpeningStyle,
+};
+use crate::search::repetition::stagnation_penalty;
+use crate::search::replay::{options_hash, ReplayBlob};
+use crate::search::search_log::{SearchLog, SearchLogConfig};
+use crate::search::season::SeasonScorer;
+use crate::search::session::{SearchSession, SessionPool};
+use crate::search::trajectory::ScTrajectory;
+use crate::search::transposition::TranspositionTable;
+use crate::search::{CacheStats, OpponentSummary, RankedOrderSet, SearchResult};
+use crate::throughput::{per_second, WorkCounts};
 
 /// Default number of candidate order sets to generate per power (used in tests).
 #[cfg(test)]
@@ -49,15 +75,99 @@ fn num_candidates(unit_count: usize) -> usize {
     16.max(4 * unit_count)
 }
 
+/// Candidates added for another power that keep to its press agreements.
+const AGREED_CANDIDATES: usize = 4;
+
 /// Minimum number of RM+ iterations (guarantees quality even with short budgets).
 const MIN_RM_ITERATIONS: usize = 48;
 
 /// Multi-ply lookahead depth (in half-turns).
 const LOOKAHEAD_DEPTH: usize = 2;
 
+/// Reduced lookahead depth for counterfactuals (relative regret only).
+const CF_LOOKAHEAD_DEPTH: usize = 1;
+
 /// Regret discount factor per iteration (smooth RM+).
 const REGRET_DISCOUNT: f64 = 0.95;
 
+/// Iterations between convergence checks.
+const CONVERGE_CHECK_EVERY: u64 = 32;
+
+/// Largest change in average-strategy entropy (nats) between checks that
+/// still counts as converged.
+const CONVERGE_ENTROPY_DELTA: f64 = 0.01;
+
+/// Largest L1 change in normalized regrets between checks that still
+/// counts as converged.
+const CONVERGE_REGRET_DELTA: f64 = 0.05;
+
+/// Consecutive calm checks needed before the loop exits early.
+const CONVERGE_STREAK: u32 = 3;
+
+/// Watches our power's strategy across RM+ iterations and reports when
+/// further iterations are unlikely to change it.
+///
+/// At each check it compares the entropy of the average strategy and the
+/// normalized cumulative regrets with the previous check; the search has
+/// converged once both moved less than their thresholds for
+/// `CONVERGE_STREAK` checks in a row.
+struct ConvergenceMonitor {
+    entropy: Option<f64>,
+    regrets: Vec<f64>,
+    streak: u32,
+}
+
+impl ConvergenceMonitor {
+    fn new() -> Self {
+        ConvergenceMonitor {
+            entropy: None,
+            regrets: Vec::new(),
+            streak: 0,
+        }
+    }
+
+    /// Records a check and returns true once the strategy has converged.
+    fn observe(&mut self, weights: &[f64], regrets: &[f64]) -> bool {
+        let entropy = entropy(&normalized(weights));
+        let regrets = normalized(regrets);
+        let calm = match self.entropy {
+            Some(prev) => {
+                let regret_delta: f64 = regrets
+                    .iter()
+                    .zip(&self.regrets)
+                    .map(|(a, b)| (a - b).abs())
+                    .sum();
+                (entropy - prev).abs() < CONVERGE_ENTROPY_DELTA
+                    && regret_delta < CONVERGE_REGRET_DELTA
+            }
+            None => false,
+        };
+        self.streak = if calm { self.streak + 1 } else { 0 };
+        self.entropy = Some(entropy);
+        self.regrets = regrets;
+        self.streak >= CONVERGE_STREAK
+    }
+}
+
+/// Scales non-negative weights to sum to 1, or uniform if they sum to 0.
+fn normalized(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    if total > 0.0 {
+        weights.iter().map(|w| w / total).collect()
+    } else {
+        vec![1.0 / weights.len() as f64; weights.len()]
+    }
+}
+
+/// Shannon entropy of a probability vector, in nats.
+fn entropy(probs: &[f64]) -> f64 {
+    probs
+        .iter()
+        .filter(|&&p| p > 0.0)
+        .map(|&p| -p * p.ln())
+        .sum()
+}
+
 /// Budget fraction for candidate generation.
 const BUDGET_CAND_GEN: f64 = 0.15;
 
@@ -71,59 +181,117 @@ const NEURAL_VALUE_WEIGHT: f64 = 0.6;
 /// The heuristic eval typically returns values in [0, ~200], so we scale neural accordingly.
 const NEURAL_VALUE_SCALE: f64 = 200.0;
 
-/// Maximum entries in the second-ply greedy order cache.
-const GREEDY_CACHE_CAPACITY: usize = 1024;
+/// Rollouts averaged for the post-search SC trajectory projection.
+const TRAJECTORY_ROLLOUTS: usize = 16;
 
 /// Computes a hash of the board state fields relevant to movegen.
 ///
-/// Hashes units, fleet_coast, sc_owner, season, and phase — the fields that
-/// determine which greedy orders will be generated. Skips year and dislodged
-/// since they don't affect movement order generation.
-fn hash_board_for_movegen(state: &BoardState) -> u64 {
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    state.season.hash(&mut hasher);
-    state.phase.hash(&mut hasher);
-    for u in &state.units {
-        u.hash(&mut hasher);
+/// Greedy lookahead orders per board, backed by the search's shared
+/// [`TranspositionTable`] so every task of a search reuses the others'
+/// order sets.
+///
+/// `supports` selects the lookahead fidelity: when set, cached order sets
+/// include one support-hold per threatened SC (see `add_lookahead_supports`).
+/// `builds` holds the opponents' predicted Winter adjustments for a Fall
+/// search (see `build_prediction`).
+pub(crate) struct GreedyOrderCache {
+    table: Arc<TranspositionTable>,
+    supports: bool,
+    builds: Vec<BuildPrediction>,
+}
+
+impl GreedyOrderCache {
+    pub(crate) fn new(table: Arc<TranspositionTable>, supports: bool) -> Self {
+        GreedyOrderCache {
+            table,
+            supports,
+            builds: Vec::new(),
+        }
     }
-    for c in &state.fleet_coast {
-        c.hash(&mut hasher);
+
+    /// Plays `builds` in the lookahead's adjustment phases.
+    pub(crate) fn with_builds(mut self, builds: Vec<BuildPrediction>) -> Self {
+        self.builds = builds;
+        self
     }
-    for o in &state.sc_owner {
-        o.hash(&mut hasher);
+
+    /// Adjustment orders for a power in a lookahead Build phase: its
+    /// predicted orders when they still fit the board, else the heuristic.
+    fn build_orders_for(&self, power: Power, state: &BoardState) -> Vec<Order> {
+        self.builds
+            .iter()
+            .find(|b| b.power == power && fits(&b.orders, power, state))
+            .map(|b| b.orders.clone())
+            .unwrap_or_else(|| heuristic_build_orders(power, state))
+    }
+
+    /// Returns greedy lookahead orders for a board, generating and caching
+    /// them on a miss.
+    fn orders_for(&mut self, state: &BoardState) -> Vec<(Order, Power)> {
+        if let Some(cached) = self.table.orders(state) {
+            return cached;
+        }
+        let orders = greedy_lookahead_orders(state, self.supports);
+        self.table.store_orders(state, orders.clone());
+        orders
     }
-    hasher.finish()
 }
 
-/// Simple cache for second-ply greedy orders, keyed by board state hash.
+/// Memo of evaluated futures across RM+ iterations.
 ///
-/// When capacity is exceeded, the cache is cleared (simpler than true LRU,
-/// and the cache rebuilds quickly within an RM+ search).
-struct GreedyOrderCache {
-    map: HashMap<u64, Vec<(Order, Power)>>,
+/// Keyed by `(our candidate index, opponent profile hash, lookahead depth)`.
+/// Lookahead is deterministic given the profile, and as strategies
+/// concentrate the same pairings recur, so repeats skip resolution,
+/// simulation, and evaluation entirely. Values exclude the cooperation
+/// penalty, which depends only on our candidate.
+struct FutureCache {
+    map: HashMap<(usize, u64, usize), f64>,
     capacity: usize,
+    lookups: u64,
+    hits: u64,
+    evictions: u64,
 }
 
-impl GreedyOrderCache {
+impl FutureCache {
     fn new(capacity: usize) -> Self {
-        GreedyOrderCache {
-            map: HashMap::with_capacity(capacity),
+        FutureCache {
+            map: HashMap::new(),
             capacity,
+            lookups: 0,
+            hits: 0,
+            evictions: 0,
         }
     }
 
-    /// Looks up cached greedy orders for a board state hash.
-    fn get(&self, key: u64) -> Option<&Vec<(Order, Power)>> {
-        self.map.get(&key)
+    /// Looks up a future value, counting the lookup toward the dedup rate.
+    fn get(&mut self, key: (usize, u64, usize)) -> Option<f64> {
+        self.lookups += 1;
+        let v = self.map.get(&key).copied();
+        if v.is_some() {
+            self.hits += 1;
+        }
+        v
     }
 
-    /// Inserts greedy orders for a board state hash, evicting all entries if at capacity.
-    fn insert(&mut self, key: u64, orders: Vec<(Order, Power)>) {
+    /// Stores a future value, evicting all entries if at capacity.
+    fn insert(&mut self, key: (usize, u64, usize), value: f64) {
         if self.map.len() >= self.capacity {
+            self.evictions += self.map.len() as u64;
             self.map.clear();
         }
-        self.map.insert(key, orders);
+        self.map.insert(key, value);
+    }
+}
+
+/// Hashes the sampled candidate indices of every power except ours.
+fn opponent_profile_hash(sampled: &[usize], our_power_idx: usize) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for (pi, idx) in sampled.iter().enumerate() {
+        if pi != our_power_idx {
+            idx.hash(&mut hasher);
+        }
     }
+    hasher.finish()
 }
 
 /// A scored candidate order for a single unit.
@@ -133,66 +301,35 @@ struct ScoredOrder {
     score: f32,
 }
 
-/// Scores a single movement order using heuristic features.
-/// Returns the number of unoccupied home SCs for a power (potential build slots).
-fn unoccupied_home_sc_count(power: Power, state: &BoardState) -> i32 {
-    let mut count = 0i32;
-    for (i, p) in ALL_PROVINCES.iter().enumerate() {
-        if p.is_supply_center()
-            && p.home_power() == Some(power)
-            && state.sc_owner[i] == Some(power)
-            && state.units[i].is_none()
-        {
-            count += 1;
-        }
-    }
-    count
-}
-
+/// Scores a single movement order using heuristic features: the
+/// season-independent terms here, plus the [`SeasonScorer`] terms.
 fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
     match *order {
         Order::Hold { unit } => {
             let prov = unit.location.province;
             let mut score: f32 = 0.0;
-            if prov.is_supply_center() && state.sc_owner[prov as usize] == Some(power) {
-                let threat = province_threat(prov, power, state);
-                if threat > 0 {
-                    score += 3.0 + threat as f32;
+            if state.map().is_supply_center(prov) && state.sc_owner[prov.index()] == Some(power) {
+                let ex = static_exchange(prov, power, state);
+                if ex.contested() {
+                    // Holding pays most when the center can actually be kept.
+                    score += if ex.wins() {
+                        3.0 + ex.theirs as f32
+                    } else {
+                        1.0
+                    };
                 }
             }
             score -= 1.0;
-
-            // Fall penalty: holding on a home SC when we need builds blocks construction
-            if state.season == Season::Fall
-                && prov.is_supply_center()
-                && prov.home_power() == Some(power)
-                && state.sc_owner[prov as usize] == Some(power)
-            {
-                let sc_count = count_scs(state, power);
-                let unit_count = state
-                    .units
-                    .iter()
-                    .filter(|u| matches!(u, Some((p, _)) if *p == power))
-                    .count() as i32;
-                let pending_builds = sc_count - unit_count;
-                if pending_builds > 0 {
-                    let free_homes = unoccupied_home_sc_count(power, state);
-                    if free_homes < pending_builds {
-                        score -= 8.0;
-                    }
-                }
-            }
-
-            score
+            score + SeasonScorer::for_state(state).hold(prov, power, state)
         }
-        Order::Move { unit, dest } => {
+        Order::Move { unit, dest, .. } => {
             let src = unit.location.province;
             let dst = dest.province;
             let is_fleet = unit.unit_type == UnitType::Fleet;
             let mut score: f32 = 0.0;
 
-            if dst.is_supply_center() {
-                let owner = state.sc_owner[dst as usize];
+            if state.map().is_supply_center(dst) {
+                let owner = state.sc_owner[dst.index()];
                 match owner {
                     None => score += 10.0,
                     Some(o) if o != power => {
@@ -204,37 +341,16 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                     }
                     _ => score += 1.0,
                 }
-            }
-
-            if state.season == Season::Fall
-                && src.is_supply_center()
-                && state.sc_owner[src as usize] != Some(power)
-            {
-                score -= 12.0;
-            }
-
-            // Fall home SC vacating bonus: move off home SCs to make room for builds
-            if state.season == Season::Fall
-                && src.is_supply_center()
-                && src.home_power() == Some(power)
-                && state.sc_owner[src as usize] == Some(power)
-            {
-                let sc_count = count_scs(state, power);
-                let unit_count = state
-                    .units
-                    .iter()
-                    .filter(|u| matches!(u, Some((p, _)) if *p == power))
-                    .count() as i32;
-                let pending_builds = sc_count - unit_count;
-                if pending_builds > 0 {
-                    let free_homes = unoccupied_home_sc_count(power, state);
-                    if free_homes < pending_builds {
-                        score += 8.0;
+                // Walking into a center we cannot win only bounces.
+                if owner != Some(power) {
+                    let ex = static_exchange(dst, power, state);
+                    if ex.contested() {
+                        score += if ex.wins() { 2.0 } else { -2.0 };
                     }
                 }
             }
 
-            if src.is_supply_center() && state.sc_owner[src as usize] == Some(power) {
+            if state.map().is_supply_center(src) && state.sc_owner[src.index()] == Some(power) {
                 let threat = province_threat(src, power, state);
                 if threat > 0 {
                     let defense = province_defense(src, power, state);
@@ -244,7 +360,7 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 }
             }
 
-            if let Some((p, _)) = state.units[dst as usize] {
+            if let Some((p, _)) = state.units[dst.index()] {
                 if p == power {
                     score -= 15.0;
                 }
@@ -257,12 +373,9 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 score += 3.0 / dist as f32;
             }
 
-            if state.season == Season::Spring && dst.is_supply_center() {
-                let owner = state.sc_owner[dst as usize];
-                if owner != Some(power) {
-                    score += 4.0;
-                }
-            }
+            let season = SeasonScorer::for_state(state);
+            score += season.movement(src, dst, power, state);
+            score += season.denial(dst, power, state);
 
             score
         }
@@ -273,15 +386,21 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 -2.0 // No threat = waste of a move
             } else {
                 let mut score: f32 = 1.0;
-                if prov.is_supply_center() && state.sc_owner[prov as usize] == Some(power) {
-                    score += 4.0 + threat as f32;
+                if state.map().is_supply_center(prov) && state.sc_owner[prov.index()] == Some(power)
+                {
+                    let ex = static_exchange(prov, power, state);
+                    score += if ex.wins() {
+                        4.0 + ex.theirs as f32
+                    } else {
+                        1.0
+                    };
                 }
                 score
             }
         }
         Order::SupportMove { dest, .. } => {
             let dst = dest.province;
-            let has_enemy_unit = matches!(state.units[dst as usize], Some((p, _)) if p != power);
+            let has_enemy_unit = matches!(state.units[dst.index()], Some((p, _)) if p != power);
             let threat = province_threat(dst, power, state);
 
             // If destination has no enemy unit AND no adjacent enemies that could
@@ -291,8 +410,8 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
             }
 
             let mut score: f32 = 2.0;
-            if dst.is_supply_center() {
-                let owner = state.sc_owner[dst as usize];
+            if state.map().is_supply_center(dst) {
+                let owner = state.sc_owner[dst.index()];
                 if owner.is_none() {
                     score += 6.0;
                 } else if owner != Some(power) {
@@ -304,10 +423,13 @@ fn score_order(order: &Order, power: Power, state: &BoardState) -> f32 {
                 // Dislodge-for-capture: supporting a move into an SC occupied by
                 // an enemy is very high value — the support enables both the
                 // dislodge and the SC flip.
-                if dst.is_supply_center() && state.sc_owner[dst as usize] != Some(power) {
+                if state.map().is_supply_center(dst) && state.sc_owner[dst.index()] != Some(power) {
                     score += 6.0;
                 }
             }
+            if state.map().is_supply_center(dst) && !static_exchange(dst, power, state).wins() {
+                score -= 2.0; // even with every supporter the attack fails
+            }
             score
         }
         Order::Convoy { .. } => 1.0,
@@ -380,9 +502,29 @@ fn pick_non_colliding(cands: &[ScoredOrder], claimed: &HashSet<Province>) -> Ord
     hold
 }
 
+/// Returns true if the order keeps its unit in place, so a support-hold for
+/// it is real. Convoying fleets stay put like holding and supporting units.
+fn stays_in_place(order: &Order) -> bool {
+    matches!(
+        order,
+        Order::Hold { .. }
+            | Order::SupportHold { .. }
+            | Order::SupportMove { .. }
+            | Order::Convoy { .. }
+    )
+}
+
+/// Returns true if a support-move into `dest` backs the supported unit's
+/// actual order. A move ordered via convoy is backed like any other move
+/// into the same province.
+fn backs_move(supported_order: Option<Order>, dest: Province) -> bool {
+    matches!(supported_order, Some(Order::Move { dest: d, .. }) if d.province == dest)
+}
+
 /// This prevents wasting orders on phantom supports within a single power's
 /// order set, and also replaces support-moves for foreign units (whose actual
-/// orders are unknown) with support-holds or better alternatives.
+/// orders are unknown) with support-holds or better alternatives. A
+/// support-hold for one of our own units that is moving away is phantom too.
 fn coordinate_candidate_supports(
     candidate: &mut Vec<(Order, Power)>,
     per_unit: &[Vec<ScoredOrder>],
@@ -464,12 +606,7 @@ fn coordinate_candidate_supports(
                     .find(|(p, _)| *p == supported_prov)
                     .map(|(_, o)| *o);
 
-                let is_matching = match supported_order {
-                    Some(Order::Move { dest: d, .. }) => d.province == dest.province,
-                    _ => false,
-                };
-
-                if is_matching {
+                if backs_move(supported_order, dest.province) {
                     continue; // Support matches the actual move -- all good.
                 }
 
@@ -494,6 +631,39 @@ fn coordinate_candidate_supports(
                     .unwrap_or(Order::Hold { unit });
                 candidate[ci] = (new_order, power);
                 changed = true;
+            } else if let Order::SupportHold { unit, supported } = order {
+                // Foreign units may well hold; only our own movers are known.
+                let supported_prov = supported.location.province;
+                let supported_order = match unit_orders.iter().find(|(p, _)| *p == supported_prov) {
+                    Some((_, o)) => *o,
+                    None => continue,
+                };
+                if stays_in_place(&supported_order) {
+                    continue;
+                }
+                let ui = match unit_provinces
+                    .iter()
+                    .position(|&p| p == unit.location.province)
+                {
+                    Some(idx) => idx,
+                    None => continue,
+                };
+                let new_order = find_replacement_order(
+                    &per_unit[ui],
+                    supported_prov,
+                    Some(supported_order),
+                    &unit_orders,
+                    unit_provinces,
+                )
+                .or_else(|| {
+                    per_unit[ui]
+                        .iter()
+                        .find(|so| matches!(so.order, Order::Move { .. }))
+                        .map(|so| so.order)
+                })
+                .unwrap_or(Order::Hold { unit });
+                candidate[ci] = (new_order, power);
+                changed = true;
             }
         }
 
@@ -537,11 +707,11 @@ fn coordinate_candidate_supports(
             let needs_replacement = if !supported_is_ours {
                 true
             } else {
-                let is_matching = final_orders.iter().any(|(p, o)| {
-                    *p == supported_prov
-                        && matches!(*o, Order::Move { dest: d, .. } if d.province == dest.province)
-                });
-                !is_matching
+                let supported_order = final_orders
+                    .iter()
+                    .find(|(p, _)| *p == supported_prov)
+                    .map(|(_, o)| *o);
+                !backs_move(supported_order, dest.province)
             };
             if needs_replacement {
                 // Instead of forcing Hold, find the best Move from this unit's
@@ -730,25 +900,56 @@ fn find_foreign_support_replacement(
 }
 
 /// Generates top-K orders per unit for a given power, sorted descending by score.
-fn top_k_per_unit(power: Power, state: &BoardState, k: usize) -> Vec<Vec<ScoredOrder>> {
+///
+/// `prior_scale` weights the power-specific opening priors added to each
+/// order's heuristic score (0.0 disables them).
+fn top_k_per_unit(
+    power: Power,
+    state: &BoardState,
+    k: usize,
+    prior_scale: f32,
+) -> Vec<Vec<ScoredOrder>> {
+    top_k_per_unit_constrained(power, state, k, prior_scale, &Constraints::default())
+}
+
+/// [`top_k_per_unit`] honouring `constraints`: a pinned unit's only
+/// candidate is its pinned order, and forbidden orders are never kept.
+fn top_k_per_unit_constrained(
+    power: Power,
+    state: &BoardState,
+    k: usize,
+    prior_scale: f32,
+    constraints: &Constraints,
+) -> Vec<Vec<ScoredOrder>> {
+    let use_priors = prior_scale > 0.0 && priors_active(state);
     let mut per_unit: Vec<Vec<ScoredOrder>> = Vec::new();
+    let mut legal = Vec::new();
 
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some((p, _)) = state.units[i] {
             if p != power {
                 continue;
             }
-            let prov = ALL_PROVINCES[i];
-            let legal = legal_orders(prov, state);
+            let prov = Province::from_index(i);
+            if let Some(order) = constraints.pinned_for(prov) {
+                let score = score_order(&order, power, state);
+                per_unit.push(vec![ScoredOrder { order, score }]);
+                continue;
+            }
+            legal_orders_into(prov, state, &mut legal);
+            legal.retain(|o| constraints.allows(o));
             if legal.is_empty() {
                 continue;
             }
 
             let mut scored: Vec<ScoredOrder> = legal
-                .into_iter()
-                .map(|o| ScoredOrder {
-                    order: o,
-                    score: score_order(&o, power, state),
+                .iter()
+                .map(|&o| {
+                    let mut score = score_order(&o, power, state);
+                    if use_priors {
+                        score += prior_scale * opening_prior(&o, power, state);
+                    }
+                    ScoredOrder { order: o, score }
                 })
                 .collect();
 
@@ -757,6 +958,8 @@ fn top_k_per_unit(power: Power, state: &BoardState, k: usize) -> Vec<Vec<ScoredO
                     .partial_cmp(&a.score)
                     .unwrap_or(std::cmp::Ordering::Equal)
             });
+            keep_denial_candidate(&mut scored, power, state, k);
+            keep_hold_and_move(&mut scored, k);
             scored.truncate(k);
             per_unit.push(scored);
         }
@@ -765,6 +968,85 @@ fn top_k_per_unit(power: Power, state: &BoardState, k: usize) -> Vec<Vec<ScoredO
     per_unit
 }
 
+/// Each of `power`'s units' highest-scoring heuristic order, before any
+/// sampling or coordination. `prior_scale` weights the opening priors.
+pub(crate) fn heuristic_top_orders(
+    power: Power,
+    state: &BoardState,
+    prior_scale: f32,
+) -> Vec<Order> {
+    top_k_per_unit(power, state, usize::MAX, prior_scale)
+        .iter()
+        .filter_map(|unit| unit.first().map(|s| s.order))
+        .collect()
+}
+
+/// Ensures the top-K cut keeps the best build-denying move, if the unit has one.
+///
+/// Swaps it into the last kept slot when the SC-capture-centric scores would
+/// otherwise filter it out. `scored` must be sorted descending.
+fn keep_denial_candidate(scored: &mut [ScoredOrder], power: Power, state: &BoardState, k: usize) {
+    let season = SeasonScorer::for_state(state);
+    if k == 0 || scored.len() <= k || season != SeasonScorer::Fall {
+        return;
+    }
+    let is_denial = |so: &ScoredOrder| matches!(so.order, Order::Move { dest, .. } if season.denial(dest.province, power, state) > 0.0);
+    if scored[..k].iter().any(is_denial) {
+        return;
+    }
+    if let Some(pos) = scored[k..].iter().position(is_denial) {
+        scored.swap(k - 1, k + pos);
+    }
+}
+
+/// Ensures the top-K cut keeps the unit's Hold and its best Move.
+///
+/// In crowded positions every neighbour offers a support, and those can
+/// fill the whole cut; the unit is then left without a plain order to fall
+/// back on when the supported move is not played. A missing one replaces
+/// the lowest-scoring kept support or convoy, or failing that the Hold
+/// replaces the lowest-scoring kept Move other than the best. The kept
+/// slots are re-sorted. `scored` must be sorted descending; needs `k >= 2`.
+fn keep_hold_and_move(scored: &mut [ScoredOrder], k: usize) {
+    if k < 2 || scored.len() <= k {
+        return;
+    }
+    let is_hold = |so: &ScoredOrder| matches!(so.order, Order::Hold { .. });
+    let is_move = |so: &ScoredOrder| matches!(so.order, Order::Move { .. });
+    let lowest_support =
+        |kept: &[ScoredOrder]| kept.iter().rposition(|so| !is_hold(so) && !is_move(so));
+    let mut changed = false;
+
+    if !scored[..k].iter().any(is_move) {
+        if let (Some(pos), Some(slot)) = (
+            scored[k..].iter().position(is_move),
+            lowest_support(&scored[..k]),
+        ) {
+            scored.swap(slot, k + pos);
+            changed = true;
+        }
+    }
+    if !scored[..k].iter().any(is_hold) {
+        let best_move = scored[..k].iter().position(is_move);
+        let slot = lowest_support(&scored[..k]).or_else(|| {
+            (0..k)
+                .rev()
+                .find(|&i| is_move(&scored[i]) && Some(i) != best_move)
+        });
+        if let (Some(pos), Some(slot)) = (scored[k..].iter().position(is_hold), slot) {
+            scored.swap(slot, k + pos);
+            changed = true;
+        }
+    }
+    if changed {
+        scored[..k].sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+}
+
 /// Generates diverse candidate order sets for a power by sampling from top-K per unit.
 ///
 /// Generates one greedy candidate (best per unit), stochastically sampled candidates
@@ -774,9 +1056,31 @@ fn generate_candidates(
     power: Power,
     state: &BoardState,
     count: usize,
+    prior_scale: f32,
+    rng: &mut SmallRng,
+) -> Vec<Vec<(Order, Power)>> {
+    generate_constrained(
+        power,
+        state,
+        count,
+        prior_scale,
+        &Constraints::default(),
+        rng,
+    )
+}
+
+/// [`generate_candidates`] with per-unit `constraints` applied before
+/// sampling and coordination. Coordinated candidates may still override a
+/// pinned unit, so callers finish with [`Constraints::enforce`].
+fn generate_constrained(
+    power: Power,
+    state: &BoardState,
+    count: usize,
+    prior_scale: f32,
+    constraints: &Constraints,
     rng: &mut SmallRng,
 ) -> Vec<Vec<(Order, Power)>> {
-    let per_unit = top_k_per_unit(power, state, 5);
+    let per_unit = top_k_per_unit_constrained(power, state, 5, prior_scale, constraints);
     if per_unit.is_empty() {
         return Vec::new();
     }
@@ -799,12 +1103,12 @@ fn generate_candidates(
     // Reserve space for greedy + sampled + coordinated
     let sampled_count = count.saturating_sub(5);
     let mut candidates: Vec<Vec<(Order, Power)>> = Vec::with_capacity(count);
-    let mut seen_orders: Vec<Vec<Order>> = Vec::new();
+    let mut seen_orders: HashSet<Vec<(Order, Power)>> = HashSet::new();
 
     // First candidate: greedy best (with same-power collision avoidance).
     let mut greedy_orders: Vec<(Order, Power)> = dedup_greedy_orders(&per_unit, power);
     coordinate_candidate_supports(&mut greedy_orders, &per_unit, &unit_provinces, power);
-    seen_orders.push(greedy_orders.iter().map(|(o, _)| *o).collect());
+    seen_orders.insert(canonical_orders(&greedy_orders));
     candidates.push(greedy_orders);
 
     // Sampled candidates: softmax noise for diversity
@@ -838,9 +1142,7 @@ fn generate_candidates(
         // the supported unit's actual order in this candidate set.
         coordinate_candidate_supports(&mut orders, &per_unit, &unit_provinces, power);
 
-        let order_key: Vec<Order> = orders.iter().map(|(o, _)| *o).collect();
-        if !seen_orders.contains(&order_key) {
-            seen_orders.push(order_key);
+        if seen_orders.insert(canonical_orders(&orders)) {
             candidates.push(orders);
         }
     }
@@ -854,7 +1156,7 @@ fn generate_candidates(
         &unit_provinces,
         &mut candidates,
         &mut seen_orders,
-        8,
+        coordinated_budget(power, state),
     );
 
     // Fix any phantom supports in the newly-injected coordinated candidates.
@@ -867,19 +1169,53 @@ fn generate_candidates(
     candidates
 }
 
+/// Fewest coordinated candidates injected per search.
+const MIN_COORDINATED: usize = 8;
+
+/// Most coordinated candidates injected per search.
+const MAX_COORDINATED: usize = 24;
+
+/// Priority bonus for a support-move that dislodges an enemy unit from a
+/// supply center we do not own.
+const CAPTURE_PAIR_BONUS: f32 = 3.0;
+
+/// Number of coordinated candidates to inject for `power`.
+///
+/// Scales with the units to coordinate and the owned supply centers under
+/// threat, since large late-game positions have many more useful
+/// support pairings than the opening.
+fn coordinated_budget(power: Power, state: &BoardState) -> usize {
+    let units = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count();
+    let threatened = state
+        .map()
+        .provinces()
+        .iter()
+        .filter(|&&prov| {
+            state.sc_owner[prov.index()] == Some(power) && province_threat(prov, power, state) > 0
+        })
+        .count();
+    (units + threatened).clamp(MIN_COORDINATED, MAX_COORDINATED)
+}
+
 /// Injects coordinated candidates that pair support orders with their matching moves/holds.
 ///
 /// For each support-move order in any unit's top-K, finds the supported unit and
 /// creates a candidate where the supporter plays the support and the mover plays
 /// the matching move, with other units keeping greedy orders. Also creates
-/// support-hold candidates for threatened owned supply centers.
+/// support-hold candidates for threatened owned supply centers. Pairs that
+/// dislodge an enemy unit from a center we do not own are tried first, and
+/// at most `max_coordinated` candidates are added.
 fn inject_coordinated_candidates(
     power: Power,
     state: &BoardState,
     per_unit: &[Vec<ScoredOrder>],
     unit_provinces: &[Province],
     candidates: &mut Vec<Vec<(Order, Power)>>,
-    seen_orders: &mut Vec<Vec<Order>>,
+    seen_orders: &mut HashSet<Vec<(Order, Power)>>,
     max_coordinated: usize,
 ) {
     let mut added = 0usize;
@@ -895,7 +1231,7 @@ fn inject_coordinated_candidates(
                 } => {
                     let dst = dest.province;
                     let has_enemy_unit =
-                        matches!(state.units[dst as usize], Some((p, _)) if p != power);
+                        matches!(state.units[dst.index()], Some((p, _)) if p != power);
                     let threat = province_threat(dst, power, state);
                     // Only inject support-move when destination is contested
                     if !has_enemy_unit && threat == 0 {
@@ -909,14 +1245,19 @@ fn inject_coordinated_candidates(
                             matches!(to.order, Order::Move { dest: d, .. } if d.province == dest.province)
                         });
                         if has_matching_move {
-                            support_opportunities.push((ui, so.order, so.score));
+                            // Dislodging a unit from a center we want comes first.
+                            let capture = has_enemy_unit
+                                && state.map().is_supply_center(dst)
+                                && state.sc_owner[dst.index()] != Some(power);
+                            let bonus = if capture { CAPTURE_PAIR_BONUS } else { 0.0 };
+                            support_opportunities.push((ui, so.order, so.score + bonus));
                         }
                     }
                 }
                 Order::SupportHold { supported, .. } => {
                     let supported_prov = supported.location.province;
-                    if supported_prov.is_supply_center()
-                        && state.sc_owner[supported_prov as usize] == Some(power)
+                    if state.map().is_supply_center(supported_prov)
+                        && state.sc_owner[supported_prov.index()] == Some(power)
                         && province_threat(supported_prov, power, state) > 0
                     {
                         if unit_provinces.iter().any(|&p| p == supported_prov) {
@@ -993,15 +1334,208 @@ fn inject_coordinated_candidates(
             }
         }
 
-        let order_key: Vec<Order> = coord_orders.iter().map(|(o, _)| *o).collect();
-        if !seen_orders.contains(&order_key) {
-            seen_orders.push(order_key);
+        if seen_orders.insert(canonical_orders(&coord_orders)) {
             candidates.push(coord_orders);
             added += 1;
         }
     }
 }
 
+/// Maximum number of unit-group plans injected as candidates.
+const MAX_PLAN_CANDIDATES: usize = 3;
+
+/// Injects the first-turn orders of beam-searched unit-group plans.
+///
+/// Each plan's orders replace the planned units' orders in the greedy
+/// candidate (the first one). Other units that would bounce with a planned
+/// unit hold instead, and supports are re-coordinated afterwards.
+fn inject_plan_candidates(
+    power: Power,
+    state: &BoardState,
+    candidates: &mut Vec<Vec<(Order, Power)>>,
+) {
+    if candidates.is_empty() {
+        return;
+    }
+    let plans = plan_candidates(power, state, MAX_PLAN_CANDIDATES);
+    if plans.is_empty() {
+        return;
+    }
+    let per_unit = top_k_per_unit(power, state, 5, 0.0);
+    let unit_provinces: Vec<Province> = per_unit
+        .iter()
+        .filter_map(|cands| cands.first().and_then(|so| order_unit_province(&so.order)))
+        .collect();
+
+    for plan in &plans {
+        let first = plan.first_orders();
+        let claimed: Vec<Province> = first
+            .iter()
+            .filter_map(|o| match *o {
+                Order::Move { dest, .. } => Some(dest.province),
+                _ => order_unit_province(o),
+            })
+            .collect();
+
+        let mut cand = candidates[0].clone();
+        for entry in cand.iter_mut() {
+            let prov = match order_unit_province(&entry.0) {
+                Some(p) => p,
+                None => continue,
+            };
+            if let Some(planned) = first.iter().find(|o| order_unit_province(o) == Some(prov)) {
+                entry.0 = *planned;
+            } else if let Order::Move { unit, dest, .. } = entry.0 {
+                if claimed.contains(&dest.province) {
+                    entry.0 = Order::Hold { unit };
+                }
+            }
+        }
+        coordinate_candidate_supports(&mut cand, &per_unit, &unit_provinces, power);
+        if !candidates.iter().any(|c| orders_equal(c, &cand)) {
+            candidates.push(cand);
+        }
+    }
+}
+
+/// Maximum number of buffer-state support-hold candidates injected.
+const MAX_BUFFER_CANDIDATES: usize = 2;
+
+/// Largest SC count a third power can have and still be treated as a buffer.
+const BUFFER_MAX_SCS: i32 = 4;
+
+/// Finds support-holds for a weak third power's unit whose survival blocks
+/// a dangerous rival.
+///
+/// A buffer unit belongs to a power with at most `BUFFER_MAX_SCS` centers,
+/// stands next to one of our centers, and can be attacked by a rival with at
+/// least as many centers as we have. Each returned order is a legal
+/// support-hold by one of our units, strongest rival first; at most one per
+/// buffer unit.
+fn buffer_support_orders(power: Power, state: &BoardState) -> Vec<Order> {
+    let our_scs = count_scs(state, power);
+    let mut found: Vec<(i32, Order)> = Vec::new();
+
+    for (i, unit) in state.units.iter().enumerate() {
+        let buffer_power = match unit {
+            Some((p, _)) if *p != power => *p,
+            _ => continue,
+        };
+        let prov = Province::from_index(i);
+        let buffer_scs = count_scs(state, buffer_power);
+        if buffer_scs > BUFFER_MAX_SCS {
+            continue;
+        }
+        let borders_us = state
+            .map()
+            .adj_from(prov)
+            .iter()
+            .any(|adj| state.sc_owner[adj.to.index()] == Some(power));
+        if !borders_us {
+            continue;
+        }
+
+        // Strongest rival (not us, not the buffer) that can hit the province.
+        let rival_scs = state
+            .units
+            .iter()
+            .enumerate()
+            .filter_map(|(j, u)| match u {
+                Some((p, ut)) if *p != power && *p != buffer_power => {
+                    let coast = state.fleet_coast[j].unwrap_or(Coast::None);
+                    unit_can_reach(state.map(), Province::from_index(j), coast, *ut, prov)
+                        .then(|| count_scs(state, *p))
+                }
+                _ => None,
+            })
+            .max();
+        let rival_scs = match rival_scs {
+            Some(n) if n >= our_scs && n > buffer_scs => n,
+            _ => continue,
+        };
+
+        let support = state.units.iter().enumerate().find_map(|(j, u)| match u {
+            Some((p, _)) if *p == power => legal_orders(Province::from_index(j), state)
+                .into_iter()
+                .find(|o| {
+                    matches!(o, Order::SupportHold { supported, .. }
+                        if supported.location.province == prov)
+                }),
+            _ => None,
+        });
+        if let Some(order) = support {
+            found.push((rival_scs, order));
+        }
+    }
+
+    found.sort_by_key(|(rival_scs, _)| std::cmp::Reverse(*rival_scs));
+    found.into_iter().map(|(_, o)| o).collect()
+}
+
+/// Injects buffer-state candidates: the greedy candidate with one unit
+/// support-holding a weak third power's unit (see `buffer_support_orders`).
+///
+/// Our own moves into the buffer province, and supports for them, become
+/// holds. `inject_coordinated_candidates` only pairs supports between our
+/// own units, so cross-power support-holds never come out of it; they are
+/// built here instead and survive re-coordination, which only rewrites
+/// support-moves.
+fn inject_buffer_candidates(
+    power: Power,
+    state: &BoardState,
+    candidates: &mut Vec<Vec<(Order, Power)>>,
+) {
+    if candidates.is_empty() {
+        return;
+    }
+    let supports = buffer_support_orders(power, state);
+    if supports.is_empty() {
+        return;
+    }
+    let per_unit = top_k_per_unit(power, state, 5, 0.0);
+    let unit_provinces: Vec<Province> = per_unit
+        .iter()
+        .filter_map(|cands| cands.first().and_then(|so| order_unit_province(&so.order)))
+        .collect();
+
+    for support in supports.into_iter().take(MAX_BUFFER_CANDIDATES) {
+        let (supporter, buffer) = match support {
+            Order::SupportHold { unit, supported } => {
+                (unit.location.province, supported.location.province)
+            }
+            _ => continue,
+        };
+        let mut cand = candidates[0].clone();
+        for entry in cand.iter_mut() {
+            match entry.0 {
+                _ if order_unit_province(&entry.0) == Some(supporter) => entry.0 = support,
+                Order::Move { unit, dest, .. } | Order::SupportMove { unit, dest, .. }
+                    if dest.province == buffer =>
+                {
+                    entry.0 = Order::Hold { unit }
+                }
+                _ => {}
+            }
+        }
+        coordinate_candidate_supports(&mut cand, &per_unit, &unit_provinces, power);
+        if !candidates.iter().any(|c| orders_equal(c, &cand)) {
+            candidates.push(cand);
+        }
+    }
+}
+
+/// Returns the province of the unit an order is for, if any.
+fn order_unit_province(order: &Order) -> Option<Province> {
+    match *order {
+        Order::Hold { unit }
+        | Order::Move { unit, .. }
+        | Order::SupportHold { unit, .. }
+        | Order::SupportMove { unit, .. }
+        | Order::Convoy { unit, .. } => Some(unit.location.province),
+        _ => None,
+    }
+}
+
 /// Blended candidate order for a single unit, carrying both heuristic and neural scores.
 #[derive(Clone, Copy)]
 struct BlendedOrder {
@@ -1019,20 +1553,22 @@ fn generate_candidates_neural(
     evaluator: &NeuralEvaluator,
     count: usize,
     neural_weight: f32,
+    prior_scale: f32,
     rng: &mut SmallRng,
 ) -> Vec<Vec<(Order, Power)>> {
     // Get neural candidates per unit.
     let neural_per_unit = neural_top_k_per_unit(evaluator, power, state, 8);
 
-    // Get heuristic candidates per unit.
-    let heuristic_per_unit = top_k_per_unit(power, state, 5);
-
-    // If neural failed, fall back to pure heuristic.
+    // If neural failed, fall back to pure heuristic with full-strength priors.
     let neural_per_unit = match neural_per_unit {
         Some(n) if !n.is_empty() => n,
-        _ => return generate_candidates(power, state, count, rng),
+        _ => return generate_candidates(power, state, count, prior_scale, rng),
     };
 
+    // Get heuristic candidates per unit. Opening priors fade out as the
+    // policy network takes over.
+    let heuristic_per_unit = top_k_per_unit(power, state, 5, prior_scale * (1.0 - neural_weight));
+
     if heuristic_per_unit.is_empty() {
         return Vec::new();
     }
@@ -1235,10 +1771,8 @@ fn generate_candidates_neural(
 
     // Add coordinated candidates using the blended per-unit data.
     let pre_coord_len = candidates.len();
-    let mut seen_orders: Vec<Vec<Order>> = candidates
-        .iter()
-        .map(|c| c.iter().map(|(o, _)| *o).collect())
-        .collect();
+    let mut seen_orders: HashSet<Vec<(Order, Power)>> =
+        candidates.iter().map(|c| canonical_orders(c)).collect();
 
     inject_coordinated_candidates(
         power,
@@ -1247,7 +1781,7 @@ fn generate_candidates_neural(
         &blended_unit_provinces,
         &mut candidates,
         &mut seen_orders,
-        8,
+        coordinated_budget(power, state),
     );
 
     // Fix phantom supports in newly-injected coordinated candidates.
@@ -1267,7 +1801,7 @@ fn generate_candidates_neural(
 ///
 /// Uses the policy network to score each candidate order set, then
 /// normalizes the scores to use as initial strategy weights.
-fn policy_guided_init(
+pub(crate) fn policy_guided_init(
     evaluator: &NeuralEvaluator,
     power: Power,
     state: &BoardState,
@@ -1283,7 +1817,7 @@ fn policy_guided_init(
 
     // Collect unit province indices for this power.
     let mut unit_prov_indices: Vec<usize> = Vec::new();
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         if let Some((p, _)) = state.units[i] {
             if p == power {
                 unit_prov_indices.push(i);
@@ -1307,7 +1841,7 @@ fn policy_guided_init(
                 | Order::Move { unit, .. }
                 | Order::SupportHold { unit, .. }
                 | Order::SupportMove { unit, .. }
-                | Order::Convoy { unit, .. } => unit.location.province as usize,
+                | Order::Convoy { unit, .. } => unit.location.province.index(),
                 _ => continue,
             };
 
@@ -1348,7 +1882,7 @@ fn score_order_with_logits(order: &Order, logits: &[f32]) -> f32 {
             (Province::Spa, crate::board::province::Coast::South) => 78,
             (Province::Stp, crate::board::province::Coast::North) => 79,
             (Province::Stp, crate::board::province::Coast::South) => 80,
-            _ => prov as usize,
+            _ => prov.index(),
         }
     };
 
@@ -1358,7 +1892,7 @@ fn score_order_with_logits(order: &Order, logits: &[f32]) -> f32 {
 
     match *order {
         Order::Hold { ref unit } => logits[0] + logits[src_offset + unit_area(unit)],
-        Order::Move { ref unit, dest } => {
+        Order::Move { ref unit, dest, .. } => {
             logits[1] + logits[src_offset + unit_area(unit)] + logits[dst_offset + loc_area(dest)]
         }
         Order::SupportHold {
@@ -1385,17 +1919,47 @@ fn score_order_with_logits(order: &Order, logits: &[f32]) -> f32 {
     }
 }
 
-/// Computes the cooperation penalty: penalizes attacking multiple distinct powers.
+/// Scales the multi-front part of the cooperation penalty to the game state.
 ///
-/// When trust scores are provided, attacks against hostile powers (low trust)
-/// are penalized less, and attacks against allied powers (high trust) are
-/// penalized more.
-fn cooperation_penalty(
-    orders: &[(Order, Power)],
-    state: &BoardState,
-    power: Power,
-    trust_scores: Option<&[f64; 7]>,
-) -> f64 {
+/// Returns 1.0 for an early-game, seven-power board where `power` holds an
+/// average share of SCs. The penalty relaxes after 1904 (10% per year, down
+/// to 40%) and as powers are eliminated, since with few survivors fighting on
+/// several fronts is unavoidable. A power below the average survivor's SC
+/// count is penalized more (up to 1.5x) and one above it less (down to 0.5x).
+fn cooperation_scale(state: &BoardState, power: Power) -> f64 {
+    let year_factor = (1.0 - 0.1 * state.year.saturating_sub(1904) as f64).max(0.4);
+
+    let alive: Vec<Power> = ALL_POWERS
+        .iter()
+        .copied()
+        .filter(|&p| p == power || power_has_units(state, p))
+        .collect();
+    let survivor_factor = alive.len().saturating_sub(1) as f64 / 6.0;
+
+    let total_scs: i32 = alive.iter().map(|&p| count_scs(state, p)).sum();
+    let avg_scs = total_scs as f64 / alive.len() as f64;
+    let own_scs = count_scs(state, power).max(1) as f64;
+    let strength_factor = if avg_scs > 0.0 {
+        (avg_scs / own_scs).clamp(0.5, 1.5)
+    } else {
+        1.0
+    };
+
+    year_factor * survivor_factor * strength_factor
+}
+
+/// Computes the cooperation penalty: penalizes attacking multiple distinct powers.
+///
+/// The per-extra-power cost is scaled by `cooperation_scale`. When trust
+/// scores are provided, attacks against hostile powers (low trust) are
+/// penalized less, and attacks against allied powers (high trust) are
+/// penalized more.
+pub(crate) fn cooperation_penalty(
+    orders: &[(Order, Power)],
+    state: &BoardState,
+    power: Power,
+    trust_scores: Option<&[f64; 7]>,
+) -> f64 {
     let mut attacked = [false; 7];
     let mut count = 0usize;
     let mut trust_adjustment = 0.0f64;
@@ -1404,7 +1968,7 @@ fn cooperation_penalty(
         if let Order::Move { dest, .. } = order {
             let dst = dest.province;
             // SC ownership attack
-            if let Some(owner) = state.sc_owner[dst as usize] {
+            if let Some(owner) = state.sc_owner[dst.index()] {
                 if owner != power {
                     let idx = ALL_POWERS.iter().position(|&p| p == owner).unwrap();
                     if !attacked[idx] {
@@ -1420,7 +1984,7 @@ fn cooperation_penalty(
                 }
             }
             // Unit dislodge attempt
-            if let Some((p, _)) = state.units[dst as usize] {
+            if let Some((p, _)) = state.units[dst.index()] {
                 if p != power {
                     let idx = ALL_POWERS.iter().position(|&pw| pw == p).unwrap();
                     if !attacked[idx] {
@@ -1438,7 +2002,8 @@ fn cooperation_penalty(
     if count <= 1 {
         trust_adjustment.max(0.0)
     } else {
-        (1.0 * (count - 1) as f64 + trust_adjustment).max(0.0)
+        let scale = cooperation_scale(state, power);
+        (scale * (count - 1) as f64 + trust_adjustment).max(0.0)
     }
 }
 
@@ -1450,7 +2015,7 @@ fn cooperation_penalty(
 ///
 /// An LRU cache avoids redundant greedy movegen for board states that have
 /// already been seen during the current search.
-fn simulate_n_phases(
+pub(crate) fn simulate_n_phases(
     state: &BoardState,
     _power: Power,
     resolver: &mut Resolver,
@@ -1465,57 +2030,292 @@ fn simulate_n_phases(
         if current.year > start_year + 2 {
             break;
         }
+        step_phase_greedy(&mut current, resolver, greedy_cache);
+    }
 
-        match current.phase {
-            Phase::Movement => {
-                let board_hash = hash_board_for_movegen(&current);
-                let all_orders = if let Some(cached) = greedy_cache.get(board_hash) {
-                    cached.clone()
-                } else {
-                    let orders = generate_greedy_orders_fast(&current);
-                    greedy_cache.insert(board_hash, orders.clone());
-                    orders
-                };
+    // Finish a Winter in progress, so Fall moves are judged with the
+    // year's adjustments played out.
+    while depth > 0 && current.season == Season::Fall && current.phase != Phase::Movement {
+        step_phase_greedy(&mut current, resolver, greedy_cache);
+    }
 
-                let (results, dislodged) = resolver.resolve(&all_orders, &current);
-                apply_resolution(&mut current, &results, &dislodged);
-                let has_dislodged = current.dislodged.iter().any(|d| d.is_some());
-                advance_state(&mut current, has_dislodged);
-            }
-            Phase::Retreat => {
-                for &p in ALL_POWERS.iter() {
-                    let retreat_orders = heuristic_retreat_orders(p, &current);
-                    if !retreat_orders.is_empty() {
-                        use crate::resolve::{apply_retreats, resolve_retreats};
-                        let retreat_with_power: Vec<(Order, Power)> =
-                            retreat_orders.into_iter().map(|o| (o, p)).collect();
-                        let results = resolve_retreats(&retreat_with_power, &current);
-                        apply_retreats(&mut current, &results);
-                    }
+    // Quiescence: don't stop the lookahead with supported attacks on owned
+    // SCs still pending, or the eval credits SCs that are about to be lost.
+    if depth > 0 {
+        if let Some(extended) = quiescence_extend(&current, resolver) {
+            current = extended;
+        }
+    }
+
+    current
+}
+
+/// Advances the board by one phase with heuristic play for all powers.
+///
+/// Movement uses the cached greedy order generator; retreats and builds use
+/// the cartesian search heuristics.
+pub(crate) fn step_phase_greedy(
+    current: &mut BoardState,
+    resolver: &mut Resolver,
+    greedy_cache: &mut GreedyOrderCache,
+) {
+    match current.phase {
+        Phase::Movement => {
+            let all_orders = greedy_cache.orders_for(current);
+            let (results, dislodged) = resolver.resolve(&all_orders, current);
+            apply_resolution(current, &results, &dislodged);
+            let has_dislodged = current.dislodged.iter().any(|d| d.is_some());
+            advance_state(current, has_dislodged);
+        }
+        Phase::Retreat => {
+            for &p in ALL_POWERS.iter() {
+                let retreat_orders = heuristic_retreat_orders(p, current);
+                if !retreat_orders.is_empty() {
+                    use crate::resolve::{apply_retreats, resolve_retreats};
+                    let retreat_with_power: Vec<(Order, Power)> =
+                        retreat_orders.into_iter().map(|o| (o, p)).collect();
+                    let results = resolve_retreats(&retreat_with_power, current);
+                    apply_retreats(current, &results);
                 }
-                advance_state(&mut current, false);
-            }
-            Phase::Build => {
-                for &p in ALL_POWERS.iter() {
-                    let build_orders = heuristic_build_orders(p, &current);
-                    if !build_orders.is_empty() {
-                        use crate::resolve::{apply_builds, resolve_builds};
-                        let builds_with_power: Vec<(Order, Power)> =
-                            build_orders.into_iter().map(|o| (o, p)).collect();
-                        let results = resolve_builds(&builds_with_power, &current);
-                        apply_builds(&mut current, &results);
-                    }
+            }
+            advance_state(current, false);
+        }
+        Phase::Build => {
+            for &p in ALL_POWERS.iter() {
+                let build_orders = greedy_cache.build_orders_for(p, current);
+                if !build_orders.is_empty() {
+                    use crate::resolve::{apply_builds, resolve_builds};
+                    let builds_with_power: Vec<(Order, Power)> =
+                        build_orders.into_iter().map(|o| (o, p)).collect();
+                    let results = resolve_builds(&builds_with_power, current);
+                    apply_builds(current, &results);
                 }
-                if current.phase == Phase::Build && !needs_build_phase(&current) {
-                    advance_state(&mut current, false);
-                } else {
-                    advance_state(&mut current, false);
+            }
+            if current.phase == Phase::Build && !needs_build_phase(current) {
+                advance_state(current, false);
+            } else {
+                advance_state(current, false);
+            }
+        }
+    }
+}
+
+/// A power paired with its candidate order sets.
+pub(crate) type PowerCandidates = (Power, Vec<Vec<(Order, Power)>>);
+
+/// Projects end-of-year SC counts from the RM+ equilibrium.
+///
+/// Each rollout samples one candidate per power from its average strategy,
+/// resolves it, then plays the following phases greedily, snapshotting the
+/// board each time the game year rolls over.
+fn project_sc_trajectory(
+    state: &BoardState,
+    power_candidates: &[PowerCandidates],
+    avg_strategies: &[Vec<f64>],
+    years: usize,
+    resolver: &mut Resolver,
+    rng: &mut SmallRng,
+    greedy_cache: &mut GreedyOrderCache,
+) -> ScTrajectory {
+    let mut trajectory = ScTrajectory::new(state.year, years);
+    if years == 0 {
+        return trajectory;
+    }
+    // A game year is at most five phases (two movement, two retreat, one build).
+    let max_steps = years * 5;
+    let mut combined: Vec<(Order, Power)> = Vec::with_capacity(32);
+
+    for _ in 0..TRAJECTORY_ROLLOUTS {
+        combined.clear();
+        for (pi, (_, cands)) in power_candidates.iter().enumerate() {
+            combined.extend_from_slice(&cands[weighted_sample(&avg_strategies[pi], rng)]);
+        }
+        let (results, dislodged) = resolver.resolve(&combined, state);
+        let mut current = state.clone();
+        apply_resolution(&mut current, &results, &dislodged);
+        let has_dislodged = current.dislodged.iter().any(|d| d.is_some());
+        advance_state(&mut current, has_dislodged);
+
+        let mut snapshots: Vec<BoardState> = Vec::with_capacity(years);
+        let mut year = state.year;
+        let mut steps = 0;
+        while snapshots.len() < years && steps < max_steps {
+            if current.year != year {
+                snapshots.push(current.clone());
+                year = current.year;
+                continue;
+            }
+            step_phase_greedy(&mut current, resolver, greedy_cache);
+            steps += 1;
+        }
+        if snapshots.is_empty() {
+            snapshots.push(current);
+        }
+        trajectory.add_rollout(&snapshots);
+    }
+    trajectory
+}
+
+/// A supported attack pending against an owned supply center.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ScContest {
+    sc: Province,
+    defender: Power,
+    attacker: Power,
+    /// Unit that moves in.
+    mover: Province,
+    /// Unit that supports the move.
+    supporter: Province,
+}
+
+/// Finds owned SCs where another power can bring a supported attack that
+/// outnumbers the owner's units able to hold or support-hold the SC.
+///
+/// Each attacking unit is used in at most one contest.
+fn find_sc_contests(state: &BoardState) -> Vec<ScContest> {
+    let mut contests = Vec::new();
+    let mut used = [false; MAX_PROVINCES];
+
+    for &sc in state.map().provinces() {
+        let i = sc.index();
+        if !state.map().is_supply_center(sc) {
+            continue;
+        }
+        let defender = match state.sc_owner[i] {
+            Some(o) => o,
+            None => continue,
+        };
+
+        // Reachable neighbors per power (plus the occupant for the defender).
+        let mut reach = [0i32; 7];
+        let mut attackers: [[Option<Province>; 2]; 7] = [[None; 2]; 7];
+        if let Some((p, _)) = state.units[i] {
+            reach[p as usize] += 1;
+        }
+        for adj in state.map().adj_from(sc) {
+            let from = adj.to;
+            let fi = from.index();
+            let (p, ut) = match state.units[fi] {
+                Some(u) => u,
+                None => continue,
+            };
+            if used[fi] || attackers[p as usize].contains(&Some(from)) {
+                continue;
+            }
+            let coast = state.fleet_coast[fi].unwrap_or(Coast::None);
+            if !crate::eval::heuristic::unit_can_reach(state.map(), from, coast, ut, sc) {
+                continue;
+            }
+            // A fleet can't be ordered into a split-coast SC without a coast.
+            let can_move_in = !(ut == UnitType::Fleet && state.map().has_coasts(sc));
+            let slots = &mut attackers[p as usize];
+            if slots[0].is_none() && can_move_in {
+                slots[0] = Some(from);
+            } else if slots[1].is_none() {
+                slots[1] = Some(from);
+            }
+            reach[p as usize] += 1;
+        }
+
+        for &attacker in ALL_POWERS.iter() {
+            if attacker == defender {
+                continue;
+            }
+            let a = attacker as usize;
+            if let [Some(mover), Some(supporter)] = attackers[a] {
+                if reach[a] > reach[defender as usize] {
+                    used[mover.index()] = true;
+                    used[supporter.index()] = true;
+                    contests.push(ScContest {
+                        sc,
+                        defender,
+                        attacker,
+                        mover,
+                        supporter,
+                    });
+                    break;
                 }
             }
         }
     }
 
-    current
+    contests
+}
+
+/// Extends a movement-phase leaf by one resolution when SC contests are pending.
+///
+/// Only the powers involved in a contest act: attackers play the supported
+/// attack, and the rest of their units (and the defenders') play greedy
+/// orders. Uninvolved powers hold. Returns `None` for quiet positions.
+fn quiescence_extend(state: &BoardState, resolver: &mut Resolver) -> Option<BoardState> {
+    if state.phase != Phase::Movement {
+        return None;
+    }
+    let contests = find_sc_contests(state);
+    if contests.is_empty() {
+        return None;
+    }
+
+    let mut involved = [false; 7];
+    for c in &contests {
+        involved[c.attacker as usize] = true;
+        involved[c.defender as usize] = true;
+    }
+
+    let unit_at = |prov: Province| -> OrderUnit {
+        let (_, unit_type) = state.units[prov.index()].unwrap();
+        let coast = state.fleet_coast[prov.index()].unwrap_or(Coast::None);
+        OrderUnit {
+            unit_type,
+            location: Location::with_coast(prov, coast),
+        }
+    };
+
+    let mut orders: Vec<(Order, Power)> = generate_greedy_orders_fast(state)
+        .into_iter()
+        .map(|(order, p)| {
+            if involved[p as usize] {
+                (order, p)
+            } else {
+                let unit = match order {
+                    Order::Hold { unit } | Order::Move { unit, .. } => unit,
+                    other => return (other, p),
+                };
+                (Order::Hold { unit }, p)
+            }
+        })
+        .collect();
+
+    for c in &contests {
+        let mover = unit_at(c.mover);
+        let dest = Location::new(c.sc);
+        for (order, _) in orders.iter_mut() {
+            let prov = match *order {
+                Order::Hold { unit } | Order::Move { unit, .. } => unit.location.province,
+                _ => continue,
+            };
+            if prov == c.mover {
+                *order = Order::Move {
+                    unit: mover,
+                    dest,
+                    via_convoy: false,
+                };
+            } else if prov == c.supporter {
+                *order = Order::SupportMove {
+                    unit: unit_at(c.supporter),
+                    supported: mover,
+                    dest,
+                };
+            }
+        }
+    }
+
+    let mut next = state.clone();
+    let (results, dislodged) = resolver.resolve(&orders, state);
+    apply_resolution(&mut next, &results, &dislodged);
+    let has_dislodged = next.dislodged.iter().any(|d| d.is_some());
+    advance_state(&mut next, has_dislodged);
+    Some(next)
 }
 
 /// Lightweight scoring for lookahead move selection (O(1) per order).
@@ -1525,10 +2325,10 @@ fn simulate_n_phases(
 /// `province_defense`, and `nearest_unowned_sc_dist`.
 #[inline]
 fn score_move_fast(dest: Province, power: Power, state: &BoardState) -> f32 {
-    let dst = dest as usize;
+    let dst = dest.index();
     let mut score: f32 = 0.0;
 
-    if dest.is_supply_center() {
+    if state.map().is_supply_center(dest) {
         match state.sc_owner[dst] {
             None => score += 10.0,
             Some(o) if o != power => score += 7.0,
@@ -1562,12 +2362,12 @@ fn generate_greedy_orders_fast(state: &BoardState) -> Vec<(Order, Power)> {
 
     let mut entries: Vec<UnitEntry> = Vec::with_capacity(22);
 
-    for i in 0..PROVINCE_COUNT {
+    for i in 0..MAX_PROVINCES {
         let (power, unit_type) = match state.units[i] {
             Some(pu) => pu,
             None => continue,
         };
-        let prov = ALL_PROVINCES[i];
+        let prov = Province::from_index(i);
         let coast = state.fleet_coast[i].unwrap_or(Coast::None);
         let is_fleet = unit_type == UnitType::Fleet;
         let unit = OrderUnit {
@@ -1579,7 +2379,7 @@ fn generate_greedy_orders_fast(state: &BoardState) -> Vec<(Order, Power)> {
         let mut best: (Order, f32) = (hold_order, f32::NEG_INFINITY);
         let mut second: (Order, f32) = (hold_order, f32::NEG_INFINITY);
 
-        for adj in adj_from(prov) {
+        for adj in state.map().adj_from(prov) {
             if is_fleet && !adj.fleet_ok {
                 continue;
             }
@@ -1590,7 +2390,7 @@ fn generate_greedy_orders_fast(state: &BoardState) -> Vec<(Order, Power)> {
                 continue;
             }
             let dest = adj.to;
-            let dest_type = dest.province_type();
+            let dest_type = state.map().province_type(dest);
 
             if is_fleet && dest_type == ProvinceType::Land {
                 continue;
@@ -1599,7 +2399,7 @@ fn generate_greedy_orders_fast(state: &BoardState) -> Vec<(Order, Power)> {
                 continue;
             }
 
-            let dest_coast = if is_fleet && dest.has_coasts() {
+            let dest_coast = if is_fleet && state.map().has_coasts(dest) {
                 adj.to_coast
             } else {
                 Coast::None
@@ -1609,6 +2409,7 @@ fn generate_greedy_orders_fast(state: &BoardState) -> Vec<(Order, Power)> {
             let move_order = Order::Move {
                 unit,
                 dest: Location::with_coast(dest, dest_coast),
+                via_convoy: false,
             };
 
             if score > best.1 {
@@ -1725,6 +2526,262 @@ fn generate_greedy_orders_fast(state: &BoardState) -> Vec<(Order, Power)> {
         .collect()
 }
 
+/// Adds the candidates only the searching power gets: unit-group plans and
+/// buffer-state supports, as enabled in `options`, and the host's
+/// suggestions.
+fn inject_own_candidates(
+    power: Power,
+    state: &BoardState,
+    options: &RmOptions,
+    cands: &mut Vec<Vec<(Order, Power)>>,
+) {
+    if options.group_plans {
+        inject_plan_candidates(power, state, cands);
+    }
+    if options.buffer_states {
+        inject_buffer_candidates(power, state, cands);
+    }
+    inject_suggested_candidates(&options.suggestions, cands);
+}
+
+/// Injects the host's suggested order sets.
+///
+/// A suggestion may order only some of our units: its orders replace
+/// those of the same units in the greedy candidate (the first one), and
+/// the rest keep their greedy orders.
+fn inject_suggested_candidates(
+    suggestions: &[Vec<Order>],
+    candidates: &mut Vec<Vec<(Order, Power)>>,
+) {
+    if candidates.is_empty() {
+        return;
+    }
+    for suggestion in suggestions {
+        let mut cand = candidates[0].clone();
+        for entry in cand.iter_mut() {
+            let prov = order_unit_province(&entry.0);
+            if let Some(suggested) = suggestion
+                .iter()
+                .find(|o| prov.is_some() && order_unit_province(o) == prov)
+            {
+                entry.0 = *suggested;
+            }
+        }
+        if !candidates.iter().any(|c| orders_equal(c, &cand)) {
+            candidates.push(cand);
+        }
+    }
+}
+
+/// The heuristic candidate pool RM+ starts from for `power`, generated with
+/// a fixed seed.
+///
+/// This is the search's own generation step (sampled, coordinated, plan,
+/// and buffer candidates, with opening priors as set in `options`) minus
+/// the neural path, so the same state, seed, and options always give the
+/// same pool. Golden-fixture tests use it to turn scoring changes into
+/// reviewable diffs of candidate pools.
+pub fn candidate_pool(
+    power: Power,
+    state: &BoardState,
+    seed: u64,
+    options: &RmOptions,
+) -> Vec<Vec<(Order, Power)>> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let unit_count = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count();
+    let prior_scale = if options.opening_priors { 1.0 } else { 0.0 };
+    let mut cands = generate_candidates(
+        power,
+        state,
+        num_candidates(unit_count),
+        prior_scale,
+        &mut rng,
+    );
+    if !cands.is_empty() {
+        inject_own_candidates(power, state, options, &mut cands);
+    }
+    cands
+}
+
+/// Adds candidates for `power` that keep to what it agreed in press
+/// (`RmOptions::agreements`), so the search weighs its keeping the deal
+/// alongside its other options.
+fn inject_agreed_candidates(
+    power: Power,
+    state: &BoardState,
+    options: &RmOptions,
+    prior_scale: f32,
+    rng: &mut SmallRng,
+    cands: &mut Vec<Vec<(Order, Power)>>,
+) {
+    let Some((_, agreed)) = options.agreements.iter().find(|(p, _)| *p == power) else {
+        return;
+    };
+    let mut extra = generate_constrained(power, state, AGREED_CANDIDATES, prior_scale, agreed, rng);
+    agreed.enforce(&mut extra, power);
+    let mut seen: HashSet<Vec<(Order, Power)>> =
+        cands.iter().map(|c| canonical_orders(c)).collect();
+    for cand in extra {
+        if seen.insert(canonical_orders(&cand)) {
+            cands.push(cand);
+        }
+    }
+}
+
+/// Candidate order sets for `p` at the start of a search for `power`.
+///
+/// Neural-guided when a policy model is loaded, heuristic otherwise; our
+/// own pool is also constrained and gets plan and buffer candidates, and
+/// other powers get candidates keeping to what they agreed in press. The
+/// caller still applies `options.constraints` to our pool once it is final.
+pub(crate) fn search_candidates(
+    p: Power,
+    power: Power,
+    state: &BoardState,
+    neural: Option<&NeuralEvaluator>,
+    neural_weight: f32,
+    options: &RmOptions,
+    rng: &mut SmallRng,
+) -> Vec<Vec<(Order, Power)>> {
+    let unit_count = (0..MAX_PROVINCES)
+        .filter(|&i| matches!(state.units[i], Some((pw, _)) if pw == p))
+        .count();
+    let n_cands = num_candidates(unit_count);
+    let prior_scale = if options.opening_priors { 1.0 } else { 0.0 };
+
+    let mut cands = match neural.filter(|n| n.has_policy()) {
+        // Use neural-guided candidates for all powers.
+        Some(evaluator) => generate_candidates_neural(
+            p,
+            state,
+            evaluator,
+            n_cands,
+            neural_weight,
+            prior_scale,
+            rng,
+        ),
+        None if p == power => {
+            generate_constrained(p, state, n_cands, prior_scale, &options.constraints, rng)
+        }
+        None => generate_candidates(p, state, n_cands, prior_scale, rng),
+    };
+    if cands.is_empty() {
+        return cands;
+    }
+    if p == power {
+        inject_own_candidates(power, state, options, &mut cands);
+    } else {
+        inject_agreed_candidates(p, state, options, prior_scale, rng, &mut cands);
+    }
+    cands
+}
+
+/// Candidate order sets for `power` that satisfy `constraints`: the
+/// search's heuristic generation step with pinned units fixed and
+/// forbidden orders excluded, sized for the power's unit count. Empty when
+/// the power has no units.
+pub fn generate_candidates_constrained(
+    power: Power,
+    state: &BoardState,
+    constraints: &Constraints,
+) -> Vec<Vec<(Order, Power)>> {
+    let mut rng = entropy_rng();
+    let unit_count = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count();
+    let mut cands = generate_constrained(
+        power,
+        state,
+        num_candidates(unit_count),
+        1.0,
+        constraints,
+        &mut rng,
+    );
+    constraints.enforce(&mut cands, power);
+    cands
+}
+
+/// Greedy lookahead orders for every unit on the board.
+///
+/// With `supports` off this is `generate_greedy_orders_fast`: holds and moves
+/// only, which tends to overstate dislodgements in 2-ply futures because no
+/// one ever defends. With `supports` on, each threatened SC whose occupant
+/// holds gets one support-hold from a neighbouring unit of the same power.
+pub fn greedy_lookahead_orders(state: &BoardState, supports: bool) -> Vec<(Order, Power)> {
+    let mut orders = generate_greedy_orders_fast(state);
+    if supports {
+        add_lookahead_supports(&mut orders, state);
+    }
+    orders
+}
+
+/// Converts one unit per threatened, held SC into a support-hold for it.
+///
+/// The supporter is the adjacent same-power unit whose greedy order is a
+/// hold, or failing that a move that does not target a supply center. Each
+/// unit supports at most once, and units that are themselves being
+/// supported keep their hold.
+fn add_lookahead_supports(orders: &mut [(Order, Power)], state: &BoardState) {
+    let mut used = [false; MAX_PROVINCES];
+
+    for hi in 0..orders.len() {
+        let (holder, power) = match orders[hi] {
+            (Order::Hold { unit }, p) => (unit, p),
+            _ => continue,
+        };
+        let sc = holder.location.province;
+        if !state.map().is_supply_center(sc) || province_threat(sc, power, state) == 0 {
+            continue;
+        }
+
+        let mut best: Option<(usize, bool)> = None;
+        for (si, &(order, p)) in orders.iter().enumerate() {
+            if p != power || si == hi {
+                continue;
+            }
+            let (unit, holding) = match order {
+                Order::Hold { unit } => (unit, true),
+                Order::Move { unit, dest, .. } if !state.map().is_supply_center(dest.province) => {
+                    (unit, false)
+                }
+                _ => continue,
+            };
+            let from = unit.location.province;
+            if used[from.index()]
+                || !unit_can_reach(state.map(), from, unit.location.coast, unit.unit_type, sc)
+            {
+                continue;
+            }
+            if best.is_none_or(|(_, h)| holding && !h) {
+                best = Some((si, holding));
+            }
+        }
+
+        if let Some((si, _)) = best {
+            let (order, p) = orders[si];
+            let unit = match order {
+                Order::Hold { unit } | Order::Move { unit, .. } => unit,
+                _ => continue,
+            };
+            orders[si] = (
+                Order::SupportHold {
+                    unit,
+                    supported: holder,
+                },
+                p,
+            );
+            used[unit.location.province.index()] = true;
+            used[sc.index()] = true;
+        }
+    }
+}
+
 /// Enhanced position evaluation for RM+ (more features than basic evaluate).
 fn rm_evaluate(power: Power, state: &BoardState) -> f64 {
     let base = evaluate(power, state) as f64;
@@ -1754,7 +2811,7 @@ fn rm_evaluate(power: Power, state: &BoardState) -> f64 {
         .filter_map(|(i, u)| {
             u.and_then(|(p, ut)| {
                 if p == power {
-                    Some((ALL_PROVINCES[i], ut))
+                    Some((Province::from_index(i), ut))
                 } else {
                     None
                 }
@@ -1765,9 +2822,15 @@ fn rm_evaluate(power: Power, state: &BoardState) -> f64 {
         let mut neighbors = 0;
         for (j, &(prov_b, ut_b)) in own_units.iter().enumerate() {
             if i != j {
-                let coast_b = state.fleet_coast[prov_b as usize]
+                let coast_b = state.fleet_coast[prov_b.index()]
                     .unwrap_or(crate::board::province::Coast::None);
-                if crate::eval::heuristic::unit_can_reach(prov_b, coast_b, ut_b, prov_a) {
+                if crate::eval::heuristic::unit_can_reach(
+                    state.map(),
+                    prov_b,
+                    coast_b,
+                    ut_b,
+                    prov_a,
+                ) {
                     neighbors += 1;
                 }
             }
@@ -1780,14 +2843,13 @@ fn rm_evaluate(power: Power, state: &BoardState) -> f64 {
     let mut support_potential = 0.0f64;
     let mut scored_targets: HashSet<Province> = HashSet::new();
     for &(prov, ut) in &own_units {
-        let _coast =
-            state.fleet_coast[prov as usize].unwrap_or(crate::board::province::Coast::None);
-        for adj in adj_from(prov) {
+        let _coast = state.fleet_coast[prov.index()].unwrap_or(crate::board::province::Coast::None);
+        for adj in state.map().adj_from(prov) {
             let target = adj.to;
-            if !target.is_supply_center() {
+            if !state.map().is_supply_center(target) {
                 continue;
             }
-            if state.sc_owner[target as usize] == Some(power) {
+            if state.sc_owner[target.index()] == Some(power) {
                 continue;
             }
             if scored_targets.contains(&target) {
@@ -1806,9 +2868,10 @@ fn rm_evaluate(power: Power, state: &BoardState) -> f64 {
                 .iter()
                 .filter(|&&(other_prov, other_ut)| {
                     other_prov != prov && {
-                        let other_coast = state.fleet_coast[other_prov as usize]
+                        let other_coast = state.fleet_coast[other_prov.index()]
                             .unwrap_or(crate::board::province::Coast::None);
                         crate::eval::heuristic::unit_can_reach(
+                            state.map(),
                             other_prov,
                             other_coast,
                             other_ut,
@@ -1824,20 +2887,24 @@ fn rm_evaluate(power: Power, state: &BoardState) -> f64 {
         }
     }
 
-    // Solo threat penalty for enemies near 18
+    // Solo threat penalty for enemies near 18, unless stalemate lines
+    // already keep them short of it.
     let mut solo_penalty = 0.0f64;
     for &p in ALL_POWERS.iter() {
         if p == power {
             continue;
         }
         let sc = count_scs(state, p);
-        if sc >= 16 {
-            solo_penalty += 20.0;
-        } else if sc >= 14 {
-            solo_penalty += 10.0;
-        } else if sc >= 12 {
-            solo_penalty += 4.0;
+        if sc < 12 || solo_distance(p, state).is_none() {
+            continue;
         }
+        solo_penalty += if sc >= 16 {
+            20.0
+        } else if sc >= 14 {
+            10.0
+        } else {
+            4.0
+        };
     }
 
     base + lead_bonus + cohesion + support_potential - solo_penalty
@@ -1866,7 +2933,11 @@ fn neural_value_to_scalar(value: &[f32; 4]) -> f64 {
 /// When a neural evaluator with a loaded value model is provided, computes
 /// both heuristic and neural eval and blends them with NEURAL_VALUE_WEIGHT.
 /// Falls back to pure heuristic when no neural model is available.
-fn rm_evaluate_blended(power: Power, state: &BoardState, neural: Option<&NeuralEvaluator>) -> f64 {
+pub(crate) fn rm_evaluate_blended(
+    power: Power,
+    state: &BoardState,
+    neural: Option<&NeuralEvaluator>,
+) -> f64 {
     let heuristic = rm_evaluate(power, state);
 
     let evaluator = match neural {
@@ -1916,81 +2987,260 @@ pub fn regret_matching_search<W: Write>(
     trust_scores: Option<&[f64; 7]>,
     stop: &AtomicBool,
 ) -> SearchResult {
-    let start = Instant::now();
-    let mut rng = SmallRng::from_entropy();
-    let mut resolver = Resolver::new(64);
-
-    // Neural blend weight: maps strength 1-100 to 0.0-1.0.
-    // At strength 50: 50% neural. At 100: 100% neural. At 1: ~1% neural.
-    let neural_weight = (strength as f32 / 100.0).clamp(0.0, 1.0);
-    let has_neural = neural.map_or(false, |n| n.has_policy());
-
-    // Phase 1: Candidate generation for all powers (budget: 25%)
-    let cand_budget = Duration::from_nanos((movetime.as_nanos() as f64 * BUDGET_CAND_GEN) as u64);
-
-    // Generate candidates for each alive power
-    let mut power_candidates: Vec<(Power, Vec<Vec<(Order, Power)>>)> = Vec::new();
-    let mut our_power_idx: usize = 0;
-
-    for &p in ALL_POWERS.iter() {
-        if !power_has_units(state, p) {
-            continue;
-        }
-
-        // Count units for this power to scale candidate count.
-        let unit_count = (0..PROVINCE_COUNT)
-            .filter(|&i| matches!(state.units[i], Some((pw, _)) if pw == p))
-            .count();
-        let n_cands = num_candidates(unit_count);
-
-        let cands = if has_neural {
-            // Use neural-guided candidates for all powers.
-            generate_candidates_neural(p, state, neural.unwrap(), n_cands, neural_weight, &mut rng)
-        } else {
-            generate_candidates(p, state, n_cands, &mut rng)
-        };
-        if cands.is_empty() {
-            continue;
-        }
+    regret_matching_search_with_options(
+        power,
+        state,
+        movetime,
+        out,
+        neural,
+        strength,
+        trust_scores,
+        stop,
+        &RmOptions::default(),
+    )
+}
 
-        if p == power {
-            our_power_idx = power_candidates.len();
-        }
-        power_candidates.push((p, cands));
+/// Tunable knobs for the RM+ search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RmOptions {
+    /// Adds power-specific opening priors to candidate scores in 1901-1902.
+    pub opening_priors: bool,
+    /// Whether we consolidate or strike early in 1901-1903 when playing a
+    /// corner power (see `search::opening_priors`).
+    pub corner_opening: OpeningStyle,
+    /// Injects first-turn orders of beam-searched unit-group plans as
+    /// candidates for the searching power.
+    pub group_plans: bool,
+    /// Injects candidates that support-hold a weak third power's unit
+    /// shielding us from a stronger rival.
+    pub buffer_states: bool,
+    /// Game years of projected SC counts to report after search (0 = off).
+    pub trajectory_years: usize,
+    /// Adds one support-hold per threatened SC to greedy lookahead orders.
+    pub lookahead_supports: bool,
+    /// Records why top non-chosen candidates lost (see `search::introspect`).
+    pub introspection: bool,
+    /// Established gunboat conventions; our candidates that break one we
+    /// are party to pay a penalty (see `search::conventions`).
+    pub conventions: Vec<Convention>,
+    /// Entry capacities of the search caches (see `search::cache_budget`).
+    pub cache: CacheBudget,
+    /// Number of our top candidates to list after search as
+    /// `info string candidate` lines (0 = none).
+    pub candidate_dump: usize,
+    /// Number of our order sets, the chosen one first, to return in
+    /// [`SearchResult::order_sets`] (0 = none).
+    pub multi_order_sets: usize,
+    /// Ends the RM+ loop before its time budget once our strategy has
+    /// converged (see `ConvergenceMonitor`).
+    pub early_exit: bool,
+    /// Pinned orders and forbidden destinations for our candidates, e.g.
+    /// the host's `lockorder` locks (see `search::constraints`).
+    pub constraints: Constraints,
+    /// What other powers agreed to in press (see `press::negotiation`);
+    /// each gets extra candidates that keep to its terms.
+    pub agreements: Vec<(Power, Constraints)>,
+    /// Appends a JSONL trace of the RM+ iterations to a file (see
+    /// `search::search_log`).
+    pub search_log: Option<SearchLogConfig>,
+    /// Years the position has stood unchanged; once the game is stagnant,
+    /// our candidates that move nothing pay a penalty (see
+    /// `search::repetition`).
+    pub stagnant_years: u32,
+    /// Provinces our moves keep bouncing in, with their failed-move
+    /// counts; unsupported moves into them pay a penalty (see
+    /// `analysis::contention`).
+    pub contention: Vec<(Province, u32)>,
+    /// Powers we have clashed with in recent turns; while there are any,
+    /// our candidates that strike someone else pay a penalty (see
+    /// `analysis::relations`).
+    pub fronts: Vec<Power>,
+    /// Refines the chosen set with single-order mutations scored against
+    /// the opponents' equilibrium (see `search::local_search`).
+    pub local_search: bool,
+    /// With three or four powers left, chooses the set with the best
+    /// worst case against any coalition of the others rather than the
+    /// equilibrium pick (see `search::coalition`).
+    pub coalition_proof: bool,
+    /// Reruns the search a blob describes: its seed, its number of powers
+    /// and exactly its iteration count, ignoring the clock (see
+    /// `search::replay`).
+    pub replay: Option<ReplayBlob>,
+    /// Where the search publishes its leading candidate as it runs (see
+    /// `search::currbest`).
+    pub currbest: Option<Arc<CurrentBest>>,
+    /// Order sets the host suggested with `suggest`; each becomes one of
+    /// our candidates (see [`inject_suggested_candidates`]).
+    pub suggestions: Vec<Vec<Order>>,
+}
 
-        if start.elapsed() >= cand_budget {
-            break;
+impl Default for RmOptions {
+    fn default() -> Self {
+        RmOptions {
+            opening_priors: true,
+            corner_opening: OpeningStyle::Standard,
+            group_plans: true,
+            buffer_states: true,
+            trajectory_years: 2,
+            lookahead_supports: false,
+            introspection: false,
+            conventions: Vec::new(),
+            cache: CacheBudget::default(),
+            candidate_dump: 0,
+            multi_order_sets: 0,
+            early_exit: true,
+            constraints: Constraints::default(),
+            agreements: Vec::new(),
+            search_log: None,
+            stagnant_years: 0,
+            contention: Vec::new(),
+            fronts: Vec::new(),
+            local_search: true,
+            coalition_proof: false,
+            replay: None,
+            currbest: None,
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+/// Runs RM+ search with explicit options. See [`regret_matching_search`].
+#[allow(clippy::too_many_arguments)]
+pub fn regret_matching_search_with_options<W: Write>(
+    power: Power,
+    state: &BoardState,
+    movetime: Duration,
+    out: &mut W,
+    neural: Option<&NeuralEvaluator>,
+    strength: u64,
+    trust_scores: Option<&[f64; 7]>,
+    stop: &AtomicBool,
+    options: &RmOptions,
+) -> SearchResult {
+    regret_matching_search_in_session(
+        power,
+        state,
+        movetime,
+        out,
+        neural,
+        strength,
+        trust_scores,
+        stop,
+        options,
+        &mut SearchSession::new(),
+    )
+}
+
+/// Runs RM+ search within a [`SearchSession`].
+///
+/// When the session holds a search of the same position for the same
+/// power, its candidate pools, regrets and strategy weights are resumed;
+/// otherwise pools are generated fresh, seeded with the session's still
+/// legal order sets. The session is updated with this search's pools.
+#[allow(clippy::too_many_arguments)]
+pub fn regret_matching_search_in_session<W: Write>(
+    power: Power,
+    state: &BoardState,
+    movetime: Duration,
+    out: &mut W,
+    neural: Option<&NeuralEvaluator>,
+    strength: u64,
+    trust_scores: Option<&[f64; 7]>,
+    stop: &AtomicBool,
+    options: &RmOptions,
+    session: &mut SearchSession,
+) -> SearchResult {
+    let start = Instant::now();
+    let work_start = WorkCounts::now();
+    let seed = match options.replay {
+        Some(blob) => blob.seed,
+        None => entropy_rng().gen(),
+    };
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let pools = session.fingerprint();
+    let mut resolver = Resolver::new(64);
+
+    // Neural blend weight: maps strength 1-100 to 0.0-1.0.
+    // At strength 50: 50% neural. At 100: 100% neural. At 1: ~1% neural.
+    let neural_weight = (strength as f32 / 100.0).clamp(0.0, 1.0);
+    let has_neural = neural.map_or(false, |n| n.has_policy());
+
+    // Phase 1: Candidate generation for all powers (budget: 25%)
+    let cand_budget = Duration::from_nanos((movetime.as_nanos() as f64 * BUDGET_CAND_GEN) as u64);
+
+    // Generate candidates for each alive power, or resume the session's.
+    let resumed = session
+        .last
+        .take()
+        .filter(|last| last.power == power && last.state == *state);
+    let mut power_candidates: Vec<(Power, Vec<Vec<(Order, Power)>>)> = Vec::new();
+    let mut our_power_idx: usize = 0;
+    if let Some(last) = resumed.as_ref() {
+        power_candidates = last.candidates.clone();
+        our_power_idx = last.our_idx;
+    }
+
+    for &p in ALL_POWERS.iter() {
+        if resumed.is_some() {
+            break;
+        }
+        if !power_has_units(state, p) {
+            continue;
+        }
+
+        // Count units for this power to scale candidate count.
+        let mut cands =
+            search_candidates(p, power, state, neural, neural_weight, options, &mut rng);
+        if cands.is_empty() {
+            continue;
+        }
+        let carried = session.carry_over(p, state);
+        if !carried.is_empty() {
+            let mut seen: HashSet<Vec<(Order, Power)>> =
+                cands.iter().map(|c| canonical_orders(c)).collect();
+            for cand in carried {
+                if seen.insert(canonical_orders(&cand)) {
+                    cands.push(cand);
+                }
+            }
+        }
+
+        if p == power {
+            options.constraints.enforce(&mut cands, power);
+            our_power_idx = power_candidates.len();
+        }
+        power_candidates.push((p, cands));
+
+        let done = match options.replay {
+            Some(blob) => power_candidates.len() >= blob.powers,
+            None => start.elapsed() >= cand_budget,
+        };
+        if done {
+            break;
         }
     }
 
     // Fallback: if we have no candidates for our power, use the opponent predictor
     if power_candidates.is_empty() || !power_candidates.iter().any(|(p, _)| *p == power) {
         let opponent_orders = predict_opponent_orders(power, state);
-        return SearchResult {
-            orders: opponent_orders.iter().map(|(o, _)| *o).collect(),
-            score: 0.0,
-            nodes: 0,
-        };
+        return SearchResult::new(opponent_orders.iter().map(|(o, _)| *o).collect(), 0.0, 0);
     }
 
     // Get our candidate count
     let our_k = power_candidates[our_power_idx].1.len();
     if our_k == 0 {
-        return SearchResult {
-            orders: Vec::new(),
-            score: 0.0,
-            nodes: 0,
-        };
+        return SearchResult::new(Vec::new(), 0.0, 0);
     }
     if our_k == 1 {
-        let orders = power_candidates[our_power_idx].1[0]
+        let orders: Vec<Order> = power_candidates[our_power_idx].1[0]
             .iter()
             .map(|(o, _)| *o)
             .collect();
+        let confidence = vec![1.0; orders.len()];
         return SearchResult {
-            orders,
-            score: 0.0,
-            nodes: 1,
+            confidence,
+            elapsed: start.elapsed(),
+            ..SearchResult::new(orders, 0.0, 1)
         };
     }
 
@@ -2003,8 +3253,19 @@ pub fn regret_matching_search<W: Write>(
         .iter()
         .map(|(_, cands)| vec![1.0; cands.len()])
         .collect();
+    // Accumulated strategy weights for final selection
+    let mut total_weights: Vec<Vec<f64>> = power_candidates
+        .iter()
+        .map(|(_, cands)| vec![0.0; cands.len()])
+        .collect();
+    let mut prior_iterations: u64 = 0;
+    if let Some(last) = resumed {
+        cum_regrets = last.cum_regrets;
+        total_weights = last.total_weights;
+        prior_iterations = last.iterations;
+    }
 
-    if has_neural {
+    if has_neural && prior_iterations == 0 {
         if let Some(evaluator) = neural {
             if let Some(init_weights) =
                 policy_guided_init(evaluator, power, state, &power_candidates[our_power_idx].1)
@@ -2016,24 +3277,28 @@ pub fn regret_matching_search<W: Write>(
         }
     }
 
-    // Accumulated strategy weights for final selection
-    let mut total_weights: Vec<Vec<f64>> = power_candidates
-        .iter()
-        .map(|(_, cands)| vec![0.0; cands.len()])
-        .collect();
-
-    // Pre-compute cooperation penalties for our power's candidates
+    // Pre-compute cooperation, convention, stagnation, opening-style and
+    // new-front penalties for our power's candidates
+    let penalty = |cand: &[(Order, Power)]| {
+        cooperation_penalty(cand, state, power, trust_scores)
+            + convention_penalty(cand, power, &options.conventions)
+            + stagnation_penalty(cand, power, options.stagnant_years)
+            + contention_penalty(cand, &options.contention)
+            + corner_style_penalty(cand, power, state, options.corner_opening)
+            + new_front_penalty(cand, state, power, &options.fronts)
+    };
     let coop_penalties: Vec<f64> = power_candidates[our_power_idx]
         .1
         .iter()
-        .map(|cand| cooperation_penalty(cand, state, power, trust_scores))
+        .map(|cand| penalty(cand))
         .collect();
 
     let start_year = state.year;
     let mut nodes: u64 = 0;
 
-    // Warm-start: score each of our candidates once with a fixed opponent profile
-    {
+    // Warm-start: score each of our candidates once with a fixed opponent
+    // profile, unless the regrets were resumed from the session.
+    if prior_iterations == 0 {
         let opponent_profile: Vec<(Order, Power)> = power_candidates
             .iter()
             .enumerate()
@@ -2043,20 +3308,23 @@ pub fn regret_matching_search<W: Write>(
 
         let warm_results: Vec<(usize, f64)> = (0..our_k)
             .into_par_iter()
-            .map(|ci| {
-                let mut all_orders: Vec<(Order, Power)> = Vec::with_capacity(
-                    power_candidates[our_power_idx].1[ci].len() + opponent_profile.len(),
-                );
-                all_orders.extend_from_slice(&power_candidates[our_power_idx].1[ci]);
-                all_orders.extend_from_slice(&opponent_profile);
-
-                let mut tl_resolver = Resolver::new(64);
-                let (results, dislodged) = tl_resolver.resolve(&all_orders, state);
-                let mut scratch = state.clone();
-                apply_resolution(&mut scratch, &results, &dislodged);
-                let score = rm_evaluate_blended(power, &scratch, neural) - coop_penalties[ci];
-                (ci, f64::max(0.0, score))
-            })
+            .map_init(
+                || state.clone(),
+                |board, ci| {
+                    let mut all_orders: Vec<(Order, Power)> = Vec::with_capacity(
+                        power_candidates[our_power_idx].1[ci].len() + opponent_profile.len(),
+                    );
+                    all_orders.extend_from_slice(&power_candidates[our_power_idx].1[ci]);
+                    all_orders.extend_from_slice(&opponent_profile);
+
+                    let mut tl_resolver = Resolver::new(64);
+                    let (results, dislodged) = tl_resolver.resolve(&all_orders, state);
+                    let undo = board.apply(&resolution_delta(board, &results, &dislodged));
+                    let score = rm_evaluate_blended(power, board, neural) - coop_penalties[ci];
+                    board.undo(undo);
+                    (ci, f64::max(0.0, score))
+                },
+            )
             .collect();
 
         for (ci, score) in warm_results {
@@ -2078,7 +3346,28 @@ pub fn regret_matching_search<W: Write>(
         .collect();
     let mut sampled: Vec<usize> = vec![0; num_powers];
     let mut combined: Vec<(Order, Power)> = Vec::with_capacity(32);
-    let mut greedy_cache = GreedyOrderCache::new(GREEDY_CACHE_CAPACITY);
+    let builds = predict_opponent_builds(power, state, neural);
+    let table = Arc::new(TranspositionTable::new(
+        options.cache.greedy_entries,
+        options.cache.eval_entries,
+    ));
+    let mut greedy_cache = GreedyOrderCache::new(Arc::clone(&table), options.lookahead_supports)
+        .with_builds(builds.clone());
+    let mut future_cache = FutureCache::new(options.cache.future_entries);
+    let mut trace = options.introspection.then(|| RegretTrace::new(our_k));
+    let mut search_log = options.search_log.as_ref().and_then(|config| {
+        SearchLog::start(config, power, state, &power_candidates)
+            .map_err(|e| eprintln!("search log {}: {}", config.path.display(), e))
+            .ok()
+    });
+    let mut monitor = ConvergenceMonitor::new();
+    let mut converged = false;
+    // Sum and count of the values each of our candidates was scored at.
+    let mut our_values: Vec<(f64, u32)> = vec![(0.0, 0); our_k];
+
+    // Resolved profiles are applied to this board and undone after their
+    // lookahead, rather than each played on a fresh copy.
+    let mut board = state.clone();
 
     // Main RM+ loop (time-based with minimum iteration guarantee)
     let min_iters = if has_neural {
@@ -2091,8 +3380,13 @@ pub fn regret_matching_search<W: Write>(
         if stop.load(Ordering::Relaxed) {
             break;
         }
-        // After minimum iterations, check time budget
-        if iteration_count >= min_iters as u64 && Instant::now() >= rm_deadline {
+        // A replay runs the recorded iterations; otherwise, after the
+        // minimum iterations, check the time budget.
+        if let Some(blob) = options.replay {
+            if iteration_count >= blob.iterations {
+                break;
+            }
+        } else if iteration_count >= min_iters as u64 && Instant::now() >= rm_deadline {
             break;
         }
 
@@ -2129,70 +3423,120 @@ pub fn regret_matching_search<W: Write>(
             combined.extend_from_slice(&cands[sampled[pi]]);
         }
 
-        // Resolve and evaluate the sampled profile
-        let (results, dislodged) = resolver.resolve(&combined, state);
-        let mut scratch = state.clone();
-        apply_resolution(&mut scratch, &results, &dislodged);
-        let has_dislodged = scratch.dislodged.iter().any(|d| d.is_some());
-        advance_state(&mut scratch, has_dislodged);
-
-        // Lookahead: fast greedy simulation for post-resolution board state
-        let future = simulate_n_phases(
-            &scratch,
-            power,
-            &mut resolver,
-            LOOKAHEAD_DEPTH,
-            start_year,
-            &mut rng,
-            &mut greedy_cache,
-        );
-        let base_value =
-            rm_evaluate_blended(power, &future, neural) - coop_penalties[sampled[our_power_idx]];
-        nodes += 1;
-
-        // Counterfactual regret update for our power's alternatives (parallelized with rayon)
-        let cf_seed_base = iteration_count * 1000;
-        let cf_results: Vec<(usize, f64)> = (0..our_k)
-            .into_par_iter()
-            .filter(|&ci| ci != sampled[our_power_idx])
-            .map(|ci| {
-                let mut alt_orders: Vec<(Order, Power)> = Vec::with_capacity(32);
-                for (pi, (_, cands)) in power_candidates.iter().enumerate() {
-                    if pi == our_power_idx {
-                        alt_orders.extend_from_slice(&cands[ci]);
-                    } else {
-                        alt_orders.extend_from_slice(&cands[sampled[pi]]);
-                    }
-                }
-
-                let mut tl_resolver = Resolver::new(64);
-                let mut tl_rng = SmallRng::seed_from_u64(cf_seed_base + ci as u64);
-                let mut tl_cache = GreedyOrderCache::new(GREEDY_CACHE_CAPACITY);
-
-                let (alt_results, alt_dislodged) = tl_resolver.resolve(&alt_orders, state);
-                let mut alt_scratch = state.clone();
-                apply_resolution(&mut alt_scratch, &alt_results, &alt_dislodged);
-                let alt_has_dislodged = alt_scratch.dislodged.iter().any(|d| d.is_some());
-                advance_state(&mut alt_scratch, alt_has_dislodged);
-
-                let alt_future = simulate_n_phases(
-                    &alt_scratch,
+        // Resolve and evaluate the sampled profile, unless this pairing has
+        // been seen before.
+        let opp_hash = opponent_profile_hash(&sampled, our_power_idx);
+        let our_ci = sampled[our_power_idx];
+        let base_future = match future_cache.get((our_ci, opp_hash, LOOKAHEAD_DEPTH)) {
+            Some(v) => v,
+            None => {
+                let (results, dislodged) = resolver.resolve(&combined, state);
+                let resolved = board.apply(&resolution_delta(&board, &results, &dislodged));
+                let has_dislodged = board.dislodged.iter().any(|d| d.is_some());
+                let advanced = board.apply(&advance_delta(&board, has_dislodged));
+
+                // Lookahead: fast greedy simulation for post-resolution board state
+                let future = simulate_n_phases(
+                    &board,
                     power,
-                    &mut tl_resolver,
-                    1, // Reduced depth for counterfactuals (relative regret only)
+                    &mut resolver,
+                    LOOKAHEAD_DEPTH,
                     start_year,
-                    &mut tl_rng,
-                    &mut tl_cache,
+                    &mut rng,
+                    &mut greedy_cache,
                 );
-                let cf_value = rm_evaluate_blended(power, &alt_future, neural) - coop_penalties[ci];
-                (ci, cf_value)
-            })
+                board.undo(advanced);
+                board.undo(resolved);
+                let v = table.evaluate(power, &future, || {
+                    rm_evaluate_blended(power, &future, neural)
+                });
+                future_cache.insert((our_ci, opp_hash, LOOKAHEAD_DEPTH), v);
+                nodes += 1;
+                v
+            }
+        };
+        let base_value = base_future - coop_penalties[our_ci];
+        our_values[our_ci].0 += base_value;
+        our_values[our_ci].1 += 1;
+
+        // Counterfactual regret update for our power's alternatives. Memoized
+        // values are reused; the rest are evaluated in parallel with rayon.
+        let mut cf_results: Vec<(usize, f64)> = Vec::with_capacity(our_k);
+        let mut cf_pending: Vec<usize> = Vec::with_capacity(our_k);
+        for ci in (0..our_k).filter(|&ci| ci != our_ci) {
+            match future_cache.get((ci, opp_hash, CF_LOOKAHEAD_DEPTH)) {
+                Some(v) => cf_results.push((ci, v - coop_penalties[ci])),
+                None => cf_pending.push(ci),
+            }
+        }
+        let cf_seed_base = iteration_count * 1000;
+        let cf_computed: Vec<(usize, f64)> = cf_pending
+            .par_iter()
+            .map_init(
+                || state.clone(),
+                |alt_board, &ci| {
+                    let mut alt_orders: Vec<(Order, Power)> = Vec::with_capacity(32);
+                    for (pi, (_, cands)) in power_candidates.iter().enumerate() {
+                        if pi == our_power_idx {
+                            alt_orders.extend_from_slice(&cands[ci]);
+                        } else {
+                            alt_orders.extend_from_slice(&cands[sampled[pi]]);
+                        }
+                    }
+
+                    let mut tl_resolver = Resolver::new(64);
+                    let mut tl_rng = SmallRng::seed_from_u64(cf_seed_base + ci as u64);
+                    let mut tl_cache =
+                        GreedyOrderCache::new(Arc::clone(&table), options.lookahead_supports)
+                            .with_builds(builds.clone());
+
+                    let (alt_results, alt_dislodged) = tl_resolver.resolve(&alt_orders, state);
+                    let resolved =
+                        alt_board.apply(&resolution_delta(alt_board, &alt_results, &alt_dislodged));
+                    let alt_has_dislodged = alt_board.dislodged.iter().any(|d| d.is_some());
+                    let advanced = alt_board.apply(&advance_delta(alt_board, alt_has_dislodged));
+
+                    let alt_future = simulate_n_phases(
+                        alt_board,
+                        power,
+                        &mut tl_resolver,
+                        CF_LOOKAHEAD_DEPTH,
+                        start_year,
+                        &mut tl_rng,
+                        &mut tl_cache,
+                    );
+                    alt_board.undo(advanced);
+                    alt_board.undo(resolved);
+                    let v = table.evaluate(power, &alt_future, || {
+                        rm_evaluate_blended(power, &alt_future, neural)
+                    });
+                    (ci, v)
+                },
+            )
             .collect();
+        for &(ci, v) in &cf_computed {
+            future_cache.insert((ci, opp_hash, CF_LOOKAHEAD_DEPTH), v);
+            cf_results.push((ci, v - coop_penalties[ci]));
+            nodes += 1;
+        }
 
         for (ci, cf_value) in &cf_results {
+            our_values[*ci].0 += cf_value;
+            our_values[*ci].1 += 1;
             cum_regrets[our_power_idx][*ci] =
                 f64::max(0.0, cum_regrets[our_power_idx][*ci] + cf_value - base_value);
-            nodes += 1;
+            if let Some(trace) = trace.as_mut() {
+                trace.record(*ci, cf_value - base_value, &sampled);
+            }
+        }
+        if let Some(log) = search_log.as_mut().filter(|l| l.wants(iteration_count)) {
+            log.iteration(
+                iteration_count,
+                &sampled,
+                base_value,
+                &cf_results,
+                &cum_regrets[our_power_idx],
+            );
         }
 
         // Accumulate weighted strategy for final selection
@@ -2201,21 +3545,95 @@ pub fn regret_matching_search<W: Write>(
                 total_weights[pi][j] += w;
             }
         }
+        if let Some(currbest) = &options.currbest {
+            let leader = total_weights[our_power_idx]
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map_or(0, |(i, _)| i);
+            currbest.offer(leader, || {
+                power_candidates[our_power_idx].1[leader]
+                    .iter()
+                    .map(|(o, _)| *o)
+                    .collect()
+            });
+        }
 
         iteration_count += 1;
+
+        if options.early_exit
+            && iteration_count >= min_iters as u64
+            && iteration_count.is_multiple_of(CONVERGE_CHECK_EVERY)
+            && monitor.observe(&total_weights[our_power_idx], &cum_regrets[our_power_idx])
+        {
+            converged = true;
+            break;
+        }
     }
 
     // Phase 3: Best-response extraction (remaining budget)
     // Select by best average weight for our power
-    let our_weights = &total_weights[our_power_idx];
-    let best_idx = our_weights
+    let mut best_idx = total_weights[our_power_idx]
         .iter()
         .enumerate()
         .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
         .map(|(i, _)| i)
         .unwrap_or(0);
 
-    let best_orders: Vec<Order> = power_candidates[our_power_idx].1[best_idx]
+    // Phase 3b: with three or four powers left, prefer the set with the best
+    // worst case against any coalition of the others.
+    if options.coalition_proof
+        && coalition::applies(power_candidates.len())
+        && !stop.load(Ordering::Relaxed)
+    {
+        let mut combined = Vec::with_capacity(32);
+        let choice = coalition_proof(
+            &power_candidates,
+            &total_weights,
+            our_power_idx,
+            start + movetime,
+            stop,
+            |ours, profile| {
+                combined.clear();
+                combined.extend_from_slice(ours);
+                combined.extend_from_slice(profile);
+                let after = outcome(&mut resolver, &combined, state);
+                table.evaluate(power, &after, || rm_evaluate_blended(power, &after, neural))
+                    - penalty(ours)
+            },
+        );
+        if let Some(choice) = choice {
+            let against = if choice.coalition.is_empty() {
+                "none".to_string()
+            } else {
+                let names: Vec<&str> = choice.coalition.iter().map(|p| p.name()).collect();
+                names.join(",")
+            };
+            let _ = writeln!(
+                out,
+                "info string coalition worst {:.3} equilibrium {:.3} against {} rank {} profiles {}",
+                choice.worst,
+                choice.equilibrium,
+                against,
+                choice.rank + 1,
+                choice.evaluated
+            );
+            best_idx = choice.index;
+        }
+    }
+    let order_sets = ranked_order_sets(
+        &power_candidates[our_power_idx].1,
+        &normalized(&total_weights[our_power_idx]),
+        &our_values,
+        best_idx,
+        options.multi_order_sets,
+    );
+    let our_weights = &total_weights[our_power_idx];
+    if let Some(log) = search_log {
+        log.finish(iteration_count, converged, best_idx, our_weights);
+    }
+
+    let mut best_orders: Vec<Order> = power_candidates[our_power_idx].1[best_idx]
         .iter()
         .map(|(o, _)| *o)
         .collect();
@@ -2223,26 +3641,280 @@ pub fn regret_matching_search<W: Write>(
     let best_score = rm_evaluate_blended(power, state, neural) as f32;
 
     let has_value_net = neural.map_or(false, |n| n.has_value());
-    let elapsed_ms = start.elapsed().as_millis() as u64;
+    let elapsed = start.elapsed();
+    let _ = writeln!(
+        out,
+        "info depth {} nodes {} nps {} score {} time {} iterations {} value_net {}",
+        LOOKAHEAD_DEPTH,
+        nodes,
+        per_second(nodes, elapsed),
+        best_score as i32,
+        elapsed.as_millis() as u64,
+        iteration_count,
+        has_value_net
+    );
     let _ = writeln!(
         out,
-        "info depth {} nodes {} score {} time {} iterations {} value_net {}",
-        LOOKAHEAD_DEPTH, nodes, best_score as i32, elapsed_ms, iteration_count, has_value_net
+        "info string {}",
+        WorkCounts::now().since(work_start).rates_line(elapsed)
     );
+    let order_stats = table.order_stats();
+    let eval_stats = table.eval_stats();
+    let cache = CacheStats {
+        lookups: future_cache.lookups,
+        hits: future_cache.hits,
+        evictions: future_cache.evictions,
+        greedy_entries: order_stats.entries,
+        greedy_evictions: order_stats.evictions,
+        eval_entries: eval_stats.entries,
+        eval_lookups: eval_stats.lookups,
+        eval_hits: eval_stats.hits,
+        budget_mb: options.cache.megabytes,
+    };
+
+    let avg_strategies: Vec<Vec<f64>> = total_weights.iter().map(|w| normalized(w)).collect();
+    if options.candidate_dump > 0 {
+        write_candidate_dump(
+            out,
+            state.map(),
+            &power_candidates[our_power_idx].1,
+            &avg_strategies[our_power_idx],
+            options.candidate_dump,
+        );
+    }
+    let mut confidence = order_confidence(
+        &power_candidates[our_power_idx].1,
+        &avg_strategies[our_power_idx],
+        best_idx,
+    );
+    let opponents = opponent_summaries(&power_candidates, &avg_strategies, our_power_idx);
+
+    if options.trajectory_years > 0 && !stop.load(Ordering::Relaxed) {
+        let trajectory = project_sc_trajectory(
+            state,
+            &power_candidates,
+            &avg_strategies,
+            options.trajectory_years,
+            &mut resolver,
+            &mut rng,
+            &mut greedy_cache,
+        );
+        let _ = writeln!(out, "info trajectory {}", trajectory.to_json());
+    }
+
+    let mut whynot =
+        trace.map(|t| t.report(&power_candidates, our_power_idx, our_weights, best_idx));
+
+    // Phase 4: local search from the chosen set. An improved set joins our
+    // candidates with no weight, so a resumed search can pick it up; for
+    // order confidence it stands in for the chosen set.
+    if options.local_search && !stop.load(Ordering::Relaxed) {
+        let profiles = equilibrium_profiles(
+            &power_candidates,
+            &total_weights,
+            our_power_idx,
+            REFINE_PROFILES,
+        );
+        let refined = refine(
+            power,
+            state,
+            &power_candidates[our_power_idx].1[best_idx],
+            &profiles,
+            &options.constraints,
+            start + movetime,
+            stop,
+            |orders, after| {
+                table.evaluate(power, after, || rm_evaluate_blended(power, after, neural))
+                    - penalty(orders)
+            },
+        );
+        if refined.accepted > 0 {
+            let _ = writeln!(
+                out,
+                "info string refine changes {} gain {:.3} sets {}",
+                refined.accepted,
+                refined.after - refined.before,
+                refined.evaluated
+            );
+            best_orders = refined.orders.iter().map(|(o, _)| *o).collect();
+            if let Some(report) = whynot.as_mut() {
+                report.chosen = best_orders.clone();
+            }
+            let ours = &mut power_candidates[our_power_idx].1;
+            let refined_idx = match ours.iter().position(|c| *c == refined.orders) {
+                Some(i) => i,
+                None => {
+                    ours.push(refined.orders);
+                    cum_regrets[our_power_idx].push(0.0);
+                    total_weights[our_power_idx].push(0.0);
+                    ours.len() - 1
+                }
+            };
+            let mut strategy = avg_strategies[our_power_idx].clone();
+            strategy.resize(ours.len(), 0.0);
+            strategy[refined_idx] += strategy[best_idx];
+            strategy[best_idx] = 0.0;
+            confidence = order_confidence(ours, &strategy, refined_idx);
+        }
+    }
+
+    let blob = ReplayBlob {
+        seed,
+        options: options_hash(options, strength, trust_scores, neural.is_some()),
+        position: state.zobrist(),
+        iterations: iteration_count,
+        powers: power_candidates.len(),
+        pools,
+    };
+    let _ = writeln!(out, "info string replay {}", blob);
+
+    session.last = Some(SessionPool {
+        state: state.clone(),
+        power,
+        candidates: power_candidates,
+        cum_regrets,
+        total_weights,
+        our_idx: our_power_idx,
+        iterations: prior_iterations + iteration_count,
+    });
 
     SearchResult {
         orders: best_orders,
         score: best_score,
         nodes,
+        whynot,
+        confidence,
+        opponents,
+        order_sets,
+        iterations: iteration_count,
+        elapsed: start.elapsed(),
+        cache: Some(cache),
+        converged,
+    }
+}
+
+/// Writes our `limit` heaviest candidates, best first, as
+/// `info string candidate <rank> weight <w> pv <orders>` lines.
+fn write_candidate_dump<W: Write>(
+    out: &mut W,
+    map: &GameMap,
+    candidates: &[Vec<(Order, Power)>],
+    strategy: &[f64],
+    limit: usize,
+) {
+    let mut ranked: Vec<usize> = (0..candidates.len()).collect();
+    ranked.sort_by(|&a, &b| {
+        strategy[b]
+            .partial_cmp(&strategy[a])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for (rank, &ci) in ranked.iter().take(limit).enumerate() {
+        let orders: Vec<Order> = candidates[ci].iter().map(|(o, _)| *o).collect();
+        let _ = writeln!(
+            out,
+            "info string candidate {} weight {:.3} pv {}",
+            rank + 1,
+            strategy[ci],
+            format_orders_on(map, &orders)
+        );
     }
 }
 
+/// The chosen candidate and then our heaviest others, `limit` in all,
+/// with their average strategy weights and mean values.
+fn ranked_order_sets(
+    candidates: &[Vec<(Order, Power)>],
+    strategy: &[f64],
+    values: &[(f64, u32)],
+    best_idx: usize,
+    limit: usize,
+) -> Vec<RankedOrderSet> {
+    let mut ranked: Vec<usize> = (0..candidates.len()).filter(|&ci| ci != best_idx).collect();
+    ranked.sort_by(|&a, &b| {
+        strategy[b]
+            .partial_cmp(&strategy[a])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    std::iter::once(best_idx)
+        .chain(ranked)
+        .take(limit)
+        .map(|ci| RankedOrderSet {
+            orders: candidates[ci].iter().map(|(o, _)| *o).collect(),
+            weight: strategy[ci],
+            score: values
+                .get(ci)
+                .filter(|(_, n)| *n > 0)
+                .map(|(sum, n)| sum / *n as f64),
+        })
+        .collect()
+}
+
+/// Confidence in each order of the chosen candidate: the total average
+/// strategy weight on candidates that give the same order to that unit.
+pub(crate) fn order_confidence(
+    candidates: &[Vec<(Order, Power)>],
+    strategy: &[f64],
+    best_idx: usize,
+) -> Vec<f32> {
+    candidates[best_idx]
+        .iter()
+        .map(|(order, _)| {
+            candidates
+                .iter()
+                .zip(strategy)
+                .filter(|(cand, _)| cand.iter().any(|(o, _)| o == order))
+                .map(|(_, &w)| w)
+                .sum::<f64>()
+                .min(1.0) as f32
+        })
+        .collect()
+}
+
+/// Summarizes each opponent's average strategy by its heaviest candidate.
+pub(crate) fn opponent_summaries(
+    power_candidates: &[PowerCandidates],
+    strategies: &[Vec<f64>],
+    our_power_idx: usize,
+) -> Vec<OpponentSummary> {
+    power_candidates
+        .iter()
+        .zip(strategies)
+        .enumerate()
+        .filter(|(pi, _)| *pi != our_power_idx)
+        .map(|(_, ((p, cands), strat))| {
+            let (best, prob) =
+                strat
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .fold(
+                        (0, f64::NEG_INFINITY),
+                        |acc, (i, w)| {
+                            if w > acc.1 {
+                                (i, w)
+                            } else {
+                                acc
+                            }
+                        },
+                    );
+            OpponentSummary {
+                power: *p,
+                orders: cands[best].iter().map(|(o, _)| *o).collect(),
+                probability: prob.max(0.0) as f32,
+                confidence: order_confidence(cands, strat, best),
+                candidates: cands.len(),
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::province::Coast;
+    use crate::board::province::{Coast, ALL_PROVINCES, PROVINCE_COUNT};
     use crate::board::state::Phase;
     use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
 
     const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
 
@@ -2286,48 +3958,334 @@ mod tests {
     }
 
     #[test]
-    fn rm_search_respects_time_budget() {
-        let state = initial_state();
+    fn rm_search_respects_time_budget() {
+        let state = initial_state();
+        let mut out = Vec::new();
+        let start = Instant::now();
+        let _result = regret_matching_search(
+            Power::Austria,
+            &state,
+            Duration::from_millis(500),
+            &mut out,
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+        );
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed < Duration::from_millis(2000),
+            "Search took too long: {:?}",
+            elapsed
+        );
+    }
+
+    #[test]
+    fn rm_search_emits_info_lines() {
+        let state = initial_state();
+        let mut out = Vec::new();
+        let _result = regret_matching_search(
+            Power::Austria,
+            &state,
+            Duration::from_millis(1000),
+            &mut out,
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+        );
+        let output = String::from_utf8(out).unwrap();
+        assert!(
+            output.contains("info depth"),
+            "Should emit info lines, got: {}",
+            output
+        );
+        let depth_line = output
+            .lines()
+            .find(|l| l.starts_with("info depth"))
+            .unwrap();
+        assert!(depth_line.contains(" nps "), "{}", depth_line);
+        let rates = output
+            .lines()
+            .find(|l| l.starts_with("info string nps resolves "))
+            .expect("throughput line");
+        let fields: Vec<&str> = rates.split_whitespace().collect();
+        let resolves: u64 = fields[4].parse().unwrap();
+        let evals: u64 = fields[6].parse().unwrap();
+        assert!(resolves > 0 && evals > 0, "{}", rates);
+    }
+
+    #[test]
+    fn rm_search_introspection_reports_rejected_candidates() {
+        let state = initial_state();
+        let mut out = Vec::new();
+        let options = RmOptions {
+            introspection: true,
+            ..RmOptions::default()
+        };
+        let result = regret_matching_search_with_options(
+            Power::Austria,
+            &state,
+            Duration::from_millis(300),
+            &mut out,
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+            &options,
+        );
+        let report = result.whynot.expect("introspection report");
+        assert_eq!(report.power, Power::Austria);
+        assert_eq!(report.chosen, result.orders);
+        assert!(!report.rejected.is_empty() && report.rejected.len() <= 3);
+
+        let plain = regret_matching_search(
+            Power::Austria,
+            &state,
+            Duration::from_millis(100),
+            &mut Vec::new(),
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+        );
+        assert!(plain.whynot.is_none());
+    }
+
+    #[test]
+    fn rm_search_guards_against_coalitions_late_in_the_game() {
+        // Austria, Russia and Turkey are left.
+        let state = parse_dfen(
+            "1908fm/Aabud,Aavie,Aaser,Rawar,Ragal,Rfsev,Tabul,Tfbla,Taarm/Abud,Avie,Atri,Aser,Rmos,Rwar,Rsev,Rstp,Tank,Tcon,Tsmy,Tbul/-",
+        )
+        .unwrap();
+        let run = |coalition_proof: bool| {
+            let mut out = Vec::new();
+            let options = RmOptions {
+                coalition_proof,
+                trajectory_years: 0,
+                ..RmOptions::default()
+            };
+            let result = regret_matching_search_with_options(
+                Power::Austria,
+                &state,
+                Duration::from_millis(300),
+                &mut out,
+                None,
+                100,
+                None,
+                &AtomicBool::new(false),
+                &options,
+            );
+            (result, String::from_utf8(out).unwrap())
+        };
+
+        let (result, output) = run(true);
+        let line = output
+            .lines()
+            .find(|l| l.starts_with("info string coalition "))
+            .expect("coalition line");
+        assert!(line.contains(" worst ") && line.contains(" against "));
+        assert_eq!(result.orders.len(), 3);
+
+        let (_, plain) = run(false);
+        assert!(!plain.contains("info string coalition "));
+    }
+
+    #[test]
+    fn rm_search_emits_sc_trajectory() {
+        let state = initial_state();
+        let mut out = Vec::new();
+        let options = RmOptions {
+            trajectory_years: 3,
+            ..RmOptions::default()
+        };
+        regret_matching_search_with_options(
+            Power::Austria,
+            &state,
+            Duration::from_millis(300),
+            &mut out,
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+            &options,
+        );
+        let output = String::from_utf8(out).unwrap();
+        let line = output
+            .lines()
+            .find(|l| l.starts_with("info trajectory "))
+            .expect("trajectory line");
+        let v: serde_json::Value =
+            serde_json::from_str(line.trim_start_matches("info trajectory ")).unwrap();
+        assert_eq!(v["years"], serde_json::json!([1901, 1902, 1903]));
+        assert_eq!(v["rollouts"], TRAJECTORY_ROLLOUTS);
+        let total: f64 = v["powers"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|s| s[0].as_f64().unwrap())
+            .sum();
+        assert!(total > 22.0 && total <= 34.0);
+    }
+
+    #[test]
+    fn future_cache_counts_hits_and_lookups() {
+        let mut cache = FutureCache::new(2);
+        assert_eq!(cache.get((0, 1, 2)), None);
+        cache.insert((0, 1, 2), 3.5);
+        assert_eq!(cache.get((0, 1, 2)), Some(3.5));
+        assert_eq!(cache.get((0, 1, 1)), None);
+        assert_eq!((cache.lookups, cache.hits), (3, 1));
+        let stats = CacheStats {
+            lookups: cache.lookups,
+            hits: cache.hits,
+            ..CacheStats::default()
+        };
+        assert!((stats.dedup_rate() - 1.0 / 3.0).abs() < 1e-9);
+
+        // Capacity overflow clears the memo.
+        cache.insert((1, 1, 2), 1.0);
+        cache.insert((2, 1, 2), 2.0);
+        assert_eq!(cache.map.len(), 1);
+        assert_eq!(cache.evictions, 2);
+    }
+
+    #[test]
+    fn opponent_profile_hash_ignores_our_slot() {
+        assert_eq!(
+            opponent_profile_hash(&[3, 1, 4], 1),
+            opponent_profile_hash(&[3, 9, 4], 1)
+        );
+        assert_ne!(
+            opponent_profile_hash(&[3, 1, 4], 1),
+            opponent_profile_hash(&[3, 1, 5], 1)
+        );
+    }
+
+    #[test]
+    fn rm_search_reports_future_cache_dedup() {
+        let state = initial_state();
+        let mut out = Vec::new();
+        let result = regret_matching_search(
+            Power::Austria,
+            &state,
+            Duration::from_millis(300),
+            &mut out,
+            None,
+            100,
+            None,
+            &AtomicBool::new(false),
+        );
+        let cache = result.cache.expect("RM+ reports cache stats");
+        assert!(cache.lookups > 0);
+        assert!(cache.hits <= cache.lookups);
+    }
+
+    #[test]
+    fn session_resumes_the_same_position() {
+        let state = parse_dfen("1905fm/Aabud,Aavie,Tabul/Abud,Avie,Nser,Nrum,Tbul/-").unwrap();
+        // Refinement may add a set to the pool when one run has time left
+        // for it and the other does not.
+        let options = RmOptions {
+            trajectory_years: 0,
+            early_exit: false,
+            local_search: false,
+            ..RmOptions::default()
+        };
+        let mut session = SearchSession::new();
+        let run = |session: &mut SearchSession| {
+            regret_matching_search_in_session(
+                Power::Austria,
+                &state,
+                Duration::from_millis(200),
+                &mut Vec::new(),
+                None,
+                100,
+                None,
+                &AtomicBool::new(false),
+                &options,
+                session,
+            )
+        };
+
+        let first = run(&mut session);
+        assert!(session.is_warm_for(Power::Austria, &state));
+        let pool = session.last.as_ref().unwrap().candidates.clone();
+        let after_first = session.iterations();
+        assert_eq!(after_first, first.iterations);
+
+        let second = run(&mut session);
+        let last = session.last.as_ref().unwrap();
+        assert_eq!(last.candidates, pool, "the pool is reused, not regenerated");
+        assert_eq!(session.iterations(), after_first + second.iterations);
+        assert_eq!(session.top_order_sets(1)[0].0, second.orders);
+    }
+
+    #[test]
+    fn convergence_monitor_needs_a_calm_streak() {
+        let mut monitor = ConvergenceMonitor::new();
+        let weights = [3.0, 1.0, 0.0];
+        let regrets = [2.0, 2.0, 0.0];
+        for _ in 0..CONVERGE_STREAK {
+            assert!(!monitor.observe(&weights, &regrets));
+        }
+        assert!(monitor.observe(&weights, &regrets));
+
+        // A shift in regrets resets the streak.
+        assert!(!monitor.observe(&weights, &[0.0, 2.0, 2.0]));
+        assert!(!monitor.observe(&weights, &[0.0, 2.0, 2.0]));
+    }
+
+    #[test]
+    fn rm_search_exits_early_once_converged() {
+        let state = parse_dfen("1905fm/Aabud,Aavie,Tabul/Abud,Avie,Nser,Nrum,Tbul/-").unwrap();
+        let movetime = Duration::from_millis(3000);
         let mut out = Vec::new();
-        let start = Instant::now();
-        let _result = regret_matching_search(
+        let result = regret_matching_search_with_options(
             Power::Austria,
             &state,
-            Duration::from_millis(500),
+            movetime,
             &mut out,
             None,
             100,
             None,
             &AtomicBool::new(false),
+            &RmOptions {
+                trajectory_years: 0,
+                ..RmOptions::default()
+            },
         );
-        let elapsed = start.elapsed();
-        assert!(
-            elapsed < Duration::from_millis(2000),
-            "Search took too long: {:?}",
-            elapsed
-        );
+        assert!(result.converged);
+        assert!(result.iterations >= MIN_RM_ITERATIONS as u64);
+        assert!(result.unused(movetime) > Duration::ZERO);
     }
 
     #[test]
-    fn rm_search_emits_info_lines() {
+    fn rm_search_reports_confidence_and_opponents() {
         let state = initial_state();
         let mut out = Vec::new();
-        let _result = regret_matching_search(
+        let result = regret_matching_search(
             Power::Austria,
             &state,
-            Duration::from_millis(1000),
+            Duration::from_millis(300),
             &mut out,
             None,
             100,
             None,
             &AtomicBool::new(false),
         );
-        let output = String::from_utf8(out).unwrap();
-        assert!(
-            output.contains("info depth"),
-            "Should emit info lines, got: {}",
-            output
-        );
+        assert_eq!(result.confidence.len(), result.orders.len());
+        assert!(result.confidence.iter().all(|&c| c > 0.0 && c <= 1.0));
+        assert_eq!(result.opponents.len(), 6);
+        assert!(result.opponents.iter().all(|o| o.power != Power::Austria
+            && !o.orders.is_empty()
+            && o.probability > 0.0
+            && o.probability <= 1.0
+            && o.confidence.len() == o.orders.len()
+            && o.confidence.iter().all(|&c| c >= o.probability - 1e-6)));
+        assert!(result.iterations >= MIN_RM_ITERATIONS as u64);
+        assert!(result.elapsed > Duration::ZERO);
     }
 
     #[test]
@@ -2352,7 +4310,7 @@ mod tests {
         match result.orders[0] {
             Order::Move { dest, .. } => {
                 assert!(
-                    dest.province.is_supply_center(),
+                    state.map().is_supply_center(dest.province),
                     "Should move to an SC, got {:?}",
                     dest.province
                 );
@@ -2391,6 +4349,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn candidate_pool_is_deterministic_per_seed() {
+        let state = initial_state();
+        let options = RmOptions::default();
+        let a = candidate_pool(Power::Austria, &state, 7, &options);
+        let b = candidate_pool(Power::Austria, &state, 7, &options);
+        assert!(!a.is_empty());
+        assert_eq!(a, b);
+        assert!(a
+            .iter()
+            .all(|c| c.len() == 3 && c.iter().all(|&(_, p)| p == Power::Austria)));
+    }
+
     #[test]
     fn cooperation_penalty_none_for_single_target() {
         let state = BoardState::empty(1901, Season::Spring, Phase::Movement);
@@ -2418,6 +4389,7 @@ mod tests {
                         location: Location::new(Province::Bud),
                     },
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -2428,6 +4400,7 @@ mod tests {
                         location: Location::new(Province::Tyr),
                     },
                     dest: Location::new(Province::Ven),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -2445,7 +4418,7 @@ mod tests {
     fn generate_candidates_produces_diverse_sets() {
         let state = initial_state();
         let mut rng = SmallRng::seed_from_u64(42);
-        let cands = generate_candidates(Power::Austria, &state, 8, &mut rng);
+        let cands = generate_candidates(Power::Austria, &state, 8, 0.0, &mut rng);
         assert!(
             cands.len() >= 2,
             "Should generate at least 2 candidates, got {}",
@@ -2558,7 +4531,7 @@ mod tests {
         state.set_sc_owner(Province::Rum, Some(Power::Turkey));
 
         let mut rng = SmallRng::seed_from_u64(42);
-        let cands = generate_candidates(Power::Austria, &state, NUM_CANDIDATES, &mut rng);
+        let cands = generate_candidates(Power::Austria, &state, NUM_CANDIDATES, 0.0, &mut rng);
 
         let has_support_move = cands.iter().any(|cand| {
             cand.iter()
@@ -2582,7 +4555,7 @@ mod tests {
         state.set_sc_owner(Province::Rum, Some(Power::Turkey));
 
         let mut rng = SmallRng::seed_from_u64(42);
-        let cands = generate_candidates(Power::Austria, &state, NUM_CANDIDATES, &mut rng);
+        let cands = generate_candidates(Power::Austria, &state, NUM_CANDIDATES, 0.0, &mut rng);
 
         let has_coordinated_pair = cands.iter().any(|cand| {
             // Find a support-move order and check if the matching move exists.
@@ -2607,13 +4580,114 @@ mod tests {
     }
 
     #[test]
-    fn cooperation_penalty_reduced() {
-        // Verify the cooperation penalty is now lower (1.0 per extra power instead of 2.0).
-        let mut state = BoardState::empty(1903, Season::Spring, Phase::Movement);
+    fn coordinated_budget_scales_with_units_and_threats() {
+        let initial = initial_state();
+        assert_eq!(
+            coordinated_budget(Power::Austria, &initial),
+            MIN_COORDINATED
+        );
+
+        // Twelve Russian units across the east, with Austria next to war and sev.
+        let mut state = BoardState::empty(1908, Season::Spring, Phase::Movement);
+        for prov in [
+            Province::Stp,
+            Province::Mos,
+            Province::War,
+            Province::Sev,
+            Province::Ukr,
+            Province::Lvn,
+            Province::Fin,
+            Province::Swe,
+            Province::Nwy,
+            Province::Rum,
+            Province::Pru,
+            Province::Sil,
+        ] {
+            state.place_unit(prov, Power::Russia, UnitType::Army, Coast::None);
+        }
+        for prov in [Province::Mos, Province::War, Province::Sev, Province::Rum] {
+            state.set_sc_owner(prov, Some(Power::Russia));
+        }
+        state.place_unit(Province::Gal, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Arm, Power::Turkey, UnitType::Army, Coast::None);
+        // 12 units + war, sev, rum threatened.
+        assert_eq!(coordinated_budget(Power::Russia, &state), 15);
+    }
+
+    /// Turkey in Ser and Italy in Ven, each owning that SC.
+    fn three_power_contact_state(year: u16) -> BoardState {
+        let mut state = BoardState::empty(year, Season::Spring, Phase::Movement);
         state.place_unit(Province::Ser, Power::Turkey, UnitType::Army, Coast::None);
         state.set_sc_owner(Province::Ser, Some(Power::Turkey));
         state.place_unit(Province::Ven, Power::Italy, UnitType::Army, Coast::None);
         state.set_sc_owner(Province::Ven, Some(Power::Italy));
+        state
+    }
+
+    /// Austria attacking both Ser (Turkey) and Ven (Italy).
+    fn two_front_orders() -> Vec<(Order, Power)> {
+        vec![
+            (army_move(Province::Bud, Province::Ser), Power::Austria),
+            (army_move(Province::Tyr, Province::Ven), Power::Austria),
+        ]
+    }
+
+    #[test]
+    fn cooperation_scale_relaxes_with_year() {
+        let mut early = three_power_contact_state(1903);
+        early.set_sc_owner(Province::Vie, Some(Power::Austria));
+        let mut late = early.clone();
+        late.set_turn(1908, late.season, late.phase);
+        let early_scale = cooperation_scale(&early, Power::Austria);
+        let late_scale = cooperation_scale(&late, Power::Austria);
+        assert!((late_scale / early_scale - 0.6).abs() < 1e-9);
+        late.set_turn(1930, late.season, late.phase);
+        assert!((cooperation_scale(&late, Power::Austria) / early_scale - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cooperation_scale_shrinks_with_survivors() {
+        let three = three_power_contact_state(1903);
+        let mut five = three.clone();
+        five.place_unit(Province::Lon, Power::England, UnitType::Army, Coast::None);
+        five.place_unit(Province::Par, Power::France, UnitType::Army, Coast::None);
+        let orders = two_front_orders();
+        assert!(
+            cooperation_penalty(&orders, &three, Power::Austria, None)
+                < cooperation_penalty(&orders, &five, Power::Austria, None)
+        );
+    }
+
+    #[test]
+    fn cooperation_scale_penalizes_weaker_power_more() {
+        let mut weak = three_power_contact_state(1903);
+        weak.set_sc_owner(Province::Vie, Some(Power::Austria));
+        weak.set_sc_owner(Province::Bul, Some(Power::Turkey));
+        weak.set_sc_owner(Province::Gre, Some(Power::Turkey));
+        let mut strong = weak.clone();
+        for prov in [Province::Bud, Province::Tri, Province::Rum, Province::Alb] {
+            strong.set_sc_owner(prov, Some(Power::Austria));
+        }
+        assert!(
+            cooperation_scale(&weak, Power::Austria) > cooperation_scale(&strong, Power::Austria)
+        );
+    }
+
+    #[test]
+    fn cooperation_penalty_reduced() {
+        // Verify the cooperation penalty is now lower (1.0 per extra power instead of 2.0).
+        // Full seven-power board with one SC each, so the dynamic scale is 1.0.
+        let mut state = three_power_contact_state(1903);
+        for (prov, p) in [
+            (Province::Bud, Power::Austria),
+            (Province::Lon, Power::England),
+            (Province::Par, Power::France),
+            (Province::Ber, Power::Germany),
+            (Province::Mos, Power::Russia),
+        ] {
+            state.place_unit(prov, p, UnitType::Army, Coast::None);
+            state.set_sc_owner(prov, Some(p));
+        }
 
         use crate::board::order::{Location, OrderUnit};
         let orders = vec![
@@ -2624,6 +4698,7 @@ mod tests {
                         location: Location::new(Province::Bud),
                     },
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -2634,6 +4709,7 @@ mod tests {
                         location: Location::new(Province::Tyr),
                     },
                     dest: Location::new(Province::Ven),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -2726,13 +4802,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn coordination_keeps_convoy_supports_and_drops_holds_for_movers() {
+        let state = parse_dfen("1901sm/Eayor,Efnth,Efedi,Efnrg,Eawal/Elon/-").unwrap();
+        let per_unit = top_k_per_unit(Power::England, &state, 5, 0.0);
+        let unit_provinces: Vec<Province> = per_unit
+            .iter()
+            .filter_map(|cands| cands.first().and_then(|so| order_unit_province(&so.order)))
+            .collect();
+        let orders = |list: &[&str]| -> Vec<(Order, Power)> {
+            list.iter()
+                .map(|s| (parse_order(s).unwrap(), Power::England))
+                .collect()
+        };
+
+        let mut cand = orders(&[
+            "A yor - nwy via convoy",
+            "F nth C A yor - nwy",
+            "F edi S F nth H",
+            "F nrg S A yor - nwy",
+            "A wal S A yor H",
+        ]);
+        coordinate_candidate_supports(&mut cand, &per_unit, &unit_provinces, Power::England);
+
+        let kept = orders(&[
+            "A yor - nwy via convoy",
+            "F nth C A yor - nwy",
+            "F edi S F nth H",
+            "F nrg S A yor - nwy",
+        ]);
+        assert_eq!(&cand[..4], &kept[..], "convoy-backed orders must survive");
+        assert!(
+            !matches!(cand[4].0, Order::SupportHold { .. }),
+            "support-hold for a moving unit is phantom, got {:?}",
+            cand[4].0
+        );
+    }
+
     #[test]
     fn candidates_have_no_phantom_support_moves() {
         // Verify that support-move orders in candidates match the supported unit's
         // actual order in the same candidate set (no phantom supports).
         let state = initial_state();
         let mut rng = SmallRng::seed_from_u64(42);
-        let cands = generate_candidates(Power::Austria, &state, NUM_CANDIDATES, &mut rng);
+        let cands = generate_candidates(Power::Austria, &state, NUM_CANDIDATES, 0.0, &mut rng);
 
         let mut phantom_count = 0;
         let mut support_move_count = 0;
@@ -2790,7 +4903,7 @@ mod tests {
 
         for &p in ALL_POWERS.iter() {
             let mut rng = SmallRng::seed_from_u64(42);
-            let cands = generate_candidates(p, &state, NUM_CANDIDATES, &mut rng);
+            let cands = generate_candidates(p, &state, NUM_CANDIDATES, 0.0, &mut rng);
 
             for (ci, cand) in cands.iter().enumerate() {
                 for (order, _) in cand {
@@ -2839,7 +4952,7 @@ mod tests {
 
         for &p in ALL_POWERS.iter() {
             let mut rng = SmallRng::seed_from_u64(42);
-            let cands = generate_candidates(p, &state, NUM_CANDIDATES, &mut rng);
+            let cands = generate_candidates(p, &state, NUM_CANDIDATES, 0.0, &mut rng);
 
             let our_provinces: Vec<Province> = (0..PROVINCE_COUNT)
                 .filter_map(|i| {
@@ -2917,7 +5030,7 @@ mod tests {
         state.set_sc_owner(Province::Ven, Some(Power::Italy));
 
         let mut rng = SmallRng::seed_from_u64(42);
-        let cands = generate_candidates(Power::Austria, &state, 32, &mut rng);
+        let cands = generate_candidates(Power::Austria, &state, 32, 0.0, &mut rng);
 
         let our_provinces: Vec<Province> = (0..PROVINCE_COUNT)
             .filter_map(|i| {
@@ -2972,7 +5085,7 @@ mod tests {
         // For Hold { Vie Army }, prov_area(Vie) = Province::Vie as usize
         let mut logits = [0.0f32; 169];
         logits[0] = 2.5; // hold arm
-        let vie_idx = Province::Vie as usize;
+        let vie_idx = Province::Vie.index();
         logits[7 + vie_idx] = 1.0; // src offset = 7
         let order = Order::Hold {
             unit: OrderUnit {
@@ -2994,8 +5107,8 @@ mod tests {
         // Move order: logits[1] + logits[src_offset + src_area] + logits[dst_offset + dst_area]
         let mut logits = [0.0f32; 169];
         logits[1] = 1.0; // move arm
-        let bud_idx = Province::Bud as usize;
-        let ser_idx = Province::Ser as usize;
+        let bud_idx = Province::Bud.index();
+        let ser_idx = Province::Ser.index();
         logits[7 + bud_idx] = 0.5; // src
         logits[7 + 81 + ser_idx] = 2.0; // dst (dst_offset = 7 + 81 = 88)
         let order = Order::Move {
@@ -3004,6 +5117,7 @@ mod tests {
                 location: Location::new(Province::Bud),
             },
             dest: Location::new(Province::Ser),
+            via_convoy: false,
         };
         let score = score_order_with_logits(&order, &logits);
         assert!(
@@ -3019,8 +5133,8 @@ mod tests {
         // SupportHold: logits[2] + logits[src_offset + unit_area] + logits[dst_offset + supported_area]
         let mut logits = [0.0f32; 169];
         logits[2] = 0.8; // support arm
-        let gal_idx = Province::Gal as usize;
-        let bud_idx = Province::Bud as usize;
+        let gal_idx = Province::Gal.index();
+        let bud_idx = Province::Bud.index();
         logits[7 + gal_idx] = 0.3;
         logits[88 + bud_idx] = 1.5;
         let order = Order::SupportHold {
@@ -3047,8 +5161,8 @@ mod tests {
         // SupportMove: logits[2] + logits[src_offset + unit_area] + logits[dst_offset + dest_area]
         let mut logits = [0.0f32; 169];
         logits[2] = 1.2;
-        let gal_idx = Province::Gal as usize;
-        let rum_idx = Province::Rum as usize;
+        let gal_idx = Province::Gal.index();
+        let rum_idx = Province::Rum.index();
         logits[7 + gal_idx] = 0.4;
         logits[88 + rum_idx] = 0.9;
         let order = Order::SupportMove {
@@ -3076,8 +5190,8 @@ mod tests {
         // Convoy: logits[3] + logits[src_offset + unit_area] + logits[dst_offset + dest_area]
         let mut logits = [0.0f32; 169];
         logits[3] = 0.6; // convoy arm
-        let nth_idx = Province::Nth as usize;
-        let nwy_idx = Province::Nwy as usize;
+        let nth_idx = Province::Nth.index();
+        let nwy_idx = Province::Nwy.index();
         logits[7 + nth_idx] = 0.2;
         logits[88 + nwy_idx] = 1.1;
         let order = Order::Convoy {
@@ -3123,7 +5237,7 @@ mod tests {
                          // Bul ec area = 75
         logits[88 + 75] = 3.0; // dst_offset + bul_ec
 
-        let smy_idx = Province::Smy as usize;
+        let smy_idx = Province::Smy.index();
         logits[7 + smy_idx] = 0.5;
 
         let order = Order::Move {
@@ -3132,6 +5246,7 @@ mod tests {
                 location: Location::new(Province::Smy),
             },
             dest: Location::with_coast(Province::Bul, Coast::East),
+            via_convoy: false,
         };
         let score = score_order_with_logits(&order, &logits);
         assert!(
@@ -3141,56 +5256,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn unoccupied_home_sc_count_all_occupied() {
-        // Austria has 3 home SCs (Vie, Bud, Tri). All occupied -> 0.
-        let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
-        state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
-        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
-        state.place_unit(Province::Tri, Power::Austria, UnitType::Fleet, Coast::None);
-        state.set_sc_owner(Province::Vie, Some(Power::Austria));
-        state.set_sc_owner(Province::Bud, Some(Power::Austria));
-        state.set_sc_owner(Province::Tri, Some(Power::Austria));
-        let count = unoccupied_home_sc_count(Power::Austria, &state);
-        assert_eq!(count, 0, "All home SCs occupied should give 0");
-    }
-
-    #[test]
-    fn unoccupied_home_sc_count_one_empty() {
-        // Austria owns all 3 home SCs, but one (Vie) has no unit.
-        let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
-        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
-        state.place_unit(Province::Tri, Power::Austria, UnitType::Fleet, Coast::None);
-        state.set_sc_owner(Province::Vie, Some(Power::Austria));
-        state.set_sc_owner(Province::Bud, Some(Power::Austria));
-        state.set_sc_owner(Province::Tri, Some(Power::Austria));
-        let count = unoccupied_home_sc_count(Power::Austria, &state);
-        assert_eq!(count, 1, "One empty owned home SC should give 1");
-    }
-
-    #[test]
-    fn unoccupied_home_sc_count_lost_sc() {
-        // Austria lost Vie to Turkey -> doesn't count even if empty.
-        let mut state = BoardState::empty(1903, Season::Spring, Phase::Movement);
-        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
-        state.set_sc_owner(Province::Vie, Some(Power::Turkey)); // lost
-        state.set_sc_owner(Province::Bud, Some(Power::Austria));
-        state.set_sc_owner(Province::Tri, Some(Power::Austria));
-        // Tri is empty and still owned -> 1
-        let count = unoccupied_home_sc_count(Power::Austria, &state);
-        assert_eq!(
-            count, 1,
-            "Lost home SC should not count, one empty owned should give 1"
-        );
-    }
-
-    #[test]
-    fn unoccupied_home_sc_count_no_scs_owned() {
-        let state = BoardState::empty(1905, Season::Spring, Phase::Movement);
-        let count = unoccupied_home_sc_count(Power::Austria, &state);
-        assert_eq!(count, 0, "No SCs owned should give 0");
-    }
-
     #[test]
     fn pick_non_colliding_skips_claimed_destinations() {
         use crate::board::order::{Location, OrderUnit};
@@ -3203,6 +5268,7 @@ mod tests {
                 order: Order::Move {
                     unit,
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 score: 10.0,
             },
@@ -3210,6 +5276,7 @@ mod tests {
                 order: Order::Move {
                     unit,
                     dest: Location::new(Province::Rum),
+                    via_convoy: false,
                 },
                 score: 8.0,
             },
@@ -3217,6 +5284,7 @@ mod tests {
                 order: Order::Move {
                     unit,
                     dest: Location::new(Province::Gal),
+                    via_convoy: false,
                 },
                 score: 5.0,
             },
@@ -3255,6 +5323,7 @@ mod tests {
                 order: Order::Move {
                     unit,
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 score: 10.0,
             },
@@ -3262,6 +5331,7 @@ mod tests {
                 order: Order::Move {
                     unit,
                     dest: Location::new(Province::Rum),
+                    via_convoy: false,
                 },
                 score: 8.0,
             },
@@ -3292,6 +5362,7 @@ mod tests {
                 order: Order::Move {
                     unit,
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 score: 10.0,
             },
@@ -3322,6 +5393,151 @@ mod tests {
         );
     }
 
+    /// Austrian army alone on Serbia, Turkish armies in Bulgaria and Greece.
+    fn serbia_contest_state() -> BoardState {
+        let mut state = BoardState::empty(1903, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Ser, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Bul, Power::Turkey, UnitType::Army, Coast::None);
+        state.place_unit(Province::Gre, Power::Turkey, UnitType::Army, Coast::None);
+        state.set_sc_owner(Province::Ser, Some(Power::Austria));
+        state.set_sc_owner(Province::Bul, Some(Power::Turkey));
+        state.set_sc_owner(Province::Gre, Some(Power::Turkey));
+        state
+    }
+
+    #[test]
+    fn find_sc_contests_detects_supported_attack() {
+        let contests = find_sc_contests(&serbia_contest_state());
+        assert_eq!(contests.len(), 1);
+        let c = contests[0];
+        assert_eq!(c.sc, Province::Ser);
+        assert_eq!(c.defender, Power::Austria);
+        assert_eq!(c.attacker, Power::Turkey);
+        assert_ne!(c.mover, c.supporter);
+    }
+
+    #[test]
+    fn find_sc_contests_ignores_defended_sc() {
+        let mut state = serbia_contest_state();
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        assert!(find_sc_contests(&state).is_empty());
+    }
+
+    #[test]
+    fn lookahead_plays_predicted_builds_that_fit() {
+        let winter = parse_dfen("1901fb/Rawar/Rmos,Rwar,Rsev/-").unwrap();
+        let predicted = vec![
+            parse_order("F sev B").unwrap(),
+            parse_order("A mos B").unwrap(),
+        ];
+        let cache = GreedyOrderCache::new(Arc::new(TranspositionTable::new(16, 16)), false)
+            .with_builds(vec![BuildPrediction {
+                power: Power::Russia,
+                orders: predicted.clone(),
+            }]);
+        assert_eq!(cache.build_orders_for(Power::Russia, &winter), predicted);
+
+        // A unit now stands in sev: the prediction no longer fits.
+        let moved = parse_dfen("1901fb/Rawar,Rasev/Rmos,Rwar,Rsev,Rstp/-").unwrap();
+        assert_eq!(
+            cache.build_orders_for(Power::Russia, &moved),
+            heuristic_build_orders(Power::Russia, &moved)
+        );
+    }
+
+    #[test]
+    fn fall_lookahead_finishes_the_winter() {
+        let mut state = parse_dfen("1901fr/Rawar,Aavie/Rmos,Rwar,Avie/-").unwrap();
+        state.dislodged = [None; MAX_PROVINCES];
+        let mut resolver = Resolver::new(64);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut cache = GreedyOrderCache::new(Arc::new(TranspositionTable::new(16, 16)), false);
+        let future = simulate_n_phases(
+            &state,
+            Power::Austria,
+            &mut resolver,
+            1,
+            1901,
+            &mut rng,
+            &mut cache,
+        );
+        assert_eq!(future.phase, Phase::Movement);
+        assert_eq!(future.year, 1902);
+        assert!(matches!(
+            future.units[Province::Mos.index()],
+            Some((Power::Russia, _))
+        ));
+    }
+
+    #[test]
+    fn quiescence_extend_quiet_position_is_none() {
+        let state = initial_state();
+        let mut resolver = Resolver::new(64);
+        assert!(quiescence_extend(&state, &mut resolver).is_none());
+    }
+
+    #[test]
+    fn quiescence_extend_resolves_pending_capture() {
+        let state = serbia_contest_state();
+        let mut resolver = Resolver::new(64);
+        let next = quiescence_extend(&state, &mut resolver).expect("should extend");
+        assert!(matches!(
+            next.units[Province::Ser.index()],
+            Some((Power::Turkey, _))
+        ));
+        assert_eq!(next.sc_owner[Province::Ser.index()], Some(Power::Turkey));
+    }
+
+    #[test]
+    fn lookahead_supports_defend_threatened_sc() {
+        let mut state = BoardState::empty(1902, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Ser, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Tri, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Bul, Power::Turkey, UnitType::Army, Coast::None);
+        state.set_sc_owner(Province::Ser, Some(Power::Austria));
+
+        let hold = |p: Province| Order::Hold {
+            unit: OrderUnit {
+                unit_type: UnitType::Army,
+                location: Location::new(p),
+            },
+        };
+        let mut orders = vec![
+            (hold(Province::Ser), Power::Austria),
+            (army_move(Province::Bud, Province::Rum), Power::Austria),
+            (hold(Province::Tri), Power::Austria),
+            (hold(Province::Bul), Power::Turkey),
+        ];
+        add_lookahead_supports(&mut orders, &state);
+
+        // Tri (holding) supports rather than Bud, whose move targets an SC.
+        assert!(matches!(
+            orders[2].0,
+            Order::SupportHold { supported, .. } if supported.location.province == Province::Ser
+        ));
+        assert_eq!(orders[1].0, army_move(Province::Bud, Province::Rum));
+        assert_eq!(orders[0].0, hold(Province::Ser));
+        assert_eq!(
+            orders
+                .iter()
+                .filter(|(o, _)| matches!(o, Order::SupportHold { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn greedy_lookahead_orders_without_supports_matches_fast() {
+        let state = initial_state();
+        assert_eq!(
+            greedy_lookahead_orders(&state, false),
+            generate_greedy_orders_fast(&state)
+        );
+        let with = greedy_lookahead_orders(&state, true);
+        assert_eq!(with.len(), 22);
+    }
+
     #[test]
     fn generate_greedy_orders_fast_resolves_collisions() {
         // Two Austrian armies both wanting to move to Ser.
@@ -3387,6 +5603,245 @@ mod tests {
         );
     }
 
+    fn army_move(from: Province, to: Province) -> Order {
+        Order::Move {
+            unit: OrderUnit {
+                unit_type: UnitType::Army,
+                location: Location::new(from),
+            },
+            dest: Location::new(to),
+            via_convoy: false,
+        }
+    }
+
+    #[test]
+    fn score_order_credits_blocking_home_build() {
+        // Germany owns 4 SCs with 3 units away from Munich: it is owed a build.
+        let mut state = BoardState::empty(1902, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Tyr, Power::Austria, UnitType::Army, Coast::None);
+        for prov in [Province::Ber, Province::Kie, Province::Hol] {
+            state.place_unit(prov, Power::Germany, UnitType::Army, Coast::None);
+        }
+        for prov in [Province::Mun, Province::Ber, Province::Kie, Province::Hol] {
+            state.set_sc_owner(prov, Some(Power::Germany));
+        }
+        let tyr_mun = army_move(Province::Tyr, Province::Mun);
+        let owed = score_order(&tyr_mun, Power::Austria, &state);
+        assert!(owed > 0.0);
+
+        state.set_sc_owner(Province::Hol, None);
+        assert!(owed > score_order(&tyr_mun, Power::Austria, &state));
+    }
+
+    #[test]
+    fn keep_denial_candidate_survives_top_k() {
+        let mut state = BoardState::empty(1902, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Ukr, Power::Russia, UnitType::Army, Coast::None);
+        let mut scored = vec![
+            ScoredOrder {
+                order: army_move(Province::Bud, Province::Ser),
+                score: 5.0,
+            },
+            ScoredOrder {
+                order: army_move(Province::Bud, Province::Gal),
+                score: 4.0,
+            },
+            ScoredOrder {
+                order: army_move(Province::Bud, Province::Rum),
+                score: 1.0,
+            },
+        ];
+        keep_denial_candidate(&mut scored, Power::Austria, &state, 2);
+        scored.truncate(2);
+        assert!(scored.iter().any(|so| matches!(
+            so.order,
+            Order::Move { dest, .. } if dest.province == Province::Rum
+        )));
+    }
+
+    #[test]
+    fn top_k_keeps_hold_and_best_move_among_supports() {
+        // The German armies are packed together, so supports outscore the
+        // plain orders and would otherwise fill the cut (Ber keeps nothing
+        // but supports into Mun).
+        let mut state = BoardState::empty(1902, Season::Spring, Phase::Movement);
+        for prov in [
+            Province::Mun,
+            Province::Ber,
+            Province::Kie,
+            Province::Ruh,
+            Province::Sil,
+            Province::Boh,
+            Province::Tyr,
+        ] {
+            state.place_unit(prov, Power::Germany, UnitType::Army, Coast::None);
+        }
+        state.place_unit(Province::Bur, Power::France, UnitType::Army, Coast::None);
+        state.place_unit(Province::War, Power::Russia, UnitType::Army, Coast::None);
+
+        for k in [2, 3, 5] {
+            let per_unit = top_k_per_unit(Power::Germany, &state, k, 0.0);
+            for unit in &per_unit {
+                assert!(unit.len() <= k);
+                assert!(unit.iter().any(|so| matches!(so.order, Order::Hold { .. })));
+                assert!(unit.iter().any(|so| matches!(so.order, Order::Move { .. })));
+                assert!(unit.windows(2).all(|w| w[0].score >= w[1].score));
+            }
+        }
+    }
+
+    #[test]
+    fn inject_plan_candidates_adds_plan_first_turn() {
+        let mut state = BoardState::empty(1902, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Tri, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Ser, Power::Turkey, UnitType::Army, Coast::None);
+        state.set_sc_owner(Province::Ser, Some(Power::Turkey));
+
+        let mut rng = SmallRng::seed_from_u64(7);
+        let mut cands = generate_candidates(Power::Austria, &state, 4, 0.0, &mut rng);
+        let before = cands.len();
+        inject_plan_candidates(Power::Austria, &state, &mut cands);
+
+        let plan = &plan_candidates(Power::Austria, &state, MAX_PLAN_CANDIDATES)[0];
+        assert!(cands.len() >= before);
+        assert!(cands.iter().any(|c| plan
+            .first_orders()
+            .iter()
+            .all(|o| c.iter().any(|(co, _)| co == o))));
+    }
+
+    #[test]
+    fn inject_suggested_candidates_keeps_greedy_orders_for_other_units() {
+        let mut state = BoardState::empty(1902, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Tri, Power::Austria, UnitType::Army, Coast::None);
+
+        let mut rng = SmallRng::seed_from_u64(7);
+        let mut cands = generate_candidates(Power::Austria, &state, 4, 0.0, &mut rng);
+        let greedy = cands[0].clone();
+        let suggestion = crate::protocol::dson::parse_orders("A tri - tyr").unwrap();
+        inject_suggested_candidates(std::slice::from_ref(&suggestion), &mut cands);
+
+        let injected = cands
+            .iter()
+            .find(|c| c.iter().any(|(o, _)| *o == suggestion[0]))
+            .expect("suggestion injected");
+        let vie = |c: &[(Order, Power)]| {
+            c.iter()
+                .find(|(o, _)| order_unit_province(o) == Some(Province::Vie))
+                .map(|(o, _)| *o)
+        };
+        assert_eq!(vie(injected), vie(&greedy));
+        assert_eq!(injected.len(), greedy.len());
+    }
+
+    /// Austria (Gal, Bud; 3 SCs) next to a one-center Rumania held by Italy,
+    /// which a five-center Russia in Ukr and Sev can attack.
+    fn buffer_state() -> BoardState {
+        let mut state = BoardState::empty(1904, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Gal, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Rum, Power::Italy, UnitType::Army, Coast::None);
+        state.place_unit(Province::Ukr, Power::Russia, UnitType::Army, Coast::None);
+        state.place_unit(Province::Sev, Power::Russia, UnitType::Army, Coast::None);
+        for p in [Province::Vie, Province::Bud, Province::Tri] {
+            state.set_sc_owner(p, Some(Power::Austria));
+        }
+        state.set_sc_owner(Province::Rum, Some(Power::Italy));
+        for p in [
+            Province::Mos,
+            Province::Sev,
+            Province::War,
+            Province::Stp,
+            Province::Swe,
+        ] {
+            state.set_sc_owner(p, Some(Power::Russia));
+        }
+        state
+    }
+
+    #[test]
+    fn buffer_support_orders_shield_weak_neighbour() {
+        let state = buffer_state();
+        let orders = buffer_support_orders(Power::Austria, &state);
+        assert!(!orders.is_empty());
+        assert!(orders.iter().all(|o| matches!(
+            o,
+            Order::SupportHold { supported, .. } if supported.location.province == Province::Rum
+        )));
+
+        // No rival strong enough: Russia shrinks below Austria.
+        let mut weak_rival = state.clone();
+        for p in [Province::War, Province::Stp, Province::Swe] {
+            weak_rival.set_sc_owner(p, None);
+        }
+        assert!(buffer_support_orders(Power::Austria, &weak_rival).is_empty());
+    }
+
+    #[test]
+    fn inject_buffer_candidates_adds_foreign_support_hold() {
+        let state = buffer_state();
+        let mut rng = SmallRng::seed_from_u64(11);
+        let mut cands = generate_candidates(Power::Austria, &state, 4, 0.0, &mut rng);
+        inject_buffer_candidates(Power::Austria, &state, &mut cands);
+        let buffered = cands
+            .iter()
+            .find(|c| {
+                c.iter().any(|(o, _)| {
+                    matches!(o, Order::SupportHold { supported, .. }
+                        if supported.location.province == Province::Rum)
+                })
+            })
+            .expect("buffer candidate");
+        assert!(!buffered.iter().any(|(o, _)| matches!(
+            o,
+            Order::Move { dest, .. } if dest.province == Province::Rum
+        )));
+    }
+
+    #[test]
+    fn constrained_candidates_keep_pins_and_avoid_forbidden_provinces() {
+        let state = initial_state();
+        let pin = army_move(Province::Vie, Province::Tyr);
+        let constraints = Constraints {
+            pinned: vec![pin],
+            forbidden: vec![Province::Ser, Province::Gal],
+        };
+        let cands = generate_candidates_constrained(Power::Austria, &state, &constraints);
+        assert!(cands.len() > 1);
+        for cand in &cands {
+            assert_eq!(cand.len(), 3);
+            assert!(cand.iter().any(|(o, _)| *o == pin));
+            assert!(cand.iter().all(|(o, _)| constraints.allows(o)));
+        }
+        assert!(generate_candidates_constrained(
+            Power::Austria,
+            &BoardState::empty(1901, Season::Spring, Phase::Movement),
+            &constraints
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn top_k_per_unit_applies_scaled_opening_priors() {
+        let state = initial_state();
+        let score_of = |scale: f32| {
+            top_k_per_unit(Power::France, &state, 20, scale)
+                .into_iter()
+                .flatten()
+                .find(|so| so.order == army_move(Province::Mar, Province::Spa))
+                .map(|so| so.score)
+                .unwrap()
+        };
+        let base = score_of(0.0);
+        let full = score_of(1.0);
+        let half = score_of(0.5);
+        assert!(full > base);
+        assert!((half - (base + full) / 2.0).abs() < 1e-4);
+    }
+
     #[test]
     fn score_order_fall_home_sc_vacating_bonus() {
         use crate::board::order::{Location, OrderUnit};
@@ -3409,6 +5864,7 @@ mod tests {
                 location: Location::new(Province::Vie),
             },
             dest: Location::new(Province::Tyr),
+            via_convoy: false,
         };
 
         // Hold on Vie
@@ -3431,6 +5887,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn score_order_prefers_winnable_exchanges() {
+        // Same attack on a Turkish A bul: Austria brings ser and gre against
+        // it alone, or only ser against a supported defence.
+        let winnable = parse_dfen("1902sm/Aaser,Aagre,Tabul/Aser,Agre,Tbul/-").unwrap();
+        let lost = parse_dfen("1902sm/Aaser,Tabul,Tacon/Aser,Tbul,Tcon/-").unwrap();
+        let attack = parse_order("A ser - bul").unwrap();
+        assert!(
+            score_order(&attack, Power::Austria, &winnable)
+                > score_order(&attack, Power::Austria, &lost) + 3.0
+        );
+
+        // Support-holding a center that falls anyway is worth little.
+        let held = parse_dfen("1902sm/Tabul,Tacon,Aaser/Tbul,Tcon,Aser/-").unwrap();
+        let falls = parse_dfen("1902sm/Tabul,Tacon,Aaser,Aagre,Aarum/Tbul,Tcon,Aser/-").unwrap();
+        let support = parse_order("A con S A bul H").unwrap();
+        assert!(
+            score_order(&support, Power::Turkey, &held)
+                > score_order(&support, Power::Turkey, &falls)
+        );
+    }
+
     #[test]
     fn score_order_spring_sc_attack_bonus() {
         use crate::board::order::{Location, OrderUnit};
@@ -3446,6 +5924,7 @@ mod tests {
                 location: Location::new(Province::Bud),
             },
             dest: Location::new(Province::Rum),
+            via_convoy: false,
         };
 
         // Move to a non-SC, non-owned province for comparison.
@@ -3455,6 +5934,7 @@ mod tests {
                 location: Location::new(Province::Bud),
             },
             dest: Location::new(Province::Gal),
+            via_convoy: false,
         };
 
         let sc_score = score_order(&attack_sc, Power::Austria, &state);
@@ -3519,8 +5999,15 @@ mod tests {
             }
             let state = initial_state();
             let mut rng = SmallRng::seed_from_u64(42);
-            let cands =
-                generate_candidates_neural(Power::Austria, &state, &evaluator, 8, 0.7, &mut rng);
+            let cands = generate_candidates_neural(
+                Power::Austria,
+                &state,
+                &evaluator,
+                8,
+                0.7,
+                0.0,
+                &mut rng,
+            );
             assert!(
                 cands.len() >= 2,
                 "Neural candidates should produce at least 2 sets, got {}",
@@ -3553,7 +6040,8 @@ mod tests {
                     .filter(|&i| matches!(state.units[i], Some((pw, _)) if pw == p))
                     .count();
                 let mut rng = SmallRng::seed_from_u64(42);
-                let cands = generate_candidates_neural(p, &state, &evaluator, 8, 0.5, &mut rng);
+                let cands =
+                    generate_candidates_neural(p, &state, &evaluator, 8, 0.5, 0.0, &mut rng);
                 assert!(
                     !cands.is_empty(),
                     "Power {:?} should get at least 1 candidate set",
@@ -3581,7 +6069,7 @@ mod tests {
             }
             let state = initial_state();
             let mut rng = SmallRng::seed_from_u64(42);
-            let cands = generate_candidates(Power::Austria, &state, 8, &mut rng);
+            let cands = generate_candidates(Power::Austria, &state, 8, 0.0, &mut rng);
 
             let weights = policy_guided_init(&evaluator, Power::Austria, &state, &cands);
             assert!(weights.is_some(), "Should produce weights with valid model");
@@ -0,0 +1,141 @@
+//! Position repetition across turns.
+//!
+//! A stalemated game repeats itself: every spring the same units stand in
+//! the same provinces over the same center owners. [`RepetitionTracker`]
+//! watches the movement positions the engine is given and counts how many
+//! consecutive years the current one has stood unchanged. Once that reaches
+//! `STAGNATION_YEARS` the engine warns the host, and RM+ charges our
+//! candidates that move none of our units a small penalty, so a
+//! deadlocked engine at least probes for a way through. The count is also
+//! the stagnation signal a draw offer can be based on.
+
+use crate::board::order::Order;
+use crate::board::province::Power;
+use crate::board::state::{BoardState, Phase};
+use crate::protocol::dfen::encode_dfen;
+
+/// Consecutive unchanged years before the game counts as stagnant.
+pub const STAGNATION_YEARS: u32 = 2;
+
+/// Penalty per stagnant year for a candidate whose orders all stay put.
+const STAGNATION_WEIGHT: f64 = 0.25;
+
+/// Stagnant years beyond this do not raise the penalty further.
+const MAX_PENALIZED_YEARS: u32 = 4;
+
+/// Records consecutive movement positions by units and center owners.
+#[derive(Debug, Clone, Default)]
+pub struct RepetitionTracker {
+    /// (turn index, position without its phase field), consecutive turns.
+    history: Vec<(u32, String)>,
+}
+
+/// The units, center owners and dislodged units of a position.
+fn signature(state: &BoardState) -> String {
+    let dfen = encode_dfen(state);
+    match dfen.split_once('/') {
+        Some((_, rest)) => rest.to_string(),
+        None => dfen,
+    }
+}
+
+impl RepetitionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets all observations.
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    /// Observes a new position.
+    ///
+    /// Only movement positions count. The same turn seen again replaces the
+    /// earlier sighting; a position that does not directly follow the
+    /// previous one (new game, rewind, skipped turn) restarts tracking.
+    pub fn observe(&mut self, state: &BoardState) {
+        if state.phase != Phase::Movement {
+            return;
+        }
+        let turn = state.turn_index();
+        match self.history.last() {
+            Some(&(last, _)) if turn == last => {
+                self.history.pop();
+            }
+            Some(&(last, _)) if turn == last + 1 => {}
+            _ => self.history.clear(),
+        }
+        self.history.push((turn, signature(state)));
+    }
+
+    /// Consecutive years, counting back from the last position, in which
+    /// the position at the same season was identical.
+    pub fn stagnant_years(&self) -> u32 {
+        let Some((_, current)) = self.history.last() else {
+            return 0;
+        };
+        self.history
+            .iter()
+            .rev()
+            .step_by(2)
+            .skip(1)
+            .take_while(|(_, sig)| sig == current)
+            .count() as u32
+    }
+}
+
+/// Penalty for a candidate of `power` that moves none of its units while
+/// the game has been stagnant for `stagnant_years`.
+pub fn stagnation_penalty(orders: &[(Order, Power)], power: Power, stagnant_years: u32) -> f64 {
+    if stagnant_years < STAGNATION_YEARS {
+        return 0.0;
+    }
+    let moves = orders
+        .iter()
+        .any(|(o, p)| *p == power && matches!(o, Order::Move { .. }));
+    if moves {
+        return 0.0;
+    }
+    STAGNATION_WEIGHT * stagnant_years.min(MAX_PENALIZED_YEARS) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn position(year: u16, season: char, units: &str) -> BoardState {
+        parse_dfen(&format!("{}{}m/{}/Avie,Rwar/-", year, season, units)).unwrap()
+    }
+
+    #[test]
+    fn counts_years_the_position_stood_still() {
+        let mut tracker = RepetitionTracker::new();
+        tracker.observe(&position(1905, 's', "Aavie,Rawar"));
+        tracker.observe(&position(1905, 'f', "Aavie,Ragal"));
+        tracker.observe(&position(1906, 's', "Aavie,Rawar"));
+        assert_eq!(tracker.stagnant_years(), 1);
+        tracker.observe(&position(1906, 'f', "Aavie,Ragal"));
+        tracker.observe(&position(1907, 's', "Aavie,Rawar"));
+        // Seeing the same turn again does not count twice.
+        tracker.observe(&position(1907, 's', "Aavie,Rawar"));
+        assert_eq!(tracker.stagnant_years(), 2);
+
+        tracker.observe(&position(1907, 'f', "Aabud,Ragal"));
+        assert_eq!(tracker.stagnant_years(), 0);
+        tracker.observe(&position(1910, 's', "Aavie,Rawar"));
+        assert_eq!(tracker.stagnant_years(), 0);
+    }
+
+    #[test]
+    fn penalty_applies_to_candidates_that_stay_put() {
+        let hold = [(parse_order("A vie H").unwrap(), Power::Austria)];
+        let mv = [(parse_order("A vie - gal").unwrap(), Power::Austria)];
+        assert_eq!(stagnation_penalty(&hold, Power::Austria, 1), 0.0);
+        assert_eq!(stagnation_penalty(&hold, Power::Austria, 2), 0.5);
+        assert_eq!(stagnation_penalty(&hold, Power::Austria, 9), 1.0);
+        assert_eq!(stagnation_penalty(&mv, Power::Austria, 3), 0.0);
+    }
+}
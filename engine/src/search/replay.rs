@@ -0,0 +1,177 @@
+//! Reproducible RM+ searches.
+//!
+//! An RM+ search is random (candidate sampling, opponent sampling in the
+//! counterfactual pass) and stops on the clock, so two searches of one
+//! position rarely agree. For bug reports every search ends with a
+//! [`ReplayBlob`]: the seed it drew, hashes of its effective options and of
+//! the position, how many RM+ iterations it ran, how many powers it built
+//! candidate pools for, and a fingerprint of the session pools it started
+//! from. Given the same position, options and starting pools, a search run
+//! with the blob in [`RmOptions::replay`] draws the same numbers and stops
+//! after the same iteration instead of on the clock, so it repeats the
+//! original exactly. Local search and the coalition check still stop at
+//! the search's deadline, which a replay does not reach, so a search whose
+//! clock cut them short may choose differently when replayed.
+//!
+//! The blob is written as `v1.<seed>.<options>.<position>.<iterations>.
+//! <powers>.<pools>`, hashes and seed in hex, counts in decimal; `pools`
+//! is `0` when the search started from an empty session.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use crate::search::cache_budget::CacheBudget;
+use crate::search::regret_matching::RmOptions;
+
+/// Version tag at the start of a blob.
+const BLOB_VERSION: &str = "v1";
+
+/// What it takes to rerun one RM+ search. See the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayBlob {
+    /// Seed of the search's random number generator.
+    pub seed: u64,
+    /// [`options_hash`] of the search's configuration.
+    pub options: u64,
+    /// Zobrist hash of the searched position.
+    pub position: u64,
+    /// RM+ iterations the search ran.
+    pub iterations: u64,
+    /// Powers the search built candidate pools for.
+    pub powers: usize,
+    /// [`crate::search::SearchSession::fingerprint`] of the session the
+    /// search started from, 0 if it was empty.
+    pub pools: u64,
+}
+
+/// Error parsing a [`ReplayBlob`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReplayBlobError {
+    #[error("replay blob must start with '{BLOB_VERSION}.', got '{0}'")]
+    Version(String),
+    #[error("replay blob needs 7 dot-separated fields, got {0}")]
+    FieldCount(usize),
+    #[error("invalid replay blob field '{0}'")]
+    Field(String),
+}
+
+impl fmt::Display for ReplayBlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{:x}.{:x}.{:x}.{}.{}.{:x}",
+            BLOB_VERSION,
+            self.seed,
+            self.options,
+            self.position,
+            self.iterations,
+            self.powers,
+            self.pools
+        )
+    }
+}
+
+impl FromStr for ReplayBlob {
+    type Err = ReplayBlobError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.trim().split('.').collect();
+        if fields[0] != BLOB_VERSION {
+            return Err(ReplayBlobError::Version(fields[0].to_string()));
+        }
+        if fields.len() != 7 {
+            return Err(ReplayBlobError::FieldCount(fields.len()));
+        }
+        let hex =
+            |f: &str| u64::from_str_radix(f, 16).map_err(|_| ReplayBlobError::Field(f.to_string()));
+        let dec = |f: &str| {
+            f.parse::<u64>()
+                .map_err(|_| ReplayBlobError::Field(f.to_string()))
+        };
+        Ok(ReplayBlob {
+            seed: hex(fields[1])?,
+            options: hex(fields[2])?,
+            position: hex(fields[3])?,
+            iterations: dec(fields[4])?,
+            powers: dec(fields[5])? as usize,
+            pools: hex(fields[6])?,
+        })
+    }
+}
+
+/// Hashes everything besides the position and pools that steers an RM+
+/// search: its options (without the replay blob, the search log, the
+/// cache budget and the interim-best slot, which do not change its result), strength, press trust
+/// and whether a neural evaluator is loaded.
+pub fn options_hash(
+    options: &RmOptions,
+    strength: u64,
+    trust_scores: Option<&[f64; 7]>,
+    neural: bool,
+) -> u64 {
+    let normalized = RmOptions {
+        replay: None,
+        search_log: None,
+        cache: CacheBudget::default(),
+        currbest: None,
+        ..options.clone()
+    };
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", normalized).hash(&mut hasher);
+    strength.hash(&mut hasher);
+    format!("{:?}", trust_scores).hash(&mut hasher);
+    neural.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_round_trips() {
+        let blob = ReplayBlob {
+            seed: 0xdead_beef_0042,
+            options: u64::MAX,
+            position: 1,
+            iterations: 1440,
+            powers: 7,
+            pools: 0,
+        };
+        let text = blob.to_string();
+        assert_eq!(text, "v1.deadbeef0042.ffffffffffffffff.1.1440.7.0");
+        assert_eq!(text.parse::<ReplayBlob>(), Ok(blob));
+        assert_eq!(
+            "v2.1.2.3.4.5.6".parse::<ReplayBlob>(),
+            Err(ReplayBlobError::Version("v2".into()))
+        );
+        assert_eq!(
+            "v1.1.2.3".parse::<ReplayBlob>(),
+            Err(ReplayBlobError::FieldCount(4))
+        );
+        assert_eq!(
+            "v1.1.2.3.x.5.6".parse::<ReplayBlob>(),
+            Err(ReplayBlobError::Field("x".into()))
+        );
+    }
+
+    #[test]
+    fn options_hash_ignores_what_does_not_change_the_result() {
+        let base = RmOptions::default();
+        let h = options_hash(&base, 100, None, false);
+        let replaying = RmOptions {
+            cache: CacheBudget::from_mb(64),
+            ..base.clone()
+        };
+        assert_eq!(options_hash(&replaying, 100, None, false), h);
+        let changed = RmOptions {
+            local_search: false,
+            ..base.clone()
+        };
+        assert_ne!(options_hash(&changed, 100, None, false), h);
+        assert_ne!(options_hash(&base, 90, None, false), h);
+        assert_ne!(options_hash(&base, 100, Some(&[0.5; 7]), false), h);
+    }
+}
@@ -0,0 +1,211 @@
+//! JSONL trace of RM+ iterations for offline convergence analysis.
+//!
+//! With the `SearchLog` option set, every RM+ search appends to that file:
+//! one `search` line naming the position and every power's candidate order
+//! sets, one `iteration` line per traced iteration, and a closing `done`
+//! line. Iteration lines carry the sampled profile (a candidate index per
+//! power, in `candidates` order), its value to us, each counterfactual's
+//! value and regret delta, and our cumulative regrets after the update.
+//! Tracing every iteration of a long search is large; `SearchLogEvery`
+//! keeps one iteration in N.
+//!
+//! This is separate from the DUI stream: nothing here reaches the server.
+//! Write errors end the trace for that search without affecting it.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::board::province::Power;
+use crate::board::state::BoardState;
+use crate::protocol::dfen::encode_dfen;
+use crate::protocol::dson::format_orders_on;
+use crate::search::regret_matching::PowerCandidates;
+
+/// Where and how densely to trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchLogConfig {
+    /// JSONL file appended to.
+    pub path: PathBuf,
+    /// Trace one iteration in this many (1 = every iteration).
+    pub every: u64,
+}
+
+impl SearchLogConfig {
+    pub fn new(path: impl Into<PathBuf>, every: u64) -> Self {
+        SearchLogConfig {
+            path: path.into(),
+            every: every.max(1),
+        }
+    }
+}
+
+/// An open trace for one search.
+pub(crate) struct SearchLog {
+    writer: Option<BufWriter<File>>,
+    every: u64,
+}
+
+fn round4(x: f64) -> f64 {
+    (x * 10_000.0).round() / 10_000.0
+}
+
+impl SearchLog {
+    /// Opens the trace file and writes the `search` header line.
+    pub(crate) fn start(
+        config: &SearchLogConfig,
+        power: Power,
+        state: &BoardState,
+        power_candidates: &[PowerCandidates],
+    ) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+        let mut log = SearchLog {
+            writer: Some(BufWriter::new(file)),
+            every: config.every,
+        };
+        let candidates: Vec<Value> = power_candidates
+            .iter()
+            .map(|(p, cands)| {
+                let sets: Vec<String> = cands
+                    .iter()
+                    .map(|c| {
+                        let orders: Vec<_> = c.iter().map(|(o, _)| *o).collect();
+                        format_orders_on(state.map(), &orders)
+                    })
+                    .collect();
+                json!({ "power": p.name(), "sets": sets })
+            })
+            .collect();
+        log.write(json!({
+            "event": "search",
+            "power": power.name(),
+            "position": encode_dfen(state),
+            "every": config.every,
+            "candidates": candidates,
+        }));
+        Ok(log)
+    }
+
+    /// Returns true if iteration `iteration` (counted from 0) is traced.
+    pub(crate) fn wants(&self, iteration: u64) -> bool {
+        self.writer.is_some() && iteration.is_multiple_of(self.every)
+    }
+
+    /// Writes an `iteration` line. `counterfactuals` pairs candidate
+    /// indices with their values (cooperation penalty included, like
+    /// `value`).
+    pub(crate) fn iteration(
+        &mut self,
+        iteration: u64,
+        sampled: &[usize],
+        value: f64,
+        counterfactuals: &[(usize, f64)],
+        regrets: &[f64],
+    ) {
+        let mut cf: Vec<(usize, f64)> = counterfactuals.to_vec();
+        cf.sort_by_key(|&(ci, _)| ci);
+        let cf: Vec<Value> = cf
+            .iter()
+            .map(|&(ci, v)| {
+                json!({
+                    "candidate": ci,
+                    "value": round4(v),
+                    "delta": round4(v - value),
+                })
+            })
+            .collect();
+        let regrets: Vec<f64> = regrets.iter().map(|&r| round4(r)).collect();
+        self.write(json!({
+            "event": "iteration",
+            "iteration": iteration,
+            "profile": sampled,
+            "value": round4(value),
+            "counterfactuals": cf,
+            "regrets": regrets,
+        }));
+    }
+
+    /// Writes the closing `done` line with our average strategy and flushes.
+    pub(crate) fn finish(
+        mut self,
+        iterations: u64,
+        converged: bool,
+        chosen: usize,
+        weights: &[f64],
+    ) {
+        let total: f64 = weights.iter().sum();
+        let strategy: Vec<f64> = weights
+            .iter()
+            .map(|&w| if total > 0.0 { round4(w / total) } else { 0.0 })
+            .collect();
+        self.write(json!({
+            "event": "done",
+            "iterations": iterations,
+            "converged": converged,
+            "chosen": chosen,
+            "strategy": strategy,
+        }));
+        if let Some(w) = self.writer.as_mut() {
+            let _ = w.flush();
+        }
+    }
+
+    fn write(&mut self, line: Value) {
+        if let Some(w) = self.writer.as_mut() {
+            if writeln!(w, "{}", line).is_err() {
+                self.writer = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Order;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    #[test]
+    fn writes_header_sampled_iterations_and_summary() {
+        let path = std::env::temp_dir().join(format!(
+            "realpolitik-search-log-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let state = parse_dfen("1901sm/Aavie/Avie/-").unwrap();
+        let set =
+            |s: &str| -> Vec<(Order, Power)> { vec![(parse_order(s).unwrap(), Power::Austria)] };
+        let cands = vec![(Power::Austria, vec![set("A vie H"), set("A vie - gal")])];
+
+        let config = SearchLogConfig::new(&path, 2);
+        let mut log = SearchLog::start(&config, Power::Austria, &state, &cands).unwrap();
+        for i in 0..4 {
+            if log.wants(i) {
+                log.iteration(i, &[0], 1.0, &[(1, 1.5)], &[0.0, 0.5]);
+            }
+        }
+        log.finish(4, false, 1, &[1.0, 3.0]);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["event"], "search");
+        assert_eq!(lines[0]["candidates"][0]["sets"][1], "A vie - gal");
+        assert_eq!(lines[1]["iteration"], 0);
+        assert_eq!(lines[2]["iteration"], 2);
+        assert_eq!(lines[2]["counterfactuals"][0]["delta"], 0.5);
+        assert_eq!(lines[3]["event"], "done");
+        assert_eq!(lines[3]["strategy"], json!([0.25, 0.75]));
+        assert_eq!(SearchLogConfig::new("x", 0).every, 1);
+    }
+}
@@ -0,0 +1,343 @@
+//! Season-specific order scoring.
+//!
+//! The per-unit order scorers share one set of season-independent features
+//! (center value, threats, proximity, collisions). What a move is worth
+//! also depends on the season:
+//!
+//! - **Spring** is about positioning and tempo: stepping onto a center
+//!   nobody can take this turn still puts the unit in place for Fall.
+//! - **Fall** is about capture and builds: ownership changes at the end of
+//!   the turn, so leaving a center we don't own yet forfeits it, home
+//!   centers must be vacated to make room for owed builds, and moves that
+//!   deny an opponent's builds are worth extra.
+//!
+//! [`SeasonScorer`] holds those terms, so the base scorers stay
+//! season-free and each season's heuristics can be tested and tuned alone.
+
+use crate::board::province::{Coast, Power, Province};
+use crate::board::state::{BoardState, Season};
+use crate::eval::heuristic::{count_scs, unit_can_reach};
+
+/// Spring bonus for moving onto a center we don't own.
+const SPRING_CENTER_APPROACH: f32 = 4.0;
+
+/// Fall penalty for leaving a center we occupy but don't own yet.
+const FALL_ABANDON_CENTER: f32 = 12.0;
+
+/// Fall bonus for vacating (penalty for holding on) a home center needed
+/// for an owed build.
+const FALL_FREE_BUILD_SITE: f32 = 8.0;
+
+/// Fall bonus for occupying a home center its owner needs for a build.
+const FALL_BLOCK_BUILD: f32 = 4.0;
+
+/// Fall bonus for contesting a center a lone opponent could take.
+const FALL_BOUNCE_CAPTURE: f32 = 3.0;
+
+/// The season-specific part of order scoring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SeasonScorer {
+    /// Positioning and tempo.
+    Spring,
+    /// Center capture and builds.
+    Fall,
+}
+
+impl SeasonScorer {
+    /// The scorer for the season of `state`.
+    pub(crate) fn for_state(state: &BoardState) -> Self {
+        match state.season {
+            Season::Spring => SeasonScorer::Spring,
+            Season::Fall => SeasonScorer::Fall,
+        }
+    }
+
+    /// Adjustment for holding in `prov`.
+    pub(crate) fn hold(self, prov: Province, power: Power, state: &BoardState) -> f32 {
+        match self {
+            SeasonScorer::Spring => 0.0,
+            SeasonScorer::Fall => {
+                if blocks_own_build(prov, power, state) {
+                    -FALL_FREE_BUILD_SITE
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Adjustment for moving from `src` to `dst`.
+    pub(crate) fn movement(
+        self,
+        src: Province,
+        dst: Province,
+        power: Power,
+        state: &BoardState,
+    ) -> f32 {
+        match self {
+            SeasonScorer::Spring => {
+                if state.map().is_supply_center(dst) && state.sc_owner[dst.index()] != Some(power) {
+                    SPRING_CENTER_APPROACH
+                } else {
+                    0.0
+                }
+            }
+            SeasonScorer::Fall => {
+                let mut score = 0.0;
+                if state.map().is_supply_center(src) && state.sc_owner[src.index()] != Some(power) {
+                    score -= FALL_ABANDON_CENTER;
+                }
+                if blocks_own_build(src, power, state) {
+                    score += FALL_FREE_BUILD_SITE;
+                }
+                score
+            }
+        }
+    }
+
+    /// Bonus for a move into `dst` that denies an opponent's builds. Only
+    /// Fall moves deny builds.
+    ///
+    /// Two cases the center-capture scoring undervalues:
+    /// - Occupying an opponent's home center while that power is owed
+    ///   builds (a unit sitting there blocks the build even if the center
+    ///   isn't taken).
+    /// - Contesting a center an opponent is positioned to capture, so their
+    ///   attempt bounces and the count they build from stays flat.
+    pub(crate) fn denial(self, dst: Province, power: Power, state: &BoardState) -> f32 {
+        if self != SeasonScorer::Fall || !state.map().is_supply_center(dst) {
+            return 0.0;
+        }
+        let mut bonus: f32 = 0.0;
+        let owner = state.sc_owner[dst.index()];
+
+        if let Some(home) = state.map().home_power(dst) {
+            if home != power && owner == Some(home) && pending_builds(home, state) > 0 {
+                bonus += FALL_BLOCK_BUILD;
+            }
+        }
+
+        // Opponents (other than the current owner) who can move in this turn.
+        let mut capturers = 0;
+        for adj in state.map().adj_from(dst) {
+            let fi = adj.to.index();
+            if let Some((p, ut)) = state.units[fi] {
+                if p == power || Some(p) == owner {
+                    continue;
+                }
+                let coast = state.fleet_coast[fi].unwrap_or(Coast::None);
+                if unit_can_reach(state.map(), adj.to, coast, ut, dst) {
+                    capturers += 1;
+                }
+            }
+        }
+        if capturers == 1 {
+            // A lone attacker bounces against our move.
+            bonus += FALL_BOUNCE_CAPTURE;
+        }
+
+        bonus
+    }
+}
+
+/// Returns true if `prov` is an owned home center whose unit stands in the
+/// way of a build the power is owed and has nowhere else to place.
+fn blocks_own_build(prov: Province, power: Power, state: &BoardState) -> bool {
+    if !state.map().is_supply_center(prov)
+        || state.map().home_power(prov) != Some(power)
+        || state.sc_owner[prov.index()] != Some(power)
+    {
+        return false;
+    }
+    let owed = pending_builds(power, state);
+    owed > 0 && unoccupied_home_sc_count(power, state) < owed
+}
+
+/// Returns the number of unoccupied home SCs for a power (potential build slots).
+fn unoccupied_home_sc_count(power: Power, state: &BoardState) -> i32 {
+    let mut count = 0i32;
+    for &p in state.map().provinces() {
+        let i = p.index();
+        if state.map().is_supply_center(p)
+            && state.map().home_power(p) == Some(power)
+            && state.sc_owner[i] == Some(power)
+            && state.units[i].is_none()
+        {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Returns the number of builds a power is owed (SCs minus units, floored at 0).
+fn pending_builds(power: Power, state: &BoardState) -> i32 {
+    let unit_count = state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count() as i32;
+    (count_scs(state, power) - unit_count).max(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::state::Phase;
+    use crate::board::unit::UnitType;
+
+    /// Austria with all three home centers plus Serbia and units in Vie,
+    /// Bud and Tri: one build owed and no free home center.
+    fn owed_build_state(season: Season) -> BoardState {
+        let mut state = BoardState::empty(1901, season, Phase::Movement);
+        for prov in [Province::Vie, Province::Bud, Province::Tri] {
+            state.place_unit(prov, Power::Austria, UnitType::Army, Coast::None);
+            state.set_sc_owner(prov, Some(Power::Austria));
+        }
+        state.set_sc_owner(Province::Ser, Some(Power::Austria));
+        state
+    }
+
+    #[test]
+    fn spring_scores_positioning_only() {
+        let state = owed_build_state(Season::Spring);
+        let spring = SeasonScorer::for_state(&state);
+        assert_eq!(spring, SeasonScorer::Spring);
+        let p = Power::Austria;
+        assert_eq!(spring.hold(Province::Vie, p, &state), 0.0);
+        assert_eq!(
+            spring.movement(Province::Bud, Province::Rum, p, &state),
+            SPRING_CENTER_APPROACH
+        );
+        assert_eq!(
+            spring.movement(Province::Bud, Province::Gal, p, &state),
+            0.0
+        );
+        assert_eq!(
+            spring.movement(Province::Bud, Province::Ser, p, &state),
+            0.0
+        );
+        assert_eq!(spring.denial(Province::Rum, p, &state), 0.0);
+    }
+
+    #[test]
+    fn fall_frees_home_centers_for_owed_builds() {
+        let mut state = owed_build_state(Season::Fall);
+        let fall = SeasonScorer::for_state(&state);
+        assert_eq!(fall, SeasonScorer::Fall);
+        let p = Power::Austria;
+        assert_eq!(fall.hold(Province::Vie, p, &state), -FALL_FREE_BUILD_SITE);
+        assert_eq!(
+            fall.movement(Province::Vie, Province::Gal, p, &state),
+            FALL_FREE_BUILD_SITE
+        );
+
+        // With Tri empty the build has a site: no pressure to move.
+        state.remove_unit(Province::Tri);
+        state.place_unit(Province::Alb, Power::Austria, UnitType::Army, Coast::None);
+        assert_eq!(fall.hold(Province::Vie, p, &state), 0.0);
+        assert_eq!(fall.movement(Province::Vie, Province::Gal, p, &state), 0.0);
+    }
+
+    #[test]
+    fn fall_penalizes_abandoning_an_unowned_center() {
+        let mut state = BoardState::empty(1902, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Rum, Power::Austria, UnitType::Army, Coast::None);
+        let fall = SeasonScorer::Fall;
+        assert_eq!(
+            fall.movement(Province::Rum, Province::Bul, Power::Austria, &state),
+            -FALL_ABANDON_CENTER
+        );
+        assert_eq!(
+            SeasonScorer::Spring.movement(Province::Rum, Province::Gal, Power::Austria, &state),
+            0.0
+        );
+    }
+
+    #[test]
+    fn unoccupied_home_sc_count_all_occupied() {
+        // Austria has 3 home SCs (Vie, Bud, Tri). All occupied -> 0.
+        let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Vie, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Tri, Power::Austria, UnitType::Fleet, Coast::None);
+        state.set_sc_owner(Province::Vie, Some(Power::Austria));
+        state.set_sc_owner(Province::Bud, Some(Power::Austria));
+        state.set_sc_owner(Province::Tri, Some(Power::Austria));
+        let count = unoccupied_home_sc_count(Power::Austria, &state);
+        assert_eq!(count, 0, "All home SCs occupied should give 0");
+    }
+
+    #[test]
+    fn unoccupied_home_sc_count_one_empty() {
+        // Austria owns all 3 home SCs, but one (Vie) has no unit.
+        let mut state = BoardState::empty(1901, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        state.place_unit(Province::Tri, Power::Austria, UnitType::Fleet, Coast::None);
+        state.set_sc_owner(Province::Vie, Some(Power::Austria));
+        state.set_sc_owner(Province::Bud, Some(Power::Austria));
+        state.set_sc_owner(Province::Tri, Some(Power::Austria));
+        let count = unoccupied_home_sc_count(Power::Austria, &state);
+        assert_eq!(count, 1, "One empty owned home SC should give 1");
+    }
+
+    #[test]
+    fn unoccupied_home_sc_count_lost_sc() {
+        // Austria lost Vie to Turkey -> doesn't count even if empty.
+        let mut state = BoardState::empty(1903, Season::Spring, Phase::Movement);
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        state.set_sc_owner(Province::Vie, Some(Power::Turkey)); // lost
+        state.set_sc_owner(Province::Bud, Some(Power::Austria));
+        state.set_sc_owner(Province::Tri, Some(Power::Austria));
+        // Tri is empty and still owned -> 1
+        let count = unoccupied_home_sc_count(Power::Austria, &state);
+        assert_eq!(
+            count, 1,
+            "Lost home SC should not count, one empty owned should give 1"
+        );
+    }
+
+    #[test]
+    fn unoccupied_home_sc_count_no_scs_owned() {
+        let state = BoardState::empty(1905, Season::Spring, Phase::Movement);
+        let count = unoccupied_home_sc_count(Power::Austria, &state);
+        assert_eq!(count, 0, "No SCs owned should give 0");
+    }
+
+    #[test]
+    fn denial_for_blocking_home_build() {
+        // Germany owns 4 SCs with 3 units away from Munich: it is owed a build.
+        let mut state = BoardState::empty(1902, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Tyr, Power::Austria, UnitType::Army, Coast::None);
+        for prov in [Province::Ber, Province::Kie, Province::Hol] {
+            state.place_unit(prov, Power::Germany, UnitType::Army, Coast::None);
+        }
+        for prov in [Province::Mun, Province::Ber, Province::Kie, Province::Hol] {
+            state.set_sc_owner(prov, Some(Power::Germany));
+        }
+        let fall = SeasonScorer::Fall;
+        let owed = fall.denial(Province::Mun, Power::Austria, &state);
+
+        // Without the extra SC Germany has no pending build.
+        state.set_sc_owner(Province::Hol, None);
+        let not_owed = fall.denial(Province::Mun, Power::Austria, &state);
+
+        assert!(owed > not_owed, "owed={} not_owed={}", owed, not_owed);
+    }
+
+    #[test]
+    fn denial_for_bouncing_capture() {
+        let mut state = BoardState::empty(1902, Season::Fall, Phase::Movement);
+        state.place_unit(Province::Bud, Power::Austria, UnitType::Army, Coast::None);
+        let fall = SeasonScorer::for_state(&state);
+        let quiet = fall.denial(Province::Rum, Power::Austria, &state);
+        state.place_unit(Province::Ukr, Power::Russia, UnitType::Army, Coast::None);
+        let contested = fall.denial(Province::Rum, Power::Austria, &state);
+        assert_eq!(quiet, 0.0);
+        assert!(contested > 0.0);
+
+        // Spring moves don't deny builds.
+        state.set_turn(state.year, Season::Spring, state.phase);
+        let spring = SeasonScorer::for_state(&state);
+        assert_eq!(spring.denial(Province::Rum, Power::Austria, &state), 0.0);
+    }
+}
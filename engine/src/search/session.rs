@@ -0,0 +1,241 @@
+//! Search state kept between RM+ searches.
+//!
+//! Each RM+ search builds candidate pools for every power and learns
+//! regrets over them. A `SearchSession` keeps that work after the search
+//! returns, so it can be reused:
+//!
+//! - Searching the same position for the same power again (a second `go`,
+//!   a search resumed after `stop`, pondering) resumes from the stored pools,
+//!   regrets and strategy weights instead of starting over.
+//! - Reporting several order sets (MultiPV) reads the ranked pool of the
+//!   last search without searching again.
+//! - Searching a new position (the next turn) starts fresh, but each
+//!   power's best stored order sets that are still legal there are added to
+//!   its pool as a warm start.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::board::state::BoardState;
+use crate::board::{orders_hash, Order, Power};
+use crate::resolve::is_legal_order;
+use crate::search::forecast::{forecast, ProvinceForecast};
+use crate::search::regret_matching::PowerCandidates;
+
+/// Stored order sets per power offered to the next position's pools.
+pub(crate) const CARRY_OVER: usize = 4;
+
+/// The pools and learned weights of one finished search.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionPool {
+    pub(crate) state: BoardState,
+    pub(crate) power: Power,
+    pub(crate) candidates: Vec<PowerCandidates>,
+    pub(crate) cum_regrets: Vec<Vec<f64>>,
+    pub(crate) total_weights: Vec<Vec<f64>>,
+    pub(crate) our_idx: usize,
+    pub(crate) iterations: u64,
+}
+
+/// Candidate pools and regrets shared across searches. See the module docs.
+#[derive(Debug, Clone, Default)]
+pub struct SearchSession {
+    pub(crate) last: Option<SessionPool>,
+}
+
+impl SearchSession {
+    /// Creates an empty session.
+    pub fn new() -> Self {
+        SearchSession::default()
+    }
+
+    /// Forgets the stored search, e.g. at the start of a new game.
+    pub fn clear(&mut self) {
+        self.last = None;
+    }
+
+    /// Returns true if a search of `state` for `power` would resume the
+    /// stored one.
+    pub fn is_warm_for(&self, power: Power, state: &BoardState) -> bool {
+        self.last
+            .as_ref()
+            .is_some_and(|l| l.power == power && l.state == *state)
+    }
+
+    /// Hash of the stored pools, regrets and weights, 0 when nothing is
+    /// stored. Two sessions with the same fingerprint start a search the
+    /// same way (see `search::replay`).
+    pub fn fingerprint(&self) -> u64 {
+        let Some(last) = self.last.as_ref() else {
+            return 0;
+        };
+        let mut hasher = DefaultHasher::new();
+        last.state.zobrist().hash(&mut hasher);
+        last.power.hash(&mut hasher);
+        last.our_idx.hash(&mut hasher);
+        last.iterations.hash(&mut hasher);
+        for (pi, (p, cands)) in last.candidates.iter().enumerate() {
+            p.hash(&mut hasher);
+            for c in cands {
+                orders_hash(c).hash(&mut hasher);
+            }
+            for w in last.cum_regrets[pi].iter().chain(&last.total_weights[pi]) {
+                w.to_bits().hash(&mut hasher);
+            }
+        }
+        // Keep 0 for the empty session.
+        hasher.finish().max(1)
+    }
+
+    /// RM+ iterations accumulated on the stored pools, over all resumed
+    /// searches of that position.
+    pub fn iterations(&self) -> u64 {
+        self.last.as_ref().map_or(0, |l| l.iterations)
+    }
+
+    /// The pressure on `power`'s provinces forecast from the stored
+    /// search's equilibrium (see `search::forecast`), `None` unless that
+    /// search was of `state`.
+    pub fn forecast(&self, power: Power, state: &BoardState) -> Option<Vec<ProvinceForecast>> {
+        self.last
+            .as_ref()
+            .filter(|l| l.state == *state)
+            .map(|l| forecast(power, state, &l.candidates, &l.total_weights))
+    }
+
+    /// The searching power's `n` best order sets from the stored search,
+    /// with their average strategy probabilities, best first.
+    pub fn top_order_sets(&self, n: usize) -> Vec<(Vec<Order>, f64)> {
+        let Some(last) = self.last.as_ref() else {
+            return Vec::new();
+        };
+        ranked(
+            &last.candidates[last.our_idx].1,
+            &last.total_weights[last.our_idx],
+        )
+        .into_iter()
+        .take(n)
+        .collect()
+    }
+
+    /// Order sets from the stored search worth offering `power` in `state`:
+    /// up to [`CARRY_OVER`] of its best stored sets whose orders are all
+    /// still legal. Empty when nothing is stored or `state` is the stored
+    /// position.
+    pub(crate) fn carry_over(&self, power: Power, state: &BoardState) -> Vec<Vec<(Order, Power)>> {
+        let Some(last) = self.last.as_ref() else {
+            return Vec::new();
+        };
+        if last.state == *state {
+            return Vec::new();
+        }
+        let Some(pi) = last.candidates.iter().position(|(p, _)| *p == power) else {
+            return Vec::new();
+        };
+        let cands = &last.candidates[pi].1;
+        ranked(cands, &last.total_weights[pi])
+            .into_iter()
+            .map(|(orders, _)| orders)
+            .filter(|orders| {
+                !orders.is_empty() && orders.iter().all(|o| is_legal_order(o, power, state))
+            })
+            .take(CARRY_OVER)
+            .map(|orders| orders.into_iter().map(|o| (o, power)).collect())
+            .collect()
+    }
+}
+
+/// Candidates with their normalized weights, heaviest first.
+fn ranked(cands: &[Vec<(Order, Power)>], weights: &[f64]) -> Vec<(Vec<Order>, f64)> {
+    let total: f64 = weights.iter().sum();
+    let mut out: Vec<(Vec<Order>, f64)> = cands
+        .iter()
+        .zip(weights)
+        .map(|(cand, &w)| {
+            let p = if total > 0.0 {
+                w / total
+            } else {
+                1.0 / cands.len() as f64
+            };
+            (cand.iter().map(|(o, _)| *o).collect(), p)
+        })
+        .collect();
+    out.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+    use crate::protocol::dson::parse_order;
+
+    fn set(orders: &[&str], power: Power) -> Vec<(Order, Power)> {
+        orders
+            .iter()
+            .map(|s| (parse_order(s).unwrap(), power))
+            .collect()
+    }
+
+    fn session_for(state: &BoardState) -> SearchSession {
+        SearchSession {
+            last: Some(SessionPool {
+                state: state.clone(),
+                power: Power::Austria,
+                candidates: vec![(
+                    Power::Austria,
+                    vec![
+                        set(&["A vie - gal", "A bud - ser"], Power::Austria),
+                        set(&["A vie H", "A bud H"], Power::Austria),
+                        set(&["A vie - tyr", "A bud - rum"], Power::Austria),
+                    ],
+                )],
+                cum_regrets: vec![vec![1.0; 3]],
+                total_weights: vec![vec![1.0, 6.0, 3.0]],
+                our_idx: 0,
+                iterations: 48,
+            }),
+        }
+    }
+
+    #[test]
+    fn ranks_stored_order_sets() {
+        let state = parse_dfen("1901sm/Aavie,Aabud/Avie,Abud/-").unwrap();
+        let session = session_for(&state);
+        assert!(session.is_warm_for(Power::Austria, &state));
+        assert!(!session.is_warm_for(Power::Russia, &state));
+        assert_eq!(session.iterations(), 48);
+
+        let top = session.top_order_sets(2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(
+            top[0].0,
+            vec![
+                parse_order("A vie H").unwrap(),
+                parse_order("A bud H").unwrap()
+            ]
+        );
+        assert!((top[0].1 - 0.6).abs() < 1e-9);
+        assert!((top[1].1 - 0.3).abs() < 1e-9);
+        assert!(SearchSession::new().top_order_sets(3).is_empty());
+    }
+
+    #[test]
+    fn carries_over_only_still_legal_sets() {
+        let before = parse_dfen("1901sm/Aavie,Aabud/Avie,Abud/-").unwrap();
+        let session = session_for(&before);
+        assert!(session.carry_over(Power::Austria, &before).is_empty());
+
+        // A bud moved to ser: only sets not ordering A bud could stay legal,
+        // and every stored set orders it.
+        let moved = parse_dfen("1901fm/Aavie,Aaser/Avie,Abud/-").unwrap();
+        assert!(session.carry_over(Power::Austria, &moved).is_empty());
+
+        // Same units in the fall: every stored set is legal again.
+        let fall = parse_dfen("1901fm/Aavie,Aabud/Avie,Abud/-").unwrap();
+        let carried = session.carry_over(Power::Austria, &fall);
+        assert_eq!(carried.len(), 3);
+        assert_eq!(carried[0], set(&["A vie H", "A bud H"], Power::Austria));
+        assert!(session.carry_over(Power::Russia, &fall).is_empty());
+    }
+}
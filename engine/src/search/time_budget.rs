@@ -0,0 +1,144 @@
+//! Per-power time budgeting for multi-power order computation.
+//!
+//! When one process computes orders for several powers in the same phase
+//! (self-play, arena runs), an equal split wastes time on powers with one
+//! unit in a quiet corner while starving large powers in the middle of a
+//! fight. The scheduler splits the phase budget proportionally to each
+//! power's unit count plus the number of supply centers it is contesting,
+//! after reserving a floor so every power still gets a meaningful search.
+
+use std::time::Duration;
+
+use crate::board::province::Power;
+use crate::board::state::BoardState;
+use crate::eval::heuristic::{province_defense, province_threat};
+
+/// Fraction of the total budget split equally before weighting.
+const FLOOR_FRACTION: f64 = 0.25;
+
+/// Weight of one contested supply center relative to one unit.
+const CONTESTED_SC_WEIGHT: f64 = 1.5;
+
+/// Counts a power's units on the board.
+fn unit_count(state: &BoardState, power: Power) -> usize {
+    state
+        .units
+        .iter()
+        .filter(|u| matches!(u, Some((p, _)) if *p == power))
+        .count()
+}
+
+/// Counts supply centers a power is fighting over: its own centers an enemy
+/// can reach, and foreign or neutral centers it can reach while an enemy
+/// can too.
+pub fn contested_scs(state: &BoardState, power: Power) -> usize {
+    state
+        .map()
+        .provinces()
+        .iter()
+        .filter(|p| state.map().is_supply_center(**p))
+        .filter(|&&prov| {
+            if province_threat(prov, power, state) == 0 {
+                return false;
+            }
+            let owned = state.sc_owner[prov.index()] == Some(power);
+            let occupied_by_us = matches!(state.units[prov.index()], Some((p, _)) if p == power);
+            owned || occupied_by_us || province_defense(prov, power, state) > 0
+        })
+        .count()
+}
+
+/// Scheduling weight of a power: units plus weighted contested centers.
+pub fn criticality(state: &BoardState, power: Power) -> f64 {
+    unit_count(state, power) as f64 + CONTESTED_SC_WEIGHT * contested_scs(state, power) as f64
+}
+
+/// Splits `total` across `powers` by criticality.
+///
+/// Every power receives an equal share of `FLOOR_FRACTION` of the budget;
+/// the rest is divided in proportion to [`criticality`]. Budgets are in
+/// whole milliseconds and sum to at most `total`. Returns an equal split
+/// when no power has any weight.
+pub fn split_movetime(
+    state: &BoardState,
+    powers: &[Power],
+    total: Duration,
+) -> Vec<(Power, Duration)> {
+    if powers.is_empty() {
+        return Vec::new();
+    }
+    let total_ms = total.as_millis() as f64;
+    let n = powers.len() as f64;
+    let weights: Vec<f64> = powers.iter().map(|&p| criticality(state, p)).collect();
+    let weight_sum: f64 = weights.iter().sum();
+
+    powers
+        .iter()
+        .zip(weights.iter())
+        .map(|(&power, &w)| {
+            let ms = if weight_sum > 0.0 {
+                total_ms * (FLOOR_FRACTION / n + (1.0 - FLOOR_FRACTION) * w / weight_sum)
+            } else {
+                total_ms / n
+            };
+            (power, Duration::from_millis(ms.floor() as u64))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::ALL_POWERS;
+    use crate::protocol::dfen::parse_dfen;
+
+    const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+    #[test]
+    fn split_favors_large_embattled_power() {
+        // Germany: four units around contested Mun/Ber; England: one fleet
+        // far from anyone.
+        let state =
+            parse_dfen("1905fm/Gamun,Gaber,Gfkie,Gasil,Faboh,Fabur,Rapru,Efnao/Gmun,Gber,Gkie,Fpar,Rwar,Elon/-")
+                .unwrap();
+        let powers = [Power::Germany, Power::England];
+        let budgets = split_movetime(&state, &powers, Duration::from_millis(4000));
+        assert_eq!(budgets[0].0, Power::Germany);
+        assert!(budgets[0].1 > budgets[1].1, "{:?}", budgets);
+        // The floor keeps the quiet power searching.
+        assert!(budgets[1].1 >= Duration::from_millis(500), "{:?}", budgets);
+    }
+
+    #[test]
+    fn split_never_exceeds_total() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let total = Duration::from_millis(7001);
+        let budgets = split_movetime(&state, &ALL_POWERS, total);
+        assert_eq!(budgets.len(), 7);
+        let sum: Duration = budgets.iter().map(|(_, d)| *d).sum();
+        assert!(sum <= total && sum >= total - Duration::from_millis(7));
+        // Russia has four units and contests Rumania; England sits quietly
+        // behind the Channel with three.
+        let russia = budgets.iter().find(|(p, _)| *p == Power::Russia).unwrap().1;
+        let england = budgets
+            .iter()
+            .find(|(p, _)| *p == Power::England)
+            .unwrap()
+            .1;
+        assert!(russia > england);
+    }
+
+    #[test]
+    fn split_without_weight_is_equal() {
+        let state = parse_dfen(INITIAL_DFEN).unwrap();
+        let empty = BoardState::empty(state.year, state.season, state.phase);
+        let budgets = split_movetime(
+            &empty,
+            &[Power::Austria, Power::Turkey],
+            Duration::from_millis(1000),
+        );
+        assert_eq!(budgets[0].1, Duration::from_millis(500));
+        assert_eq!(budgets[1].1, Duration::from_millis(500));
+        assert!(split_movetime(&state, &[], Duration::from_millis(1000)).is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+//! Projected supply center trajectories.
+//!
+//! After an RM+ search, the engine rolls the position forward several game
+//! years from profiles sampled out of the equilibrium strategies and averages
+//! each power's SC count at the end of every year. Hosts chart the result
+//! live as an expected game trajectory.
+
+use serde_json::{json, Map, Value};
+
+use crate::board::province::{Power, ALL_POWERS};
+use crate::board::state::BoardState;
+use crate::eval::heuristic::count_scs;
+
+/// Average end-of-year SC counts per power over a set of rollouts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScTrajectory {
+    /// Game years covered, ascending.
+    pub years: Vec<u16>,
+    /// Mean SC count per power at the end of each year, indexed by
+    /// `Power as usize`. Parallel to `years`.
+    pub counts: Vec<[f32; 7]>,
+    /// Number of rollouts averaged.
+    pub rollouts: usize,
+}
+
+impl ScTrajectory {
+    /// Creates an empty trajectory covering `years` game years from `first_year`.
+    pub fn new(first_year: u16, years: usize) -> Self {
+        ScTrajectory {
+            years: (0..years as u16).map(|y| first_year + y).collect(),
+            counts: vec![[0.0; 7]; years],
+            rollouts: 0,
+        }
+    }
+
+    /// Adds one rollout's end-of-year snapshots (parallel to `years`; a
+    /// rollout cut short repeats its last snapshot for the remaining years).
+    pub fn add_rollout(&mut self, snapshots: &[BoardState]) {
+        if snapshots.is_empty() {
+            return;
+        }
+        let n = self.rollouts as f32;
+        for (yi, slot) in self.counts.iter_mut().enumerate() {
+            let snap = &snapshots[yi.min(snapshots.len() - 1)];
+            for &p in ALL_POWERS.iter() {
+                let c = count_scs(snap, p) as f32;
+                let i = p as usize;
+                slot[i] = (slot[i] * n + c) / (n + 1.0);
+            }
+        }
+        self.rollouts += 1;
+    }
+
+    /// Returns the projected SC count for a power at the end of a year.
+    pub fn projected(&self, power: Power, year: u16) -> Option<f32> {
+        let yi = self.years.iter().position(|&y| y == year)?;
+        Some(self.counts[yi][power as usize])
+    }
+
+    /// Serializes the trajectory as a single-line JSON object.
+    ///
+    /// Format: `{"years":[1901,1902],"rollouts":16,"powers":{"austria":[4.0,4.5],...}}`
+    /// with counts rounded to two decimals.
+    pub fn to_json(&self) -> String {
+        let mut powers = Map::new();
+        for &p in ALL_POWERS.iter() {
+            let series: Vec<Value> = self
+                .counts
+                .iter()
+                .map(|c| json!((c[p as usize] * 100.0).round() / 100.0))
+                .collect();
+            powers.insert(p.name().to_string(), Value::Array(series));
+        }
+        json!({
+            "years": self.years,
+            "rollouts": self.rollouts,
+            "powers": powers,
+        })
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::Province;
+    use crate::board::state::{Phase, Season};
+
+    fn state_with_scs(year: u16, austria: &[Province]) -> BoardState {
+        let mut s = BoardState::empty(year, Season::Spring, Phase::Movement);
+        for &p in austria {
+            s.set_sc_owner(p, Some(Power::Austria));
+        }
+        s
+    }
+
+    #[test]
+    fn add_rollout_averages_counts() {
+        let mut t = ScTrajectory::new(1901, 2);
+        t.add_rollout(&[
+            state_with_scs(1902, &[Province::Vie, Province::Bud]),
+            state_with_scs(1903, &[Province::Vie, Province::Bud, Province::Tri]),
+        ]);
+        t.add_rollout(&[
+            state_with_scs(1902, &[Province::Vie]),
+            state_with_scs(1903, &[Province::Vie]),
+        ]);
+        assert_eq!(t.rollouts, 2);
+        assert_eq!(t.projected(Power::Austria, 1901), Some(1.5));
+        assert_eq!(t.projected(Power::Austria, 1902), Some(2.0));
+        assert_eq!(t.projected(Power::Russia, 1902), Some(0.0));
+        assert_eq!(t.projected(Power::Austria, 1905), None);
+    }
+
+    #[test]
+    fn short_rollout_repeats_last_snapshot() {
+        let mut t = ScTrajectory::new(1901, 3);
+        t.add_rollout(&[state_with_scs(1902, &[Province::Vie])]);
+        assert_eq!(t.projected(Power::Austria, 1903), Some(1.0));
+    }
+
+    #[test]
+    fn to_json_lists_every_power() {
+        let mut t = ScTrajectory::new(1901, 1);
+        t.add_rollout(&[state_with_scs(1902, &[Province::Vie])]);
+        let v: Value = serde_json::from_str(&t.to_json()).unwrap();
+        assert_eq!(v["years"][0], 1901);
+        assert_eq!(v["rollouts"], 1);
+        assert_eq!(v["powers"]["austria"][0], 1.0);
+        assert_eq!(v["powers"].as_object().unwrap().len(), 7);
+    }
+}
@@ -0,0 +1,179 @@
+//! Transposition table shared by all threads of one search.
+//!
+//! The search reaches the same boards many times: different candidate
+//! pairings transpose into one position, and the lookahead from those
+//! positions replays the same greedy turns. The table keys those boards by
+//! their Zobrist hash (see `board::zobrist`) and remembers, per board, the
+//! greedy orders every power plays there and the evaluations made of it,
+//! so the work is done once per search instead of once per thread or
+//! iteration. The table is sharded behind mutexes; a shard that fills up
+//! is cleared, as the per-task caches it replaces were.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::board::order::Order;
+use crate::board::province::Power;
+use crate::board::state::BoardState;
+
+/// Number of independently locked shards.
+const SHARDS: usize = 16;
+
+/// Seeds the key of an evaluation so it differs from the board's own key.
+const EVAL_SALT: u64 = 0x6576_616c_7561_7465;
+
+/// A sharded map from board hash to a cached value.
+struct ShardedMap<V> {
+    shards: Vec<Mutex<HashMap<u64, V>>>,
+    /// Entries per shard before it is cleared.
+    shard_capacity: usize,
+    lookups: AtomicU64,
+    hits: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<V: Clone> ShardedMap<V> {
+    fn new(capacity: usize) -> Self {
+        ShardedMap {
+            shards: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+            shard_capacity: capacity.div_ceil(SHARDS).max(1),
+            lookups: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, key: u64) -> std::sync::MutexGuard<'_, HashMap<u64, V>> {
+        // The low bits pick the hash map bucket; shard on the high ones.
+        let shard = &self.shards[(key >> 58) as usize % SHARDS];
+        shard.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn get(&self, key: u64) -> Option<V> {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        let v = self.shard(key).get(&key).cloned();
+        if v.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        v
+    }
+
+    fn insert(&self, key: u64, value: V) {
+        let mut shard = self.shard(key);
+        if shard.len() >= self.shard_capacity {
+            self.evictions
+                .fetch_add(shard.len() as u64, Ordering::Relaxed);
+            shard.clear();
+        }
+        shard.insert(key, value);
+    }
+
+    fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| s.lock().map_or(0, |s| s.len()))
+            .sum()
+    }
+
+    fn stats(&self) -> TableStats {
+        TableStats {
+            entries: self.len(),
+            lookups: self.lookups.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters of one of the table's caches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub entries: usize,
+    pub lookups: u64,
+    pub hits: u64,
+    pub evictions: u64,
+}
+
+/// Greedy orders and evaluations per board, shared across a search.
+pub struct TranspositionTable {
+    orders: ShardedMap<Vec<(Order, Power)>>,
+    evals: ShardedMap<f64>,
+}
+
+impl TranspositionTable {
+    /// A table holding up to `order_entries` greedy order sets and
+    /// `eval_entries` evaluations.
+    pub fn new(order_entries: usize, eval_entries: usize) -> Self {
+        TranspositionTable {
+            orders: ShardedMap::new(order_entries),
+            evals: ShardedMap::new(eval_entries),
+        }
+    }
+
+    /// Greedy orders cached for `state`.
+    pub fn orders(&self, state: &BoardState) -> Option<Vec<(Order, Power)>> {
+        self.orders.get(state.zobrist())
+    }
+
+    /// Caches the greedy orders for `state`.
+    pub fn store_orders(&self, state: &BoardState, orders: Vec<(Order, Power)>) {
+        self.orders.insert(state.zobrist(), orders);
+    }
+
+    /// `power`'s evaluation of `state`, computed with `eval` on a miss.
+    pub fn evaluate(&self, power: Power, state: &BoardState, eval: impl FnOnce() -> f64) -> f64 {
+        let key = state.zobrist() ^ EVAL_SALT.rotate_left(power as u32 * 9);
+        if let Some(v) = self.evals.get(key) {
+            return v;
+        }
+        let v = eval();
+        self.evals.insert(key, v);
+        v
+    }
+
+    /// Counters of the greedy order cache.
+    pub fn order_stats(&self) -> TableStats {
+        self.orders.stats()
+    }
+
+    /// Counters of the evaluation cache.
+    pub fn eval_stats(&self) -> TableStats {
+        self.evals.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::dfen::parse_dfen;
+
+    #[test]
+    fn caches_evaluations_per_power_and_board() {
+        let table = TranspositionTable::new(64, 64);
+        let a = parse_dfen("1901sm/Aavie,Rawar/Avie,Rwar/-").unwrap();
+        let b = parse_dfen("1901sm/Aabud,Rawar/Avie,Rwar/-").unwrap();
+
+        assert_eq!(table.evaluate(Power::Austria, &a, || 1.0), 1.0);
+        assert_eq!(table.evaluate(Power::Austria, &a, || 2.0), 1.0);
+        assert_eq!(table.evaluate(Power::Russia, &a, || 3.0), 3.0);
+        assert_eq!(table.evaluate(Power::Austria, &b, || 4.0), 4.0);
+
+        let stats = table.eval_stats();
+        assert_eq!((stats.entries, stats.lookups, stats.hits), (3, 4, 1));
+    }
+
+    #[test]
+    fn full_shards_are_cleared() {
+        let table = TranspositionTable::new(SHARDS, SHARDS);
+        let mut state = parse_dfen("1901sm/Aavie/Avie/-").unwrap();
+        for year in 1901..1901 + 4 * SHARDS as u16 {
+            state.set_turn(year, state.season, state.phase);
+            table.store_orders(&state, Vec::new());
+        }
+        let stats = table.order_stats();
+        assert!(stats.entries <= SHARDS);
+        assert!(stats.evictions > 0);
+        assert_eq!(table.orders(&state), Some(Vec::new()));
+    }
+}
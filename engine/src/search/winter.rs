@@ -0,0 +1,224 @@
+//! Build-phase search.
+//!
+//! The build heuristic scores sites one at a time from static features, so
+//! whether a new unit should be a fleet or an army, and at which home
+//! center, never depends on what it could do next turn. [`winter_search`]
+//! tries each feasible adjustment instead: every way to fill the build
+//! slots from the open home centers, or every choice of disbands among the
+//! units the heuristic values least. For each it plays the other powers'
+//! adjustments with the heuristic, simulates the next Spring with the
+//! greedy lookahead, and keeps the adjustment whose Spring evaluates best
+//! for us. The heuristic's own choice is always tried, first, and wins
+//! ties.
+
+use std::sync::Arc;
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use crate::board::order::Order;
+use crate::board::province::{Power, Province, ALL_POWERS};
+use crate::board::state::{BoardState, Phase};
+use crate::eval::evaluate;
+use crate::movegen::build::legal_builds;
+use crate::resolve::{advance_state, apply_builds, resolve_builds, Resolver};
+use crate::search::cartesian::{heuristic_build_orders, heuristic_disbands};
+use crate::search::regret_matching::{simulate_n_phases, GreedyOrderCache};
+use crate::search::transposition::TranspositionTable;
+
+/// Most adjustments simulated per search.
+pub const MAX_COMBINATIONS: usize = 96;
+
+/// Units beyond the number to disband that are considered for disbanding.
+const DISBAND_SPARES: usize = 3;
+
+/// Movement phases simulated after the winter.
+const SPRING_PLIES: usize = 1;
+
+/// The best adjustment orders for `power` in a Build phase, judged by the
+/// Spring that follows. Empty when it has nothing to adjust.
+pub fn winter_search(power: Power, state: &BoardState) -> Vec<Order> {
+    let heuristic = heuristic_build_orders(power, state);
+    if heuristic.is_empty() || state.phase != Phase::Build {
+        return heuristic;
+    }
+    let mut combos = vec![heuristic.clone()];
+    for combo in adjustments(power, state, heuristic.len()) {
+        if combos.len() >= MAX_COMBINATIONS {
+            break;
+        }
+        if !combos.iter().any(|c| same_orders(c, &combo)) {
+            combos.push(combo);
+        }
+    }
+    if combos.len() == 1 {
+        return heuristic;
+    }
+
+    let others: Vec<(Order, Power)> = ALL_POWERS
+        .iter()
+        .filter(|&&p| p != power)
+        .flat_map(|&p| {
+            heuristic_build_orders(p, state)
+                .into_iter()
+                .map(move |o| (o, p))
+        })
+        .collect();
+    let mut cache = GreedyOrderCache::new(Arc::new(TranspositionTable::new(256, 16)), true);
+    let mut resolver = Resolver::new(64);
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut best = (f32::NEG_INFINITY, 0);
+    for (i, combo) in combos.iter().enumerate() {
+        let mut spring = state.clone();
+        let orders: Vec<(Order, Power)> = combo
+            .iter()
+            .map(|&o| (o, power))
+            .chain(others.iter().copied())
+            .collect();
+        let results = resolve_builds(&orders, &spring);
+        apply_builds(&mut spring, &results);
+        advance_state(&mut spring, false);
+        let year = spring.year;
+        let after = simulate_n_phases(
+            &spring,
+            power,
+            &mut resolver,
+            SPRING_PLIES,
+            year,
+            &mut rng,
+            &mut cache,
+        );
+        let score = evaluate(power, &after);
+        if score > best.0 {
+            best = (score, i);
+        }
+    }
+    combos.swap_remove(best.1)
+}
+
+/// Feasible adjustments of `count` orders for `power`: builds on distinct
+/// open home centers (waiving slots there are no sites for), or disbands
+/// among its least valuable units.
+fn adjustments(power: Power, state: &BoardState, count: usize) -> Vec<Vec<Order>> {
+    let legal = legal_builds(power, state);
+    if legal.iter().any(|o| matches!(o, Order::Disband { .. })) {
+        let pool = heuristic_disbands(power, state, &legal, count + DISBAND_SPARES);
+        let mut out = Vec::new();
+        choose(&pool, count, &mut Vec::new(), &mut out);
+        return out;
+    }
+
+    // Build variants grouped by site.
+    let mut sites: Vec<(Province, Vec<Order>)> = Vec::new();
+    for order in legal {
+        let Order::Build { unit } = order else {
+            continue;
+        };
+        let prov = unit.location.province;
+        match sites.iter_mut().find(|(p, _)| *p == prov) {
+            Some((_, variants)) => variants.push(order),
+            None => sites.push((prov, vec![order])),
+        }
+    }
+    let mut chosen = Vec::new();
+    choose(&sites, count.min(sites.len()), &mut Vec::new(), &mut chosen);
+    let mut out = Vec::new();
+    for group in chosen {
+        let mut combos: Vec<Vec<Order>> = vec![Vec::new()];
+        for (_, variants) in &group {
+            combos = combos
+                .into_iter()
+                .flat_map(|c| {
+                    variants.iter().map(move |&v| {
+                        let mut next = c.clone();
+                        next.push(v);
+                        next
+                    })
+                })
+                .collect();
+        }
+        for mut combo in combos {
+            combo.resize(count, Order::Waive);
+            out.push(combo);
+        }
+    }
+    out
+}
+
+/// Appends to `out` every `k`-element subset of `items`, in order.
+fn choose<T: Clone>(items: &[T], k: usize, current: &mut Vec<T>, out: &mut Vec<Vec<T>>) {
+    if current.len() == k {
+        out.push(current.clone());
+        return;
+    }
+    let needed = k - current.len();
+    for i in 0..items.len() {
+        if items.len() - i < needed {
+            break;
+        }
+        current.push(items[i].clone());
+        choose(&items[i + 1..], k, current, out);
+        current.pop();
+    }
+}
+
+/// Whether two adjustments hold the same orders in any order.
+fn same_orders(a: &[Order], b: &[Order]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|o| a.iter().filter(|x| *x == o).count() == b.iter().filter(|x| *x == o).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::province::Coast;
+    use crate::board::state::Season;
+    use crate::board::unit::UnitType;
+
+    #[test]
+    fn enumerates_every_build_and_disband_choice() {
+        // Russia owns all four home centers with one unit: three builds
+        // over Stp (army or two fleets), Mos, War and Sev (army or fleet).
+        let mut state = BoardState::empty(1901, Season::Fall, Phase::Build);
+        for c in [Province::Stp, Province::Mos, Province::War, Province::Sev] {
+            state.set_sc_owner(c, Some(Power::Russia));
+        }
+        state.place_unit(Province::Ukr, Power::Russia, UnitType::Army, Coast::None);
+        let builds = adjustments(Power::Russia, &state, 3);
+        // Site triples: {stp,mos,war} 3, {stp,mos,sev} 6, {stp,war,sev} 6,
+        // {mos,war,sev} 2.
+        assert_eq!(builds.len(), 17);
+        assert!(builds.iter().all(|c| c.len() == 3));
+
+        // Austria with one center and four units disbands three of them.
+        let mut state = BoardState::empty(1901, Season::Fall, Phase::Build);
+        state.set_sc_owner(Province::Vie, Some(Power::Austria));
+        for p in [Province::Vie, Province::Bud, Province::Gal, Province::Ser] {
+            state.place_unit(p, Power::Austria, UnitType::Army, Coast::None);
+        }
+        let disbands = adjustments(Power::Austria, &state, 3);
+        assert_eq!(disbands.len(), 4);
+        let reversed: Vec<Order> = disbands[0].iter().rev().copied().collect();
+        assert!(same_orders(&disbands[0], &reversed));
+        assert!(!same_orders(&disbands[0], &disbands[1]));
+    }
+
+    #[test]
+    fn searches_a_legal_adjustment() {
+        let mut state = BoardState::empty(1901, Season::Fall, Phase::Build);
+        for c in [Province::Lon, Province::Edi, Province::Lvp, Province::Nwy] {
+            state.set_sc_owner(c, Some(Power::England));
+        }
+        state.place_unit(Province::Nwy, Power::England, UnitType::Fleet, Coast::None);
+        state.place_unit(Province::Nth, Power::England, UnitType::Fleet, Coast::None);
+        state.place_unit(Province::Bre, Power::France, UnitType::Fleet, Coast::None);
+        state.set_sc_owner(Province::Bre, Some(Power::France));
+
+        let orders = winter_search(Power::England, &state);
+        assert_eq!(orders.len(), 2);
+        let legal = legal_builds(Power::England, &state);
+        assert!(orders.iter().all(|o| legal.contains(o)));
+        assert!(winter_search(Power::France, &state).is_empty());
+    }
+}
@@ -3,27 +3,42 @@
 //! Plays full Diplomacy games by cycling through all seven powers each phase,
 //! using the engine's search to select orders. Records DFEN states, orders,
 //! value estimates, and SC counts per phase for reinforcement learning.
+//!
+//! Every seat plays with the run's strength and default search options
+//! unless `SelfPlayConfig::seats` gives it a [`Persona`], so a single process
+//! can pit differently configured powers against each other (e.g. a neural
+//! Austria against six heuristic opponents) for ablation runs. With
+//! `SelfPlayConfig::duel` only two powers play, under a [`Duel`]'s rules,
+//! and with `SelfPlayConfig::script` the powers an [`OpponentScript`]
+//! names play its fixed orders.
 
 use std::io::Write;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 
-use crate::board::province::{Power, ALL_POWERS};
+use crate::analysis::summary;
+use crate::board::province::{Power, Province, ALL_POWERS};
 use crate::board::state::{BoardState, Phase};
 use crate::board::Order;
-use crate::eval::evaluate_all;
+use crate::eval::{evaluate_all, solo_distance, NeuralEvaluator};
 use crate::movegen::random_orders;
-use crate::protocol::dfen::{encode_dfen, parse_dfen};
+use crate::protocol::dfen::{diff, encode_dfen, parse_dfen};
 use crate::protocol::dson::format_orders;
 use crate::resolve::{
-    advance_state, apply_builds, apply_resolution, apply_retreats, is_game_over, needs_build_phase,
-    resolve_builds, resolve_retreats, Resolver,
+    advance_state, apply_builds, apply_resolution, apply_retreats, destroyed_units, is_game_over,
+    needs_build_phase, resolve_builds, resolve_retreats, sanitize_orders, Destruction, Duel,
+    Resolver,
 };
+use crate::rng::entropy_rng;
+use crate::script::OpponentScript;
+use crate::search::opening_priors::OpeningStyle;
 use crate::search::{
-    heuristic_build_orders, heuristic_retreat_orders, regret_matching_search, search,
+    heuristic_build_orders, heuristic_retreat_orders, regret_matching_search_with_options, search,
+    split_movetime, RmOptions,
 };
 
 /// Standard opening DFEN for a new game.
@@ -34,8 +49,12 @@ const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fap
 pub struct SelfPlayConfig {
     /// Number of games to play.
     pub num_games: usize,
-    /// Time budget per move search (milliseconds).
+    /// Time budget per move search (milliseconds). With `asymmetric_time`
+    /// this is the average per power; the phase total is split by criticality.
     pub movetime_ms: u64,
+    /// Split each movement phase's budget across powers by unit count and
+    /// contested SCs instead of giving every power `movetime_ms`.
+    pub asymmetric_time: bool,
     /// Engine strength (1-100). Controls heuristic vs neural blend.
     pub strength: u64,
     /// Maximum game year before forced termination.
@@ -60,6 +79,129 @@ pub struct SelfPlayConfig {
     pub seed: u64,
     /// Suppress per-game progress output.
     pub quiet: bool,
+    /// Per-seat personas. Powers without an entry play as
+    /// `Persona::default_for(strength)`.
+    pub seats: Vec<(Power, Persona)>,
+    /// Play two-power duels instead of full games: the other five powers
+    /// are removed from the start and the duel's win conditions apply.
+    pub duel: Option<Duel>,
+    /// Fixed orders for some powers; the powers it names play them instead
+    /// of searching.
+    pub script: Option<Arc<OpponentScript>>,
+}
+
+impl SelfPlayConfig {
+    /// Returns the persona `power` plays with in this run.
+    pub fn persona(&self, power: Power) -> Persona {
+        self.seats
+            .iter()
+            .rev()
+            .find(|(p, _)| *p == power)
+            .map(|(_, persona)| persona.clone())
+            .unwrap_or_else(|| Persona::default_for(self.strength))
+    }
+
+    /// Persona names for every seat, in `ALL_POWERS` order, or empty when
+    /// no seat has its own persona.
+    pub fn seat_names(&self) -> Vec<(Power, String)> {
+        if self.seats.is_empty() {
+            return Vec::new();
+        }
+        ALL_POWERS
+            .iter()
+            .map(|&p| (p, self.persona(p).name))
+            .collect()
+    }
+}
+
+/// How one seat plays: search strength, RM+ options and, optionally, a
+/// neural model.
+#[derive(Clone)]
+pub struct Persona {
+    /// Label written to game records and summaries.
+    pub name: String,
+    /// Engine strength (1-100). Seats at 80 or above use RM+ search,
+    /// weaker seats the cartesian search.
+    pub strength: u64,
+    /// Directory holding `policy_v2.onnx` and `value_v2.onnx`, if this seat
+    /// evaluates with a neural model.
+    pub model: Option<String>,
+    /// RM+ search options for this seat.
+    pub options: RmOptions,
+    neural: Option<Arc<NeuralEvaluator>>,
+}
+
+impl Persona {
+    /// The persona a seat without its own plays with.
+    pub fn default_for(strength: u64) -> Self {
+        Persona {
+            name: "default".to_string(),
+            strength,
+            model: None,
+            options: RmOptions::default(),
+            neural: None,
+        }
+    }
+
+    /// Parses a persona spec: comma-separated `key:value` pairs over the
+    /// run's defaults. Keys are `name`, `strength`, `model`, the RM+
+    /// toggles `priors`, `plans`, `buffers`, `supports` and `early_exit`,
+    /// and `corner`, the opening style England or Turkey plays
+    /// (`standard`, `consolidate` or `aggressive`).
+    /// The name defaults to the spec itself. A `model` is loaded here, once
+    /// for the whole run.
+    pub fn parse(spec: &str, strength: u64) -> Result<Self, String> {
+        let mut persona = Persona {
+            name: spec.to_string(),
+            ..Persona::default_for(strength)
+        };
+        for pair in spec.split(',').filter(|p| !p.is_empty()) {
+            let (key, value) = pair
+                .split_once(':')
+                .ok_or_else(|| format!("expected key:value, got '{}'", pair))?;
+            let flag = || match value {
+                "true" | "on" => Ok(true),
+                "false" | "off" => Ok(false),
+                _ => Err(format!("invalid {} value '{}'", key, value)),
+            };
+            match key {
+                "name" => persona.name = value.to_string(),
+                "strength" => {
+                    persona.strength = value
+                        .parse()
+                        .map_err(|_| format!("invalid strength '{}'", value))?
+                }
+                "model" => persona.model = Some(value.to_string()),
+                "priors" => persona.options.opening_priors = flag()?,
+                "plans" => persona.options.group_plans = flag()?,
+                "buffers" => persona.options.buffer_states = flag()?,
+                "supports" => persona.options.lookahead_supports = flag()?,
+                "early_exit" => persona.options.early_exit = flag()?,
+                "corner" => {
+                    persona.options.corner_opening = OpeningStyle::parse(value)
+                        .ok_or_else(|| format!("invalid corner value '{}'", value))?
+                }
+                _ => return Err(format!("unknown persona key '{}'", key)),
+            }
+        }
+        if let Some(dir) = &persona.model {
+            persona.neural = Some(Arc::new(NeuralEvaluator::new(
+                Some(&format!("{}/policy_v2.onnx", dir)),
+                Some(&format!("{}/value_v2.onnx", dir)),
+            )));
+        }
+        Ok(persona)
+    }
+
+    /// Parses a `--seat` argument, `<power>=<spec>`.
+    pub fn parse_seat(arg: &str, strength: u64) -> Result<(Power, Self), String> {
+        let (power, spec) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("expected power=spec, got '{}'", arg))?;
+        let power = Power::from_name(&power.to_ascii_lowercase())
+            .ok_or_else(|| format!("unknown power '{}'", power))?;
+        Ok((power, Persona::parse(spec, strength)?))
+    }
 }
 
 impl Default for SelfPlayConfig {
@@ -67,6 +209,7 @@ impl Default for SelfPlayConfig {
         SelfPlayConfig {
             num_games: 10,
             movetime_ms: 2000,
+            asymmetric_time: true,
             strength: 100,
             max_year: 1920,
             temperature: 1.0,
@@ -79,6 +222,9 @@ impl Default for SelfPlayConfig {
             threads: 4,
             seed: 0,
             quiet: false,
+            seats: Vec::new(),
+            duel: None,
+            script: None,
         }
     }
 }
@@ -100,6 +246,8 @@ pub struct PhaseRecord {
     pub values: [f32; 7],
     /// SC counts for each power at this state.
     pub sc_counts: [i32; 7],
+    /// Units removed in a retreat phase: owner, province, and cause.
+    pub destroyed: Vec<(Power, Province, Destruction)>,
 }
 
 /// Quality flags for a completed game.
@@ -128,6 +276,9 @@ pub struct GameRecord {
     pub final_year: u16,
     /// Quality assessment.
     pub quality: GameQuality,
+    /// Persona name of every seat, or empty when all seats used the
+    /// run's defaults.
+    pub personas: Vec<(Power, String)>,
 }
 
 /// Counts supply centers for each power.
@@ -208,12 +359,16 @@ fn gamma_sample(rng: &mut SmallRng, alpha: f64) -> f64 {
 /// Plays a single self-play game and returns the game record.
 pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) -> GameRecord {
     let mut state = parse_dfen(INITIAL_DFEN).expect("failed to parse initial DFEN");
+    if let Some(duel) = &config.duel {
+        duel.restrict(&mut state);
+    }
     let mut resolver = Resolver::new(64);
     let mut phases: Vec<PhaseRecord> = Vec::new();
     let mut prev_year_scs = sc_counts(&state);
     let mut stalemate_count = 0u32;
     let mut winner: Option<Power> = None;
     let mut quality = GameQuality::default();
+    let personas: Vec<Persona> = ALL_POWERS.iter().map(|&p| config.persona(p)).collect();
 
     // Compute effective temperature per year (decays over time).
     let base_temp = config.temperature;
@@ -227,7 +382,11 @@ pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) ->
         if state.year > config.max_year {
             break;
         }
-        if let Some(w) = is_game_over(&state) {
+        let decided = match &config.duel {
+            Some(duel) => duel.winner(&state),
+            None => is_game_over(&state),
+        };
+        if let Some(w) = decided {
             winner = Some(w);
             break;
         }
@@ -253,24 +412,45 @@ pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) ->
         // Collect orders for all alive powers.
         let mut phase_orders: Vec<(Power, String)> = Vec::new();
         let mut all_orders: Vec<(Order, Power)> = Vec::new();
+        let mut destroyed = Vec::new();
 
         match state.phase {
             Phase::Movement => {
+                let scripted = |p: Power| config.script.as_ref().is_some_and(|s| s.is_scripted(p));
+                let alive: Vec<Power> = ALL_POWERS
+                    .iter()
+                    .copied()
+                    .filter(|&p| power_has_units(&state, p) && !scripted(p))
+                    .collect();
                 for &power in ALL_POWERS.iter() {
-                    if !power_has_units(&state, power) {
-                        continue;
+                    if scripted(power) && power_has_units(&state, power) {
+                        let orders = scripted_orders(config, power, &state);
+                        phase_orders.push((power, format_orders(&orders)));
+                        all_orders.extend(orders.into_iter().map(|o| (o, power)));
                     }
-
-                    let result = if config.strength >= 80 {
-                        regret_matching_search(
+                }
+                let budgets: Vec<(Power, Duration)> = if config.asymmetric_time {
+                    split_movetime(&state, &alive, movetime * alive.len() as u32)
+                } else {
+                    alive.iter().map(|&p| (p, movetime)).collect()
+                };
+
+                // Time a converged search leaves unused goes to the next power.
+                let mut banked = Duration::ZERO;
+                for (power, movetime) in budgets {
+                    let movetime = movetime + std::mem::take(&mut banked);
+                    let persona = &personas[ALL_POWERS.iter().position(|&p| p == power).unwrap()];
+                    let result = if persona.strength >= 80 {
+                        regret_matching_search_with_options(
                             power,
                             &state,
                             movetime,
                             &mut null_out,
-                            None,
-                            config.strength,
+                            persona.neural.as_deref(),
+                            persona.strength,
                             None,
                             &AtomicBool::new(false),
+                            &persona.options,
                         )
                     } else {
                         search(
@@ -281,6 +461,9 @@ pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) ->
                             &AtomicBool::new(false),
                         )
                     };
+                    if result.converged {
+                        banked = result.unused(movetime);
+                    }
 
                     let orders = if result.orders.is_empty() {
                         random_orders(power, &state, rng)
@@ -310,24 +493,28 @@ pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) ->
                 advance_state(&mut state, has_dislodged);
             }
             Phase::Retreat => {
+                // Retreats are simultaneous: resolve every power's orders
+                // together so bounces between powers are detected.
                 for &power in ALL_POWERS.iter() {
-                    let retreat_orders = heuristic_retreat_orders(power, &state);
+                    let retreat_orders = script_or(config, power, &state, heuristic_retreat_orders);
                     if retreat_orders.is_empty() {
                         continue;
                     }
                     let dson = format_orders(&retreat_orders);
                     phase_orders.push((power, dson));
-                    let with_power: Vec<(Order, Power)> =
-                        retreat_orders.into_iter().map(|o| (o, power)).collect();
-                    let results = resolve_retreats(&with_power, &state);
-                    apply_retreats(&mut state, &results);
+                    for o in retreat_orders {
+                        all_orders.push((o, power));
+                    }
                 }
+                let results = resolve_retreats(&all_orders, &state);
+                destroyed = destroyed_units(&results);
+                apply_retreats(&mut state, &results);
                 advance_state(&mut state, false);
             }
             Phase::Build => {
                 let mut build_orders_all: Vec<(Order, Power)> = Vec::new();
                 for &power in ALL_POWERS.iter() {
-                    let build_orders = heuristic_build_orders(power, &state);
+                    let build_orders = script_or(config, power, &state, heuristic_build_orders);
                     if build_orders.is_empty() {
                         continue;
                     }
@@ -344,8 +531,12 @@ pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) ->
                 let new_scs = sc_counts(&state);
                 if is_stalemate(&prev_year_scs, &new_scs) {
                     stalemate_count += 1;
-                    if stalemate_count >= 3 {
-                        // Three consecutive years with no SC changes = stalemate.
+                    let solo_possible = ALL_POWERS
+                        .iter()
+                        .any(|&p| solo_distance(p, &state).is_some());
+                    if stalemate_count >= 3 || (stalemate_count >= 2 && !solo_possible) {
+                        // Three consecutive years with no SC changes = stalemate,
+                        // or two once stalemate lines rule out every solo.
                         if state.year < config.min_stalemate_year {
                             quality.early_stalemate = true;
                         }
@@ -372,6 +563,7 @@ pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) ->
             orders: phase_orders,
             values,
             sc_counts: counts,
+            destroyed,
         });
     }
 
@@ -384,6 +576,38 @@ pub fn play_game(config: &SelfPlayConfig, game_id: usize, rng: &mut SmallRng) ->
         final_sc_counts: final_scs,
         final_year: state.year,
         quality,
+        personas: config.seat_names(),
+    }
+}
+
+/// A scripted power's movement orders: the script's for this phase, plus
+/// holds for the units it leaves unordered.
+fn scripted_orders(config: &SelfPlayConfig, power: Power, state: &BoardState) -> Vec<Order> {
+    let script = config.script.as_ref().and_then(|s| s.orders(power, state));
+    let pairs: Vec<(Order, Power)> = script
+        .unwrap_or_default()
+        .iter()
+        .map(|&o| (o, power))
+        .collect();
+    sanitize_orders(&pairs, state)
+        .orders
+        .into_iter()
+        .filter(|&(_, p)| p == power)
+        .map(|(o, _)| o)
+        .collect()
+}
+
+/// The script's retreat or build orders for `power` in this phase, or
+/// `heuristic`'s when it has none.
+fn script_or(
+    config: &SelfPlayConfig,
+    power: Power,
+    state: &BoardState,
+    heuristic: fn(Power, &BoardState) -> Vec<Order>,
+) -> Vec<Order> {
+    match config.script.as_ref().and_then(|s| s.orders(power, state)) {
+        Some(orders) => orders.to_vec(),
+        None => heuristic(power, state),
     }
 }
 
@@ -409,16 +633,17 @@ pub fn run_self_play(config: &SelfPlayConfig) -> Vec<GameRecord> {
 /// Runs self-play generation, calling `on_game` with each completed game record.
 ///
 /// This allows the caller to process games incrementally (e.g. write to disk)
-/// rather than waiting for all games to finish.
+/// rather than waiting for all games to finish. Without the `parallel`
+/// feature games are played one at a time whatever `threads` says.
 pub fn run_self_play_with_callback<F>(config: &SelfPlayConfig, on_game: F)
 where
     F: FnMut(GameRecord) + Send,
 {
+    #[cfg(feature = "parallel")]
     if config.threads > 1 {
-        run_self_play_parallel(config, on_game);
-    } else {
-        run_self_play_sequential(config, on_game);
+        return run_self_play_parallel(config, on_game);
     }
+    run_self_play_sequential(config, on_game);
 }
 
 /// Sequential self-play: plays games one at a time.
@@ -429,7 +654,7 @@ where
     let mut rng = if config.seed != 0 {
         SmallRng::seed_from_u64(config.seed)
     } else {
-        SmallRng::from_entropy()
+        entropy_rng()
     };
 
     for i in 0..config.num_games {
@@ -456,11 +681,13 @@ where
 
 /// Parallel self-play: plays games concurrently using rayon.
 /// Uses a channel to deliver completed games to the callback from worker threads.
+#[cfg(feature = "parallel")]
 fn run_self_play_parallel<F>(config: &SelfPlayConfig, mut on_game: F)
 where
     F: FnMut(GameRecord) + Send,
 {
     use rayon::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::mpsc;
 
     let completed = AtomicUsize::new(0);
@@ -481,7 +708,7 @@ where
                     let mut rng = if config_clone.seed != 0 {
                         SmallRng::seed_from_u64(config_clone.seed.wrapping_add(i as u64))
                     } else {
-                        SmallRng::from_entropy()
+                        entropy_rng()
                     };
                     let game_start = Instant::now();
                     let game = play_game(&config_clone, i, &mut rng);
@@ -545,19 +772,35 @@ pub fn write_game_json<W: Write>(game: &GameRecord, out: &mut W) -> std::io::Res
     )?;
     write!(out, "}}")?;
 
+    if !game.personas.is_empty() {
+        write!(out, ",\"personas\":{{")?;
+        for (i, (power, name)) in game.personas.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(out, "\"{}\":\"{}\"", power_name(*power), escape_json(name))?;
+        }
+        write!(out, "}}")?;
+    }
+
     write!(out, ",\"phases\":[")?;
     for (pi, phase) in game.phases.iter().enumerate() {
         if pi > 0 {
             write!(out, ",")?;
         }
-        write_phase_json(phase, out)?;
+        write_phase_json(phase, game.phases.get(pi + 1), out)?;
     }
     write!(out, "]")?;
     write!(out, "}}")
 }
 
-/// Writes a single phase record as a JSON object.
-fn write_phase_json<W: Write>(phase: &PhaseRecord, out: &mut W) -> std::io::Result<()> {
+/// Writes a single phase record as a JSON object, with the changes up to
+/// `next` when there is a next phase.
+fn write_phase_json<W: Write>(
+    phase: &PhaseRecord,
+    next: Option<&PhaseRecord>,
+    out: &mut W,
+) -> std::io::Result<()> {
     write!(out, "{{")?;
     write!(out, "\"dfen\":\"{}\",", escape_json(&phase.dfen))?;
     write!(
@@ -593,6 +836,29 @@ fn write_phase_json<W: Write>(phase: &PhaseRecord, out: &mut W) -> std::io::Resu
     }
     write!(out, "]")?;
 
+    write!(out, ",\"destroyed\":[")?;
+    for (i, (power, prov, cause)) in phase.destroyed.iter().enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(
+            out,
+            "{{\"power\":\"{}\",\"province\":\"{}\",\"cause\":\"{}\"",
+            power_name(*power),
+            prov.abbr(),
+            cause.name()
+        )?;
+        if let Destruction::Bounced { dest } = cause {
+            write!(out, ",\"dest\":\"{}\"", dest.abbr())?;
+        }
+        write!(out, "}}")?;
+    }
+    write!(out, "]")?;
+
+    if let (Ok(a), Some(Ok(b))) = (parse_dfen(&phase.dfen), next.map(|n| parse_dfen(&n.dfen))) {
+        write!(out, ",\"changes\":{}", diff(&a, &b).to_json(a.map()))?;
+    }
+
     write!(out, "}}")
 }
 
@@ -667,16 +933,288 @@ pub fn print_summary(games: &[GameRecord]) {
     eprintln!("Draws: {}", draw_count);
     eprintln!("Early stalemates (filtered): {}", stalemate_count);
     eprintln!("Early dominations (flagged): {}", domination_count);
+    let summaries: Vec<_> = games.iter().map(summary).collect();
+    let blood: Vec<u16> = summaries.iter().filter_map(|s| s.first_blood).collect();
+    if !blood.is_empty() {
+        eprintln!(
+            "Avg first blood: {:.1}",
+            blood.iter().map(|&y| y as f64).sum::<f64>() / blood.len() as f64
+        );
+    }
+    let stabs: usize = summaries.iter().map(|s| s.stabs.len()).sum();
+    let spells: Vec<u16> = summaries
+        .iter()
+        .flat_map(|s| s.alliances.iter().map(|a| a.years()))
+        .collect();
+    eprintln!("Stabs/game: {:.1}", stabs as f64 / total.max(1) as f64);
+    eprintln!(
+        "Alliances/game: {:.1} (avg {:.1} years)",
+        spells.len() as f64 / total.max(1) as f64,
+        spells.iter().map(|&y| y as f64).sum::<f64>() / spells.len().max(1) as f64
+    );
+    eprintln!("Final SCs:");
+    let _ = write_sc_stats(games, &mut std::io::stderr());
     eprintln!("Win distribution:");
+    let personas = games.first().map_or(&[][..], |g| &g.personas[..]);
     for (i, &power) in ALL_POWERS.iter().enumerate() {
         let pct = 100.0 * win_counts[i] as f64 / total.max(1) as f64;
+        let persona = personas
+            .iter()
+            .find(|(p, _)| *p == power)
+            .map_or(String::new(), |(_, name)| format!(" [{}]", name));
         eprintln!(
-            "  {:>8}: {} ({:.1}%)",
+            "  {:>8}: {} ({:.1}%){}",
             power_name(power),
             win_counts[i],
-            pct
+            pct,
+            persona
+        );
+    }
+}
+
+/// Final supply center statistics of one power over a set of games.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerScStats {
+    pub power: Power,
+    /// Mean final SC count.
+    pub mean: f64,
+    pub min: i32,
+    pub max: i32,
+    /// Games the power won outright.
+    pub solos: usize,
+    /// Games the power finished with at least one SC.
+    pub survived: usize,
+}
+
+/// Per-power final SC statistics of `games`, in [`ALL_POWERS`] order.
+pub fn sc_stats(games: &[GameRecord]) -> Vec<PowerScStats> {
+    ALL_POWERS
+        .iter()
+        .enumerate()
+        .map(|(i, &power)| {
+            let finals = games.iter().map(|g| g.final_sc_counts[i]);
+            PowerScStats {
+                power,
+                mean: finals.clone().sum::<i32>() as f64 / games.len().max(1) as f64,
+                min: finals.clone().min().unwrap_or(0),
+                max: finals.clone().max().unwrap_or(0),
+                solos: games.iter().filter(|g| g.winner == Some(power)).count(),
+                survived: finals.filter(|&n| n > 0).count(),
+            }
+        })
+        .collect()
+}
+
+/// Writes [`sc_stats`] of `games` as an aligned table.
+pub fn write_sc_stats<W: Write>(games: &[GameRecord], out: &mut W) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{:>8}  {:>5}  {:>3}  {:>3}  {:>5}  {:>8}",
+        "power", "mean", "min", "max", "solos", "survived"
+    )?;
+    for s in sc_stats(games) {
+        writeln!(
+            out,
+            "{:>8}  {:>5.1}  {:>3}  {:>3}  {:>5}  {:>8}",
+            power_name(s.power),
+            s.mean,
+            s.min,
+            s.max,
+            s.solos,
+            s.survived
+        )?;
+    }
+    Ok(())
+}
+
+/// Usage of the self-play command line, shared by `realpolitik selfplay`
+/// and the `selfplay` binary.
+pub const USAGE: &str = "\
+Options:
+  --games N        Number of games to play (default: 10)
+  --movetime MS    Search time per move in ms (default: 2000)
+  --equal-time     Give every power the full movetime (no criticality split)
+  --strength N     Engine strength 1-100 (default: 100)
+  --max-year Y     Maximum game year (default: 1920)
+  --temperature T  Exploration temperature (default: 1.0)
+  --threads N      Number of parallel threads (default: 4)
+  --seed N         Random seed, 0 for entropy (default: 0)
+  --seat P=SPEC    Persona for one power, e.g. austria=strength:100,model:models
+                   keys: name strength model priors plans buffers supports early_exit
+  --duel P,P       Two-power duel, other powers removed (e.g. france,germany)
+  --output FILE    Write game records (JSONL) to FILE (default: stdout)
+  --summary FILE   Write per-game summary statistics (JSONL) to FILE
+  --quiet          Suppress progress and the final summary
+  --help           Show this help
+";
+
+/// Runs self-play from command-line arguments (without the program or
+/// subcommand name) and returns the process exit code.
+///
+/// Game records go to `--output` or stdout as they finish, leaving out
+/// early stalemates. Unless `--quiet`, the run summary and each power's
+/// final supply center statistics follow on stderr.
+pub fn run_cli(args: &[String]) -> i32 {
+    let mut config = SelfPlayConfig::default();
+    let mut seats: Vec<&str> = Vec::new();
+    let mut output: Option<&str> = None;
+    let mut summary_path: Option<&str> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--equal-time" => config.asymmetric_time = false,
+            "--quiet" => config.quiet = true,
+            "--help" | "-h" => {
+                eprint!("{}", USAGE);
+                return 0;
+            }
+            flag => {
+                let Some(value) = args.get(i + 1).map(String::as_str) else {
+                    eprintln!("selfplay: {} needs a value\n{}", flag, USAGE);
+                    return 2;
+                };
+                let bad = || format!("bad {} '{}'", flag, value);
+                let result: Result<(), String> = match flag {
+                    "--games" => value
+                        .parse()
+                        .map(|n| config.num_games = n)
+                        .map_err(|_| bad()),
+                    "--movetime" => value
+                        .parse()
+                        .map(|n| config.movetime_ms = n)
+                        .map_err(|_| bad()),
+                    "--strength" => value
+                        .parse()
+                        .map(|n| config.strength = n)
+                        .map_err(|_| bad()),
+                    "--max-year" => value
+                        .parse()
+                        .map(|n| config.max_year = n)
+                        .map_err(|_| bad()),
+                    "--temperature" => value
+                        .parse()
+                        .map(|t| config.temperature = t)
+                        .map_err(|_| bad()),
+                    "--threads" => value.parse().map(|n| config.threads = n).map_err(|_| bad()),
+                    "--seed" => value.parse().map(|n| config.seed = n).map_err(|_| bad()),
+                    "--seat" => {
+                        seats.push(value);
+                        Ok(())
+                    }
+                    "--duel" => value
+                        .parse()
+                        .map(|d| config.duel = Some(d))
+                        .map_err(|e| format!("bad --duel '{}': {}", value, e)),
+                    "--output" => {
+                        output = Some(value);
+                        Ok(())
+                    }
+                    "--summary" => {
+                        summary_path = Some(value);
+                        Ok(())
+                    }
+                    _ => Err(format!("unknown option {}", flag)),
+                };
+                if let Err(e) = result {
+                    eprintln!("selfplay: {}\n{}", e, USAGE);
+                    return 2;
+                }
+                i += 1;
+            }
+        }
+        i += 1;
+    }
+    // Seats are parsed last so they inherit the final --strength.
+    for seat in seats {
+        match Persona::parse_seat(seat, config.strength) {
+            Ok(parsed) => config.seats.push(parsed),
+            Err(e) => {
+                eprintln!("selfplay: bad --seat '{}': {}", seat, e);
+                return 2;
+            }
+        }
+    }
+
+    let open = |path: &str| {
+        std::fs::File::create(path)
+            .map(std::io::BufWriter::new)
+            .map_err(|e| eprintln!("selfplay: {}: {}", path, e))
+    };
+    let mut records: Box<dyn Write + Send> = match output {
+        Some(path) => match open(path) {
+            Ok(file) => Box::new(file),
+            Err(()) => return 1,
+        },
+        None => Box::new(std::io::stdout()),
+    };
+    let mut summaries = match summary_path.map(open).transpose() {
+        Ok(file) => file,
+        Err(()) => return 1,
+    };
+
+    if !config.quiet {
+        eprintln!(
+            "Self-play: {} games, {}ms/move, strength {}, max year {}, temp {:.2}, {} threads",
+            config.num_games,
+            config.movetime_ms,
+            config.strength,
+            config.max_year,
+            config.temperature,
+            config.threads
         );
+        for (power, name) in config.seat_names() {
+            eprintln!("  {:?}: {}", power, name);
+        }
     }
+
+    let start = Instant::now();
+    let mut games: Vec<GameRecord> = Vec::with_capacity(config.num_games);
+    let mut written = 0usize;
+    let mut write_error: Option<(&str, std::io::Error)> = None;
+    run_self_play_with_callback(&config, |game| {
+        if write_error.is_none() {
+            // Flushed per game so a follow-mode importer sees each one.
+            if !game.quality.early_stalemate {
+                let result = write_game_json(&game, &mut records)
+                    .and_then(|()| writeln!(records))
+                    .and_then(|()| records.flush());
+                match result {
+                    Ok(()) => written += 1,
+                    Err(e) => write_error = Some((output.unwrap_or("stdout"), e)),
+                }
+            }
+            if let Some(w) = summaries.as_mut() {
+                let result = writeln!(w, "{}", summary(&game).to_json()).and_then(|()| w.flush());
+                if let Err(e) = result {
+                    write_error = Some((summary_path.unwrap_or_default(), e));
+                }
+            }
+        }
+        games.push(game);
+    });
+    if let Some((path, e)) = write_error {
+        eprintln!("selfplay: {}: {}", path, e);
+        return 1;
+    }
+
+    if !config.quiet {
+        let elapsed = start.elapsed().as_secs_f64();
+        eprintln!(
+            "Completed {} games in {:.1}s ({:.1} games/hour)",
+            games.len(),
+            elapsed,
+            games.len() as f64 / elapsed * 3600.0
+        );
+        eprintln!(
+            "Valid games written: {} (discarded {} early stalemates)",
+            written,
+            games.len() - written
+        );
+        print_summary(&games);
+        let _ = write_sc_stats(&games, &mut std::io::stderr());
+    }
+    0
 }
 
 #[cfg(test)]
@@ -708,6 +1246,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn equal_time_game_completes() {
+        let config = SelfPlayConfig {
+            num_games: 1,
+            movetime_ms: 50,
+            asymmetric_time: false,
+            strength: 50,
+            max_year: 1901,
+            temperature: 0.0,
+            seed: 7,
+            ..Default::default()
+        };
+        let mut rng = SmallRng::seed_from_u64(7);
+        let game = play_game(&config, 0, &mut rng);
+        assert_eq!(game.phases[0].orders.len(), 7);
+    }
+
     #[test]
     fn game_record_has_valid_dfen() {
         let config = SelfPlayConfig {
@@ -797,6 +1352,210 @@ mod tests {
         }
     }
 
+    #[test]
+    fn phase_json_lists_destroyed_units() {
+        let phase = PhaseRecord {
+            dfen: INITIAL_DFEN.to_string(),
+            year: 1902,
+            season: 's',
+            phase: 'r',
+            orders: Vec::new(),
+            values: [0.0; 7],
+            sc_counts: [0; 7],
+            destroyed: vec![(
+                Power::Austria,
+                Province::Ser,
+                Destruction::Bounced {
+                    dest: Province::Alb,
+                },
+            )],
+        };
+        let mut buf = Vec::new();
+        write_phase_json(&phase, None, &mut buf).unwrap();
+        let line = String::from_utf8(buf).unwrap();
+        assert!(line.contains(
+            "\"destroyed\":[{\"power\":\"austria\",\"province\":\"ser\",\"cause\":\"bounced\",\"dest\":\"alb\"}]"
+        ));
+        assert!(!line.contains("\"changes\""));
+
+        let next = PhaseRecord {
+            dfen: INITIAL_DFEN.replace("Aavie", "Aagal"),
+            destroyed: Vec::new(),
+            ..phase.clone()
+        };
+        let mut buf = Vec::new();
+        write_phase_json(&phase, Some(&next), &mut buf).unwrap();
+        let line = String::from_utf8(buf).unwrap();
+        assert!(line.contains(
+            "\"changes\":{\"builds\":[],\"centers\":[],\"disbands\":[],\"dislodged\":[],\"moves\":[{\"from\":\"vie\""
+        ));
+    }
+
+    #[test]
+    fn persona_specs_parse_over_run_defaults() {
+        let persona = Persona::parse("strength:60,priors:false,early_exit:off", 100).unwrap();
+        assert_eq!(persona.name, "strength:60,priors:false,early_exit:off");
+        assert_eq!(persona.strength, 60);
+        assert!(!persona.options.opening_priors);
+        assert!(!persona.options.early_exit);
+        assert!(persona.options.group_plans);
+        assert!(persona.model.is_none());
+
+        let (power, persona) = Persona::parse_seat("Austria=name:rm,strength:90", 50).unwrap();
+        assert_eq!(power, Power::Austria);
+        assert_eq!((persona.name.as_str(), persona.strength), ("rm", 90));
+
+        assert!(Persona::parse("strength", 100).is_err());
+        assert!(Persona::parse("depth:3", 100).is_err());
+        assert!(Persona::parse("plans:maybe", 100).is_err());
+        assert_eq!(
+            Persona::parse("corner:aggressive", 100)
+                .unwrap()
+                .options
+                .corner_opening,
+            OpeningStyle::Aggressive
+        );
+        assert!(Persona::parse("corner:turtle", 100).is_err());
+        assert!(Persona::parse_seat("prussia=strength:10", 100).is_err());
+    }
+
+    #[test]
+    fn seats_play_their_own_personas() {
+        let (power, persona) = Persona::parse_seat("austria=name:rm,strength:100", 50).unwrap();
+        let config = SelfPlayConfig {
+            num_games: 1,
+            movetime_ms: 50,
+            asymmetric_time: false,
+            strength: 50,
+            max_year: 1901,
+            temperature: 0.0,
+            seed: 11,
+            seats: vec![(power, persona)],
+            ..Default::default()
+        };
+        assert_eq!(config.persona(Power::Austria).strength, 100);
+        assert_eq!(config.persona(Power::Turkey).strength, 50);
+
+        let mut rng = SmallRng::seed_from_u64(11);
+        let game = play_game(&config, 0, &mut rng);
+        assert_eq!(game.personas.len(), 7);
+        assert_eq!(game.personas[0], (Power::Austria, "rm".to_string()));
+        assert_eq!(game.personas[6], (Power::Turkey, "default".to_string()));
+
+        let mut buf = Vec::new();
+        write_game_json(&game, &mut buf).unwrap();
+        let line = String::from_utf8(buf).unwrap();
+        assert!(line.contains("\"personas\":{\"austria\":\"rm\",\"england\":\"default\""));
+    }
+
+    #[test]
+    fn duel_games_keep_two_powers() {
+        let duel: Duel = "france,germany".parse().unwrap();
+        let config = SelfPlayConfig {
+            num_games: 1,
+            movetime_ms: 20,
+            asymmetric_time: false,
+            strength: 50,
+            max_year: 1902,
+            temperature: 0.0,
+            seed: 5,
+            duel: Some(duel),
+            ..Default::default()
+        };
+        let mut rng = SmallRng::seed_from_u64(5);
+        let game = play_game(&config, 0, &mut rng);
+        for phase in &game.phases {
+            assert!(phase.orders.iter().all(|(p, _)| duel.includes(*p)));
+        }
+        assert_eq!(game.phases[0].orders.len(), 2);
+        for (i, &power) in ALL_POWERS.iter().enumerate() {
+            if !duel.includes(power) {
+                assert_eq!(game.final_sc_counts[i], 0);
+            }
+        }
+    }
+
+    #[test]
+    fn scripted_powers_play_their_script() {
+        let script = OpponentScript::parse(
+            "1901sm austria: A vie H ; A bud - ser\n1901fm austria: A ser - gre",
+        )
+        .unwrap();
+        let config = SelfPlayConfig {
+            num_games: 1,
+            movetime_ms: 20,
+            asymmetric_time: false,
+            strength: 50,
+            max_year: 1901,
+            temperature: 0.0,
+            seed: 3,
+            script: Some(Arc::new(script)),
+            ..Default::default()
+        };
+        let mut rng = SmallRng::seed_from_u64(3);
+        let game = play_game(&config, 0, &mut rng);
+        let austria = |phase: &PhaseRecord| {
+            phase
+                .orders
+                .iter()
+                .find(|(p, _)| *p == Power::Austria)
+                .map(|(_, o)| o.clone())
+        };
+        assert_eq!(
+            austria(&game.phases[0]).unwrap(),
+            "A vie H ; A bud - ser ; F tri H"
+        );
+        let fall = game
+            .phases
+            .iter()
+            .find(|p| p.dfen.starts_with("1901fm"))
+            .unwrap();
+        let orders = austria(fall).unwrap();
+        assert!(fall.dfen.contains("Aaser"));
+        assert!(orders.contains("A ser - gre"));
+        assert_eq!(orders.matches(" H").count(), 2);
+    }
+
+    #[test]
+    fn sc_stats_aggregate_final_counts() {
+        let game = |finals: [i32; 7], winner| GameRecord {
+            game_id: 0,
+            phases: Vec::new(),
+            winner,
+            final_sc_counts: finals,
+            final_year: 1910,
+            quality: GameQuality::default(),
+            personas: Vec::new(),
+        };
+        let games = [
+            game([18, 4, 3, 2, 2, 5, 0], Some(Power::Austria)),
+            game([6, 5, 5, 4, 4, 5, 5], None),
+        ];
+        let stats = sc_stats(&games);
+        assert_eq!(
+            stats[0],
+            PowerScStats {
+                power: Power::Austria,
+                mean: 12.0,
+                min: 6,
+                max: 18,
+                solos: 1,
+                survived: 2,
+            }
+        );
+        assert_eq!(stats[6].survived, 1);
+        assert_eq!(stats[6].solos, 0);
+
+        let mut out = Vec::new();
+        write_sc_stats(&games, &mut out).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert_eq!(table.lines().count(), 8);
+        assert_eq!(
+            table.lines().nth(1).unwrap(),
+            " austria   12.0    6   18      1         2"
+        );
+    }
+
     #[test]
     fn sc_counts_initial_position() {
         let state = parse_dfen(INITIAL_DFEN).unwrap();
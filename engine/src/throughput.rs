@@ -0,0 +1,220 @@
+//! Process-wide work counters for throughput reporting.
+//!
+//! The resolver, the heuristic evaluator, and the neural evaluator bump a
+//! relaxed atomic counter on every call. Searches take a snapshot when they
+//! start and report the per-second rates of the difference in their `info`
+//! output, so throughput regressions show up in normal play rather than only
+//! in benchmarks. Counters are global: when several searches run at once in
+//! one process (tests, self-play threads) each sees the combined work.
+//!
+//! Finished searches are tallied here too ([`record_search`]), for the
+//! server's `/metrics` endpoint: how many ran, their nodes, how much of
+//! their time budget they used, and their cache lookups.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A counter on its own cache line, so the hot resolve and eval paths on
+/// different threads do not contend on the same line.
+#[repr(align(64))]
+struct Counter(AtomicU64);
+
+impl Counter {
+    const fn new() -> Self {
+        Counter(AtomicU64::new(0))
+    }
+
+    fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+static RESOLVES: Counter = Counter::new();
+static EVALS: Counter = Counter::new();
+static INFERENCES: Counter = Counter::new();
+
+static SEARCHES: Counter = Counter::new();
+static SEARCH_NODES: Counter = Counter::new();
+static SEARCH_MICROS: Counter = Counter::new();
+static BUDGET_MICROS: Counter = Counter::new();
+static CACHE_LOOKUPS: Counter = Counter::new();
+static CACHE_HITS: Counter = Counter::new();
+
+/// Records one resolver invocation.
+pub fn record_resolve() {
+    RESOLVES.add(1);
+}
+
+/// Records one heuristic evaluation.
+pub fn record_eval() {
+    EVALS.add(1);
+}
+
+/// Records `n` neural network inferences (one per position in a batch).
+pub fn record_inferences(n: u64) {
+    INFERENCES.add(n);
+}
+
+/// Records a finished search: its nodes, wall time, the time it was given,
+/// and its cache counters if it kept any.
+pub fn record_search(nodes: u64, elapsed: Duration, budget: Duration, cache: Option<(u64, u64)>) {
+    SEARCHES.add(1);
+    SEARCH_NODES.add(nodes);
+    SEARCH_MICROS.add(elapsed.as_micros() as u64);
+    BUDGET_MICROS.add(budget.as_micros() as u64);
+    if let Some((lookups, hits)) = cache {
+        CACHE_LOOKUPS.add(lookups);
+        CACHE_HITS.add(hits);
+    }
+}
+
+/// Cumulative totals over every finished search.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchTotals {
+    pub searches: u64,
+    pub nodes: u64,
+    /// Wall time spent searching.
+    pub elapsed: Duration,
+    /// Time the searches were given.
+    pub budget: Duration,
+    pub cache_lookups: u64,
+    pub cache_hits: u64,
+}
+
+impl SearchTotals {
+    /// Returns the current totals.
+    pub fn now() -> Self {
+        SearchTotals {
+            searches: SEARCHES.get(),
+            nodes: SEARCH_NODES.get(),
+            elapsed: Duration::from_micros(SEARCH_MICROS.get()),
+            budget: Duration::from_micros(BUDGET_MICROS.get()),
+            cache_lookups: CACHE_LOOKUPS.get(),
+            cache_hits: CACHE_HITS.get(),
+        }
+    }
+
+    /// Share of the time budget searches used, on average (0 before any).
+    pub fn utilization(&self) -> f64 {
+        ratio(self.elapsed.as_secs_f64(), self.budget.as_secs_f64())
+    }
+
+    /// Nodes per second of search time.
+    pub fn nps(&self) -> f64 {
+        ratio(self.nodes as f64, self.elapsed.as_secs_f64())
+    }
+
+    /// Fraction of cache lookups that hit.
+    pub fn cache_hit_rate(&self) -> f64 {
+        ratio(self.cache_hits as f64, self.cache_lookups as f64)
+    }
+}
+
+fn ratio(num: f64, den: f64) -> f64 {
+    if den > 0.0 {
+        num / den
+    } else {
+        0.0
+    }
+}
+
+/// Cumulative work counts at a point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkCounts {
+    pub resolves: u64,
+    pub evals: u64,
+    pub inferences: u64,
+}
+
+impl WorkCounts {
+    /// Returns the current totals.
+    pub fn now() -> Self {
+        WorkCounts {
+            resolves: RESOLVES.get(),
+            evals: EVALS.get(),
+            inferences: INFERENCES.get(),
+        }
+    }
+
+    /// Returns the work done since `earlier`.
+    pub fn since(self, earlier: WorkCounts) -> WorkCounts {
+        WorkCounts {
+            resolves: self.resolves.saturating_sub(earlier.resolves),
+            evals: self.evals.saturating_sub(earlier.evals),
+            inferences: self.inferences.saturating_sub(earlier.inferences),
+        }
+    }
+
+    /// Formats the per-second rates as an `info string nps ...` line body.
+    pub fn rates_line(self, elapsed: Duration) -> String {
+        format!(
+            "nps resolves {} evals {} nn {}",
+            per_second(self.resolves, elapsed),
+            per_second(self.evals, elapsed),
+            per_second(self.inferences, elapsed)
+        )
+    }
+}
+
+/// Converts a count over `elapsed` into a whole per-second rate. Elapsed
+/// times under a millisecond are treated as one millisecond.
+pub fn per_second(count: u64, elapsed: Duration) -> u64 {
+    let secs = elapsed.as_secs_f64().max(0.001);
+    (count as f64 / secs) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_second_scales_and_guards_zero_elapsed() {
+        assert_eq!(per_second(500, Duration::from_millis(250)), 2000);
+        assert_eq!(per_second(5, Duration::ZERO), 5000);
+    }
+
+    #[test]
+    fn counters_accumulate_between_snapshots() {
+        let before = WorkCounts::now();
+        record_resolve();
+        record_eval();
+        record_eval();
+        record_inferences(3);
+        let delta = WorkCounts::now().since(before);
+        // Other tests may run concurrently, so only lower bounds hold.
+        assert!(delta.resolves >= 1);
+        assert!(delta.evals >= 2);
+        assert!(delta.inferences >= 3);
+    }
+
+    #[test]
+    fn search_totals_derive_rates() {
+        let before = SearchTotals::now();
+        record_search(
+            1000,
+            Duration::from_millis(500),
+            Duration::from_secs(1),
+            Some((10, 4)),
+        );
+        let after = SearchTotals::now();
+        assert!(after.searches > before.searches);
+        assert!(after.nodes >= before.nodes + 1000);
+
+        let one = SearchTotals {
+            searches: 1,
+            nodes: 1000,
+            elapsed: Duration::from_millis(500),
+            budget: Duration::from_secs(1),
+            cache_lookups: 10,
+            cache_hits: 4,
+        };
+        assert_eq!(one.utilization(), 0.5);
+        assert_eq!(one.nps(), 2000.0);
+        assert_eq!(one.cache_hit_rate(), 0.4);
+        assert_eq!(SearchTotals::default().nps(), 0.0);
+    }
+}
@@ -0,0 +1,120 @@
+//! Golden-fixture tests for RM+ candidate generation.
+//!
+//! Each case generates the heuristic candidate pool for one power in a
+//! fixed position with a fixed seed and compares it, one DSON order set per
+//! line, against `tests/fixtures/candidates/<case>.txt`. A change to order
+//! scoring that reshapes the pools fails here instead of silently shifting
+//! play. When the change is intended, regenerate the fixtures and review
+//! the diff with the change:
+//!
+//!   UPDATE_GOLDEN=1 cargo test --test candidate_golden
+
+use std::fs;
+use std::path::PathBuf;
+
+use realpolitik::board::{canonical_orders, Power};
+use realpolitik::protocol::dfen::parse_dfen;
+use realpolitik::protocol::dson::format_orders;
+use realpolitik::search::{candidate_pool, RmOptions};
+
+const INITIAL_DFEN: &str = "1901sm/Aavie,Aabud,Aftri,Eflon,Efedi,Ealvp,Ffbre,Fapar,Famar,Gfkie,Gaber,Gamun,Ifnap,Iarom,Iaven,Rfstp.sc,Ramos,Rawar,Rfsev,Tfank,Tacon,Tasmy/Abud,Atri,Avie,Eedi,Elon,Elvp,Fbre,Fmar,Fpar,Gber,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rstp,Rwar,Tank,Tcon,Tsmy,Nbel,Nbul,Nden,Ngre,Nhol,Nnwy,Npor,Nrum,Nser,Nspa,Nswe,Ntun/-";
+
+const MIDGAME_DFEN: &str = "1903fm/Aabud,Aarum,Afgre,Aavie,Efnth,Efnwy,Eayor,Eflon,Ffmao,Fabur,Famar,Ffpor,Gaden,Gahol,Gamun,Gfkie,Gfska,Iftys,Iaven,Iarom,Rfsev,Ramos,Rawar,Tfank,Tabul,Tacon,Tasmy/Abud,Agre,Arum,Atri,Avie,Eedi,Elon,Elvp,Enwy,Fbre,Fmar,Fpar,Fspa,Gber,Gden,Ghol,Gkie,Gmun,Inap,Irom,Iven,Rmos,Rsev,Rwar,Tank,Tbul,Tcon,Tsmy,Nbel,Npor,Nser,Nstp,Nswe,Ntun/-";
+
+const SEED: u64 = 20240501;
+
+/// Renders a case as fixture text: a header naming the inputs, then one
+/// candidate per line in generation order.
+fn render(dfen: &str, power: Power) -> String {
+    let state = parse_dfen(dfen).expect("fixture DFEN must parse");
+    let pool = candidate_pool(power, &state, SEED, &RmOptions::default());
+    let mut text = format!(
+        "# power {}\n# seed {}\n# dfen {}\n",
+        power.name(),
+        SEED,
+        dfen
+    );
+    for cand in pool {
+        let orders: Vec<_> = canonical_orders(&cand)
+            .into_iter()
+            .map(|(o, _)| o)
+            .collect();
+        text.push_str(&format_orders(&orders));
+        text.push('\n');
+    }
+    text
+}
+
+/// Compares a case against its fixture, or rewrites the fixture when
+/// `UPDATE_GOLDEN` is set.
+fn check(case: &str, dfen: &str, power: Power) {
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "tests",
+        "fixtures",
+        "candidates",
+        &format!("{}.txt", case),
+    ]
+    .iter()
+    .collect();
+    let actual = render(dfen, power);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &actual).expect("failed to write fixture");
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "missing fixture {} ({}); run with UPDATE_GOLDEN=1 to create it",
+            path.display(),
+            e
+        )
+    });
+    if actual != expected {
+        let mut diff = String::new();
+        for line in expected
+            .lines()
+            .filter(|l| !actual.lines().any(|a| a == *l))
+        {
+            diff.push_str(&format!("- {}\n", line));
+        }
+        for line in actual
+            .lines()
+            .filter(|l| !expected.lines().any(|e| e == *l))
+        {
+            diff.push_str(&format!("+ {}\n", line));
+        }
+        if diff.is_empty() {
+            diff.push_str("(same candidates, different order)\n");
+        }
+        panic!(
+            "candidate pool for {} changed; review and rerun with UPDATE_GOLDEN=1 if intended:\n{}",
+            case, diff
+        );
+    }
+}
+
+#[test]
+fn golden_initial_austria() {
+    check("initial_austria", INITIAL_DFEN, Power::Austria);
+}
+
+#[test]
+fn golden_initial_england() {
+    check("initial_england", INITIAL_DFEN, Power::England);
+}
+
+#[test]
+fn golden_initial_turkey() {
+    check("initial_turkey", INITIAL_DFEN, Power::Turkey);
+}
+
+#[test]
+fn golden_midgame_france() {
+    check("midgame_france", MIDGAME_DFEN, Power::France);
+}
+
+#[test]
+fn golden_midgame_germany() {
+    check("midgame_germany", MIDGAME_DFEN, Power::Germany);
+}
@@ -11,7 +11,7 @@ use realpolitik::board::order::{Location, Order, OrderUnit};
 use realpolitik::board::province::{Coast, Power, Province};
 use realpolitik::board::state::{BoardState, Phase, Season};
 use realpolitik::board::unit::UnitType;
-use realpolitik::resolve::kruijswijk::{resolve_orders, OrderResult, ResolvedOrder};
+use realpolitik::resolve::kruijswijk::{resolve_orders, OrderResult, ResolvedOrder, Resolver};
 
 // ---------------------------------------------------------------------------
 // Helpers
@@ -166,6 +166,7 @@ fn datc_6a5_self_support_hold_not_possible() {
             Order::Move {
                 unit: army(Province::Tri),
                 dest: loc(Province::Ven),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -202,6 +203,7 @@ fn datc_6a6_unit_ordered_to_support_can_still_move() {
             Order::Move {
                 unit: fleet(Province::Kie),
                 dest: loc(Province::Ber),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -209,6 +211,7 @@ fn datc_6a6_unit_ordered_to_support_can_still_move() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Sil),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -278,6 +281,7 @@ fn datc_6a8_no_self_support() {
             Order::Move {
                 unit: army(Province::Tri),
                 dest: loc(Province::Ven),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -299,6 +303,7 @@ fn datc_6a9_fleet_coast_movement() {
         Order::Move {
             unit: fleet(Province::Gol),
             dest: loc_coast(Province::Spa, Coast::South),
+            via_convoy: false,
         },
         Power::France,
     )];
@@ -347,6 +352,7 @@ fn datc_6b1_fleet_split_coast_one_reachable() {
         Order::Move {
             unit: fleet(Province::Gol),
             dest: loc_coast(Province::Spa, Coast::South),
+            via_convoy: false,
         },
         Power::France,
     )];
@@ -364,6 +370,7 @@ fn datc_6b2_fleet_split_coast_both_reachable() {
         Order::Move {
             unit: fleet(Province::Mao),
             dest: loc_coast(Province::Spa, Coast::North),
+            via_convoy: false,
         },
         Power::France,
     )];
@@ -412,6 +419,7 @@ fn datc_6b4_support_to_reachable_coast() {
             Order::Move {
                 unit: army(Province::Mar),
                 dest: loc(Province::Spa),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -438,6 +446,7 @@ fn datc_6b5_fleet_on_split_coast_movement() {
         Order::Move {
             unit: fleet_coast(Province::Bul, Coast::South),
             dest: loc(Province::Con),
+            via_convoy: false,
         },
         Power::Turkey,
     )];
@@ -454,6 +463,7 @@ fn datc_6b6_stp_sc_to_bot() {
         Order::Move {
             unit: fleet_coast(Province::Stp, Coast::South),
             dest: loc(Province::Bot),
+            via_convoy: false,
         },
         Power::Russia,
     )];
@@ -470,6 +480,7 @@ fn datc_6b7_stp_nc_to_bar() {
         Order::Move {
             unit: fleet_coast(Province::Stp, Coast::North),
             dest: loc(Province::Bar),
+            via_convoy: false,
         },
         Power::Russia,
     )];
@@ -486,6 +497,7 @@ fn datc_6b8_bul_ec_to_bla() {
         Order::Move {
             unit: fleet_coast(Province::Bul, Coast::East),
             dest: loc(Province::Bla),
+            via_convoy: false,
         },
         Power::Turkey,
     )];
@@ -510,6 +522,7 @@ fn datc_6c1_three_army_circular() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -517,6 +530,7 @@ fn datc_6c1_three_army_circular() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Sil),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -524,6 +538,7 @@ fn datc_6c1_three_army_circular() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -548,6 +563,7 @@ fn datc_6c2_circular_with_support() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -555,6 +571,7 @@ fn datc_6c2_circular_with_support() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Sil),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -562,6 +579,7 @@ fn datc_6c2_circular_with_support() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -597,6 +615,7 @@ fn datc_6c3_disrupted_circular_with_outside_attack() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -604,6 +623,7 @@ fn datc_6c3_disrupted_circular_with_outside_attack() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Sil),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -611,6 +631,7 @@ fn datc_6c3_disrupted_circular_with_outside_attack() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -618,6 +639,7 @@ fn datc_6c3_disrupted_circular_with_outside_attack() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -645,6 +667,7 @@ fn datc_6c4_three_fleet_rotation() {
             Order::Move {
                 unit: fleet(Province::Bre),
                 dest: loc(Province::Eng),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -652,6 +675,7 @@ fn datc_6c4_three_fleet_rotation() {
             Order::Move {
                 unit: fleet(Province::Eng),
                 dest: loc(Province::Mao),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -659,6 +683,7 @@ fn datc_6c4_three_fleet_rotation() {
             Order::Move {
                 unit: fleet(Province::Mao),
                 dest: loc(Province::Bre),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -683,6 +708,7 @@ fn datc_6c5_circular_blocked_by_head_to_head() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -690,6 +716,7 @@ fn datc_6c5_circular_blocked_by_head_to_head() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -697,6 +724,7 @@ fn datc_6c5_circular_blocked_by_head_to_head() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -721,6 +749,7 @@ fn datc_6c6_swap_fails_without_convoy() {
             Order::Move {
                 unit: army(Province::Par),
                 dest: loc(Province::Bur),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -728,6 +757,7 @@ fn datc_6c6_swap_fails_without_convoy() {
             Order::Move {
                 unit: army(Province::Bur),
                 dest: loc(Province::Par),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -766,6 +796,7 @@ fn datc_6d1_supported_hold() {
             Order::Move {
                 unit: army(Province::Rum),
                 dest: loc(Province::Bud),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -801,6 +832,7 @@ fn datc_6d2_move_cuts_support_on_hold() {
             Order::Move {
                 unit: army(Province::Rum),
                 dest: loc(Province::Bud),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -808,6 +840,7 @@ fn datc_6d2_move_cuts_support_on_hold() {
             Order::Move {
                 unit: army(Province::Bul),
                 dest: loc(Province::Ser),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -838,6 +871,7 @@ fn datc_6d3_move_cuts_support_on_move() {
             Order::Move {
                 unit: army(Province::Bud),
                 dest: loc(Province::Rum),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -851,6 +885,7 @@ fn datc_6d3_move_cuts_support_on_move() {
             Order::Move {
                 unit: army(Province::Bul),
                 dest: loc(Province::Ser),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -887,6 +922,7 @@ fn datc_6d4_mutual_support_hold() {
             Order::Move {
                 unit: army(Province::Pru),
                 dest: loc(Province::Ber),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -928,6 +964,7 @@ fn datc_6d5_support_foreign_dislodge_own_unit() {
             Order::Move {
                 unit: army(Province::Wal),
                 dest: loc(Province::Lon),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -972,6 +1009,7 @@ fn datc_6d6_support_not_cut_by_own_power() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Sil),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -985,6 +1023,7 @@ fn datc_6d6_support_not_cut_by_own_power() {
             Order::Move {
                 unit: fleet(Province::Kie),
                 dest: loc(Province::Ber),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1019,6 +1058,7 @@ fn datc_6d7_support_cant_be_cut_by_target() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1026,6 +1066,7 @@ fn datc_6d7_support_cant_be_cut_by_target() {
             Order::Move {
                 unit: army(Province::War),
                 dest: loc(Province::Sil),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -1033,6 +1074,7 @@ fn datc_6d7_support_cant_be_cut_by_target() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1064,6 +1106,7 @@ fn datc_6d8_support_can_be_cut_normally() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1077,6 +1120,7 @@ fn datc_6d8_support_can_be_cut_normally() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1110,6 +1154,7 @@ fn datc_6d9_target_cannot_cut_support() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1117,6 +1162,7 @@ fn datc_6d9_target_cannot_cut_support() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1151,6 +1197,7 @@ fn datc_6d10_dislodging_support_unit_cuts_support() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1164,6 +1211,7 @@ fn datc_6d10_dislodging_support_unit_cuts_support() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1200,6 +1248,7 @@ fn datc_6d11_no_self_dislodge() {
             Order::Move {
                 unit: army(Province::Ber),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1240,6 +1289,7 @@ fn datc_6d12_supported_attack_dislodges_foreign() {
             Order::Move {
                 unit: army(Province::War),
                 dest: loc(Province::Sil),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -1279,6 +1329,7 @@ fn datc_6d13_three_way_standoff() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1286,6 +1337,7 @@ fn datc_6d13_three_way_standoff() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1293,6 +1345,7 @@ fn datc_6d13_three_way_standoff() {
             Order::Move {
                 unit: army(Province::Sil),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -1325,6 +1378,7 @@ fn datc_6d14_cross_power_support() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1356,6 +1410,7 @@ fn datc_6e1_no_swap() {
             Order::Move {
                 unit: army(Province::Rom),
                 dest: loc(Province::Ven),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -1363,6 +1418,7 @@ fn datc_6e1_no_swap() {
             Order::Move {
                 unit: army(Province::Ven),
                 dest: loc(Province::Rom),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -1392,6 +1448,7 @@ fn datc_6e2_supported_head_to_head() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Ven),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1399,6 +1456,7 @@ fn datc_6e2_supported_head_to_head() {
             Order::Move {
                 unit: army(Province::Ven),
                 dest: loc(Province::Tyr),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -1425,6 +1483,7 @@ fn datc_6e3_unbalanced_head_to_head() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1440,6 +1499,7 @@ fn datc_6e3_unbalanced_head_to_head() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1473,6 +1533,7 @@ fn datc_6e4_stronger_head_to_head_wins() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1499,6 +1560,7 @@ fn datc_6e4_stronger_head_to_head_wins() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1542,6 +1604,7 @@ fn datc_6e5_one_sided_support_head_to_head() {
             Order::Move {
                 unit: army(Province::Boh),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -1549,6 +1612,7 @@ fn datc_6e5_one_sided_support_head_to_head() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Boh),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -1586,6 +1650,7 @@ fn datc_6e6_beleaguered_garrison() {
             Order::Move {
                 unit: army(Province::Bur),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -1593,6 +1658,7 @@ fn datc_6e6_beleaguered_garrison() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -1626,6 +1692,7 @@ fn datc_6e7_beleaguered_with_support() {
             Order::Move {
                 unit: army(Province::Bur),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -1641,6 +1708,7 @@ fn datc_6e7_beleaguered_with_support() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -1674,6 +1742,7 @@ fn datc_6e8_beleaguered_equal_support_both_sides() {
             Order::Move {
                 unit: army(Province::Bur),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -1689,6 +1758,7 @@ fn datc_6e8_beleaguered_equal_support_both_sides() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -1731,6 +1801,7 @@ fn datc_6e9_unequal_beleaguered() {
             Order::Move {
                 unit: army(Province::Bur),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -1754,6 +1825,7 @@ fn datc_6e9_unequal_beleaguered() {
             Order::Move {
                 unit: army(Province::Tyr),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -1790,6 +1862,7 @@ fn datc_6f1_simple_convoy() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -1820,6 +1893,7 @@ fn datc_6f2_disrupted_convoy() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -1835,6 +1909,7 @@ fn datc_6f2_disrupted_convoy() {
             Order::Move {
                 unit: fleet(Province::Eng),
                 dest: loc(Province::Nth),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -1849,7 +1924,7 @@ fn datc_6f2_disrupted_convoy() {
     ];
     let (results, _) = resolve_orders(&orders, &state);
     assert_eq!(result_for(&results, Province::Nth), OrderResult::Dislodged);
-    assert_eq!(result_for(&results, Province::Lon), OrderResult::Bounced);
+    assert_eq!(result_for(&results, Province::Lon), OrderResult::NoConvoy);
 }
 
 /// 6.F.3: Two-fleet convoy chain.
@@ -1866,6 +1941,7 @@ fn datc_6f3_convoy_via_single_sea() {
             Order::Move {
                 unit: army(Province::Bre),
                 dest: loc(Province::Naf),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -1899,6 +1975,7 @@ fn datc_6f4_multi_fleet_convoy() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Tun),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -1945,6 +2022,7 @@ fn datc_6f5_convoy_dislodges_at_destination() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -1993,6 +2071,7 @@ fn datc_6f6_convoyed_army_cuts_support() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2016,6 +2095,7 @@ fn datc_6f6_convoyed_army_cuts_support() {
             Order::Move {
                 unit: army(Province::Swe),
                 dest: loc(Province::Den),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2055,6 +2135,7 @@ fn datc_6g1_convoy_disrupted_by_fleet_dislodgement() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2070,6 +2151,7 @@ fn datc_6g1_convoy_disrupted_by_fleet_dislodgement() {
             Order::Move {
                 unit: fleet(Province::Eng),
                 dest: loc(Province::Nth),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2084,7 +2166,7 @@ fn datc_6g1_convoy_disrupted_by_fleet_dislodgement() {
     ];
     let (results, _) = resolve_orders(&orders, &state);
     assert_eq!(result_for(&results, Province::Nth), OrderResult::Dislodged);
-    assert_eq!(result_for(&results, Province::Lon), OrderResult::Bounced);
+    assert_eq!(result_for(&results, Province::Lon), OrderResult::NoConvoy);
 }
 
 /// 6.G.2: Convoy NOT disrupted when fleet is not dislodged.
@@ -2101,6 +2183,7 @@ fn datc_6g2_convoy_not_disrupted_when_fleet_holds() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2116,6 +2199,7 @@ fn datc_6g2_convoy_not_disrupted_when_fleet_holds() {
             Order::Move {
                 unit: fleet(Province::Eng),
                 dest: loc(Province::Nth),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2146,6 +2230,7 @@ fn datc_6g3_chain_broken_by_one_link() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2169,6 +2254,7 @@ fn datc_6g3_chain_broken_by_one_link() {
             Order::Move {
                 unit: fleet(Province::Eng),
                 dest: loc(Province::Nth),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2191,7 +2277,7 @@ fn datc_6g3_chain_broken_by_one_link() {
     // With NTH dislodged, the direct path through NTH alone would also fail.
     // But the BFS finds: NTH is dislodged (convoy fails), so NTH not in chain.
     // NRG: adjacent to Lon? No. So no path starting from Lon.
-    assert_eq!(result_for(&results, Province::Lon), OrderResult::Bounced);
+    assert_eq!(result_for(&results, Province::Lon), OrderResult::NoConvoy);
 }
 
 /// 6.G.4: Convoy survives when attack on fleet bounces.
@@ -2208,6 +2294,7 @@ fn datc_6g4_convoy_survives_bounce() {
             Order::Move {
                 unit: army(Province::Lon),
                 dest: loc(Province::Nwy),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2230,6 +2317,7 @@ fn datc_6g4_convoy_survives_bounce() {
             Order::Move {
                 unit: fleet(Province::Ska),
                 dest: loc(Province::Nth),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2240,6 +2328,293 @@ fn datc_6g4_convoy_survives_bounce() {
     assert_eq!(result_for(&results, Province::Lon), OrderResult::Succeeded);
 }
 
+/// 6.G.5: Two units can swap places by convoy.
+/// Nwy orders `via convoy` to adjacent Swe, so the Swedish army moving to
+/// Nwy is not a head-to-head battle and both moves succeed.
+#[test]
+fn datc_6g5_swap_places_by_explicit_convoy() {
+    let mut state = empty_state();
+    state.place_unit(Province::Nwy, Power::England, UnitType::Army, Coast::None);
+    state.place_unit(Province::Ska, Power::England, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Swe, Power::Russia, UnitType::Army, Coast::None);
+    let orders = vec![
+        (
+            Order::Move {
+                unit: army(Province::Nwy),
+                dest: loc(Province::Swe),
+                via_convoy: true,
+            },
+            Power::England,
+        ),
+        (
+            Order::Convoy {
+                unit: fleet(Province::Ska),
+                convoyed_from: loc(Province::Nwy),
+                convoyed_to: loc(Province::Swe),
+            },
+            Power::England,
+        ),
+        (
+            Order::Move {
+                unit: army(Province::Swe),
+                dest: loc(Province::Nwy),
+                via_convoy: false,
+            },
+            Power::Russia,
+        ),
+    ];
+    let (results, dislodged) = resolve_orders(&orders, &state);
+    assert_eq!(result_for(&results, Province::Nwy), OrderResult::Succeeded);
+    assert_eq!(result_for(&results, Province::Swe), OrderResult::Succeeded);
+    assert!(dislodged.is_empty());
+}
+
+/// 6.G.6: Without explicit intent an adjacent move goes over land.
+/// Same position as 6.G.5 but the English order has no `via convoy`: the
+/// armies meet head-to-head and both bounce, even though a fleet offers
+/// the convoy (no "kidnapping" onto the convoy route).
+#[test]
+fn datc_6g6_adjacent_move_without_intent_is_head_to_head() {
+    let mut state = empty_state();
+    state.place_unit(Province::Nwy, Power::England, UnitType::Army, Coast::None);
+    state.place_unit(Province::Ska, Power::Germany, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Swe, Power::Russia, UnitType::Army, Coast::None);
+    let orders = vec![
+        (
+            Order::Move {
+                unit: army(Province::Nwy),
+                dest: loc(Province::Swe),
+                via_convoy: false,
+            },
+            Power::England,
+        ),
+        (
+            Order::Convoy {
+                unit: fleet(Province::Ska),
+                convoyed_from: loc(Province::Nwy),
+                convoyed_to: loc(Province::Swe),
+            },
+            Power::Germany,
+        ),
+        (
+            Order::Move {
+                unit: army(Province::Swe),
+                dest: loc(Province::Nwy),
+                via_convoy: false,
+            },
+            Power::Russia,
+        ),
+    ];
+    let (results, _) = resolve_orders(&orders, &state);
+    assert_eq!(result_for(&results, Province::Nwy), OrderResult::Bounced);
+    assert_eq!(result_for(&results, Province::Swe), OrderResult::Bounced);
+}
+
+/// 6.G.7: A `via convoy` move with no convoy route fails.
+/// The army does not fall back to the land route to adjacent Bel.
+#[test]
+fn datc_6g7_explicit_convoy_without_route_fails() {
+    let mut state = empty_state();
+    state.place_unit(Province::Pic, Power::France, UnitType::Army, Coast::None);
+    let orders = vec![(
+        Order::Move {
+            unit: army(Province::Pic),
+            dest: loc(Province::Bel),
+            via_convoy: true,
+        },
+        Power::France,
+    )];
+    let (results, _) = resolve_orders(&orders, &state);
+    assert_eq!(result_for(&results, Province::Pic), OrderResult::NoConvoy);
+}
+
+fn convoy(fleet_prov: Province, from: Province, to: Province) -> (Order, Power) {
+    (
+        Order::Convoy {
+            unit: fleet(fleet_prov),
+            convoyed_from: loc(from),
+            convoyed_to: loc(to),
+        },
+        Power::England,
+    )
+}
+
+/// 6.G.8: Two disjoint single-fleet routes, one disrupted.
+/// Lon - Bel can go through Eng or Nth. France dislodges the Eng fleet, but
+/// the Nth route is intact, so the army arrives and the route is recorded.
+#[test]
+fn datc_6g8_two_routes_one_disrupted() {
+    let mut state = empty_state();
+    state.place_unit(Province::Lon, Power::England, UnitType::Army, Coast::None);
+    state.place_unit(Province::Eng, Power::England, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Nth, Power::England, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Bre, Power::France, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Mao, Power::France, UnitType::Fleet, Coast::None);
+    let orders = vec![
+        (
+            Order::Move {
+                unit: army(Province::Lon),
+                dest: loc(Province::Bel),
+                via_convoy: false,
+            },
+            Power::England,
+        ),
+        convoy(Province::Eng, Province::Lon, Province::Bel),
+        convoy(Province::Nth, Province::Lon, Province::Bel),
+        (
+            Order::Move {
+                unit: fleet(Province::Bre),
+                dest: loc(Province::Eng),
+                via_convoy: false,
+            },
+            Power::France,
+        ),
+        (
+            Order::SupportMove {
+                unit: fleet(Province::Mao),
+                supported: fleet(Province::Bre),
+                dest: loc(Province::Eng),
+            },
+            Power::France,
+        ),
+    ];
+    let mut resolver = Resolver::new(orders.len());
+    let (results, dislodged) = resolver.resolve(&orders, &state);
+    assert_eq!(result_for(&results, Province::Eng), OrderResult::Dislodged);
+    assert!(dislodged.iter().any(|d| d.province == Province::Eng));
+    assert_eq!(result_for(&results, Province::Lon), OrderResult::Succeeded);
+    assert_eq!(
+        resolver.convoy_routes(),
+        &[(Province::Lon, vec![Province::Nth])]
+    );
+}
+
+/// 6.G.9: Two disjoint multi-fleet routes, one disrupted.
+/// Wal - Nwy via Eng-Nth, or via Iri-Nao-Nrg. Germany dislodges the Nth
+/// fleet; the long western chain still carries the army.
+#[test]
+fn datc_6g9_two_multi_fleet_routes_one_disrupted() {
+    let mut state = empty_state();
+    state.place_unit(Province::Wal, Power::England, UnitType::Army, Coast::None);
+    for sea in [
+        Province::Eng,
+        Province::Nth,
+        Province::Iri,
+        Province::Nao,
+        Province::Nrg,
+    ] {
+        state.place_unit(sea, Power::England, UnitType::Fleet, Coast::None);
+    }
+    state.place_unit(Province::Ska, Power::Germany, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Den, Power::Germany, UnitType::Fleet, Coast::None);
+    let mut orders = vec![(
+        Order::Move {
+            unit: army(Province::Wal),
+            dest: loc(Province::Nwy),
+            via_convoy: false,
+        },
+        Power::England,
+    )];
+    for sea in [
+        Province::Eng,
+        Province::Nth,
+        Province::Iri,
+        Province::Nao,
+        Province::Nrg,
+    ] {
+        orders.push(convoy(sea, Province::Wal, Province::Nwy));
+    }
+    orders.push((
+        Order::Move {
+            unit: fleet(Province::Ska),
+            dest: loc(Province::Nth),
+            via_convoy: false,
+        },
+        Power::Germany,
+    ));
+    orders.push((
+        Order::SupportMove {
+            unit: fleet(Province::Den),
+            supported: fleet(Province::Ska),
+            dest: loc(Province::Nth),
+        },
+        Power::Germany,
+    ));
+    let mut resolver = Resolver::new(orders.len());
+    let (results, _) = resolver.resolve(&orders, &state);
+    assert_eq!(result_for(&results, Province::Nth), OrderResult::Dislodged);
+    assert_eq!(result_for(&results, Province::Wal), OrderResult::Succeeded);
+    assert_eq!(
+        resolver.convoy_routes(),
+        &[(
+            Province::Wal,
+            vec![Province::Iri, Province::Nao, Province::Nrg]
+        )]
+    );
+}
+
+/// 6.G.10: Both routes disrupted: the convoyed move fails and no route is
+/// recorded.
+#[test]
+fn datc_6g10_all_routes_disrupted() {
+    let mut state = empty_state();
+    state.place_unit(Province::Lon, Power::England, UnitType::Army, Coast::None);
+    state.place_unit(Province::Eng, Power::England, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Nth, Power::England, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Bre, Power::France, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Mao, Power::France, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Hel, Power::Germany, UnitType::Fleet, Coast::None);
+    state.place_unit(Province::Den, Power::Germany, UnitType::Fleet, Coast::None);
+    let orders = vec![
+        (
+            Order::Move {
+                unit: army(Province::Lon),
+                dest: loc(Province::Bel),
+                via_convoy: false,
+            },
+            Power::England,
+        ),
+        convoy(Province::Eng, Province::Lon, Province::Bel),
+        convoy(Province::Nth, Province::Lon, Province::Bel),
+        (
+            Order::Move {
+                unit: fleet(Province::Bre),
+                dest: loc(Province::Eng),
+                via_convoy: false,
+            },
+            Power::France,
+        ),
+        (
+            Order::SupportMove {
+                unit: fleet(Province::Mao),
+                supported: fleet(Province::Bre),
+                dest: loc(Province::Eng),
+            },
+            Power::France,
+        ),
+        (
+            Order::Move {
+                unit: fleet(Province::Hel),
+                dest: loc(Province::Nth),
+                via_convoy: false,
+            },
+            Power::Germany,
+        ),
+        (
+            Order::SupportMove {
+                unit: fleet(Province::Den),
+                supported: fleet(Province::Hel),
+                dest: loc(Province::Nth),
+            },
+            Power::Germany,
+        ),
+    ];
+    let mut resolver = Resolver::new(orders.len());
+    let (results, _) = resolver.resolve(&orders, &state);
+    assert_eq!(result_for(&results, Province::Lon), OrderResult::NoConvoy);
+    assert!(resolver.convoy_routes().is_empty());
+}
+
 // ===========================================================================
 // SECTION 6.H: RETREAT PHASE (unit-test level; the resolver handles movement)
 // ===========================================================================
@@ -2260,6 +2635,7 @@ fn datc_6h1_basic_retreat_proxy() {
             Order::Move {
                 unit: army(Province::Bur),
                 dest: loc(Province::Mun),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -2286,6 +2662,7 @@ fn datc_6h2_retreat_bounce() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Ruh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -2293,6 +2670,7 @@ fn datc_6h2_retreat_bounce() {
             Order::Move {
                 unit: army(Province::Bur),
                 dest: loc(Province::Ruh),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2353,6 +2731,7 @@ fn regression_smyrna_ankara_army_only() {
         Order::Move {
             unit: army(Province::Smy),
             dest: loc(Province::Ank),
+            via_convoy: false,
         },
         Power::Turkey,
     )];
@@ -2370,12 +2749,13 @@ fn regression_vienna_venice_not_adjacent() {
         Order::Move {
             unit: army(Province::Vie),
             dest: loc(Province::Ven),
+            via_convoy: false,
         },
         Power::Austria,
     )];
     let (results, _) = resolve_orders(&orders, &state);
-    // Vie and Ven are not adjacent. needs_convoy is true, no convoy. Fails.
-    assert_eq!(result_for(&results, Province::Vie), OrderResult::Bounced);
+    // Vie and Ven are not adjacent. needs_convoy is true, no convoy.
+    assert_eq!(result_for(&results, Province::Vie), OrderResult::NoConvoy);
 }
 
 // ===========================================================================
@@ -2401,6 +2781,7 @@ fn edge_uncontested_move() {
         Order::Move {
             unit: army(Province::Par),
             dest: loc(Province::Bur),
+            via_convoy: false,
         },
         Power::France,
     )];
@@ -2419,6 +2800,7 @@ fn edge_chained_moves() {
             Order::Move {
                 unit: army(Province::Par),
                 dest: loc(Province::Bre),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2426,6 +2808,7 @@ fn edge_chained_moves() {
             Order::Move {
                 unit: fleet(Province::Bre),
                 dest: loc(Province::Gas),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2464,6 +2847,7 @@ fn edge_supported_hold_against_two_attackers() {
             Order::Move {
                 unit: army(Province::Rum),
                 dest: loc(Province::Bud),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2471,6 +2855,7 @@ fn edge_supported_hold_against_two_attackers() {
             Order::Move {
                 unit: army(Province::Gal),
                 dest: loc(Province::Bud),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2492,6 +2877,7 @@ fn edge_move_into_own_holding_unit() {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: loc(Province::Bud),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -2518,6 +2904,7 @@ fn edge_move_into_own_leaving_unit() {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: loc(Province::Bud),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -2525,6 +2912,7 @@ fn edge_move_into_own_leaving_unit() {
             Order::Move {
                 unit: army(Province::Bud),
                 dest: loc(Province::Rum),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -2576,6 +2964,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Vie),
                 dest: loc(Province::Gal),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -2583,6 +2972,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Bud),
                 dest: loc(Province::Ser),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -2590,6 +2980,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Tri),
                 dest: loc(Province::Alb),
+                via_convoy: false,
             },
             Power::Austria,
         ),
@@ -2598,6 +2989,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Lon),
                 dest: loc(Province::Nth),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2605,6 +2997,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Edi),
                 dest: loc(Province::Nrg),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2612,6 +3005,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Lvp),
                 dest: loc(Province::Yor),
+                via_convoy: false,
             },
             Power::England,
         ),
@@ -2620,6 +3014,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Bre),
                 dest: loc(Province::Mao),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2627,6 +3022,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Par),
                 dest: loc(Province::Bur),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2634,6 +3030,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Mar),
                 dest: loc(Province::Spa),
+                via_convoy: false,
             },
             Power::France,
         ),
@@ -2642,6 +3039,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Kie),
                 dest: loc(Province::Den),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -2649,6 +3047,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Ber),
                 dest: loc(Province::Kie),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -2656,6 +3055,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Mun),
                 dest: loc(Province::Ruh),
+                via_convoy: false,
             },
             Power::Germany,
         ),
@@ -2664,6 +3064,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Nap),
                 dest: loc(Province::Ion),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -2671,6 +3072,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Rom),
                 dest: loc(Province::Apu),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -2678,6 +3080,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Ven),
                 dest: loc(Province::Tyr),
+                via_convoy: false,
             },
             Power::Italy,
         ),
@@ -2686,6 +3089,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet_coast(Province::Stp, Coast::South),
                 dest: loc(Province::Bot),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2693,6 +3097,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Mos),
                 dest: loc(Province::Ukr),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2700,6 +3105,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::War),
                 dest: loc(Province::Gal),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2707,6 +3113,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Sev),
                 dest: loc(Province::Bla),
+                via_convoy: false,
             },
             Power::Russia,
         ),
@@ -2715,6 +3122,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: fleet(Province::Ank),
                 dest: loc(Province::Bla),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -2722,6 +3130,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Con),
                 dest: loc(Province::Bul),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -2729,6 +3138,7 @@ fn edge_full_opening_moves() {
             Order::Move {
                 unit: army(Province::Smy),
                 dest: loc(Province::Con),
+                via_convoy: false,
             },
             Power::Turkey,
         ),
@@ -78,6 +78,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Vie),
                     dest: Location::new(Province::Gal),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -85,6 +86,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Bud),
                     dest: Location::new(Province::Ser),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -92,6 +94,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Tri),
                     dest: Location::new(Province::Alb),
+                    via_convoy: false,
                 },
                 Power::Austria,
             ),
@@ -99,6 +102,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Lon),
                     dest: Location::new(Province::Nth),
+                    via_convoy: false,
                 },
                 Power::England,
             ),
@@ -106,6 +110,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Edi),
                     dest: Location::new(Province::Nrg),
+                    via_convoy: false,
                 },
                 Power::England,
             ),
@@ -113,6 +118,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Lvp),
                     dest: Location::new(Province::Yor),
+                    via_convoy: false,
                 },
                 Power::England,
             ),
@@ -120,6 +126,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Bre),
                     dest: Location::new(Province::Mao),
+                    via_convoy: false,
                 },
                 Power::France,
             ),
@@ -127,6 +134,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Par),
                     dest: Location::new(Province::Bur),
+                    via_convoy: false,
                 },
                 Power::France,
             ),
@@ -134,6 +142,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Mar),
                     dest: Location::new(Province::Pie),
+                    via_convoy: false,
                 },
                 Power::France,
             ),
@@ -141,6 +150,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Kie),
                     dest: Location::new(Province::Den),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -148,6 +158,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Ber),
                     dest: Location::new(Province::Kie),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -155,6 +166,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Mun),
                     dest: Location::new(Province::Ruh),
+                    via_convoy: false,
                 },
                 Power::Germany,
             ),
@@ -162,6 +174,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Nap),
                     dest: Location::new(Province::Ion),
+                    via_convoy: false,
                 },
                 Power::Italy,
             ),
@@ -169,6 +182,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Rom),
                     dest: Location::new(Province::Apu),
+                    via_convoy: false,
                 },
                 Power::Italy,
             ),
@@ -176,6 +190,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Ven),
                     dest: Location::new(Province::Tri),
+                    via_convoy: false,
                 },
                 Power::Italy,
             ),
@@ -183,6 +198,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet_coast(Province::Stp, Coast::South),
                     dest: Location::new(Province::Bot),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -190,6 +206,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Mos),
                     dest: Location::new(Province::Ukr),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -197,6 +214,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::War),
                     dest: Location::new(Province::Gal),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -204,6 +222,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Sev),
                     dest: Location::new(Province::Bla),
+                    via_convoy: false,
                 },
                 Power::Russia,
             ),
@@ -211,6 +230,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: fleet(Province::Ank),
                     dest: Location::new(Province::Bla),
+                    via_convoy: false,
                 },
                 Power::Turkey,
             ),
@@ -218,6 +238,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Con),
                     dest: Location::new(Province::Bul),
+                    via_convoy: false,
                 },
                 Power::Turkey,
             ),
@@ -225,6 +246,7 @@ fn profile_rm_search() {
                 Order::Move {
                     unit: army(Province::Smy),
                     dest: Location::new(Province::Con),
+                    via_convoy: false,
                 },
                 Power::Turkey,
             ),
@@ -113,6 +113,7 @@ fn profile_post_opt() {
                             location: Location::with_coast(prov, coast),
                         },
                         dest: Location::new(prov), // Hold-equivalent (move to self fails gracefully)
+                        via_convoy: false,
                     },
                     power,
                 ));
@@ -0,0 +1,198 @@
+//! Integration tests for variant maps.
+
+use std::io::{BufRead, Write};
+use std::process::{Command, Stdio};
+
+use std::sync::Arc;
+
+use realpolitik::board::map::GameMap;
+use realpolitik::board::mapdef::{AdjacencyDef, MapDef, ProvinceDef};
+use realpolitik::board::{Province, UnitType};
+use realpolitik::movegen::movement::legal_orders;
+use realpolitik::protocol::dfen::{encode_dfen, parse_dfen, parse_dfen_on};
+use realpolitik::protocol::dson::{format_order, format_order_on, parse_order_on};
+
+/// Austria and Italy alone, with Tyrolia a neutral supply center.
+const TYROLIA_DFEN: &str = "1901sm/Aavie,Iaven/Avie,Iven,Ntyr/-";
+
+/// The standard map with Tyrolia as a supply center and armies able to
+/// cross between Vienna and Venice.
+fn variant() -> MapDef {
+    let mut def = MapDef::standard();
+    def.name = "vienna-venice".to_string();
+    for p in def.provinces.iter_mut().filter(|p| p.id == "tyr") {
+        p.supply_center = true;
+    }
+    for (from, to) in [("vie", "ven"), ("ven", "vie")] {
+        def.adjacencies.push(AdjacencyDef {
+            from: from.to_string(),
+            from_coast: None,
+            to: to.to_string(),
+            to_coast: None,
+            army: true,
+            fleet: false,
+        });
+    }
+    def
+}
+
+/// The standard map with Switzerland, an inland province of its own,
+/// between Marseilles, Piedmont, Munich and Burgundy.
+fn switzerland() -> MapDef {
+    let mut def = MapDef::standard();
+    def.name = "switzerland".to_string();
+    def.provinces.push(ProvinceDef {
+        id: "swi".to_string(),
+        name: "Switzerland".to_string(),
+        kind: "land".to_string(),
+        supply_center: false,
+        home: None,
+        coasts: Vec::new(),
+        centroid: Some([560, 760]),
+        label: None,
+    });
+    for other in ["mar", "pie", "mun", "bur"] {
+        for (from, to) in [("swi", other), (other, "swi")] {
+            def.adjacencies.push(AdjacencyDef {
+                from: from.to_string(),
+                from_coast: None,
+                to: to.to_string(),
+                to_coast: None,
+                army: true,
+                fleet: false,
+            });
+        }
+    }
+    def
+}
+
+/// Spawns the engine, feeds it `commands`, and returns stdout and stderr.
+fn run_engine(commands: &[String]) -> (Vec<String>, String) {
+    let exe = env!("CARGO_BIN_EXE_realpolitik");
+    let mut child = Command::new(exe)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start realpolitik");
+    let mut stdin = child.stdin.take().unwrap();
+    for cmd in commands {
+        writeln!(stdin, "{}", cmd).unwrap();
+    }
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = output.stdout.lines().map(|l| l.unwrap()).collect();
+    (stdout, String::from_utf8(output.stderr).unwrap())
+}
+
+#[test]
+fn variant_position_drives_movegen_and_dfen() {
+    assert!(parse_dfen(TYROLIA_DFEN).is_err());
+
+    let map = Arc::new(GameMap::from_def(&variant()).unwrap());
+    let state = parse_dfen_on(Arc::clone(&map), TYROLIA_DFEN).unwrap();
+    assert!(matches!(
+        state.units[Province::Vie.index()],
+        Some((_, UnitType::Army))
+    ));
+    let orders: Vec<String> = legal_orders(Province::Vie, &state)
+        .iter()
+        .map(format_order)
+        .collect();
+    assert!(orders.iter().any(|o| o == "A vie - ven"), "{:?}", orders);
+    // Positions derived from it stay on the variant.
+    assert!(!state.clone().map().is_standard());
+
+    // A standard position in the same process is unaffected.
+    let standard = parse_dfen("1901sm/Aavie,Iaven/Avie,Iven/-").unwrap();
+    let orders: Vec<String> = legal_orders(Province::Vie, &standard)
+        .iter()
+        .map(format_order)
+        .collect();
+    assert!(!orders.iter().any(|o| o == "A vie - ven"), "{:?}", orders);
+    assert!(parse_dfen(TYROLIA_DFEN).is_err());
+}
+
+#[test]
+fn newgame_loads_the_map_file() {
+    let path = std::env::temp_dir().join(format!("realpolitik-map-{}.json", std::process::id()));
+    std::fs::write(&path, serde_json::to_string(&variant()).unwrap()).unwrap();
+
+    let (stdout, stderr) = run_engine(&[
+        "dui".to_string(),
+        format!("setoption name MapFile value {}", path.display()),
+        "newgame".to_string(),
+        format!("position {}", TYROLIA_DFEN),
+        "setpower austria".to_string(),
+        "go movetime 200".to_string(),
+        "isready".to_string(),
+        "quit".to_string(),
+    ]);
+    std::fs::remove_file(&path).ok();
+
+    assert!(stdout
+        .iter()
+        .any(|l| l == "option name MapFile type string default <empty>"));
+    assert!(stdout.iter().any(|l| l.starts_with("bestorders A vie")));
+    assert!(
+        stderr.contains("info string loaded map vienna-venice (35 centers, solo at 18)"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn provinces_of_its_own_drive_movegen_dfen_and_dson() {
+    let map = Arc::new(GameMap::from_def(&switzerland()).unwrap());
+    let swi = map.province("swi").unwrap();
+    assert_eq!(map.abbr(swi), "swi");
+
+    let dfen = "1901sm/Aavie,Faswi,Iamar/Avie,Fpar,Irom/-";
+    assert!(parse_dfen(dfen).is_err());
+    let state = parse_dfen_on(Arc::clone(&map), dfen).unwrap();
+    let encoded = encode_dfen(&state);
+    assert!(encoded.contains("Faswi"), "{}", encoded);
+    let reparsed = parse_dfen_on(Arc::clone(&map), &encoded).unwrap();
+    assert_eq!(encode_dfen(&reparsed), encoded);
+
+    let orders: Vec<String> = legal_orders(swi, &state)
+        .iter()
+        .map(|o| format_order_on(&map, o))
+        .collect();
+    for dest in ["mar", "pie", "mun", "bur"] {
+        let order = format!("A swi - {}", dest);
+        assert!(orders.contains(&order), "{:?}", orders);
+    }
+    let order = parse_order_on(&map, "A mar - swi").unwrap();
+    assert_eq!(format_order_on(&map, &order), "A mar - swi");
+    let orders: Vec<String> = legal_orders(Province::Mar, &state)
+        .iter()
+        .map(|o| format_order_on(&map, o))
+        .collect();
+    assert!(orders.contains(&"A mar - swi".to_string()), "{:?}", orders);
+}
+
+#[test]
+fn newgame_plays_a_builtin_map() {
+    let (stdout, _) = run_engine(&[
+        "dui".to_string(),
+        "setoption name Map value pure".to_string(),
+        "newgame".to_string(),
+        "position 1901sm/Aavie,Ealon,Fapar,Gaber,Iarom,Ramos,Tacon/Avie,Elon,Fpar,Gber,Irom,Rmos,Tcon/-".to_string(),
+        "setpower austria".to_string(),
+        "go movetime 200".to_string(),
+        "isready".to_string(),
+        "quit".to_string(),
+    ]);
+
+    assert!(stdout.iter().any(|l| l == "capability maps standard pure"));
+    let best = stdout
+        .iter()
+        .find(|l| l.starts_with("bestorders "))
+        .expect("no bestorders");
+    assert!(best.starts_with("bestorders A vie"), "{}", best);
+    // Every province on Pure borders every other, so Vienna may go anywhere
+    // but nowhere off the map.
+    assert!(!best.contains("tyr") && !best.contains("bud"), "{}", best);
+}